@@ -5,7 +5,7 @@
 
 use std::sync::Arc;
 
-use crate::session::{PromptManager, SessionManager};
+use crate::session::{PromptManager, SessionIndex, SessionManager};
 use crate::types::AgentConfig;
 
 /// Claude ACP Agent
@@ -46,9 +46,14 @@ impl ClaudeAcpAgent {
             "Agent initialized with configuration"
         );
 
+        let mut sessions = SessionManager::new();
+        if let Some(index) = SessionIndex::user() {
+            sessions = sessions.with_session_index(index);
+        }
+
         Self {
             config,
-            sessions: Arc::new(SessionManager::new()),
+            sessions: Arc::new(sessions),
             prompt_manager: Arc::new(PromptManager::new()),
         }
     }
@@ -77,14 +82,14 @@ impl ClaudeAcpAgent {
         &self.prompt_manager
     }
 
-    /// Get agent name for logging
-    pub fn name(&self) -> &'static str {
-        "claude-code-acp-rs"
+    /// Get agent name reported to clients and used for logging
+    pub fn name(&self) -> &str {
+        self.config.agent_name()
     }
 
-    /// Get agent version
-    pub fn version(&self) -> &'static str {
-        env!("CARGO_PKG_VERSION")
+    /// Get agent version reported to clients
+    pub fn version(&self) -> &str {
+        self.config.agent_version()
     }
 }
 
@@ -97,6 +102,10 @@ impl Default for ClaudeAcpAgent {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mcp::{
+        DomainFilter, EnvInfoConfig, FinalNewlinePolicy, InteractiveInputPolicy, ToolTimeouts,
+    };
+    use crate::types::PricingTable;
 
     #[test]
     fn test_agent_new() {
@@ -113,6 +122,40 @@ mod tests {
             model: Some("claude-3-opus".to_string()),
             small_fast_model: None,
             max_thinking_tokens: Some(4096),
+            auto_generate_title: false,
+            auto_compact: false,
+            context_window: None,
+            synthesize_bash_description: false,
+            interactive_input: InteractiveInputPolicy::default(),
+            max_line_length: None,
+            read_max_lines: None,
+            max_file_size: None,
+            tool_timeouts: ToolTimeouts::default(),
+            max_tool_argument_bytes: None,
+            bash_output_limit_bytes: None,
+            bash_timeout_warning_percent: None,
+            combine_streams: true,
+            plans_dir: None,
+            accept_edits_after_plan: false,
+            auto_approve_within_cwd: false,
+            permission_audit_log: None,
+            permission_request_timeout: None,
+            permission_fallback: crate::permissions::PermissionFallback::Deny,
+            verify_writes: false,
+            prefer_ripgrep: true,
+            diff_context_lines: None,
+            env_info: EnvInfoConfig::default(),
+            allow_localhost_fetch: false,
+            web_fetch_domains: DomainFilter::default(),
+            web_search_domains: DomainFilter::default(),
+            background_max_lifetime: None,
+            background_output_limit: None,
+            model_pricing: PricingTable::default(),
+            final_newline: FinalNewlinePolicy::Off,
+            agent_name: None,
+            agent_version: None,
+            thinking_triggers: None,
+            custom_modes: std::collections::HashMap::new(),
         };
 
         let agent = ClaudeAcpAgent::with_config(config);
@@ -5,6 +5,7 @@
 //! - session/new: Create a new session
 //! - session/prompt: Execute a prompt (Phase 1: simplified)
 //! - session/setMode: Set permission mode
+//! - Extension methods outside the ACP schema (session/setTitle, session/list, session/usage)
 
 use std::pin::Pin;
 use std::sync::Arc;
@@ -15,51 +16,105 @@ use sacp::JrConnectionCx;
 use sacp::link::AgentToClient;
 use tokio_util::sync::CancellationToken;
 use sacp::schema::{
-    AgentCapabilities, ContentBlock, CurrentModeUpdate, Implementation,
-    InitializeRequest, InitializeResponse, LoadSessionRequest, LoadSessionResponse,
+    AgentCapabilities, ContentBlock, ContentChunk, Implementation, InitializeRequest,
+    InitializeResponse, LoadSessionRequest, LoadSessionResponse, McpCapabilities,
     NewSessionRequest, NewSessionResponse, PromptCapabilities, PromptRequest, PromptResponse,
-    SessionId, SessionMode, SessionModeId, SessionModeState, SessionNotification, SessionUpdate,
-    SetSessionModeRequest, SetSessionModeResponse, StopReason,
+    SessionId, SessionMode, SessionModeState, SessionNotification, SessionUpdate,
+    SetSessionModeRequest, SetSessionModeResponse, StopReason, TextContent,
 };
 
 // Unstable types from agent-client-protocol-schema
 use agent_client_protocol_schema::{ModelInfo, SessionModelState};
+use serde::Deserialize;
 use tokio::sync::broadcast;
 use tracing::instrument;
 
 use crate::agent::flush;
 use crate::agent::slash_commands::{get_predefined_commands, transform_mcp_command_input};
-use crate::session::{PermissionMode, SessionManager};
+use crate::converter::PromptConverter;
+use crate::session::{
+    CompactionPolicy, CustomMode, PermissionMode, SdkFastModel, Session, SessionManager,
+    ToolPermissionResult, compact_if_needed, generate_session_title,
+};
+use crate::settings::PermissionDecision;
 use crate::terminal::TerminalClient;
-use crate::types::{AgentConfig, AgentError, NewSessionMeta};
+use crate::types::{AgentConfig, AgentError, MODEL_ALIASES, NewSessionMeta, TokenUsage};
+
+/// Key in the `_meta` of the client capabilities to opt out of permission requests
+pub const REQUEST_PERMISSION_CAPABILITY: &str = "requestPermission";
+
+/// Whether the client answers `session/request_permission` requests
+///
+/// Every ACP client is expected to, so only a client setting
+/// `requestPermission: false` in the `_meta` of its capabilities is
+/// treated as unable to; calls needing permission then use the
+/// `permissionFallback` setting instead of waiting for an answer.
+pub fn supports_permission_requests(request: &InitializeRequest) -> bool {
+    let supported = request
+        .client_capabilities
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.get(REQUEST_PERMISSION_CAPABILITY))
+        .and_then(serde_json::Value::as_bool)
+        .unwrap_or(true);
+    if !supported {
+        tracing::info!("Client doesn't support permission requests, using permissionFallback");
+    }
+    supported
+}
 
 /// Handle initialize request
 ///
 /// Returns the agent's capabilities and protocol version.
 #[instrument(
     name = "acp_initialize",
-    skip(request, _config),
+    skip(request, config),
     fields(
         protocol_version = ?request.protocol_version,
-        agent_version = %env!("CARGO_PKG_VERSION"),
+        agent_version = %config.agent_version(),
     )
 )]
-pub fn handle_initialize(request: InitializeRequest, _config: &AgentConfig) -> InitializeResponse {
+pub fn handle_initialize(request: InitializeRequest, config: &AgentConfig) -> InitializeResponse {
     tracing::info!(
         protocol_version = ?request.protocol_version,
-        agent_name = "claude-code-acp-rs",
-        agent_version = %env!("CARGO_PKG_VERSION"),
+        agent_name = %config.agent_name(),
+        agent_version = %config.agent_version(),
         "Handling ACP initialize request"
     );
 
     // Build agent capabilities using builder pattern
     let prompt_caps = PromptCapabilities::new().image(true).embedded_context(true);
 
-    let capabilities = AgentCapabilities::new().prompt_capabilities(prompt_caps);
+    // External MCP servers can be reached over stdio (always) and SSE
+    let mcp_caps = McpCapabilities::new().sse(true);
+
+    // Advertise selectable models so clients can show a model picker before
+    // the first session; they are switched with session/set_model
+    let mut meta = serde_json::Map::new();
+    meta.insert(
+        "models".to_string(),
+        serde_json::to_value(build_selectable_models(config)).unwrap_or_default(),
+    );
+    // Likewise the permission modes, switched with session/setMode. The
+    // custom modes are only listed, so their rules' directory doesn't matter
+    let custom_modes = CustomMode::all(&config.custom_modes, std::path::Path::new("."));
+    let modes = SessionModeState::new(
+        PermissionMode::Default.as_str(),
+        build_available_modes(&custom_modes),
+    );
+    meta.insert(
+        "modes".to_string(),
+        serde_json::to_value(modes).unwrap_or_default(),
+    );
+
+    let capabilities = AgentCapabilities::new()
+        .prompt_capabilities(prompt_caps)
+        .mcp_capabilities(mcp_caps)
+        .meta(meta);
 
     // Build agent info
     let agent_info =
-        Implementation::new("claude-code-acp-rs", env!("CARGO_PKG_VERSION")).title("Claude Code");
+        Implementation::new(config.agent_name(), config.agent_version()).title("Claude Code");
 
     tracing::debug!(
         capabilities = ?capabilities,
@@ -115,11 +170,14 @@ pub async fn handle_new_session(
     }
 
     // Parse metadata from request if present
-    let meta = request.meta.as_ref().and_then(|m| {
+    let mut meta = request.meta.as_ref().and_then(|m| {
         serde_json::to_value(m)
             .ok()
             .map(|v| NewSessionMeta::from_request_meta(Some(&v)))
     });
+    if let Some(meta) = &mut meta {
+        sessions.restore_permission_mode(meta);
+    }
 
     // Get working directory from request
     let cwd = request.cwd;
@@ -142,8 +200,8 @@ pub async fn handle_new_session(
     }
 
     // Build available modes
-    let available_modes = build_available_modes();
-    let mode_state = SessionModeState::new("default", available_modes);
+    let available_modes = build_available_modes(&session.custom_modes());
+    let mode_state = SessionModeState::new(initial_mode_id(meta.as_ref()), available_modes);
 
     // Build available models
     let model_state = build_available_models(config);
@@ -212,8 +270,10 @@ pub fn handle_load_session(
     );
 
     // Create NewSessionMeta with resume option
-    // This tells the underlying SDK to resume from the specified session
-    let meta = NewSessionMeta::with_resume(&resume_session_id);
+    // This tells the underlying SDK to resume from the specified session,
+    // in the permission mode the session was left in
+    let mut meta = NewSessionMeta::with_resume(&resume_session_id);
+    sessions.restore_permission_mode(&mut meta);
 
     // Generate a new session ID for this loaded session
     // Note: We use the same session ID as the one being loaded
@@ -246,8 +306,9 @@ pub fn handle_load_session(
     }
 
     // Build available modes (same as new session)
-    let available_modes = build_available_modes();
-    let mode_state = SessionModeState::new("default", available_modes);
+    let session = sessions.get_session_or_error(&session_id)?;
+    let available_modes = build_available_modes(&session.custom_modes());
+    let mode_state = SessionModeState::new(initial_mode_id(Some(&meta)), available_modes);
 
     // Build available models
     let model_state = build_available_models(config);
@@ -257,11 +318,20 @@ pub fn handle_load_session(
         .models(model_state))
 }
 
+/// ID of the permission mode a session created with `meta` starts in
+fn initial_mode_id(meta: Option<&NewSessionMeta>) -> &'static str {
+    meta.and_then(NewSessionMeta::get_permission_mode)
+        .and_then(PermissionMode::parse)
+        .unwrap_or(PermissionMode::Default)
+        .as_str()
+}
+
 /// Build available permission modes
 ///
-/// Returns the list of permission modes available in the agent.
-fn build_available_modes() -> Vec<SessionMode> {
-    vec![
+/// Returns the built-in permission modes followed by the custom modes from
+/// settings. session/setMode only accepts the modes listed here.
+fn build_available_modes(custom_modes: &[Arc<CustomMode>]) -> Vec<SessionMode> {
+    let mut modes = vec![
         SessionMode::new("default", "Default")
             .description("Standard behavior, prompts for dangerous operations"),
         SessionMode::new("acceptEdits", "Accept Edits")
@@ -272,7 +342,15 @@ fn build_available_modes() -> Vec<SessionMode> {
             .description("Don't prompt for permissions, deny if not pre-approved"),
         SessionMode::new("bypassPermissions", "Bypass Permissions")
             .description("Bypass all permission checks"),
-    ]
+    ];
+    for custom_mode in custom_modes {
+        let description = custom_mode.description().map_or_else(
+            || format!("Custom mode based on {}", custom_mode.base().as_str()),
+            str::to_string,
+        );
+        modes.push(SessionMode::new(custom_mode.id(), custom_mode.name()).description(description));
+    }
+    modes
 }
 
 /// Build available models for session
@@ -300,6 +378,44 @@ fn build_available_models(config: &AgentConfig) -> SessionModelState {
     SessionModelState::new(current_model_id, available_models)
 }
 
+/// Build the models advertised in the initialize response
+///
+/// Lists the configured primary and small/fast models (from settings or
+/// `ANTHROPIC_MODEL`/`ANTHROPIC_SMALL_FAST_MODEL`) followed by the Claude
+/// model aliases. The current model is the configured primary model, or
+/// `default` when none is configured.
+fn build_selectable_models(config: &AgentConfig) -> SessionModelState {
+    let primary = config
+        .model
+        .clone()
+        .or_else(|| std::env::var("ANTHROPIC_MODEL").ok());
+    let small_fast = config
+        .small_fast_model
+        .clone()
+        .or_else(|| std::env::var("ANTHROPIC_SMALL_FAST_MODEL").ok());
+
+    let listed = |models: &[ModelInfo], id: &str| models.iter().any(|m| &*m.model_id.0 == id);
+    let mut models: Vec<ModelInfo> = Vec::new();
+    let configured = [
+        (primary.as_deref(), "Configured model"),
+        (small_fast.as_deref(), "Configured small/fast model"),
+    ];
+    for (model_id, description) in configured {
+        if let Some(model_id) = model_id
+            && !listed(&models, model_id)
+        {
+            models.push(ModelInfo::new(model_id, model_id).description(description));
+        }
+    }
+    for &(alias, name) in MODEL_ALIASES {
+        if !listed(&models, alias) {
+            models.push(ModelInfo::new(alias, name).description("Claude model alias"));
+        }
+    }
+
+    SessionModelState::new(primary.unwrap_or_else(|| "default".to_string()), models)
+}
+
 /// Send available commands update to client
 ///
 /// Sends the list of available slash commands to the client via ACP notification.
@@ -350,7 +466,7 @@ fn send_available_commands_update(
 /// Sends the prompt to Claude and streams responses back as notifications.
 #[instrument(
     name = "acp_prompt",
-    skip(request, _config, sessions, connection_cx),
+    skip(request, config, sessions, connection_cx),
     fields(
         session_id = %request.session_id.0,
         prompt_blocks = request.prompt.len(),
@@ -358,7 +474,7 @@ fn send_available_commands_update(
 )]
 pub async fn handle_prompt(
     request: PromptRequest,
-    _config: &AgentConfig,
+    config: &AgentConfig,
     sessions: &Arc<SessionManager>,
     connection_cx: JrConnectionCx<AgentToClient>,
     cancel_token: CancellationToken,
@@ -398,10 +514,10 @@ pub async fn handle_prompt(
 
     // Configure ACP MCP server with connection and terminal client
     // This enables tools like Bash to send terminal updates
-    let terminal_client = Arc::new(TerminalClient::new(
-        connection_cx.clone(),
-        session_id.to_string(),
-    ));
+    let terminal_client = Arc::new(
+        TerminalClient::new(connection_cx.clone(), session_id.to_string())
+            .with_env(session.client_env().clone()),
+    );
     session
         .configure_acp_server(connection_cx.clone(), Some(terminal_client))
         .await;
@@ -446,12 +562,32 @@ pub async fn handle_prompt(
         );
     }
 
+    // Apply a model switch requested since the last prompt
+    session.apply_model_change().await?;
+
+    // Compact the conversation before it outgrows the context window
+    if config.auto_compact {
+        compact_before_prompt(&session, &connection_cx).await;
+    }
+
     // Extract text from prompt content blocks
     let query_text = extract_text_from_content(&request.prompt);
     let query_preview = query_text.chars().take(200).collect::<String>();
 
+    // Name the session after its first prompt unless a title was already set
+    session.set_title_from_prompt(&query_text);
+
+    // Scale this turn's thinking budget with "think harder"-style phrases
+    if config.thinking_triggers.is_some() {
+        let budget = PromptConverter::new()
+            .with_thinking_triggers(config.thinking_triggers)
+            .thinking_budget(&query_text);
+        session.set_turn_thinking_budget(budget).await?;
+    }
+
     tracing::info!(
         session_id = %session_id,
+        session_title = ?session.title(),
         query_len = query_text.len(),
         query_preview = %query_preview,
         "Sending query to Claude CLI"
@@ -466,7 +602,14 @@ pub async fn handle_prompt(
         // Send the query
         if !query_text.is_empty() {
             // Transform MCP command format: /mcp:server:cmd -> /server:cmd (MCP)
-            let transformed_query = transform_mcp_command_input(&query_text);
+            let mut transformed_query = transform_mcp_command_input(&query_text);
+            // Tell Claude about a working directory change since the last prompt
+            if let Some(notice) = session.take_cwd_notice() {
+                transformed_query = format!(
+                    "<system-reminder>\n{}\n</system-reminder>\n\n{}",
+                    notice, transformed_query
+                );
+            }
             client.query(&transformed_query).await.map_err(AgentError::from)?;
         }
     }
@@ -599,6 +742,17 @@ pub async fn handle_prompt(
                     "Received message from SDK"
                 );
 
+                // The latest model call of the main conversation sees the
+                // whole context; the result's usage sums every call of the turn
+                if let claude_code_agent_sdk::Message::Assistant(ref assistant) = message
+                    && assistant.parent_tool_use_id.is_none()
+                    && let Some(ref usage) = assistant.message.usage
+                {
+                    session
+                        .usage_tracker()
+                        .record_context(&TokenUsage::from_sdk_usage(usage));
+                }
+
                 // Track ResultMessage for stop reason determination
                 if let claude_code_agent_sdk::Message::Result(ref result) = message {
                     tracing::info!(
@@ -609,6 +763,21 @@ pub async fn handle_prompt(
                         num_turns = result.num_turns,
                         "Received ResultMessage from Claude CLI"
                     );
+                    session.set_cli_session_id(&result.session_id);
+                    if let Some(ref usage) = result.usage {
+                        let usage = TokenUsage::from_sdk_usage(usage);
+                        session.usage_tracker().add(&usage);
+
+                        let budget = session.context_budget();
+                        if budget.low {
+                            tracing::warn!(
+                                session_id = %session_id,
+                                remaining_tokens = budget.remaining_tokens,
+                                context_window = budget.context_window,
+                                "Remaining context is low"
+                            );
+                        }
+                    }
                     last_result = Some(result.clone());
                 }
 
@@ -772,6 +941,21 @@ pub async fn handle_prompt(
                 StopReason::Refusal
             }
         };
+
+        // Name the session with the fast model once the first turn has completed
+        if config.auto_generate_title
+            && stop_reason == StopReason::EndTurn
+            && !session.has_explicit_title()
+            && session.claim_title_generation()
+        {
+            spawn_title_generation(
+                session.clone(),
+                config,
+                query_text,
+                result.result.clone().unwrap_or_default(),
+            );
+        }
+
         return Ok(PromptResponse::new(stop_reason));
     }
 
@@ -786,6 +970,71 @@ pub async fn handle_prompt(
     ))
 }
 
+/// Run automatic compaction and tell the client when it happened
+///
+/// Failures are logged and the prompt continues uncompacted.
+async fn compact_before_prompt(
+    session: &Arc<Session>,
+    connection_cx: &JrConnectionCx<AgentToClient>,
+) {
+    let policy = CompactionPolicy::new(session.context_window());
+    match compact_if_needed(session, &policy, session.as_ref()).await {
+        Ok(Some(context_tokens)) => {
+            let mut meta = serde_json::Map::new();
+            meta.insert(
+                "compaction".to_string(),
+                serde_json::json!({ "contextTokens": context_tokens }),
+            );
+            let notification = SessionNotification::new(
+                SessionId::new(session.session_id.clone()),
+                SessionUpdate::AgentMessageChunk(ContentChunk::new(ContentBlock::Text(
+                    TextContent::new(format!(
+                        "Conversation compacted: earlier turns were summarized to stay within the context window (~{} tokens before compaction).\n\n",
+                        context_tokens
+                    )),
+                ))),
+            )
+            .meta(meta);
+            if let Err(e) = send_notification(connection_cx, notification) {
+                tracing::warn!(
+                    session_id = %session.session_id,
+                    error = %e,
+                    "Failed to send compaction notification"
+                );
+            }
+        }
+        Ok(None) => {}
+        Err(e) => {
+            tracing::warn!(
+                session_id = %session.session_id,
+                error = %e,
+                "Automatic compaction failed, continuing with full context"
+            );
+        }
+    }
+}
+
+/// Generate a session title in the background so the prompt response isn't delayed
+fn spawn_title_generation(
+    session: Arc<Session>,
+    config: &AgentConfig,
+    user_prompt: String,
+    assistant_response: String,
+) {
+    let model = SdkFastModel::new(config.clone());
+    tokio::spawn(async move {
+        if let Err(e) =
+            generate_session_title(&session, &model, &user_prompt, &assistant_response).await
+        {
+            tracing::warn!(
+                session_id = %session.session_id,
+                error = %e,
+                "Failed to generate session title"
+            );
+        }
+    });
+}
+
 /// Send a notification via the connection context
 fn send_notification(
     cx: &JrConnectionCx<AgentToClient>,
@@ -822,20 +1071,69 @@ pub async fn handle_set_mode(
     let session = sessions.get_session_or_error(session_id_str)?;
 
     // Get previous mode for logging
-    let previous_mode = session.permission_mode().await;
+    let previous_mode = session.permission_mode_id().await;
+
+    let notification = set_session_mode(&session, mode_id_str).await?;
 
-    // Parse the mode from mode_id
-    let mode = PermissionMode::parse(mode_id_str).ok_or_else(|| {
+    // Send CurrentModeUpdate notification to inform the client
+    if let Err(e) = connection_cx.send_notification(notification) {
+        tracing::warn!(
+            session_id = %session_id_str,
+            error = %e,
+            "Failed to send CurrentModeUpdate notification"
+        );
+    }
+
+    tracing::info!(
+        session_id = %session_id_str,
+        previous_mode = %previous_mode,
+        new_mode = %mode_id_str,
+        "Session mode changed successfully"
+    );
+
+    Ok(SetSessionModeResponse::new())
+}
+
+/// Switch `session` to the permission mode `mode_id`
+///
+/// Only modes advertised for the session can be selected. Returns the
+/// CurrentModeUpdate notification to send to the client.
+async fn set_session_mode(
+    session: &Session,
+    mode_id_str: &str,
+) -> Result<SessionNotification, AgentError> {
+    let session_id_str = session.session_id.as_str();
+    let available_modes = build_available_modes(&session.custom_modes());
+    if !available_modes
+        .iter()
+        .any(|mode| &*mode.id.0 == mode_id_str)
+    {
         tracing::warn!(
             session_id = %session_id_str,
             mode_id = %mode_id_str,
             "Invalid mode ID"
         );
-        AgentError::InvalidMode(mode_id_str.to_string())
-    })?;
+        let available: Vec<&str> = available_modes.iter().map(|mode| &*mode.id.0).collect();
+        return Err(AgentError::InvalidMode(format!(
+            "{} (available modes: {})",
+            mode_id_str,
+            available.join(", ")
+        )));
+    }
 
-    // Set the mode in our permission handler
-    session.set_permission_mode(mode).await;
+    // A built-in mode, or a custom mode from settings, which runs on top of
+    // its base mode
+    let mode = if let Some(custom_mode) = session.custom_mode(mode_id_str) {
+        let base = custom_mode.base();
+        session.set_custom_mode(custom_mode).await;
+        base
+    } else {
+        let mode = PermissionMode::parse(mode_id_str)
+            .ok_or_else(|| AgentError::InvalidMode(mode_id_str.to_string()))?;
+        // Set the mode in our permission handler
+        session.set_permission_mode(mode).await;
+        mode
+    };
 
     // Also set the mode in the SDK client
     // This is important for the SDK to know the current permission mode
@@ -850,29 +1148,300 @@ pub async fn handle_set_mode(
         // Don't fail - the local mode is still set
     }
 
-    // Send CurrentModeUpdate notification to inform the client
-    let mode_update = CurrentModeUpdate::new(SessionModeId::new(mode_id_str));
-    let notification = SessionNotification::new(
-        SessionId::new(session_id_str),
-        SessionUpdate::CurrentModeUpdate(mode_update),
-    );
+    Ok(session.mode_update_notification(mode_id_str))
+}
 
-    if let Err(e) = connection_cx.send_notification(notification) {
-        tracing::warn!(
-            session_id = %session_id_str,
-            error = %e,
-            "Failed to send CurrentModeUpdate notification"
-        );
+/// Extension method: set a human-readable session title
+pub const SET_SESSION_TITLE_METHOD: &str = "session/setTitle";
+
+/// Extension method: list active sessions with their titles
+pub const LIST_SESSIONS_METHOD: &str = "session/list";
+
+/// Extension method: token usage and remaining context of a session
+pub const SESSION_USAGE_METHOD: &str = "session/usage";
+
+/// Extension method: change the working directory of a session
+pub const SET_SESSION_CWD_METHOD: &str = "session/setCwd";
+
+/// Extension method: the current todo list (plan) of a session
+pub const SESSION_TODOS_METHOD: &str = "session/todos";
+
+/// Extension method: save the todo list (plan) of a session as a markdown
+/// checklist in the plans directory
+pub const EXPORT_PLAN_METHOD: &str = "session/exportPlan";
+
+/// Extension method: switch the model of a session
+///
+/// Handled here until sacp supports ACP's `SetSessionModelRequest`.
+pub const SET_SESSION_MODEL_METHOD: &str = "session/set_model";
+
+/// Extension method: check a tool call against a session's permission rules
+/// without running it
+pub const CHECK_PERMISSION_METHOD: &str = "session/checkPermission";
+
+/// Parameters for `session/setTitle`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetSessionTitleParams {
+    session_id: String,
+    title: String,
+}
+
+/// Parameters for extension requests that only name a session
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionParams {
+    session_id: String,
+}
+
+/// Parameters for `session/set_model`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetSessionModelParams {
+    session_id: String,
+    model_id: String,
+}
+
+/// Parameters for `session/checkPermission`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CheckPermissionParams {
+    session_id: String,
+    tool_name: String,
+    /// Sample tool input (default: none)
+    #[serde(default)]
+    tool_input: serde_json::Value,
+}
+
+/// Parameters for `session/exportPlan`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportPlanParams {
+    session_id: String,
+    /// Name of the file in the plans directory (default: after the session)
+    #[serde(default)]
+    file_name: Option<String>,
+}
+
+/// Parameters for `session/setCwd`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SetSessionCwdParams {
+    session_id: String,
+    /// New directory, absolute or relative to the current one
+    cwd: std::path::PathBuf,
+}
+
+/// Handle an extension request that is not part of the ACP schema
+///
+/// Returns `None` when the method is not a known extension, so the caller
+/// can reply with a method-not-found error.
+#[instrument(name = "acp_ext_request", skip(params, config, sessions))]
+pub async fn handle_ext_request(
+    method: &str,
+    params: serde_json::Value,
+    config: &AgentConfig,
+    sessions: &Arc<SessionManager>,
+) -> Option<Result<serde_json::Value, AgentError>> {
+    let result = match method {
+        SET_SESSION_TITLE_METHOD => handle_set_title(params, sessions),
+        LIST_SESSIONS_METHOD => Ok(serde_json::json!({
+            "sessions": sessions.list_sessions(),
+        })),
+        SESSION_USAGE_METHOD => handle_session_usage(params, sessions),
+        SET_SESSION_CWD_METHOD => handle_set_cwd(params, sessions).await,
+        SESSION_TODOS_METHOD => handle_session_todos(params, sessions),
+        EXPORT_PLAN_METHOD => handle_export_plan(params, sessions),
+        SET_SESSION_MODEL_METHOD => handle_set_model(params, config, sessions),
+        CHECK_PERMISSION_METHOD => handle_check_permission(params, config, sessions).await,
+        _ => return None,
+    };
+    Some(result)
+}
+
+/// Parse extension request params, mapping failures to InvalidParams
+fn parse_ext_params<T: serde::de::DeserializeOwned>(
+    params: serde_json::Value,
+) -> Result<T, AgentError> {
+    serde_json::from_value(params).map_err(|e| AgentError::invalid_params(e.to_string()))
+}
+
+/// Handle session/setTitle extension request
+fn handle_set_title(
+    params: serde_json::Value,
+    sessions: &Arc<SessionManager>,
+) -> Result<serde_json::Value, AgentError> {
+    let params: SetSessionTitleParams = parse_ext_params(params)?;
+    let session = sessions.get_session_or_error(&params.session_id)?;
+    let title = session.set_title(&params.title);
+
+    Ok(serde_json::json!({
+        "sessionId": params.session_id,
+        "title": title,
+    }))
+}
+
+/// Handle session/usage extension request
+fn handle_session_usage(
+    params: serde_json::Value,
+    sessions: &Arc<SessionManager>,
+) -> Result<serde_json::Value, AgentError> {
+    let params: SessionParams = parse_ext_params(params)?;
+    let session = sessions.get_session_or_error(&params.session_id)?;
+
+    serde_json::to_value(session.stats()).map_err(|e| AgentError::Internal(e.to_string()))
+}
+
+/// Handle session/todos extension request
+fn handle_session_todos(
+    params: serde_json::Value,
+    sessions: &Arc<SessionManager>,
+) -> Result<serde_json::Value, AgentError> {
+    let params: SessionParams = parse_ext_params(params)?;
+    let session = sessions.get_session_or_error(&params.session_id)?;
+
+    Ok(serde_json::json!({
+        "sessionId": params.session_id,
+        "todos": session.current_todos(),
+    }))
+}
+
+/// Handle session/exportPlan extension request
+fn handle_export_plan(
+    params: serde_json::Value,
+    sessions: &Arc<SessionManager>,
+) -> Result<serde_json::Value, AgentError> {
+    let params: ExportPlanParams = parse_ext_params(params)?;
+    let session = sessions.get_session_or_error(&params.session_id)?;
+    let plans_dir = session
+        .current_plan()
+        .plans_dir()
+        .ok_or_else(|| AgentError::config_error("Could not determine the plans directory"))?;
+    let path = session.export_plan(&plans_dir, params.file_name.as_deref())?;
+
+    Ok(serde_json::json!({
+        "sessionId": params.session_id,
+        "path": path.display().to_string(),
+    }))
+}
+
+/// Handle session/checkPermission extension request
+///
+/// Reports how a tool call would be decided without running it: the
+/// effective decision after the rules and the permission mode, what decided
+/// it (`rule`, `mode` or `explicitApproval`), and the rule that matched with
+/// its decision, file of origin and layer (`settings`, `session` for
+/// "Always Allow" rules, or `customMode`).
+async fn handle_check_permission(
+    params: serde_json::Value,
+    config: &AgentConfig,
+    sessions: &Arc<SessionManager>,
+) -> Result<serde_json::Value, AgentError> {
+    let params: CheckPermissionParams = parse_ext_params(params)?;
+    let session = sessions.get_session_or_error(&params.session_id)?;
+    let (tool_name, tool_input) = (params.tool_name.as_str(), &params.tool_input);
+
+    let (mut effective, custom_decision, mode) = {
+        let handler = session.permission().await;
+        (
+            handler.check_permission(tool_name, tool_input).await,
+            handler.custom_decision(tool_name, tool_input),
+            handler.mode(),
+        )
+    };
+    let explicit_approval = crate::permissions::requires_explicit_approval(tool_name, tool_input);
+    let checker = session.permission_checker().read().await;
+    let settings_check = checker.check_permission(tool_name, tool_input);
+
+    // Default mode's approval of files inside the working directory is
+    // applied by the pre-tool-use hook, not the permission handler
+    if effective == ToolPermissionResult::NeedsPermission
+        && custom_decision.is_none()
+        && mode == PermissionMode::Default
+        && config.auto_approve_within_cwd
+        && !explicit_approval
+        && settings_check.rule.is_none()
+        && crate::permissions::is_within_cwd(tool_name, tool_input, checker.cwd())
+    {
+        effective = ToolPermissionResult::Allowed;
     }
 
-    tracing::info!(
-        session_id = %session_id_str,
-        previous_mode = ?previous_mode,
-        new_mode = %mode_id_str,
-        "Session mode changed successfully"
-    );
+    // Same order as the pre-tool-use hook: settings deny rules win over a
+    // custom mode's rules
+    let (check, layer) = match custom_decision {
+        Some(custom) if settings_check.decision != PermissionDecision::Deny => {
+            (custom, Some("customMode"))
+        }
+        _ => {
+            let layer = checker.rule_layer(&settings_check);
+            (settings_check, layer)
+        }
+    };
 
-    Ok(SetSessionModeResponse::new())
+    let (decision, reason) = match effective {
+        ToolPermissionResult::Allowed => (PermissionDecision::Allow, None),
+        ToolPermissionResult::Blocked { reason } => (PermissionDecision::Deny, Some(reason)),
+        ToolPermissionResult::NeedsPermission => (PermissionDecision::Ask, None),
+    };
+    let decided_by = if check.rule.is_some() && check.decision == decision {
+        "rule"
+    } else if explicit_approval && decision == PermissionDecision::Ask {
+        "explicitApproval"
+    } else {
+        "mode"
+    };
+
+    Ok(serde_json::json!({
+        "sessionId": params.session_id,
+        "toolName": params.tool_name,
+        "mode": session.permission_mode_id().await,
+        "decision": decision.as_str(),
+        "decidedBy": decided_by,
+        "reason": reason,
+        "ruleDecision": check.rule.is_some().then_some(check.decision.as_str()),
+        "rule": check.rule,
+        "source": check.source,
+        "origin": check.origin,
+        "layer": layer,
+    }))
+}
+
+/// Handle session/set_model extension request
+fn handle_set_model(
+    params: serde_json::Value,
+    config: &AgentConfig,
+    sessions: &Arc<SessionManager>,
+) -> Result<serde_json::Value, AgentError> {
+    let params: SetSessionModelParams = parse_ext_params(params)?;
+    let model_id = params.model_id.trim();
+    if !config.is_known_model(model_id) {
+        return Err(AgentError::invalid_params(format!(
+            "Unknown model: {}",
+            params.model_id
+        )));
+    }
+    let session = sessions.get_session_or_error(&params.session_id)?;
+    session.set_model(model_id, config.context_window_of(model_id));
+
+    Ok(serde_json::json!({
+        "sessionId": params.session_id,
+        "currentModelId": model_id,
+    }))
+}
+
+/// Handle session/setCwd extension request
+async fn handle_set_cwd(
+    params: serde_json::Value,
+    sessions: &Arc<SessionManager>,
+) -> Result<serde_json::Value, AgentError> {
+    let params: SetSessionCwdParams = parse_ext_params(params)?;
+    let session = sessions.get_session_or_error(&params.session_id)?;
+    let cwd = session.set_cwd(&params.cwd).await?;
+
+    Ok(serde_json::json!({
+        "sessionId": params.session_id,
+        "cwd": cwd.display().to_string(),
+    }))
 }
 
 /// Handle session cancellation
@@ -1145,7 +1714,9 @@ async fn drain_messages_synchronously(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use sacp::schema::{ProtocolVersion, TextContent};
+    use crate::permissions::PermissionFallback;
+    use crate::session::SessionIndex;
+    use sacp::schema::{ClientCapabilities, ProtocolVersion, TextContent};
     use serial_test::serial;
     use std::time::Duration;
     use futures::stream;
@@ -1158,6 +1729,131 @@ mod tests {
         let response = handle_initialize(request, &config);
 
         assert_eq!(response.protocol_version, ProtocolVersion::LATEST);
+        assert!(response.agent_capabilities.mcp_capabilities.sse);
+        let agent_info = response.agent_info.unwrap();
+        assert_eq!(agent_info.name, "claude-code-acp-rs");
+        assert_eq!(agent_info.version, env!("CARGO_PKG_VERSION"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_handle_initialize_reports_models() {
+        unsafe { std::env::remove_var("ANTHROPIC_MODEL") };
+        unsafe { std::env::remove_var("ANTHROPIC_SMALL_FAST_MODEL") };
+        let config = AgentConfig {
+            model: Some("glm-4.7".to_string()),
+            small_fast_model: Some("haiku".to_string()),
+            ..Default::default()
+        };
+
+        let response = handle_initialize(InitializeRequest::new(ProtocolVersion::LATEST), &config);
+
+        let meta = response.agent_capabilities.meta.unwrap();
+        let models = &meta["models"];
+        assert_eq!(models["currentModelId"], "glm-4.7");
+        let available = models["availableModels"].as_array().unwrap();
+        let ids: Vec<&str> = available
+            .iter()
+            .map(|model| model["modelId"].as_str().unwrap())
+            .collect();
+        // Configured models first, aliases after without duplicates
+        assert_eq!(
+            ids,
+            ["glm-4.7", "haiku", "default", "opus", "sonnet", "opusplan"]
+        );
+        assert_eq!(available[3]["name"], "Opus");
+
+        // Without configuration the alias list is offered with `default` selected
+        let response = handle_initialize(
+            InitializeRequest::new(ProtocolVersion::LATEST),
+            &AgentConfig::default(),
+        );
+        let meta = response.agent_capabilities.meta.unwrap();
+        assert_eq!(meta["models"]["currentModelId"], "default");
+        assert_eq!(
+            meta["models"]["availableModels"].as_array().unwrap().len(),
+            MODEL_ALIASES.len()
+        );
+    }
+
+    #[test]
+    fn test_handle_initialize_reports_modes() {
+        let review: crate::settings::CustomModeSettings =
+            serde_json::from_value(serde_json::json!({"name": "Review", "baseMode": "plan"}))
+                .unwrap();
+        let config = AgentConfig {
+            custom_modes: std::collections::HashMap::from([("review".to_string(), review)]),
+            ..Default::default()
+        };
+
+        let response = handle_initialize(InitializeRequest::new(ProtocolVersion::LATEST), &config);
+
+        let meta = response.agent_capabilities.meta.unwrap();
+        assert_eq!(meta["modes"]["currentModeId"], "default");
+        let available = meta["modes"]["availableModes"].as_array().unwrap();
+        let ids: Vec<&str> = available
+            .iter()
+            .map(|mode| mode["id"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                "default",
+                "acceptEdits",
+                "plan",
+                "dontAsk",
+                "bypassPermissions",
+                "review"
+            ]
+        );
+        assert_eq!(available[5]["name"], "Review");
+        assert_eq!(available[5]["description"], "Custom mode based on plan");
+    }
+
+    #[tokio::test]
+    async fn test_supports_permission_requests() {
+        assert!(supports_permission_requests(&InitializeRequest::new(
+            ProtocolVersion::LATEST
+        )));
+
+        // A client lacking the capability gets the fallback instead of requests
+        let mut meta = serde_json::Map::new();
+        meta.insert(
+            REQUEST_PERMISSION_CAPABILITY.to_string(),
+            serde_json::Value::Bool(false),
+        );
+        let request = InitializeRequest::new(ProtocolVersion::LATEST)
+            .client_capabilities(ClientCapabilities::new().meta(meta));
+        assert!(!supports_permission_requests(&request));
+
+        let sessions = SessionManager::new();
+        sessions.set_permission_requests_supported(supports_permission_requests(&request));
+        let config = AgentConfig {
+            permission_fallback: PermissionFallback::Rules,
+            ..Default::default()
+        };
+        let session = sessions
+            .create_session(
+                "no-requests".to_string(),
+                std::path::PathBuf::from("/tmp"),
+                &config,
+                None,
+            )
+            .unwrap();
+        assert!(!session.permission_requests_supported());
+        assert_eq!(session.permission_fallback(), PermissionFallback::Rules);
+    }
+
+    #[test]
+    fn test_handle_initialize_custom_identity() {
+        let request = InitializeRequest::new(ProtocolVersion::LATEST);
+        let config = AgentConfig::default().with_agent_identity("acme-agent", "2.3.0");
+
+        let response = handle_initialize(request, &config);
+
+        let agent_info = response.agent_info.unwrap();
+        assert_eq!(agent_info.name, "acme-agent");
+        assert_eq!(agent_info.version, "2.3.0");
     }
 
     #[tokio::test]
@@ -1168,6 +1864,349 @@ mod tests {
         // TODO: Add integration test for session/new with available commands update
     }
 
+    #[tokio::test]
+    async fn test_set_session_mode() {
+        let config = AgentConfig::default();
+        let sessions = SessionManager::new();
+        let session = sessions
+            .create_session(
+                "set-mode".to_string(),
+                std::path::PathBuf::from("/tmp"),
+                &config,
+                None,
+            )
+            .unwrap();
+
+        let notification = set_session_mode(&session, "acceptEdits").await.unwrap();
+        assert_eq!(session.permission_mode().await, PermissionMode::AcceptEdits);
+        assert_eq!(&*notification.session_id.0, "set-mode");
+        let SessionUpdate::CurrentModeUpdate(update) = &notification.update else {
+            panic!("expected a mode update, got {:?}", notification.update);
+        };
+        assert_eq!(&*update.current_mode_id.0, "acceptEdits");
+
+        let err = set_session_mode(&session, "yolo").await.unwrap_err();
+        assert!(matches!(err, AgentError::InvalidMode(_)));
+        assert_eq!(
+            err.to_string(),
+            "Invalid mode: yolo (available modes: default, acceptEdits, plan, dontAsk, bypassPermissions)"
+        );
+        // The mode is unchanged
+        assert_eq!(session.permission_mode().await, PermissionMode::AcceptEdits);
+    }
+
+    #[tokio::test]
+    async fn test_load_session_resumes_in_recorded_mode() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let index = SessionIndex::new(dir.path().join("acp-sessions.json"));
+        index
+            .record_permission_mode("planning-session", PermissionMode::Plan)
+            .unwrap();
+
+        let config = AgentConfig::default();
+        let sessions = Arc::new(SessionManager::new().with_session_index(index));
+        let request = LoadSessionRequest::new("planning-session", std::path::PathBuf::from("/tmp"));
+        let response = handle_load_session(request, &config, &sessions).unwrap();
+
+        assert_eq!(response.modes.unwrap().current_mode_id.0.as_ref(), "plan");
+        let session = sessions.get_session("planning-session").unwrap();
+        assert_eq!(session.permission_mode().await, PermissionMode::Plan);
+
+        // Leaving Plan mode is recorded for the next resume
+        session
+            .set_permission_mode(PermissionMode::AcceptEdits)
+            .await;
+        assert_eq!(
+            sessions.recorded_permission_mode("planning-session"),
+            Some(PermissionMode::AcceptEdits)
+        );
+
+        // Modes approving edits without asking aren't restored
+        for mode in [
+            PermissionMode::AcceptEdits,
+            PermissionMode::BypassPermissions,
+        ] {
+            let session_id = format!("{}-session", mode.as_str());
+            let index = SessionIndex::new(dir.path().join("acp-sessions.json"));
+            index.record_permission_mode(&session_id, mode).unwrap();
+            let sessions = Arc::new(SessionManager::new().with_session_index(index));
+            let request = LoadSessionRequest::new(session_id, std::path::PathBuf::from("/tmp"));
+            let response = handle_load_session(request, &config, &sessions).unwrap();
+            assert_eq!(
+                response.modes.unwrap().current_mode_id.0.as_ref(),
+                "default"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_handle_ext_set_title_and_list() {
+        let config = AgentConfig::default();
+        let sessions = Arc::new(SessionManager::new());
+        sessions
+            .create_session(
+                "title-session".to_string(),
+                std::path::PathBuf::from("/tmp"),
+                &config,
+                None,
+            )
+            .unwrap();
+
+        let response = handle_ext_request(
+            SET_SESSION_TITLE_METHOD,
+            serde_json::json!({"sessionId": "title-session", "title": "  Fix flaky tests "}),
+            &config,
+            &sessions,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(response["title"], "Fix flaky tests");
+
+        let listing = handle_ext_request(
+            LIST_SESSIONS_METHOD,
+            serde_json::json!({}),
+            &config,
+            &sessions,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            listing["sessions"],
+            serde_json::json!([{
+                "sessionId": "title-session",
+                "cwd": "/tmp",
+                "title": "Fix flaky tests"
+            }])
+        );
+
+        // Unknown sessions and malformed params are reported as errors
+        let missing = handle_ext_request(
+            SET_SESSION_TITLE_METHOD,
+            serde_json::json!({"sessionId": "nope", "title": "x"}),
+            &config,
+            &sessions,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(missing, Err(AgentError::SessionNotFound(_))));
+
+        let invalid = handle_ext_request(
+            SET_SESSION_TITLE_METHOD,
+            serde_json::json!({}),
+            &config,
+            &sessions,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(invalid, Err(AgentError::InvalidParams(_))));
+
+        // Unknown methods are left to the caller
+        assert!(
+            handle_ext_request("session/unknown", serde_json::json!({}), &config, &sessions)
+                .await
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handle_ext_session_usage() {
+        let sessions = Arc::new(SessionManager::new());
+        let config = AgentConfig {
+            model: Some("claude-sonnet-4-5".to_string()),
+            ..Default::default()
+        };
+        let session = sessions
+            .create_session(
+                "usage-session".to_string(),
+                std::path::PathBuf::from("/tmp"),
+                &config,
+                None,
+            )
+            .unwrap();
+        let usage = TokenUsage {
+            input_tokens: 2_000,
+            output_tokens: 1_000,
+            cache_read_input_tokens: Some(167_000),
+            cache_creation_input_tokens: None,
+        };
+        session.usage_tracker().add(&usage);
+        session.usage_tracker().record_context(&usage);
+        session.usage_tracker().add_cost(&usage);
+
+        let response = handle_ext_request(
+            SESSION_USAGE_METHOD,
+            serde_json::json!({"sessionId": "usage-session"}),
+            &config,
+            &sessions,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(response["totalUsage"]["input_tokens"], 2_000);
+        // Priced as Sonnet: $0.006 input, $0.015 output, $0.0501 cache reads
+        let cost = response["totalCostUsd"].as_f64().unwrap();
+        assert!((cost - 0.0711).abs() < 1e-9, "{cost}");
+        assert_eq!(
+            response["context"],
+            serde_json::json!({
+                "contextWindow": 200_000,
+                "usedTokens": 170_000,
+                "remainingTokens": 30_000,
+                "low": true
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_handle_ext_session_todos() {
+        let config = AgentConfig::default();
+        let sessions = Arc::new(SessionManager::new());
+        sessions
+            .create_session(
+                "todos-session".to_string(),
+                std::path::PathBuf::from("/tmp"),
+                &config,
+                None,
+            )
+            .unwrap();
+
+        let response = handle_ext_request(
+            SESSION_TODOS_METHOD,
+            serde_json::json!({"sessionId": "todos-session"}),
+            &config,
+            &sessions,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            response,
+            serde_json::json!({"sessionId": "todos-session", "todos": []})
+        );
+
+        // Without todos there is no plan to export
+        let result = handle_ext_request(
+            EXPORT_PLAN_METHOD,
+            serde_json::json!({"sessionId": "todos-session"}),
+            &config,
+            &sessions,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(result, Err(AgentError::InvalidParams(_))));
+    }
+
+    #[tokio::test]
+    async fn test_handle_ext_check_permission() {
+        let config = AgentConfig::default();
+        let sessions = Arc::new(SessionManager::new());
+        let session = sessions
+            .create_session(
+                "check-session".to_string(),
+                std::path::PathBuf::from("/tmp"),
+                &config,
+                None,
+            )
+            .unwrap();
+        session
+            .permission_checker()
+            .write()
+            .await
+            .add_deny_rule("Bash(rm:*)");
+
+        let check = |command: &str| {
+            handle_ext_request(
+                CHECK_PERMISSION_METHOD,
+                serde_json::json!({
+                    "sessionId": "check-session",
+                    "toolName": "Bash",
+                    "toolInput": {"command": command},
+                }),
+                &config,
+                &sessions,
+            )
+        };
+        let response = check("rm -rf target").await.unwrap().unwrap();
+        assert_eq!(response["decision"], "deny");
+        assert_eq!(response["rule"], "Bash(rm:*)");
+        assert_eq!(response["source"], "deny");
+        assert_eq!(response["layer"], "session");
+        assert_eq!(response["origin"], "session");
+        assert_eq!(response["decidedBy"], "rule");
+        assert_eq!(response["mode"], "default");
+
+        let response = check("cargo build").await.unwrap().unwrap();
+        assert_eq!(response["decision"], "ask");
+        assert_eq!(response["decidedBy"], "mode");
+        assert!(response["rule"].is_null());
+
+        // The mode's outcome is reported, and deny rules still win over it
+        session
+            .set_permission_mode(PermissionMode::BypassPermissions)
+            .await;
+        let response = check("cargo build").await.unwrap().unwrap();
+        assert_eq!(response["decision"], "allow");
+        assert_eq!(response["decidedBy"], "mode");
+        assert_eq!(response["mode"], "bypassPermissions");
+        let response = check("rm -rf target").await.unwrap().unwrap();
+        assert_eq!(response["decision"], "deny");
+        assert_eq!(response["decidedBy"], "rule");
+    }
+
+    #[tokio::test]
+    async fn test_handle_ext_set_model() {
+        let config = AgentConfig {
+            model: Some("claude-sonnet-4-5".to_string()),
+            ..Default::default()
+        };
+        let sessions = Arc::new(SessionManager::new());
+        let session = sessions
+            .create_session(
+                "model-session".to_string(),
+                std::path::PathBuf::from("/tmp"),
+                &config,
+                None,
+            )
+            .unwrap();
+        assert_eq!(
+            session.current_model().as_deref(),
+            Some("claude-sonnet-4-5")
+        );
+
+        let response = handle_ext_request(
+            SET_SESSION_MODEL_METHOD,
+            serde_json::json!({"sessionId": "model-session", "modelId": "claude-opus-4-5[1m]"}),
+            &config,
+            &sessions,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(response["currentModelId"], "claude-opus-4-5[1m]");
+        assert_eq!(
+            session.current_model().as_deref(),
+            Some("claude-opus-4-5[1m]")
+        );
+        assert_eq!(session.context_window(), 1_000_000);
+
+        // Unknown models are rejected and leave the session's model alone
+        let unknown = handle_ext_request(
+            SET_SESSION_MODEL_METHOD,
+            serde_json::json!({"sessionId": "model-session", "modelId": "gpt-5"}),
+            &config,
+            &sessions,
+        )
+        .await
+        .unwrap();
+        assert!(matches!(unknown, Err(AgentError::InvalidParams(_))));
+        assert_eq!(
+            session.current_model().as_deref(),
+            Some("claude-opus-4-5[1m]")
+        );
+    }
+
     #[test]
     fn test_extract_text_from_content() {
         let blocks = vec![
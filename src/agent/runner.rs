@@ -260,6 +260,14 @@ pub async fn run_acp_with_cli(cli: &Cli) -> anyhow::Result<()> {
             );
         }
 
+        if let Some(config_dir) = &cli.config_dir {
+            crate::settings::set_user_config_dir(config_dir.clone());
+            tracing::info!(
+                config_dir = %config_dir.display(),
+                "Using user configuration directory"
+            );
+        }
+
         if let Some(otel_endpoint) = &cli.otel_endpoint {
             tracing::info!(
                 otel_endpoint = %otel_endpoint,
@@ -425,6 +433,7 @@ async fn run_acp_server() -> Result<(), sacp::Error> {
         .on_receive_request(
             {
                 let config = config.clone();
+                let sessions = sessions.clone();
                 async move |request: InitializeRequest, request_cx, _connection_cx| {
                     let protocol_version = format!("{:?}", request.protocol_version);
                     let span = tracing::info_span!(
@@ -437,6 +446,9 @@ async fn run_acp_server() -> Result<(), sacp::Error> {
                             "Received initialize request (protocol version: {})",
                             protocol_version
                         );
+                        sessions.set_permission_requests_supported(
+                            handlers::supports_permission_requests(&request),
+                        );
                         let response = handlers::handle_initialize(request, &config);
                         tracing::debug!("Sending initialize response");
                         request_cx.respond(response)
@@ -681,9 +693,9 @@ async fn run_acp_server() -> Result<(), sacp::Error> {
             },
             sacp::on_receive_request!(),
         )
-        // Note: SetSessionModel is not yet supported by sacp SDK (JrRequest not implemented)
-        // The model selection is returned in NewSessionResponse, but changing it mid-session
-        // is not yet available. When sacp adds support, uncomment the following handler.
+        // Note: SetSessionModel is not yet supported by sacp SDK (JrRequest not implemented),
+        // so changing the model mid-session goes through the session/set_model extension
+        // request handled below.
         // Handle session/cancel notification
         .on_receive_notification(
             {
@@ -711,24 +723,58 @@ async fn run_acp_server() -> Result<(), sacp::Error> {
             },
             sacp::on_receive_notification!(),
         )
-        // Handle unknown messages
+        // Handle extension requests (session/setTitle, session/list, session/usage, session/setCwd,
+        // session/todos, session/exportPlan, session/set_model, session/checkPermission)
+        // and unknown messages
         .on_receive_message(
-            async move |message: MessageCx, connection_cx: JrConnectionCx<AgentToClient>| {
-                let method = message.message().method.clone();
-                let span = tracing::warn_span!(
-                    "handle_unknown_message",
-                    method = ?method,
-                );
-
-                async {
-                    tracing::warn!("Received unknown message: {:?}", method);
-                    message.respond_with_error(
-                        sacp::util::internal_error("Unknown method"),
-                        connection_cx,
-                    )
+            {
+                let config = config.clone();
+                let sessions = sessions.clone();
+                async move |message: MessageCx, connection_cx: JrConnectionCx<AgentToClient>| {
+                    let method = message.message().method.clone();
+                    let span = tracing::info_span!(
+                        "handle_ext_message",
+                        method = %method,
+                    );
+
+                    async {
+                        match message {
+                            MessageCx::Request(request, request_cx) => {
+                                match handlers::handle_ext_request(
+                                    &request.method,
+                                    request.params,
+                                    &config,
+                                    &sessions,
+                                )
+                                .await
+                                {
+                                    Some(Ok(response)) => request_cx.respond(response),
+                                    Some(Err(e)) => {
+                                        tracing::warn!("Extension request {} failed: {}", method, e);
+                                        request_cx.respond_with_error(
+                                            sacp::util::internal_error(e.to_string()),
+                                        )
+                                    }
+                                    None => {
+                                        tracing::warn!("Received unknown request: {}", method);
+                                        request_cx.respond_with_error(
+                                            sacp::util::internal_error("Unknown method"),
+                                        )
+                                    }
+                                }
+                            }
+                            message => {
+                                tracing::warn!("Received unknown message: {}", method);
+                                message.respond_with_error(
+                                    sacp::util::internal_error("Unknown method"),
+                                    connection_cx,
+                                )
+                            }
+                        }
+                    }
+                    .instrument(span)
+                    .await
                 }
-                .instrument(span)
-                .await
             },
             sacp::on_receive_message!(),
         )
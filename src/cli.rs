@@ -47,6 +47,11 @@ pub struct Cli {
     #[arg(long, value_name = "URL", env = "OTEL_EXPORTER_OTLP_ENDPOINT")]
     pub otel_endpoint: Option<String>,
 
+    /// User configuration directory to use instead of ~/.claude
+    /// (settings, plans and memory)
+    #[arg(long, value_name = "DIR", env = "CLAUDE_CONFIG_DIR")]
+    pub config_dir: Option<PathBuf>,
+
     /// OpenTelemetry service name
     #[arg(long, value_name = "NAME", default_value = "claude-code-acp-rs")]
     pub otel_service_name: String,
@@ -64,6 +69,7 @@ impl Default for Cli {
             verbose: 0,
             quiet: false,
             otel_endpoint: None,
+            config_dir: None,
             otel_service_name: "claude-code-acp-rs".to_string(),
         }
     }
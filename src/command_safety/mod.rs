@@ -7,9 +7,11 @@
 
 mod is_dangerous_command;
 mod is_safe_command;
+mod simple_commands;
 
 pub use is_dangerous_command::command_might_be_dangerous;
 pub use is_safe_command::is_known_safe_command;
+pub use simple_commands::{SimpleCommand, split_simple_commands};
 
 /// Extract the basename of a command, handling full paths
 ///
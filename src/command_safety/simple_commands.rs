@@ -0,0 +1,278 @@
+//! Splitting shell commands into simple commands
+//!
+//! Permission rules match one program invocation at a time, so a command
+//! line is split at `;`, `&`, `&&`, `||`, `|`, `|&` and newlines before
+//! matching, with quotes and escapes taken into account.
+//!
+//! A simple command is *plain* if running it does nothing but start the
+//! program it names with literal arguments: no expansions (`$VAR`, `$(..)`,
+//! backticks), redirections or process substitutions (`>`, `<`, `<(..)`),
+//! subshells or groups, comments, leading keywords (`if`, `then`, `!`, ...)
+//! or variable assignments (`FOO=1 cmd`). Only plain commands may be allowed
+//! by an allow rule; deny rules are matched against every command.
+
+/// Reserved words that may precede a command
+const LEADING_KEYWORDS: &[&str] = &[
+    "if", "then", "else", "elif", "do", "while", "until", "!", "time",
+];
+
+/// One program invocation of a command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimpleCommand {
+    /// The command as written, without leading keywords and assignments
+    pub command: String,
+    /// Whether the command only runs the named program with literal arguments
+    pub plain: bool,
+}
+
+/// Split a command line into its simple commands
+///
+/// Empty commands (e.g. after a trailing `;`) are skipped. Returns None if
+/// the command can't be tokenized, e.g. because of an unterminated quote.
+pub fn split_simple_commands(command_line: &str) -> Option<Vec<SimpleCommand>> {
+    let mut commands = Vec::new();
+    let mut current = Segment::default();
+    let mut chars = command_line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next()? {
+                // A backslash-newline continues the line
+                '\n' => {}
+                escaped => {
+                    current.text.push(c);
+                    current.text.push(escaped);
+                }
+            },
+            '\'' => {
+                current.text.push(c);
+                loop {
+                    let quoted = chars.next()?;
+                    current.text.push(quoted);
+                    if quoted == '\'' {
+                        break;
+                    }
+                }
+            }
+            '"' => {
+                current.text.push(c);
+                loop {
+                    let quoted = chars.next()?;
+                    current.text.push(quoted);
+                    match quoted {
+                        '"' => break,
+                        '\\' => current.text.push(chars.next()?),
+                        '$' | '`' => current.plain = false,
+                        _ => {}
+                    }
+                }
+            }
+            ';' | '\n' | '&' | '|' => {
+                // `&&`, `||` and `|&` separate like their first character
+                if matches!(
+                    (c, chars.peek().copied()),
+                    ('&', Some('&')) | ('|', Some('|' | '&'))
+                ) {
+                    chars.next();
+                }
+                commands.extend(std::mem::take(&mut current).finish());
+            }
+            '<' | '>' => {
+                current.text.push(c);
+                current.plain &= current.read_descriptor_duplication(&mut chars);
+            }
+            '$' | '`' | '(' | ')' | '{' | '}' => {
+                current.plain = false;
+                current.text.push(c);
+            }
+            '#' if current.at_word_start() => {
+                current.plain = false;
+                // The comment runs to the end of the line
+                while chars.next_if(|&c| c != '\n').is_some() {}
+            }
+            c => current.text.push(c),
+        }
+    }
+    commands.extend(current.finish());
+    Some(commands)
+}
+
+/// The simple command being read
+#[derive(Debug)]
+struct Segment {
+    text: String,
+    plain: bool,
+}
+
+impl Default for Segment {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            plain: true,
+        }
+    }
+}
+
+impl Segment {
+    fn at_word_start(&self) -> bool {
+        self.text
+            .chars()
+            .next_back()
+            .is_none_or(char::is_whitespace)
+    }
+
+    /// Read the rest of a redirection after its `<` or `>`, returning
+    /// whether it only duplicates a file descriptor (`2>&1`)
+    fn read_descriptor_duplication(
+        &mut self,
+        chars: &mut std::iter::Peekable<std::str::Chars<'_>>,
+    ) -> bool {
+        if chars.next_if_eq(&'&').is_none() {
+            return false;
+        }
+        self.text.push('&');
+        let mut target = false;
+        while let Some(c) = chars.next_if(|c| c.is_ascii_digit() || *c == '-') {
+            self.text.push(c);
+            target = true;
+        }
+        target
+            && chars
+                .peek()
+                .is_none_or(|c| c.is_whitespace() || matches!(c, ';' | '&' | '|'))
+    }
+
+    /// The finished command, None if it is empty
+    fn finish(self) -> Option<SimpleCommand> {
+        let mut plain = self.plain;
+        let mut rest = self.text.trim();
+        // Skip leading keywords and `NAME=value` assignments
+        loop {
+            let (word, after) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            if word.is_empty() || !(LEADING_KEYWORDS.contains(&word) || is_assignment(word)) {
+                break;
+            }
+            plain = false;
+            rest = after.trim_start();
+        }
+        if rest.is_empty() {
+            return None;
+        }
+        Some(SimpleCommand {
+            command: rest.to_string(),
+            plain,
+        })
+    }
+}
+
+/// Whether a word is a variable assignment (`NAME=value`)
+fn is_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        let mut chars = name.chars();
+        chars
+            .next()
+            .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commands(command_line: &str) -> Vec<(String, bool)> {
+        split_simple_commands(command_line)
+            .unwrap()
+            .into_iter()
+            .map(|command| (command.command, command.plain))
+            .collect()
+    }
+
+    fn plain(command: &str) -> (String, bool) {
+        (command.to_string(), true)
+    }
+
+    fn not_plain(command: &str) -> (String, bool) {
+        (command.to_string(), false)
+    }
+
+    #[test]
+    fn test_split_at_operators() {
+        assert_eq!(commands("git status"), vec![plain("git status")]);
+        assert_eq!(
+            commands("git status; git push"),
+            vec![plain("git status"), plain("git push")]
+        );
+        assert_eq!(
+            commands("git status & rm -rf ~"),
+            vec![plain("git status"), plain("rm -rf ~")]
+        );
+        assert_eq!(
+            commands("make && make test || echo failed |& tee log\nls;"),
+            vec![
+                plain("make"),
+                plain("make test"),
+                plain("echo failed"),
+                plain("tee log"),
+                plain("ls")
+            ]
+        );
+
+        // Operators inside quotes or escaped don't split
+        assert_eq!(
+            commands(r#"git commit -m "fix; tidy" && echo 'a|b' c\;d"#),
+            vec![
+                plain(r#"git commit -m "fix; tidy""#),
+                plain(r"echo 'a|b' c\;d")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_commands_that_are_not_plain() {
+        // Duplicating a descriptor writes no file
+        assert_eq!(
+            commands("cargo test 2>&1 | tail"),
+            vec![plain("cargo test 2>&1"), plain("tail")]
+        );
+        assert_eq!(
+            commands("cargo test >&out.log"),
+            vec![not_plain("cargo test >&out.log")]
+        );
+
+        assert_eq!(
+            commands("git log > ~/.bashrc"),
+            vec![not_plain("git log > ~/.bashrc")]
+        );
+        assert_eq!(
+            commands("git diff <(cat a) $HOME \"$(whoami)\" `id`"),
+            vec![not_plain("git diff <(cat a) $HOME \"$(whoami)\" `id`")]
+        );
+        assert_eq!(
+            commands("RUSTC_WRAPPER=/tmp/x cargo test"),
+            vec![not_plain("cargo test")]
+        );
+        assert_eq!(
+            commands("if git status; then rm -rf ~; fi"),
+            vec![not_plain("git status"), not_plain("rm -rf ~"), plain("fi")]
+        );
+        assert_eq!(
+            commands("(cd /tmp) # comment; rm"),
+            vec![not_plain("(cd /tmp)")]
+        );
+
+        // Quoted text and `=` in arguments are fine
+        assert_eq!(
+            commands("git log --format='$H > x' --author=me"),
+            vec![plain("git log --format='$H > x' --author=me")]
+        );
+    }
+
+    #[test]
+    fn test_unterminated_quotes() {
+        assert!(split_simple_commands("echo 'oops").is_none());
+        assert!(split_simple_commands("echo \"oops").is_none());
+        assert!(split_simple_commands("echo oops\\").is_none());
+        assert_eq!(commands(""), Vec::<(String, bool)>::new());
+    }
+}
@@ -0,0 +1,146 @@
+//! Diff context limiting for tool call Diff content
+//!
+//! Edit diffs carry the full old and new text, which for large edits buries
+//! the actual change. With a context size configured, only the changed
+//! lines and that many unchanged lines around each change are kept; the
+//! lines left out are replaced by a `...` line in both texts.
+
+use similar::TextDiff;
+
+/// Line standing in for unchanged lines left out of a diff
+const ELIDED_LINES: &str = "...\n";
+
+/// Reduce `old` and `new` to their changes plus `context_lines` unchanged
+/// lines around each change
+///
+/// Texts without changes are returned unchanged.
+pub fn limit_diff_context(old: &str, new: &str, context_lines: usize) -> (String, String) {
+    let diff = TextDiff::from_lines(old, new);
+    let groups = diff.grouped_ops(context_lines);
+    if groups.is_empty() {
+        return (old.to_string(), new.to_string());
+    }
+
+    let (old_lines, new_lines) = (diff.old_slices(), diff.new_slices());
+    let (mut old_out, mut new_out) = (String::new(), String::new());
+    let mut old_end = 0;
+    for group in &groups {
+        let (Some(first), Some(last)) = (group.first(), group.last()) else {
+            continue;
+        };
+        let old_range = first.old_range().start..last.old_range().end;
+        let new_range = first.new_range().start..last.new_range().end;
+        if old_range.start > old_end {
+            old_out.push_str(ELIDED_LINES);
+            new_out.push_str(ELIDED_LINES);
+        }
+        push_lines(&mut old_out, &old_lines[old_range.clone()]);
+        push_lines(&mut new_out, &new_lines[new_range]);
+        old_end = old_range.end;
+    }
+    if old_end < old_lines.len() {
+        old_out.push_str(ELIDED_LINES);
+        new_out.push_str(ELIDED_LINES);
+    }
+    (old_out, new_out)
+}
+
+/// Old and new text of the hunks of a unified diff
+///
+/// Anything before the first hunk (such as file headers) is skipped and the
+/// lines between hunks are replaced by a `...` line. Returns None if there
+/// are no hunks.
+pub fn unified_diff_texts(diff: &str) -> Option<(String, String)> {
+    let (mut old_out, mut new_out) = (String::new(), String::new());
+    let mut in_hunk = false;
+    for line in diff.lines() {
+        if line.starts_with("@@") {
+            if in_hunk {
+                old_out.push_str(ELIDED_LINES);
+                new_out.push_str(ELIDED_LINES);
+            }
+            in_hunk = true;
+        } else if in_hunk {
+            match line.split_at_checked(1) {
+                Some(("-", rest)) => push_lines(&mut old_out, &[rest]),
+                Some(("+", rest)) => push_lines(&mut new_out, &[rest]),
+                Some((" ", rest)) => {
+                    push_lines(&mut old_out, &[rest]);
+                    push_lines(&mut new_out, &[rest]);
+                }
+                // A blank context line whose leading space was trimmed
+                _ if line.is_empty() => {
+                    old_out.push('\n');
+                    new_out.push('\n');
+                }
+                // "\ No newline at end of file"
+                _ => {}
+            }
+        }
+    }
+    in_hunk.then_some((old_out, new_out))
+}
+
+/// Append lines, ending the last one with a newline if it has none
+fn push_lines(out: &mut String, lines: &[&str]) {
+    for line in lines {
+        out.push_str(line);
+        if !line.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbered(lines: std::ops::Range<usize>, changed: Option<usize>) -> String {
+        let mut text = String::new();
+        for i in lines {
+            let word = if Some(i) == changed {
+                "changed"
+            } else {
+                "line"
+            };
+            text.push_str(&format!("{word} {i}\n"));
+        }
+        text
+    }
+
+    #[test]
+    fn test_limit_diff_context() {
+        let old = numbered(0..100, None);
+        let new = numbered(0..100, Some(50));
+
+        let (old_out, new_out) = limit_diff_context(&old, &new, 2);
+        assert_eq!(
+            old_out,
+            "...\nline 48\nline 49\nline 50\nline 51\nline 52\n...\n"
+        );
+        assert_eq!(
+            new_out,
+            "...\nline 48\nline 49\nchanged 50\nline 51\nline 52\n...\n"
+        );
+
+        // Changes near the edges aren't preceded/followed by a marker
+        let (old_out, _) = limit_diff_context("a\nb\n", "a\nc\n", 3);
+        assert_eq!(old_out, "a\nb\n");
+
+        // Identical texts come back unchanged
+        let (old_out, new_out) = limit_diff_context(&old, &old, 2);
+        assert_eq!(old_out, old);
+        assert_eq!(new_out, old);
+    }
+
+    #[test]
+    fn test_unified_diff_texts() {
+        let diff = "2 additions\n\n--- a.txt\n+++ b.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+2\n\
+                    @@ -9 +9,2 @@\n nine\n+ten\n\\ No newline at end of file\n";
+        let (old, new) = unified_diff_texts(diff).unwrap();
+        assert_eq!(old, "one\ntwo\n...\nnine\n");
+        assert_eq!(new, "one\n2\n...\nnine\nten\n");
+
+        assert!(unified_diff_texts("a.txt and b.txt are identical").is_none());
+    }
+}
@@ -4,10 +4,11 @@
 //! - ACP `PromptRequest` → Claude SDK `UserContentBlock`
 //! - Claude SDK `Message` → ACP `SessionNotification`
 
+mod diff;
 mod notification;
 mod prompt;
 mod tool;
 
 pub use notification::NotificationConverter;
-pub use prompt::PromptConverter;
-pub use tool::extract_tool_info;
+pub use prompt::{PromptConverter, ThinkingTriggers};
+pub use tool::{extract_tool_info, synthesize_bash_description};
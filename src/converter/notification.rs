@@ -3,6 +3,7 @@
 //! Converts SDK messages (assistant, system, result, stream events)
 //! into ACP session notifications for the client.
 
+use std::sync::Arc;
 use std::time::Instant;
 
 use claude_code_agent_sdk::{
@@ -18,9 +19,12 @@ use sacp::schema::{
     ToolCallUpdate, ToolCallUpdateFields, ToolKind as AcpToolKind,
 };
 
-use crate::types::{ToolKind, ToolUseEntry};
+use crate::mcp::tools::{TodoItem, TodoList, TodoPriority};
+use crate::session::{CurrentPlan, UsageTracker};
+use crate::types::{TokenUsage, ToolKind, ToolUseEntry};
 
-use super::extract_tool_info;
+use super::diff::{limit_diff_context, unified_diff_texts};
+use super::{extract_tool_info, synthesize_bash_description};
 
 /// Static regex for finding backtick sequences at start of lines
 /// Used by markdown_escape to determine the appropriate escape sequence
@@ -67,6 +71,32 @@ fn remove_system_reminders(text: &str) -> String {
     SYSTEM_REMINDER_REGEX.replace_all(text, "").to_string()
 }
 
+/// Separate image blocks from the other blocks of a tool result
+///
+/// Returns the other blocks serialized as the text output, and the images
+/// so they can be shown as images instead of base64 text.
+fn split_image_blocks(blocks: &impl serde::Serialize) -> (String, Vec<ImageBlock>) {
+    let Ok(serde_json::Value::Array(blocks)) = serde_json::to_value(blocks) else {
+        return (
+            serde_json::to_string(blocks).unwrap_or_default(),
+            Vec::new(),
+        );
+    };
+
+    let mut images = Vec::new();
+    let mut others = Vec::new();
+    for block in blocks {
+        let image = (block.get("type").and_then(|t| t.as_str()) == Some("image"))
+            .then(|| serde_json::from_value::<ImageBlock>(block.clone()).ok())
+            .flatten();
+        match image {
+            Some(image) => images.push(image),
+            None => others.push(block),
+        }
+    }
+    (serde_json::to_string(&others).unwrap_or_default(), images)
+}
+
 /// Notification converter for transforming SDK messages to ACP notifications
 ///
 /// Maintains a cache of tool uses to correlate tool_use blocks with their results.
@@ -78,6 +108,16 @@ pub struct NotificationConverter {
     cwd: Option<std::path::PathBuf>,
     /// Optional request_id for tracking prompt requests
     request_id: Option<String>,
+    /// Title Bash tool calls without a `description` with a synthesized one
+    synthesize_bash_description: bool,
+    /// Unchanged lines kept around each change of an Edit diff (None = all)
+    diff_context_lines: Option<usize>,
+    /// Session todo list that TodoWrite calls are applied to
+    todo_list: Option<Arc<TodoList>>,
+    /// Session plan file that writes to the plans directory are recorded in
+    current_plan: Option<Arc<CurrentPlan>>,
+    /// Session usage tracker that the cost of each turn is added to
+    usage_tracker: Option<Arc<UsageTracker>>,
 }
 
 impl Default for NotificationConverter {
@@ -93,6 +133,11 @@ impl NotificationConverter {
             tool_use_cache: DashMap::new(),
             cwd: None,
             request_id: None,
+            synthesize_bash_description: false,
+            diff_context_lines: None,
+            todo_list: None,
+            current_plan: None,
+            usage_tracker: None,
         }
     }
 
@@ -106,9 +151,61 @@ impl NotificationConverter {
             tool_use_cache: DashMap::new(),
             cwd: Some(cwd),
             request_id: None,
+            synthesize_bash_description: false,
+            diff_context_lines: None,
+            todo_list: None,
+            current_plan: None,
+            usage_tracker: None,
         }
     }
 
+    /// Synthesize a short title for Bash tool calls that have no `description`
+    ///
+    /// When disabled, such tool calls are titled with the (truncated) command.
+    /// The full command is always available in the tool call's `raw_input`.
+    #[must_use]
+    pub fn with_bash_description_synthesis(mut self, enabled: bool) -> Self {
+        self.synthesize_bash_description = enabled;
+        self
+    }
+
+    /// Limit Edit and MultiEdit diffs to the changed lines plus this many
+    /// unchanged lines around each change
+    ///
+    /// None keeps the full old and new text.
+    #[must_use]
+    pub fn with_diff_context_lines(mut self, context_lines: Option<usize>) -> Self {
+        self.diff_context_lines = context_lines;
+        self
+    }
+
+    /// Apply TodoWrite calls to a todo list
+    ///
+    /// Plans are then built from the updated list, so a TodoWrite naming only
+    /// some todos still shows the whole plan.
+    #[must_use]
+    pub fn with_todo_list(mut self, todo_list: Arc<TodoList>) -> Self {
+        self.todo_list = Some(todo_list);
+        self
+    }
+
+    /// Record writes to plan files as the session's current plan
+    ///
+    /// Each successful edit of the current plan then updates the client's
+    /// plan with the file's list items.
+    #[must_use]
+    pub fn with_current_plan(mut self, current_plan: Arc<CurrentPlan>) -> Self {
+        self.current_plan = Some(current_plan);
+        self
+    }
+
+    /// Add the cost of each turn's usage to a usage tracker
+    #[must_use]
+    pub fn with_usage_tracker(mut self, usage_tracker: Arc<UsageTracker>) -> Self {
+        self.usage_tracker = Some(usage_tracker);
+        self
+    }
+
     /// Set the request_id for this converter
     ///
     /// The request_id will be attached to all SessionNotification instances
@@ -122,6 +219,11 @@ impl NotificationConverter {
         self.request_id = Some(request_id);
     }
 
+    /// Set the working directory used for relative path display
+    pub fn set_cwd(&mut self, cwd: std::path::PathBuf) {
+        self.cwd = Some(cwd);
+    }
+
     /// Clear the request_id
     pub fn clear_request_id(&mut self) {
         self.request_id = None;
@@ -167,7 +269,11 @@ impl NotificationConverter {
             Message::Assistant(assistant) => self.convert_assistant_message(assistant, &sid),
             Message::StreamEvent(event) => self.convert_stream_event(event, &sid),
             Message::Result(result) => self.convert_result_message(result, &sid),
-            Message::System(_) => {
+            Message::System(system) => {
+                // Price usage at the model the CLI reports it uses
+                if let (Some(tracker), Some(model)) = (&self.usage_tracker, &system.model) {
+                    tracker.set_model(model);
+                }
                 // System messages are typically internal, not sent as notifications
                 vec![]
             }
@@ -431,11 +537,15 @@ impl NotificationConverter {
     /// Convert a result message
     fn convert_result_message(
         &self,
-        _result: &ResultMessage,
+        result: &ResultMessage,
         _session_id: &SessionId,
     ) -> Vec<SessionNotification> {
         // Result messages update usage statistics but don't typically
         // generate notifications (the prompt response handles completion)
+        if let Some(tracker) = &self.usage_tracker {
+            let usage = result.usage.as_ref().map(TokenUsage::from_sdk_usage);
+            tracker.add_turn_cost(usage.as_ref(), result.total_cost_usd);
+        }
         vec![]
     }
 
@@ -586,6 +696,9 @@ impl NotificationConverter {
 
             match (description, command) {
                 (Some(desc), _) => desc.to_string(),
+                (None, Some(cmd)) if self.synthesize_bash_description => {
+                    synthesize_bash_description(cmd).unwrap_or_else(|| tool_info.title.clone())
+                }
                 (None, Some(cmd)) => {
                     // Truncate long commands for display
                     if cmd.len() > 80 {
@@ -639,7 +752,7 @@ impl NotificationConverter {
     ///
     /// Returns a vector of notifications:
     /// - ToolCallUpdate for all tools
-    /// - Plan notification for TodoWrite tool (when successful)
+    /// - An image AgentMessageChunk for each image in the result (e.g. Read of a PNG)
     fn make_tool_result(
         &self,
         session_id: &SessionId,
@@ -665,12 +778,10 @@ impl NotificationConverter {
             "Processing tool result notification"
         );
 
-        let output = match &tool_result.content {
-            Some(ToolResultContent::Text(text)) => text.clone(),
-            Some(ToolResultContent::Blocks(blocks)) => {
-                serde_json::to_string(blocks).unwrap_or_default()
-            }
-            None => String::new(),
+        let (output, images) = match &tool_result.content {
+            Some(ToolResultContent::Text(text)) => (text.clone(), Vec::new()),
+            Some(ToolResultContent::Blocks(blocks)) => split_image_blocks(blocks),
+            None => (String::new(), Vec::new()),
         };
 
         let is_error = tool_result.is_error.unwrap_or(false);
@@ -700,11 +811,20 @@ impl NotificationConverter {
             session_id.clone(),
             SessionUpdate::ToolCallUpdate(update),
         );
-        let notifications = vec![self.attach_request_id(notification)];
+        let mut notifications = vec![self.attach_request_id(notification)];
+        notifications.extend(
+            images
+                .iter()
+                .map(|image| self.make_image_message(session_id, image)),
+        );
 
         // Note: Plan notification for TodoWrite is now sent at tool_use time
         // (in make_plan_from_todo_write), so we don't send it here anymore.
         // This matches TypeScript behavior: acp-agent.ts lines 1051-1058
+        // A plan file is only read once the write has happened, though.
+        if !is_error && let Some(notification) = self.make_plan_from_plan_file(session_id, &entry) {
+            notifications.push(notification);
+        }
 
         notifications
     }
@@ -714,7 +834,6 @@ impl NotificationConverter {
     /// This is called when we receive a TodoWrite tool_use, to send the Plan
     /// immediately (instead of waiting for tool_result).
     /// Reference: vendors/claude-code-acp/src/acp-agent.ts lines 1051-1058
-    #[allow(clippy::unused_self)]
     fn make_plan_from_todo_write(
         &self,
         session_id: &SessionId,
@@ -723,23 +842,41 @@ impl NotificationConverter {
         // Extract todos from input
         let todos = tool_use.input.get("todos")?.as_array()?;
 
-        let plan_entries: Vec<PlanEntry> = todos
-            .iter()
-            .filter_map(|todo| {
-                let content = todo.get("content")?.as_str()?;
-                let status_str = todo.get("status")?.as_str()?;
-
-                // Convert TodoWrite status to PlanEntryStatus
-                let status = match status_str {
-                    "in_progress" => PlanEntryStatus::InProgress,
-                    "completed" => PlanEntryStatus::Completed,
-                    _ => PlanEntryStatus::Pending,
-                };
-
-                // TodoWrite doesn't have priority, default to Medium
-                Some(PlanEntry::new(content, PlanEntryPriority::Medium, status))
-            })
-            .collect();
+        // Todos without a priority are Medium
+        let plan_entries: Vec<PlanEntry> = if let Some(todo_list) = &self.todo_list
+            && let Ok(items) = serde_json::from_value::<Vec<TodoItem>>(todos.clone().into())
+        {
+            let merge = tool_use
+                .input
+                .get("merge")
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(false);
+            todo_list.apply(items, merge);
+            todo_list
+                .get_all()
+                .into_iter()
+                .map(|item| {
+                    let priority = plan_entry_priority(item.priority());
+                    let status = plan_entry_status(&item.status);
+                    PlanEntry::new(item.content, priority, status)
+                })
+                .collect()
+        } else {
+            todos
+                .iter()
+                .filter_map(|todo| {
+                    let content = todo.get("content")?.as_str()?;
+                    let status = plan_entry_status(todo.get("status")?.as_str()?);
+                    let priority = plan_entry_priority(
+                        todo.get("priority")
+                            .and_then(|priority| priority.as_str())
+                            .map(TodoPriority::parse)
+                            .unwrap_or_default(),
+                    );
+                    Some(PlanEntry::new(content, priority, status))
+                })
+                .collect()
+        };
 
         if plan_entries.is_empty() {
             return None;
@@ -753,9 +890,54 @@ impl NotificationConverter {
         Some(self.attach_request_id(notification))
     }
 
+    /// Make a Plan notification from a write to the current plan file
+    ///
+    /// Called for successful Edit, MultiEdit and Write results. A file in the
+    /// plans directory becomes the session's current plan, and the plan is
+    /// rebuilt from the file's list items.
+    fn make_plan_from_plan_file(
+        &self,
+        session_id: &SessionId,
+        entry: &ToolUseEntry,
+    ) -> Option<SessionNotification> {
+        let current_plan = self.current_plan.as_ref()?;
+        let effective_name = entry.name.strip_prefix("mcp__acp__").unwrap_or(&entry.name);
+        if !matches!(effective_name, "Edit" | "MultiEdit" | "Write")
+            || crate::permissions::is_dry_run(effective_name, &entry.input)
+        {
+            return None;
+        }
+
+        let file_path = entry.input.get("file_path")?.as_str()?;
+        let path = if let Some(rest) = file_path.strip_prefix("~/") {
+            dirs::home_dir()?.join(rest)
+        } else {
+            match &self.cwd {
+                Some(cwd) => cwd.join(file_path),
+                None => std::path::PathBuf::from(file_path),
+            }
+        };
+        if !current_plan.record_write(&path) {
+            return None;
+        }
+
+        // Goes through the tracker, which skips plan files over its size limit
+        let markdown = current_plan.read().ok()??;
+        let plan_entries = plan_entries_from_markdown(&markdown);
+        if plan_entries.is_empty() {
+            return None;
+        }
+
+        let notification = SessionNotification::new(
+            session_id.clone(),
+            SessionUpdate::Plan(Plan::new(plan_entries)),
+        );
+        Some(self.attach_request_id(notification))
+    }
+
     /// Build tool result content based on tool type
     ///
-    /// For Edit/Write tools, returns Diff content.
+    /// For Edit/MultiEdit/Write tools, returns Diff content.
     /// For Read tool, removes SYSTEM_REMINDER and wraps with markdown.
     /// For errors, wraps with markdown code block.
     /// Reference: vendors/claude-code-acp/src/tools.ts toolUpdateFromToolResult
@@ -771,6 +953,24 @@ impl NotificationConverter {
             .strip_prefix("mcp__acp__")
             .unwrap_or(&entry.name);
 
+        // A dry run wrote nothing: show the previewed diff from its output
+        // next to the notice saying so
+        if !is_error && crate::permissions::is_dry_run(effective_name, &entry.input) {
+            let file_path = entry
+                .input
+                .get("file_path")
+                .and_then(|v| v.as_str())
+                .unwrap_or("");
+            let notice = output.lines().next().unwrap_or_default().to_string();
+            return match unified_diff_texts(output) {
+                Some((old_text, new_text)) if !file_path.is_empty() => vec![
+                    ToolCallContent::Diff(self.edit_diff(file_path, &old_text, &new_text)),
+                    notice.into(),
+                ],
+                _ => vec![output.to_string().into()],
+            };
+        }
+
         match effective_name {
             "Edit" if !is_error => {
                 // Extract file_path, old_string, new_string from input
@@ -793,12 +993,46 @@ impl NotificationConverter {
                 if !file_path.is_empty() && !new_string.is_empty() {
                     // Create structured Diff content for Zed to render as visual diff
                     // Reference: vendors/claude-code-acp/src/tools.ts:460-507
-                    let diff = Diff::new(file_path, new_string).old_text(old_string);
+                    let diff = match old_string {
+                        Some(old_string) => self.edit_diff(file_path, &old_string, new_string),
+                        None => Diff::new(file_path, new_string),
+                    };
                     vec![ToolCallContent::Diff(diff)]
                 } else {
                     vec![output.to_string().into()]
                 }
             }
+            "MultiEdit" if !is_error => {
+                // One Diff per edit: the edits' strings are separate hunks,
+                // and joining them would show text the file never held
+                let file_path = entry
+                    .input
+                    .get("file_path")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+                let edits = entry
+                    .input
+                    .get("edits")
+                    .and_then(|v| v.as_array())
+                    .map(Vec::as_slice)
+                    .unwrap_or_default();
+                let diffs: Vec<ToolCallContent> = edits
+                    .iter()
+                    .filter_map(|edit| {
+                        let old_string = edit.get("old_string")?.as_str()?;
+                        let new_string = edit.get("new_string")?.as_str()?;
+                        Some(ToolCallContent::Diff(
+                            self.edit_diff(file_path, old_string, new_string),
+                        ))
+                    })
+                    .collect();
+
+                if file_path.is_empty() || diffs.is_empty() {
+                    vec![output.to_string().into()]
+                } else {
+                    diffs
+                }
+            }
             "Write" if !is_error => {
                 // Extract file_path and content from input
                 let file_path = entry
@@ -820,6 +1054,21 @@ impl NotificationConverter {
                     vec![ToolCallContent::Diff(diff)]
                 }
             }
+            "DiffFiles" if !is_error => {
+                // Show the hunks of the unified diff as a visual diff
+                let new_path = entry
+                    .input
+                    .get("new_path")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("");
+                match unified_diff_texts(output) {
+                    Some((old_text, new_text)) if !new_path.is_empty() => {
+                        let diff = Diff::new(new_path, new_text).old_text(Some(old_text));
+                        vec![ToolCallContent::Diff(diff), output.to_string().into()]
+                    }
+                    _ => vec![output.to_string().into()],
+                }
+            }
             "Read" if !is_error => {
                 // Remove SYSTEM_REMINDER and wrap with markdown
                 // Reference: vendors/claude-code-acp/src/tools.ts:430-431
@@ -840,6 +1089,15 @@ impl NotificationConverter {
         }
     }
 
+    /// Diff content for an edit, limited to the configured context size
+    fn edit_diff(&self, file_path: &str, old_text: &str, new_text: &str) -> Diff {
+        let (old_text, new_text) = match self.diff_context_lines {
+            Some(context_lines) => limit_diff_context(old_text, new_text, context_lines),
+            None => (old_text.to_string(), new_text.to_string()),
+        };
+        Diff::new(file_path, new_text).old_text(Some(old_text))
+    }
+
     /// Build Terminal content for embedding a terminal in tool result
     ///
     /// This is used when a tool (like Bash) uses the Terminal API to execute commands.
@@ -891,6 +1149,77 @@ impl NotificationConverter {
     }
 }
 
+/// Convert a TodoWrite priority to a PlanEntryPriority
+fn plan_entry_priority(priority: TodoPriority) -> PlanEntryPriority {
+    match priority {
+        TodoPriority::High => PlanEntryPriority::High,
+        TodoPriority::Medium => PlanEntryPriority::Medium,
+        TodoPriority::Low => PlanEntryPriority::Low,
+    }
+}
+
+/// Plan entries for the top-level list items of a markdown plan file
+///
+/// `- [x] step` is a completed entry; `- [ ] step`, `- step` and `1. step`
+/// are pending. Nested items are details of a step and code blocks are not
+/// part of the plan, so neither becomes an entry.
+fn plan_entries_from_markdown(markdown: &str) -> Vec<PlanEntry> {
+    let mut entries = Vec::new();
+    let mut in_code_block = false;
+
+    for line in markdown.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+            continue;
+        }
+        if in_code_block {
+            continue;
+        }
+
+        let numbered = line.trim_start_matches(|c: char| c.is_ascii_digit());
+        let item = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|marker| line.strip_prefix(marker))
+            .or_else(|| {
+                (numbered.len() < line.len())
+                    .then(|| {
+                        numbered
+                            .strip_prefix(". ")
+                            .or_else(|| numbered.strip_prefix(") "))
+                    })
+                    .flatten()
+            });
+        let Some(item) = item.map(str::trim) else {
+            continue;
+        };
+
+        let (status, content) = match item
+            .strip_prefix("[x]")
+            .or_else(|| item.strip_prefix("[X]"))
+        {
+            Some(done) => (PlanEntryStatus::Completed, done.trim_start()),
+            None => (
+                PlanEntryStatus::Pending,
+                item.strip_prefix("[ ]").unwrap_or(item).trim_start(),
+            ),
+        };
+        if !content.is_empty() {
+            entries.push(PlanEntry::new(content, PlanEntryPriority::Medium, status));
+        }
+    }
+
+    entries
+}
+
+/// Convert a TodoWrite status to a PlanEntryStatus
+fn plan_entry_status(status: &str) -> PlanEntryStatus {
+    match status {
+        "in_progress" => PlanEntryStatus::InProgress,
+        "completed" => PlanEntryStatus::Completed,
+        _ => PlanEntryStatus::Pending,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -977,6 +1306,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_make_tool_call_synthesizes_bash_description() {
+        let converter = NotificationConverter::new().with_bash_description_synthesis(true);
+        let session_id = SessionId::new("session-1");
+        let input = json!({"command": "RUST_LOG=debug cargo test --workspace"});
+        let tool_use = ToolUseBlock {
+            id: "tool_457".to_string(),
+            name: "Bash".to_string(),
+            input: input.clone(),
+        };
+
+        let notification = converter.make_tool_call(&session_id, &tool_use);
+
+        let SessionUpdate::ToolCall(tool_call) = &notification.update else {
+            panic!("expected a ToolCall update");
+        };
+        assert_eq!(tool_call.title, "cargo …");
+        assert_eq!(tool_call.raw_input, Some(input));
+
+        // A description from the model always wins
+        let tool_use = ToolUseBlock {
+            id: "tool_458".to_string(),
+            name: "Bash".to_string(),
+            input: json!({"command": "/usr/bin/ls", "description": "List files"}),
+        };
+        let notification = converter.make_tool_call(&session_id, &tool_use);
+        if let SessionUpdate::ToolCall(tool_call) = &notification.update {
+            assert_eq!(tool_call.title, "List files");
+        }
+        assert_eq!(
+            synthesize_bash_description("/usr/bin/ls").as_deref(),
+            Some("ls")
+        );
+        assert_eq!(synthesize_bash_description("  "), None);
+    }
+
     #[test]
     fn test_remove_tool_use() {
         let converter = NotificationConverter::new();
@@ -994,6 +1359,130 @@ mod tests {
         assert!(converter.get_tool_use("tool_789").is_none());
     }
 
+    #[test]
+    fn test_multi_edit_result_has_a_diff_per_edit() {
+        let converter = NotificationConverter::new();
+        let entry = ToolUseEntry::new(
+            "tool_1".to_string(),
+            "mcp__acp__MultiEdit".to_string(),
+            json!({
+                "file_path": "/tmp/lib.rs",
+                "edits": [
+                    {"old_string": "fn a()", "new_string": "fn b()"},
+                    {"old_string": "a();", "new_string": "b();", "replace_all": true}
+                ]
+            }),
+        );
+
+        let content = converter.build_tool_result_content(&entry, "Edited", false);
+        assert_eq!(content.len(), 2);
+        let diffs: Vec<_> = content
+            .iter()
+            .map(|content| match content {
+                ToolCallContent::Diff(diff) => diff,
+                other => panic!("expected a diff, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(diffs[0].path, std::path::PathBuf::from("/tmp/lib.rs"));
+        assert_eq!(diffs[0].old_text.as_deref(), Some("fn a()"));
+        assert_eq!(diffs[0].new_text, "fn b()");
+        assert_eq!(diffs[1].path, std::path::PathBuf::from("/tmp/lib.rs"));
+        assert_eq!(diffs[1].old_text.as_deref(), Some("a();"));
+        assert_eq!(diffs[1].new_text, "b();");
+
+        // Failed calls show the error instead
+        let content = converter.build_tool_result_content(&entry, "Edit 2: failed", true);
+        assert!(matches!(content[0], ToolCallContent::Content(_)));
+    }
+
+    #[test]
+    fn test_diff_files_result_includes_diff() {
+        let converter = NotificationConverter::new();
+        let entry = ToolUseEntry::new(
+            "tool_1".to_string(),
+            "mcp__acp__DiffFiles".to_string(),
+            json!({"old_path": "expected.txt", "new_path": "actual.txt"}),
+        );
+        let output = "1 addition, 1 removal\n\n--- expected.txt\n+++ actual.txt\n\
+                      @@ -1,2 +1,2 @@\n one\n-two\n+2\n";
+
+        let content = converter.build_tool_result_content(&entry, output, false);
+        assert_eq!(content.len(), 2);
+        let ToolCallContent::Diff(diff) = &content[0] else {
+            panic!("expected a diff, got {content:?}");
+        };
+        assert_eq!(diff.path, std::path::PathBuf::from("actual.txt"));
+        assert_eq!(diff.old_text.as_deref(), Some("one\ntwo\n"));
+        assert_eq!(diff.new_text, "one\n2\n");
+
+        // Identical files have no hunks to show
+        let content = converter.build_tool_result_content(&entry, "identical", false);
+        assert!(matches!(content[..], [ToolCallContent::Content(_)]));
+    }
+
+    #[test]
+    fn test_dry_run_result_shows_previewed_diff() {
+        let converter = NotificationConverter::new();
+        let entry = ToolUseEntry::new(
+            "tool_1".to_string(),
+            "mcp__acp__Write".to_string(),
+            json!({"file_path": "/tmp/notes.txt", "content": "one\n2\n", "dry_run": true}),
+        );
+        let output = "Preview only, no changes written.\n\n--- /tmp/notes.txt\n\
+                      +++ /tmp/notes.txt\n@@ -1,2 +1,2 @@\n one\n-two\n+2\n";
+
+        // The diff of the file, not of a new file with the given content
+        let content = converter.build_tool_result_content(&entry, output, false);
+        assert_eq!(content.len(), 2);
+        let ToolCallContent::Diff(diff) = &content[0] else {
+            panic!("expected a diff, got {content:?}");
+        };
+        assert_eq!(diff.old_text.as_deref(), Some("one\ntwo\n"));
+        assert_eq!(diff.new_text, "one\n2\n");
+        let ToolCallContent::Content(notice) = &content[1] else {
+            panic!("expected the preview notice, got {content:?}");
+        };
+        assert!(matches!(
+            &notice.content,
+            AcpContentBlock::Text(text) if text.text == "Preview only, no changes written."
+        ));
+    }
+
+    #[test]
+    fn test_diff_context_lines_bound_edit_diff() {
+        let lines: Vec<String> = (0..40).map(|i| format!("line {i}\n")).collect();
+        let old_string = lines.concat();
+        let new_string = old_string.replace("line 20\n", "edited 20\n");
+        let entry = ToolUseEntry::new(
+            "tool_1".to_string(),
+            "Edit".to_string(),
+            json!({
+                "file_path": "/tmp/big.rs",
+                "old_string": old_string,
+                "new_string": new_string
+            }),
+        );
+
+        // Unset: the full texts
+        let content =
+            NotificationConverter::new().build_tool_result_content(&entry, "Edited", false);
+        let ToolCallContent::Diff(diff) = &content[0] else {
+            panic!("expected a diff, got {content:?}");
+        };
+        assert_eq!(diff.new_text.lines().count(), 40);
+
+        let converter = NotificationConverter::new().with_diff_context_lines(Some(1));
+        let content = converter.build_tool_result_content(&entry, "Edited", false);
+        let ToolCallContent::Diff(diff) = &content[0] else {
+            panic!("expected a diff, got {content:?}");
+        };
+        assert_eq!(diff.new_text, "...\nline 19\nedited 20\nline 21\n...\n");
+        assert_eq!(
+            diff.old_text.as_deref(),
+            Some("...\nline 19\nline 20\nline 21\n...\n")
+        );
+    }
+
     #[test]
     fn test_map_tool_kind() {
         assert!(matches!(
@@ -1024,7 +1513,8 @@ mod tests {
                     {
                         "content": "Implement feature",
                         "status": "in_progress",
-                        "activeForm": "Implementing feature"
+                        "activeForm": "Implementing feature",
+                        "priority": "high"
                     },
                     {
                         "content": "Write tests",
@@ -1034,7 +1524,8 @@ mod tests {
                     {
                         "content": "Setup project",
                         "status": "completed",
-                        "activeForm": "Setting up project"
+                        "activeForm": "Setting up project",
+                        "priority": "low"
                     }
                 ]
             }),
@@ -1053,11 +1544,68 @@ mod tests {
             assert_eq!(plan.entries[1].status, PlanEntryStatus::Pending);
             assert_eq!(plan.entries[2].content, "Setup project");
             assert_eq!(plan.entries[2].status, PlanEntryStatus::Completed);
+            // Todos without a priority default to Medium
+            assert_eq!(plan.entries[0].priority, PlanEntryPriority::High);
+            assert_eq!(plan.entries[1].priority, PlanEntryPriority::Medium);
+            assert_eq!(plan.entries[2].priority, PlanEntryPriority::Low);
         } else {
             panic!("Expected Plan update");
         }
     }
 
+    #[test]
+    fn test_make_plan_from_partial_todo_write() {
+        let todo_list = Arc::new(TodoList::new());
+        let converter = NotificationConverter::new().with_todo_list(todo_list.clone());
+        let session_id = SessionId::new("session-1");
+        let todo_write = |input: serde_json::Value| ToolUseBlock {
+            id: "todo_789".to_string(),
+            name: "TodoWrite".to_string(),
+            input,
+        };
+
+        converter.make_plan_from_todo_write(
+            &session_id,
+            &todo_write(json!({
+                "todos": [
+                    {"content": "Task 1", "status": "in_progress", "activeForm": "Doing task 1"},
+                    {"content": "Task 2", "status": "pending", "activeForm": "Doing task 2"}
+                ]
+            })),
+        );
+
+        // Only the changed todo is sent with merge; the plan still lists both
+        let notification = converter
+            .make_plan_from_todo_write(
+                &session_id,
+                &todo_write(json!({
+                    "todos": [{"content": "Task 1", "status": "completed", "priority": "high"}],
+                    "merge": true
+                })),
+            )
+            .unwrap();
+
+        let SessionUpdate::Plan(plan) = &notification.update else {
+            panic!("Expected Plan update");
+        };
+        assert_eq!(plan.entries.len(), 2);
+        assert_eq!(plan.entries[0].content, "Task 1");
+        assert_eq!(plan.entries[0].status, PlanEntryStatus::Completed);
+        assert_eq!(plan.entries[0].priority, PlanEntryPriority::High);
+        assert_eq!(plan.entries[1].content, "Task 2");
+        assert_eq!(plan.entries[1].status, PlanEntryStatus::Pending);
+        assert_eq!(todo_list.get_all()[0].active_form, "Doing task 1");
+
+        // Without merge the list is replaced
+        converter.make_plan_from_todo_write(
+            &session_id,
+            &todo_write(json!({
+                "todos": [{"content": "Task 2", "status": "completed"}]
+            })),
+        );
+        assert_eq!(todo_list.get_all().len(), 1);
+    }
+
     #[test]
     fn test_make_tool_result_todowrite_no_duplicate_plan() {
         // Since Plan is now sent at tool_use time, tool_result should NOT include Plan
@@ -1097,6 +1645,47 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_make_tool_result_emits_images() {
+        let converter = NotificationConverter::new();
+        let session_id = SessionId::new("session-1");
+        converter.cache_tool_use(&ToolUseBlock {
+            id: "read_1".to_string(),
+            name: "mcp__acp__Read".to_string(),
+            input: json!({"file_path": "/tmp/a.png"}),
+        });
+
+        let tool_result: ToolResultBlock = serde_json::from_value(json!({
+            "type": "tool_result",
+            "tool_use_id": "read_1",
+            "content": [
+                {"type": "text", "text": "Image: /tmp/a.png"},
+                {
+                    "type": "image",
+                    "source": {"type": "base64", "media_type": "image/png", "data": "aGVsbG8="}
+                }
+            ]
+        }))
+        .unwrap();
+
+        let notifications = converter.make_tool_result(&session_id, &tool_result);
+        assert_eq!(notifications.len(), 2);
+        let SessionUpdate::ToolCallUpdate(update) = &notifications[0].update else {
+            panic!("expected a ToolCallUpdate");
+        };
+        // The image data isn't repeated in the text output
+        let raw_output = update.fields.raw_output.as_ref().unwrap().to_string();
+        assert!(!raw_output.contains("aGVsbG8="));
+        let SessionUpdate::AgentMessageChunk(chunk) = &notifications[1].update else {
+            panic!("expected an AgentMessageChunk");
+        };
+        let AcpContentBlock::Image(image) = &chunk.content else {
+            panic!("expected an image");
+        };
+        assert_eq!(image.data, "aGVsbG8=");
+        assert_eq!(image.mime_type, "image/png");
+    }
+
     #[test]
     fn test_build_terminal_content() {
         let content = NotificationConverter::build_terminal_content("term-123");
@@ -1248,4 +1837,90 @@ mod tests {
         // Default request_id should be None even with cwd
         assert!(converter.request_id.is_none());
     }
+
+    #[test]
+    fn test_plan_entries_from_markdown() {
+        let entries = plan_entries_from_markdown(
+            "# Plan\n\n- [x] Add the setting\n  - details\n- [ ] Wire it up\n\n```\n- not a step\n```\n2. Write tests\n",
+        );
+        let steps: Vec<_> = entries
+            .iter()
+            .map(|entry| (entry.content.as_str(), entry.status.clone()))
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                ("Add the setting", PlanEntryStatus::Completed),
+                ("Wire it up", PlanEntryStatus::Pending),
+                ("Write tests", PlanEntryStatus::Pending),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_editing_current_plan_updates_plan() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let plans_dir = temp_dir.path().join("plans");
+        std::fs::create_dir(&plans_dir).unwrap();
+        let current_plan = Arc::new(CurrentPlan::with_plans_dir(plans_dir.clone()));
+        let converter = NotificationConverter::with_cwd(temp_dir.path().to_path_buf())
+            .with_current_plan(current_plan.clone());
+        let session_id = SessionId::new("session-1");
+        let plan_file = plans_dir.join("plan.md");
+        std::fs::write(
+            &plan_file,
+            "# Plan\n\n- [x] Read the code\n- [ ] Fix the bug\n",
+        )
+        .unwrap();
+
+        // The model appends a step to its plan
+        converter.cache_tool_use(&ToolUseBlock {
+            id: "edit_plan".to_string(),
+            name: "mcp__acp__Edit".to_string(),
+            input: json!({
+                "file_path": plan_file.to_str().unwrap(),
+                "old_string": "- [ ] Fix the bug\n",
+                "new_string": "- [ ] Fix the bug\n- [ ] Add a test\n"
+            }),
+        });
+        std::fs::write(
+            &plan_file,
+            "# Plan\n\n- [x] Read the code\n- [ ] Fix the bug\n- [ ] Add a test\n",
+        )
+        .unwrap();
+        let notifications = converter.make_tool_result(
+            &session_id,
+            &ToolResultBlock {
+                tool_use_id: "edit_plan".to_string(),
+                content: Some(ToolResultContent::Text("File edited".to_string())),
+                is_error: Some(false),
+            },
+        );
+
+        assert_eq!(current_plan.path(), Some(plan_file));
+        assert_eq!(notifications.len(), 2);
+        let SessionUpdate::Plan(plan) = &notifications[1].update else {
+            panic!("Expected Plan update");
+        };
+        assert_eq!(plan.entries.len(), 3);
+        assert_eq!(plan.entries[0].status, PlanEntryStatus::Completed);
+        assert_eq!(plan.entries[2].content, "Add a test");
+        assert_eq!(plan.entries[2].status, PlanEntryStatus::Pending);
+
+        // Writing other files doesn't touch the plan
+        converter.cache_tool_use(&ToolUseBlock {
+            id: "write_src".to_string(),
+            name: "Write".to_string(),
+            input: json!({"file_path": "src/main.rs", "content": "fn main() {}\n"}),
+        });
+        let notifications = converter.make_tool_result(
+            &session_id,
+            &ToolResultBlock {
+                tool_use_id: "write_src".to_string(),
+                content: Some(ToolResultContent::Text("File written".to_string())),
+                is_error: Some(false),
+            },
+        );
+        assert_eq!(notifications.len(), 1);
+    }
 }
@@ -3,17 +3,102 @@
 //! Converts ACP `PromptRequest` content to Claude SDK `UserContentBlock`s.
 
 use claude_code_agent_sdk::UserContentBlock;
+use serde::{Deserialize, Serialize};
+
+/// Thinking budgets for the "think" trigger phrases of a prompt
+///
+/// Mirrors Claude Code, where asking the model to "think", "think hard",
+/// "think harder" or "ultrathink" raises its thinking budget for that turn.
+/// Settings field: `thinkingTriggers`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ThinkingTriggers {
+    /// Budget for "think"
+    pub think: u32,
+    /// Budget for "think hard", "think deeply" and "megathink"
+    pub think_hard: u32,
+    /// Budget for "think harder", "think intensely" and "think really hard"
+    pub think_harder: u32,
+    /// Budget for "ultrathink"
+    pub ultrathink: u32,
+}
+
+impl Default for ThinkingTriggers {
+    fn default() -> Self {
+        Self {
+            think: 4_000,
+            think_hard: 10_000,
+            think_harder: 20_000,
+            ultrathink: 31_999,
+        }
+    }
+}
+
+impl ThinkingTriggers {
+    /// Thinking budget of the strongest trigger phrase in `text`
+    ///
+    /// Phrases match whole words, case-insensitively. Returns None when the
+    /// text contains no trigger.
+    pub fn budget_for(&self, text: &str) -> Option<u32> {
+        let words: Vec<String> = text
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        let contains = |phrases: &[&[&str]]| {
+            phrases.iter().any(|phrase| {
+                words
+                    .windows(phrase.len())
+                    .any(|window| window.iter().zip(phrase.iter()).all(|(w, p)| w == p))
+            })
+        };
+
+        if contains(&[&["ultrathink"]]) {
+            Some(self.ultrathink)
+        } else if contains(&[
+            &["think", "harder"],
+            &["think", "intensely"],
+            &["think", "really", "hard"],
+        ]) {
+            Some(self.think_harder)
+        } else if contains(&[&["think", "hard"], &["think", "deeply"], &["megathink"]]) {
+            Some(self.think_hard)
+        } else if contains(&[&["think"]]) {
+            Some(self.think)
+        } else {
+            None
+        }
+    }
+}
 
 /// Prompt content converter
 ///
 /// Handles conversion from ACP prompt content types to Claude SDK content blocks.
 #[derive(Debug, Default)]
-pub struct PromptConverter;
+pub struct PromptConverter {
+    /// Trigger phrase budgets (None = trigger phrases are ignored)
+    thinking_triggers: Option<ThinkingTriggers>,
+}
 
 impl PromptConverter {
     /// Create a new prompt converter
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Scale the thinking budget of a turn with trigger phrases in its prompt
+    #[must_use]
+    pub fn with_thinking_triggers(mut self, triggers: Option<ThinkingTriggers>) -> Self {
+        self.thinking_triggers = triggers;
+        self
+    }
+
+    /// Thinking budget requested by the prompt text, overriding the session
+    /// default for this turn
+    ///
+    /// Returns None when trigger phrases are disabled or the prompt has none.
+    pub fn thinking_budget(&self, text: &str) -> Option<u32> {
+        self.thinking_triggers?.budget_for(text)
     }
 
     /// Convert ACP prompt content to SDK user content blocks
@@ -133,6 +218,29 @@ mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn test_thinking_budget_from_trigger_phrases() {
+        let triggers = ThinkingTriggers {
+            think_harder: 24_000,
+            ..ThinkingTriggers::default()
+        };
+        let converter = PromptConverter::new().with_thinking_triggers(Some(triggers));
+
+        assert_eq!(
+            converter.thinking_budget("Please think harder about this race condition"),
+            Some(24_000)
+        );
+        assert_eq!(converter.thinking_budget("Think hard."), Some(10_000));
+        assert_eq!(
+            converter.thinking_budget("ULTRATHINK: redesign the cache"),
+            Some(31_999)
+        );
+        assert_eq!(converter.thinking_budget("rethink the thinker"), None);
+
+        // Off by default
+        assert_eq!(PromptConverter::new().thinking_budget("think harder"), None);
+    }
+
     #[test]
     fn test_convert_text_content() {
         let converter = PromptConverter::new();
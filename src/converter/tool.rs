@@ -159,6 +159,24 @@ pub fn extract_tool_info(name: &str, input: &serde_json::Value, cwd: Option<&Pat
             ToolInfo::new(title, ToolKind::Edit).with_location(path)
         }
 
+        "MultiEdit" => {
+            let path = input
+                .get("file_path")
+                .and_then(|v| v.as_str())
+                .unwrap_or("file");
+            let edits = input
+                .get("edits")
+                .and_then(|v| v.as_array())
+                .map_or(0, Vec::len);
+            let title = format!(
+                "Edit {} ({} edit{})",
+                truncate_path(path, cwd_path),
+                edits,
+                if edits == 1 { "" } else { "s" }
+            );
+            ToolInfo::new(title, ToolKind::Edit).with_location(path)
+        }
+
         "Write" => {
             let path = input
                 .get("file_path")
@@ -168,6 +186,88 @@ pub fn extract_tool_info(name: &str, input: &serde_json::Value, cwd: Option<&Pat
             ToolInfo::new(title, ToolKind::Edit).with_location(path)
         }
 
+        "Touch" => {
+            let path = input
+                .get("file_path")
+                .and_then(|v| v.as_str())
+                .unwrap_or("file");
+            let title = format!("Touch {}", truncate_path(path, cwd_path));
+            ToolInfo::new(title, ToolKind::Edit).with_location(path)
+        }
+
+        "JsonEdit" | "YamlEdit" | "TomlEdit" => {
+            let path = input
+                .get("file_path")
+                .and_then(|v| v.as_str())
+                .unwrap_or("file");
+            let key_path = input
+                .get("path")
+                .and_then(|v| v.as_str())
+                .unwrap_or("value");
+            let title = format!("Set {} in {}", key_path, truncate_path(path, cwd_path));
+            ToolInfo::new(title, ToolKind::Edit).with_location(path)
+        }
+
+        "CargoAdd" => {
+            let path = input
+                .get("manifest_path")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Cargo.toml");
+            let name = input
+                .get("name")
+                .and_then(|v| v.as_str())
+                .unwrap_or("dependency");
+            let title = format!("Add {} to {}", name, truncate_path(path, cwd_path));
+            ToolInfo::new(title, ToolKind::Edit).with_location(path)
+        }
+
+        "CreateDirectory" => {
+            let path = input
+                .get("path")
+                .and_then(|v| v.as_str())
+                .unwrap_or("directory");
+            let title = format!("Create directory {}", truncate_path(path, cwd_path));
+            ToolInfo::new(title, ToolKind::Edit).with_location(path)
+        }
+
+        "Move" => {
+            let source = input
+                .get("source")
+                .and_then(|v| v.as_str())
+                .unwrap_or("file");
+            let destination = input
+                .get("destination")
+                .and_then(|v| v.as_str())
+                .unwrap_or("destination");
+            let title = format!(
+                "Move {} to {}",
+                truncate_path(source, cwd_path),
+                truncate_path(destination, cwd_path)
+            );
+            ToolInfo::new(title, ToolKind::Edit)
+                .with_location(source)
+                .with_location(destination)
+        }
+
+        "Delete" => {
+            let path = input
+                .get("path")
+                .or_else(|| input.get("file_path"))
+                .and_then(|v| v.as_str())
+                .unwrap_or("file");
+            let permanent = input
+                .get("permanent")
+                .and_then(serde_json::Value::as_bool)
+                .unwrap_or(false);
+            let verb = if permanent {
+                "Permanently delete"
+            } else {
+                "Delete"
+            };
+            let title = format!("{} {}", verb, truncate_path(path, cwd_path));
+            ToolInfo::new(title, ToolKind::Edit).with_location(path)
+        }
+
         "Bash" => {
             let cmd = input.get("command").and_then(|v| v.as_str()).unwrap_or("");
 
@@ -210,6 +310,43 @@ pub fn extract_tool_info(name: &str, input: &serde_json::Value, cwd: Option<&Pat
             ToolInfo::new(title, ToolKind::Search)
         }
 
+        "RepoStats" => {
+            let title = match input.get("path").and_then(|v| v.as_str()) {
+                Some(path) => format!("Repository stats for {}", truncate_path(path, cwd_path)),
+                None => "Repository stats".to_string(),
+            };
+            ToolInfo::new(title, ToolKind::Search)
+        }
+
+        "EnvInfo" => ToolInfo::new("Environment info", ToolKind::Read),
+
+        "DiffFiles" => {
+            let old_path = input
+                .get("old_path")
+                .and_then(|v| v.as_str())
+                .unwrap_or("file");
+            let new_path = input
+                .get("new_path")
+                .and_then(|v| v.as_str())
+                .unwrap_or("file");
+            let title = format!(
+                "Diff {} and {}",
+                truncate_path(old_path, cwd_path),
+                truncate_path(new_path, cwd_path)
+            );
+            ToolInfo::new(title, ToolKind::Read)
+                .with_location(old_path)
+                .with_location(new_path)
+        }
+
+        "RecentFiles" => {
+            let title = match input.get("path").and_then(|v| v.as_str()) {
+                Some(path) => format!("Recent files in {}", truncate_path(path, cwd_path)),
+                None => "Recent files".to_string(),
+            };
+            ToolInfo::new(title, ToolKind::Search)
+        }
+
         "BashOutput" => {
             // Reference: vendors/claude-code-acp/src/tools.ts:344
             ToolInfo::new("Tail Logs", ToolKind::Execute)
@@ -220,6 +357,8 @@ pub fn extract_tool_info(name: &str, input: &serde_json::Value, cwd: Option<&Pat
             ToolInfo::new("Kill Process", ToolKind::Execute)
         }
 
+        "ListShells" => ToolInfo::new("List Background Shells", ToolKind::Read),
+
         "WebFetch" => {
             let url = input.get("url").and_then(|v| v.as_str()).unwrap_or("");
             let title = format!("Fetch {}", truncate_string(url, 50));
@@ -293,6 +432,38 @@ pub fn extract_tool_info(name: &str, input: &serde_json::Value, cwd: Option<&Pat
     }
 }
 
+/// Synthesize a short Bash description from the command
+///
+/// Used as the tool call title when the model omits `description`: the
+/// program name (after any leading `VAR=value` assignments), followed by
+/// "…" when the command has more to it. Returns `None` for an empty command.
+pub fn synthesize_bash_description(command: &str) -> Option<String> {
+    let mut words = command
+        .split_whitespace()
+        .skip_while(|word| is_env_assignment(word));
+    let program = words.next()?;
+    let program = program
+        .rsplit('/')
+        .next()
+        .filter(|p| !p.is_empty())
+        .unwrap_or(program);
+
+    if words.next().is_some() || command.contains(['|', ';', '&']) {
+        Some(format!("{} …", program))
+    } else {
+        Some(program.to_string())
+    }
+}
+
+/// Check whether a shell word is a `VAR=value` assignment
+fn is_env_assignment(word: &str) -> bool {
+    word.split_once('=').is_some_and(|(name, _)| {
+        !name.is_empty()
+            && !name.starts_with(|c: char| c.is_ascii_digit())
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    })
+}
+
 /// Truncate a file path for display
 ///
 /// # Arguments
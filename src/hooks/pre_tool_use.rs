@@ -24,9 +24,10 @@ use tokio::sync::RwLock;
 use tracing::Instrument;
 
 use crate::command_safety::{command_might_be_dangerous, is_known_safe_command};
-use crate::session::{PermissionMode, PermissionHandler};
-use crate::settings::PermissionChecker;
-use crate::utils::is_plans_directory_path;
+use crate::session::{
+    AuditEntry, AuditSource, PermissionAuditLog, PermissionHandler, PermissionMode,
+};
+use crate::settings::{PermissionCheckResult, PermissionChecker};
 
 /// Creates a PreToolUse hook that checks permissions using settings rules and permission mode.
 ///
@@ -46,7 +47,8 @@ use crate::utils::is_plans_directory_path;
 ///   (AcceptEdits behaves like BypassPermissions for root compatibility)
 /// - **Plan**: Blocks write operations (Edit, Write, Bash, NotebookEdit)
 /// - **Default**: Auto-allows read-only operations (Read, Grep, Glob, LS, NotebookRead),
-///   checks settings rules for other tools
+///   and with `auto_approve_within_cwd` Edit/Write/MultiEdit of files inside the
+///   working directory; checks settings rules for other tools
 /// - **DontAsk**: Checks settings rules and mode-based auto-approval
 ///
 /// # Architecture
@@ -66,6 +68,8 @@ use crate::utils::is_plans_directory_path;
 /// * `permission` - Shared permission handler (contains mode that can be updated at runtime)
 /// * `permission_cache` - Cache for storing permission results (for can_use_tool callback)
 /// * `tool_use_id_cache` - Cache for storing tool_use_id (for can_use_tool callback)
+/// * `auto_approve_within_cwd` - Auto-allow file calls inside the working directory in Default mode
+/// * `audit_log` - Optional log the hook's permission decisions are appended to
 ///
 /// # Returns
 ///
@@ -77,6 +81,8 @@ pub fn create_pre_tool_use_hook(
     permission: Arc<RwLock<PermissionHandler>>,
     permission_cache: Arc<DashMap<String, bool>>,
     tool_use_id_cache: Arc<DashMap<String, String>>,
+    auto_approve_within_cwd: bool,
+    audit_log: Option<Arc<PermissionAuditLog>>,
 ) -> HookCallback {
     Arc::new(
         move |input: HookInput, tool_use_id: Option<String>, _context: HookContext| {
@@ -87,6 +93,7 @@ pub fn create_pre_tool_use_hook(
             let session_id = session_id.clone();
             let _permission_cache = Arc::clone(&permission_cache);
             let tool_use_id_cache = Arc::clone(&tool_use_id_cache);
+            let audit_log = audit_log.clone();
 
             // Extract tool name early for span naming
             let (tool_name, is_pre_tool) = match &input {
@@ -132,6 +139,20 @@ pub fn create_pre_tool_use_hook(
                         "PreToolUse hook triggered"
                     );
 
+                    // Record a decision in the audit log, if one is configured
+                    let audit = |decision: &str, rule: Option<&str>, source: AuditSource| {
+                        if let Some(audit_log) = &audit_log {
+                            audit_log.record(&AuditEntry::new(
+                                &session_id,
+                                &tool_name,
+                                decision,
+                                rule,
+                                source,
+                                &tool_input,
+                            ));
+                        }
+                    };
+
                     // IMPORTANT: ExitPlanMode is handled specially by canUseTool callback
                     // We skip all permission checks here to avoid double permission prompts
                     // The canUseTool callback will handle the permission dialog
@@ -157,15 +178,32 @@ pub fn create_pre_tool_use_hook(
                         });
                     }
 
-                    // Get current permission mode
-                    let mode = permission.read().await.mode();
+                    // Get current permission mode, and the decision of a custom
+                    // mode's rules, which comes before the built-in mode behavior
+                    let (mode, custom_decision) = {
+                        let handler = permission.read().await;
+                        (handler.mode(), handler.custom_decision(&tool_name, &tool_input))
+                    };
+                    let mode_applies = custom_decision.is_none();
+
+                    // Calls such as permanent deletes always go to the user, so they
+                    // skip the mode auto-approval and allow rules below
+                    let explicit_approval =
+                        crate::permissions::requires_explicit_approval(&tool_name, &tool_input);
+
+                    // Edit and Write dry runs only preview a change, so they are
+                    // approved like read-only tools
+                    let dry_run = crate::permissions::is_dry_run(&tool_name, &tool_input);
 
                     // BypassPermissions and AcceptEdits modes allow everything
                     // (AcceptEdits behaves like BypassPermissions for root compatibility)
-                    if matches!(
-                        mode,
-                        PermissionMode::BypassPermissions | PermissionMode::AcceptEdits
-                    ) {
+                    if !explicit_approval
+                        && mode_applies
+                        && matches!(
+                            mode,
+                            PermissionMode::BypassPermissions | PermissionMode::AcceptEdits
+                        )
+                    {
                         let elapsed = start_time.elapsed();
                         let mode_str = match mode {
                             PermissionMode::BypassPermissions => "BypassPermissions",
@@ -179,6 +217,7 @@ pub fn create_pre_tool_use_hook(
                             elapsed_us = elapsed.as_micros(),
                             "Tool allowed by permission mode (auto-approve all)"
                         );
+                        audit("allow", Some(mode.as_str()), AuditSource::Mode);
 
                         return HookJsonOutput::Sync(SyncHookJsonOutput {
                             continue_: Some(true),
@@ -198,11 +237,11 @@ pub fn create_pre_tool_use_hook(
 
                     // Default mode: auto-allow read-only operations
                     // This allows tools like Read, Grep, Glob, LS, NotebookRead to execute without permission prompt
-                    if mode == PermissionMode::Default {
+                    if mode_applies && mode == PermissionMode::Default {
                         let is_read_only = matches!(
                             stripped_tool_name,
                             "Read" | "Grep" | "Glob" | "LS" | "NotebookRead"
-                        );
+                        ) || dry_run;
                         if is_read_only {
                             let elapsed = start_time.elapsed();
                             tracing::debug!(
@@ -212,6 +251,7 @@ pub fn create_pre_tool_use_hook(
                                 elapsed_us = elapsed.as_micros(),
                                 "Tool auto-allowed in Default mode (read-only operation)"
                             );
+                            audit("allow", Some("default: read-only"), AuditSource::Mode);
                             return HookJsonOutput::Sync(SyncHookJsonOutput {
                                 continue_: Some(true),
                                 hook_specific_output: Some(HookSpecificOutput::PreToolUse(
@@ -228,6 +268,45 @@ pub fn create_pre_tool_use_hook(
                             });
                         }
 
+                        // Files inside the working directory, unless a settings rule
+                        // decides the call
+                        if auto_approve_within_cwd
+                            && !explicit_approval
+                            && let Some(checker) = &permission_checker
+                        {
+                            let checker = checker.read().await;
+                            if crate::permissions::is_within_cwd(&tool_name, &tool_input, checker.cwd())
+                                && checker.check_permission(&tool_name, &tool_input).rule.is_none()
+                            {
+                                tracing::debug!(
+                                    tool_name = %tool_name,
+                                    tool_use_id = ?tool_use_id,
+                                    mode = "default",
+                                    elapsed_us = start_time.elapsed().as_micros(),
+                                    "Tool auto-allowed in Default mode (file inside working directory)"
+                                );
+                                audit(
+                                    "allow",
+                                    Some("default: inside the working directory"),
+                                    AuditSource::Mode,
+                                );
+                                return HookJsonOutput::Sync(SyncHookJsonOutput {
+                                    continue_: Some(true),
+                                    hook_specific_output: Some(HookSpecificOutput::PreToolUse(
+                                        PreToolUseHookSpecificOutput {
+                                            permission_decision: Some("allow".to_string()),
+                                            permission_decision_reason: Some(
+                                                "Auto-allowed in Default mode (file inside the working directory)"
+                                                    .to_string(),
+                                            ),
+                                            updated_input: None,
+                                        },
+                                    )),
+                                    ..Default::default()
+                                });
+                            }
+                        }
+
                         // Check Bash commands for known safe commands (auto-allow)
                         if stripped_tool_name == "Bash" {
                             if let Some(cmd) = tool_input.get("command").and_then(|v| v.as_str()) {
@@ -242,6 +321,11 @@ pub fn create_pre_tool_use_hook(
                                         elapsed_us = elapsed.as_micros(),
                                         "Bash command auto-allowed (known safe command)"
                                     );
+                                    audit(
+                                        "allow",
+                                        Some("default: known safe command"),
+                                        AuditSource::Mode,
+                                    );
                                     return HookJsonOutput::Sync(SyncHookJsonOutput {
                                         continue_: Some(true),
                                         hook_specific_output: Some(HookSpecificOutput::PreToolUse(
@@ -273,21 +357,35 @@ pub fn create_pre_tool_use_hook(
                     }
 
                     // Plan mode: Block write operations EXCEPT for plan files
-                    if mode == PermissionMode::Plan {
+                    if mode_applies && mode == PermissionMode::Plan {
                         let is_write_operation = matches!(
                             stripped_tool_name,
-                            "Edit" | "Write" | "Bash" | "NotebookEdit"
-                        );
+                            "Edit"
+                                | "MultiEdit"
+                                | "JsonEdit"
+                                | "YamlEdit"
+                                | "TomlEdit"
+                                | "CargoAdd"
+                                | "Write"
+                                | "Touch"
+                                | "CreateDirectory"
+                                | "Delete"
+                                | "Move"
+                                | "Bash"
+                                | "NotebookEdit"
+                        ) && !dry_run;
 
                         if is_write_operation {
                             // For file operations, check if writing to plans directory
-                            let is_plan_file = if matches!(stripped_tool_name, "Edit" | "Write" | "NotebookEdit") {
-                                tool_input
+                            let is_plan_file = if matches!(stripped_tool_name, "Edit" | "MultiEdit" | "Write" | "Touch" | "NotebookEdit") {
+                                match tool_input
                                     .get("file_path")
                                     .or_else(|| tool_input.get("path"))
                                     .and_then(|v| v.as_str())
-                                    .map(is_plans_directory_path)
-                                    .unwrap_or(false)
+                                {
+                                    Some(path) => permission.read().await.is_plan_file(path),
+                                    None => false,
+                                }
                             } else {
                                 // Bash is never allowed in Plan mode
                                 false
@@ -295,7 +393,7 @@ pub fn create_pre_tool_use_hook(
 
                             if !is_plan_file {
                                 let reason = format!(
-                                    "Tool {} is not allowed in Plan mode (only read operations and writing to ~/.claude/plans/ are allowed)",
+                                    "Tool {} is not allowed in Plan mode (only read operations and writing to the plans directory are allowed)",
                                     stripped_tool_name
                                 );
                                 tracing::warn!(
@@ -305,12 +403,14 @@ pub fn create_pre_tool_use_hook(
                                     elapsed_us = start_time.elapsed().as_micros(),
                                     "Tool blocked by Plan mode"
                                 );
+                                audit("deny", Some("plan"), AuditSource::Mode);
                                 return create_deny_response(
                                     &connection_cx_lock,
                                     &session_id,
                                     tool_use_id.as_ref(),
                                     &tool_name,
                                     reason,
+                                    None,
                                 );
                             }
 
@@ -326,8 +426,9 @@ pub fn create_pre_tool_use_hook(
                         let is_read_only = matches!(
                             stripped_tool_name,
                             "Read" | "Grep" | "Glob" | "LS" | "NotebookRead"
-                        );
+                        ) || dry_run;
                         if is_read_only {
+                            audit("allow", Some("plan: read-only"), AuditSource::Mode);
                             return HookJsonOutput::Sync(SyncHookJsonOutput {
                                 continue_: Some(true),
                                 hook_specific_output: Some(HookSpecificOutput::PreToolUse(
@@ -345,7 +446,7 @@ pub fn create_pre_tool_use_hook(
                     }
 
                     // Check permission (if checker is available, otherwise default to Ask)
-                    let permission_check = if let Some(checker) = &permission_checker {
+                    let settings_check = if let Some(checker) = &permission_checker {
                         let checker = checker.read().await;
                         checker.check_permission(&tool_name, &tool_input)
                     } else {
@@ -354,7 +455,18 @@ pub fn create_pre_tool_use_hook(
                             decision: crate::settings::PermissionDecision::Ask,
                             rule: None,
                             source: None,
+                            origin: None,
+                        }
+                    };
+                    // Settings deny rules still win over a custom mode's rules
+                    let permission_check = match custom_decision {
+                        Some(custom)
+                            if settings_check.decision
+                                != crate::settings::PermissionDecision::Deny =>
+                        {
+                            custom
                         }
+                        _ => settings_check,
                     };
                     let elapsed = start_time.elapsed();
 
@@ -378,11 +490,17 @@ pub fn create_pre_tool_use_hook(
                         "Permission check completed"
                     );
 
+                    audit(
+                        permission_check.decision.as_str(),
+                        permission_check.rule.as_deref(),
+                        AuditSource::Rules,
+                    );
+
                     // 根据权限决策返回相应的 Hook 输出
                     // SDK 已修改为在 mcp_message 处理中调用 can_use_tool 回调，
                     // 因此 Ask 决策会由 SDK 层处理，Hook 只需要返回 continue_: true
                     match permission_check.decision {
-                        crate::settings::PermissionDecision::Allow => {
+                        crate::settings::PermissionDecision::Allow if !explicit_approval => {
                             tracing::debug!(
                                 tool_name = %tool_name,
                                 rule = ?permission_check.rule,
@@ -406,26 +524,29 @@ pub fn create_pre_tool_use_hook(
                                 rule = ?permission_check.rule,
                                 "Tool execution denied by rule"
                             );
-                            let reason = permission_check.rule.unwrap_or_else(|| {
-                                // Use stripped tool name, or fall back to original, or a default
-                                let display_name = if !stripped_tool_name.is_empty() {
-                                    stripped_tool_name
-                                } else if !tool_name.is_empty() {
-                                    tool_name.as_str()
-                                } else {
-                                    "the requested tool" // Fallback if both are empty
-                                };
-                                format!("Tool {} denied by permission settings", display_name)
-                            });
+                            // Use stripped tool name, or fall back to original, or a default
+                            let display_name = if !stripped_tool_name.is_empty() {
+                                stripped_tool_name
+                            } else if !tool_name.is_empty() {
+                                tool_name.as_str()
+                            } else {
+                                "the requested tool" // Fallback if both are empty
+                            };
+                            let reason = permission_check.explain().map_or_else(
+                                || format!("Tool {} denied by permission settings", display_name),
+                                |rule| format!("{} matches {}", display_name, rule),
+                            );
                             create_deny_response(
                                 &connection_cx_lock,
                                 &session_id,
                                 tool_use_id.as_ref(),
                                 &tool_name,
                                 reason,
+                                Some(&permission_check),
                             )
                         }
-                        crate::settings::PermissionDecision::Ask => {
+                        crate::settings::PermissionDecision::Allow
+                        | crate::settings::PermissionDecision::Ask => {
                             // Following TypeScript version's design:
                             // For "ask" decisions, we just return { continue: true } to let the
                             // normal permission flow continue. The actual permission request
@@ -482,6 +603,7 @@ pub fn create_pre_tool_use_hook(
 /// * `tool_use_id` - The tool use ID to correlate with the tool_use notification
 /// * `tool_name` - The name of the tool that was denied
 /// * `reason` - The reason for the denial
+/// * `check` - The permission check that denied the tool, if a rule did
 ///
 /// # Note
 ///
@@ -495,6 +617,7 @@ fn send_denied_tool_result(
     tool_use_id: &str,
     tool_name: &str,
     reason: &str,
+    check: Option<&PermissionCheckResult>,
 ) {
     let Some(connection_cx) = connection_cx_lock.get() else {
         tracing::warn!(
@@ -512,11 +635,17 @@ fn send_denied_tool_result(
     let error_content = format!("Tool execution denied: {}", reason);
     let content: Vec<ToolCallContent> = vec![format!("```\n{}\n```", error_content).into()];
 
-    // Build raw_output JSON
-    let raw_output = serde_json::json!({
+    // Build raw_output JSON, with the rule that denied the tool so clients
+    // can point users at the setting to change
+    let mut raw_output = serde_json::json!({
         "content": error_content,
         "is_error": true
     });
+    if let Some(check) = check {
+        raw_output["rule"] = check.rule.clone().into();
+        raw_output["source"] = check.source.clone().into();
+        raw_output["origin"] = check.origin.clone().into();
+    }
 
     // Create tool result notification with Failed status
     let update_fields = ToolCallUpdateFields::new()
@@ -562,6 +691,7 @@ fn send_denied_tool_result(
 /// * `tool_use_id` - Optional tool use ID
 /// * `tool_name` - The name of the tool that was denied
 /// * `reason` - The reason for the denial
+/// * `check` - The permission check that denied the tool, if a rule did
 ///
 /// # Returns
 ///
@@ -572,6 +702,7 @@ fn create_deny_response(
     tool_use_id: Option<&String>,
     tool_name: &str,
     reason: String,
+    check: Option<&PermissionCheckResult>,
 ) -> HookJsonOutput {
     // Send tool_result notification to client so Zed doesn't show "Tool call not found"
     // Note: send_notification is non-blocking (uses unbounded_send)
@@ -582,6 +713,7 @@ fn create_deny_response(
             tuid,
             tool_name,
             &reason,
+            check,
         );
     }
 
@@ -602,6 +734,7 @@ fn create_deny_response(
 mod tests {
     use super::*;
     use crate::settings::{PermissionSettings, Settings};
+    use crate::utils::is_plans_directory_path;
     use serde_json::json;
 
     fn make_permission_checker(permissions: PermissionSettings) -> Arc<RwLock<PermissionChecker>> {
@@ -633,13 +766,15 @@ mod tests {
             Arc::new(RwLock::new(permission)),
             permission_cache,
             tool_use_id_cache,
+            false,
+            None,
         )
     }
 
     #[tokio::test]
     async fn test_pre_tool_use_hook_allow() {
         let checker = make_permission_checker(PermissionSettings {
-            allow: Some(vec!["Read".to_string()]),
+            allow: Some(vec!["Read".into()]),
             ..Default::default()
         });
 
@@ -673,7 +808,7 @@ mod tests {
     // #[tokio::test]
     // async fn test_pre_tool_use_hook_deny() {
     //     let checker = make_permission_checker(PermissionSettings {
-    //         deny: Some(vec!["Bash".to_string()]),
+    //         deny: Some(vec!["Bash".into()]),
     //         ..Default::default()
     //     });
     //
@@ -792,7 +927,7 @@ mod tests {
     async fn test_bypass_permissions_mode_allows_everything() {
         // BypassPermissions mode should allow all tools without checking rules
         let checker = make_permission_checker(PermissionSettings {
-            deny: Some(vec!["Bash".to_string()]),
+            deny: Some(vec!["Bash".into()]),
             ..Default::default()
         });
 
@@ -832,7 +967,7 @@ mod tests {
     async fn test_default_mode_respects_settings_rules() {
         // Default mode should respect settings rules
         let checker = make_permission_checker(PermissionSettings {
-            allow: Some(vec!["Read".to_string()]),
+            allow: Some(vec!["Read".into()]),
             ..Default::default()
         });
 
@@ -1068,6 +1203,133 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_default_mode_auto_allows_files_within_cwd() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cwd = dir.path().join("project");
+        std::fs::create_dir_all(cwd.join("src")).unwrap();
+        let permissions: PermissionSettings = serde_json::from_value(json!({
+            "ask": [{"rule": "Edit", "paths": [".github/**"]}]
+        }))
+        .unwrap();
+        let settings = Settings {
+            permissions: Some(permissions),
+            ..Default::default()
+        };
+        let checker = Arc::new(RwLock::new(PermissionChecker::new(settings, &cwd)));
+        let hook = create_pre_tool_use_hook(
+            Arc::new(OnceLock::new()),
+            "test-session".to_string(),
+            Some(checker),
+            Arc::new(RwLock::new(PermissionHandler::with_mode(
+                PermissionMode::Default,
+            ))),
+            Arc::new(DashMap::new()),
+            Arc::new(DashMap::new()),
+            true,
+            None,
+        );
+
+        let decision = |tool_name: &str, file_path: &str| {
+            let input = HookInput::PreToolUse(claude_code_agent_sdk::PreToolUseHookInput {
+                session_id: "test".to_string(),
+                transcript_path: "/tmp/test".to_string(),
+                cwd: "/tmp".to_string(),
+                permission_mode: None,
+                tool_name: tool_name.to_string(),
+                tool_input: json!({"file_path": file_path, "content": "x"}),
+            });
+            let result = hook(input, None, HookContext::default());
+            async move {
+                match result.await {
+                    HookJsonOutput::Sync(output) => match output.hook_specific_output {
+                        Some(HookSpecificOutput::PreToolUse(specific)) => {
+                            specific.permission_decision
+                        }
+                        _ => None,
+                    },
+                    HookJsonOutput::Async(_) => panic!("Expected sync output"),
+                }
+            }
+        };
+
+        let allow = Some("allow".to_string());
+        assert_eq!(decision("Edit", "src/lib.rs").await, allow);
+        assert_eq!(decision("mcp__acp__Write", "src/new.rs").await, allow);
+        // Outside the working directory, or decided by a rule: ask
+        assert_eq!(decision("Write", "../outside.txt").await, None);
+        assert_eq!(decision("Edit", ".github/ci.yml").await, None);
+        assert_eq!(decision("Delete", "src/lib.rs").await, None);
+    }
+
+    #[tokio::test]
+    async fn test_rule_decisions_are_audited() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let audit_log = Arc::new(PermissionAuditLog::new(dir.path().join("audit.jsonl")));
+        let checker = make_permission_checker(PermissionSettings {
+            deny: Some(vec!["Bash(rm:*)".into()]),
+            ..Default::default()
+        });
+        let hook = create_pre_tool_use_hook(
+            Arc::new(OnceLock::new()),
+            "test-session".to_string(),
+            Some(checker),
+            Arc::new(RwLock::new(PermissionHandler::with_mode(
+                PermissionMode::Default,
+            ))),
+            Arc::new(DashMap::new()),
+            Arc::new(DashMap::new()),
+            false,
+            Some(Arc::clone(&audit_log)),
+        );
+
+        let tool_input = json!({"command": "rm -rf target"});
+        let input = HookInput::PreToolUse(claude_code_agent_sdk::PreToolUseHookInput {
+            session_id: "test".to_string(),
+            transcript_path: "/tmp/test".to_string(),
+            cwd: "/tmp".to_string(),
+            permission_mode: None,
+            tool_name: "Bash".to_string(),
+            tool_input: tool_input.clone(),
+        });
+        hook(input, None, HookContext::default()).await;
+
+        let content = std::fs::read_to_string(audit_log.path()).unwrap();
+        let entry: serde_json::Value = serde_json::from_str(content.trim()).unwrap();
+        assert_eq!(entry["session_id"], "test-session");
+        assert_eq!(entry["tool"], "Bash");
+        assert_eq!(entry["decision"], "deny");
+        assert_eq!(entry["rule"], "Bash(rm:*)");
+        assert_eq!(entry["source"], "rules");
+        assert_eq!(
+            entry["input_digest"],
+            crate::session::input_digest(&tool_input)
+        );
+        assert!(!content.contains("rm -rf"));
+
+        // Calls the mode approves are logged too
+        let input = HookInput::PreToolUse(claude_code_agent_sdk::PreToolUseHookInput {
+            session_id: "test".to_string(),
+            transcript_path: "/tmp/test".to_string(),
+            cwd: "/tmp".to_string(),
+            permission_mode: None,
+            tool_name: "Read".to_string(),
+            tool_input: json!({"file_path": "/tmp/notes.txt"}),
+        });
+        hook(input, None, HookContext::default()).await;
+
+        let content = std::fs::read_to_string(audit_log.path()).unwrap();
+        let entries: Vec<serde_json::Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1]["tool"], "Read");
+        assert_eq!(entries[1]["decision"], "allow");
+        assert_eq!(entries[1]["rule"], "default: read-only");
+        assert_eq!(entries[1]["source"], "mode");
+    }
+
     #[tokio::test]
     async fn test_create_deny_response_without_tool_use_id() {
         // Test that create_deny_response handles missing tool_use_id gracefully
@@ -1084,6 +1346,8 @@ mod tests {
             Arc::new(RwLock::new(permission)),
             permission_cache,
             tool_use_id_cache,
+            false,
+            None,
         );
 
         // Test with no tool_use_id - should not panic
@@ -1204,6 +1468,39 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_plan_mode_allows_dry_runs() {
+        // Previews write nothing, so Plan mode allows them anywhere
+        let checker = make_permission_checker(PermissionSettings::default());
+        let hook = make_test_hook_with_mode(checker, PermissionMode::Plan);
+
+        let input = HookInput::PreToolUse(claude_code_agent_sdk::PreToolUseHookInput {
+            session_id: "test".to_string(),
+            transcript_path: "/tmp/test".to_string(),
+            cwd: "/tmp".to_string(),
+            permission_mode: None,
+            tool_name: "mcp__acp__Write".to_string(),
+            tool_input: json!({
+                "file_path": "/tmp/test.txt",
+                "content": "test",
+                "dry_run": true
+            }),
+        });
+
+        let result = hook(input, None, HookContext::default()).await;
+
+        match result {
+            HookJsonOutput::Sync(output) => {
+                let Some(HookSpecificOutput::PreToolUse(specific)) = output.hook_specific_output
+                else {
+                    panic!("Expected PreToolUse output");
+                };
+                assert_eq!(specific.permission_decision, Some("allow".to_string()));
+            }
+            HookJsonOutput::Async(_) => panic!("Expected sync output"),
+        }
+    }
+
     #[tokio::test]
     async fn test_plan_mode_blocks_bash() {
         // Plan mode should block Bash commands even in plans directory
@@ -1281,6 +1578,51 @@ mod tests {
         assert!(!is_plans_directory_path("~/../.claude/plans/plan.md"));
     }
 
+    #[tokio::test]
+    async fn test_denied_reason_names_rule_and_file() {
+        let mut permissions = PermissionSettings {
+            deny: Some(vec!["Bash(rm:*)".into()]),
+            ..Default::default()
+        };
+        permissions.set_rule_file(std::path::Path::new("/repo/.claude/settings.json"));
+        let checker = make_permission_checker(permissions);
+        checker.write().await.add_deny_rule("Bash(curl:*)");
+        let hook = make_test_hook(checker);
+
+        let deny_reason = |command: &str| {
+            let input = HookInput::PreToolUse(claude_code_agent_sdk::PreToolUseHookInput {
+                session_id: "test".to_string(),
+                transcript_path: "/tmp/test".to_string(),
+                cwd: "/tmp".to_string(),
+                permission_mode: None,
+                tool_name: "Bash".to_string(),
+                tool_input: json!({"command": command}),
+            });
+            let result = hook(input, None, HookContext::default());
+            async move {
+                match result.await {
+                    HookJsonOutput::Sync(SyncHookJsonOutput {
+                        hook_specific_output: Some(HookSpecificOutput::PreToolUse(specific)),
+                        ..
+                    }) => {
+                        assert_eq!(specific.permission_decision, Some("deny".to_string()));
+                        specific.permission_decision_reason.unwrap()
+                    }
+                    _ => panic!("Expected PreToolUse specific output"),
+                }
+            }
+        };
+
+        assert_eq!(
+            deny_reason("rm -rf target").await,
+            "Bash matches deny rule `Bash(rm:*)` in /repo/.claude/settings.json"
+        );
+        assert_eq!(
+            deny_reason("curl example.com").await,
+            "Bash matches deny rule `Bash(curl:*)` in session"
+        );
+    }
+
     #[tokio::test]
     async fn test_plan_mode_allows_edit_in_plans_dir() {
         // Plan mode should allow Edit operations in plans directory
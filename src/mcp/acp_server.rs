@@ -6,6 +6,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::OnceLock;
+use std::sync::RwLock as StdRwLock;
 use std::time::Instant;
 
 use async_trait::async_trait;
@@ -18,17 +19,47 @@ use sacp::JrConnectionCx;
 use sacp::link::AgentToClient;
 use sacp::schema::{
     Meta, SessionId, SessionNotification, SessionUpdate, Terminal, ToolCall, ToolCallContent,
-    ToolCallId, ToolCallStatus, ToolCallUpdate, ToolCallUpdateFields, ToolKind,
+    ToolCallId, ToolCallLocation as AcpToolCallLocation, ToolCallStatus, ToolCallUpdate,
+    ToolCallUpdateFields, ToolKind,
 };
 use serde_json::Value;
+use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::sync::{Mutex, RwLock};
 use tracing::instrument;
 
-use super::registry::{ToolContext, ToolResult};
+use super::interactive::{
+    InputAction, InteractiveInputPolicy, OutputActivity, wait_with_input_detection,
+};
+use super::registry::{ToolContext, ToolResult, ToolTimeouts};
 use super::server::McpServer;
-use crate::session::BackgroundProcessManager;
+use super::tools::{
+    BashTool, DomainFilter, EnvInfoConfig, FileEncodings, FinalNewlinePolicy, check_command_env,
+    parse_test_output, resolve_command_cwd, unexpected_exit_message,
+};
+use crate::session::{
+    BackgroundProcessManager, BackgroundTerminal, ChildHandle, OutputBuffer, TerminalExitStatus,
+};
 use crate::settings::PermissionChecker;
 use crate::terminal::TerminalClient;
+use crate::utils::truncate_middle;
+
+/// Default limit on the serialized size of a tool call's arguments (8MB)
+pub const DEFAULT_MAX_ARGUMENT_BYTES: usize = 8 * 1024 * 1024;
+
+/// Default limit on the Bash output returned to the model (100KB)
+pub const DEFAULT_BASH_OUTPUT_LIMIT: usize = 100 * 1024;
+
+/// Default share of a Bash timeout after which the client is warned (80%)
+pub const DEFAULT_BASH_TIMEOUT_WARNING_PERCENT: u8 = 80;
+
+/// Bytes read from a Bash command's stdout or stderr at a time
+const OUTPUT_CHUNK_SIZE: usize = 8192;
+
+/// Bash timeout for calls without one when none is configured (2 minutes)
+const DEFAULT_BASH_TIMEOUT_MS: u64 = 120_000;
+
+/// Cap on the timeout a Bash call may request (10 minutes)
+const MAX_BASH_TIMEOUT_MS: u64 = 600_000;
 
 /// Type alias for the cancel callback to reduce type complexity
 type CancelCallback = Arc<Mutex<Option<Box<dyn Fn() + Send + Sync>>>>;
@@ -44,7 +75,7 @@ type CancelCallback = Arc<Mutex<Option<Box<dyn Fn() + Send + Sync>>>>;
 /// # Lock Ordering (Critical for Deadlock Prevention)
 ///
 /// To prevent deadlocks, follow this lock acquisition order:
-/// 1. OnceLock values (cwd) - lock-free after initialization
+/// 1. cwd (std RwLock) - only held to clone or replace the path, never across await
 /// 2. cancel_callback (Mutex) - use try_lock for non-critical access
 /// 3. OnceLock values (session_id, connection_cx, etc.) - lock-free
 ///
@@ -72,16 +103,50 @@ pub struct AcpMcpServer {
     terminal_client: OnceLock<Arc<TerminalClient>>,
     /// Background process manager (set once at initialization)
     background_processes: OnceLock<Arc<BackgroundProcessManager>>,
-    /// Working directory (set once at initialization)
+    /// Working directory (set at initialization, changed by `session/setCwd`)
     ///
-    /// Uses OnceLock instead of RwLock because:
-    /// - cwd is set exactly once during session initialization
-    /// - All tools only read cwd (no tool ever modifies it)
-    /// - OnceLock provides lock-free reads after initialization
-    /// - No deadlock risk
-    cwd: OnceLock<std::path::PathBuf>,
+    /// Uses a std RwLock rather than the tokio one: the guard is only held to
+    /// clone or replace the path and never across an await, so there is no
+    /// deadlock risk.
+    cwd: StdRwLock<Option<std::path::PathBuf>>,
     /// Permission checker for tool-level permission checks
     permission_checker: OnceLock<Arc<RwLock<PermissionChecker>>>,
+    /// Handling of Bash commands waiting for input (set once at initialization)
+    interactive_input: OnceLock<InteractiveInputPolicy>,
+    /// Maximum characters per line of tool output (set once at initialization)
+    max_line_length: OnceLock<usize>,
+    /// Maximum lines returned by Read without a range (set once at initialization)
+    max_read_lines: OnceLock<usize>,
+    /// Largest file Read reads and Write writes (set once at initialization)
+    max_file_size: OnceLock<u64>,
+    /// Client-provided environment for Bash commands (set once at initialization)
+    env: OnceLock<HashMap<String, String>>,
+    /// Execution time limits of built-in tools (set once at initialization)
+    tool_timeouts: OnceLock<ToolTimeouts>,
+    /// Maximum serialized size of tool arguments (set once at initialization)
+    max_argument_bytes: OnceLock<usize>,
+    /// Maximum Bash output returned to the model (set once at initialization)
+    bash_output_limit: OnceLock<usize>,
+    /// Percentage of a Bash timeout before warning (set once at initialization)
+    bash_timeout_warning_percent: OnceLock<u8>,
+    /// Whether Bash merges stdout and stderr (set once at initialization)
+    combine_streams: OnceLock<bool>,
+    /// Whether Write and Edit verify written files (set once at initialization)
+    verify_writes: OnceLock<bool>,
+    /// Whether Grep uses ripgrep when available (set once at initialization)
+    prefer_ripgrep: OnceLock<bool>,
+    /// What EnvInfo reports (set once at initialization)
+    env_info: OnceLock<EnvInfoConfig>,
+    /// Whether WebFetch may fetch local addresses (set once at initialization)
+    allow_localhost_fetch: OnceLock<bool>,
+    /// Hosts WebFetch may fetch (set once at initialization)
+    web_fetch_domains: OnceLock<DomainFilter>,
+    /// Domains WebSearch may return results from (set once at initialization)
+    web_search_domains: OnceLock<DomainFilter>,
+    /// When Write and Edit report a missing final newline (set once at initialization)
+    final_newline: OnceLock<FinalNewlinePolicy>,
+    /// Encodings of non-UTF-8 files read in the session, so Write keeps them
+    file_encodings: Arc<FileEncodings>,
     /// Cancel callback - called when MCP cancellation notification is received
     /// Uses Mutex (not RwLock) because writes are rare and we need try_lock for deadlock safety
     cancel_callback: CancelCallback,
@@ -111,8 +176,26 @@ impl AcpMcpServer {
             connection_cx: OnceLock::new(),
             terminal_client: OnceLock::new(),
             background_processes: OnceLock::new(),
-            cwd: OnceLock::new(),
+            cwd: StdRwLock::new(None),
             permission_checker: OnceLock::new(),
+            interactive_input: OnceLock::new(),
+            max_line_length: OnceLock::new(),
+            max_read_lines: OnceLock::new(),
+            max_file_size: OnceLock::new(),
+            env: OnceLock::new(),
+            tool_timeouts: OnceLock::new(),
+            max_argument_bytes: OnceLock::new(),
+            bash_output_limit: OnceLock::new(),
+            bash_timeout_warning_percent: OnceLock::new(),
+            combine_streams: OnceLock::new(),
+            verify_writes: OnceLock::new(),
+            prefer_ripgrep: OnceLock::new(),
+            env_info: OnceLock::new(),
+            allow_localhost_fetch: OnceLock::new(),
+            web_fetch_domains: OnceLock::new(),
+            web_search_domains: OnceLock::new(),
+            final_newline: OnceLock::new(),
+            file_encodings: Arc::new(FileEncodings::new()),
             cancel_callback: Arc::new(Mutex::new(None)),
         }
     }
@@ -157,35 +240,154 @@ impl AcpMcpServer {
         }
     }
 
-    /// Set the working directory (synchronous, lock-free)
-    ///
-    /// Uses OnceLock to set the value on first call.
-    /// - First call: Sets the cwd
-    /// - Subsequent calls with same value: Silently ignored
-    /// - Subsequent calls with different value: Logs warning, ignored
-    ///
-    /// This is synchronous (not async) because OnceLock operations are
-    /// non-blocking and lock-free after initialization.
-    ///
-    /// # Deadlock Prevention
+    /// Set how Bash commands waiting for input are handled (only sets if not already set)
+    pub fn set_interactive_input(&self, policy: InteractiveInputPolicy) {
+        if self.interactive_input.get().is_none() {
+            drop(self.interactive_input.set(policy));
+        }
+    }
+
+    /// Set the maximum characters per line of tool output (only sets if not already set)
+    pub fn set_max_line_length(&self, max_line_length: usize) {
+        if self.max_line_length.get().is_none() {
+            drop(self.max_line_length.set(max_line_length));
+        }
+    }
+
+    /// Set the maximum lines returned by Read without a range (only sets if not already set)
+    pub fn set_max_read_lines(&self, max_read_lines: usize) {
+        if self.max_read_lines.get().is_none() {
+            drop(self.max_read_lines.set(max_read_lines));
+        }
+    }
+
+    /// Set the largest file Read reads and Write writes (only sets if not already set)
+    pub fn set_max_file_size(&self, max_file_size: u64) {
+        if self.max_file_size.get().is_none() {
+            drop(self.max_file_size.set(max_file_size));
+        }
+    }
+
+    /// Set the client-provided environment for Bash commands (only sets if not already set)
+    pub fn set_env(&self, env: HashMap<String, String>) {
+        if self.env.get().is_none() {
+            drop(self.env.set(env));
+        }
+    }
+
+    /// Set the execution time limits of built-in tools (only sets if not already set)
+    pub fn set_tool_timeouts(&self, tool_timeouts: ToolTimeouts) {
+        if self.tool_timeouts.get().is_none() {
+            drop(self.tool_timeouts.set(tool_timeouts));
+        }
+    }
+
+    /// Set the maximum serialized size of tool arguments (only sets if not already set)
+    pub fn set_max_argument_bytes(&self, max_argument_bytes: usize) {
+        if self.max_argument_bytes.get().is_none() {
+            drop(self.max_argument_bytes.set(max_argument_bytes));
+        }
+    }
+
+    /// Set the maximum bytes of Bash output returned to the model (only sets if not already set)
+    pub fn set_bash_output_limit(&self, bash_output_limit: usize) {
+        if self.bash_output_limit.get().is_none() {
+            drop(self.bash_output_limit.set(bash_output_limit));
+        }
+    }
+
+    /// Set when a Bash command nearing its timeout is warned about (only sets if not already set)
+    pub fn set_bash_timeout_warning_percent(&self, percent: u8) {
+        if self.bash_timeout_warning_percent.get().is_none() {
+            drop(self.bash_timeout_warning_percent.set(percent));
+        }
+    }
+
+    /// Set whether Bash merges stdout and stderr in write order (only sets if not already set)
+    pub fn set_combine_streams(&self, combine_streams: bool) {
+        if self.combine_streams.get().is_none() {
+            drop(self.combine_streams.set(combine_streams));
+        }
+    }
+
+    /// Set whether Write and Edit verify written files (only sets if not already set)
+    pub fn set_verify_writes(&self, verify_writes: bool) {
+        if self.verify_writes.get().is_none() {
+            drop(self.verify_writes.set(verify_writes));
+        }
+    }
+
+    /// Set whether Grep uses ripgrep when available (only sets if not already set)
+    pub fn set_prefer_ripgrep(&self, prefer_ripgrep: bool) {
+        if self.prefer_ripgrep.get().is_none() {
+            drop(self.prefer_ripgrep.set(prefer_ripgrep));
+        }
+    }
+
+    /// Set what EnvInfo reports (only sets if not already set)
+    pub fn set_env_info(&self, env_info: EnvInfoConfig) {
+        if self.env_info.get().is_none() {
+            drop(self.env_info.set(env_info));
+        }
+    }
+
+    /// Set whether WebFetch may fetch loopback and private addresses (only sets if not already set)
+    pub fn set_allow_localhost_fetch(&self, allow: bool) {
+        if self.allow_localhost_fetch.get().is_none() {
+            drop(self.allow_localhost_fetch.set(allow));
+        }
+    }
+
+    /// Set the hosts WebFetch may fetch (only sets if not already set)
+    pub fn set_web_fetch_domains(&self, domains: DomainFilter) {
+        if self.web_fetch_domains.get().is_none() {
+            drop(self.web_fetch_domains.set(domains));
+        }
+    }
+
+    /// Set the domains WebSearch may return results from (only sets if not already set)
+    pub fn set_web_search_domains(&self, domains: DomainFilter) {
+        if self.web_search_domains.get().is_none() {
+            drop(self.web_search_domains.set(domains));
+        }
+    }
+
+    /// Set when Write and Edit report whether a file ends with a newline (only sets if not already set)
+    pub fn set_final_newline(&self, policy: FinalNewlinePolicy) {
+        if self.final_newline.get().is_none() {
+            drop(self.final_newline.set(policy));
+        }
+    }
+
+    /// Set the working directory (synchronous)
     ///
-    /// OnceLock provides lock-free reads after initialization, eliminating
-    /// the deadlock risk that existed with RwLock.
+    /// Unlike the other setters, later calls replace the value: the session's
+    /// working directory can be changed with `session/setCwd`. Tool calls
+    /// already running keep the directory they started with.
     pub fn set_cwd(&self, cwd: impl Into<std::path::PathBuf>) {
         let new_cwd = cwd.into();
-        if let Some(existing) = self.cwd.get() {
-            if existing != &new_cwd {
-                tracing::warn!(
-                    existing = %existing.display(),
-                    new = %new_cwd.display(),
-                    "cwd already set with different value, ignoring new value"
-                );
-            }
-            // If already set, silently ignore (first value wins)
-            // This matches the pattern of other OnceLock setters in this struct
-        } else {
-            self.cwd.set(new_cwd).expect("Failed to set cwd");
+        let mut guard = self
+            .cwd
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(existing) = guard.as_ref()
+            && existing != &new_cwd
+        {
+            tracing::info!(
+                existing = %existing.display(),
+                new = %new_cwd.display(),
+                "Tool working directory changed"
+            );
         }
+        *guard = Some(new_cwd);
+    }
+
+    /// Get the working directory, if it has been set
+    pub fn cwd(&self) -> Option<std::path::PathBuf> {
+        self.cwd
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
     }
 
     /// Set the cancel callback
@@ -437,11 +639,10 @@ impl AcpMcpServer {
     ///
     /// # Deadlock Prevention
     ///
-    /// OnceLock provides lock-free reads after initialization, eliminating
-    /// the deadlock risk that existed with RwLock.
+    /// The cwd is cloned out of its lock and the OnceLock values are
+    /// lock-free after initialization, so no lock is held past this call.
     async fn create_tool_context(&self, tool_use_id: Option<&str>) -> ToolContext {
-        // OnceLock provides lock-free read after initialization
-        let cwd = self.cwd.get().expect("cwd not initialized").clone();
+        let cwd = self.cwd().expect("cwd not initialized");
 
         // OnceLock provides lock-free access after initialization
         let session_id = self
@@ -477,15 +678,63 @@ impl AcpMcpServer {
             context = context.with_permission_checker(checker.clone());
         }
 
-        context
+        if let Some(&max_line_length) = self.max_line_length.get() {
+            context = context.with_max_line_length(max_line_length);
+        }
+
+        if let Some(&max_read_lines) = self.max_read_lines.get() {
+            context = context.with_max_read_lines(max_read_lines);
+        }
+
+        if let Some(&max_file_size) = self.max_file_size.get() {
+            context = context.with_max_file_size(max_file_size);
+        }
+
+        if let Some(env) = self.env.get() {
+            context = context.with_env(env.clone());
+        }
+
+        if let Some(tool_timeouts) = self.tool_timeouts.get() {
+            context = context.with_tool_timeouts(tool_timeouts.clone());
+        }
+
+        if let Some(&verify_writes) = self.verify_writes.get() {
+            context = context.with_verify_writes(verify_writes);
+        }
+
+        if let Some(&prefer_ripgrep) = self.prefer_ripgrep.get() {
+            context = context.with_prefer_ripgrep(prefer_ripgrep);
+        }
+
+        if let Some(env_info) = self.env_info.get() {
+            context = context.with_env_info(env_info.clone());
+        }
+
+        if let Some(&allow_localhost_fetch) = self.allow_localhost_fetch.get() {
+            context = context.with_allow_localhost_fetch(allow_localhost_fetch);
+        }
+
+        if let Some(web_fetch_domains) = self.web_fetch_domains.get() {
+            context = context.with_web_fetch_domains(web_fetch_domains.clone());
+        }
+
+        if let Some(web_search_domains) = self.web_search_domains.get() {
+            context = context.with_web_search_domains(web_search_domains.clone());
+        }
+
+        if let Some(&final_newline) = self.final_newline.get() {
+            context = context.with_final_newline(final_newline);
+        }
+
+        context.with_file_encodings(self.file_encodings.clone())
     }
 
     /// Execute a tool with ACP integration
     ///
     /// # Deadlock Prevention
     ///
-    /// OnceLock provides lock-free access to cwd after initialization,
-    /// eliminating the deadlock risk that existed with RwLock.
+    /// The tool context is built before execution, so no lock on cwd is
+    /// held while the tool runs.
     #[instrument(
         name = "acp_execute_tool",
         skip(self, arguments),
@@ -506,6 +755,29 @@ impl AcpMcpServer {
         // Log arguments preview (truncated for large inputs)
         let args_str = arguments.to_string();
 
+        // Reject oversized calls before they reach the tool (0 = unlimited)
+        let max_argument_bytes = self
+            .max_argument_bytes
+            .get()
+            .copied()
+            .unwrap_or(DEFAULT_MAX_ARGUMENT_BYTES);
+        if max_argument_bytes > 0 && args_str.len() > max_argument_bytes {
+            tracing::warn!(
+                tool_name = %tool_name,
+                tool_use_id = ?tool_use_id,
+                args_len = args_str.len(),
+                max_argument_bytes = max_argument_bytes,
+                "Rejected oversized tool arguments"
+            );
+            let result = ToolResult::error(format!(
+                "Arguments of {tool_name} are too large ({} bytes, limit {max_argument_bytes} bytes). {}",
+                args_str.len(),
+                oversized_arguments_hint(tool_name)
+            ));
+            self.send_completion(tool_use_id, &result);
+            return Ok(result);
+        }
+
         // Truncate at character boundary to avoid panic on multi-byte UTF-8
         let args_preview = if args_str.len() > 500 {
             // Find a safe character boundary near byte 500
@@ -532,7 +804,12 @@ impl AcpMcpServer {
         tracing::debug!("Tool context created, calling tool execution");
 
         // Special handling for Bash tool - use early return to match original behavior
+        // (it bypasses McpServer::execute, so prepare its arguments here)
         if tool_name == "Bash" {
+            let arguments = match self.mcp_server.prepare_arguments(tool_name, arguments) {
+                Ok(arguments) => arguments,
+                Err(e) => return Ok(ToolResult::error(e)),
+            };
             let result = self
                 .execute_bash_tool(arguments, tool_use_id, &context)
                 .await;
@@ -570,71 +847,7 @@ impl AcpMcpServer {
         tracing::debug!("Tool execution returned, preparing to send completion notification");
 
         // Send completion notification to Zed (required for non-Bash tools)
-        // Use RAII scoping to ensure locks are released promptly
-        {
-            #[cfg(feature = "verbose-debug")]
-            tracing::debug!("Acquiring locks for completion notification");
-
-            // OnceLock provides lock-free access after initialization
-            let session_id = self.session_id.get();
-            let connection_cx = self.connection_cx.get();
-
-            #[cfg(feature = "verbose-debug")]
-            tracing::debug!("Locks acquired, checking if we have all required data");
-
-            if let (Some(cx), Some(session_id), Some(tool_use_id)) =
-                (connection_cx, session_id, tool_use_id)
-            {
-                let status = if result.is_error {
-                    ToolCallStatus::Failed
-                } else {
-                    ToolCallStatus::Completed
-                };
-
-                #[cfg(feature = "verbose-debug")]
-                tracing::debug!(
-                    tool_name = %tool_name,
-                    tool_use_id = %tool_use_id,
-                    status = ?status,
-                    is_error = result.is_error,
-                    content_len = result.content.len(),
-                    "Sending completion notification"
-                );
-
-                // Prepare content for the notification
-                // For errors, include the error message so Zed can display it
-                let content: Option<Vec<ToolCallContent>> = if result.is_error {
-                    Some(vec![result.content.clone().into()])
-                } else {
-                    // For successful completion, no need to send content
-                    // The tool result will be sent separately via result message
-                    None
-                };
-
-                // Send completion notification with content for errors
-                if let Err(e) = Self::send_tool_call_update_with_meta(
-                    cx,
-                    session_id,
-                    tool_use_id,
-                    Some(status),
-                    None,
-                    content,
-                    None,
-                ) {
-                    tracing::debug!("Failed to send tool completion notification: {}", e);
-                }
-
-                #[cfg(feature = "verbose-debug")]
-                tracing::debug!("Completion notification sent successfully");
-            }
-            // Locks are automatically released when this block ends
-
-            #[cfg(feature = "verbose-debug")]
-            tracing::debug!("RAII block ending, locks will be released");
-        }
-
-        #[cfg(feature = "verbose-debug")]
-        tracing::debug!("RAII block ended, locks released, continuing execution");
+        self.send_completion(tool_use_id, &result);
 
         let elapsed = start_time.elapsed();
 
@@ -676,6 +889,10 @@ impl AcpMcpServer {
     /// - terminal_info: { terminal_id, cwd } - sent at start
     /// - terminal_output: { terminal_id, data } - sent for each output chunk
     /// - terminal_exit: { terminal_id, exit_code } - sent when command completes
+    ///
+    /// A command still running at the configured share of its timeout also
+    /// gets terminal_timeout_warning: { terminal_id, timeout_ms, remaining_ms },
+    /// so the user can intervene before it's killed.
     #[instrument(
         name = "acp_bash_tool",
         skip(self, arguments, context),
@@ -690,6 +907,7 @@ impl AcpMcpServer {
         context: &ToolContext,
     ) -> Result<ToolResult, String> {
         let bash_start = Instant::now();
+        let session_context = context;
 
         // OnceLock provides lock-free access after initialization
         let session_id = self.session_id.get();
@@ -709,11 +927,39 @@ impl AcpMcpServer {
             .get("run_in_background")
             .and_then(|v| v.as_bool())
             .unwrap_or(false);
-        let timeout_ms = arguments
-            .get("timeout")
-            .and_then(|v| v.as_u64())
-            .unwrap_or(120_000)
-            .min(600_000); // Max 10 minutes
+        let expect_exit_code = arguments
+            .get("expect_exit_code")
+            .and_then(|v| v.as_i64())
+            .and_then(|code| i32::try_from(code).ok());
+        let timeout_ms = bash_timeout_ms(
+            arguments.get("timeout").and_then(|v| v.as_u64()),
+            context.tool_timeouts(),
+        );
+
+        // Run in the requested directory, if any
+        let cwd_context;
+        let requested_cwd = arguments.get("cwd").and_then(|v| v.as_str());
+        let context = match resolve_command_cwd(requested_cwd, context).await {
+            Ok(None) => context,
+            Ok(Some(dir)) => {
+                let mut overridden = context.clone();
+                overridden.cwd = dir;
+                cwd_context = overridden;
+                &cwd_context
+            }
+            Err(rejection) => return Ok(rejection),
+        };
+
+        // Extra environment variables for this call
+        let env: HashMap<String, String> = match arguments.get("env") {
+            Some(value) => {
+                serde_json::from_value(value.clone()).map_err(|e| format!("Invalid env: {}", e))?
+            }
+            None => HashMap::new(),
+        };
+        if let Some(rejection) = check_command_env(&env) {
+            return Ok(rejection);
+        }
 
         // Generate unique terminal ID for tracking
         let terminal_id = uuid::Uuid::new_v4().to_string();
@@ -728,6 +974,8 @@ impl AcpMcpServer {
             run_in_background = run_in_background,
             timeout_ms = timeout_ms,
             cwd = ?context.cwd,
+            // Values may be secrets; only log the names
+            env_keys = ?env.keys().collect::<Vec<_>>(),
             "Executing Bash command with streaming output"
         );
 
@@ -767,45 +1015,56 @@ impl AcpMcpServer {
         let session_id_clone = session_id.cloned();
         let tool_use_id_clone = tool_use_id.map(String::from);
 
-        // Execute command with streaming
-        let result = self
-            .execute_command_with_streaming(
+        // Execute command with streaming; benchmarks run the command
+        // repeatedly without streaming and show their timings instead
+        let result = if arguments.get("repeat").is_some() {
+            let result = self
+                .mcp_server
+                .execute("Bash", arguments, session_context)
+                .await;
+            if let (Some(cx), Some(session_id), Some(tool_use_id)) = (
+                cx_clone.as_ref(),
+                session_id_clone.as_ref(),
+                tool_use_id_clone.as_ref(),
+            ) {
+                TerminalOutputSink {
+                    cx: cx.clone(),
+                    session_id: session_id.clone(),
+                    tool_use_id: tool_use_id.clone(),
+                    terminal_id: terminal_id.clone(),
+                }
+                .send(&format!("{}\n", result.content));
+            }
+            Ok(result)
+        } else {
+            self.execute_command_with_streaming(
                 &command,
                 &terminal_id,
                 context,
+                &env,
                 timeout_ms,
                 run_in_background,
+                expect_exit_code,
                 cx_clone.as_ref(),
                 session_id_clone.as_deref(),
                 tool_use_id_clone.as_deref(),
             )
-            .await;
+            .await
+        };
 
-        // Send terminal_exit notification
-        if let (Some(cx), Some(session_id), Some(tool_use_id)) = (
+        // Send terminal_exit notification with the structured output
+        if let (Some(cx), Some(session_id), Some(tool_use_id), Ok(result)) = (
             cx_clone.as_ref(),
             session_id_clone.as_ref(),
             tool_use_id_clone.as_ref(),
+            result.as_ref(),
         ) {
-            let exit_code = match &result {
-                Ok(r) if !r.is_error => 0,
-                _ => 1,
-            };
-            let meta = Self::value_to_meta(serde_json::json!({
-                "terminal_exit": {
-                    "terminal_id": &terminal_id,
-                    "exit_code": exit_code
-                }
-            }));
-            if let Err(e) = Self::send_tool_call_update_with_meta(
-                cx,
-                session_id,
-                tool_use_id,
-                Some(ToolCallStatus::Completed),
-                None,
-                None, // No content for terminal_exit
-                meta,
-            ) {
+            let update = Self::bash_completion_update(tool_use_id, &terminal_id, result);
+            let notification = SessionNotification::new(
+                SessionId::new(session_id.clone()),
+                SessionUpdate::ToolCallUpdate(update),
+            );
+            if let Err(e) = cx.send_notification(notification) {
                 tracing::debug!("Failed to send terminal_exit: {}", e);
             }
         }
@@ -822,6 +1081,167 @@ impl AcpMcpServer {
         result
     }
 
+    /// Build the final ToolCallUpdate for a completed non-Bash tool
+    ///
+    /// Failures carry the error message as content so Zed can display it.
+    /// Successes skip the content (the tool result is sent separately via
+    /// the result message) but carry the result's locations, e.g. Grep
+    /// matches, so the client can link to them.
+    fn completion_update(tool_use_id: &str, result: &ToolResult) -> ToolCallUpdate {
+        let mut fields = if result.is_error {
+            ToolCallUpdateFields::new()
+                .status(ToolCallStatus::Failed)
+                .content(vec![result.content.clone().into()])
+        } else {
+            ToolCallUpdateFields::new().status(ToolCallStatus::Completed)
+        };
+        if !result.locations.is_empty() {
+            fields = fields.locations(
+                result
+                    .locations
+                    .iter()
+                    .map(|loc| {
+                        let mut location = AcpToolCallLocation::new(&loc.path);
+                        if let Some(line) = loc.line {
+                            location = location.line(line);
+                        }
+                        location
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+        ToolCallUpdate::new(ToolCallId::new(tool_use_id.to_string()), fields)
+    }
+
+    /// Send the completion notification for a tool call
+    ///
+    /// Skipped when there is no connection, session or tool call ID to
+    /// address it to. OnceLock provides lock-free access, so no lock is held.
+    fn send_completion(&self, tool_use_id: Option<&str>, result: &ToolResult) {
+        let (Some(cx), Some(session_id), Some(tool_use_id)) =
+            (self.connection_cx.get(), self.session_id.get(), tool_use_id)
+        else {
+            return;
+        };
+
+        #[cfg(feature = "verbose-debug")]
+        tracing::debug!(
+            tool_use_id = %tool_use_id,
+            is_error = result.is_error,
+            content_len = result.content.len(),
+            "Sending completion notification"
+        );
+
+        // Send completion notification with content for errors
+        let notification = SessionNotification::new(
+            SessionId::new(session_id.clone()),
+            SessionUpdate::ToolCallUpdate(Self::completion_update(tool_use_id, result)),
+        );
+        if let Err(e) = cx.send_notification(notification) {
+            tracing::debug!("Failed to send tool completion notification: {}", e);
+        }
+    }
+
+    /// MCP `tools/call` response for a tool result
+    ///
+    /// An image (e.g. from Read) is sent as MCP image content after the
+    /// text, so the model sees it as an image rather than as text.
+    fn tools_call_response(result: &ToolResult) -> Value {
+        let mut content = vec![serde_json::json!({
+            "type": "text",
+            "text": result.content
+        })];
+        if let Some(image) = &result.image {
+            content.push(serde_json::json!({
+                "type": "image",
+                "data": image.data,
+                "mimeType": image.media_type
+            }));
+        }
+        serde_json::json!({
+            "content": content,
+            "is_error": result.is_error
+        })
+    }
+
+    /// Build the final ToolCallUpdate for a completed Bash command
+    ///
+    /// Carries the `terminal_exit` meta for terminal rendering and the
+    /// structured `{command, exit_code, stdout, stderr, duration_ms}` output
+    /// (from the result's metadata) as `raw_output`, with the streams
+    /// truncated like the model's output. With merged streams, `stdout`
+    /// holds all output and `stderr` is empty.
+    fn bash_completion_update(
+        tool_use_id: &str,
+        terminal_id: &str,
+        result: &ToolResult,
+    ) -> ToolCallUpdate {
+        let exit_code = i32::from(result.is_error);
+        let mut fields = ToolCallUpdateFields::new().status(ToolCallStatus::Completed);
+        if let Some(raw_output) = &result.metadata {
+            fields = fields.raw_output(raw_output.clone());
+        }
+
+        let mut update = ToolCallUpdate::new(ToolCallId::new(tool_use_id.to_string()), fields);
+        if let Some(meta) = Self::value_to_meta(serde_json::json!({
+            "terminal_exit": {
+                "terminal_id": terminal_id,
+                "exit_code": exit_code
+            }
+        })) {
+            update = update.meta(meta);
+        }
+        update
+    }
+
+    /// Hand a background command's process to the manager as `shell_id`
+    ///
+    /// Its output is collected for BashOutput until it closes, then the
+    /// shell is marked as finished with the process's exit code.
+    fn register_background_command(
+        mut child: tokio::process::Child,
+        command: &str,
+        shell_id: &str,
+        manager: &Arc<BackgroundProcessManager>,
+        context: &ToolContext,
+    ) {
+        // Background commands get no input
+        drop(child.stdin.take());
+        let stdout = child.stdout.take();
+        let stderr = child.stderr.take();
+
+        let mut handle = ChildHandle::Unwrapped {
+            child: Arc::new(Mutex::new(child)),
+        };
+        let terminal =
+            BackgroundTerminal::new_running_with_limit(handle.clone(), manager.max_output_bytes());
+        let output_buffer = match &terminal {
+            BackgroundTerminal::Running { output_buffer, .. } => output_buffer.clone(),
+            BackgroundTerminal::Finished { .. } => unreachable!(),
+        };
+
+        // Tell the client when the command finishes, however it ends
+        if context.tool_use_id().is_some() {
+            BashTool::notify_on_exit(manager, shell_id.to_string(), context.clone());
+        }
+        manager.register(shell_id.to_string(), command, terminal);
+
+        let manager = manager.clone();
+        let shell_id = shell_id.to_string();
+        tokio::spawn(async move {
+            tokio::join!(
+                read_background_output(stdout, &output_buffer),
+                read_background_output(stderr, &output_buffer),
+            );
+            let status = match handle.wait().await {
+                Ok(status) => TerminalExitStatus::Exited(status.code().unwrap_or(-1)),
+                Err(_) => TerminalExitStatus::Aborted,
+            };
+            // A shell killed with KillShell keeps its status
+            manager.finish_terminal(&shell_id, status).await;
+        });
+    }
+
     /// Execute command with streaming output via meta field
     ///
     /// This function executes the command directly using tokio::process::Command
@@ -832,103 +1252,156 @@ impl AcpMcpServer {
         command: &str,
         terminal_id: &str,
         context: &ToolContext,
+        env: &HashMap<String, String>,
         timeout_ms: u64,
         run_in_background: bool,
+        expect_exit_code: Option<i32>,
         cx: Option<&JrConnectionCx<AgentToClient>>,
         session_id: Option<&str>,
         tool_use_id: Option<&str>,
     ) -> Result<ToolResult, String> {
-        use tokio::io::{AsyncBufReadExt, BufReader};
         use tokio::process::Command;
 
+        // Background shells are kept by the manager so BashOutput can poll them
+        let background_manager = match (run_in_background, context.background_processes()) {
+            (false, _) => None,
+            (true, Some(manager)) => Some(manager.clone()),
+            (true, None) => {
+                return Ok(ToolResult::error(
+                    "Background process manager not available",
+                ));
+            }
+        };
+
         // Spawn the command
+        let start = Instant::now();
         let mut child = Command::new("bash")
             .arg("-c")
             .arg(command)
             .current_dir(&context.cwd)
+            .envs(context.env())
             .env("CLAUDECODE", "1")
+            .envs(env)
+            .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
             .spawn()
             .map_err(|e| format!("Failed to spawn command: {}", e))?;
 
         // Handle background execution
-        if run_in_background {
-            let shell_id = format!("term-{}", terminal_id);
+        if let Some(manager) = background_manager {
+            let shell_id = format!("shell-{}", terminal_id);
+            Self::register_background_command(child, command, &shell_id, &manager, context);
             tracing::info!(shell_id = %shell_id, "Command started in background");
             return Ok(ToolResult::success(format!(
                 "Command started in background.\n\nShell ID: {}\n\nUse BashOutput to check status.",
                 shell_id
-            )));
+            ))
+            .with_metadata(serde_json::json!({
+                "shell_id": shell_id,
+                "status": "running"
+            })));
         }
 
         // Collect output
         let mut output = String::new();
         let mut stderr_output = String::new();
 
-        // Take stdout and stderr
+        // Take stdin, stdout and stderr
+        let stdin = child.stdin.take();
         let stdout = child.stdout.take();
         let stderr = child.stderr.take();
+        let activity = Arc::new(OutputActivity::new());
+        let interactive_input = self.interactive_input.get().copied().unwrap_or_default();
+
+        // Read stdout and stderr in tasks, streaming both to the terminal
+        let combine_streams = self.combine_streams.get().copied().unwrap_or(true);
+        let sink = match (cx, session_id, tool_use_id) {
+            (Some(cx), Some(session_id), Some(tool_use_id)) => Some(Arc::new(TerminalOutputSink {
+                cx: cx.clone(),
+                session_id: session_id.to_string(),
+                tool_use_id: tool_use_id.to_string(),
+                terminal_id: terminal_id.to_string(),
+            })),
+            _ => None,
+        };
+        let (stdout_task, stderr_task) = match (stdout, stderr) {
+            (Some(stdout), Some(stderr)) if combine_streams => (
+                Some(tokio::spawn(read_merged_output(
+                    stdout,
+                    stderr,
+                    activity.clone(),
+                    sink,
+                ))),
+                None,
+            ),
+            (stdout, stderr) => (
+                stdout.map(|stdout| {
+                    tokio::spawn(read_output(stdout, activity.clone(), sink.clone()))
+                }),
+                stderr.map(|stderr| tokio::spawn(read_output(stderr, activity.clone(), sink))),
+            ),
+        };
 
-        // Read stdout in a task
-        let stdout_task = if let Some(stdout) = stdout {
+        // Warn the client once the command nears its timeout
+        let warning_percent = self
+            .bash_timeout_warning_percent
+            .get()
+            .copied()
+            .unwrap_or(DEFAULT_BASH_TIMEOUT_WARNING_PERCENT);
+        let warning_task = bash_timeout_warning_ms(timeout_ms, warning_percent).map(|warn_ms| {
             let cx = cx.cloned();
             let session_id = session_id.map(String::from);
             let tool_use_id = tool_use_id.map(String::from);
             let terminal_id = terminal_id.to_string();
-
-            Some(tokio::spawn(async move {
-                let reader = BufReader::new(stdout);
-                let mut lines = reader.lines();
-                let mut collected = String::new();
-
-                while let Ok(Some(line)) = lines.next_line().await {
-                    collected.push_str(&line);
-                    collected.push('\n');
-
-                    // Send terminal_output notification
-                    if let (Some(cx), Some(session_id), Some(tool_use_id)) =
-                        (cx.as_ref(), session_id.as_ref(), tool_use_id.as_ref())
-                    {
-                        let meta = Self::value_to_meta(serde_json::json!({
-                            "terminal_output": {
-                                "terminal_id": &terminal_id,
-                                "data": format!("{}\n", line)
-                            }
-                        }));
-                        drop(Self::send_tool_call_update_with_meta(
-                            cx,
-                            session_id,
-                            tool_use_id,
-                            None, // No status change for terminal_output
-                            None,
-                            None, // No content for terminal_output
-                            meta,
-                        ));
-                    }
-                }
-                collected
-            }))
-        } else {
-            None
-        };
-
-        // Read stderr in a task
-        let stderr_task = stderr.map(|stderr| tokio::spawn(async move {
-                let reader = BufReader::new(stderr);
-                let mut lines = reader.lines();
-                let mut collected = String::new();
-
-                while let Ok(Some(line)) = lines.next_line().await {
-                    collected.push_str(&line);
-                    collected.push('\n');
+            tokio::spawn(async move {
+                tokio::time::sleep(std::time::Duration::from_millis(warn_ms)).await;
+                tracing::info!(
+                    terminal_id = %terminal_id,
+                    timeout_ms = timeout_ms,
+                    "Bash command approaching its timeout"
+                );
+                if let (Some(cx), Some(session_id), Some(tool_use_id)) =
+                    (cx.as_ref(), session_id.as_ref(), tool_use_id.as_ref())
+                {
+                    let meta = Self::value_to_meta(serde_json::json!({
+                        "terminal_timeout_warning": {
+                            "terminal_id": &terminal_id,
+                            "timeout_ms": timeout_ms,
+                            "remaining_ms": timeout_ms - warn_ms
+                        }
+                    }));
+                    drop(Self::send_tool_call_update_with_meta(
+                        cx,
+                        session_id,
+                        tool_use_id,
+                        None,
+                        None,
+                        None,
+                        meta,
+                    ));
                 }
-                collected
-            }));
+                warn_ms
+            })
+        });
 
         // Wait for command with timeout
         let timeout_duration = std::time::Duration::from_millis(timeout_ms);
-        let wait_result = tokio::time::timeout(timeout_duration, child.wait()).await;
+        let wait_result = tokio::time::timeout(
+            timeout_duration,
+            wait_with_input_detection(&mut child, stdin, &activity, interactive_input),
+        )
+        .await;
+
+        // When the warning was sent, if it was
+        let timeout_warning_ms = match warning_task {
+            Some(task) if task.is_finished() => task.await.ok(),
+            Some(task) => {
+                task.abort();
+                None
+            }
+            None => None,
+        };
 
         // Collect outputs
         if let Some(task) = stdout_task {
@@ -942,6 +1415,40 @@ impl AcpMcpServer {
             }
         }
 
+        // Test runner output is summarized before any truncation
+        let test_results = parse_test_output(&output).or_else(|| parse_test_output(&stderr_output));
+
+        // Only the model's copy is truncated; terminal_output streamed everything
+        let output_limit = self
+            .bash_output_limit
+            .get()
+            .copied()
+            .unwrap_or(DEFAULT_BASH_OUTPUT_LIMIT);
+        let truncate = |text: &str| -> serde_json::Value {
+            if output_limit > 0 {
+                truncate_middle(text, output_limit).into()
+            } else {
+                text.into()
+            }
+        };
+
+        // Structured output for the final update's raw_output, truncated
+        // like the model's copy
+        let exit_code = match &wait_result {
+            Ok((Ok(status), _)) => status.code(),
+            _ => None,
+        };
+        #[allow(clippy::cast_possible_truncation)]
+        let raw_output = serde_json::json!({
+            "command": command,
+            "exit_code": exit_code,
+            "stdout": truncate(&output),
+            "stderr": truncate(&stderr_output),
+            "duration_ms": start.elapsed().as_millis() as u64,
+            "timeout_warning_ms": timeout_warning_ms,
+            "test_results": test_results
+        });
+
         // Combine output
         let combined_output = if stderr_output.is_empty() {
             output
@@ -950,39 +1457,60 @@ impl AcpMcpServer {
         } else {
             format!("{}\n--- stderr ---\n{}", output, stderr_output)
         };
+        let combined_output = context.limit_line_length(&combined_output).into_owned();
+        let combined_output = if output_limit > 0 {
+            truncate_middle(&combined_output, output_limit).into_owned()
+        } else {
+            combined_output
+        };
+        let combined_output = match &test_results {
+            Some(summary) => format!("{}\n{}", combined_output, summary.summary_line()),
+            None => combined_output,
+        };
 
         // Process result
-        match wait_result {
-            Ok(Ok(status)) => {
+        let result = match wait_result {
+            Ok((_, Some(InputAction::Stopped))) => ToolResult::error(format!(
+                "{}\n{}",
+                InputAction::Stopped.describe(interactive_input.grace),
+                combined_output
+            )),
+            Ok((Ok(status), action)) => {
+                let combined_output = match action {
+                    Some(action) => format!(
+                        "{}\n{}",
+                        combined_output,
+                        action.describe(interactive_input.grace)
+                    ),
+                    None => combined_output,
+                };
                 let exit_code = status.code().unwrap_or(-1);
                 tracing::info!(exit_code = exit_code, command = %command, "Command completed");
 
-                if status.success() {
-                    Ok(ToolResult::success(combined_output))
-                } else {
-                    Ok(ToolResult::error(format!(
-                        "Command failed with exit code {}\n{}",
-                        exit_code, combined_output
-                    )))
+                match unexpected_exit_message(exit_code, expect_exit_code) {
+                    None => ToolResult::success(combined_output),
+                    Some(message) => ToolResult::error(format!("{}\n{}", message, combined_output)),
                 }
             }
-            Ok(Err(e)) => {
+            Ok((Err(e), _)) => {
                 tracing::error!(error = %e, "Failed to wait for command");
-                Ok(ToolResult::error(format!(
+                ToolResult::error(format!(
                     "Failed to wait for command: {}\n{}",
                     e, combined_output
-                )))
+                ))
             }
             Err(_) => {
                 tracing::warn!(timeout_ms = timeout_ms, "Command timed out");
                 // Try to kill the process
                 drop(child.kill().await);
-                Ok(ToolResult::error(format!(
+                ToolResult::error(format!(
                     "Command timed out after {}ms\n{}",
                     timeout_ms, combined_output
-                )))
+                ))
             }
-        }
+        };
+
+        Ok(result.with_metadata(raw_output))
     }
 
     /// Execute Bash command via direct execution (legacy fallback)
@@ -1162,13 +1690,7 @@ impl SdkMcpServer for AcpMcpServer {
                 #[cfg(feature = "verbose-debug")]
                 tracing::debug!("About to create response JSON");
 
-                let response = Ok(serde_json::json!({
-                    "content": [{
-                        "type": "text",
-                        "text": result.content
-                    }],
-                    "is_error": result.is_error
-                }));
+                let response = Ok(Self::tools_call_response(&result));
 
                 #[cfg(feature = "verbose-debug")]
                 tracing::debug!("Response JSON created successfully");
@@ -1264,6 +1786,212 @@ impl SdkMcpServer for AcpMcpServer {
     }
 }
 
+/// How to retry a tool call whose arguments are over the size limit
+fn oversized_arguments_hint(tool_name: &str) -> String {
+    match tool_name {
+        "Write" | "Edit" | "MultiEdit" => "Write large content in chunks: create the file \
+            with a first part using Write, then add the rest with further Edit calls."
+            .to_string(),
+        _ => format!("Split the work into several smaller {tool_name} calls."),
+    }
+}
+
+/// Timeout of a Bash call in milliseconds
+///
+/// A configured `Bash` entry in `toolTimeouts` replaces the default and
+/// raises the cap to match, so slow commands get more room without raising
+/// it for everyone; a zero entry disables the limit.
+fn bash_timeout_ms(requested: Option<u64>, tool_timeouts: &ToolTimeouts) -> u64 {
+    let configured = tool_timeouts.per_tool.get("Bash").map(|timeout| {
+        match u64::try_from(timeout.as_millis()) {
+            Ok(0) | Err(_) => u64::MAX,
+            Ok(ms) => ms,
+        }
+    });
+    let default = configured.unwrap_or(DEFAULT_BASH_TIMEOUT_MS);
+    let cap = configured.map_or(MAX_BASH_TIMEOUT_MS, |ms| ms.max(MAX_BASH_TIMEOUT_MS));
+    requested.unwrap_or(default).min(cap)
+}
+
+/// Milliseconds into a Bash command at which to warn about its timeout
+///
+/// None when the warning is disabled (0%) or pointless (100% or more, or
+/// no timeout at all).
+fn bash_timeout_warning_ms(timeout_ms: u64, percent: u8) -> Option<u64> {
+    if timeout_ms == u64::MAX || percent == 0 || percent >= 100 {
+        return None;
+    }
+    Some(timeout_ms.checked_mul(u64::from(percent))? / 100)
+}
+
+/// Destination of a Bash command's live output
+struct TerminalOutputSink {
+    cx: JrConnectionCx<AgentToClient>,
+    session_id: String,
+    tool_use_id: String,
+    terminal_id: String,
+}
+
+impl TerminalOutputSink {
+    /// Send a terminal_output notification for `data`
+    fn send(&self, data: &str) {
+        let meta = AcpMcpServer::value_to_meta(serde_json::json!({
+            "terminal_output": {
+                "terminal_id": &self.terminal_id,
+                "data": data
+            }
+        }));
+        drop(AcpMcpServer::send_tool_call_update_with_meta(
+            &self.cx,
+            &self.session_id,
+            &self.tool_use_id,
+            None, // No status change for terminal_output
+            None,
+            None, // No content for terminal_output
+            meta,
+        ));
+    }
+}
+
+/// Read a Bash command's output until it closes, returning all of it
+///
+/// Output is read in chunks rather than lines, so progress bars redrawn
+/// with `\r` and prompts without a trailing newline reach the terminal as
+/// they are printed.
+async fn read_output(
+    mut reader: impl AsyncRead + Unpin,
+    activity: Arc<OutputActivity>,
+    sink: Option<Arc<TerminalOutputSink>>,
+) -> String {
+    let mut buffer = vec![0; OUTPUT_CHUNK_SIZE];
+    let mut pending = Vec::new();
+    let mut collected = String::new();
+    let mut forward = |chunk: &str| {
+        if chunk.is_empty() {
+            return;
+        }
+        if let Some(sink) = &sink {
+            sink.send(chunk);
+        }
+        collected.push_str(chunk);
+    };
+
+    while let Ok(n) = reader.read(&mut buffer).await
+        && n > 0
+    {
+        activity.touch();
+        forward(&decode_output_chunk(&mut pending, &buffer[..n]));
+    }
+    // A character cut off by the end of the output
+    forward(&String::from_utf8_lossy(&pending));
+    collected
+}
+
+/// Read a background command's output into its shell's buffer until it closes
+async fn read_background_output(
+    reader: Option<impl AsyncRead + Unpin>,
+    output_buffer: &Mutex<OutputBuffer>,
+) {
+    let Some(mut reader) = reader else {
+        return;
+    };
+    let mut buffer = vec![0; OUTPUT_CHUNK_SIZE];
+    let mut pending = Vec::new();
+    while let Ok(n) = reader.read(&mut buffer).await
+        && n > 0
+    {
+        let chunk = decode_output_chunk(&mut pending, &buffer[..n]);
+        output_buffer.lock().await.push_str(&chunk);
+    }
+    // A character cut off by the end of the output
+    output_buffer
+        .lock()
+        .await
+        .push_str(&String::from_utf8_lossy(&pending));
+}
+
+/// Read a Bash command's stdout and stderr together until both close
+///
+/// Chunks are forwarded and collected in the order they arrive from either
+/// stream, which keeps errors next to the output they belong to.
+async fn read_merged_output(
+    mut stdout: impl AsyncRead + Unpin,
+    mut stderr: impl AsyncRead + Unpin,
+    activity: Arc<OutputActivity>,
+    sink: Option<Arc<TerminalOutputSink>>,
+) -> String {
+    let mut stdout_buffer = vec![0; OUTPUT_CHUNK_SIZE];
+    let mut stderr_buffer = vec![0; OUTPUT_CHUNK_SIZE];
+    let mut stdout_pending = Vec::new();
+    let mut stderr_pending = Vec::new();
+    let (mut stdout_open, mut stderr_open) = (true, true);
+    let mut collected = String::new();
+    let mut forward = |chunk: &str| {
+        if chunk.is_empty() {
+            return;
+        }
+        if let Some(sink) = &sink {
+            sink.send(chunk);
+        }
+        collected.push_str(chunk);
+    };
+
+    while stdout_open || stderr_open {
+        tokio::select! {
+            read = stdout.read(&mut stdout_buffer), if stdout_open => match read {
+                Ok(0) | Err(_) => stdout_open = false,
+                Ok(n) => {
+                    activity.touch();
+                    forward(&decode_output_chunk(&mut stdout_pending, &stdout_buffer[..n]));
+                }
+            },
+            read = stderr.read(&mut stderr_buffer), if stderr_open => match read {
+                Ok(0) | Err(_) => stderr_open = false,
+                Ok(n) => {
+                    activity.touch();
+                    forward(&decode_output_chunk(&mut stderr_pending, &stderr_buffer[..n]));
+                }
+            },
+        }
+    }
+    // Characters cut off by the end of the output
+    forward(&String::from_utf8_lossy(&stdout_pending));
+    forward(&String::from_utf8_lossy(&stderr_pending));
+    collected
+}
+
+/// Decode a chunk of output as UTF-8, replacing invalid bytes
+///
+/// A character split across chunks is held back in `pending` until the
+/// rest of it arrives, rather than being replaced.
+fn decode_output_chunk(pending: &mut Vec<u8>, chunk: &[u8]) -> String {
+    pending.extend_from_slice(chunk);
+    let incomplete = incomplete_utf8_suffix(pending);
+    let rest = pending.split_off(pending.len() - incomplete);
+    let decoded = String::from_utf8_lossy(pending).into_owned();
+    *pending = rest;
+    decoded
+}
+
+/// Length of an unfinished UTF-8 character at the end of `bytes`
+fn incomplete_utf8_suffix(bytes: &[u8]) -> usize {
+    for back in 1..=bytes.len().min(3) {
+        let byte = bytes[bytes.len() - back];
+        // Skip continuation bytes until the character's first byte
+        if byte & 0xC0 == 0x80 {
+            continue;
+        }
+        let needed = match byte {
+            0xF0.. => 4,
+            0xE0.. => 3,
+            0xC0.. => 2,
+            _ => 1,
+        };
+        return if needed > back { back } else { 0 };
+    }
+    0
+}
+
 /// Get the list of built-in tools that should be disabled in the SDK
 ///
 /// When using AcpMcpServer, these tools should be disabled in the SDK
@@ -1276,6 +2004,7 @@ pub fn get_disallowed_tools() -> Vec<String> {
         "Read".to_string(),
         "Write".to_string(),
         "Edit".to_string(),
+        "MultiEdit".to_string(),
         "Glob".to_string(),
         "Grep".to_string(),
         "LS".to_string(),
@@ -1294,6 +2023,7 @@ pub fn get_disallowed_tools() -> Vec<String> {
 mod tests {
     use super::*;
     use claude_code_agent_sdk::ACP_TOOL_PREFIX;
+    use std::time::Duration;
 
     #[test]
     fn test_acp_mcp_server_creation() {
@@ -1320,6 +2050,7 @@ mod tests {
         let tools = get_disallowed_tools();
         assert!(tools.contains(&"Bash".to_string()));
         assert!(tools.contains(&"Read".to_string()));
+        assert!(tools.contains(&"MultiEdit".to_string()));
     }
 
     #[test]
@@ -1595,6 +2326,457 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_bash_output_truncated_from_the_middle() {
+        let server = AcpMcpServer::new("test-server", "1.0.0");
+        server.set_cwd(std::env::temp_dir());
+        server.set_session_id("test-session");
+        server.set_bash_output_limit(1000);
+
+        let result = server
+            .execute_tool(
+                "Bash",
+                serde_json::json!({
+                    "command": "seq 1 10000"
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_error, "got: {}", result.content);
+        assert!(result.content.starts_with("1\n2\n3\n"));
+        assert!(result.content.ends_with("9999\n10000\n"));
+        assert!(result.content.contains("bytes truncated]..."));
+        assert!(result.content.len() < 1100);
+
+        // The structured output is truncated the same way
+        let stdout = result.metadata.unwrap()["stdout"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        assert!(stdout.starts_with("1\n2\n3\n"));
+        assert!(stdout.ends_with("9999\n10000\n"));
+        assert!(stdout.len() < 1100);
+    }
+
+    #[tokio::test]
+    async fn test_bash_expect_exit_code() {
+        let server = AcpMcpServer::new("test-server", "1.0.0");
+        server.set_cwd(std::env::temp_dir());
+        server.set_session_id("test-session");
+
+        let result = server
+            .execute_tool(
+                "Bash",
+                serde_json::json!({
+                    "command": "exit 3",
+                    "expect_exit_code": 3
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_error, "got: {}", result.content);
+        assert_eq!(result.metadata.unwrap()["exit_code"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_bash_background_output_is_polled() {
+        let server = AcpMcpServer::new("test-server", "1.0.0");
+        server.set_cwd(std::env::temp_dir());
+        server.set_session_id("test-session");
+        server.set_background_processes(Arc::new(BackgroundProcessManager::new()));
+
+        let result = server
+            .execute_tool(
+                "Bash",
+                serde_json::json!({
+                    "command": "echo first; sleep 0.3; echo second >&2; exit 4",
+                    "run_in_background": true
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "got: {}", result.content);
+        let shell_id = result.metadata.unwrap()["shell_id"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let mut output = String::new();
+        let deadline = Instant::now() + Duration::from_secs(10);
+        loop {
+            let result = server
+                .execute_tool(
+                    "BashOutput",
+                    serde_json::json!({"bash_id": &shell_id}),
+                    None,
+                )
+                .await
+                .unwrap();
+            assert!(!result.is_error, "got: {}", result.content);
+            output.push_str(&result.content);
+            if result.metadata.unwrap()["exit_code"] == 4 {
+                break;
+            }
+            assert!(Instant::now() < deadline, "command should finish");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+        assert!(output.contains("Status: running"));
+        assert!(output.contains("Status: exited (exit code 4)"));
+        assert_eq!(output.matches("first").count(), 1);
+        assert!(output.contains("second"));
+    }
+
+    #[tokio::test]
+    async fn test_bash_env_argument() {
+        let server = AcpMcpServer::new("test-server", "1.0.0");
+        server.set_cwd(std::env::temp_dir());
+        server.set_session_id("test-session");
+
+        let result = server
+            .execute_tool(
+                "Bash",
+                serde_json::json!({
+                    "command": "echo \"$NODE_ENV $CLAUDECODE\"",
+                    "env": {"NODE_ENV": "hello"}
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "got: {}", result.content);
+        assert!(result.content.contains("hello 1"));
+
+        let result = server
+            .execute_tool(
+                "Bash",
+                serde_json::json!({"command": "true", "env": {"PERL5OPT": "-Mevil"}}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert!(result.content.contains("PERL5OPT"));
+    }
+
+    #[tokio::test]
+    async fn test_bash_merges_streams_in_order() {
+        let server = AcpMcpServer::new("test-server", "1.0.0");
+        server.set_cwd(std::env::temp_dir());
+        server.set_session_id("test-session");
+
+        let result = server
+            .execute_tool(
+                "Bash",
+                serde_json::json!({
+                    "command": "echo one; sleep 0.1; echo two >&2; sleep 0.1; echo three"
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(!result.is_error, "got: {}", result.content);
+        assert_eq!(result.content, "one\ntwo\nthree\n");
+        let raw_output = result.metadata.unwrap();
+        assert_eq!(raw_output["stdout"], "one\ntwo\nthree\n");
+        assert_eq!(raw_output["stderr"], "");
+    }
+
+    #[tokio::test]
+    async fn test_bash_summarizes_test_output() {
+        let server = AcpMcpServer::new("test-server", "1.0.0");
+        server.set_cwd(std::env::temp_dir());
+        server.set_session_id("test-session");
+
+        let result = server
+            .execute_tool(
+                "Bash",
+                serde_json::json!({
+                    "command": "printf 'test a ... ok\\ntest b ... FAILED\\n\\ntest result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured\\n'; exit 101"
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(result.is_error);
+        assert!(
+            result
+                .content
+                .ends_with("cargo test: 1 passed, 1 failed (b)"),
+            "got: {}",
+            result.content
+        );
+        let test_results = &result.metadata.unwrap()["test_results"];
+        assert_eq!(test_results["framework"], "cargo");
+        assert_eq!(test_results["failures"][0]["name"], "b");
+    }
+
+    #[tokio::test]
+    async fn test_bash_runs_in_requested_cwd() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        let server = AcpMcpServer::new("test-server", "1.0.0");
+        server.set_cwd(temp_dir.path());
+        server.set_session_id("test-session");
+
+        let result = server
+            .execute_tool(
+                "Bash",
+                serde_json::json!({"command": "pwd", "cwd": "sub"}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "got: {}", result.content);
+        assert!(result.content.trim_end().ends_with("sub"));
+
+        let result = server
+            .execute_tool(
+                "Bash",
+                serde_json::json!({"command": "pwd", "cwd": "../.."}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert!(result.content.contains("outside the workspace"));
+    }
+
+    #[tokio::test]
+    async fn test_read_output_in_chunks() {
+        let output = "Compiling ✓ 50%\rCompiling ✓ 100%\nPassword: ";
+        let collected = read_output(output.as_bytes(), Arc::new(OutputActivity::new()), None).await;
+        assert_eq!(collected, output);
+
+        // Characters split between chunks are decoded once complete
+        let bytes = "a✓b".as_bytes();
+        let mut pending = Vec::new();
+        assert_eq!(decode_output_chunk(&mut pending, &bytes[..2]), "a");
+        assert_eq!(decode_output_chunk(&mut pending, &bytes[2..3]), "");
+        assert_eq!(decode_output_chunk(&mut pending, &bytes[3..]), "✓b");
+        assert!(pending.is_empty());
+
+        // Invalid bytes are replaced rather than held back
+        assert_eq!(decode_output_chunk(&mut pending, b"\xFFok"), "\u{FFFD}ok");
+    }
+
+    #[test]
+    fn test_bash_timeout_warning_ms() {
+        assert_eq!(bash_timeout_warning_ms(120_000, 80), Some(96_000));
+        assert_eq!(bash_timeout_warning_ms(120_000, 0), None);
+        assert_eq!(bash_timeout_warning_ms(120_000, 100), None);
+        assert_eq!(bash_timeout_warning_ms(u64::MAX, 80), None);
+    }
+
+    #[tokio::test]
+    async fn test_bash_warns_before_timeout() {
+        let server = AcpMcpServer::new("test-server", "1.0.0");
+        server.set_cwd(std::env::temp_dir());
+        server.set_session_id("test-session");
+        server.set_bash_timeout_warning_percent(50);
+
+        let result = server
+            .execute_tool(
+                "Bash",
+                serde_json::json!({
+                    "command": "sleep 5",
+                    "timeout": 400
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(result.is_error);
+        assert!(
+            result.content.starts_with("Command timed out after 400ms"),
+            "got: {}",
+            result.content
+        );
+        // The warning fired halfway, before the command was killed
+        assert_eq!(result.metadata.unwrap()["timeout_warning_ms"], 200);
+
+        // A command finishing in time is never warned about
+        let result = server
+            .execute_tool(
+                "Bash",
+                serde_json::json!({
+                    "command": "echo quick",
+                    "timeout": 10_000
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error);
+        assert!(result.metadata.unwrap()["timeout_warning_ms"].is_null());
+    }
+
+    #[test]
+    fn test_bash_timeout_uses_configured_default() {
+        let defaults = ToolTimeouts::default();
+        assert_eq!(bash_timeout_ms(None, &defaults), 120_000);
+        assert_eq!(bash_timeout_ms(Some(900_000), &defaults), 600_000);
+
+        let configured = ToolTimeouts {
+            per_tool: [("Bash".to_string(), Duration::from_secs(900))].into(),
+            ..ToolTimeouts::default()
+        };
+        assert_eq!(bash_timeout_ms(None, &configured), 900_000);
+        assert_eq!(bash_timeout_ms(Some(5_000), &configured), 5_000);
+        assert_eq!(bash_timeout_ms(Some(1_200_000), &configured), 900_000);
+
+        let unlimited = ToolTimeouts {
+            per_tool: [("Bash".to_string(), Duration::ZERO)].into(),
+            ..ToolTimeouts::default()
+        };
+        assert_eq!(bash_timeout_ms(None, &unlimited), u64::MAX);
+    }
+
+    #[tokio::test]
+    async fn test_execute_rejects_oversized_arguments() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let server = AcpMcpServer::new("test-server", "1.0.0");
+        server.set_cwd(temp_dir.path());
+        server.set_session_id("test-session");
+        server.set_max_argument_bytes(1024);
+
+        let file_path = temp_dir.path().join("huge.txt");
+        let result = server
+            .execute_tool(
+                "Write",
+                serde_json::json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "content": "x".repeat(2048)
+                }),
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(result.is_error);
+        assert!(
+            result
+                .content
+                .starts_with("Arguments of Write are too large"),
+            "got: {}",
+            result.content
+        );
+        assert!(result.content.contains("in chunks"));
+        assert!(!file_path.exists(), "Oversized Write must not run");
+
+        // Other tools are not told to write files
+        let result = server
+            .execute_tool(
+                "Bash",
+                serde_json::json!({"command": format!("echo {}", "x".repeat(2048))}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(result.is_error);
+        assert!(
+            result
+                .content
+                .starts_with("Arguments of Bash are too large"),
+            "got: {}",
+            result.content
+        );
+        assert!(result.content.contains("smaller Bash calls"));
+        assert!(!result.content.contains("Edit"));
+    }
+
+    #[tokio::test]
+    async fn test_bash_repeat_runs_in_requested_cwd() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        let server = AcpMcpServer::new("test-server", "1.0.0");
+        server.set_cwd(temp_dir.path());
+        server.set_session_id("test-session");
+
+        let result = server
+            .execute_tool(
+                "Bash",
+                serde_json::json!({"command": "test -d ../sub", "cwd": "sub", "repeat": 2}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "got: {}", result.content);
+        assert_eq!(result.metadata.unwrap()["runs"], 2);
+    }
+
+    #[tokio::test]
+    async fn test_bash_completion_carries_raw_output() {
+        let server = AcpMcpServer::new("test-server", "1.0.0");
+        server.set_cwd(std::env::temp_dir());
+        server.set_session_id("test-session");
+
+        server.set_combine_streams(false);
+
+        let result = server
+            .execute_bash_tool(
+                serde_json::json!({"command": "echo out; echo err >&2; exit 3"}),
+                Some("tool-1"),
+                &ToolContext::new("test-session", std::env::temp_dir()),
+            )
+            .await
+            .unwrap();
+        assert!(result.is_error);
+
+        let update = AcpMcpServer::bash_completion_update("tool-1", "term-1", &result);
+        assert_eq!(update.fields.status, Some(ToolCallStatus::Completed));
+        let raw_output = update.fields.raw_output.expect("raw_output should be set");
+        assert_eq!(raw_output["command"], "echo out; echo err >&2; exit 3");
+        assert_eq!(raw_output["exit_code"], 3);
+        assert_eq!(raw_output["stdout"], "out\n");
+        assert_eq!(raw_output["stderr"], "err\n");
+        assert!(raw_output["duration_ms"].is_u64());
+        assert_eq!(
+            update.meta.unwrap()["terminal_exit"]["terminal_id"],
+            "term-1"
+        );
+    }
+
+    #[test]
+    fn test_completion_update_carries_locations() {
+        let result = ToolResult::success("a.rs:3:needle")
+            .with_locations(vec![crate::types::ToolCallLocation::with_line("a.rs", 3)]);
+        let update = AcpMcpServer::completion_update("tool-1", &result);
+        assert_eq!(update.fields.status, Some(ToolCallStatus::Completed));
+        assert!(update.fields.content.is_none());
+        let locations = update.fields.locations.expect("locations should be set");
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].path, std::path::PathBuf::from("a.rs"));
+        assert_eq!(locations[0].line, Some(3));
+
+        let update = AcpMcpServer::completion_update("tool-1", &ToolResult::error("boom"));
+        assert_eq!(update.fields.status, Some(ToolCallStatus::Failed));
+        assert!(update.fields.content.is_some());
+        assert!(update.fields.locations.is_none());
+    }
+
+    #[test]
+    fn test_tools_call_response_includes_image() {
+        let response = AcpMcpServer::tools_call_response(&ToolResult::success("text only"));
+        assert_eq!(response["content"].as_array().unwrap().len(), 1);
+
+        let result = ToolResult::success("Image: a.png").with_image("image/png", "aGVsbG8=");
+        let response = AcpMcpServer::tools_call_response(&result);
+        assert_eq!(response["content"][0]["text"], "Image: a.png");
+        assert_eq!(
+            response["content"][1],
+            serde_json::json!({"type": "image", "data": "aGVsbG8=", "mimeType": "image/png"})
+        );
+        assert_eq!(response["is_error"], false);
+    }
+
     #[tokio::test]
     async fn test_execute_glob_tool() {
         let server = AcpMcpServer::new("test-server", "1.0.0");
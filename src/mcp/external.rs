@@ -2,8 +2,8 @@
 //!
 //! Supports connecting to external MCP servers for extended tool capabilities.
 
-use std::collections::HashMap;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -18,6 +18,7 @@ use tracing::{Span, instrument};
 use process_wrap::tokio::*;
 
 use super::registry::{ToolResult, ToolSchema};
+use super::sse::SseConnection;
 use crate::session::WrappedChild;
 
 /// Default timeout for MCP requests (3 minutes)
@@ -27,6 +28,21 @@ const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(180);
 /// Default timeout for MCP initialization (60 seconds, MCP servers may need time to start)
 const DEFAULT_INIT_TIMEOUT: Duration = Duration::from_secs(60);
 
+/// Default number of attempts to restart a crashed server
+const DEFAULT_MAX_RETRIES: u32 = 3;
+
+/// Default delay before the first restart attempt
+const DEFAULT_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// How to spawn a stdio server again after it crashes
+#[derive(Debug, Clone)]
+struct StdioLaunch {
+    command: String,
+    args: Vec<String>,
+    env: Option<HashMap<String, String>>,
+    cwd: Option<PathBuf>,
+}
+
 /// External MCP server connection type
 pub enum McpConnection {
     /// Stdio-based connection (spawned process)
@@ -39,6 +55,8 @@ pub enum McpConnection {
         /// Reader to receive messages
         stdout: BufReader<ChildStdout>,
     },
+    /// HTTP+SSE connection (hosted server)
+    Sse(SseConnection),
 }
 
 /// External MCP server state
@@ -48,8 +66,14 @@ pub struct ExternalMcpServer {
     pub name: String,
     /// Connection to the server
     connection: McpConnection,
+    /// How the server was spawned (None for hosted servers)
+    launch: Option<StdioLaunch>,
+    /// Whether the connection is still usable (false once the server exits)
+    connected: bool,
     /// Available tools from this server
     tools: Vec<ToolSchema>,
+    /// Tools annotated as read-only or idempotent, safe to call again
+    replayable_tools: HashSet<String>,
     /// Whether the server is initialized
     initialized: bool,
     /// Request ID counter for JSON-RPC
@@ -220,7 +244,70 @@ impl ExternalMcpServer {
         Ok(Self {
             name,
             connection,
+            launch: Some(StdioLaunch {
+                command: command.to_string(),
+                args: args.to_vec(),
+                env: env.cloned(),
+                cwd: cwd.map(Path::to_path_buf),
+            }),
+            connected: true,
+            tools: Vec::new(),
+            replayable_tools: HashSet::new(),
+            initialized: false,
+            request_id: AtomicU64::new(1),
+            total_requests: AtomicU64::new(0),
+            total_request_time_ms: AtomicU64::new(0),
+            connected_at: Some(start_time),
+            initialized_at: None,
+        })
+    }
+
+    /// Connect to a hosted MCP server over HTTP+SSE
+    ///
+    /// Opens the server's event stream and waits for its message endpoint.
+    /// Use `initialize()` after connecting to complete the handshake.
+    #[instrument(
+        name = "mcp_connect_sse",
+        skip(headers),
+        fields(server_name = %name, url = %url, header_count = headers.len())
+    )]
+    pub async fn connect_sse(
+        name: String,
+        url: &str,
+        headers: &HashMap<String, String>,
+    ) -> Result<Self, ExternalMcpError> {
+        let start_time = Instant::now();
+
+        tracing::info!(
+            server_name = %name,
+            url = %url,
+            "Connecting to external MCP server over SSE"
+        );
+
+        #[allow(clippy::cast_possible_truncation)]
+        let connection = tokio::time::timeout(
+            DEFAULT_INIT_TIMEOUT,
+            SseConnection::connect(&name, url, headers),
+        )
+        .await
+        .map_err(|_| ExternalMcpError::Timeout {
+            operation: "connect".to_string(),
+            timeout_ms: DEFAULT_INIT_TIMEOUT.as_millis() as u64,
+        })??;
+
+        tracing::info!(
+            server_name = %name,
+            elapsed_ms = start_time.elapsed().as_millis(),
+            "MCP server SSE stream connected"
+        );
+
+        Ok(Self {
+            name,
+            connection: McpConnection::Sse(connection),
+            launch: None,
+            connected: true,
             tools: Vec::new(),
+            replayable_tools: HashSet::new(),
             initialized: false,
             request_id: AtomicU64::new(1),
             total_requests: AtomicU64::new(0),
@@ -334,6 +421,11 @@ impl ExternalMcpServer {
                             })
                         })
                         .collect();
+                    self.replayable_tools = tools
+                        .iter()
+                        .filter(|t| is_replayable(t))
+                        .filter_map(|t| Some(t.get("name")?.as_str()?.to_string()))
+                        .collect();
 
                     // Log tool names
                     let tool_names: Vec<&str> =
@@ -401,6 +493,7 @@ impl ExternalMcpServer {
     /// Send a JSON-RPC request and wait for response (with timeout)
     ///
     /// This is the public API that wraps the internal method with a timeout.
+    /// A zero `timeout` waits indefinitely.
     #[instrument(
         name = "mcp_send_request",
         skip(self, request),
@@ -413,26 +506,36 @@ impl ExternalMcpServer {
     async fn send_request(
         &mut self,
         request: JsonRpcRequest,
+        timeout: Duration,
     ) -> Result<JsonRpcResponse, ExternalMcpError> {
         let method = request.method.clone();
         let request_id = request.id;
 
-        let result =
-            tokio::time::timeout(DEFAULT_REQUEST_TIMEOUT, self.send_request_internal(request))
-                .await;
+        let result = if timeout.is_zero() {
+            Ok(self.send_request_internal(request).await)
+        } else {
+            tokio::time::timeout(timeout, self.send_request_internal(request)).await
+        };
 
-        if let Ok(inner_result) = result { inner_result } else {
+        if let Ok(inner_result) = result {
+            if let Err(e) = &inner_result
+                && e.is_disconnected()
+            {
+                self.connected = false;
+            }
+            inner_result
+        } else {
             tracing::error!(
                 server_name = %self.name,
                 method = %method,
                 request_id = request_id,
-                timeout_ms = DEFAULT_REQUEST_TIMEOUT.as_millis(),
+                timeout_ms = timeout.as_millis(),
                 "MCP request timed out"
             );
             #[allow(clippy::cast_possible_truncation)]
             Err(ExternalMcpError::Timeout {
                 operation: method,
-                timeout_ms: DEFAULT_REQUEST_TIMEOUT.as_millis() as u64,
+                timeout_ms: timeout.as_millis() as u64,
             })
         }
     }
@@ -446,8 +549,6 @@ impl ExternalMcpServer {
         let method = request.method.clone();
         let request_id = request.id;
 
-        let McpConnection::Stdio { stdin, stdout, .. } = &mut self.connection;
-
         // Serialize and send request
         let request_json = serde_json::to_string(&request)
             .map_err(|e| ExternalMcpError::SerializationError(e.to_string()))?;
@@ -460,6 +561,21 @@ impl ExternalMcpServer {
             "Sending JSON-RPC request to MCP server"
         );
 
+        let (stdin, stdout) = match &mut self.connection {
+            McpConnection::Stdio { stdin, stdout, .. } => (stdin, stdout),
+            McpConnection::Sse(sse) => {
+                let line = sse.request(request_id, &request_json).await?;
+                return self.finish_request(
+                    &method,
+                    request_id,
+                    &request_json,
+                    &line,
+                    start_time,
+                    None,
+                );
+            }
+        };
+
         stdin
             .write_all(request_json.as_bytes())
             .await
@@ -473,16 +589,16 @@ impl ExternalMcpServer {
                     error_kind = ?e.kind(),
                     "Failed to write request to MCP server"
                 );
-                ExternalMcpError::WriteError(e.to_string())
+                write_error(&self.name, &e)
             })?;
         stdin
             .write_all(b"\n")
             .await
-            .map_err(|e| ExternalMcpError::WriteError(e.to_string()))?;
+            .map_err(|e| write_error(&self.name, &e))?;
         stdin
             .flush()
             .await
-            .map_err(|e| ExternalMcpError::WriteError(e.to_string()))?;
+            .map_err(|e| write_error(&self.name, &e))?;
 
         let write_elapsed = start_time.elapsed();
         tracing::debug!(
@@ -494,7 +610,7 @@ impl ExternalMcpServer {
 
         // Read response
         let mut line = String::new();
-        stdout.read_line(&mut line).await.map_err(|e| {
+        let bytes_read = stdout.read_line(&mut line).await.map_err(|e| {
             tracing::error!(
                 server_name = %self.name,
                 method = %method,
@@ -503,7 +619,40 @@ impl ExternalMcpServer {
             );
             ExternalMcpError::ReadError(e.to_string())
         })?;
+        if bytes_read == 0 {
+            tracing::error!(
+                server_name = %self.name,
+                method = %method,
+                "MCP server closed its output before responding"
+            );
+            return Err(ExternalMcpError::DisconnectedAfterRequest(
+                self.name.clone(),
+            ));
+        }
 
+        self.finish_request(
+            &method,
+            request_id,
+            &request_json,
+            &line,
+            start_time,
+            Some(write_elapsed),
+        )
+    }
+
+    /// Record statistics for a completed request and parse its response
+    ///
+    /// `write_elapsed` is the time spent sending the request, when the
+    /// transport measures it separately.
+    fn finish_request(
+        &self,
+        method: &str,
+        request_id: u64,
+        request_json: &str,
+        line: &str,
+        start_time: Instant,
+        write_elapsed: Option<Duration>,
+    ) -> Result<JsonRpcResponse, ExternalMcpError> {
         let total_elapsed = start_time.elapsed();
 
         // Update statistics
@@ -521,7 +670,7 @@ impl ExternalMcpServer {
             "Received response from MCP server"
         );
 
-        let response: JsonRpcResponse = serde_json::from_str(&line).map_err(|e| {
+        let response: JsonRpcResponse = serde_json::from_str(line).map_err(|e| {
             tracing::error!(
                 server_name = %self.name,
                 method = %method,
@@ -532,6 +681,7 @@ impl ExternalMcpServer {
             ExternalMcpError::DeserializationError(e.to_string())
         })?;
 
+        let write_elapsed = write_elapsed.unwrap_or_default();
         let read_elapsed = total_elapsed.saturating_sub(write_elapsed);
 
         // Comprehensive performance summary
@@ -580,8 +730,6 @@ impl ExternalMcpServer {
         method: &str,
         params: Option<serde_json::Value>,
     ) -> Result<(), ExternalMcpError> {
-        let McpConnection::Stdio { stdin, .. } = &mut self.connection;
-
         let notification = serde_json::json!({
             "jsonrpc": "2.0",
             "method": method,
@@ -591,18 +739,23 @@ impl ExternalMcpServer {
         let notification_json = serde_json::to_string(&notification)
             .map_err(|e| ExternalMcpError::SerializationError(e.to_string()))?;
 
+        let stdin = match &mut self.connection {
+            McpConnection::Stdio { stdin, .. } => stdin,
+            McpConnection::Sse(sse) => return sse.notify(&notification_json).await,
+        };
+
         stdin
             .write_all(notification_json.as_bytes())
             .await
-            .map_err(|e| ExternalMcpError::WriteError(e.to_string()))?;
+            .map_err(|e| write_error(&self.name, &e))?;
         stdin
             .write_all(b"\n")
             .await
-            .map_err(|e| ExternalMcpError::WriteError(e.to_string()))?;
+            .map_err(|e| write_error(&self.name, &e))?;
         stdin
             .flush()
             .await
-            .map_err(|e| ExternalMcpError::WriteError(e.to_string()))?;
+            .map_err(|e| write_error(&self.name, &e))?;
 
         Ok(())
     }
@@ -610,6 +763,18 @@ impl ExternalMcpServer {
     /// Call a tool on this server
     ///
     /// Executes a tool on the external MCP server with timeout protection.
+    pub async fn call_tool(
+        &mut self,
+        tool_name: &str,
+        arguments: serde_json::Value,
+    ) -> Result<ToolResult, ExternalMcpError> {
+        self.call_tool_with_timeout(tool_name, arguments, DEFAULT_REQUEST_TIMEOUT)
+            .await
+    }
+
+    /// Call a tool on this server, giving up after `timeout`
+    ///
+    /// A zero `timeout` waits for the result indefinitely.
     #[instrument(
         name = "mcp_call_tool",
         skip(self, arguments),
@@ -617,12 +782,14 @@ impl ExternalMcpServer {
             server_name = %self.name,
             tool_name = %tool_name,
             args_size = arguments.to_string().len(),
+            timeout_ms = timeout.as_millis(),
         )
     )]
-    pub async fn call_tool(
+    pub async fn call_tool_with_timeout(
         &mut self,
         tool_name: &str,
         arguments: serde_json::Value,
+        timeout: Duration,
     ) -> Result<ToolResult, ExternalMcpError> {
         let start_time = Instant::now();
 
@@ -651,7 +818,7 @@ impl ExternalMcpServer {
             })),
         );
 
-        let response = self.send_request(request).await?;
+        let response = self.send_request(request, timeout).await?;
 
         let elapsed = start_time.elapsed();
 
@@ -741,6 +908,44 @@ impl ExternalMcpServer {
         self.initialized
     }
 
+    /// Check if a tool can be called again without repeating side effects
+    ///
+    /// True when the server annotates the tool with `readOnlyHint` or
+    /// `idempotentHint`.
+    pub fn is_replayable(&self, tool_name: &str) -> bool {
+        self.replayable_tools.contains(tool_name)
+    }
+
+    /// Check if the server is still reachable
+    ///
+    /// Becomes false once a request finds the server's process gone.
+    pub fn is_connected(&self) -> bool {
+        self.connected
+    }
+
+    /// Spawn a crashed stdio server again and redo the MCP handshake
+    ///
+    /// Only replaces this server's connection once the new process has
+    /// initialized, so a failed attempt can be retried.
+    pub async fn respawn(&mut self) -> Result<(), ExternalMcpError> {
+        let Some(launch) = self.launch.clone() else {
+            return Err(ExternalMcpError::ServerDisconnected(self.name.clone()));
+        };
+
+        let mut server = Self::connect_stdio(
+            self.name.clone(),
+            &launch.command,
+            &launch.args,
+            launch.env.as_ref(),
+            launch.cwd.as_deref(),
+        )
+        .await?;
+        server.initialize().await?;
+
+        *self = server;
+        Ok(())
+    }
+
     /// Cleanup the MCP server process and wait for exit
     ///
     /// This kills the process group and waits for the process to exit,
@@ -751,7 +956,14 @@ impl ExternalMcpServer {
         fields(server_name = %self.name)
     )]
     pub async fn cleanup(&mut self) -> Result<(), ExternalMcpError> {
-        let McpConnection::Stdio { child, .. } = &mut self.connection;
+        let child = match &mut self.connection {
+            McpConnection::Stdio { child, .. } => child,
+            McpConnection::Sse(sse) => {
+                sse.close();
+                tracing::info!(server_name = %self.name, "Closed MCP server SSE stream");
+                return Ok(());
+            }
+        };
 
         let start_time = Instant::now();
 
@@ -783,9 +995,11 @@ impl ExternalMcpServer {
 /// Note: We can't wait in Drop, so we only start the kill.
 impl Drop for ExternalMcpServer {
     fn drop(&mut self) {
-        // Best-effort cleanup (can't wait in Drop)
-        let McpConnection::Stdio { child, .. } = &mut self.connection;
-        drop(child.start_kill());
+        // Best-effort cleanup (can't wait in Drop); SSE connections stop
+        // their reader when dropped
+        if let McpConnection::Stdio { child, .. } = &mut self.connection {
+            drop(child.start_kill());
+        }
     }
 }
 
@@ -796,6 +1010,8 @@ pub struct ExternalMcpManager {
     /// Using DashMap for lock-free concurrent access to different servers
     /// Using tokio::sync::Mutex to allow holding lock across .await points
     servers: DashMap<String, Arc<tokio::sync::Mutex<ExternalMcpServer>>>,
+    /// How crashed servers are restarted
+    retry_policy: RetryPolicy,
 }
 
 impl ExternalMcpManager {
@@ -803,7 +1019,64 @@ impl ExternalMcpManager {
     pub fn new() -> Self {
         Self {
             servers: DashMap::new(),
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Set how crashed stdio servers are restarted
+    ///
+    /// A server whose process exits is spawned again up to `max_retries`
+    /// times, waiting `base_delay` before the first attempt and doubling
+    /// the wait for each further one. Zero retries disables restarts.
+    pub fn with_retry_policy(mut self, max_retries: u32, base_delay: Duration) -> Self {
+        self.retry_policy = RetryPolicy {
+            max_retries,
+            base_delay,
+        };
+        self
+    }
+
+    /// Restart a crashed server according to the retry policy
+    ///
+    /// Called with the server's lock held, so tool calls issued meanwhile
+    /// wait for the restart instead of failing.
+    async fn restart_server(&self, server: &mut ExternalMcpServer) -> Result<(), ExternalMcpError> {
+        let mut last_error = ExternalMcpError::ServerDisconnected(server.name.clone());
+
+        for attempt in 0..self.retry_policy.max_retries {
+            let delay = self.retry_policy.delay(attempt);
+            tracing::warn!(
+                server_name = %server.name,
+                attempt = attempt + 1,
+                max_retries = self.retry_policy.max_retries,
+                delay_ms = delay.as_millis(),
+                "Restarting crashed external MCP server"
+            );
+            tokio::time::sleep(delay).await;
+
+            match server.respawn().await {
+                Ok(()) => {
+                    tracing::info!(
+                        server_name = %server.name,
+                        attempt = attempt + 1,
+                        tool_count = server.tools().len(),
+                        "External MCP server restarted"
+                    );
+                    return Ok(());
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        server_name = %server.name,
+                        attempt = attempt + 1,
+                        error = %e,
+                        "Failed to restart external MCP server"
+                    );
+                    last_error = e;
+                }
+            }
         }
+
+        Err(last_error)
     }
 
     /// Connect to an MCP server
@@ -877,6 +1150,38 @@ impl ExternalMcpManager {
         Ok(())
     }
 
+    /// Connect to a hosted MCP server over HTTP+SSE
+    ///
+    /// Opens the server's event stream and performs the MCP handshake
+    /// (initialize + tools/list).
+    #[instrument(
+        name = "mcp_manager_connect_sse",
+        skip(self, headers),
+        fields(server_name = %name, url = %url)
+    )]
+    pub async fn connect_sse(
+        &self,
+        name: String,
+        url: &str,
+        headers: &HashMap<String, String>,
+    ) -> Result<(), ExternalMcpError> {
+        let start_time = Instant::now();
+
+        let mut server = ExternalMcpServer::connect_sse(name.clone(), url, headers).await?;
+        server.initialize().await?;
+
+        tracing::info!(
+            server_name = %name,
+            tool_count = server.tools().len(),
+            total_elapsed_ms = start_time.elapsed().as_millis(),
+            "Successfully connected and initialized MCP server over SSE"
+        );
+
+        self.servers
+            .insert(name, Arc::new(tokio::sync::Mutex::new(server)));
+        Ok(())
+    }
+
     /// Disconnect from an MCP server
     ///
     /// This properly cleans up the server process and prevents zombie processes.
@@ -934,6 +1239,19 @@ impl ExternalMcpManager {
     /// Call a tool on an external server
     ///
     /// Tool name should be prefixed with `mcp__<server>__`
+    pub async fn call_tool(
+        &self,
+        full_tool_name: &str,
+        arguments: serde_json::Value,
+    ) -> Result<ToolResult, ExternalMcpError> {
+        self.call_tool_with_timeout(full_tool_name, arguments, None)
+            .await
+    }
+
+    /// Call a tool on an external server with a configured timeout
+    ///
+    /// `timeout` of None uses the default request timeout (180 seconds);
+    /// a zero duration waits for the result indefinitely.
     #[instrument(
         name = "mcp_manager_call_tool",
         skip(self, arguments),
@@ -941,11 +1259,14 @@ impl ExternalMcpManager {
             full_tool_name = %full_tool_name,
         )
     )]
-    pub async fn call_tool(
+    pub async fn call_tool_with_timeout(
         &self,
         full_tool_name: &str,
         arguments: serde_json::Value,
+        timeout: Option<Duration>,
     ) -> Result<ToolResult, ExternalMcpError> {
+        let timeout = timeout.unwrap_or(DEFAULT_REQUEST_TIMEOUT);
+
         // Parse server name and tool name from `mcp__<server>__<tool>`
         let parts: Vec<&str> = full_tool_name.splitn(3, "__").collect();
         if parts.len() != 3 || parts[0] != "mcp" {
@@ -993,7 +1314,34 @@ impl ExternalMcpManager {
         // tokio::sync::Mutex allows holding lock across .await points
         let result = {
             let mut server_guard = server.lock().await;
-            server_guard.call_tool(tool_name, arguments).await?
+            if !server_guard.is_connected() {
+                self.restart_server(&mut server_guard).await?;
+            }
+            match server_guard
+                .call_tool_with_timeout(tool_name, arguments.clone(), timeout)
+                .await
+            {
+                Err(e) if e.is_disconnected() => {
+                    // A call the server may have received is only sent again
+                    // when the tool says repeating it is harmless
+                    let replay = matches!(e, ExternalMcpError::ServerDisconnected(_))
+                        || server_guard.is_replayable(tool_name);
+                    tracing::warn!(
+                        server_name = %server_name,
+                        tool_name = %tool_name,
+                        replay,
+                        "External MCP server exited during tool call"
+                    );
+                    self.restart_server(&mut server_guard).await?;
+                    if !replay {
+                        return Err(e);
+                    }
+                    server_guard
+                        .call_tool_with_timeout(tool_name, arguments, timeout)
+                        .await?
+                }
+                result => result?,
+            }
         };
 
         let elapsed = start_time.elapsed();
@@ -1129,6 +1477,10 @@ pub enum ExternalMcpError {
     #[error("Failed to spawn MCP server '{command}': {error}")]
     SpawnFailed { command: String, error: String },
 
+    /// Failed to connect to a hosted MCP server
+    #[error("Failed to connect to MCP server at '{url}': {error}")]
+    ConnectFailed { url: String, error: String },
+
     /// No stdin available
     #[error("No stdin available for MCP server")]
     NoStdin,
@@ -1172,6 +1524,71 @@ pub enum ExternalMcpError {
     /// Request or operation timed out
     #[error("MCP operation '{operation}' timed out after {timeout_ms}ms")]
     Timeout { operation: String, timeout_ms: u64 },
+
+    /// The server's process exited or closed its pipes
+    #[error("MCP server '{0}' disconnected")]
+    ServerDisconnected(String),
+
+    /// The server's process exited after a request was sent, before answering
+    ///
+    /// The server may already have acted on the request.
+    #[error("MCP server '{0}' disconnected before responding")]
+    DisconnectedAfterRequest(String),
+}
+
+impl ExternalMcpError {
+    /// Whether the error means the server is gone and needs restarting
+    pub fn is_disconnected(&self) -> bool {
+        matches!(
+            self,
+            Self::ServerDisconnected(_) | Self::DisconnectedAfterRequest(_)
+        )
+    }
+}
+
+/// Whether a `tools/list` entry is annotated as safe to call again
+fn is_replayable(tool: &serde_json::Value) -> bool {
+    let hint = |name: &str| {
+        tool.pointer(&format!("/annotations/{name}"))
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false)
+    };
+    hint("readOnlyHint") || hint("idempotentHint")
+}
+
+/// Map a failed write to a server's stdin, treating a closed pipe as a disconnect
+fn write_error(server_name: &str, e: &std::io::Error) -> ExternalMcpError {
+    if e.kind() == std::io::ErrorKind::BrokenPipe {
+        ExternalMcpError::ServerDisconnected(server_name.to_string())
+    } else {
+        ExternalMcpError::WriteError(e.to_string())
+    }
+}
+
+/// Restart policy for crashed external MCP servers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct RetryPolicy {
+    /// Restart attempts before giving up
+    max_retries: u32,
+    /// Delay before the first attempt, doubled for each further one
+    base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_MAX_RETRIES,
+            base_delay: DEFAULT_RETRY_BASE_DELAY,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the given (zero-based) restart attempt
+    fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(1u32.checked_shl(attempt).unwrap_or(u32::MAX))
+    }
 }
 
 #[cfg(test)]
@@ -1265,6 +1682,204 @@ mod tests {
         );
     }
 
+    /// Serve a minimal HTTP+SSE MCP server exposing one `echo` tool
+    ///
+    /// Instead of answering `tools/call`, the server drops the event stream.
+    /// Returns the URL of the event stream.
+    async fn spawn_mock_sse_server() -> String {
+        use tokio::io::AsyncReadExt;
+        use tokio::net::{TcpListener, TcpStream};
+
+        async fn read_request(socket: &mut TcpStream) -> (String, Vec<u8>) {
+            let mut buf = Vec::new();
+            let mut chunk = [0u8; 4096];
+            let header_end = loop {
+                if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break pos + 4;
+                }
+                let n = socket.read(&mut chunk).await.unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+            };
+            let head = String::from_utf8_lossy(&buf[..header_end]).to_lowercase();
+            let content_length = head
+                .lines()
+                .find_map(|l| l.strip_prefix("content-length:"))
+                .map_or(0, |v| v.trim().parse::<usize>().unwrap());
+            while buf.len() < header_end + content_length {
+                let n = socket.read(&mut chunk).await.unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+            }
+            (head, buf[header_end..].to_vec())
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let mut stream: Option<TcpStream> = None;
+            loop {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let (head, body) = read_request(&mut socket).await;
+
+                if head.starts_with("get") {
+                    socket
+                        .write_all(
+                            b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\n\r\n\
+                              event: endpoint\ndata: /messages\n\n",
+                        )
+                        .await
+                        .unwrap();
+                    stream = Some(socket);
+                    continue;
+                }
+
+                socket
+                    .write_all(
+                        b"HTTP/1.1 202 Accepted\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+                    )
+                    .await
+                    .unwrap();
+
+                let message: serde_json::Value = serde_json::from_slice(&body).unwrap();
+                let Some(id) = message.get("id") else {
+                    continue; // notification
+                };
+                let result = match message["method"].as_str().unwrap() {
+                    "initialize" => serde_json::json!({
+                        "protocolVersion": "2024-11-05",
+                        "capabilities": {},
+                        "serverInfo": {"name": "mock", "version": "1.0.0"}
+                    }),
+                    "tools/list" => serde_json::json!({
+                        "tools": [{"name": "echo", "description": "Echo input"}]
+                    }),
+                    _ => {
+                        stream = None;
+                        continue;
+                    }
+                };
+                let response = serde_json::json!({"jsonrpc": "2.0", "id": id, "result": result});
+                let event = format!("event: message\ndata: {response}\n\n");
+                let sse = stream.as_mut().unwrap();
+                sse.write_all(event.as_bytes()).await.unwrap();
+            }
+        });
+
+        format!("http://{addr}/sse")
+    }
+
+    #[tokio::test]
+    async fn test_sse_server_initializes_and_fails_on_dropped_stream() {
+        let url = spawn_mock_sse_server().await;
+        let manager = ExternalMcpManager::new();
+
+        manager
+            .connect_sse("mock".to_string(), &url, &HashMap::new())
+            .await
+            .unwrap();
+        let tools = manager.all_tools();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].description, "[mock] Echo input");
+
+        // The server drops the stream instead of answering
+        let result = tokio::time::timeout(
+            Duration::from_secs(10),
+            manager.call_tool("mcp__mock__echo", serde_json::json!({})),
+        )
+        .await
+        .expect("dropped SSE stream should not hang the call");
+        assert!(matches!(result, Err(ExternalMcpError::ReadError(_))));
+    }
+
+    /// Minimal stdio MCP server that exits on its first `tools/call`
+    ///
+    /// It leaves a `crashed` marker in its working directory, so the
+    /// restarted process answers instead.
+    #[cfg(unix)]
+    const CRASHING_MCP_SERVER: &str = r#"
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/^.*"id":\([0-9]*\).*$/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"protocolVersion":"2024-11-05","capabilities":{}}}\n' "$id" ;;
+    *'"method":"tools/list"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"tools":[{"name":"ping","annotations":{"readOnlyHint":true}},{"name":"touch"}]}}\n' "$id" ;;
+    *'"method":"tools/call"'*)
+      if [ ! -e crashed ]; then touch crashed; exit 1; fi
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"content":[{"type":"text","text":"pong"}]}}\n' "$id" ;;
+  esac
+done
+"#;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_crashed_stdio_server_is_restarted() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manager = ExternalMcpManager::new().with_retry_policy(2, Duration::from_millis(10));
+        manager
+            .connect(
+                "flaky".to_string(),
+                "sh",
+                &["-c".to_string(), CRASHING_MCP_SERVER.to_string()],
+                None,
+                Some(temp_dir.path()),
+            )
+            .await
+            .unwrap();
+
+        let result = manager
+            .call_tool("mcp__flaky__ping", serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(result.content, "pong");
+        assert!(temp_dir.path().join("crashed").exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_crash_mid_call_is_not_replayed_for_side_effecting_tools() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let manager = ExternalMcpManager::new().with_retry_policy(2, Duration::from_millis(10));
+        manager
+            .connect(
+                "flaky".to_string(),
+                "sh",
+                &["-c".to_string(), CRASHING_MCP_SERVER.to_string()],
+                None,
+                Some(temp_dir.path()),
+            )
+            .await
+            .unwrap();
+
+        // The server may have acted before crashing, so the call fails...
+        let result = manager
+            .call_tool("mcp__flaky__touch", serde_json::json!({}))
+            .await;
+        assert!(matches!(
+            result,
+            Err(ExternalMcpError::DisconnectedAfterRequest(_))
+        ));
+
+        // ...but the server was restarted for the next call
+        let result = manager
+            .call_tool("mcp__flaky__touch", serde_json::json!({}))
+            .await
+            .unwrap();
+        assert_eq!(result.content, "pong");
+    }
+
+    #[test]
+    fn test_retry_policy_backs_off_exponentially() {
+        let policy = RetryPolicy {
+            max_retries: 3,
+            base_delay: Duration::from_millis(100),
+        };
+        assert_eq!(policy.delay(0), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(400));
+        // Large attempt numbers saturate instead of overflowing
+        assert_eq!(policy.delay(40), Duration::from_millis(100) * u32::MAX);
+    }
+
     /// Test that disconnect properly cleans up MCP server processes
     ///
     /// This test verifies that:
@@ -0,0 +1,246 @@
+//! Detection of Bash commands waiting for interactive input
+//!
+//! A command that unexpectedly prompts (`Continue? [y/N]`, a password
+//! prompt, a pager) would otherwise block until the Bash timeout with no
+//! indication of why. While a command runs, its output activity is tracked;
+//! once it has been silent for a grace period while still alive with stdin
+//! open, it is treated as waiting for input and the configured
+//! [`InteractiveInputMode`] is applied.
+
+use std::process::ExitStatus;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::process::{Child, ChildStdin};
+
+/// Default silence before a command is considered to be waiting for input
+pub const DEFAULT_INPUT_GRACE_MS: u64 = 10_000;
+
+/// What to do when a command appears to be waiting for input
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InteractiveInputMode {
+    /// Close stdin so the command reads end-of-file
+    #[default]
+    Eof,
+    /// Answer `n` and close stdin
+    Decline,
+    /// Stop the command and report that it was waiting for input
+    Report,
+    /// Leave the command alone until it exits or times out
+    Off,
+}
+
+/// Interactive input handling for Bash commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InteractiveInputPolicy {
+    /// Action taken once a command appears to wait for input
+    pub mode: InteractiveInputMode,
+    /// Silence after which a running command is considered to wait for input
+    pub grace: Duration,
+}
+
+impl Default for InteractiveInputPolicy {
+    fn default() -> Self {
+        Self {
+            mode: InteractiveInputMode::default(),
+            grace: Duration::from_millis(DEFAULT_INPUT_GRACE_MS),
+        }
+    }
+}
+
+/// Action taken on a command that appeared to wait for input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputAction {
+    /// stdin was closed
+    ClosedStdin,
+    /// `n` was written to stdin before closing it
+    Declined,
+    /// The command was stopped
+    Stopped,
+}
+
+impl InputAction {
+    /// Note appended to the command output describing the action
+    pub fn describe(self, grace: Duration) -> String {
+        let idle = grace.as_secs_f64();
+        match self {
+            Self::ClosedStdin => format!(
+                "(Command appeared to wait for input after {:.1}s without output; stdin was closed)",
+                idle
+            ),
+            Self::Declined => format!(
+                "(Command appeared to wait for input after {:.1}s without output; answered \"n\")",
+                idle
+            ),
+            Self::Stopped => format!(
+                "Command appears to be waiting for input (no output for {:.1}s) and was stopped. \
+                 Re-run it non-interactively, e.g. with a --yes flag or by piping the input.",
+                idle
+            ),
+        }
+    }
+}
+
+/// Time of the last output from a running command
+#[derive(Debug)]
+pub struct OutputActivity {
+    start: Instant,
+    last_output_ms: AtomicU64,
+}
+
+impl Default for OutputActivity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OutputActivity {
+    /// Start tracking activity from now
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            last_output_ms: AtomicU64::new(0),
+        }
+    }
+
+    /// Record that the command produced output
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn touch(&self) {
+        self.last_output_ms
+            .store(self.start.elapsed().as_millis() as u64, Ordering::Relaxed);
+    }
+
+    /// Time since the command last produced output
+    pub fn idle(&self) -> Duration {
+        let last = Duration::from_millis(self.last_output_ms.load(Ordering::Relaxed));
+        self.start.elapsed().saturating_sub(last)
+    }
+}
+
+/// Wait for `child` to exit, applying `policy` if it goes silent with stdin open
+///
+/// Returns the exit status and the action taken, if any. With
+/// [`InteractiveInputMode::Report`] the command is killed, so the status
+/// reflects the kill.
+pub async fn wait_with_input_detection(
+    child: &mut Child,
+    stdin: Option<ChildStdin>,
+    activity: &OutputActivity,
+    policy: InteractiveInputPolicy,
+) -> (std::io::Result<ExitStatus>, Option<InputAction>) {
+    let Some(mut stdin) = stdin.filter(|_| policy.mode != InteractiveInputMode::Off) else {
+        return (child.wait().await, None);
+    };
+
+    loop {
+        let remaining = policy.grace.saturating_sub(activity.idle());
+        if remaining.is_zero() {
+            break;
+        }
+        tokio::select! {
+            status = child.wait() => return (status, None),
+            () = tokio::time::sleep(remaining) => {}
+        }
+    }
+
+    tracing::info!(
+        mode = ?policy.mode,
+        grace_ms = policy.grace.as_millis(),
+        "Command appears to be waiting for input"
+    );
+    let action = match policy.mode {
+        InteractiveInputMode::Decline => {
+            drop(stdin.write_all(b"n\n").await);
+            InputAction::Declined
+        }
+        InteractiveInputMode::Report => {
+            drop(child.start_kill());
+            InputAction::Stopped
+        }
+        InteractiveInputMode::Eof | InteractiveInputMode::Off => InputAction::ClosedStdin,
+    };
+    drop(stdin);
+
+    (child.wait().await, Some(action))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Stdio;
+    use std::sync::Arc;
+    use tokio::io::{AsyncBufReadExt, BufReader};
+    use tokio::process::Command;
+
+    /// Run `command`, tracking stdout activity, and return its output
+    async fn run(
+        command: &str,
+        mode: InteractiveInputMode,
+    ) -> (ExitStatus, Option<InputAction>, String) {
+        let policy = InteractiveInputPolicy {
+            mode,
+            grace: Duration::from_millis(200),
+        };
+        let mut child = Command::new("bash")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let stdin = child.stdin.take();
+        let stdout = child.stdout.take().unwrap();
+
+        let activity = Arc::new(OutputActivity::new());
+        let reader_activity = activity.clone();
+        let reader = tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            let mut collected = String::new();
+            while let Ok(Some(line)) = lines.next_line().await {
+                reader_activity.touch();
+                collected.push_str(&line);
+                collected.push('\n');
+            }
+            collected
+        });
+
+        // Well below the time a hang would take to fail the test
+        let (status, action) = tokio::time::timeout(
+            Duration::from_secs(10),
+            wait_with_input_detection(&mut child, stdin, &activity, policy),
+        )
+        .await
+        .expect("command should not hang waiting for input");
+        (status.unwrap(), action, reader.await.unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_command_waiting_for_input_is_handled() {
+        let script = "echo 'Proceed? [y/N]'; read answer; echo \"answer=$answer\"";
+
+        let (status, action, output) = run(script, InteractiveInputMode::Eof).await;
+        assert!(status.success());
+        assert_eq!(action, Some(InputAction::ClosedStdin));
+        assert!(output.ends_with("answer=\n"));
+
+        let (_, action, output) = run(script, InteractiveInputMode::Decline).await;
+        assert_eq!(action, Some(InputAction::Declined));
+        assert!(output.ends_with("answer=n\n"));
+
+        let (status, action, _) = run(script, InteractiveInputMode::Report).await;
+        assert!(!status.success());
+        assert_eq!(action, Some(InputAction::Stopped));
+    }
+
+    #[tokio::test]
+    async fn test_chatty_command_is_left_alone() {
+        let script = "for i in 1 2 3 4 5; do echo $i; sleep 0.1; done";
+        let (status, action, output) = run(script, InteractiveInputMode::Report).await;
+        assert!(status.success());
+        assert_eq!(action, None);
+        assert_eq!(output, "1\n2\n3\n4\n5\n");
+    }
+}
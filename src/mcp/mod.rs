@@ -6,13 +6,13 @@
 //! ## Tool Categories
 //!
 //! - **File Tools**: Read, Write, Edit - File system operations
-//! - **Terminal Tools**: Bash, KillShell - Command execution
+//! - **Terminal Tools**: Bash, KillShell, ListShells - Command execution
 //! - **Search Tools**: Grep, Glob - Code search
 //!
 //! ## External MCP Servers
 //!
 //! The `external` module provides support for connecting to external MCP servers
-//! to extend tool capabilities.
+//! to extend tool capabilities, over stdio or HTTP+SSE.
 //!
 //! ## ACP Integration
 //!
@@ -21,12 +21,20 @@
 
 mod acp_server;
 mod external;
+mod interactive;
 mod registry;
+mod repair;
 mod server;
+mod sse;
 pub mod tools;
+mod validation;
 
 pub use acp_server::{AcpMcpServer, get_disallowed_tools};
 pub use external::{ExternalMcpError, ExternalMcpManager, ExternalMcpServer};
-pub use registry::{ACP_TOOL_PREFIX, ToolContext, ToolRegistry, ToolResult, ToolStatus};
+pub use interactive::{DEFAULT_INPUT_GRACE_MS, InteractiveInputMode, InteractiveInputPolicy};
+pub use registry::{
+    ACP_TOOL_PREFIX, DEFAULT_MAX_FILE_SIZE, DEFAULT_TOOL_TIMEOUT, ToolContext, ToolImage,
+    ToolRegistry, ToolResult, ToolStatus, ToolTimeouts,
+};
 pub use server::McpServer;
-pub use tools::Tool;
+pub use tools::{DomainFilter, EnvInfoConfig, FinalNewlinePolicy, Tool};
@@ -1,20 +1,27 @@
 //! Tool registry for managing MCP tools
 
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 use sacp::JrConnectionCx;
 use sacp::link::AgentToClient;
 use sacp::schema::{
-    SessionId, SessionNotification, SessionUpdate, Terminal, ToolCallContent, ToolCallId,
+    Meta, SessionId, SessionNotification, SessionUpdate, Terminal, ToolCallContent, ToolCallId,
     ToolCallStatus, ToolCallUpdate, ToolCallUpdateFields,
 };
 use serde::{Deserialize, Serialize};
 
-use super::tools::Tool;
-use crate::session::BackgroundProcessManager;
+use super::tools::workspace::{is_within, resolve};
+use super::tools::{DomainFilter, EnvInfoConfig, FileEncodings, FinalNewlinePolicy, Tool};
+use crate::session::{BackgroundExit, BackgroundProcessManager};
 use crate::settings::PermissionChecker;
 use crate::terminal::TerminalClient;
+use crate::types::ToolCallLocation;
+use crate::utils::truncate_long_lines;
 
 /// Tool execution result
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +35,24 @@ pub struct ToolResult {
     /// Additional metadata
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub metadata: Option<serde_json::Value>,
+    /// Locations the result points at, shown by the client for navigation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub locations: Vec<ToolCallLocation>,
+    /// Image returned alongside the text content (e.g. Read of a PNG)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<ToolImage>,
+}
+
+/// Base64-encoded image in a tool result
+///
+/// Sent to the model as MCP image content, which comes back in the tool
+/// result as an `ImageSource::Base64` block.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ToolImage {
+    /// MIME type, e.g. `image/png`
+    pub media_type: String,
+    /// Base64-encoded image data
+    pub data: String,
 }
 
 impl ToolResult {
@@ -38,6 +63,8 @@ impl ToolResult {
             content: content.into(),
             is_error: false,
             metadata: None,
+            locations: Vec::new(),
+            image: None,
         }
     }
 
@@ -48,14 +75,86 @@ impl ToolResult {
             content: message.into(),
             is_error: true,
             metadata: None,
+            locations: Vec::new(),
+            image: None,
         }
     }
 
+    /// Create an error result for a failed filesystem operation
+    ///
+    /// Permission and not-found errors get distinct messages. When `path`
+    /// is outside the working directory `cwd`, permission and read-only
+    /// errors suggest a path inside it. `action` describes the operation,
+    /// e.g. "writing".
+    pub fn io_error(action: &str, path: &Path, error: &std::io::Error, cwd: &Path) -> Self {
+        let path_display = path.display();
+        let hint = if is_within(path, &[resolve(cwd)]) {
+            ""
+        } else {
+            " — try a path inside the workspace"
+        };
+        let (kind, message) = match error.kind() {
+            ErrorKind::PermissionDenied => (
+                "permission_denied",
+                format!("Permission denied {action} {path_display}{hint}"),
+            ),
+            ErrorKind::ReadOnlyFilesystem => (
+                "read_only_filesystem",
+                format!("Read-only filesystem {action} {path_display}{hint}"),
+            ),
+            ErrorKind::NotFound => (
+                "not_found",
+                format!("Path not found while {action} {path_display}"),
+            ),
+            _ => ("io", format!("Failed {action} {path_display}: {error}")),
+        };
+        Self::error(message).with_metadata(serde_json::json!({
+            "error_kind": kind,
+            "path": path_display.to_string(),
+        }))
+    }
+
+    /// Create an error result for a file or content over the size limit
+    ///
+    /// The metadata carries the actual and allowed sizes in bytes.
+    pub fn file_too_large(message: impl Into<String>, path: &Path, size: u64, limit: u64) -> Self {
+        Self::error(message).with_metadata(serde_json::json!({
+            "error_kind": "file_too_large",
+            "path": path.display().to_string(),
+            "size": size,
+            "max_file_size": limit,
+        }))
+    }
+
+    /// Create an error result for a URL whose host the domain settings block
+    pub fn domain_blocked(message: impl Into<String>, url: &str, host: &str) -> Self {
+        Self::error(message).with_metadata(serde_json::json!({
+            "error_kind": "permission_denied",
+            "url": url,
+            "host": host,
+        }))
+    }
+
     /// Create a result with metadata
     pub fn with_metadata(mut self, metadata: serde_json::Value) -> Self {
         self.metadata = Some(metadata);
         self
     }
+
+    /// Add locations for the client to link to (e.g. search matches)
+    pub fn with_locations(mut self, locations: Vec<ToolCallLocation>) -> Self {
+        self.locations = locations;
+        self
+    }
+
+    /// Attach a base64-encoded image
+    pub fn with_image(mut self, media_type: impl Into<String>, data: impl Into<String>) -> Self {
+        self.image = Some(ToolImage {
+            media_type: media_type.into(),
+            data: data.into(),
+        });
+        self
+    }
 }
 
 /// Tool execution status
@@ -72,6 +171,47 @@ pub enum ToolStatus {
     Running,
 }
 
+/// Default limit on how long a built-in tool may run
+pub const DEFAULT_TOOL_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Default limit on the size of files Read reads and Write writes (10MB)
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Execution time limits of tools
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToolTimeouts {
+    /// Limit for all built-in tools (None = [`DEFAULT_TOOL_TIMEOUT`])
+    pub default: Option<Duration>,
+    /// Limits by tool name, overriding `default` and `external`
+    pub per_tool: HashMap<String, Duration>,
+    /// Limit for external MCP tool calls (None = the MCP request timeout)
+    pub external: Option<Duration>,
+}
+
+impl ToolTimeouts {
+    /// Execution time limit of a tool, None if it may run indefinitely
+    ///
+    /// Tools that enforce their own timeout (like Bash) are only limited by
+    /// a per-tool entry, which they use as their default. A zero duration
+    /// disables the limit.
+    pub fn for_tool(&self, name: &str, enforces_own_timeout: bool) -> Option<Duration> {
+        let timeout = match self.per_tool.get(name) {
+            Some(&timeout) => Some(timeout),
+            None if enforces_own_timeout => None,
+            None => Some(self.default.unwrap_or(DEFAULT_TOOL_TIMEOUT)),
+        };
+        timeout.filter(|timeout| !timeout.is_zero())
+    }
+
+    /// Configured limit of an external MCP tool (`mcp__<server>__<tool>`)
+    ///
+    /// None when neither a per-tool entry nor `external` is set, leaving the
+    /// MCP request timeout in place. A zero duration disables the limit.
+    pub fn for_external_tool(&self, name: &str) -> Option<Duration> {
+        self.per_tool.get(name).copied().or(self.external)
+    }
+}
+
 /// Tool execution context
 #[derive(Debug, Clone)]
 pub struct ToolContext {
@@ -91,6 +231,32 @@ pub struct ToolContext {
     connection_cx: Option<JrConnectionCx<AgentToClient>>,
     /// Permission checker for tool-level permission checks
     pub permission_checker: Option<Arc<tokio::sync::RwLock<PermissionChecker>>>,
+    /// Maximum characters per line of tool output
+    max_line_length: Option<usize>,
+    /// Maximum lines returned by Read when no range is given
+    max_read_lines: Option<usize>,
+    /// Largest file Read reads and Write writes in bytes (0 = unlimited)
+    max_file_size: Option<u64>,
+    /// Extra environment for commands run by tools (from the client)
+    env: HashMap<String, String>,
+    /// Execution time limits of tools
+    tool_timeouts: ToolTimeouts,
+    /// Whether written files are re-read and verified
+    verify_writes: bool,
+    /// Whether Grep uses ripgrep when available
+    prefer_ripgrep: bool,
+    /// What EnvInfo reports
+    env_info: EnvInfoConfig,
+    /// Whether WebFetch may fetch loopback and private addresses
+    allow_localhost_fetch: bool,
+    /// Hosts WebFetch may fetch
+    web_fetch_domains: DomainFilter,
+    /// Domains WebSearch may return results from
+    web_search_domains: DomainFilter,
+    /// When Write and Edit report whether a file ends with a newline
+    final_newline: FinalNewlinePolicy,
+    /// Encodings of non-UTF-8 files read in the session, reused when writing them
+    file_encodings: Option<Arc<FileEncodings>>,
 }
 
 impl ToolContext {
@@ -105,6 +271,19 @@ impl ToolContext {
             tool_use_id: None,
             connection_cx: None,
             permission_checker: None,
+            max_line_length: None,
+            max_read_lines: None,
+            max_file_size: None,
+            env: HashMap::new(),
+            tool_timeouts: ToolTimeouts::default(),
+            verify_writes: false,
+            prefer_ripgrep: true,
+            env_info: EnvInfoConfig::default(),
+            allow_localhost_fetch: false,
+            web_fetch_domains: DomainFilter::default(),
+            web_search_domains: DomainFilter::default(),
+            final_newline: FinalNewlinePolicy::Off,
+            file_encodings: None,
         }
     }
 
@@ -147,6 +326,160 @@ impl ToolContext {
         self
     }
 
+    /// Set the maximum characters per line of tool output
+    pub fn with_max_line_length(mut self, max_line_length: usize) -> Self {
+        self.max_line_length = Some(max_line_length);
+        self
+    }
+
+    /// Set the maximum lines returned by Read when no range is given
+    pub fn with_max_read_lines(mut self, max_read_lines: usize) -> Self {
+        self.max_read_lines = Some(max_read_lines);
+        self
+    }
+
+    /// Get the configured maximum lines returned by Read, if any
+    pub fn max_read_lines(&self) -> Option<usize> {
+        self.max_read_lines
+    }
+
+    /// Set the largest file Read reads and Write writes in bytes (0 = unlimited)
+    pub fn with_max_file_size(mut self, max_file_size: u64) -> Self {
+        self.max_file_size = Some(max_file_size);
+        self
+    }
+
+    /// Largest file Read reads and Write writes in bytes (None = unlimited)
+    ///
+    /// [`DEFAULT_MAX_FILE_SIZE`] unless configured.
+    pub fn max_file_size(&self) -> Option<u64> {
+        match self.max_file_size {
+            Some(0) => None,
+            Some(max_file_size) => Some(max_file_size),
+            None => Some(DEFAULT_MAX_FILE_SIZE),
+        }
+    }
+
+    /// Set extra environment variables for commands run by tools
+    pub fn with_env(mut self, env: HashMap<String, String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Get the extra environment variables for commands run by tools
+    pub fn env(&self) -> &HashMap<String, String> {
+        &self.env
+    }
+
+    /// Set the execution time limits of tools
+    pub fn with_tool_timeouts(mut self, tool_timeouts: ToolTimeouts) -> Self {
+        self.tool_timeouts = tool_timeouts;
+        self
+    }
+
+    /// Get the execution time limits of tools
+    pub fn tool_timeouts(&self) -> &ToolTimeouts {
+        &self.tool_timeouts
+    }
+
+    /// Set whether written files are re-read and verified
+    pub fn with_verify_writes(mut self, verify_writes: bool) -> Self {
+        self.verify_writes = verify_writes;
+        self
+    }
+
+    /// Whether written files are re-read and verified
+    pub fn verify_writes(&self) -> bool {
+        self.verify_writes
+    }
+
+    /// Set whether Grep uses ripgrep when available
+    pub fn with_prefer_ripgrep(mut self, prefer_ripgrep: bool) -> Self {
+        self.prefer_ripgrep = prefer_ripgrep;
+        self
+    }
+
+    /// Whether Grep uses ripgrep when available
+    pub fn prefer_ripgrep(&self) -> bool {
+        self.prefer_ripgrep
+    }
+
+    /// Set what EnvInfo reports
+    pub fn with_env_info(mut self, env_info: EnvInfoConfig) -> Self {
+        self.env_info = env_info;
+        self
+    }
+
+    /// Get what EnvInfo reports
+    pub fn env_info(&self) -> &EnvInfoConfig {
+        &self.env_info
+    }
+
+    /// Set whether WebFetch may fetch loopback and private addresses
+    pub fn with_allow_localhost_fetch(mut self, allow: bool) -> Self {
+        self.allow_localhost_fetch = allow;
+        self
+    }
+
+    /// Whether WebFetch may fetch loopback and private addresses
+    pub fn allow_localhost_fetch(&self) -> bool {
+        self.allow_localhost_fetch
+    }
+
+    /// Set the hosts WebFetch may fetch
+    pub fn with_web_fetch_domains(mut self, domains: DomainFilter) -> Self {
+        self.web_fetch_domains = domains;
+        self
+    }
+
+    /// Hosts WebFetch may fetch
+    pub fn web_fetch_domains(&self) -> &DomainFilter {
+        &self.web_fetch_domains
+    }
+
+    /// Set the domains WebSearch may return results from
+    pub fn with_web_search_domains(mut self, domains: DomainFilter) -> Self {
+        self.web_search_domains = domains;
+        self
+    }
+
+    /// Domains WebSearch may return results from
+    pub fn web_search_domains(&self) -> &DomainFilter {
+        &self.web_search_domains
+    }
+
+    /// Set when Write and Edit report whether a file ends with a newline
+    pub fn with_final_newline(mut self, policy: FinalNewlinePolicy) -> Self {
+        self.final_newline = policy;
+        self
+    }
+
+    /// When Write and Edit report whether a file ends with a newline
+    pub fn final_newline(&self) -> FinalNewlinePolicy {
+        self.final_newline
+    }
+
+    /// Set where Read records the encodings of non-UTF-8 files for Write
+    pub fn with_file_encodings(mut self, file_encodings: Arc<FileEncodings>) -> Self {
+        self.file_encodings = Some(file_encodings);
+        self
+    }
+
+    /// Get the encodings of non-UTF-8 files read in the session
+    pub fn file_encodings(&self) -> Option<&Arc<FileEncodings>> {
+        self.file_encodings.as_ref()
+    }
+
+    /// Truncate output lines beyond the configured maximum line length
+    ///
+    /// Returns the text unchanged when no maximum is configured.
+    pub fn limit_line_length<'a>(&self, text: &'a str) -> Cow<'a, str> {
+        match self.max_line_length {
+            Some(max_line_length) => truncate_long_lines(text, max_line_length),
+            None => Cow::Borrowed(text),
+        }
+    }
+
     /// Get the background process manager
     pub fn background_processes(&self) -> Option<&Arc<BackgroundProcessManager>> {
         self.background_processes.as_ref()
@@ -216,6 +549,52 @@ impl ToolContext {
             .send_notification(notification)
             .map_err(|e| format!("Failed to send notification: {}", e))
     }
+
+    /// Send a ToolCallUpdate telling the client a background command finished
+    ///
+    /// The update goes to the tool call that started the command, marking it
+    /// failed unless the command exited with code 0. Its `background_exit`
+    /// meta carries the shell ID, status and exit code (null unless the
+    /// command exited on its own).
+    pub fn send_background_exit(&self, exit: &BackgroundExit) -> Result<(), String> {
+        let Some(connection_cx) = &self.connection_cx else {
+            return Err("No connection context available".to_string());
+        };
+
+        let Some(tool_use_id) = &self.tool_use_id else {
+            return Err("No tool use ID available".to_string());
+        };
+
+        let exit_code = exit.status.exit_code();
+        let status = if exit_code == Some(0) {
+            ToolCallStatus::Completed
+        } else {
+            ToolCallStatus::Failed
+        };
+        let mut meta = Meta::new();
+        meta.insert(
+            "background_exit".to_string(),
+            serde_json::json!({
+                "shell_id": exit.shell_id,
+                "status": exit.status.as_str(),
+                "exit_code": exit_code
+            }),
+        );
+
+        let update = ToolCallUpdate::new(
+            ToolCallId::new(tool_use_id.clone()),
+            ToolCallUpdateFields::new().status(status),
+        )
+        .meta(meta);
+        let notification = SessionNotification::new(
+            SessionId::new(self.session_id.as_str()),
+            SessionUpdate::ToolCallUpdate(update),
+        );
+
+        connection_cx
+            .send_notification(notification)
+            .map_err(|e| format!("Failed to send notification: {}", e))
+    }
 }
 
 /// ACP tool prefix for compatibility with TypeScript implementation
@@ -302,6 +681,13 @@ impl ToolRegistry {
         self.tools.is_empty()
     }
 
+    /// Clear the caches of all registered tools
+    pub fn clear_caches(&self) {
+        for tool in self.tools.values() {
+            tool.clear_cache();
+        }
+    }
+
     /// Execute a tool by name
     pub async fn execute(
         &self,
@@ -366,6 +752,45 @@ mod tests {
         assert!(result.metadata.is_some());
     }
 
+    #[test]
+    fn test_tool_result_io_error() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let denied = ToolResult::io_error(
+            "writing",
+            Path::new("/etc/hosts"),
+            &ErrorKind::PermissionDenied.into(),
+            temp_dir.path(),
+        );
+        assert!(denied.is_error);
+        assert_eq!(
+            denied.content,
+            "Permission denied writing /etc/hosts — try a path inside the workspace"
+        );
+        assert_eq!(denied.metadata.unwrap()["error_kind"], "permission_denied");
+
+        // A path inside the workspace gets no hint to move into it
+        let inside = temp_dir.path().join("locked.txt");
+        let denied = ToolResult::io_error(
+            "writing",
+            &inside,
+            &ErrorKind::PermissionDenied.into(),
+            temp_dir.path(),
+        );
+        assert_eq!(
+            denied.content,
+            format!("Permission denied writing {}", inside.display())
+        );
+
+        let missing = ToolResult::io_error(
+            "reading",
+            Path::new("/missing"),
+            &ErrorKind::NotFound.into(),
+            temp_dir.path(),
+        );
+        assert_eq!(missing.content, "Path not found while reading /missing");
+        assert_eq!(missing.metadata.unwrap()["error_kind"], "not_found");
+    }
+
     #[test]
     fn test_tool_context() {
         let ctx = ToolContext::new("session-1", "/tmp").with_dangerous(true);
@@ -0,0 +1,296 @@
+//! Tool argument repair
+//!
+//! Models occasionally send arguments that are almost right: a number as a
+//! string, a JSON-encoded array, `path` where the tool expects `file_path`,
+//! or `null` for an optional argument they mean to leave out.
+//! Rejecting those costs a round-trip, so obvious mistakes are fixed against
+//! the tool's input schema before execution. Anything ambiguous is left
+//! alone for the tool to reject.
+
+use serde_json::{Map, Value};
+
+/// Argument names models commonly use instead of the canonical one
+const ARGUMENT_ALIASES: &[(&str, &[&str])] = &[
+    ("file_path", &["path", "filePath"]),
+    ("notebook_path", &["path", "notebookPath"]),
+    ("pattern", &["query"]),
+];
+
+/// Repair tool arguments against the tool's JSON schema
+///
+/// Returns the repaired arguments and a description of each repair made.
+pub fn repair_arguments(schema: &Value, input: Value) -> (Value, Vec<String>) {
+    let Value::Object(mut args) = input else {
+        return (input, Vec::new());
+    };
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return (Value::Object(args), Vec::new());
+    };
+
+    let mut repairs = Vec::new();
+    repair_aliases(properties, &mut args, &mut repairs);
+
+    for (name, value) in &mut args {
+        let Some(expected) = properties
+            .get(name)
+            .and_then(|prop| prop.get("type"))
+            .and_then(Value::as_str)
+        else {
+            continue;
+        };
+        if let Some(repaired) = coerce(value, expected) {
+            repairs.push(format!(
+                "coerced `{}` from {} to {}",
+                name,
+                json_type(value),
+                expected
+            ));
+            *value = repaired;
+        }
+    }
+    drop_null_optionals(schema, &mut args, "", &mut repairs);
+
+    (Value::Object(args), repairs)
+}
+
+/// Drop optional arguments that are `null`, so the tool uses their default
+///
+/// Arguments that are required, or whose schema allows null, are kept.
+/// Objects in array arguments (like MultiEdit's edits) are handled the same
+/// way.
+fn drop_null_optionals(
+    schema: &Value,
+    args: &mut Map<String, Value>,
+    path: &str,
+    repairs: &mut Vec<String>,
+) {
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return;
+    };
+    let required = schema.get("required").and_then(Value::as_array);
+
+    args.retain(|name, value| {
+        let Some(property) = properties.get(name) else {
+            return true;
+        };
+        let is_required = required.is_some_and(|r| r.iter().any(|n| n.as_str() == Some(name)));
+        if !value.is_null() || is_required || allows_null(property) {
+            return true;
+        }
+        repairs.push(format!("dropped null `{}{}`", path, name));
+        false
+    });
+
+    for (name, value) in args.iter_mut() {
+        let (Value::Array(items), Some(item_schema)) = (
+            value,
+            properties.get(name).and_then(|prop| prop.get("items")),
+        ) else {
+            continue;
+        };
+        for (index, item) in items.iter_mut().enumerate() {
+            if let Value::Object(item) = item {
+                let item_path = format!("{}{}[{}].", path, name, index);
+                drop_null_optionals(item_schema, item, &item_path, repairs);
+            }
+        }
+    }
+}
+
+/// Whether the property schema `property` accepts `null`
+fn allows_null(property: &Value) -> bool {
+    match property.get("type") {
+        Some(Value::String(t)) => t == "null",
+        Some(Value::Array(types)) => types.iter().any(|t| t == "null"),
+        _ => false,
+    }
+}
+
+/// Rename an alias to the canonical argument name when that is unambiguous
+///
+/// The canonical name must be part of the schema and missing from the input,
+/// the alias must not be a real argument of the tool, and exactly one alias
+/// may be present.
+fn repair_aliases(
+    properties: &Map<String, Value>,
+    args: &mut Map<String, Value>,
+    repairs: &mut Vec<String>,
+) {
+    for &(canonical, aliases) in ARGUMENT_ALIASES {
+        if !properties.contains_key(canonical) || args.contains_key(canonical) {
+            continue;
+        }
+        let mut present = aliases
+            .iter()
+            .filter(|alias| args.contains_key(**alias) && !properties.contains_key(**alias));
+        let (Some(alias), None) = (present.next(), present.next()) else {
+            continue;
+        };
+        if let Some(value) = args.remove(*alias) {
+            repairs.push(format!("renamed `{}` to `{}`", alias, canonical));
+            args.insert(canonical.to_string(), value);
+        }
+    }
+}
+
+/// Convert `value` to the schema type `expected` if the intent is obvious
+fn coerce(value: &Value, expected: &str) -> Option<Value> {
+    match (expected, value) {
+        ("integer", Value::String(s)) => {
+            let s = s.trim();
+            s.parse::<i64>()
+                .map(Value::from)
+                .or_else(|_| s.parse::<u64>().map(Value::from))
+                .ok()
+        }
+        ("number", Value::String(s)) => {
+            let s = s.trim();
+            s.parse::<i64>().map(Value::from).ok().or_else(|| {
+                s.parse::<f64>()
+                    .ok()
+                    .and_then(|n| serde_json::Number::from_f64(n).map(Value::Number))
+            })
+        }
+        ("boolean", Value::String(s)) => match s.trim() {
+            "true" => Some(Value::Bool(true)),
+            "false" => Some(Value::Bool(false)),
+            _ => None,
+        },
+        ("string", Value::Number(n)) => Some(Value::String(n.to_string())),
+        ("string", Value::Bool(b)) => Some(Value::String(b.to_string())),
+        ("array", Value::String(s)) => serde_json::from_str::<Value>(s)
+            .ok()
+            .filter(Value::is_array),
+        ("object", Value::String(s)) => serde_json::from_str::<Value>(s)
+            .ok()
+            .filter(Value::is_object),
+        _ => None,
+    }
+}
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "file_path": {"type": "string"},
+                "timeout": {"type": "integer"},
+                "ratio": {"type": "number"},
+                "recursive": {"type": "boolean"},
+                "globs": {"type": "array", "items": {"type": "string"}}
+            },
+            "required": ["file_path"]
+        })
+    }
+
+    #[test]
+    fn test_repair_coerces_obvious_types() {
+        let (args, repairs) = repair_arguments(
+            &schema(),
+            json!({
+                "file_path": "/tmp/a.txt",
+                "timeout": "5000",
+                "ratio": "0.5",
+                "recursive": "true",
+                "globs": "[\"*.rs\"]"
+            }),
+        );
+
+        assert_eq!(args["timeout"], json!(5000));
+        assert_eq!(args["ratio"], json!(0.5));
+        assert_eq!(args["recursive"], json!(true));
+        assert_eq!(args["globs"], json!(["*.rs"]));
+        assert_eq!(repairs.len(), 4);
+    }
+
+    #[test]
+    fn test_repair_renames_path_alias() {
+        let (args, repairs) = repair_arguments(&schema(), json!({"path": "/tmp/a.txt"}));
+        assert_eq!(args, json!({"file_path": "/tmp/a.txt"}));
+        assert_eq!(repairs, vec!["renamed `path` to `file_path`"]);
+    }
+
+    #[test]
+    fn test_repair_drops_null_optional_arguments() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "file_path": {"type": "string"},
+                "limit": {"type": "integer"},
+                "note": {"type": ["string", "null"]},
+                "edits": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "old_string": {"type": "string"},
+                            "replace_all": {"type": "boolean"}
+                        },
+                        "required": ["old_string"]
+                    }
+                }
+            },
+            "required": ["file_path"]
+        });
+
+        let (args, repairs) = repair_arguments(
+            &schema,
+            json!({
+                "file_path": null,
+                "limit": null,
+                "note": null,
+                "edits": [{"old_string": "a", "replace_all": null}]
+            }),
+        );
+
+        // Required arguments stay for validation to reject
+        assert_eq!(
+            args,
+            json!({"file_path": null, "note": null, "edits": [{"old_string": "a"}]})
+        );
+        assert_eq!(
+            repairs,
+            vec![
+                "dropped null `limit`",
+                "dropped null `edits[0].replace_all`"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repair_leaves_ambiguous_input_alone() {
+        // Not a number, not a boolean, two competing aliases
+        let input = json!({
+            "path": "/a",
+            "filePath": "/b",
+            "timeout": "5s",
+            "recursive": "yes"
+        });
+        let (args, repairs) = repair_arguments(&schema(), input.clone());
+        assert_eq!(args, input);
+        assert!(repairs.is_empty());
+
+        // `path` is left alone when it is a real argument of the tool
+        let schema = json!({
+            "properties": {"file_path": {"type": "string"}, "path": {"type": "string"}}
+        });
+        let (args, repairs) = repair_arguments(&schema, json!({"path": "/a"}));
+        assert_eq!(args, json!({"path": "/a"}));
+        assert!(repairs.is_empty());
+    }
+}
@@ -3,17 +3,24 @@
 //! Manages tool registration and provides the server interface.
 //! Supports both built-in tools and external MCP servers.
 
+use std::panic::AssertUnwindSafe;
 use std::path::Path;
 use std::sync::Arc;
 
+use futures::FutureExt;
+
 use crate::mcp::external::{ExternalMcpError, ExternalMcpManager};
 use crate::mcp::registry::{ToolContext, ToolRegistry, ToolResult, ToolSchema};
+use crate::mcp::repair::repair_arguments;
 use crate::mcp::tools::{
-    AskUserQuestionTool, BashOutputTool, BashTool, EditTool, ExitPlanModeTool, GlobTool, GrepTool,
-    KillShellTool, LsTool, NotebookEditTool, NotebookReadTool, ReadTool, SkillTool,
-    SlashCommandTool, TaskOutputTool, TaskTool, TodoWriteTool, Tool, WebFetchTool, WebSearchTool,
-    WriteTool,
+    AskUserQuestionTool, BashOutputTool, BashTool, CargoAddTool, CreateDirectoryTool, DeleteTool,
+    DiffFilesTool, EditTool, EnvInfoTool, ExitPlanModeTool, GlobTool, GrepTool, KillShellTool,
+    ListShellsTool, LsTool, MoveTool, MultiEditTool, NotebookEditTool, NotebookReadTool, ReadTool,
+    RecentFilesTool, RepoStatsTool, SkillTool, SlashCommandTool, StructuredEditTool,
+    StructuredFormat, TaskOutputTool, TaskTool, TodoWriteTool, Tool, TouchTool, WebFetchTool,
+    WebSearchTool, WriteTool,
 };
+use crate::mcp::validation::validate_arguments;
 use crate::settings::McpServerConfig;
 
 /// MCP Server for managing and executing tools
@@ -84,15 +91,38 @@ impl McpServer {
         self.registry.register(ReadTool::new());
         self.registry.register(WriteTool::new());
         self.registry.register(EditTool::new());
+        self.registry.register(MultiEditTool::new());
+        for format in [
+            StructuredFormat::Json,
+            StructuredFormat::Yaml,
+            StructuredFormat::Toml,
+        ] {
+            self.registry.register(StructuredEditTool::new(format));
+        }
+        self.registry.register(CargoAddTool::new());
+        self.registry.register(TouchTool::new());
+        self.registry.register(CreateDirectoryTool::new());
+        self.registry.register(DeleteTool::new());
+        self.registry.register(MoveTool::new());
         self.registry.register(BashTool::new());
         self.registry.register(BashOutputTool);
         self.registry.register(KillShellTool);
+        self.registry.register(ListShellsTool::new());
         self.registry.register(GlobTool::new());
         self.registry.register(GrepTool::new());
         self.registry.register(LsTool::new());
+        self.registry.register(RepoStatsTool::new());
+        self.registry.register(RecentFilesTool::new());
+        self.registry.register(DiffFilesTool::new());
+        self.registry.register(EnvInfoTool::new());
         self.registry.register(TodoWriteTool::new());
         self.registry.register(ExitPlanModeTool::new());
-        self.registry.register(WebFetchTool::new());
+        match WebFetchTool::new() {
+            Ok(tool) => self.registry.register(tool),
+            Err(e) => {
+                tracing::error!(error = %e, "WebFetch is unavailable: failed to build its HTTP client")
+            }
+        }
         self.registry.register(WebSearchTool::new());
         self.registry.register(NotebookReadTool::new());
         self.registry.register(NotebookEditTool::new());
@@ -166,14 +196,118 @@ impl McpServer {
     ) -> ToolResult {
         // Check if this is an external MCP tool
         if ExternalMcpManager::is_external_tool(name) {
-            return match self.external.call_tool(name, input).await {
+            let timeout = context.tool_timeouts().for_external_tool(name);
+            return match self
+                .external
+                .call_tool_with_timeout(name, input, timeout)
+                .await
+            {
                 Ok(result) => result,
                 Err(e) => ToolResult::error(format!("External MCP error: {}", e)),
             };
         }
 
         // Execute built-in tool
-        self.registry.execute(name, input, context).await
+        let input = match self.prepare_arguments(name, input) {
+            Ok(input) => input,
+            Err(e) => return ToolResult::error(e),
+        };
+        // Tools like Bash apply their configured timeout themselves
+        let timeout = self
+            .registry
+            .get(name)
+            .filter(|tool| !tool.enforces_own_timeout())
+            .and_then(|tool| context.tool_timeouts().for_tool(tool.name(), false));
+        let Some(timeout) = timeout else {
+            return self.execute_builtin(name, input, context).await;
+        };
+
+        match tokio::time::timeout(timeout, self.execute_builtin(name, input, context)).await {
+            Ok(result) => result,
+            Err(_) => {
+                tracing::warn!(
+                    tool_name = %name,
+                    timeout_ms = timeout.as_millis(),
+                    "Tool execution timed out"
+                );
+                ToolResult::error(format!("{name} timed out after {timeout:?}")).with_metadata(
+                    serde_json::json!({
+                        "timed_out": true,
+                        "timeout_ms": timeout.as_millis(),
+                    }),
+                )
+            }
+        }
+    }
+
+    /// Execute a built-in tool, turning a panic into an error result
+    ///
+    /// A bad unwrap on malformed input then fails the tool call instead of
+    /// the whole turn.
+    async fn execute_builtin(
+        &self,
+        name: &str,
+        input: serde_json::Value,
+        context: &ToolContext,
+    ) -> ToolResult {
+        match AssertUnwindSafe(self.registry.execute(name, input, context))
+            .catch_unwind()
+            .await
+        {
+            Ok(result) => result,
+            Err(panic) => {
+                let message = panic
+                    .downcast_ref::<&str>()
+                    .map(|s| (*s).to_string())
+                    .or_else(|| panic.downcast_ref::<String>().cloned())
+                    .unwrap_or_else(|| "unknown panic".to_string());
+                tracing::error!(tool_name = %name, panic = %message, "Tool panicked");
+                ToolResult::error(format!("{name} failed unexpectedly: {message}"))
+                    .with_metadata(serde_json::json!({ "panicked": true }))
+            }
+        }
+    }
+
+    /// Repair and validate a built-in tool's arguments before execution
+    ///
+    /// Validation uses the same input schema that is advertised to the model,
+    /// so errors name the offending field and the expected type.
+    pub fn prepare_arguments(
+        &self,
+        name: &str,
+        input: serde_json::Value,
+    ) -> Result<serde_json::Value, String> {
+        let Some(tool) = self.registry.get(name) else {
+            return Ok(input);
+        };
+        let input = self.repair_arguments(name, input);
+        match validate_arguments(&tool.input_schema(), &input) {
+            Ok(()) => Ok(input),
+            Err(errors) => {
+                tracing::info!(tool_name = %name, errors = ?errors, "Invalid tool arguments");
+                Err(format!(
+                    "Invalid input for {}: {}",
+                    tool.name(),
+                    errors.join("; ")
+                ))
+            }
+        }
+    }
+
+    /// Fix obvious mistakes in a built-in tool's arguments
+    ///
+    /// Coerces values to the types in the tool's schema and renames common
+    /// argument aliases. Each repair is logged; unknown tools and ambiguous
+    /// arguments are returned unchanged.
+    pub fn repair_arguments(&self, name: &str, input: serde_json::Value) -> serde_json::Value {
+        let Some(tool) = self.registry.get(name) else {
+            return input;
+        };
+        let (input, repairs) = repair_arguments(&tool.input_schema(), input);
+        for repair in &repairs {
+            tracing::info!(tool_name = %name, repair = %repair, "Repaired tool argument");
+        }
+        input
     }
 
     /// Connect to external MCP servers from configuration
@@ -289,6 +423,7 @@ impl Default for McpServer {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mcp::registry::ToolTimeouts;
     use serde_json::json;
     use std::io::Write as IoWrite;
     use tempfile::TempDir;
@@ -303,9 +438,11 @@ mod tests {
         assert!(server.has_tool("Read"));
         assert!(server.has_tool("Write"));
         assert!(server.has_tool("Edit"));
+        assert!(server.has_tool("MultiEdit"));
         assert!(server.has_tool("Bash"));
         assert!(server.has_tool("BashOutput"));
         assert!(server.has_tool("KillShell"));
+        assert!(server.has_tool("ListShells"));
         assert!(server.has_tool("Glob"));
         assert!(server.has_tool("Grep"));
         assert!(server.has_tool("LS"));
@@ -317,7 +454,19 @@ mod tests {
         assert!(server.has_tool("NotebookEdit"));
         assert!(server.has_tool("Task"));
         assert!(server.has_tool("TaskOutput"));
-        assert_eq!(server.tool_count(), 20);
+        assert!(server.has_tool("RepoStats"));
+        assert!(server.has_tool("RecentFiles"));
+        assert!(server.has_tool("Touch"));
+        assert!(server.has_tool("CreateDirectory"));
+        assert!(server.has_tool("Delete"));
+        assert!(server.has_tool("Move"));
+        assert!(server.has_tool("DiffFiles"));
+        assert!(server.has_tool("JsonEdit"));
+        assert!(server.has_tool("YamlEdit"));
+        assert!(server.has_tool("TomlEdit"));
+        assert!(server.has_tool("CargoAdd"));
+        assert!(server.has_tool("EnvInfo"));
+        assert_eq!(server.tool_count(), 34);
     }
 
     #[test]
@@ -361,7 +510,7 @@ mod tests {
         let server = McpServer::new();
         let schemas = server.tool_schemas();
 
-        assert_eq!(schemas.len(), 20);
+        assert_eq!(schemas.len(), 34);
 
         // Check that each schema has required fields
         for schema in &schemas {
@@ -394,6 +543,102 @@ mod tests {
         assert!(result.content.contains("Test content"));
     }
 
+    #[tokio::test]
+    async fn test_execute_times_out_slow_tool() {
+        #[derive(Debug)]
+        struct SlowTool;
+
+        #[async_trait::async_trait]
+        impl Tool for SlowTool {
+            fn name(&self) -> &str {
+                "Slow"
+            }
+
+            fn description(&self) -> &str {
+                "Sleeps for a minute"
+            }
+
+            fn input_schema(&self) -> serde_json::Value {
+                json!({"type": "object", "properties": {}})
+            }
+
+            async fn execute(
+                &self,
+                _input: serde_json::Value,
+                _context: &ToolContext,
+            ) -> ToolResult {
+                tokio::time::sleep(std::time::Duration::from_secs(60)).await;
+                ToolResult::success("done")
+            }
+        }
+
+        let mut server = McpServer::empty();
+        server.register(SlowTool);
+        let temp_dir = TempDir::new().unwrap();
+        let timeouts = ToolTimeouts {
+            per_tool: [("Slow".to_string(), std::time::Duration::from_millis(50))].into(),
+            ..ToolTimeouts::default()
+        };
+        let context =
+            ToolContext::new("test-session", temp_dir.path()).with_tool_timeouts(timeouts);
+
+        let result = server.execute("Slow", json!({}), &context).await;
+
+        assert!(result.is_error);
+        assert_eq!(result.content, "Slow timed out after 50ms");
+        assert_eq!(result.metadata.unwrap()["timed_out"], json!(true));
+    }
+
+    #[tokio::test]
+    async fn test_execute_recovers_from_tool_panic() {
+        #[derive(Debug)]
+        struct FragileTool;
+
+        #[async_trait::async_trait]
+        impl Tool for FragileTool {
+            fn name(&self) -> &str {
+                "Fragile"
+            }
+
+            fn description(&self) -> &str {
+                "Echoes its path"
+            }
+
+            fn input_schema(&self) -> serde_json::Value {
+                json!({"type": "object", "properties": {"path": {"type": "string"}}})
+            }
+
+            async fn execute(
+                &self,
+                input: serde_json::Value,
+                _context: &ToolContext,
+            ) -> ToolResult {
+                let path = input["path"].as_str().expect("path must be a string");
+                ToolResult::success(path)
+            }
+        }
+
+        let mut server = McpServer::empty();
+        server.register(FragileTool);
+        let temp_dir = TempDir::new().unwrap();
+        let context = ToolContext::new("test-session", temp_dir.path());
+
+        let result = server.execute("Fragile", json!({}), &context).await;
+        assert!(result.is_error);
+        assert_eq!(
+            result.content,
+            "Fragile failed unexpectedly: path must be a string"
+        );
+        assert_eq!(result.metadata.unwrap()["panicked"], json!(true));
+
+        // The server keeps working after the panic
+        let result = server
+            .execute("Fragile", json!({"path": "a.rs"}), &context)
+            .await;
+        assert!(!result.is_error);
+        assert_eq!(result.content, "a.rs");
+    }
+
     #[tokio::test]
     async fn test_execute_unknown_tool() {
         let server = McpServer::new();
@@ -447,6 +692,89 @@ mod tests {
         assert!(result.content.contains("Hello from bash"));
     }
 
+    #[tokio::test]
+    async fn test_execute_repairs_string_timeout() {
+        let server = McpServer::new();
+        let temp_dir = TempDir::new().unwrap();
+        let context = ToolContext::new("test-session", temp_dir.path());
+
+        let repaired =
+            server.repair_arguments("Bash", json!({"command": "echo hi", "timeout": "5000"}));
+        assert_eq!(repaired["timeout"], json!(5000));
+
+        let result = server
+            .execute(
+                "Bash",
+                json!({"command": "echo 'repaired'", "timeout": "5000"}),
+                &context,
+            )
+            .await;
+
+        assert!(!result.is_error, "unexpected error: {}", result.content);
+        assert!(result.content.contains("repaired"));
+    }
+
+    #[tokio::test]
+    async fn test_execute_accepts_null_optional_arguments() {
+        let server = McpServer::new();
+        let temp_dir = TempDir::new().unwrap();
+        let context = ToolContext::new("test-session", temp_dir.path());
+        let file_path = temp_dir.path().join("a.txt");
+        std::fs::write(&file_path, "one\n").unwrap();
+
+        let result = server
+            .execute(
+                "Edit",
+                json!({
+                    "file_path": file_path,
+                    "old_string": "one",
+                    "new_string": "two",
+                    "replace_all": null,
+                    "dry_run": null
+                }),
+                &context,
+            )
+            .await;
+
+        assert!(!result.is_error, "unexpected error: {}", result.content);
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "two\n");
+    }
+
+    #[tokio::test]
+    async fn test_execute_reports_missing_required_field() {
+        let server = McpServer::new();
+        let temp_dir = TempDir::new().unwrap();
+        let context = ToolContext::new("test-session", temp_dir.path());
+
+        let result = server
+            .execute(
+                "Edit",
+                json!({"file_path": "/tmp/a.txt", "old_string": "a"}),
+                &context,
+            )
+            .await;
+
+        assert!(result.is_error);
+        assert_eq!(
+            result.content,
+            "Invalid input for Edit: missing required field `new_string` (a string)"
+        );
+
+        let result = server
+            .execute(
+                "Read",
+                json!({"file_path": "/tmp/a.txt", "limit": "ten"}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error);
+        assert!(
+            result.content.contains("`limit` must be"),
+            "unexpected error: {}",
+            result.content
+        );
+    }
+
     #[test]
     fn test_acp_prefix_has_tool() {
         let server = McpServer::new();
@@ -0,0 +1,364 @@
+//! SSE transport for hosted MCP servers
+//!
+//! Implements the HTTP+SSE transport of MCP (protocol version 2024-11-05):
+//! the client opens a `text/event-stream` with a GET request, the server
+//! announces the URL to POST JSON-RPC messages to in an `endpoint` event,
+//! and responses arrive as `message` events on the stream.
+//!
+//! A background task reads the stream and hands each response to the
+//! request waiting for its JSON-RPC id. When the stream drops, waiting and
+//! later requests fail with [`ExternalMcpError`] instead of hanging.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use dashmap::DashMap;
+use reqwest::header::{ACCEPT, CONTENT_TYPE, HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Response, Url};
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use super::external::ExternalMcpError;
+
+/// A server-sent event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SseEvent {
+    /// Event type (`message` when the server didn't name one)
+    pub event: String,
+    /// Event data, with multi-line data joined by newlines
+    pub data: String,
+}
+
+/// Incremental parser for a `text/event-stream` body
+#[derive(Debug, Default)]
+pub struct SseParser {
+    buffer: Vec<u8>,
+}
+
+impl SseParser {
+    /// Create an empty parser
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk of the stream, returning the events it completed
+    ///
+    /// Chunks may split events (and UTF-8 characters) anywhere.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<SseEvent> {
+        self.buffer.extend(chunk.iter().filter(|&&b| b != b'\r'));
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.windows(2).position(|w| w == b"\n\n") {
+            let block: Vec<u8> = self.buffer.drain(..end + 2).collect();
+            if let Some(event) = Self::parse_block(&String::from_utf8_lossy(&block)) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// Parse one event block, skipping comments and unknown fields
+    fn parse_block(block: &str) -> Option<SseEvent> {
+        let mut event = None;
+        let mut data: Vec<&str> = Vec::new();
+        for line in block.lines() {
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "event" => event = Some(value.to_string()),
+                "data" => data.push(value),
+                _ => {}
+            }
+        }
+        if data.is_empty() {
+            return None;
+        }
+        Some(SseEvent {
+            event: event.unwrap_or_else(|| "message".to_string()),
+            data: data.join("\n"),
+        })
+    }
+}
+
+/// Responses awaited by in-flight requests, by JSON-RPC id
+type PendingResponses = Arc<DashMap<u64, oneshot::Sender<String>>>;
+
+/// Connection to an MCP server over HTTP+SSE
+#[derive(Debug)]
+pub struct SseConnection {
+    /// HTTP client for POSTing messages
+    client: reqwest::Client,
+    /// URL messages are POSTed to (from the server's `endpoint` event)
+    endpoint: Url,
+    /// Headers sent with every request (e.g. authorization)
+    headers: HeaderMap,
+    /// Requests waiting for their response
+    pending: PendingResponses,
+    /// Whether the event stream has ended
+    closed: Arc<AtomicBool>,
+    /// Background task reading the event stream
+    reader: JoinHandle<()>,
+}
+
+impl SseConnection {
+    /// Open the event stream at `url` and wait for the message endpoint
+    ///
+    /// The caller bounds the wait with its own timeout.
+    pub async fn connect(
+        server_name: &str,
+        url: &str,
+        headers: &HashMap<String, String>,
+    ) -> Result<Self, ExternalMcpError> {
+        let connect_failed = |error: String| ExternalMcpError::ConnectFailed {
+            url: url.to_string(),
+            error,
+        };
+
+        let url = Url::parse(url).map_err(|e| connect_failed(e.to_string()))?;
+        let headers = header_map(headers).map_err(connect_failed)?;
+        let client = reqwest::Client::new();
+
+        let mut response = client
+            .get(url.clone())
+            .headers(headers.clone())
+            .header(ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .and_then(Response::error_for_status)
+            .map_err(|e| connect_failed(e.to_string()))?;
+
+        // The first event names the endpoint for our messages
+        let mut parser = SseParser::new();
+        let (endpoint, early_events) = loop {
+            let chunk = response
+                .chunk()
+                .await
+                .map_err(|e| connect_failed(e.to_string()))?
+                .ok_or_else(|| connect_failed("stream closed before the endpoint event".into()))?;
+            let mut events = parser.push(&chunk);
+            if let Some(index) = events.iter().position(|e| e.event == "endpoint") {
+                let endpoint = events.remove(index);
+                break (endpoint.data, events);
+            }
+        };
+        let endpoint = url
+            .join(endpoint.trim())
+            .map_err(|e| connect_failed(format!("invalid endpoint '{endpoint}': {e}")))?;
+
+        tracing::info!(
+            server_name = %server_name,
+            url = %url,
+            endpoint = %endpoint,
+            "SSE stream opened for MCP server"
+        );
+
+        let pending = PendingResponses::default();
+        let closed = Arc::new(AtomicBool::new(false));
+        for event in early_events {
+            dispatch_event(server_name, event, &pending);
+        }
+        let reader = tokio::spawn(read_events(
+            server_name.to_string(),
+            response,
+            parser,
+            pending.clone(),
+            closed.clone(),
+        ));
+
+        Ok(Self {
+            client,
+            endpoint,
+            headers,
+            pending,
+            closed,
+            reader,
+        })
+    }
+
+    /// POST a request and wait for its response on the event stream
+    ///
+    /// Returns the raw JSON-RPC response.
+    pub async fn request(&self, id: u64, body: &str) -> Result<String, ExternalMcpError> {
+        // Register before posting: the response may beat the POST's reply
+        let (tx, rx) = oneshot::channel();
+        self.pending.insert(id, tx);
+        let _pending = PendingGuard {
+            pending: &self.pending,
+            id,
+        };
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(stream_closed());
+        }
+
+        // Some servers answer in the POST response instead of on the stream
+        if let Some(response) = self.post(body).await? {
+            return Ok(response);
+        }
+        rx.await.map_err(|_| stream_closed())
+    }
+
+    /// POST a notification
+    pub async fn notify(&self, body: &str) -> Result<(), ExternalMcpError> {
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(stream_closed());
+        }
+        self.post(body).await.map(drop)
+    }
+
+    /// POST a message, returning a JSON body if the server sent one
+    async fn post(&self, body: &str) -> Result<Option<String>, ExternalMcpError> {
+        let response = self
+            .client
+            .post(self.endpoint.clone())
+            .headers(self.headers.clone())
+            .header(CONTENT_TYPE, "application/json")
+            .body(body.to_string())
+            .send()
+            .await
+            .and_then(Response::error_for_status)
+            .map_err(|e| ExternalMcpError::WriteError(e.to_string()))?;
+
+        let is_json = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("application/json"));
+        if !is_json {
+            return Ok(None);
+        }
+        let text = response
+            .text()
+            .await
+            .map_err(|e| ExternalMcpError::ReadError(e.to_string()))?;
+        Ok(Some(text).filter(|text| !text.trim().is_empty()))
+    }
+
+    /// Stop reading the event stream
+    pub fn close(&self) {
+        self.reader.abort();
+    }
+}
+
+impl Drop for SseConnection {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+/// Removes a request's pending entry when it completes or is cancelled
+struct PendingGuard<'a> {
+    pending: &'a PendingResponses,
+    id: u64,
+}
+
+impl Drop for PendingGuard<'_> {
+    fn drop(&mut self) {
+        self.pending.remove(&self.id);
+    }
+}
+
+fn stream_closed() -> ExternalMcpError {
+    ExternalMcpError::ReadError("SSE stream closed by the MCP server".to_string())
+}
+
+/// Convert configured headers, rejecting invalid names or values
+fn header_map(headers: &HashMap<String, String>) -> Result<HeaderMap, String> {
+    let mut map = HeaderMap::new();
+    for (name, value) in headers {
+        let name = HeaderName::try_from(name.as_str())
+            .map_err(|e| format!("invalid header name '{name}': {e}"))?;
+        let value = HeaderValue::try_from(value.as_str())
+            .map_err(|e| format!("invalid value for header '{name}': {e}"))?;
+        map.insert(name, value);
+    }
+    Ok(map)
+}
+
+/// Read the event stream until it ends, delivering responses
+async fn read_events(
+    server_name: String,
+    mut response: Response,
+    mut parser: SseParser,
+    pending: PendingResponses,
+    closed: Arc<AtomicBool>,
+) {
+    loop {
+        match response.chunk().await {
+            Ok(Some(chunk)) => {
+                for event in parser.push(&chunk) {
+                    dispatch_event(&server_name, event, &pending);
+                }
+            }
+            Ok(None) => {
+                tracing::warn!(server_name = %server_name, "MCP server closed the SSE stream");
+                break;
+            }
+            Err(e) => {
+                tracing::warn!(
+                    server_name = %server_name,
+                    error = %e,
+                    "SSE stream from MCP server failed"
+                );
+                break;
+            }
+        }
+    }
+
+    // Dropping the senders fails every waiting request
+    closed.store(true, Ordering::SeqCst);
+    pending.clear();
+}
+
+/// Hand a `message` event to the request waiting for its id
+fn dispatch_event(server_name: &str, event: SseEvent, pending: &PendingResponses) {
+    if event.event != "message" {
+        tracing::debug!(server_name = %server_name, event = %event.event, "Ignoring SSE event");
+        return;
+    }
+    let id = serde_json::from_str::<serde_json::Value>(&event.data)
+        .ok()
+        .filter(|message| message.get("result").is_some() || message.get("error").is_some())
+        .and_then(|message| message.get("id").and_then(serde_json::Value::as_u64));
+    let Some(id) = id else {
+        tracing::debug!(
+            server_name = %server_name,
+            "Ignoring SSE message that is not a response"
+        );
+        return;
+    };
+    if let Some((_, tx)) = pending.remove(&id) {
+        drop(tx.send(event.data));
+    } else {
+        tracing::warn!(
+            server_name = %server_name,
+            request_id = id,
+            "SSE response for unknown request"
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sse_parser_splits_events_across_chunks() {
+        let mut parser = SseParser::new();
+        let events = parser.push(b"event: endpoint\r\ndata: /messages?s");
+        assert!(events.is_empty());
+        assert_eq!(
+            parser.push(b"ession=1\r\n\r\n: keep-alive\n\ndata: {\"id\":1}\ndata: x\n\n"),
+            vec![
+                SseEvent {
+                    event: "endpoint".to_string(),
+                    data: "/messages?session=1".to_string(),
+                },
+                SseEvent {
+                    event: "message".to_string(),
+                    data: "{\"id\":1}\nx".to_string(),
+                },
+            ]
+        );
+    }
+}
@@ -31,6 +31,19 @@ pub trait Tool: Send + Sync + std::fmt::Debug {
     fn kind(&self) -> ToolKind {
         ToolKind::Other
     }
+
+    /// Check if this tool limits its own execution time
+    ///
+    /// Such tools aren't wrapped in a tool timeout; they read their
+    /// configured one from the context instead.
+    fn enforces_own_timeout(&self) -> bool {
+        false
+    }
+
+    /// Drop anything the tool cached between calls
+    ///
+    /// Called when the session that owns the tool ends.
+    fn clear_cache(&self) {}
 }
 
 /// Tool categories for UI display
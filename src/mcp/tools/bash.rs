@@ -7,18 +7,26 @@ use async_trait::async_trait;
 use sacp::schema::ToolCallStatus;
 use serde::Deserialize;
 use serde_json::json;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
 use tokio::process::Command;
+use tokio::sync::broadcast;
 use tokio::time::timeout;
 use uuid::Uuid;
 
 use super::base::{Tool, ToolKind};
+use super::test_results::parse_test_output;
+use super::workspace::{normalize, reject_outside_workspace};
 use crate::mcp::registry::{ToolContext, ToolResult};
-use crate::session::{BackgroundTerminal, ChildHandle, TerminalExitStatus, WrappedChild};
+use crate::session::{
+    BackgroundProcessManager, BackgroundTerminal, ChildHandle, TerminalExitStatus, WrappedChild,
+};
 use crate::terminal::TerminalClient;
+use crate::types::{ALLOWED_CLIENT_ENV_VARS, is_allowed_client_env};
 
 // Process group management
 use process_wrap::tokio::*;
@@ -26,6 +34,12 @@ use process_wrap::tokio::*;
 /// Maximum output size in characters
 const MAX_OUTPUT_SIZE: usize = 30_000;
 
+/// Maximum runs of a benchmarked command (`repeat`)
+const MAX_BENCH_RUNS: u32 = 100;
+
+/// Time budget for all runs of a benchmark without a timeout (10 minutes)
+const DEFAULT_BENCH_TIME_LIMIT_MS: u64 = 600_000;
+
 /// Shell operators that indicate command chaining (security risk)
 ///
 /// These operators allow chaining multiple commands, which could be used
@@ -54,6 +68,70 @@ pub fn contains_shell_operator(command: &str) -> bool {
     SHELL_OPERATORS.iter().any(|op| command.contains(op))
 }
 
+/// Failure message for a command's exit code, if it is unexpected
+///
+/// Commands are expected to exit with 0 unless the call's `expect_exit_code`
+/// says otherwise, e.g. a test that should fail.
+pub fn unexpected_exit_message(exit_code: i32, expected: Option<i32>) -> Option<String> {
+    match expected {
+        None if exit_code == 0 => None,
+        None => Some(format!("Command failed with exit code {}", exit_code)),
+        Some(expected) if expected == exit_code => None,
+        Some(expected) => Some(format!(
+            "Command exited with code {}, expected {}",
+            exit_code, expected
+        )),
+    }
+}
+
+/// Directory a command should run in, when the call's `cwd` overrides it
+///
+/// A relative `cwd` is taken from the session's working directory. It must
+/// be an existing directory inside the workspace (the working directory and
+/// the `additionalDirectories` setting), so `..` or an absolute path can't
+/// escape it. Returns None without an override.
+pub async fn resolve_command_cwd(
+    requested: Option<&str>,
+    context: &ToolContext,
+) -> Result<Option<PathBuf>, ToolResult> {
+    let Some(requested) = requested.filter(|dir| !dir.is_empty()) else {
+        return Ok(None);
+    };
+    let dir = normalize(&context.cwd.join(requested));
+    if let Some(rejection) = reject_outside_workspace("run a command in", &dir, context).await {
+        return Err(rejection);
+    }
+    if !dir.is_dir() {
+        return Err(ToolResult::error(format!(
+            "Working directory does not exist: {}",
+            dir.display()
+        )));
+    }
+    Ok(Some(dir))
+}
+
+/// Reject a call's `env` if it sets variables it may not
+///
+/// Only the names in [`ALLOWED_CLIENT_ENV_VARS`] may be set, the same as for
+/// the client's `_meta.env`. Permission rules only look at `command`, so an
+/// allowed `Bash(cargo test:*)` must not be able to add `RUSTC_WRAPPER`.
+pub fn check_command_env(env: &HashMap<String, String>) -> Option<ToolResult> {
+    let mut rejected: Vec<&str> = env
+        .keys()
+        .map(String::as_str)
+        .filter(|name| !is_allowed_client_env(name))
+        .collect();
+    if rejected.is_empty() {
+        return None;
+    }
+    rejected.sort_unstable();
+    Some(ToolResult::error(format!(
+        "Environment variables can't be set: {} (allowed: {})",
+        rejected.join(", "),
+        ALLOWED_CLIENT_ENV_VARS.join(", ")
+    )))
+}
+
 /// Bash tool for executing shell commands
 #[derive(Debug, Default)]
 pub struct BashTool;
@@ -72,6 +150,18 @@ struct BashInput {
     /// Run command in background (returns immediately with shell ID)
     #[serde(default)]
     run_in_background: Option<bool>,
+    /// Exit code that counts as success (0 if unset)
+    #[serde(default)]
+    expect_exit_code: Option<i32>,
+    /// Directory to run in instead of the session's working directory
+    #[serde(default)]
+    cwd: Option<String>,
+    /// Run the command this many times and report timings
+    #[serde(default)]
+    repeat: Option<u32>,
+    /// Extra environment variables for the command
+    #[serde(default)]
+    env: Option<HashMap<String, String>>,
 }
 
 impl BashTool {
@@ -145,6 +235,25 @@ impl Tool for BashTool {
                 "run_in_background": {
                     "type": "boolean",
                     "description": "Run command in background. Returns immediately with a shell ID that can be used with BashOutput to retrieve output."
+                },
+                "expect_exit_code": {
+                    "type": "integer",
+                    "description": "Exit code that counts as success (default 0). Use it for commands expected to fail, e.g. a test that should not pass yet."
+                },
+                "cwd": {
+                    "type": "string",
+                    "description": "Directory to run the command in, relative to the working directory (must be inside the workspace). Use this instead of `cd dir &&`."
+                },
+                "repeat": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": MAX_BENCH_RUNS,
+                    "description": "Benchmark the command: run it this many times (max 100) and report min/median/max wall time instead of its output. The timeout applies to all runs together."
+                },
+                "env": {
+                    "type": "object",
+                    "additionalProperties": { "type": "string" },
+                    "description": "Extra environment variables for the command, e.g. {\"RUST_LOG\": \"debug\"}. Only mode, locale and output variables can be set: CI, NODE_ENV, RUST_LOG, RUST_BACKTRACE, NO_COLOR, TZ, LANG and similar."
                 }
             }
         })
@@ -158,6 +267,10 @@ impl Tool for BashTool {
         true // Command execution requires permission
     }
 
+    fn enforces_own_timeout(&self) -> bool {
+        true // Uses the call's `timeout` argument or the configured default
+    }
+
     async fn execute(&self, input: serde_json::Value, context: &ToolContext) -> ToolResult {
         // Check permission before executing
         if let Some(result) = self.check_permission(&input, context) {
@@ -170,8 +283,38 @@ impl Tool for BashTool {
             Err(e) => return ToolResult::error(format!("Invalid input: {}", e)),
         };
 
-        // Prefer Terminal API when available (Client-side PTY)
-        if let Some(terminal_client) = context.terminal_client() {
+        // Run in the requested directory, if any
+        let cwd_context;
+        let context = match resolve_command_cwd(params.cwd.as_deref(), context).await {
+            Ok(None) => context,
+            Ok(Some(dir)) => {
+                tracing::info!(cwd = %dir.display(), "Running Bash command in requested directory");
+                let mut overridden = context.clone();
+                overridden.cwd = dir;
+                cwd_context = overridden;
+                &cwd_context
+            }
+            Err(rejection) => return rejection,
+        };
+
+        if let Some(env) = &params.env {
+            if let Some(rejection) = check_command_env(env) {
+                return rejection;
+            }
+            // Values may be secrets; only log the names
+            tracing::info!(env_keys = ?env.keys().collect::<Vec<_>>(), "Running Bash command with extra environment");
+        }
+
+        // Benchmarks run directly; their output isn't shown
+        if let Some(runs) = params.repeat {
+            return Self::execute_bench(&params, runs, context).await;
+        }
+
+        // Prefer Terminal API when available (Client-side PTY); client
+        // terminals get the session's environment, so a call's `env` runs directly
+        if let Some(terminal_client) = context.terminal_client()
+            && params.env.is_none()
+        {
             if params.run_in_background.unwrap_or(false) {
                 return self
                     .execute_terminal_background(&params, terminal_client, context)
@@ -191,13 +334,139 @@ impl Tool for BashTool {
     }
 }
 
+/// Run one benchmark iteration, returning its exit status and stderr
+///
+/// Only the first `MAX_OUTPUT_SIZE` bytes of stderr are kept; the rest is
+/// read and discarded so a chatty command can't block or exhaust memory.
+async fn run_bench_once(cmd: &mut Command) -> std::io::Result<(std::process::ExitStatus, String)> {
+    let mut child = cmd.spawn()?;
+    let mut stderr = child.stderr.take().ok_or_else(|| {
+        std::io::Error::new(std::io::ErrorKind::BrokenPipe, "stderr is not piped")
+    })?;
+    let mut kept = Vec::new();
+    let read_stderr = async {
+        (&mut stderr)
+            .take(MAX_OUTPUT_SIZE as u64)
+            .read_to_end(&mut kept)
+            .await?;
+        tokio::io::copy(&mut stderr, &mut tokio::io::sink()).await
+    };
+    let (read, status) = tokio::join!(read_stderr, child.wait());
+    read?;
+    Ok((status?, String::from_utf8_lossy(&kept).into_owned()))
+}
+
+/// Timeout for calls without one, from the `Bash` entry of `toolTimeouts`
+fn configured_timeout_ms(context: &ToolContext) -> Option<u64> {
+    context
+        .tool_timeouts()
+        .for_tool("Bash", true)
+        .map(|timeout| u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX))
+}
+
 impl BashTool {
+    /// Run a command `runs` times and report its wall time
+    ///
+    /// Stops at the first failing run, or once the call's timeout (all runs
+    /// together) is used up, reporting the runs completed so far.
+    async fn execute_bench(params: &BashInput, runs: u32, context: &ToolContext) -> ToolResult {
+        let runs = runs.clamp(1, MAX_BENCH_RUNS);
+        let time_limit = Duration::from_millis(
+            params
+                .timeout
+                .or_else(|| configured_timeout_ms(context))
+                .unwrap_or(DEFAULT_BENCH_TIME_LIMIT_MS),
+        );
+        let bench_start = Instant::now();
+        let mut samples = Vec::with_capacity(runs as usize);
+        let mut out_of_time = false;
+
+        for _ in 0..runs {
+            let Some(remaining) = time_limit.checked_sub(bench_start.elapsed()) else {
+                out_of_time = true;
+                break;
+            };
+            let mut cmd = Command::new("bash");
+            cmd.arg("-c")
+                .arg(&params.command)
+                .current_dir(&context.cwd)
+                .envs(context.env())
+                .envs(params.env.iter().flatten())
+                .stdin(Stdio::null())
+                .stdout(Stdio::null())
+                .stderr(Stdio::piped())
+                .kill_on_drop(true);
+
+            let run_start = Instant::now();
+            match timeout(remaining, run_bench_once(&mut cmd)).await {
+                Ok(Ok((status, _))) if status.success() => samples.push(run_start.elapsed()),
+                Ok(Ok((status, stderr))) => {
+                    return ToolResult::error(format!(
+                        "Command failed with exit code {} on run {} of {}\n{}",
+                        status.code().unwrap_or(-1),
+                        samples.len() + 1,
+                        runs,
+                        context.limit_line_length(stderr.trim_end())
+                    ));
+                }
+                Ok(Err(e)) => {
+                    return ToolResult::error(format!("Failed to execute command: {}", e));
+                }
+                Err(_) => {
+                    out_of_time = true;
+                    break;
+                }
+            }
+        }
+
+        if samples.is_empty() {
+            return ToolResult::error(format!(
+                "Benchmark timed out after {}ms before a run completed",
+                time_limit.as_millis()
+            ));
+        }
+
+        let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+        let mut sorted = samples.clone();
+        sorted.sort();
+        let middle = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[middle - 1] + sorted[middle]) / 2
+        } else {
+            sorted[middle]
+        };
+        let (min, max) = (sorted[0], sorted[sorted.len() - 1]);
+
+        let mut text = format!(
+            "Ran `{}` {} time(s): min {:.1}ms, median {:.1}ms, max {:.1}ms",
+            params.command,
+            samples.len(),
+            millis(min),
+            millis(median),
+            millis(max)
+        );
+        if out_of_time {
+            text.push_str(" (stopped early: time limit reached)");
+        }
+
+        ToolResult::success(text).with_metadata(json!({
+            "runs": samples.len(),
+            "requested_runs": runs,
+            "samples_ms": samples.iter().map(|sample| millis(*sample)).collect::<Vec<_>>(),
+            "min_ms": millis(min),
+            "median_ms": millis(median),
+            "max_ms": millis(max),
+            "out_of_time": out_of_time
+        }))
+    }
+
     /// Execute command in foreground (blocking)
     async fn execute_foreground(&self, params: &BashInput, context: &ToolContext) -> ToolResult {
         let cmd_start = Instant::now();
 
-        // Use timeout as specified by user, without limiting maximum
-        let timeout_ms = params.timeout;
+        // Use timeout as specified by user, without limiting maximum; calls
+        // without one get the configured Bash timeout, if any
+        let timeout_ms = params.timeout.or_else(|| configured_timeout_ms(context));
 
         // Stage 1: Build the command
         let build_start = Instant::now();
@@ -205,6 +474,8 @@ impl BashTool {
         cmd.arg("-c")
             .arg(&params.command)
             .current_dir(&context.cwd)
+            .envs(context.env())
+            .envs(params.env.iter().flatten())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped());
         let build_duration = build_start.elapsed();
@@ -281,10 +552,16 @@ impl BashTool {
             }
             result_text.push_str(&stderr);
         }
+        let test_results = parse_test_output(&result_text);
 
-        // Truncate if too long (using UTF-8 safe truncation)
+        // Truncate overly long lines, then the whole output (using UTF-8 safe truncation)
+        let mut result_text = context.limit_line_length(&result_text).into_owned();
         let was_truncated = result_text.len() > MAX_OUTPUT_SIZE;
         Self::safe_truncate(&mut result_text, MAX_OUTPUT_SIZE);
+        if let Some(summary) = &test_results {
+            result_text.push('\n');
+            result_text.push_str(&summary.summary_line());
+        }
 
         // Handle empty output
         if result_text.is_empty() {
@@ -295,7 +572,8 @@ impl BashTool {
         let total_elapsed = cmd_start.elapsed();
 
         let exit_code = output.status.code().unwrap_or(-1);
-        let success = output.status.success();
+        let failure = unexpected_exit_message(exit_code, params.expect_exit_code);
+        let success = failure.is_none();
 
         // Log execution summary
         tracing::info!(
@@ -311,25 +589,25 @@ impl BashTool {
             "Bash command execution summary"
         );
 
-        if success {
-            ToolResult::success(result_text).with_metadata(json!({
+        match failure {
+            None => ToolResult::success(result_text).with_metadata(json!({
                 "exit_code": exit_code,
+                "expected_exit_code": params.expect_exit_code,
                 "truncated": was_truncated,
                 "description": params.description,
                 "total_elapsed_ms": total_elapsed.as_millis(),
-                "exec_duration_ms": exec_duration.as_millis()
-            }))
-        } else {
-            ToolResult::error(format!(
-                "Command failed with exit code {}\n{}",
-                exit_code, result_text
-            ))
-            .with_metadata(json!({
-                "exit_code": exit_code,
-                "truncated": was_truncated,
-                "total_elapsed_ms": total_elapsed.as_millis(),
-                "exec_duration_ms": exec_duration.as_millis()
-            }))
+                "exec_duration_ms": exec_duration.as_millis(),
+                "test_results": test_results
+            })),
+            Some(message) => ToolResult::error(format!("{}\n{}", message, result_text))
+                .with_metadata(json!({
+                    "exit_code": exit_code,
+                    "expected_exit_code": params.expect_exit_code,
+                    "truncated": was_truncated,
+                    "total_elapsed_ms": total_elapsed.as_millis(),
+                    "exec_duration_ms": exec_duration.as_millis(),
+                    "test_results": test_results
+                })),
         }
     }
 
@@ -348,6 +626,8 @@ impl BashTool {
             c.arg("-c")
                 .arg(&params.command)
                 .current_dir(&context.cwd)
+                .envs(context.env())
+                .envs(params.env.iter().flatten())
                 .stdout(Stdio::piped())
                 .stderr(Stdio::piped());
         });
@@ -378,7 +658,8 @@ impl BashTool {
         };
 
         // Create background terminal
-        let terminal = BackgroundTerminal::new_running(child_handle);
+        let terminal =
+            BackgroundTerminal::new_running_with_limit(child_handle, manager.max_output_bytes());
 
         // Get reference to output buffer for the read task
         let output_buffer = match &terminal {
@@ -386,23 +667,24 @@ impl BashTool {
             BackgroundTerminal::Finished { .. } => unreachable!(),
         };
 
+        // Tell the client when the command finishes, however it ends
+        if context.tool_use_id().is_some() {
+            Self::notify_on_exit(&manager, shell_id.clone(), context.clone());
+        }
+
         // Register with manager
         let shell_id_clone = shell_id.clone();
-        manager.register(shell_id.clone(), terminal);
+        manager.register(shell_id.clone(), &params.command, terminal);
 
         // Spawn task to read output
         let manager_clone = manager.clone();
         let description = params.description.clone();
         tokio::spawn(async move {
-            let mut combined_output = String::new();
-
             // Read stdout
             if let Some(stdout) = stdout {
                 let reader = BufReader::new(stdout);
                 let mut lines = reader.lines();
                 while let Ok(Some(line)) = lines.next_line().await {
-                    combined_output.push_str(&line);
-                    combined_output.push('\n');
                     let mut buffer = output_buffer.lock().await;
                     buffer.push_str(&line);
                     buffer.push('\n');
@@ -414,11 +696,6 @@ impl BashTool {
                 let reader = BufReader::new(stderr);
                 let mut lines = reader.lines();
                 while let Ok(Some(line)) = lines.next_line().await {
-                    if !combined_output.is_empty() && !combined_output.ends_with('\n') {
-                        combined_output.push('\n');
-                    }
-                    combined_output.push_str(&line);
-                    combined_output.push('\n');
                     let mut buffer = output_buffer.lock().await;
                     buffer.push_str(&line);
                     buffer.push('\n');
@@ -460,6 +737,31 @@ impl BashTool {
         }))
     }
 
+    /// Send a background exit notification once `shell_id` finishes
+    ///
+    /// Subscribes before the shell is registered so its exit can't be missed.
+    pub(crate) fn notify_on_exit(
+        manager: &BackgroundProcessManager,
+        shell_id: String,
+        context: ToolContext,
+    ) {
+        let mut exits = manager.subscribe_exits();
+        tokio::spawn(async move {
+            loop {
+                match exits.recv().await {
+                    Ok(exit) if exit.shell_id == shell_id => {
+                        if let Err(e) = context.send_background_exit(&exit) {
+                            tracing::debug!("Failed to send background exit: {}", e);
+                        }
+                        break;
+                    }
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+    }
+
     /// Execute command using Terminal API in foreground (blocking)
     ///
     /// Uses Client-side PTY for execution, which provides better terminal
@@ -470,8 +772,9 @@ impl BashTool {
         terminal_client: &Arc<TerminalClient>,
         context: &ToolContext,
     ) -> ToolResult {
-        // Use timeout as specified by user, without limiting maximum
-        let timeout_ms = params.timeout;
+        // Use timeout as specified by user, without limiting maximum; calls
+        // without one get the configured Bash timeout, if any
+        let timeout_ms = params.timeout.or_else(|| configured_timeout_ms(context));
 
         // Create terminal with bash -c command
         let terminal_id = match terminal_client
@@ -529,33 +832,31 @@ impl BashTool {
                 #[allow(clippy::cast_possible_wrap)]
                 let exit_code = exit_status.exit_code.map(|c| c as i32).unwrap_or(-1);
 
-                // Apply UTF-8 safe truncation if needed
+                // Apply line length limit and UTF-8 safe truncation if needed
                 let mut result_text = if output.is_empty() {
                     "(no output)".to_string()
                 } else {
-                    output
+                    context.limit_line_length(&output).into_owned()
                 };
 
                 let was_truncated = result_text.len() > MAX_OUTPUT_SIZE;
                 Self::safe_truncate(&mut result_text, MAX_OUTPUT_SIZE);
 
-                if exit_code == 0 {
-                    ToolResult::success(result_text).with_metadata(json!({
+                match unexpected_exit_message(exit_code, params.expect_exit_code) {
+                    None => ToolResult::success(result_text).with_metadata(json!({
                         "exit_code": exit_code,
+                        "expected_exit_code": params.expect_exit_code,
                         "truncated": was_truncated,
                         "description": params.description,
                         "terminal_api": true
-                    }))
-                } else {
-                    ToolResult::error(format!(
-                        "Command failed with exit code {}\n{}",
-                        exit_code, result_text
-                    ))
-                    .with_metadata(json!({
-                        "exit_code": exit_code,
-                        "truncated": was_truncated,
-                        "terminal_api": true
-                    }))
+                    })),
+                    Some(message) => ToolResult::error(format!("{}\n{}", message, result_text))
+                        .with_metadata(json!({
+                            "exit_code": exit_code,
+                            "expected_exit_code": params.expect_exit_code,
+                            "truncated": was_truncated,
+                            "terminal_api": true
+                        })),
                 }
             }
             Ok(Err(e)) => ToolResult::error(format!("Terminal execution failed: {}", e)),
@@ -622,6 +923,7 @@ impl BashTool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mcp::registry::ToolTimeouts;
     use tempfile::TempDir;
 
     #[tokio::test]
@@ -662,6 +964,70 @@ mod tests {
         assert!(result.content.contains(temp_dir.path().to_str().unwrap()));
     }
 
+    #[tokio::test]
+    async fn test_bash_uses_client_env_from_meta() {
+        let temp_dir = TempDir::new().unwrap();
+        let meta = crate::types::NewSessionMeta::from_request_meta(Some(&json!({
+            "env": {"DATABASE_URL": "postgres://localhost/app", "LD_PRELOAD": "/tmp/evil.so"}
+        })));
+        let tool = BashTool::new();
+        let context = ToolContext::new("test", temp_dir.path()).with_env(meta.env);
+
+        let result = tool
+            .execute(
+                json!({
+                    "command": "echo \"db=$DATABASE_URL preload=$LD_PRELOAD\""
+                }),
+                &context,
+            )
+            .await;
+
+        assert!(!result.is_error);
+        assert!(result.content.contains("db=postgres://localhost/app"));
+        assert!(!result.content.contains("evil.so"));
+    }
+
+    #[tokio::test]
+    async fn test_bash_env_argument() {
+        let temp_dir = TempDir::new().unwrap();
+        let tool = BashTool::new();
+        let context = ToolContext::new("test", temp_dir.path())
+            .with_env(HashMap::from([("NODE_ENV".to_string(), "dev".to_string())]));
+
+        // Added on top of the session environment, overriding it
+        let result = tool
+            .execute(
+                json!({
+                    "command": "echo \"$NODE_ENV $RUST_LOG\"",
+                    "env": {"NODE_ENV": "test", "RUST_LOG": "debug"}
+                }),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("test debug"));
+
+        // Variables that make tools load code are rejected
+        let result = tool
+            .execute(
+                json!({
+                    "command": "cargo test",
+                    "env": {
+                        "RUSTC_WRAPPER": "/tmp/evil",
+                        "GIT_SSH_COMMAND": "/tmp/evil",
+                        "PATH": "/tmp",
+                        "CI": "1"
+                    }
+                }),
+                &context,
+            )
+            .await;
+        assert!(result.is_error);
+        assert!(result.content.starts_with(
+            "Environment variables can't be set: GIT_SSH_COMMAND, PATH, RUSTC_WRAPPER ("
+        ));
+    }
+
     #[tokio::test]
     async fn test_bash_failure() {
         let temp_dir = TempDir::new().unwrap();
@@ -681,6 +1047,96 @@ mod tests {
         assert!(result.content.contains("exit code 1"));
     }
 
+    #[tokio::test]
+    async fn test_bash_expect_exit_code() {
+        let temp_dir = TempDir::new().unwrap();
+        let tool = BashTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+
+        let result = tool
+            .execute(
+                json!({"command": "exit 3", "expect_exit_code": 3}),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "got: {}", result.content);
+        assert_eq!(result.metadata.unwrap()["exit_code"], 3);
+
+        // Exiting with 0 is now the unexpected outcome
+        let result = tool
+            .execute(json!({"command": "true", "expect_exit_code": 3}), &context)
+            .await;
+        assert!(result.is_error);
+        assert!(
+            result
+                .content
+                .starts_with("Command exited with code 0, expected 3")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bash_cwd_override() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir(temp_dir.path().join("sub")).unwrap();
+        let tool = BashTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+
+        let result = tool
+            .execute(json!({"command": "pwd", "cwd": "sub"}), &context)
+            .await;
+        assert!(!result.is_error, "got: {}", result.content);
+        assert!(result.content.trim_end().ends_with("sub"));
+
+        // Escapes from the workspace are refused
+        for cwd in ["..", "/"] {
+            let result = tool
+                .execute(json!({"command": "pwd", "cwd": cwd}), &context)
+                .await;
+            assert!(result.is_error, "cwd {cwd} should be refused");
+            assert!(result.content.contains("outside the workspace"));
+        }
+
+        let result = tool
+            .execute(json!({"command": "pwd", "cwd": "missing"}), &context)
+            .await;
+        assert!(result.is_error);
+        assert!(
+            result
+                .content
+                .starts_with("Working directory does not exist")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bash_repeat_reports_timings() {
+        let temp_dir = TempDir::new().unwrap();
+        let tool = BashTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+
+        let result = tool
+            .execute(json!({"command": "true", "repeat": 2}), &context)
+            .await;
+        assert!(!result.is_error, "got: {}", result.content);
+        assert!(result.content.starts_with("Ran `true` 2 time(s): min "));
+
+        let metadata = result.metadata.unwrap();
+        assert_eq!(metadata["runs"], 2);
+        let samples = metadata["samples_ms"].as_array().unwrap();
+        assert_eq!(samples.len(), 2);
+        assert!(samples.iter().all(|sample| sample.as_f64().unwrap() > 0.0));
+        assert!(metadata["min_ms"].as_f64() <= metadata["max_ms"].as_f64());
+
+        let result = tool
+            .execute(json!({"command": "exit 2", "repeat": 3}), &context)
+            .await;
+        assert!(result.is_error);
+        assert!(
+            result
+                .content
+                .starts_with("Command failed with exit code 2 on run 1 of 3")
+        );
+    }
+
     #[tokio::test]
     async fn test_bash_stderr() {
         let temp_dir = TempDir::new().unwrap();
@@ -720,6 +1176,22 @@ mod tests {
         assert!(result.content.contains("timed out"));
     }
 
+    #[tokio::test]
+    async fn test_bash_uses_configured_timeout() {
+        let temp_dir = TempDir::new().unwrap();
+        let tool = BashTool::new();
+        let timeouts = ToolTimeouts {
+            per_tool: [("Bash".to_string(), Duration::from_millis(100))].into(),
+            ..ToolTimeouts::default()
+        };
+        let context = ToolContext::new("test", temp_dir.path()).with_tool_timeouts(timeouts);
+
+        let result = tool.execute(json!({"command": "sleep 10"}), &context).await;
+
+        assert!(result.is_error);
+        assert!(result.content.contains("timed out"));
+    }
+
     #[test]
     fn test_bash_tool_properties() {
         let tool = BashTool::new();
@@ -3,16 +3,21 @@
 //! This tool retrieves incremental output from a running or completed background
 //! shell process started with `run_in_background=true`.
 //!
+//! Each call returns the output produced since the previous one, optionally
+//! only the lines matching a `filter` regex.
+//!
 //! Supports two execution modes:
 //! - Direct process execution: shell IDs starting with "shell-"
 //! - Terminal API: shell IDs starting with "term-" (Client-side PTY)
 
 use async_trait::async_trait;
+use regex::Regex;
 use serde::Deserialize;
 use serde_json::{Value, json};
 
 use super::base::Tool;
 use crate::mcp::registry::{ToolContext, ToolResult};
+use crate::session::BackgroundTerminal;
 use crate::terminal::TerminalId;
 
 /// Prefix for Terminal API shell IDs
@@ -27,6 +32,9 @@ pub struct BashOutputTool;
 struct BashOutputInput {
     /// The ID of the background shell to get output from
     bash_id: String,
+    /// Only show output lines matching this regex
+    #[serde(default)]
+    filter: Option<String>,
 }
 
 #[async_trait]
@@ -47,6 +55,10 @@ impl Tool for BashOutputTool {
                 "bash_id": {
                     "type": "string",
                     "description": "The ID of the background shell returned when the command was started"
+                },
+                "filter": {
+                    "type": "string",
+                    "description": "Optional regular expression; only output lines matching it are shown. Lines that don't match are discarded and can't be read later."
                 }
             },
             "required": ["bash_id"]
@@ -60,19 +72,32 @@ impl Tool for BashOutputTool {
             Err(e) => return ToolResult::error(format!("Invalid input: {}", e)),
         };
 
+        let filter = match params.filter.as_deref().map(Regex::new).transpose() {
+            Ok(filter) => filter,
+            Err(e) => return ToolResult::error(format!("Invalid filter regex: {}", e)),
+        };
+
         // Check if this is a Terminal API shell
         if let Some(terminal_id) = params.bash_id.strip_prefix(TERMINAL_API_PREFIX) {
-            return self.get_terminal_output(terminal_id, context).await;
+            return self
+                .get_terminal_output(terminal_id, filter.as_ref(), context)
+                .await;
         }
 
         // Fall back to background process manager
-        self.get_background_output(&params.bash_id, context).await
+        self.get_background_output(&params.bash_id, filter.as_ref(), context)
+            .await
     }
 }
 
 impl BashOutputTool {
     /// Get output from Terminal API
-    async fn get_terminal_output(&self, terminal_id: &str, context: &ToolContext) -> ToolResult {
+    async fn get_terminal_output(
+        &self,
+        terminal_id: &str,
+        filter: Option<&Regex>,
+        context: &ToolContext,
+    ) -> ToolResult {
         let Some(terminal_client) = context.terminal_client() else {
             return ToolResult::error("Terminal API not available");
         };
@@ -99,7 +124,7 @@ impl BashOutputTool {
                     None => "running".to_string(),
                 };
 
-                let output = &response.output;
+                let output = filter_lines(&response.output, filter);
                 let response_text = if output.is_empty() {
                     format!("Status: {}\n\n(No output yet)", status)
                 } else {
@@ -117,7 +142,12 @@ impl BashOutputTool {
     }
 
     /// Get output from background process manager
-    async fn get_background_output(&self, bash_id: &str, context: &ToolContext) -> ToolResult {
+    async fn get_background_output(
+        &self,
+        bash_id: &str,
+        filter: Option<&Regex>,
+        context: &ToolContext,
+    ) -> ToolResult {
         // Get the background process manager from context
         let Some(manager) = context.background_processes() else {
             return ToolResult::error("Background process manager not available");
@@ -129,8 +159,15 @@ impl BashOutputTool {
         };
 
         // Get incremental output
-        let output = terminal.get_incremental_output().await;
-        let status = terminal.status_str();
+        let output = filter_lines(&terminal.get_incremental_output().await, filter);
+        let exit_code = match &*terminal {
+            BackgroundTerminal::Finished { status, .. } => status.exit_code(),
+            BackgroundTerminal::Running { .. } => None,
+        };
+        let status = match exit_code {
+            Some(code) => format!("{} (exit code {})", terminal.status_str(), code),
+            None => terminal.status_str().to_string(),
+        };
 
         // Format response
         let response = if output.is_empty() {
@@ -139,13 +176,33 @@ impl BashOutputTool {
             format!("Status: {}\n\n{}", status, output)
         };
 
-        ToolResult::success(response)
+        ToolResult::success(response).with_metadata(json!({
+            "shell_id": bash_id,
+            "status": terminal.status_str(),
+            "exit_code": exit_code
+        }))
+    }
+}
+
+/// Keep only the lines of `output` matching `filter`, if any
+fn filter_lines(output: &str, filter: Option<&Regex>) -> String {
+    let Some(filter) = filter else {
+        return output.to_string();
+    };
+    let mut filtered = String::new();
+    for line in output.lines().filter(|line| filter.is_match(line)) {
+        filtered.push_str(line);
+        filtered.push('\n');
     }
+    filtered
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::session::{BackgroundProcessManager, TerminalExitStatus};
+    use std::sync::Arc;
+    use tempfile::TempDir;
 
     #[test]
     fn test_bash_output_tool_properties() {
@@ -168,4 +225,58 @@ mod tests {
                 .contains(&json!("bash_id"))
         );
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_bash_output_polls_background_shell() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = Arc::new(BackgroundProcessManager::new());
+        let context =
+            ToolContext::new("test", temp_dir.path()).with_background_processes(manager.clone());
+        let child = tokio::process::Command::new("true").spawn().unwrap();
+        manager.register(
+            "shell-1".to_string(),
+            "make",
+            BackgroundTerminal::new_running_unwrapped(child),
+        );
+        manager
+            .get("shell-1")
+            .unwrap()
+            .append_output("compiling a\nwarning: unused\n")
+            .await;
+        let tool = BashOutputTool;
+
+        let result = tool.execute(json!({"bash_id": "shell-1"}), &context).await;
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(
+            result.content,
+            "Status: running\n\ncompiling a\nwarning: unused\n"
+        );
+
+        // Only output since the last poll, filtered, with the exit status
+        manager
+            .get("shell-1")
+            .unwrap()
+            .append_output("compiling b\nwarning: deprecated\n")
+            .await;
+        manager
+            .finish_terminal("shell-1", TerminalExitStatus::Exited(1))
+            .await;
+        let result = tool
+            .execute(
+                json!({"bash_id": "shell-1", "filter": "^warning"}),
+                &context,
+            )
+            .await;
+        assert_eq!(
+            result.content,
+            "Status: exited (exit code 1)\n\nwarning: deprecated\n"
+        );
+        assert_eq!(result.metadata.unwrap()["exit_code"], 1);
+
+        let result = tool
+            .execute(json!({"bash_id": "shell-1", "filter": "("}), &context)
+            .await;
+        assert!(result.is_error);
+    }
 }
@@ -0,0 +1,539 @@
+//! CargoAdd tool implementation
+//!
+//! Adds a dependency to a `Cargo.toml`, or updates one that is already
+//! there, the way `cargo add` does. The manifest is edited with `toml_edit`,
+//! so comments, key order and the style of existing entries are kept; a new
+//! dependency is written as `name = "version"` when only a version is given
+//! and as an inline table otherwise. Other manifest keys can be set with
+//! TomlEdit.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use toml_edit::{Array, DocumentMut, InlineTable, Item, Table, TableLike};
+
+use super::base::{Tool, ToolKind};
+use super::locked_file::LockedFile;
+use crate::mcp::registry::{ToolContext, ToolResult};
+
+/// Manifest edited when no path is given
+const DEFAULT_MANIFEST: &str = "Cargo.toml";
+
+/// CargoAdd tool for adding or updating dependencies
+#[derive(Debug, Default)]
+pub struct CargoAddTool;
+
+/// Dependency table a dependency is added to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum DependencyKind {
+    /// `[dependencies]`
+    #[default]
+    Normal,
+    /// `[dev-dependencies]`
+    Dev,
+    /// `[build-dependencies]`
+    Build,
+}
+
+impl DependencyKind {
+    fn table_name(self) -> &'static str {
+        match self {
+            Self::Normal => "dependencies",
+            Self::Dev => "dev-dependencies",
+            Self::Build => "build-dependencies",
+        }
+    }
+}
+
+/// CargoAdd tool input parameters
+#[derive(Debug, Default, Deserialize)]
+struct CargoAddInput {
+    /// Path to the manifest (default: Cargo.toml in the working directory)
+    #[serde(default, alias = "file_path")]
+    manifest_path: Option<String>,
+    /// Name of the crate
+    #[serde(alias = "crate")]
+    name: String,
+    /// Version requirement
+    #[serde(default)]
+    version: Option<String>,
+    /// Features to enable, added to the ones already enabled
+    #[serde(default)]
+    features: Vec<String>,
+    /// Whether the crate's default features are enabled
+    #[serde(default)]
+    default_features: Option<bool>,
+    /// Whether the dependency is optional
+    #[serde(default)]
+    optional: Option<bool>,
+    /// Local path of the crate
+    #[serde(default)]
+    path: Option<String>,
+    /// Git repository of the crate
+    #[serde(default)]
+    git: Option<String>,
+    /// Dependency table to use
+    #[serde(default)]
+    kind: DependencyKind,
+    /// Platform the dependency is limited to, e.g. `cfg(unix)`
+    #[serde(default)]
+    target: Option<String>,
+}
+
+impl CargoAddInput {
+    /// Whether anything besides the version is set
+    fn has_details(&self) -> bool {
+        !self.features.is_empty()
+            || self.default_features.is_some()
+            || self.optional.is_some()
+            || self.path.is_some()
+            || self.git.is_some()
+    }
+
+    /// Header of the dependency table, e.g. `[target."cfg(unix)".dependencies]`
+    fn table_header(&self) -> String {
+        match &self.target {
+            Some(target) => format!("[target.\"{}\".{}]", target, self.kind.table_name()),
+            None => format!("[{}]", self.kind.table_name()),
+        }
+    }
+}
+
+/// Result of adding a dependency to a manifest
+#[derive(Debug)]
+struct AddedDependency {
+    /// The whole updated manifest
+    content: String,
+    /// The dependency entry as it was written before, or None if it was added
+    previous: Option<String>,
+}
+
+impl CargoAddTool {
+    /// Create a new CargoAdd tool instance
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Tool for CargoAddTool {
+    fn name(&self) -> &str {
+        "CargoAdd"
+    }
+
+    fn description(&self) -> &str {
+        "Add a dependency to a Cargo.toml, or update an existing one, like `cargo add`. \
+         Comments, ordering and formatting of the manifest are preserved. Features are added \
+         to the ones already enabled. Prefer this over Edit for changing dependencies."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "required": ["name"],
+            "properties": {
+                "manifest_path": {
+                    "type": "string",
+                    "description": "Path to the Cargo.toml (default: Cargo.toml in the working directory)"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Name of the crate"
+                },
+                "version": {
+                    "type": "string",
+                    "description": "Version requirement, e.g. \"1.0\""
+                },
+                "features": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Features to enable"
+                },
+                "default_features": {
+                    "type": "boolean",
+                    "description": "Whether the crate's default features are enabled"
+                },
+                "optional": {
+                    "type": "boolean",
+                    "description": "Whether the dependency is optional"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Local path of the crate"
+                },
+                "git": {
+                    "type": "string",
+                    "description": "Git repository URL of the crate"
+                },
+                "kind": {
+                    "type": "string",
+                    "enum": ["normal", "dev", "build"],
+                    "description": "Add to [dependencies] (normal, the default), [dev-dependencies] or [build-dependencies]"
+                },
+                "target": {
+                    "type": "string",
+                    "description": "Limit the dependency to a platform, e.g. cfg(unix)"
+                }
+            }
+        })
+    }
+
+    fn kind(&self) -> ToolKind {
+        ToolKind::Edit
+    }
+
+    fn requires_permission(&self) -> bool {
+        true // Editing requires permission
+    }
+
+    async fn execute(&self, input: Value, context: &ToolContext) -> ToolResult {
+        // Parse input
+        let params: CargoAddInput = match serde_json::from_value(input) {
+            Ok(p) => p,
+            Err(e) => return ToolResult::error(format!("Invalid input: {}", e)),
+        };
+        if params.name.trim().is_empty() {
+            return ToolResult::error("Invalid input: name cannot be empty");
+        }
+
+        // Resolve path relative to working directory if not absolute
+        let manifest = params.manifest_path.as_deref().unwrap_or(DEFAULT_MANIFEST);
+        let path = if std::path::Path::new(manifest).is_absolute() {
+            std::path::PathBuf::from(manifest)
+        } else {
+            context.cwd.join(manifest)
+        };
+
+        // Lock and read the manifest
+        let file = match LockedFile::open(&path, context).await {
+            Ok(file) => file,
+            Err(result) => return result,
+        };
+
+        let table = params.table_header();
+        let added = match add_dependency(file.content(), &params) {
+            Ok(added) => added,
+            Err(message) => {
+                return ToolResult::error(format!(
+                    "Cannot add {} to {}: {}",
+                    params.name,
+                    path.display(),
+                    message
+                ));
+            }
+        };
+
+        if added.content == file.content() {
+            return ToolResult::success(format!(
+                "{} in {} of {} is already up to date",
+                params.name,
+                table,
+                path.display()
+            ))
+            .with_metadata(json!({
+                "path": path.display().to_string(),
+                "name": params.name,
+                "table": table,
+                "changed": false
+            }));
+        }
+
+        if let Err(result) = file.write(&added.content, context).await {
+            return result;
+        }
+
+        tracing::info!(
+            file_path = %path.display(),
+            name = %params.name,
+            table = %table,
+            added = added.previous.is_none(),
+            "Dependency added"
+        );
+
+        let message = match &added.previous {
+            Some(previous) => format!(
+                "Updated {} in {} of {} (was {})",
+                params.name,
+                table,
+                path.display(),
+                previous
+            ),
+            None => format!("Added {} to {} of {}", params.name, table, path.display()),
+        };
+        ToolResult::success(message).with_metadata(json!({
+            "path": path.display().to_string(),
+            "name": params.name,
+            "table": table,
+            "changed": true,
+            "added": added.previous.is_none(),
+            "previous": added.previous,
+            "verified": context.verify_writes()
+        }))
+    }
+}
+
+/// Add or update the dependency described by `dep` in a manifest
+fn add_dependency(content: &str, dep: &CargoAddInput) -> Result<AddedDependency, String> {
+    let mut document: DocumentMut = content
+        .parse()
+        .map_err(|e: toml_edit::TomlError| format!("invalid TOML: {}", e.message()))?;
+
+    let mut parent = document.as_item_mut();
+    if let Some(target) = &dep.target {
+        parent = child_table(parent, "target", true)?;
+        parent = child_table(parent, target, true)?;
+    }
+    let item = child_table(parent, dep.kind.table_name(), false)?;
+    let Some(table) = item.as_table_like_mut() else {
+        return Err(format!("{} is not a table", dep.table_header()));
+    };
+
+    let previous = if let Some(entry) = table.get_mut(&dep.name) {
+        let previous = entry_text(entry);
+        update_entry(entry, dep)?;
+        Some(previous)
+    } else {
+        if dep.version.is_none() && dep.path.is_none() && dep.git.is_none() {
+            return Err("a version, path or git source is required".to_string());
+        }
+        let sorted = is_sorted(table);
+        let mut entry = if dep.version.is_some() && !dep.has_details() {
+            Item::Value(String::new().into())
+        } else {
+            Item::Value(InlineTable::new().into())
+        };
+        update_entry(&mut entry, dep)?;
+        table.insert(&dep.name, entry);
+        // Keep a sorted table sorted, as cargo add does
+        if sorted {
+            table.sort_values();
+        }
+        None
+    };
+
+    let content = document.to_string();
+    // Make sure the manifest still parses before it is written
+    content
+        .parse::<DocumentMut>()
+        .map_err(|e| format!("the edited manifest is not valid TOML: {}", e.message()))?;
+    Ok(AddedDependency { content, previous })
+}
+
+/// Get the table at `key` below `parent`, creating it if it's missing
+fn child_table<'a>(
+    parent: &'a mut Item,
+    key: &str,
+    implicit: bool,
+) -> Result<&'a mut Item, String> {
+    let Some(table) = parent.as_table_like_mut() else {
+        return Err(format!(
+            "cannot create {key} in a value that is not a table"
+        ));
+    };
+    if table.get(key).is_none() {
+        let mut child = Table::new();
+        child.set_implicit(implicit);
+        table.insert(key, Item::Table(child));
+    }
+    match table.get_mut(key) {
+        Some(child) if child.is_table_like() => Ok(child),
+        _ => Err(format!("{key} is not a table")),
+    }
+}
+
+/// Apply the requested fields to a dependency entry
+fn update_entry(entry: &mut Item, dep: &CargoAddInput) -> Result<(), String> {
+    // A version string becomes an inline table once it needs more fields
+    if let Some(version) = entry.as_str()
+        && dep.has_details()
+    {
+        let mut table = InlineTable::new();
+        table.insert("version", version.into());
+        let decor = entry.as_value().map(|v| v.decor().clone());
+        let mut value = toml_edit::Value::InlineTable(table);
+        if let Some(decor) = decor {
+            *value.decor_mut() = decor;
+        }
+        *entry = Item::Value(value);
+    }
+
+    if let Some(value) = entry.as_value_mut()
+        && value.is_str()
+    {
+        if let Some(version) = &dep.version {
+            set_value(value, version.as_str().into());
+        }
+        return Ok(());
+    }
+
+    let Some(table) = entry.as_table_like_mut() else {
+        return Err(format!(
+            "{} is neither a version string nor a table",
+            dep.name
+        ));
+    };
+    if let Some(version) = &dep.version {
+        set_field(table, "version", version.as_str().into());
+    }
+    if let Some(path) = &dep.path {
+        set_field(table, "path", path.as_str().into());
+    }
+    if let Some(git) = &dep.git {
+        set_field(table, "git", git.as_str().into());
+    }
+    if !dep.features.is_empty() {
+        if let Some(features) = table.get_mut("features").and_then(Item::as_array_mut) {
+            for feature in &dep.features {
+                if !features.iter().any(|f| f.as_str() == Some(feature)) {
+                    features.push(feature.as_str());
+                }
+            }
+        } else {
+            let features: Array = dep.features.iter().map(String::as_str).collect();
+            set_field(table, "features", features.into());
+        }
+    }
+    if let Some(default_features) = dep.default_features {
+        set_field(table, "default-features", default_features.into());
+    }
+    if let Some(optional) = dep.optional {
+        set_field(table, "optional", optional.into());
+    }
+    Ok(())
+}
+
+/// Set a key of a dependency table, keeping the spacing around an old value
+fn set_field(table: &mut dyn TableLike, key: &str, value: toml_edit::Value) {
+    if let Some(old) = table.get_mut(key).and_then(Item::as_value_mut) {
+        set_value(old, value);
+    } else {
+        table.insert(key, Item::Value(value));
+    }
+}
+
+/// Replace a value, keeping its comments and spacing
+fn set_value(old: &mut toml_edit::Value, mut value: toml_edit::Value) {
+    *value.decor_mut() = old.decor().clone();
+    *old = value;
+}
+
+/// A dependency entry as written, without surrounding comments and spacing
+fn entry_text(entry: &Item) -> String {
+    match entry.as_value() {
+        Some(value) => {
+            let mut value = value.clone();
+            value.decor_mut().clear();
+            value.to_string()
+        }
+        None => entry.to_string().trim().to_string(),
+    }
+}
+
+/// Whether the keys of a table are in alphabetical order
+fn is_sorted(table: &dyn TableLike) -> bool {
+    let keys: Vec<&str> = table.iter().map(|(key, _)| key).collect();
+    keys.is_sorted()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const MANIFEST: &str = r#"[package]
+name = "demo"   # keep this comment
+version = "0.1.0"
+
+[dependencies]
+# Serialization
+serde = { version = "1.0", features = ["derive"] }
+tokio = "1"
+
+[dev-dependencies]
+tempfile = "3"
+"#;
+
+    fn dep(name: &str) -> CargoAddInput {
+        CargoAddInput {
+            name: name.to_string(),
+            ..CargoAddInput::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cargo_add_preserves_formatting() {
+        let temp_dir = TempDir::new().unwrap();
+        let manifest = temp_dir.path().join("Cargo.toml");
+        std::fs::write(&manifest, MANIFEST).unwrap();
+
+        let tool = CargoAddTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+
+        let result = tool
+            .execute(json!({"name": "anyhow", "version": "1.0"}), &context)
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(
+            std::fs::read_to_string(&manifest).unwrap(),
+            MANIFEST.replace("# Serialization", "anyhow = \"1.0\"\n# Serialization")
+        );
+        assert_eq!(result.metadata.unwrap()["added"], true);
+
+        // Features are merged into an existing inline table
+        let result = tool
+            .execute(
+                json!({"name": "serde", "version": "1.0.200", "features": ["derive", "rc"]}),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(
+            std::fs::read_to_string(&manifest)
+                .unwrap()
+                .contains("serde = { version = \"1.0.200\", features = [\"derive\", \"rc\"] }\n")
+        );
+        assert_eq!(
+            result.metadata.unwrap()["previous"],
+            "{ version = \"1.0\", features = [\"derive\"] }"
+        );
+    }
+
+    #[test]
+    fn test_add_dependency_tables() {
+        // A version string turns into an inline table when features are added
+        let mut tokio = dep("tokio");
+        tokio.features = vec!["full".to_string()];
+        let added = add_dependency(MANIFEST, &tokio).unwrap();
+        assert!(
+            added
+                .content
+                .contains("tokio = { version = \"1\", features = [\"full\"] }\n")
+        );
+        assert_eq!(added.previous.as_deref(), Some("\"1\""));
+
+        let mut insta = dep("insta");
+        insta.version = Some("1".to_string());
+        insta.kind = DependencyKind::Dev;
+        let added = add_dependency(MANIFEST, &insta).unwrap();
+        assert!(
+            added
+                .content
+                .ends_with("[dev-dependencies]\ninsta = \"1\"\ntempfile = \"3\"\n")
+        );
+
+        let mut libc = dep("libc");
+        libc.version = Some("0.2".to_string());
+        libc.target = Some("cfg(unix)".to_string());
+        let added = add_dependency(MANIFEST, &libc).unwrap();
+        assert!(
+            added
+                .content
+                .ends_with("\n[target.\"cfg(unix)\".dependencies]\nlibc = \"0.2\"\n")
+        );
+
+        // A new dependency needs a source
+        assert!(add_dependency(MANIFEST, &dep("rand")).is_err());
+        assert!(add_dependency("[dependencies", &dep("rand")).is_err());
+    }
+}
@@ -0,0 +1,185 @@
+//! CreateDirectory tool implementation
+//!
+//! Creates directories inside the workspace without going through Bash, so
+//! the call gets a regular permission prompt and tool notification.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use std::io::ErrorKind;
+
+use super::base::{Tool, ToolKind};
+use super::workspace::{normalize, reject_outside_workspace};
+use crate::mcp::registry::{ToolContext, ToolResult};
+
+/// CreateDirectory tool for making directories
+#[derive(Debug, Default)]
+pub struct CreateDirectoryTool;
+
+/// CreateDirectory tool input parameters
+#[derive(Debug, Deserialize)]
+struct CreateDirectoryInput {
+    /// Directory to create (`dir_path` is accepted as an alias)
+    #[serde(alias = "dir_path")]
+    path: String,
+    /// Create missing parent directories too
+    #[serde(default)]
+    recursive: bool,
+}
+
+impl CreateDirectoryTool {
+    /// Create a new CreateDirectory tool instance
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Tool for CreateDirectoryTool {
+    fn name(&self) -> &str {
+        "CreateDirectory"
+    }
+
+    fn description(&self) -> &str {
+        "Create a directory inside the workspace. Set recursive to create missing parent \
+         directories as well. Succeeds if the directory already exists. Prefer this over \
+         running mkdir with Bash."
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "required": ["path"],
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "The directory to create (absolute, or relative to the working directory)"
+                },
+                "recursive": {
+                    "type": "boolean",
+                    "description": "Create missing parent directories (default: false)"
+                }
+            }
+        })
+    }
+
+    fn kind(&self) -> ToolKind {
+        ToolKind::Edit
+    }
+
+    fn requires_permission(&self) -> bool {
+        true // Creating directories requires permission
+    }
+
+    async fn execute(&self, input: serde_json::Value, context: &ToolContext) -> ToolResult {
+        // Parse input
+        let params: CreateDirectoryInput = match serde_json::from_value(input) {
+            Ok(p) => p,
+            Err(e) => return ToolResult::error(format!("Invalid input: {}", e)),
+        };
+
+        // Resolve path relative to working directory if not absolute
+        let path = normalize(&context.cwd.join(&params.path));
+
+        if let Some(result) = reject_outside_workspace("create directory", &path, context).await {
+            return result;
+        }
+
+        if path.is_dir() {
+            return ToolResult::success(format!("Directory {} already exists", path.display()))
+                .with_metadata(json!({
+                    "path": path.display().to_string(),
+                    "created": false,
+                    "recursive": params.recursive
+                }));
+        }
+
+        let result = if params.recursive {
+            tokio::fs::create_dir_all(&path).await
+        } else {
+            tokio::fs::create_dir(&path).await
+        };
+        match result {
+            Ok(()) => {
+                tracing::info!(
+                    dir_path = %path.display(),
+                    recursive = params.recursive,
+                    "Directory created"
+                );
+                ToolResult::success(format!("Created directory {}", path.display())).with_metadata(
+                    json!({
+                        "path": path.display().to_string(),
+                        "created": true,
+                        "recursive": params.recursive
+                    }),
+                )
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => ToolResult::error(format!(
+                "{} already exists and is not a directory",
+                path.display()
+            )),
+            Err(e) if e.kind() == ErrorKind::NotFound && !params.recursive => {
+                ToolResult::error(format!(
+                    "Parent directory of {} does not exist — set recursive to create it",
+                    path.display()
+                ))
+            }
+            Err(e) => ToolResult::io_error("creating directory", &path, &e, &context.cwd),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_create_nested_directories() {
+        let temp_dir = TempDir::new().unwrap();
+        let tool = CreateDirectoryTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+
+        // Missing parents need recursive
+        let result = tool
+            .execute(json!({"path": "src/nested/module"}), &context)
+            .await;
+        assert!(result.is_error);
+        assert!(result.content.contains("recursive"));
+
+        let result = tool
+            .execute(
+                json!({"path": "src/nested/module", "recursive": true}),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.metadata.unwrap()["created"], true);
+        assert!(temp_dir.path().join("src/nested/module").is_dir());
+
+        // Existing directories are fine
+        let result = tool.execute(json!({"path": "src/nested"}), &context).await;
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.metadata.unwrap()["created"], false);
+    }
+
+    #[tokio::test]
+    async fn test_create_directory_outside_workspace() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        std::fs::create_dir(&workspace).unwrap();
+        std::fs::write(workspace.join("file.txt"), "").unwrap();
+
+        let tool = CreateDirectoryTool::new();
+        let context = ToolContext::new("test", &workspace);
+
+        let result = tool.execute(json!({"path": "../escape"}), &context).await;
+        assert!(result.is_error);
+        assert_eq!(result.metadata.unwrap()["error_kind"], "outside_workspace");
+        assert!(!temp_dir.path().join("escape").exists());
+
+        let result = tool.execute(json!({"path": "file.txt"}), &context).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("not a directory"));
+    }
+}
@@ -0,0 +1,306 @@
+//! Delete tool implementation
+//!
+//! Removes files and directories inside the workspace. By default the
+//! target is moved to the session's trash directory
+//! (`.claude/trash/<session id>/` in the working directory), keeping its
+//! path relative to the working directory so moving it back restores it.
+//! `permanent` deletes it outright; such calls always ask the user (see
+//! [`crate::permissions::requires_explicit_approval`]).
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use super::base::{Tool, ToolKind};
+use super::workspace::{normalize, reject_outside_workspace, resolve, workspace_roots};
+use crate::mcp::registry::{ToolContext, ToolResult};
+
+/// Trash directory, relative to the working directory
+pub const TRASH_DIR: &str = ".claude/trash";
+
+/// Delete tool for removing files and directories
+#[derive(Debug, Default)]
+pub struct DeleteTool;
+
+/// Delete tool input parameters
+#[derive(Debug, Deserialize)]
+struct DeleteInput {
+    /// File or directory to delete (`file_path` is accepted as an alias)
+    #[serde(alias = "file_path")]
+    path: String,
+    /// Delete permanently instead of moving to the trash
+    #[serde(default)]
+    permanent: bool,
+}
+
+impl DeleteTool {
+    /// Create a new Delete tool instance
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Tool for DeleteTool {
+    fn name(&self) -> &str {
+        "Delete"
+    }
+
+    fn description(&self) -> &str {
+        "Delete a file or directory inside the workspace. By default it is moved to the \
+         session trash (.claude/trash/<session>/) where it can be restored. Set permanent \
+         to delete it outright; the user is always asked to approve permanent deletes. \
+         Prefer this over running rm with Bash."
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "required": ["path"],
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "The file or directory to delete (absolute, or relative to the working directory)"
+                },
+                "permanent": {
+                    "type": "boolean",
+                    "description": "Delete permanently instead of moving to the trash (default: false, always needs the user's approval)"
+                }
+            }
+        })
+    }
+
+    fn kind(&self) -> ToolKind {
+        ToolKind::Edit
+    }
+
+    fn requires_permission(&self) -> bool {
+        true // Deleting requires permission
+    }
+
+    async fn execute(&self, input: serde_json::Value, context: &ToolContext) -> ToolResult {
+        // Parse input
+        let params: DeleteInput = match serde_json::from_value(input) {
+            Ok(p) => p,
+            Err(e) => return ToolResult::error(format!("Invalid input: {}", e)),
+        };
+
+        // Resolve path relative to working directory if not absolute
+        let path = normalize(&context.cwd.join(&params.path));
+
+        let metadata = match std::fs::symlink_metadata(&path) {
+            Ok(metadata) => metadata,
+            Err(e) => return ToolResult::io_error("deleting", &path, &e, &context.cwd),
+        };
+
+        // A symlink is removed itself, so only its location must be inside
+        let checked = match path.parent() {
+            Some(parent) if metadata.is_symlink() => parent,
+            _ => &path,
+        };
+        if let Some(result) = reject_outside_workspace("delete", checked, context).await {
+            return result;
+        }
+        let resolved = resolve(checked);
+        if workspace_roots(context)
+            .await
+            .iter()
+            .any(|root| root.starts_with(&resolved))
+        {
+            return ToolResult::error(format!(
+                "Refusing to delete {}: it contains the workspace",
+                path.display()
+            ));
+        }
+
+        let kind = if metadata.is_dir() {
+            "directory"
+        } else {
+            "file"
+        };
+
+        if params.permanent {
+            let target = path.clone();
+            if let Err(e) = run_blocking(move || remove_path(&target)).await {
+                return ToolResult::io_error("deleting", &path, &e, &context.cwd);
+            }
+            tracing::info!(path = %path.display(), kind = kind, "Permanently deleted");
+            return ToolResult::success(format!("Permanently deleted {}", path.display()))
+                .with_metadata(json!({
+                    "path": path.display().to_string(),
+                    "kind": kind,
+                    "permanent": true
+                }));
+        }
+
+        let trash_root = context.cwd.join(TRASH_DIR).join(&context.session_id);
+        if trash_root.starts_with(&path) {
+            return ToolResult::error(format!(
+                "Refusing to move {} to the trash: it contains the trash directory. \
+                 Set permanent to delete it.",
+                path.display()
+            ));
+        }
+        let relative = path.strip_prefix(&context.cwd).map_or_else(
+            |_| PathBuf::from(path.file_name().unwrap_or_default()),
+            Path::to_path_buf,
+        );
+        let trash_path = unique_path(&trash_root.join(relative));
+
+        let (from, to) = (path.clone(), trash_path.clone());
+        let moved = run_blocking(move || {
+            if let Some(parent) = to.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            move_path(&from, &to)
+        })
+        .await;
+        if let Err(e) = moved {
+            return ToolResult::io_error("moving to the trash", &path, &e, &context.cwd);
+        }
+
+        tracing::info!(
+            path = %path.display(),
+            trash_path = %trash_path.display(),
+            kind = kind,
+            "Moved to trash"
+        );
+        ToolResult::success(format!(
+            "Moved {} to the trash at {}. Move it back to restore it.",
+            path.display(),
+            trash_path.display()
+        ))
+        .with_metadata(json!({
+            "path": path.display().to_string(),
+            "kind": kind,
+            "permanent": false,
+            "trash_path": trash_path.display().to_string()
+        }))
+    }
+}
+
+/// Run blocking filesystem work off the async runtime
+async fn run_blocking(
+    work: impl FnOnce() -> std::io::Result<()> + Send + 'static,
+) -> std::io::Result<()> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .unwrap_or_else(|e| Err(std::io::Error::other(e)))
+}
+
+/// `path`, or `path` with a `.N` suffix if something is already there
+fn unique_path(path: &Path) -> PathBuf {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let mut candidate = path.to_path_buf();
+    let mut n = 1;
+    while candidate.symlink_metadata().is_ok() {
+        candidate = path.with_file_name(format!("{name}.{n}"));
+        n += 1;
+    }
+    candidate
+}
+
+/// Remove a file, symlink or directory tree
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.symlink_metadata()?.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    }
+}
+
+/// Move `from` to `to`, copying when they are on different filesystems
+pub fn move_path(from: &Path, to: &Path) -> std::io::Result<()> {
+    match std::fs::rename(from, to) {
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            for entry in walkdir::WalkDir::new(from) {
+                let entry = entry?;
+                let target = to.join(entry.path().strip_prefix(from).unwrap_or(entry.path()));
+                if entry.file_type().is_dir() {
+                    std::fs::create_dir_all(&target)?;
+                } else {
+                    std::fs::copy(entry.path(), &target)?;
+                }
+            }
+            remove_path(from)
+        }
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_delete_moves_to_trash() {
+        let temp_dir = TempDir::new().unwrap();
+        let cwd = temp_dir.path();
+        std::fs::create_dir(cwd.join("src")).unwrap();
+        std::fs::write(cwd.join("src/old.rs"), "first").unwrap();
+
+        let tool = DeleteTool::new();
+        let context = ToolContext::new("session-1", cwd);
+
+        let result = tool.execute(json!({"path": "src/old.rs"}), &context).await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(!cwd.join("src/old.rs").exists());
+        let trash_path = cwd.join(".claude/trash/session-1/src/old.rs");
+        assert_eq!(std::fs::read_to_string(&trash_path).unwrap(), "first");
+        assert_eq!(
+            result.metadata.unwrap()["trash_path"],
+            trash_path.display().to_string()
+        );
+
+        // A second delete of the same path doesn't overwrite the first
+        std::fs::write(cwd.join("src/old.rs"), "second").unwrap();
+        let result = tool.execute(json!({"path": "src/old.rs"}), &context).await;
+        assert!(!result.is_error, "{}", result.content);
+        let second = cwd.join(".claude/trash/session-1/src/old.rs.1");
+        assert_eq!(std::fs::read_to_string(second).unwrap(), "second");
+        assert_eq!(std::fs::read_to_string(&trash_path).unwrap(), "first");
+
+        // Directories go to the trash too
+        let result = tool.execute(json!({"path": "src"}), &context).await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(!cwd.join("src").exists());
+        assert!(cwd.join(".claude/trash/session-1/src.1").is_dir());
+    }
+
+    #[tokio::test]
+    async fn test_delete_permanent_and_safety_rails() {
+        let temp_dir = TempDir::new().unwrap();
+        let workspace = temp_dir.path().join("workspace");
+        std::fs::create_dir(&workspace).unwrap();
+        std::fs::write(workspace.join("gone.txt"), "").unwrap();
+        std::fs::write(temp_dir.path().join("outside.txt"), "").unwrap();
+
+        let tool = DeleteTool::new();
+        let context = ToolContext::new("session-1", &workspace);
+
+        let result = tool
+            .execute(json!({"path": "gone.txt", "permanent": true}), &context)
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(!workspace.join("gone.txt").exists());
+        assert!(!workspace.join(TRASH_DIR).exists());
+
+        let result = tool
+            .execute(json!({"path": "../outside.txt"}), &context)
+            .await;
+        assert!(result.is_error);
+        assert_eq!(result.metadata.unwrap()["error_kind"], "outside_workspace");
+        assert!(temp_dir.path().join("outside.txt").exists());
+
+        let result = tool.execute(json!({"path": "."}), &context).await;
+        assert!(result.is_error);
+        assert!(result.content.contains("contains the workspace"));
+        assert!(workspace.exists());
+
+        let result = tool.execute(json!({"path": "missing.txt"}), &context).await;
+        assert!(result.is_error);
+    }
+}
@@ -0,0 +1,291 @@
+//! DiffFiles tool implementation
+//!
+//! Compares two text files and returns a unified diff, e.g. to check a
+//! generated file against an expected one. A path that doesn't exist is
+//! compared as an empty file, so the diff shows the other file as added or
+//! removed in full.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use similar::{ChangeTag, TextDiff};
+use std::path::Path;
+
+use super::base::{Tool, ToolKind};
+use super::special_file::reject_special_file;
+use crate::mcp::registry::{ToolContext, ToolResult};
+
+/// Default number of unchanged lines shown around each change
+const DEFAULT_CONTEXT_LINES: usize = 3;
+
+/// DiffFiles tool for comparing two files
+#[derive(Debug, Default)]
+pub struct DiffFilesTool;
+
+/// DiffFiles tool input parameters
+#[derive(Debug, Deserialize)]
+struct DiffFilesInput {
+    /// The original file (`from` is accepted as an alias)
+    #[serde(alias = "from")]
+    old_path: String,
+    /// The file compared against it (`to` is accepted as an alias)
+    #[serde(alias = "to")]
+    new_path: String,
+    /// Unchanged lines shown around each change
+    #[serde(default)]
+    context_lines: Option<usize>,
+}
+
+impl DiffFilesTool {
+    /// Create a new DiffFiles tool instance
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Tool for DiffFilesTool {
+    fn name(&self) -> &str {
+        "DiffFiles"
+    }
+
+    fn description(&self) -> &str {
+        "Compare two text files and return a unified diff of old_path against new_path. \
+         A path that doesn't exist is treated as an empty file. Use this to compare a \
+         generated file with an expected one instead of reading both."
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "required": ["old_path", "new_path"],
+            "properties": {
+                "old_path": {
+                    "type": "string",
+                    "description": "The original file (absolute, or relative to the working directory)"
+                },
+                "new_path": {
+                    "type": "string",
+                    "description": "The file to compare against it"
+                },
+                "context_lines": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Unchanged lines shown around each change (default: 3)"
+                }
+            }
+        })
+    }
+
+    fn kind(&self) -> ToolKind {
+        ToolKind::Read
+    }
+
+    fn requires_permission(&self) -> bool {
+        false // Reading doesn't require explicit permission
+    }
+
+    async fn execute(&self, input: serde_json::Value, context: &ToolContext) -> ToolResult {
+        // Parse input
+        let params: DiffFilesInput = match serde_json::from_value(input) {
+            Ok(p) => p,
+            Err(e) => return ToolResult::error(format!("Invalid input: {}", e)),
+        };
+
+        let old_path = context.cwd.join(&params.old_path);
+        let new_path = context.cwd.join(&params.new_path);
+
+        let old_text = match read_text(&old_path, context).await {
+            Ok(text) => text,
+            Err(result) => return result,
+        };
+        let new_text = match read_text(&new_path, context).await {
+            Ok(text) => text,
+            Err(result) => return result,
+        };
+        if old_text.is_none() && new_text.is_none() {
+            return ToolResult::error(format!(
+                "Neither {} nor {} exists",
+                old_path.display(),
+                new_path.display()
+            ));
+        }
+
+        let (old_missing, new_missing) = (old_text.is_none(), new_text.is_none());
+        let (old_text, new_text) = (old_text.unwrap_or_default(), new_text.unwrap_or_default());
+        let diff = TextDiff::from_lines(&old_text, &new_text);
+        let (mut additions, mut removals) = (0, 0);
+        for change in diff.iter_all_changes() {
+            match change.tag() {
+                ChangeTag::Insert => additions += 1,
+                ChangeTag::Delete => removals += 1,
+                ChangeTag::Equal => {}
+            }
+        }
+
+        let metadata = json!({
+            "old_path": old_path.display().to_string(),
+            "new_path": new_path.display().to_string(),
+            "old_missing": old_missing,
+            "new_missing": new_missing,
+            "additions": additions,
+            "removals": removals,
+            "identical": additions == 0 && removals == 0
+        });
+
+        if additions == 0 && removals == 0 {
+            return ToolResult::success(format!(
+                "{} and {} are identical",
+                old_path.display(),
+                new_path.display()
+            ))
+            .with_metadata(metadata);
+        }
+
+        let mut output = format!(
+            "{} addition{}, {} removal{}\n",
+            additions,
+            if additions == 1 { "" } else { "s" },
+            removals,
+            if removals == 1 { "" } else { "s" }
+        );
+        for (missing, path) in [(old_missing, &old_path), (new_missing, &new_path)] {
+            if missing {
+                output.push_str(&format!(
+                    "({} does not exist; compared as an empty file)\n",
+                    path.display()
+                ));
+            }
+        }
+        output.push('\n');
+        output.push_str(
+            &diff
+                .unified_diff()
+                .context_radius(params.context_lines.unwrap_or(DEFAULT_CONTEXT_LINES))
+                .header(
+                    &old_path.display().to_string(),
+                    &new_path.display().to_string(),
+                )
+                .to_string(),
+        );
+
+        tracing::info!(
+            old_path = %old_path.display(),
+            new_path = %new_path.display(),
+            additions = additions,
+            removals = removals,
+            "Files compared"
+        );
+
+        ToolResult::success(output).with_metadata(metadata)
+    }
+}
+
+/// Read a file to compare, or None if it doesn't exist
+///
+/// Files over the context's `max_file_size` are refused like Read does.
+async fn read_text(path: &Path, context: &ToolContext) -> Result<Option<String>, ToolResult> {
+    let metadata = match tokio::fs::metadata(path).await {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(ToolResult::io_error("reading", path, &e, &context.cwd)),
+    };
+
+    // Reading a FIFO would block until something writes to it
+    if let Some(result) = reject_special_file("diff", path) {
+        return Err(result);
+    }
+    if !metadata.is_file() {
+        return Err(ToolResult::error(format!("Not a file: {}", path.display())));
+    }
+    if let Some(max_file_size) = context.max_file_size()
+        && metadata.len() > max_file_size
+    {
+        return Err(ToolResult::file_too_large(
+            format!(
+                "File too large to diff: {} ({} bytes, maximum {} bytes)",
+                path.display(),
+                metadata.len(),
+                max_file_size
+            ),
+            path,
+            metadata.len(),
+            max_file_size,
+        ));
+    }
+
+    match tokio::fs::read_to_string(path).await {
+        Ok(text) => Ok(Some(text)),
+        Err(e) if e.kind() == std::io::ErrorKind::InvalidData => Err(ToolResult::error(format!(
+            "Cannot diff {}: it is not a UTF-8 text file",
+            path.display()
+        ))),
+        Err(e) => Err(ToolResult::io_error("reading", path, &e, &context.cwd)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_diff_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let cwd = temp_dir.path();
+        std::fs::write(cwd.join("expected.txt"), "one\ntwo\nthree\n").unwrap();
+        std::fs::write(cwd.join("actual.txt"), "one\n2\nthree\nfour\n").unwrap();
+
+        let tool = DiffFilesTool::new();
+        let context = ToolContext::new("test", cwd);
+
+        let result = tool
+            .execute(
+                json!({"old_path": "expected.txt", "new_path": "actual.txt"}),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.starts_with("2 additions, 1 removal\n"));
+        assert!(result.content.contains("\n-two\n+2\n three\n+four\n"));
+        let metadata = result.metadata.unwrap();
+        assert_eq!(metadata["additions"], 2);
+        assert_eq!(metadata["removals"], 1);
+
+        // A missing file is compared as empty
+        let result = tool
+            .execute(
+                json!({"old_path": "missing.txt", "new_path": "expected.txt"}),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("compared as an empty file"));
+        assert!(result.content.contains("+one\n+two\n+three\n"));
+        assert_eq!(result.metadata.unwrap()["old_missing"], true);
+
+        let result = tool
+            .execute(
+                json!({"old_path": "expected.txt", "new_path": "expected.txt"}),
+                &context,
+            )
+            .await;
+        assert!(result.content.ends_with("are identical"));
+
+        let result = tool
+            .execute(json!({"old_path": "a.txt", "new_path": "b.txt"}), &context)
+            .await;
+        assert!(result.is_error);
+
+        // Files over the size limit are refused
+        let context = context.with_max_file_size(10);
+        let result = tool
+            .execute(
+                json!({"old_path": "expected.txt", "new_path": "actual.txt"}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error);
+        assert_eq!(result.metadata.unwrap()["error_kind"], "file_too_large");
+    }
+}
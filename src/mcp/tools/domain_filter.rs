@@ -0,0 +1,146 @@
+//! Allow and deny lists of domains for WebFetch and WebSearch
+//!
+//! Entries match hosts exactly; subdomains only match entries written as
+//! `*.example.com`, so allowing `example.com` doesn't allow
+//! `evil.example.com`.
+
+/// Why a host is blocked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DomainBlock {
+    /// The host matches this entry of the deny list
+    Denied(String),
+    /// There is an allow list and the host matches none of its entries
+    NotAllowed,
+}
+
+impl DomainBlock {
+    /// Why `host` is blocked, naming the setting by its prefix
+    /// (e.g. `webFetch` for `webFetchDeniedDomains`)
+    pub fn describe(&self, host: &str, setting_prefix: &str) -> String {
+        match self {
+            Self::Denied(entry) => {
+                format!("{host} matches {entry} in {setting_prefix}DeniedDomains")
+            }
+            Self::NotAllowed => format!("{host} isn't in {setting_prefix}AllowedDomains"),
+        }
+    }
+}
+
+/// Hosts a web tool may reach
+///
+/// Denied entries win over allowed ones. With an empty allow list every
+/// host that isn't denied is allowed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DomainFilter {
+    allowed: Vec<String>,
+    denied: Vec<String>,
+}
+
+impl DomainFilter {
+    /// Create a filter from allowed and denied domains
+    ///
+    /// Entries are lowercased; empty entries are ignored.
+    pub fn new(allowed: Vec<String>, denied: Vec<String>) -> Self {
+        Self {
+            allowed: normalize_domains(allowed),
+            denied: normalize_domains(denied),
+        }
+    }
+
+    /// Whether the filter allows every host
+    pub fn is_empty(&self) -> bool {
+        self.allowed.is_empty() && self.denied.is_empty()
+    }
+
+    /// Allowed domains
+    pub fn allowed(&self) -> &[String] {
+        &self.allowed
+    }
+
+    /// Denied domains
+    pub fn denied(&self) -> &[String] {
+        &self.denied
+    }
+
+    /// Check whether `host` may be reached
+    pub fn check(&self, host: &str) -> Result<(), DomainBlock> {
+        let host = normalize_domain(host);
+        if let Some(entry) = self.denied.iter().find(|entry| matches(entry, &host)) {
+            return Err(DomainBlock::Denied(entry.clone()));
+        }
+        if !self.allowed.is_empty() && !self.allowed.iter().any(|entry| matches(entry, &host)) {
+            return Err(DomainBlock::NotAllowed);
+        }
+        Ok(())
+    }
+
+    /// Whether `host` may be reached
+    pub fn allows(&self, host: &str) -> bool {
+        self.check(host).is_ok()
+    }
+}
+
+fn normalize_domain(domain: &str) -> String {
+    domain.trim().trim_end_matches('.').to_ascii_lowercase()
+}
+
+fn normalize_domains(domains: Vec<String>) -> Vec<String> {
+    domains
+        .iter()
+        .map(|domain| normalize_domain(domain))
+        .filter(|domain| !domain.is_empty())
+        .collect()
+}
+
+/// Whether a list entry matches `host`
+///
+/// `*.example.com` matches the subdomains of example.com but not
+/// example.com itself; any other entry only matches the host it names.
+fn matches(entry: &str, host: &str) -> bool {
+    match entry.strip_prefix("*.") {
+        Some(parent) => host
+            .strip_suffix(parent)
+            .is_some_and(|subdomain| subdomain.len() > 1 && subdomain.ends_with('.')),
+        None => entry == host,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_domain_filter() {
+        let filter = DomainFilter::new(
+            vec!["Docs.rs".to_string(), "*.rust-lang.org".to_string()],
+            vec![
+                "*.example.com".to_string(),
+                "blog.rust-lang.org".to_string(),
+            ],
+        );
+        assert!(filter.allows("docs.rs"));
+        assert!(filter.allows("DOCS.RS."));
+        assert!(filter.allows("doc.rust-lang.org"));
+
+        // Subdomains only match wildcard entries
+        assert_eq!(filter.check("api.docs.rs"), Err(DomainBlock::NotAllowed));
+        assert_eq!(filter.check("rust-lang.org"), Err(DomainBlock::NotAllowed));
+        assert_eq!(
+            filter.check("evilrust-lang.org"),
+            Err(DomainBlock::NotAllowed)
+        );
+
+        // Denied wins over allowed
+        assert_eq!(
+            filter.check("blog.rust-lang.org"),
+            Err(DomainBlock::Denied("blog.rust-lang.org".to_string()))
+        );
+
+        // Without an allow list only denied hosts are blocked
+        let filter = DomainFilter::new(Vec::new(), vec!["*.example.com".to_string()]);
+        assert!(filter.allows("example.com"));
+        assert!(filter.allows("crates.io"));
+        assert!(!filter.allows("www.example.com"));
+        assert!(DomainFilter::default().is_empty());
+    }
+}
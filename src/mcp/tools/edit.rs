@@ -1,16 +1,26 @@
 //! Edit tool implementation
 //!
-//! Performs string replacement edits in files.
+//! Performs string replacement edits in files. With `dry_run` the edit is
+//! only previewed as a diff.
 
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::json;
+use similar::TextDiff;
 
 use super::base::{Tool, ToolKind};
+use super::locked_file::LockedFile;
+use super::preview::preview_result;
 use crate::mcp::registry::{ToolContext, ToolResult};
 // TODO: Uncomment when implementing permission checks
 // use crate::settings::{PermissionCheckResult, PermissionDecision};
 
+/// Most lines suggested when old_string is not found
+const MAX_HINT_LINES: usize = 3;
+
+/// Similarity (0 to 1) a line needs to be suggested when old_string is not found
+const HINT_MIN_SIMILARITY: f32 = 0.6;
+
 /// Edit tool for performing string replacements in files
 #[derive(Debug, Default)]
 pub struct EditTool;
@@ -18,7 +28,9 @@ pub struct EditTool;
 /// Edit tool input parameters
 #[derive(Debug, Deserialize)]
 struct EditInput {
-    /// Path to the file to edit
+    /// Path to the file to edit (canonical name; `path` and `filePath` are
+    /// accepted as aliases)
+    #[serde(alias = "path", alias = "filePath")]
     file_path: String,
     /// String to search for
     old_string: String,
@@ -27,6 +39,9 @@ struct EditInput {
     /// Whether to replace all occurrences (default: false)
     #[serde(default)]
     replace_all: bool,
+    /// Only preview the edit as a diff, without writing it (default: false)
+    #[serde(default)]
+    dry_run: bool,
 }
 
 impl EditTool {
@@ -80,6 +95,10 @@ impl Tool for EditTool {
                 "replace_all": {
                     "type": "boolean",
                     "description": "Whether to replace all occurrences. Default: false (requires unique match)"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "Return the diff of the edit without writing it, e.g. to show a proposed change before making it. Default: false"
                 }
             }
         })
@@ -112,31 +131,53 @@ impl Tool for EditTool {
             context.cwd.join(&params.file_path)
         };
 
-        // Check if file exists
-        if !path.exists() {
-            return ToolResult::error(format!("File not found: {}", path.display()));
-        }
-
-        // Read current content
-        let content = match tokio::fs::read_to_string(&path).await {
-            Ok(c) => c,
-            Err(e) => return ToolResult::error(format!("Failed to read file: {}", e)),
+        // Lock and read the file
+        let file = match LockedFile::open(&path, context).await {
+            Ok(file) => file,
+            Err(result) => return result,
         };
+        let content = file.content();
 
-        // Check if old_string exists
-        let match_count = content.matches(&params.old_string).count();
+        // Count the matches before replacing anything
+        if params.old_string.is_empty() {
+            return ToolResult::error(
+                "old_string is empty. Use Write to create or replace a whole file.",
+            );
+        }
+        let mut match_lines = match_lines(content, &params.old_string);
+        let match_count = match_lines.len();
+        // Occurrences on the same line are reported once
+        match_lines.dedup();
 
         if match_count == 0 {
-            return ToolResult::error(
-                "String not found in file. The old_string must match exactly.",
+            let mut message = format!(
+                "String not found in {}. The old_string must match exactly, including whitespace and indentation.",
+                path.display()
             );
+            if let Some(hint) = similar_lines_hint(content, &params.old_string) {
+                message.push_str("\n\n");
+                message.push_str(&hint);
+            }
+            return ToolResult::error(message).with_metadata(json!({
+                "path": path.display().to_string(),
+                "match_count": 0
+            }));
         }
 
         if match_count > 1 && !params.replace_all {
+            let lines: Vec<String> = match_lines.iter().map(ToString::to_string).collect();
+            let noun = if lines.len() == 1 { "line" } else { "lines" };
             return ToolResult::error(format!(
-                "Found {} occurrences of the search string. Use replace_all: true to replace all, or provide a more unique string.",
-                match_count
-            ));
+                "Found {} occurrences of the search string (at {} {}). Include more surrounding lines in old_string to make it unique, or use replace_all: true to replace all.",
+                match_count,
+                noun,
+                lines.join(", ")
+            ))
+            .with_metadata(json!({
+                "path": path.display().to_string(),
+                "match_count": match_count,
+                "match_lines": match_lines
+            }));
         }
 
         // Perform replacement
@@ -146,33 +187,102 @@ impl Tool for EditTool {
             content.replacen(&params.old_string, &params.new_string, 1)
         };
 
+        if params.dry_run {
+            return preview_result(&path, content, &new_content, false);
+        }
+
         // Write updated content
-        match tokio::fs::write(&path, &new_content).await {
+        match file.write(&new_content, context).await {
             Ok(()) => {
                 let replacements = if params.replace_all { match_count } else { 1 };
 
                 // Generate a simple diff preview
                 let diff_preview = generate_diff_preview(&params.old_string, &params.new_string);
 
-                ToolResult::success(format!(
+                let mut output = format!(
                     "Edited {} ({} replacement{})\n{}",
                     path.display(),
                     replacements,
                     if replacements > 1 { "s" } else { "" },
                     diff_preview
-                ))
-                .with_metadata(json!({
+                );
+                let final_newline = context.final_newline();
+                if let Some(note) = final_newline.note(&new_content) {
+                    output.push('\n');
+                    output.push_str(note);
+                }
+
+                ToolResult::success(output).with_metadata(json!({
                     "path": path.display().to_string(),
                     "replacements": replacements,
                     "old_length": params.old_string.len(),
-                    "new_length": params.new_string.len()
+                    "new_length": params.new_string.len(),
+                    "verified": context.verify_writes(),
+                    "ends_with_newline": final_newline
+                        .is_enabled()
+                        .then(|| new_content.ends_with('\n'))
                 }))
             }
-            Err(e) => ToolResult::error(format!("Failed to write file: {}", e)),
+            Err(result) => result,
         }
     }
 }
 
+/// Line number (1-based) at which each occurrence of `needle` starts
+fn match_lines(content: &str, needle: &str) -> Vec<usize> {
+    let mut line = 1;
+    let mut counted_to = 0;
+    content
+        .match_indices(needle)
+        .map(|(offset, _)| {
+            line += content[counted_to..offset].matches('\n').count();
+            counted_to = offset;
+            line
+        })
+        .collect()
+}
+
+/// Lines of `content` that resemble the first line of `old_string`
+///
+/// Points the model at the likely cause of a failed match, such as
+/// different indentation or a typo. None if no line is similar enough.
+fn similar_lines_hint(content: &str, old_string: &str) -> Option<String> {
+    let wanted = old_string
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())?;
+
+    let mut candidates: Vec<(f32, usize, &str)> = content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let trimmed = line.trim();
+            // Lines of very different length can't be similar enough
+            if trimmed.is_empty()
+                || trimmed.len() > wanted.len() * 2
+                || wanted.len() > trimmed.len() * 2
+            {
+                return None;
+            }
+            let similarity = TextDiff::from_chars(trimmed, wanted).ratio();
+            (similarity >= HINT_MIN_SIMILARITY).then_some((similarity, i + 1, line))
+        })
+        .collect();
+    if candidates.is_empty() {
+        return None;
+    }
+
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+    candidates.truncate(MAX_HINT_LINES);
+    candidates.sort_by_key(|&(_, number, _)| number);
+
+    let mut hint = "Similar lines in the file:\n".to_string();
+    for (_, number, line) in candidates {
+        hint.push_str(&format!("{:6}→{}\n", number, line));
+    }
+    Some(hint)
+}
+
 /// Generate a simple diff preview
 fn generate_diff_preview(old: &str, new: &str) -> String {
     let old_lines: Vec<&str> = old.lines().collect();
@@ -209,6 +319,7 @@ fn generate_diff_preview(old: &str, new: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mcp::tools::FinalNewlinePolicy;
     use std::io::Write as IoWrite;
     use tempfile::TempDir;
 
@@ -242,6 +353,45 @@ mod tests {
         assert!(content.contains("Goodbye, World!"));
     }
 
+    #[tokio::test]
+    async fn test_edit_warns_about_missing_final_newline() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("config.toml");
+        std::fs::write(&file_path, "name = \"old\"\n").unwrap();
+
+        let tool = EditTool::new();
+        let context =
+            ToolContext::new("test", temp_dir.path()).with_final_newline(FinalNewlinePolicy::Warn);
+
+        // The file keeps its newline: nothing to warn about
+        let result = tool
+            .execute(
+                json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "old_string": "old",
+                    "new_string": "new"
+                }),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(!result.content.contains("newline"));
+
+        let result = tool
+            .execute(
+                json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "old_string": "\"new\"\n",
+                    "new_string": "\"new\""
+                }),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("File does not end with a newline"));
+        assert_eq!(result.metadata.unwrap()["ends_with_newline"], false);
+    }
+
     #[tokio::test]
     async fn test_edit_replace_all() {
         let temp_dir = TempDir::new().unwrap();
@@ -296,6 +446,55 @@ mod tests {
 
         assert!(result.is_error);
         assert!(result.content.contains("2 occurrences"));
+        assert!(result.content.contains("(at line 1)"));
+        assert_eq!(result.metadata.unwrap()["match_lines"], json!([1]));
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "foo bar foo\n"
+        );
+    }
+
+    #[test]
+    fn test_match_lines() {
+        let content = "a\nfn x() {\n}\nfn x() {\n}\n";
+        assert_eq!(match_lines(content, "fn x() {\n"), vec![2, 4]);
+        assert_eq!(match_lines(content, "}\nfn"), vec![3]);
+        assert!(match_lines(content, "fn y").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_edit_not_found_suggests_similar_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("lib.rs");
+        std::fs::write(
+            &file_path,
+            "fn main() {\n    let total = compute();\n    println!(\"{total}\");\n}\n",
+        )
+        .unwrap();
+
+        let tool = EditTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+        let result = tool
+            .execute(
+                json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "old_string": "  let totl = compute();",
+                    "new_string": "  let total = compute_all();"
+                }),
+                &context,
+            )
+            .await;
+
+        assert!(result.is_error);
+        assert!(result.content.contains("String not found"));
+        assert!(
+            result
+                .content
+                .contains("Similar lines in the file:\n     2→    let total = compute();"),
+            "{}",
+            result.content
+        );
+        assert!(!result.content.contains("println"));
     }
 
     #[tokio::test]
@@ -323,6 +522,72 @@ mod tests {
         assert!(result.content.contains("not found"));
     }
 
+    #[tokio::test]
+    async fn test_edit_dry_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("lib.rs");
+        std::fs::write(&file_path, "fn a() {}\nfn b() {}\n").unwrap();
+
+        let tool = EditTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+        let result = tool
+            .execute(
+                json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "old_string": "fn b() {}",
+                    "new_string": "fn c() {}",
+                    "dry_run": true
+                }),
+                &context,
+            )
+            .await;
+
+        assert!(!result.is_error, "{}", result.content);
+        assert!(
+            result
+                .content
+                .starts_with("Preview only, no changes written.")
+        );
+        assert!(result.content.contains("-fn b() {}\n+fn c() {}"));
+        assert_eq!(result.metadata.unwrap()["dry_run"], true);
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "fn a() {}\nfn b() {}\n"
+        );
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_edits_are_not_lost() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("shared.txt");
+        let lines = |word: &str| {
+            (0..20)
+                .map(|i| format!("{word} {i}\n"))
+                .collect::<Vec<_>>()
+                .concat()
+        };
+        std::fs::write(&file_path, lines("line")).unwrap();
+
+        let edits = (0..20).map(|i| {
+            let context = ToolContext::new("test", temp_dir.path());
+            let input = json!({
+                "file_path": file_path.to_str().unwrap(),
+                "old_string": format!("line {i}\n"),
+                "new_string": format!("edited {i}\n")
+            });
+            tokio::spawn(async move { EditTool::new().execute(input, &context).await })
+        });
+        for result in futures::future::join_all(edits).await {
+            let result = result.unwrap();
+            assert!(!result.is_error, "{}", result.content);
+        }
+
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            lines("edited")
+        );
+    }
+
     #[test]
     fn test_edit_tool_properties() {
         let tool = EditTool::new();
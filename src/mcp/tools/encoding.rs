@@ -0,0 +1,271 @@
+//! Text encoding detection for Read and Write
+//!
+//! Files are decoded to UTF-8 for the model. The encoding of a file that
+//! isn't plain UTF-8 is remembered per session, so writing the file back
+//! re-encodes it the way it was.
+
+use std::path::{Path, PathBuf};
+
+use chardetng::EncodingDetector;
+use dashmap::DashMap;
+use encoding_rs::{Encoding, UTF_8, UTF_16BE, UTF_16LE, WINDOWS_1252};
+
+/// Bytes looked at when guessing whether BOM-less text is UTF-16
+const UTF16_SAMPLE_SIZE: usize = 4096;
+
+/// Encoding of a text file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileEncoding {
+    encoding: &'static Encoding,
+    /// Whether the file starts with a byte order mark
+    bom: bool,
+}
+
+impl FileEncoding {
+    /// UTF-8 without a byte order mark
+    pub const UTF8: Self = Self {
+        encoding: UTF_8,
+        bom: false,
+    };
+
+    /// Name of the encoding (e.g. `UTF-16LE`, `windows-1252`)
+    pub fn name(&self) -> &'static str {
+        self.encoding.name()
+    }
+
+    /// Whether the file starts with a byte order mark
+    pub fn has_bom(&self) -> bool {
+        self.bom
+    }
+
+    /// Whether this is UTF-8 without a byte order mark
+    pub fn is_plain_utf8(&self) -> bool {
+        *self == Self::UTF8
+    }
+
+    /// Name for messages, e.g. `UTF-8 with BOM`
+    pub fn describe(&self) -> String {
+        if self.bom {
+            format!("{} with BOM", self.name())
+        } else {
+            self.name().to_string()
+        }
+    }
+
+    /// Encode `text` in this encoding, with the byte order mark if the file had one
+    ///
+    /// Fails if `text` has characters the encoding can't represent.
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>, String> {
+        if self.encoding == UTF_16LE || self.encoding == UTF_16BE {
+            let big_endian = self.encoding == UTF_16BE;
+            let units = self
+                .bom
+                .then_some(0xFEFF)
+                .into_iter()
+                .chain(text.encode_utf16());
+            return Ok(units
+                .flat_map(|unit| {
+                    if big_endian {
+                        unit.to_be_bytes()
+                    } else {
+                        unit.to_le_bytes()
+                    }
+                })
+                .collect());
+        }
+
+        let mut bytes = Vec::with_capacity(text.len() + 3);
+        if self.bom && self.encoding == UTF_8 {
+            bytes.extend_from_slice(b"\xEF\xBB\xBF");
+        }
+        let (encoded, _, had_unmappable) = self.encoding.encode(text);
+        if had_unmappable {
+            return Err(format!(
+                "Content has characters that can't be written in the file's encoding ({})",
+                self.describe()
+            ));
+        }
+        bytes.extend_from_slice(&encoded);
+        Ok(bytes)
+    }
+}
+
+/// Decode `bytes` to UTF-8, detecting their encoding
+///
+/// A byte order mark decides the encoding. Without one, text that looks
+/// like UTF-16 is decoded as such, valid UTF-8 is taken as is, and the
+/// encoding of anything else without NUL bytes is guessed (e.g. Windows-1252
+/// for Latin-1 text, Windows-1251 for Cyrillic, Shift_JIS for Japanese).
+/// Returns None for binary content.
+pub fn decode(bytes: &[u8]) -> Option<(String, FileEncoding)> {
+    if let Some((encoding, bom_len)) = Encoding::for_bom(bytes) {
+        let text =
+            encoding.decode_without_bom_handling_and_without_replacement(&bytes[bom_len..])?;
+        return Some((
+            text.into_owned(),
+            FileEncoding {
+                encoding,
+                bom: true,
+            },
+        ));
+    }
+
+    // ASCII text in UTF-16 is also valid UTF-8, so check for UTF-16 first
+    if let Some(encoding) = guess_utf16(bytes)
+        && let Some(text) = encoding.decode_without_bom_handling_and_without_replacement(bytes)
+    {
+        return Some((
+            text.into_owned(),
+            FileEncoding {
+                encoding,
+                bom: false,
+            },
+        ));
+    }
+
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return Some((text.to_string(), FileEncoding::UTF8));
+    }
+
+    if bytes.contains(&0) {
+        return None;
+    }
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    let guessed = detector.guess(None, false);
+
+    // Windows-1252 decodes any bytes, so it backs up a guess that doesn't fit
+    // the whole file
+    let (text, encoding) = match guessed.decode_without_bom_handling_and_without_replacement(bytes)
+    {
+        Some(text) => (text, guessed),
+        None => (
+            WINDOWS_1252.decode_without_bom_handling(bytes).0,
+            WINDOWS_1252,
+        ),
+    };
+    Some((
+        text.into_owned(),
+        FileEncoding {
+            encoding,
+            bom: false,
+        },
+    ))
+}
+
+/// Guess whether BOM-less `bytes` are UTF-16 from where their NUL bytes are
+///
+/// Mostly-ASCII UTF-16 text has a NUL in every other byte: the odd ones for
+/// little endian, the even ones for big endian.
+fn guess_utf16(bytes: &[u8]) -> Option<&'static Encoding> {
+    if bytes.len() < 2 || !bytes.len().is_multiple_of(2) {
+        return None;
+    }
+    let sample = &bytes[..bytes.len().min(UTF16_SAMPLE_SIZE)];
+    let pairs = sample.len() / 2;
+    let (mut even_nuls, mut odd_nuls) = (0, 0);
+    for pair in sample.chunks_exact(2) {
+        even_nuls += usize::from(pair[0] == 0);
+        odd_nuls += usize::from(pair[1] == 0);
+    }
+
+    // At least 30% of the characters are ASCII and the other half of the pairs has no NULs
+    let mostly = |nuls: usize| nuls * 10 >= pairs * 3;
+    let rarely = |nuls: usize| nuls * 20 <= pairs;
+    if mostly(odd_nuls) && rarely(even_nuls) {
+        Some(UTF_16LE)
+    } else if mostly(even_nuls) && rarely(odd_nuls) {
+        Some(UTF_16BE)
+    } else {
+        None
+    }
+}
+
+/// Encodings of files read in a session that aren't plain UTF-8
+#[derive(Debug, Default)]
+pub struct FileEncodings {
+    by_path: DashMap<PathBuf, FileEncoding>,
+}
+
+impl FileEncodings {
+    /// Create an empty record
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that `path` was read as `encoding`
+    pub fn record(&self, path: &Path, encoding: FileEncoding) {
+        if encoding.is_plain_utf8() {
+            self.by_path.remove(path);
+        } else {
+            self.by_path.insert(path.to_path_buf(), encoding);
+        }
+    }
+
+    /// Encoding `path` was read as, if it isn't plain UTF-8
+    pub fn get(&self, path: &Path) -> Option<FileEncoding> {
+        self.by_path.get(path).map(|encoding| *encoding)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_detects_encoding() {
+        let (text, encoding) = decode("héllo".as_bytes()).unwrap();
+        assert_eq!(text, "héllo");
+        assert!(encoding.is_plain_utf8());
+
+        let (text, encoding) = decode(b"\xEF\xBB\xBFh\xC3\xA9llo").unwrap();
+        assert_eq!(text, "héllo");
+        assert_eq!(encoding.describe(), "UTF-8 with BOM");
+
+        let (text, encoding) = decode(b"\xFF\xFEh\0\xE9\0").unwrap();
+        assert_eq!(text, "hé");
+        assert_eq!(encoding.describe(), "UTF-16LE with BOM");
+
+        let (text, encoding) = decode(b"\0h\0i\0!").unwrap();
+        assert_eq!(text, "hi!");
+        assert_eq!(encoding.describe(), "UTF-16BE");
+
+        let (text, encoding) = decode(b"caf\xE9 \x80").unwrap();
+        assert_eq!(text, "café €");
+        assert_eq!(encoding.name(), "windows-1252");
+
+        let (text, encoding) = decode(b"\xCF\xF0\xE8\xE2\xE5\xF2, \xEC\xE8\xF0!").unwrap();
+        assert_eq!(text, "Привет, мир!");
+        assert_eq!(encoding.name(), "windows-1251");
+
+        let (text, encoding) = decode(b"\x82\xB1\x82\xF1\x82\xC9\x82\xBF\x82\xCD").unwrap();
+        assert_eq!(text, "こんにちは");
+        assert_eq!(encoding.name(), "Shift_JIS");
+
+        assert!(decode(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR\xFF").is_none());
+    }
+
+    #[test]
+    fn test_encode_round_trips() {
+        for bytes in [
+            &b"\xEF\xBB\xBFh\xC3\xA9llo"[..],
+            b"\xFF\xFEh\0\xE9\0",
+            b"\xFE\xFF\0h\0\xE9",
+            b"h\0i\0!\0",
+            b"caf\xE9 \x80",
+            b"\xCF\xF0\xE8\xE2\xE5\xF2, \xEC\xE8\xF0!",
+            b"\x82\xB1\x82\xF1\x82\xC9\x82\xBF\x82\xCD",
+        ] {
+            let (text, encoding) = decode(bytes).unwrap();
+            assert_eq!(
+                encoding.encode(&text).unwrap(),
+                bytes,
+                "{}",
+                encoding.describe()
+            );
+        }
+
+        let (_, latin1) = decode(b"caf\xE9").unwrap();
+        assert!(latin1.encode("snowman \u{2603}").is_err());
+    }
+}
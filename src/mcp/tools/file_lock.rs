@@ -0,0 +1,45 @@
+//! Per-file locks for tools that modify files
+//!
+//! Edit reads a file, checks the replacement and writes it back. Holding the
+//! file's lock across those steps keeps concurrent Edit and Write calls on
+//! the same file from overwriting each other's changes.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock};
+
+use dashmap::DashMap;
+use tokio::sync::{Mutex, OwnedMutexGuard};
+
+/// Locks of files currently being modified, by canonical path
+static FILE_LOCKS: LazyLock<DashMap<PathBuf, Arc<Mutex<()>>>> = LazyLock::new(DashMap::new);
+
+/// Exclusive access to a file, released on drop
+#[derive(Debug)]
+pub struct FileLockGuard {
+    path: PathBuf,
+    guard: Option<OwnedMutexGuard<()>>,
+}
+
+impl Drop for FileLockGuard {
+    fn drop(&mut self) {
+        drop(self.guard.take());
+        // Forget the lock once nobody holds or waits for it
+        FILE_LOCKS.remove_if(&self.path, |_, lock| Arc::strong_count(lock) == 1);
+    }
+}
+
+/// Lock a file for modification, waiting for other holders
+///
+/// Existing files are locked by canonical path, so different spellings of
+/// the same file share one lock.
+pub async fn lock_file(path: &Path) -> FileLockGuard {
+    let path = tokio::fs::canonicalize(path)
+        .await
+        .unwrap_or_else(|_| path.to_path_buf());
+    let lock = FILE_LOCKS.entry(path.clone()).or_default().clone();
+    let guard = lock.lock_owned().await;
+    FileLockGuard {
+        path,
+        guard: Some(guard),
+    }
+}
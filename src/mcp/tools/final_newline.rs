@@ -0,0 +1,45 @@
+//! Reporting whether written files end with a newline
+//!
+//! Linters and `git diff` complain about a missing newline at end of file,
+//! but a Write or Edit result doesn't show the file's last byte. With the
+//! `finalNewline` setting, Write, Edit and MultiEdit state it in their
+//! result so the model can fix it without reading the file again.
+
+use serde::{Deserialize, Serialize};
+
+/// When to report whether a written file ends with a newline
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FinalNewlinePolicy {
+    /// Don't check
+    #[default]
+    Off,
+    /// Always state whether the file ends with a newline
+    Report,
+    /// Only mention a missing final newline
+    Warn,
+}
+
+impl FinalNewlinePolicy {
+    /// Whether files are checked at all
+    pub fn is_enabled(self) -> bool {
+        self != Self::Off
+    }
+
+    /// Note appended to a Write or Edit result for `content`
+    ///
+    /// None when the policy is off, when only missing newlines are reported
+    /// and the content has one, and for empty files.
+    pub fn note(self, content: &str) -> Option<&'static str> {
+        if content.is_empty() {
+            return None;
+        }
+        match (self, content.ends_with('\n')) {
+            (Self::Off, _) | (Self::Warn, true) => None,
+            (Self::Report, true) => Some("File ends with a newline."),
+            (Self::Report | Self::Warn, false) => Some(
+                "File does not end with a newline; add one if the project's linters expect it.",
+            ),
+        }
+    }
+}
@@ -1,15 +1,20 @@
 //! Glob tool for file pattern matching
 //!
 //! Fast file pattern matching using glob patterns like `**/*.rs`.
+//!
+//! Like the other project walks, files ignored by `.gitignore`, `.ignore` or
+//! `.git/info/exclude` are skipped unless `respect_gitignore` is false, and
+//! hidden files are skipped unless the pattern names a hidden path (e.g.
+//! `.github/**/*.yml`).
 
 use async_trait::async_trait;
 use globset::{Glob, GlobSetBuilder};
 use serde::Deserialize;
 use serde_json::{Value, json};
 use std::path::Path;
-use walkdir::WalkDir;
 
 use super::base::Tool;
+use super::walk::project_walker;
 use crate::mcp::registry::{ToolContext, ToolResult};
 
 /// Maximum number of results to return
@@ -22,7 +27,9 @@ pub struct GlobTool;
 /// Input parameters for Glob
 #[derive(Debug, Deserialize)]
 struct GlobInput {
-    /// The glob pattern to match files against
+    /// The glob pattern to match files against (canonical name; `query` is
+    /// accepted as an alias)
+    #[serde(alias = "query")]
     pattern: String,
     /// The directory to search in (defaults to cwd)
     #[serde(default)]
@@ -33,6 +40,13 @@ struct GlobInput {
     /// Skip first N results
     #[serde(default)]
     offset: Option<usize>,
+    /// Skip files ignored by git (default: true)
+    #[serde(default = "default_respect_gitignore")]
+    respect_gitignore: bool,
+}
+
+fn default_respect_gitignore() -> bool {
+    true
 }
 
 impl GlobTool {
@@ -40,6 +54,13 @@ impl GlobTool {
     pub fn new() -> Self {
         Self
     }
+
+    /// Whether a pattern explicitly names hidden files or directories
+    fn matches_hidden(pattern: &str) -> bool {
+        pattern
+            .split('/')
+            .any(|component| component.starts_with('.') && component != "." && component != "..")
+    }
 }
 
 #[async_trait]
@@ -73,6 +94,10 @@ impl Tool for GlobTool {
                 "offset": {
                     "type": "integer",
                     "description": "Skip first N results (default: 0)"
+                },
+                "respect_gitignore": {
+                    "type": "boolean",
+                    "description": "Skip files ignored by .gitignore, .ignore and .git/info/exclude (default: true)"
                 }
             }
         })
@@ -130,15 +155,17 @@ impl Tool for GlobTool {
         // Use a 3x multiplier to handle cases where many entries are filtered (directories, non-matching files)
         let total_to_collect = offset.saturating_add(collection_limit).saturating_mul(3).min(MAX_RESULTS * 3);
 
-        for entry in WalkDir::new(&search_dir)
-            .follow_links(false)
-            .into_iter()
-            .filter_map(|e| e.ok())
-        {
+        let mut walker = project_walker(&search_dir);
+        walker.hidden(!Self::matches_hidden(&params.pattern));
+        if !params.respect_gitignore {
+            walker.ignore(false).git_ignore(false).git_exclude(false);
+        }
+
+        for entry in walker.build().filter_map(|e| e.ok()) {
             let path = entry.path();
 
             // Skip directories
-            if path.is_dir() {
+            if entry.file_type().is_none_or(|t| t.is_dir()) {
                 continue;
             }
 
@@ -200,7 +227,8 @@ impl Tool for GlobTool {
             "truncated": was_truncated,
             "pattern": params.pattern,
             "offset": offset,
-            "head_limit": collection_limit
+            "head_limit": collection_limit,
+            "respect_gitignore": params.respect_gitignore
         }))
     }
 }
@@ -267,6 +295,43 @@ mod tests {
         assert!(!result.content.contains("README.md"));
     }
 
+    #[tokio::test]
+    async fn test_glob_skips_ignored_and_hidden_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        for dir in ["src", "target/debug", ".cargo"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join("target/debug/build.rs"), "").unwrap();
+        fs::write(root.join(".cargo/config.rs"), "").unwrap();
+
+        let tool = GlobTool::new();
+        let context = ToolContext::new("test", root);
+
+        let result = tool.execute(json!({"pattern": "**/*.rs"}), &context).await;
+        assert!(result.content.contains("main.rs"));
+        assert!(!result.content.contains("build.rs"));
+        assert!(!result.content.contains("config.rs"));
+
+        let result = tool
+            .execute(
+                json!({"pattern": "**/*.rs", "respect_gitignore": false}),
+                &context,
+            )
+            .await;
+        assert!(result.content.contains("main.rs"));
+        assert!(result.content.contains("build.rs"));
+        assert!(!result.content.contains("config.rs"));
+
+        // Naming a hidden directory includes it
+        let result = tool
+            .execute(json!({"pattern": ".cargo/*.rs"}), &context)
+            .await;
+        assert!(result.content.contains("config.rs"));
+    }
+
     #[tokio::test]
     async fn test_glob_no_matches() {
         let temp_dir = TempDir::new().unwrap();
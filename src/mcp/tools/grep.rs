@@ -1,16 +1,24 @@
 //! Grep tool for content search
 //!
 //! A powerful search tool built on ripgrep for searching file contents.
+//! When `rg` isn't on PATH, fails, or the `preferRipgrep` setting is off,
+//! the built-in search in [`super::grep_builtin`] produces the same output.
 
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::{Value, json};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock};
 use tokio::process::Command;
 use tokio::time::{timeout, Duration};
 
 use super::base::Tool;
+use super::grep_builtin::{self, FileMatches, MatchedLine, SearchOptions};
 use crate::mcp::registry::{ToolContext, ToolResult};
+use crate::types::ToolCallLocation;
 
 /// Maximum output size in characters
 const MAX_OUTPUT_SIZE: usize = 50_000;
@@ -43,10 +51,68 @@ impl OutputMode {
     }
 }
 
+/// Search implementation that produced the results
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    /// The `rg` binary
+    Ripgrep,
+    /// The pure-Rust search in `grep_builtin`
+    Builtin,
+}
+
+impl Backend {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Ripgrep => "ripgrep",
+            Self::Builtin => "builtin",
+        }
+    }
+}
+
+/// A line of formatted output
+struct OutputLine {
+    text: String,
+    /// Position of the match the line shows (matching content lines only)
+    location: Option<ToolCallLocation>,
+}
+
+impl OutputLine {
+    fn new(text: String) -> Self {
+        Self {
+            text,
+            location: None,
+        }
+    }
+}
+
+/// Sets the flag when dropped, stopping a built-in search whose caller
+/// gave up (e.g. on timeout)
+struct CancelOnDrop(Arc<AtomicBool>);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Locate the `rg` binary on PATH (looked up once)
+fn ripgrep_path() -> Option<&'static Path> {
+    static RIPGREP: LazyLock<Option<PathBuf>> = LazyLock::new(|| {
+        let name = if cfg!(windows) { "rg.exe" } else { "rg" };
+        let paths = std::env::var_os("PATH")?;
+        std::env::split_paths(&paths)
+            .map(|dir| dir.join(name))
+            .find(|path| path.is_file())
+    });
+    RIPGREP.as_deref()
+}
+
 /// Input parameters for Grep
 #[derive(Debug, Deserialize)]
 struct GrepInput {
-    /// The regex pattern to search for
+    /// The regex pattern to search for (canonical name; `query` is accepted
+    /// as an alias)
+    #[serde(alias = "query")]
     pattern: String,
     /// File or directory to search in
     #[serde(default)]
@@ -89,6 +155,31 @@ struct GrepInput {
     timeout: Option<u64>,
 }
 
+impl GrepInput {
+    /// Search options shared by both backends
+    ///
+    /// Context lines only apply in content mode.
+    fn search_options(&self, mode: OutputMode) -> SearchOptions {
+        let (before_context, after_context) = match (mode, self.context) {
+            (OutputMode::Content, Some(c)) => (c, c),
+            (OutputMode::Content, None) => (
+                self.before_context.unwrap_or(0),
+                self.after_context.unwrap_or(0),
+            ),
+            _ => (0, 0),
+        };
+        SearchOptions {
+            pattern: self.pattern.clone(),
+            case_insensitive: self.case_insensitive.unwrap_or(false),
+            multiline: self.multiline.unwrap_or(false),
+            before_context,
+            after_context,
+            glob: self.glob.clone(),
+            file_type: self.file_type.clone(),
+        }
+    }
+}
+
 impl GrepTool {
     /// Create a new Grep tool instance
     pub fn new() -> Self {
@@ -128,70 +219,210 @@ impl GrepTool {
     }
 
     /// Build rg command arguments
-    fn build_args(&self, params: &GrepInput, search_path: &str, mode: OutputMode) -> Vec<String> {
-        let mut args = Vec::new();
-
-        // Output format based on mode
-        match mode {
-            OutputMode::FilesWithMatches => {
-                args.push("--files-with-matches".to_string());
-            }
-            OutputMode::Count => {
-                args.push("--count".to_string());
-            }
-            OutputMode::Content => {
-                // Default content output, add line numbers if requested
-                if params.line_numbers.unwrap_or(true) {
-                    args.push("-n".to_string());
-                }
-            }
-        }
+    ///
+    /// Results are always requested as `--json` so both backends share the
+    /// formatting in [`Self::format_results`].
+    fn build_args(options: &SearchOptions, search_path: &str) -> Vec<String> {
+        let mut args = vec!["--json".to_string()];
 
         // Case insensitive
-        if params.case_insensitive.unwrap_or(false) {
+        if options.case_insensitive {
             args.push("-i".to_string());
         }
 
         // Multiline mode
-        if params.multiline.unwrap_or(false) {
+        if options.multiline {
             args.push("-U".to_string());
             args.push("--multiline-dotall".to_string());
         }
 
-        // Context lines (only for content mode)
-        if matches!(mode, OutputMode::Content) {
-            if let Some(c) = params.context {
-                args.push(format!("-C{}", c));
-            } else {
-                if let Some(a) = params.after_context {
-                    args.push(format!("-A{}", a));
-                }
-                if let Some(b) = params.before_context {
-                    args.push(format!("-B{}", b));
-                }
-            }
+        // Context lines
+        if options.before_context > 0 {
+            args.push(format!("-B{}", options.before_context));
+        }
+        if options.after_context > 0 {
+            args.push(format!("-A{}", options.after_context));
         }
 
         // File type filter
-        if let Some(ref ft) = params.file_type {
+        if let Some(ref ft) = options.file_type {
             args.push("--type".to_string());
             args.push(ft.clone());
         }
 
         // Glob filter
-        if let Some(ref glob) = params.glob {
+        if let Some(ref glob) = options.glob {
             args.push("--glob".to_string());
             args.push(glob.clone());
         }
 
-        // Pattern
-        args.push(params.pattern.clone());
+        // Pattern (as --regexp so patterns starting with '-' aren't flags)
+        args.push("--regexp".to_string());
+        args.push(options.pattern.clone());
 
         // Search path
+        args.push("--".to_string());
         args.push(search_path.to_string());
 
         args
     }
+
+    /// Run ripgrep and collect its matches
+    async fn run_ripgrep(
+        rg: &Path,
+        args: &[String],
+        cwd: &Path,
+    ) -> Result<Vec<FileMatches>, String> {
+        let output = Command::new(rg)
+            .args(args)
+            .current_dir(cwd)
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .output()
+            .await
+            .map_err(|e| format!("Failed to execute ripgrep: {e}"))?;
+
+        // rg exits with 0 for matches, 1 for no matches, 2 for errors
+        if let Some(0 | 1) = output.status.code() {
+            Ok(Self::parse_ripgrep_json(&String::from_utf8_lossy(
+                &output.stdout,
+            )))
+        } else {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            Err(if stderr.is_empty() {
+                "ripgrep returned an error".to_string()
+            } else {
+                stderr.trim().to_string()
+            })
+        }
+    }
+
+    /// Parse ripgrep's `--json` event stream
+    ///
+    /// ripgrep emits each file's events contiguously, between `begin` and
+    /// `end`. A multi-line match is split into one line per matched line.
+    fn parse_ripgrep_json(stdout: &str) -> Vec<FileMatches> {
+        let mut files: Vec<FileMatches> = Vec::new();
+        for line in stdout.lines() {
+            let Ok(event) = serde_json::from_str::<Value>(line) else {
+                continue;
+            };
+            let data = &event["data"];
+            let is_match = match event["type"].as_str() {
+                Some("begin") => {
+                    files.push(FileMatches {
+                        path: data["path"]["text"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .to_string(),
+                        lines: Vec::new(),
+                    });
+                    continue;
+                }
+                Some("match") => true,
+                Some("context") => false,
+                _ => continue,
+            };
+            let (Some(file), Some(text), Some(number)) = (
+                files.last_mut(),
+                data["lines"]["text"].as_str(),
+                data["line_number"].as_u64(),
+            ) else {
+                continue;
+            };
+            file.lines
+                .extend(text.lines().enumerate().map(|(i, text)| MatchedLine {
+                    number: number + i as u64,
+                    text: text.to_string(),
+                    is_match,
+                }));
+        }
+        files.retain(|file| file.match_count() > 0);
+        files
+    }
+
+    /// Format results the way ripgrep prints them for the output mode
+    ///
+    /// File paths prefix content lines only when searching a directory,
+    /// matching lines use `:` and context lines `-` as separator, and `--`
+    /// separates non-adjacent groups when context lines are shown. Matching
+    /// content lines carry their location for the client.
+    fn format_results(
+        results: &[FileMatches],
+        mode: OutputMode,
+        show_path: bool,
+        line_numbers: bool,
+        has_context: bool,
+    ) -> Vec<OutputLine> {
+        let mut lines: Vec<OutputLine> = Vec::new();
+        for file in results {
+            match mode {
+                OutputMode::FilesWithMatches => lines.push(OutputLine::new(file.path.clone())),
+                OutputMode::Count if show_path => {
+                    let text = format!("{}:{}", file.path, file.match_count());
+                    lines.push(OutputLine::new(text));
+                }
+                OutputMode::Count => lines.push(OutputLine::new(file.match_count().to_string())),
+                OutputMode::Content => {
+                    let mut previous: Option<u64> = None;
+                    for line in &file.lines {
+                        let gap = previous.is_none_or(|p| line.number > p + 1);
+                        if has_context && gap && !lines.is_empty() {
+                            lines.push(OutputLine::new("--".to_string()));
+                        }
+                        let separator = if line.is_match { ':' } else { '-' };
+                        let mut formatted = String::new();
+                        if show_path {
+                            let _ = write!(formatted, "{}{separator}", file.path);
+                        }
+                        if line_numbers {
+                            let _ = write!(formatted, "{}{separator}", line.number);
+                        }
+                        formatted.push_str(&line.text);
+                        let location = line.is_match.then(|| {
+                            let number = u32::try_from(line.number).unwrap_or(u32::MAX);
+                            ToolCallLocation::with_line(&file.path, number)
+                        });
+                        lines.push(OutputLine {
+                            text: formatted,
+                            location,
+                        });
+                        previous = Some(line.number);
+                    }
+                }
+            }
+        }
+        lines
+    }
+
+    /// Search with ripgrep when preferred and available, otherwise (or when
+    /// it fails) with the built-in search
+    async fn search(
+        options: SearchOptions,
+        search_path: &str,
+        context: &ToolContext,
+    ) -> Result<(Backend, Vec<FileMatches>), String> {
+        if context.prefer_ripgrep()
+            && let Some(rg) = ripgrep_path()
+        {
+            let args = Self::build_args(&options, search_path);
+            match Self::run_ripgrep(rg, &args, &context.cwd).await {
+                Ok(results) => return Ok((Backend::Ripgrep, results)),
+                Err(e) => {
+                    tracing::warn!(error = %e, "ripgrep failed, using built-in search");
+                }
+            }
+        }
+
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let _cancel = CancelOnDrop(cancelled.clone());
+        let root = PathBuf::from(search_path);
+        tokio::task::spawn_blocking(move || grep_builtin::search(&root, &options, &cancelled))
+            .await
+            .map_err(|e| format!("Search failed: {e}"))?
+            .map(|results| (Backend::Builtin, results))
+    }
 }
 
 #[async_trait]
@@ -201,8 +432,9 @@ impl Tool for GrepTool {
     }
 
     fn description(&self) -> &str {
-        "A powerful search tool built on ripgrep. Supports regex patterns, file type filtering, \
-         and context lines. Use output_mode to control output format."
+        "A powerful search tool built on ripgrep (with a built-in fallback when rg is not \
+         installed). Supports regex patterns, file type filtering, and context lines. Use \
+         output_mode to control output format."
     }
 
     fn input_schema(&self) -> Value {
@@ -298,29 +530,13 @@ impl Tool for GrepTool {
             .map(|s| OutputMode::from_str(s))
             .unwrap_or_default();
 
-        // Build command arguments
-        let args = self.build_args(&params, &search_path, mode);
-
-        // Execute ripgrep with optional timeout
-        let mut cmd = Command::new("rg");
-        cmd.args(&args)
-            .current_dir(&context.cwd)
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped());
-
-        let output = if let Some(timeout_ms) = params.timeout {
-            let timeout_duration = Duration::from_millis(timeout_ms);
-            match timeout(timeout_duration, cmd.output()).await {
-                Ok(Ok(o)) => o,
-                Ok(Err(e)) => {
-                    // Check if rg is not installed
-                    if e.kind() == std::io::ErrorKind::NotFound {
-                        return ToolResult::error(
-                            "ripgrep (rg) not found. Please install ripgrep to use Grep tool.",
-                        );
-                    }
-                    return ToolResult::error(format!("Failed to execute ripgrep: {}", e));
-                }
+        // Search with optional timeout
+        let options = params.search_options(mode);
+        let has_context = options.before_context > 0 || options.after_context > 0;
+        let search = Self::search(options, &search_path, context);
+        let outcome = if let Some(timeout_ms) = params.timeout {
+            match timeout(Duration::from_millis(timeout_ms), search).await {
+                Ok(outcome) => outcome,
                 Err(_) => {
                     return ToolResult::error(format!(
                         "Search timed out after {}ms. Try narrowing your search pattern, path, or increase the timeout value.",
@@ -329,66 +545,73 @@ impl Tool for GrepTool {
                 }
             }
         } else {
-            match cmd.output().await {
-                Ok(o) => o,
-                Err(e) => {
-                    // Check if rg is not installed
-                    if e.kind() == std::io::ErrorKind::NotFound {
-                        return ToolResult::error(
-                            "ripgrep (rg) not found. Please install ripgrep to use Grep tool.",
-                        );
-                    }
-                    return ToolResult::error(format!("Failed to execute ripgrep: {}", e));
-                }
-            }
+            search.await
+        };
+        let (backend, results) = match outcome {
+            Ok(outcome) => outcome,
+            Err(e) => return ToolResult::error(e),
         };
 
-        // Process output
-        let stdout = String::from_utf8_lossy(&output.stdout);
-        let stderr = String::from_utf8_lossy(&output.stderr);
-
-        // Handle exit codes
-        // rg exits with 0 for matches, 1 for no matches, 2 for errors
-        if let Some(0 | 1) = output.status.code() {
-            // Apply offset and head_limit using streaming processing
-            let offset = params.offset.unwrap_or(0);
-            let head_limit = params.head_limit.unwrap_or(DEFAULT_HEAD_LIMIT);
-
-            let (result, was_truncated) = Self::process_output_with_limits(&stdout, offset, head_limit);
-
-            // Build final result
-            let result = if result.is_empty() {
-                format!(
-                    "No matches found for pattern '{}' in {}",
-                    params.pattern, search_path
-                )
-            } else {
-                let mut output = result;
-                // Add truncation notice if applicable
-                if was_truncated {
-                    output.push_str(&format!(
-                        "\n... (showing {} results, use head_limit to see more)",
-                        head_limit
-                    ));
-                }
-                output
-            };
+        let lines = Self::format_results(
+            &results,
+            mode,
+            !Path::new(&search_path).is_file(),
+            params.line_numbers.unwrap_or(true),
+            has_context,
+        );
+        let formatted = lines
+            .iter()
+            .map(|line| line.text.as_str())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        // Apply offset and head_limit using streaming processing
+        let offset = params.offset.unwrap_or(0);
+        let head_limit = params.head_limit.unwrap_or(DEFAULT_HEAD_LIMIT);
+
+        let (result, was_truncated) =
+            Self::process_output_with_limits(&formatted, offset, head_limit);
+
+        // Link the matches on the returned page for the client
+        let shown = if result.is_empty() {
+            0
+        } else {
+            result.lines().count()
+        };
+        let locations: Vec<ToolCallLocation> = lines
+            .into_iter()
+            .skip(offset)
+            .take(shown)
+            .filter_map(|line| line.location)
+            .collect();
+
+        // Build final result
+        let result = if result.is_empty() {
+            format!(
+                "No matches found for pattern '{}' in {}",
+                params.pattern, search_path
+            )
+        } else {
+            let mut output = context.limit_line_length(&result).into_owned();
+            // Add truncation notice if applicable
+            if was_truncated {
+                output.push_str(&format!(
+                    "\n... (showing {} results, use head_limit to see more)",
+                    head_limit
+                ));
+            }
+            output
+        };
 
-            ToolResult::success(result).with_metadata(json!({
+        ToolResult::success(result)
+            .with_metadata(json!({
                 "pattern": params.pattern,
                 "path": search_path,
                 "mode": format!("{:?}", mode),
+                "backend": backend.as_str(),
                 "truncated": was_truncated
             }))
-        } else {
-            // Error
-            let error_msg = if stderr.is_empty() {
-                "ripgrep returned an error".to_string()
-            } else {
-                stderr.to_string()
-            };
-            ToolResult::error(error_msg)
-        }
+            .with_locations(locations)
     }
 }
 
@@ -451,6 +674,34 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_grep_accepts_query_alias() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("test.txt"), "needle\n").unwrap();
+
+        let tool = GrepTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+
+        for key in ["pattern", "query"] {
+            let result = tool
+                .execute(json!({ key: "needle", "output_mode": "content" }), &context)
+                .await;
+            assert!(
+                !result.content.starts_with("Invalid input"),
+                "{key}: {}",
+                result.content
+            );
+            // Only check matches if rg is available
+            if !result.is_error {
+                assert!(
+                    result.content.contains("needle"),
+                    "{key}: {}",
+                    result.content
+                );
+            }
+        }
+    }
+
     #[tokio::test]
     async fn test_grep_case_insensitive() {
         let temp_dir = TempDir::new().unwrap();
@@ -517,6 +768,117 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_grep_builtin_backend() {
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("a.txt"), "one\ntwo\nneedle\nthree\n").unwrap();
+        fs::write(temp_dir.path().join("b.txt"), "needle\nneedle\n").unwrap();
+
+        let tool = GrepTool::new();
+        let context = ToolContext::new("test", temp_dir.path()).with_prefer_ripgrep(false);
+        let a = temp_dir.path().join("a.txt").display().to_string();
+        let b = temp_dir.path().join("b.txt").display().to_string();
+
+        let result = tool
+            .execute(
+                json!({"pattern": "needle", "output_mode": "content", "-B": 1}),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(
+            result.content,
+            format!("{a}-2-two\n{a}:3:needle\n--\n{b}:1:needle\n{b}:2:needle")
+        );
+        assert_eq!(result.metadata.as_ref().unwrap()["backend"], "builtin");
+        let locations: Vec<_> = result
+            .locations
+            .iter()
+            .map(|loc| (loc.path.as_str(), loc.line))
+            .collect();
+        assert_eq!(
+            locations,
+            vec![
+                (a.as_str(), Some(3)),
+                (b.as_str(), Some(1)),
+                (b.as_str(), Some(2))
+            ]
+        );
+
+        let result = tool
+            .execute(
+                json!({"pattern": "needle", "output_mode": "count"}),
+                &context,
+            )
+            .await;
+        assert_eq!(result.content, format!("{a}:1\n{b}:2"));
+        assert!(result.locations.is_empty());
+
+        let result = tool
+            .execute(
+                json!({"pattern": "needle", "path": "b.txt", "output_mode": "content"}),
+                &context,
+            )
+            .await;
+        assert_eq!(result.content, "1:needle\n2:needle");
+
+        let result = tool.execute(json!({"pattern": "absent"}), &context).await;
+        assert!(result.content.starts_with("No matches found"));
+    }
+
+    #[test]
+    fn test_parse_ripgrep_json() {
+        let stdout = r#"{"type":"begin","data":{"path":{"text":"a.txt"}}}
+{"type":"context","data":{"path":{"text":"a.txt"},"lines":{"text":"before\n"},"line_number":1}}
+{"type":"match","data":{"path":{"text":"a.txt"},"lines":{"text":"start\nend\n"},"line_number":2}}
+{"type":"end","data":{"path":{"text":"a.txt"}}}
+{"type":"summary","data":{}}"#;
+        let line = |number, text: &str, is_match| MatchedLine {
+            number,
+            text: text.to_string(),
+            is_match,
+        };
+        assert_eq!(
+            GrepTool::parse_ripgrep_json(stdout),
+            vec![FileMatches {
+                path: "a.txt".to_string(),
+                lines: vec![
+                    line(1, "before", false),
+                    line(2, "start", true),
+                    line(3, "end", true),
+                ],
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_grep_backends_agree() {
+        if ripgrep_path().is_none() {
+            return;
+        }
+        let temp_dir = TempDir::new().unwrap();
+        fs::write(
+            temp_dir.path().join("test.rs"),
+            "fn a() {}\n// Needle\nfn b() {}\n\nfn c() {}\nlet needle = 1;\n",
+        )
+        .unwrap();
+
+        let tool = GrepTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+        let builtin = context.clone().with_prefer_ripgrep(false);
+        for input in [
+            json!({"pattern": "needle", "-i": true, "output_mode": "content", "-C": 1}),
+            json!({"pattern": "fn \\w", "type": "rust", "output_mode": "count"}),
+            json!({"pattern": "Needle.*fn b", "multiline": true, "output_mode": "content"}),
+            json!({"pattern": "needle", "glob": "*.rs"}),
+        ] {
+            let rg = tool.execute(input.clone(), &context).await;
+            let fallback = tool.execute(input.clone(), &builtin).await;
+            assert_eq!(rg.metadata.as_ref().unwrap()["backend"], "ripgrep");
+            assert_eq!(rg.content, fallback.content, "{input}");
+        }
+    }
+
     #[test]
     fn test_output_mode_parsing() {
         assert!(matches!(
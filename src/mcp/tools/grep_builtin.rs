@@ -0,0 +1,324 @@
+//! Built-in content search for Grep
+//!
+//! Used when ripgrep isn't installed, fails, or the `preferRipgrep` setting
+//! is false. Mirrors ripgrep's defaults closely enough for the tool: ignore
+//! files and hidden paths are skipped, binary files aren't searched, and
+//! `glob`/`type` filters apply to files found while walking a directory.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use globset::{Glob, GlobMatcher};
+use regex::{Regex, RegexBuilder};
+
+use super::walk::project_walker;
+
+/// Bytes inspected for NUL when deciding whether a file is binary
+const BINARY_CHECK_SIZE: usize = 8 * 1024;
+
+/// Extensions of the file types accepted by `type`, by ripgrep type name
+const FILE_TYPES: &[(&str, &[&str])] = &[
+    ("c", &["c", "h"]),
+    ("cpp", &["cpp", "cc", "cxx", "hpp", "hh", "hxx", "h"]),
+    ("css", &["css", "scss"]),
+    ("go", &["go"]),
+    ("html", &["html", "htm"]),
+    ("java", &["java"]),
+    ("js", &["js", "jsx", "mjs", "cjs"]),
+    ("json", &["json"]),
+    ("md", &["md", "markdown"]),
+    ("py", &["py", "pyi"]),
+    ("rust", &["rs"]),
+    ("sh", &["sh", "bash", "zsh"]),
+    ("toml", &["toml"]),
+    ("ts", &["ts", "tsx", "mts", "cts"]),
+    ("yaml", &["yaml", "yml"]),
+];
+
+/// Lines of one file shown for a search: matches and their context
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileMatches {
+    /// Path of the file, as reached from the search path
+    pub path: String,
+    /// Shown lines in file order
+    pub lines: Vec<MatchedLine>,
+}
+
+impl FileMatches {
+    /// Number of matching (non-context) lines
+    pub fn match_count(&self) -> usize {
+        self.lines.iter().filter(|line| line.is_match).count()
+    }
+}
+
+/// A line shown in search results
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MatchedLine {
+    /// Line number (1-based)
+    pub number: u64,
+    /// Line text without its line ending
+    pub text: String,
+    /// Whether the line matched (false for context lines)
+    pub is_match: bool,
+}
+
+/// What to search for and which files to search
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    /// Regular expression to search for
+    pub pattern: String,
+    /// Match case-insensitively
+    pub case_insensitive: bool,
+    /// Let matches span lines, with `.` matching newlines
+    pub multiline: bool,
+    /// Context lines shown before each match
+    pub before_context: usize,
+    /// Context lines shown after each match
+    pub after_context: usize,
+    /// Glob filter for file paths (`!` negates it)
+    pub glob: Option<String>,
+    /// ripgrep file type name (e.g. `rust`, `py`)
+    pub file_type: Option<String>,
+}
+
+/// Which files found while walking a directory are searched
+struct FileFilter {
+    glob: Option<(GlobMatcher, bool)>,
+    extensions: Option<&'static [&'static str]>,
+}
+
+impl FileFilter {
+    fn new(options: &SearchOptions) -> Result<Self, String> {
+        let glob = options
+            .glob
+            .as_deref()
+            .map(|glob| {
+                let (glob, negated) = match glob.strip_prefix('!') {
+                    Some(glob) => (glob, true),
+                    None => (glob, false),
+                };
+                Glob::new(glob)
+                    .map(|g| (g.compile_matcher(), negated))
+                    .map_err(|e| format!("Invalid glob '{glob}': {e}"))
+            })
+            .transpose()?;
+
+        let extensions = options
+            .file_type
+            .as_deref()
+            .map(|file_type| {
+                FILE_TYPES
+                    .iter()
+                    .find(|(name, _)| *name == file_type)
+                    .map(|(_, extensions)| *extensions)
+                    .ok_or_else(|| format!("Unknown file type '{file_type}'"))
+            })
+            .transpose()?;
+
+        Ok(Self { glob, extensions })
+    }
+
+    /// Whether `path` (found under `root`) should be searched
+    ///
+    /// Globs without a `/` match the file name, like gitignore patterns.
+    fn matches(&self, root: &Path, path: &Path) -> bool {
+        if let Some((matcher, negated)) = &self.glob {
+            let matched = if matcher.glob().glob().contains('/') {
+                matcher.is_match(path.strip_prefix(root).unwrap_or(path))
+            } else {
+                path.file_name().is_some_and(|name| matcher.is_match(name))
+            };
+            if matched == *negated {
+                return false;
+            }
+        }
+
+        if let Some(extensions) = self.extensions {
+            let extension = path.extension().and_then(|e| e.to_str());
+            return extension.is_some_and(|e| extensions.contains(&e));
+        }
+        true
+    }
+}
+
+/// Search `root` (a file or directory) for `options.pattern`
+///
+/// Files are searched in path order. Stops early, returning what it found
+/// so far, once `cancelled` is set.
+pub fn search(
+    root: &Path,
+    options: &SearchOptions,
+    cancelled: &AtomicBool,
+) -> Result<Vec<FileMatches>, String> {
+    let regex = RegexBuilder::new(&options.pattern)
+        .case_insensitive(options.case_insensitive)
+        .multi_line(true)
+        .dot_matches_new_line(options.multiline)
+        .build()
+        .map_err(|e| format!("Invalid regex pattern: {e}"))?;
+
+    // Explicitly given files are searched regardless of filters
+    let files: Vec<PathBuf> = if root.is_file() {
+        vec![root.to_path_buf()]
+    } else {
+        let filter = FileFilter::new(options)?;
+        project_walker(root)
+            .sort_by_file_name(|a, b| a.cmp(b))
+            .build()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+            .map(ignore::DirEntry::into_path)
+            .filter(|path| filter.matches(root, path))
+            .collect()
+    };
+
+    let mut results = Vec::new();
+    for path in files {
+        if cancelled.load(Ordering::Relaxed) {
+            break;
+        }
+        let Ok(bytes) = std::fs::read(&path) else {
+            continue;
+        };
+        if bytes[..bytes.len().min(BINARY_CHECK_SIZE)].contains(&0) {
+            continue;
+        }
+        let lines = search_content(&regex, &String::from_utf8_lossy(&bytes), options);
+        if !lines.is_empty() {
+            results.push(FileMatches {
+                path: path.display().to_string(),
+                lines,
+            });
+        }
+    }
+    Ok(results)
+}
+
+/// Find the matching lines of `content` and their context lines
+fn search_content(regex: &Regex, content: &str, options: &SearchOptions) -> Vec<MatchedLine> {
+    let lines: Vec<&str> = content.lines().collect();
+    if lines.is_empty() {
+        return Vec::new();
+    }
+
+    let mut matched = vec![false; lines.len()];
+    if options.multiline {
+        // Mark every line a match spans
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        let line_of = |offset: usize| {
+            (line_starts.partition_point(|&start| start <= offset) - 1).min(lines.len() - 1)
+        };
+        for m in regex.find_iter(content) {
+            let first = line_of(m.start());
+            let last = line_of(m.end().saturating_sub(1).max(m.start()));
+            matched[first..=last].fill(true);
+        }
+    } else {
+        for (is_match, line) in matched.iter_mut().zip(&lines) {
+            *is_match = regex.is_match(line);
+        }
+    }
+
+    let mut shown = vec![false; lines.len()];
+    for i in (0..lines.len()).filter(|&i| matched[i]) {
+        let start = i.saturating_sub(options.before_context);
+        let end = (i + options.after_context).min(lines.len() - 1);
+        shown[start..=end].fill(true);
+    }
+
+    lines
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| shown[*i])
+        .map(|(i, text)| MatchedLine {
+            number: i as u64 + 1,
+            text: (*text).to_string(),
+            is_match: matched[i],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn options(pattern: &str) -> SearchOptions {
+        SearchOptions {
+            pattern: pattern.to_string(),
+            ..SearchOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_search_with_context_and_filters() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {\n    hello();\n}\n").unwrap();
+        std::fs::write(root.join("index.js"), "hello();\n").unwrap();
+        std::fs::write(root.join("data.bin"), b"hello\0world").unwrap();
+        std::fs::write(root.join(".gitignore"), "ignored.rs\n").unwrap();
+        std::fs::write(root.join("ignored.rs"), "hello\n").unwrap();
+
+        let search_opts = SearchOptions {
+            before_context: 1,
+            file_type: Some("rust".to_string()),
+            ..options("hello")
+        };
+        let results = search(root, &search_opts, &AtomicBool::new(false)).unwrap();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].path.ends_with("main.rs"));
+        assert_eq!(
+            results[0].lines,
+            vec![
+                MatchedLine {
+                    number: 1,
+                    text: "fn main() {".to_string(),
+                    is_match: false,
+                },
+                MatchedLine {
+                    number: 2,
+                    text: "    hello();".to_string(),
+                    is_match: true,
+                },
+            ]
+        );
+
+        // Binary and ignored files are skipped; globs match file names
+        let search_opts = SearchOptions {
+            glob: Some("!*.rs".to_string()),
+            ..options("HELLO")
+        };
+        let results = search(
+            root,
+            &SearchOptions {
+                case_insensitive: true,
+                ..search_opts
+            },
+            &AtomicBool::new(false),
+        )
+        .unwrap();
+        let paths: Vec<_> = results.iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths.len(), 1);
+        assert!(paths[0].ends_with("index.js"));
+    }
+
+    #[test]
+    fn test_search_multiline() {
+        let temp_dir = TempDir::new().unwrap();
+        let file = temp_dir.path().join("a.txt");
+        std::fs::write(&file, "start\nmiddle\nend\nother\n").unwrap();
+
+        let search_opts = SearchOptions {
+            multiline: true,
+            ..options("start.*?end")
+        };
+        let results = search(&file, &search_opts, &AtomicBool::new(false)).unwrap();
+        assert_eq!(results[0].match_count(), 3);
+
+        assert!(search(&file, &options("("), &AtomicBool::new(false)).is_err());
+    }
+}
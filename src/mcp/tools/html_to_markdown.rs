@@ -0,0 +1,404 @@
+//! HTML conversion for fetched web content
+//!
+//! Pages are parsed with html5ever and converted to markdown by html2md,
+//! which renders `<table>` elements as GitHub-flavored markdown tables so
+//! tabular data such as API references stays readable.
+//!
+//! Pages that mark their main content (`<main>`, `role="main"` or a single
+//! `<article>`) are reduced to it, dropping navigation and page chrome.
+//! Relative links are resolved against the page URL so they can be fetched
+//! in turn.
+
+use html5ever::ParseOpts;
+use html5ever::serialize::{SerializeOpts, TraversalScope, serialize};
+use html5ever::tendril::TendrilSink;
+use markup5ever_rcdom::{Handle, NodeData, RcDom, SerializableHandle};
+
+/// Elements whose content is dropped from the output
+const SKIPPED_ELEMENTS: &[&str] = &[
+    "head", "script", "style", "noscript", "template", "svg", "iframe", "canvas",
+];
+
+/// Elements rendered as a standalone block in plain text
+const BLOCK_ELEMENTS: &[&str] = &[
+    "p",
+    "div",
+    "section",
+    "article",
+    "main",
+    "header",
+    "footer",
+    "nav",
+    "aside",
+    "figure",
+    "figcaption",
+    "form",
+    "fieldset",
+    "address",
+    "details",
+    "summary",
+    "dl",
+    "dt",
+    "dd",
+    "caption",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "ul",
+    "ol",
+    "table",
+    "blockquote",
+    "hr",
+];
+
+/// Stands in for `|` in table cells while html2md renders the table, which
+/// would otherwise take it for a cell separator
+const CELL_PIPE: char = '\u{E000}';
+
+/// Convert an HTML document to markdown
+///
+/// Relative link and image URLs are resolved against `base_url` when given.
+pub(crate) fn html_to_markdown(html: &str, base_url: Option<&str>) -> String {
+    let dom = parse(html);
+    let content = main_content(&dom.document);
+    if let Some(base) = base_url.and_then(|base| reqwest::Url::parse(base).ok()) {
+        resolve_urls(&content, &base);
+    }
+    replace_cell_pipes(&content, false);
+    html2md::parse_html(&inner_html(&content)).replace(CELL_PIPE, "\\|")
+}
+
+/// Convert an HTML document to plain text
+///
+/// Blocks are separated by blank lines, list items and table rows go on
+/// their own lines and table cells are separated by tabs.
+pub(crate) fn html_to_text(html: &str) -> String {
+    let mut out = String::new();
+    let dom = parse(html);
+    render_text(&main_content(&dom.document), &mut out);
+    normalize(&out)
+}
+
+/// Parse an HTML document, dropping the elements without readable content
+///
+/// The returned DOM must outlive the handles taken from it: dropping it
+/// detaches every node from its children.
+fn parse(html: &str) -> RcDom {
+    let dom = html5ever::parse_document(RcDom::default(), ParseOpts::default()).one(html);
+    remove_skipped(&dom.document);
+    dom
+}
+
+fn element_name(node: &Handle) -> Option<&str> {
+    match &node.data {
+        NodeData::Element { name, .. } => Some(&name.local),
+        _ => None,
+    }
+}
+
+fn has_attr(node: &Handle, name: &str, value: &str) -> bool {
+    match &node.data {
+        NodeData::Element { attrs, .. } => attrs
+            .borrow()
+            .iter()
+            .any(|attr| &*attr.name.local == name && &*attr.value == value),
+        _ => false,
+    }
+}
+
+fn remove_skipped(node: &Handle) {
+    node.children
+        .borrow_mut()
+        .retain(|child| !element_name(child).is_some_and(|name| SKIPPED_ELEMENTS.contains(&name)));
+    for child in node.children.borrow().iter() {
+        remove_skipped(child);
+    }
+}
+
+/// The element holding the page's main content
+///
+/// That's the first `<main>` (or element with `role="main"`), else the
+/// page's only `<article>`, else the whole document.
+fn main_content(document: &Handle) -> Handle {
+    let mut found = Vec::new();
+    find_elements(
+        document,
+        &|node| element_name(node) == Some("main") || has_attr(node, "role", "main"),
+        &mut found,
+    );
+    if let Some(main) = found.into_iter().next() {
+        return main;
+    }
+    let mut articles = Vec::new();
+    find_elements(
+        document,
+        &|node| element_name(node) == Some("article"),
+        &mut articles,
+    );
+    match <[Handle; 1]>::try_from(articles) {
+        Ok([article]) => article,
+        Err(_) => document.clone(),
+    }
+}
+
+/// Collect the outermost elements matching a predicate
+fn find_elements(node: &Handle, matches: &dyn Fn(&Handle) -> bool, found: &mut Vec<Handle>) {
+    for child in node.children.borrow().iter() {
+        if matches(child) {
+            found.push(child.clone());
+        } else {
+            find_elements(child, matches, found);
+        }
+    }
+}
+
+/// Make relative `href` and `src` attributes absolute
+fn resolve_urls(node: &Handle, base: &reqwest::Url) {
+    if let NodeData::Element { attrs, .. } = &node.data {
+        for attr in attrs.borrow_mut().iter_mut() {
+            if matches!(&*attr.name.local, "href" | "src")
+                && !attr.value.starts_with('#')
+                && let Ok(url) = base.join(&attr.value)
+            {
+                attr.value = url.as_str().into();
+            }
+        }
+    }
+    for child in node.children.borrow().iter() {
+        resolve_urls(child, base);
+    }
+}
+
+/// Replace `|` in the text of tables with [`CELL_PIPE`]
+fn replace_cell_pipes(node: &Handle, in_table: bool) {
+    if in_table && let NodeData::Text { contents } = &node.data {
+        let text = contents.borrow().replace('|', &CELL_PIPE.to_string());
+        *contents.borrow_mut() = text.into();
+    }
+    let in_table = in_table || element_name(node) == Some("table");
+    for child in node.children.borrow().iter() {
+        replace_cell_pipes(child, in_table);
+    }
+}
+
+/// Serialize the children of `node` back to HTML
+fn inner_html(node: &Handle) -> String {
+    let mut html = Vec::new();
+    let opts = SerializeOpts {
+        traversal_scope: TraversalScope::ChildrenOnly(None),
+        ..SerializeOpts::default()
+    };
+    // Writing to a Vec can't fail
+    drop(serialize(
+        &mut html,
+        &SerializableHandle::from(node.clone()),
+        opts,
+    ));
+    String::from_utf8_lossy(&html).into_owned()
+}
+
+// ============================================================================
+// Plain text
+// ============================================================================
+
+/// Render a node's children as plain text
+fn render_text(node: &Handle, out: &mut String) {
+    for child in node.children.borrow().iter() {
+        let name = match &child.data {
+            NodeData::Text { contents } => {
+                push_text(out, &contents.borrow());
+                continue;
+            }
+            NodeData::Element { name, .. } => &*name.local,
+            _ => continue,
+        };
+
+        match name {
+            "br" => {
+                trim_trailing_spaces(out);
+                out.push('\n');
+            }
+            "pre" => {
+                let mut code = String::new();
+                collect_text(child, &mut code);
+                block_break(out);
+                out.push_str(code.trim_matches('\n'));
+                block_break(out);
+            }
+            "li" | "tr" => {
+                line_break(out);
+                render_text(child, out);
+                line_break(out);
+            }
+            "td" | "th" => {
+                if !out.is_empty() && !out.ends_with('\n') {
+                    trim_trailing_spaces(out);
+                    out.push('\t');
+                }
+                render_text(child, out);
+                trim_trailing_spaces(out);
+            }
+            _ if BLOCK_ELEMENTS.contains(&name) => {
+                block_break(out);
+                render_text(child, out);
+                block_break(out);
+            }
+            _ => render_text(child, out),
+        }
+    }
+}
+
+/// Append text, collapsing runs of whitespace like a browser would
+fn push_text(out: &mut String, text: &str) {
+    for c in text.chars() {
+        if c.is_whitespace() {
+            if !out.is_empty() && !out.ends_with([' ', '\n', '\t']) {
+                out.push(' ');
+            }
+        } else {
+            out.push(c);
+        }
+    }
+}
+
+/// Collect raw descendant text, preserving whitespace (for `<pre>`)
+fn collect_text(node: &Handle, out: &mut String) {
+    for child in node.children.borrow().iter() {
+        match &child.data {
+            NodeData::Text { contents } => out.push_str(&contents.borrow()),
+            NodeData::Element { name, .. } if &*name.local == "br" => out.push('\n'),
+            _ => collect_text(child, out),
+        }
+    }
+}
+
+/// Ensure the output ends with a blank line (unless nothing was written yet)
+fn block_break(out: &mut String) {
+    trim_trailing_spaces(out);
+    if out.is_empty() {
+        return;
+    }
+    while !out.ends_with("\n\n") {
+        out.push('\n');
+    }
+}
+
+/// Ensure the output ends with a line break (unless nothing was written yet)
+fn line_break(out: &mut String) {
+    trim_trailing_spaces(out);
+    if !out.is_empty() && !out.ends_with('\n') {
+        out.push('\n');
+    }
+}
+
+fn trim_trailing_spaces(out: &mut String) {
+    let trimmed = out.trim_end_matches(' ').len();
+    out.truncate(trimmed);
+}
+
+/// Trim trailing whitespace and collapse runs of blank lines
+fn normalize(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut blank_run = 0;
+
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            blank_run += 1;
+            if blank_run > 1 {
+                continue;
+            }
+        } else {
+            blank_run = 0;
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+
+    out.trim_matches('\n').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_is_rendered_as_gfm_table() {
+        let html = r#"
+            <h2>Parameters</h2>
+            <table>
+              <thead>
+                <tr><th>Name</th><th align="center">Type</th><th>Description</th></tr>
+              </thead>
+              <tbody>
+                <tr><td><code>id</code></td><td>int</td><td>Unique <b>identifier</b></td></tr>
+                <tr><td>mode</td><td>string</td><td>Either a | b</td></tr>
+              </tbody>
+            </table>
+            <p>Trailing text</p>
+        "#;
+
+        let markdown = html_to_markdown(html, None);
+        let rows: Vec<Vec<&str>> = markdown
+            .lines()
+            .filter(|line| line.starts_with('|'))
+            .map(|line| line.trim_matches('|').split('|').map(str::trim).collect())
+            .collect();
+
+        assert_eq!(rows.len(), 4, "unexpected markdown:\n{markdown}");
+        assert_eq!(rows[0], ["Name", "Type", "Description"]);
+        assert!(rows[1][1].starts_with(':') && rows[1][1].ends_with(':'));
+        assert_eq!(rows[2], ["`id`", "int", "Unique **identifier**"]);
+        // The pipe in the cell is escaped so it doesn't split the cell
+        assert!(markdown.contains("|mode|string|    Either a \\| b     |"));
+        assert!(markdown.starts_with("Parameters\n"));
+        assert!(markdown.ends_with("|\n\nTrailing text"));
+    }
+
+    #[test]
+    fn test_basic_blocks() {
+        let html = r#"<html><head><title>x</title><style>p { color: red }</style></head>
+            <body><h1>Title &amp; more</h1><p>See <a href="https://example.com">the docs</a>.</p>
+            <ul><li>one</li><li>two</li></ul>
+            <pre><code>fn main() {}</code></pre><script>alert("<p>")</script></body></html>"#;
+
+        let markdown = html_to_markdown(html, None);
+        assert!(markdown.starts_with("Title & more\n====="), "{markdown}");
+        assert!(markdown.contains("See [the docs](https://example.com)."));
+        assert!(markdown.contains("* one\n* two"));
+        assert!(markdown.contains("```\nfn main() {}\n```"));
+        assert!(!markdown.contains("color") && !markdown.contains("alert"));
+    }
+
+    #[test]
+    fn test_main_content_and_links() {
+        let html = r#"<html><body>
+            <nav><a href="/">Home</a></nav>
+            <main><h1>Guide</h1><p>Read <a href="../api/index.html">the API</a>
+            and <a href="https://other.example/x">more</a>.</p>
+            <img src="img/logo.png" alt="logo"></main>
+            <footer>Copyright</footer>
+        </body></html>"#;
+
+        let markdown = html_to_markdown(html, Some("https://docs.example.com/guide/start.html"));
+        assert!(markdown.contains("[the API](https://docs.example.com/api/index.html)"));
+        assert!(markdown.contains("[more](https://other.example/x)"));
+        assert!(markdown.contains("![logo](https://docs.example.com/guide/img/logo.png)"));
+        assert!(!markdown.contains("Home") && !markdown.contains("Copyright"));
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        let html = "<article><h2>Limits</h2><p>Requests <b>per</b> minute:</p>\
+                    <table><tr><th>Plan</th><th>Limit</th></tr><tr><td>Free</td><td>60</td></tr></table>\
+                    <ul><li>one</li><li>two</li></ul></article><aside>Ads</aside>";
+
+        assert_eq!(
+            html_to_text(html),
+            "Limits\n\nRequests per minute:\n\nPlan\tLimit\nFree\t60\n\none\ntwo"
+        );
+    }
+}
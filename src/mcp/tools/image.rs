@@ -0,0 +1,71 @@
+//! Image detection for the Read tool
+//!
+//! Images are recognised by their magic bytes, falling back to the file
+//! extension, and returned to the model base64 encoded instead of as text.
+//! Only the formats the model accepts are handled.
+
+use std::path::Path;
+
+use base64::Engine;
+
+/// Maximum image size returned by Read (5MB)
+pub(crate) const MAX_IMAGE_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Bytes needed to recognise any supported format
+pub(crate) const IMAGE_HEADER_SIZE: usize = 12;
+
+/// Media types of the supported image extensions
+const IMAGE_EXTENSIONS: &[(&str, &str)] = &[
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+];
+
+/// Media type of an image file, from its first bytes or its extension
+///
+/// Returns None for anything that isn't a supported image.
+pub(crate) fn image_media_type(path: &Path, header: &[u8]) -> Option<&'static str> {
+    if header.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if header.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if header.starts_with(b"GIF87a") || header.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if header.starts_with(b"RIFF") && header.get(8..12) == Some(b"WEBP") {
+        Some("image/webp")
+    } else {
+        let extension = path.extension()?.to_str()?;
+        IMAGE_EXTENSIONS
+            .iter()
+            .find(|(ext, _)| ext.eq_ignore_ascii_case(extension))
+            .map(|(_, media_type)| *media_type)
+    }
+}
+
+/// Base64-encode image data
+pub(crate) fn encode_image(bytes: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_media_type() {
+        let png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR";
+        assert_eq!(image_media_type(Path::new("a.bin"), png), Some("image/png"));
+        assert_eq!(
+            image_media_type(Path::new("a"), b"RIFF\0\0\0\0WEBPVP8 "),
+            Some("image/webp")
+        );
+        assert_eq!(
+            image_media_type(Path::new("photo.JPG"), b""),
+            Some("image/jpeg")
+        );
+        assert_eq!(image_media_type(Path::new("notes.txt"), b"hello"), None);
+        assert_eq!(image_media_type(Path::new("icon.svg"), b"<svg"), None);
+    }
+}
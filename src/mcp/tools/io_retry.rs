@@ -0,0 +1,103 @@
+//! Retrying of file operations that fail transiently
+//!
+//! A file briefly locked by another process (an editor saving, a virus
+//! scanner on Windows) or an interrupted system call can fail a Read or
+//! Write that would succeed a moment later. Such errors are retried a few
+//! times with a short backoff. Everything else, like a missing file or a
+//! permission error, fails at once.
+
+use std::future::Future;
+use std::io::{self, ErrorKind};
+use std::time::Duration;
+
+/// Attempts made before a transient error is returned
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry, doubled for each further one
+const INITIAL_BACKOFF: Duration = Duration::from_millis(25);
+
+/// Whether an IO error is likely to go away when retried
+pub fn is_transient(error: &io::Error) -> bool {
+    match error.kind() {
+        // EAGAIN/EWOULDBLOCK, EINTR, EBUSY
+        ErrorKind::WouldBlock | ErrorKind::Interrupted | ErrorKind::ResourceBusy => true,
+        // ERROR_SHARING_VIOLATION and ERROR_LOCK_VIOLATION: another process
+        // has the file open or locked
+        _ if cfg!(windows) => matches!(error.raw_os_error(), Some(32 | 33)),
+        _ => false,
+    }
+}
+
+/// Run `operation`, retrying it while it fails with a transient error
+///
+/// Makes at most three attempts, so a persistent problem is reported after
+/// well under a second.
+pub async fn retry_transient<T, F, Fut>(mut operation: F) -> io::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = io::Result<T>>,
+{
+    let mut attempt = 1;
+    let mut backoff = INITIAL_BACKOFF;
+    loop {
+        match operation().await {
+            Err(e) if attempt < MAX_ATTEMPTS && is_transient(&e) => {
+                tracing::debug!(
+                    attempt = attempt,
+                    error = %e,
+                    backoff_ms = backoff.as_millis(),
+                    "Transient IO error, retrying"
+                );
+                tokio::time::sleep(backoff).await;
+                attempt += 1;
+                backoff *= 2;
+            }
+            result => return result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    #[tokio::test]
+    async fn test_retry_transient_error_then_success() {
+        let calls = AtomicU32::new(0);
+        let result = retry_transient(|| async {
+            if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err(io::Error::from(ErrorKind::WouldBlock))
+            } else {
+                Ok("content")
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), "content");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_gives_up_and_skips_permanent_errors() {
+        let calls = AtomicU32::new(0);
+        let result: io::Result<()> = retry_transient(|| async {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Err(io::Error::from(ErrorKind::ResourceBusy))
+        })
+        .await;
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::ResourceBusy);
+        assert_eq!(calls.load(Ordering::SeqCst), MAX_ATTEMPTS);
+
+        for kind in [ErrorKind::NotFound, ErrorKind::PermissionDenied] {
+            let calls = AtomicU32::new(0);
+            let result: io::Result<()> = retry_transient(|| async {
+                calls.fetch_add(1, Ordering::SeqCst);
+                Err(io::Error::from(kind))
+            })
+            .await;
+            assert_eq!(result.unwrap_err().kind(), kind);
+            assert_eq!(calls.load(Ordering::SeqCst), 1);
+        }
+    }
+}
@@ -1,12 +1,15 @@
 //! KillShell tool for terminating background shell processes
 //!
 //! This tool kills a running background shell process started with
-//! `run_in_background=true`.
+//! `run_in_background=true`. The process gets SIGTERM first and is killed
+//! if it hasn't exited after a grace period.
 //!
 //! Supports two execution modes:
 //! - Direct process execution: shell IDs starting with "shell-"
 //! - Terminal API: shell IDs starting with "term-" (Client-side PTY)
 
+use std::time::Duration;
+
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::{Value, json};
@@ -19,6 +22,9 @@ use crate::terminal::TerminalId;
 /// Prefix for Terminal API shell IDs
 const TERMINAL_API_PREFIX: &str = "term-";
 
+/// Time a process gets to exit after SIGTERM before it is killed
+const KILL_GRACE_PERIOD: Duration = Duration::from_secs(3);
+
 /// KillShell tool implementation
 #[derive(Debug, Default)]
 pub struct KillShellTool;
@@ -120,7 +126,12 @@ impl KillShellTool {
         // Use get() because BackgroundTerminal contains ChildHandle
         // We only need a shared reference to clone the ChildHandle
         let Some(terminal) = manager.get(shell_id) else {
-            return ToolResult::error(format!("Unknown shell ID: {}", shell_id));
+            return ToolResult::success(format!(
+                "No background shell with ID {}; there is nothing to kill. \
+                 Use ListShells to see the background shells.",
+                shell_id
+            ))
+            .with_metadata(json!({ "shell_id": shell_id, "status": "unknown" }));
         };
 
         // Check the terminal state and kill if running
@@ -138,32 +149,54 @@ impl KillShellTool {
                 // Clone output and immediately release lock
                 let final_output = {
                     let buffer_guard = output_buffer_clone.lock().await;
-                    buffer_guard.clone()
+                    buffer_guard.contents()
                 }; // Lock released here
 
-                // Kill the process (ChildHandle::kill() handles locking internally)
-                match child_handle.kill().await {
-                    Ok(()) => {
-                        // Update terminal to finished state
+                // The output reader records the exit as a kill
+                manager.stop_with(shell_id, TerminalExitStatus::Killed);
+
+                // SIGTERM, then SIGKILL after the grace period
+                match child_handle.terminate(KILL_GRACE_PERIOD).await {
+                    Ok(forced) => {
+                        // The process has exited; mark it killed if the output
+                        // reader hasn't finished it yet
                         manager
                             .finish_terminal(shell_id, TerminalExitStatus::Killed)
                             .await;
-
+                        let message = if forced {
+                            format!(
+                                "Command did not exit within {}s of SIGTERM and was killed.",
+                                KILL_GRACE_PERIOD.as_secs()
+                            )
+                        } else {
+                            "Command terminated successfully.".to_string()
+                        };
                         ToolResult::success(format!(
-                            "Command killed successfully.\n\nFinal output:\n{}",
+                            "{}\n\nFinal output:\n{}",
+                            message,
                             if final_output.is_empty() {
                                 "(No output)".to_string()
                             } else {
                                 final_output
                             }
                         ))
+                        .with_metadata(json!({
+                            "shell_id": shell_id,
+                            "status": TerminalExitStatus::Killed.as_str(),
+                            "forced": forced
+                        }))
+                    }
+                    Err(e) => {
+                        // Still running, so it stays that way
+                        manager.cancel_stop(shell_id);
+                        ToolResult::error(format!("Failed to kill process: {}", e))
                     }
-                    Err(e) => ToolResult::error(format!("Failed to kill process: {}", e)),
                 }
             }
             BackgroundTerminal::Finished {
                 status,
                 final_output,
+                ..
             } => {
                 let message = match status {
                     TerminalExitStatus::Exited(code) => {
@@ -183,6 +216,11 @@ impl KillShellTool {
                         final_output.clone()
                     }
                 ))
+                .with_metadata(json!({
+                    "shell_id": shell_id,
+                    "status": status.as_str(),
+                    "exit_code": status.exit_code()
+                }))
             }
         }
     }
@@ -191,6 +229,10 @@ impl KillShellTool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::session::{BackgroundProcessManager, ChildHandle};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use tokio::sync::Mutex;
 
     #[test]
     fn test_kill_shell_tool_properties() {
@@ -213,4 +255,70 @@ mod tests {
                 .contains(&json!("shell_id"))
         );
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_kill_background_shell() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = Arc::new(BackgroundProcessManager::new());
+        let context =
+            ToolContext::new("test", temp_dir.path()).with_background_processes(manager.clone());
+        let tool = KillShellTool;
+
+        let child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let handle = ChildHandle::Unwrapped {
+            child: Arc::new(Mutex::new(child)),
+        };
+        manager.register(
+            "shell-1".to_string(),
+            "sleep 30",
+            BackgroundTerminal::new_running(handle.clone()),
+        );
+        // Like Bash's output reader, a task waits for the process meanwhile
+        // and reports its exit
+        let waiter = tokio::spawn({
+            let manager = manager.clone();
+            async move {
+                let mut handle = handle;
+                let status = handle.wait().await.unwrap();
+                manager
+                    .finish_terminal("shell-1", TerminalExitStatus::Exited(-1))
+                    .await;
+                status
+            }
+        });
+
+        let result = tokio::time::timeout(
+            Duration::from_secs(10),
+            tool.execute(json!({"shell_id": "shell-1"}), &context),
+        )
+        .await
+        .expect("killing should not wait for the process to exit on its own");
+        assert!(!result.is_error, "{}", result.content);
+        assert!(
+            result
+                .content
+                .starts_with("Command terminated successfully.")
+        );
+        assert_eq!(result.metadata.unwrap()["forced"], false);
+        assert!(!waiter.await.unwrap().success());
+        assert_eq!(manager.get("shell-1").unwrap().status_str(), "killed");
+
+        // Killing it again, or a shell that doesn't exist, is not an error
+        let result = tool.execute(json!({"shell_id": "shell-1"}), &context).await;
+        assert!(!result.is_error);
+        assert!(result.content.starts_with("Command was already killed."));
+        let result = tool
+            .execute(json!({"shell_id": "shell-404"}), &context)
+            .await;
+        assert!(!result.is_error);
+        assert!(
+            result
+                .content
+                .contains("No background shell with ID shell-404")
+        );
+    }
 }
@@ -0,0 +1,164 @@
+//! ListShells tool implementation
+//!
+//! Lists the background shells started with `run_in_background=true`, so
+//! their IDs can be found again for BashOutput and KillShell.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use serde_json::{Value, json};
+
+use super::base::{Tool, ToolKind};
+use crate::mcp::registry::{ToolContext, ToolResult};
+
+/// Maximum characters of a command shown per shell
+const MAX_COMMAND_CHARS: usize = 80;
+
+/// ListShells tool for listing background shells
+#[derive(Debug, Default)]
+pub struct ListShellsTool;
+
+impl ListShellsTool {
+    /// Create a new ListShells tool instance
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Tool for ListShellsTool {
+    fn name(&self) -> &str {
+        "ListShells"
+    }
+
+    fn description(&self) -> &str {
+        "Lists background shells started with run_in_background=true: their shell ID, \
+         command, status (running, exited, killed, ...), start time and the amount of output \
+         produced. Use the shell ID with BashOutput to read output or KillShell to stop one."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {}
+        })
+    }
+
+    fn kind(&self) -> ToolKind {
+        ToolKind::Read
+    }
+
+    fn requires_permission(&self) -> bool {
+        false // Only lists shells of this session
+    }
+
+    async fn execute(&self, _input: Value, context: &ToolContext) -> ToolResult {
+        let Some(manager) = context.background_processes() else {
+            return ToolResult::error("Background process manager not available");
+        };
+
+        let shells = manager.list().await;
+        if shells.is_empty() {
+            return ToolResult::success("No background shells.")
+                .with_metadata(json!({ "shells": [] }));
+        }
+
+        let mut output = format!("{} background shell(s):\n", shells.len());
+        let mut entries = Vec::with_capacity(shells.len());
+        for shell in &shells {
+            let started_at = DateTime::<Local>::from(shell.started_at);
+            let command: String = shell.command.chars().take(MAX_COMMAND_CHARS).collect();
+            let ellipsis = if command.len() < shell.command.len() {
+                "..."
+            } else {
+                ""
+            };
+            output.push_str(&format!(
+                "\n{}  [{}]  started {}  {} bytes of output\n  $ {}{}\n",
+                shell.shell_id,
+                shell.status,
+                started_at.format("%Y-%m-%d %H:%M:%S"),
+                shell.output_bytes,
+                command,
+                ellipsis
+            ));
+            entries.push(json!({
+                "shell_id": shell.shell_id,
+                "command": shell.command,
+                "status": shell.status,
+                "started_at": started_at.to_rfc3339(),
+                "output_bytes": shell.output_bytes
+            }));
+        }
+
+        ToolResult::success(output.trim_end()).with_metadata(json!({ "shells": entries }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::{
+        BackgroundProcessManager, BackgroundTerminal, ChildHandle, TerminalExitStatus,
+    };
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use tokio::sync::Mutex;
+
+    /// Register `program args` as a background shell
+    fn start_background(manager: &Arc<BackgroundProcessManager>, shell_id: &str, command: &str) {
+        let mut parts = command.split_whitespace();
+        let child = tokio::process::Command::new(parts.next().unwrap())
+            .args(parts)
+            .stdout(std::process::Stdio::null())
+            .spawn()
+            .unwrap();
+        let handle = ChildHandle::Unwrapped {
+            child: Arc::new(Mutex::new(child)),
+        };
+        manager.register(
+            shell_id.to_string(),
+            command,
+            BackgroundTerminal::new_running(handle),
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_list_shells() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = Arc::new(BackgroundProcessManager::new());
+        let context =
+            ToolContext::new("test", temp_dir.path()).with_background_processes(manager.clone());
+        let tool = ListShellsTool::new();
+
+        let result = tool.execute(json!({}), &context).await;
+        assert_eq!(result.content, "No background shells.");
+
+        start_background(&manager, "shell-1", "sleep 30");
+        start_background(&manager, "shell-2", "echo done");
+        manager
+            .get("shell-2")
+            .unwrap()
+            .append_output("done\n")
+            .await;
+        manager
+            .finish_terminal("shell-2", TerminalExitStatus::Exited(0))
+            .await;
+
+        let result = tool.execute(json!({}), &context).await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.starts_with("2 background shell(s):"));
+        assert!(result.content.contains("shell-1  [running]"));
+        assert!(result.content.contains("shell-2  [exited]"));
+        assert!(result.content.contains("$ sleep 30"));
+
+        let metadata = result.metadata.unwrap();
+        let shells = metadata["shells"].as_array().unwrap();
+        // Oldest first
+        assert_eq!(shells[0]["shell_id"], "shell-1");
+        assert_eq!(shells[0]["status"], "running");
+        assert_eq!(shells[1]["command"], "echo done");
+        assert_eq!(shells[1]["status"], "exited");
+        assert_eq!(shells[1]["output_bytes"], 5);
+    }
+}
@@ -0,0 +1,231 @@
+//! Locked read-modify-write of text files
+//!
+//! Edit, MultiEdit, the structured edit tools and CargoAdd all read a file,
+//! work out its new content and write it back. [`LockedFile`] holds the
+//! file's lock across those steps, refuses to overwrite a change another
+//! process made meanwhile, and writes the new content atomically, retrying
+//! transient IO errors. Files in other encodings are edited as UTF-8 and
+//! written back in their own encoding.
+
+use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+
+use serde_json::json;
+use tokio::io::AsyncWriteExt;
+
+use super::encoding::{FileEncoding, decode};
+use super::file_lock::{FileLockGuard, lock_file};
+use super::io_retry::retry_transient;
+use super::special_file::reject_special_file;
+use super::verify::verify_written;
+use crate::mcp::registry::{ToolContext, ToolResult};
+
+/// Chunk size for streaming content to disk (64KB)
+pub(crate) const WRITE_CHUNK_SIZE: usize = 64 * 1024;
+
+/// A text file read under its lock, ready to be written back
+#[derive(Debug)]
+pub(crate) struct LockedFile {
+    path: PathBuf,
+    /// The file's bytes as they were read
+    bytes: Vec<u8>,
+    /// The file's content decoded to UTF-8
+    content: String,
+    encoding: FileEncoding,
+    _lock: FileLockGuard,
+}
+
+impl LockedFile {
+    /// Lock the existing file at `path` and read it
+    ///
+    /// Fails with the result to return from the tool when the file is
+    /// missing, is a special file, can't be read or isn't text. The
+    /// encoding is recorded like Read does, so a later Write keeps it.
+    pub async fn open(path: &Path, context: &ToolContext) -> Result<Self, ToolResult> {
+        if !path.exists() {
+            return Err(ToolResult::error(format!(
+                "File not found: {}",
+                path.display()
+            )));
+        }
+
+        // Reading a FIFO would block until something writes to it
+        if let Some(result) = reject_special_file("edit", path) {
+            return Err(result);
+        }
+
+        // Hold the file's lock from read to write so concurrent edits don't
+        // overwrite each other
+        let lock = lock_file(path).await;
+        let bytes = retry_transient(|| tokio::fs::read(path))
+            .await
+            .map_err(|e| ToolResult::io_error("reading", path, &e, &context.cwd))?;
+        let Some((content, encoding)) = decode(&bytes) else {
+            return Err(ToolResult::error(format!(
+                "Failed to read {}: binary content or unsupported text encoding",
+                path.display()
+            )));
+        };
+        if let Some(file_encodings) = context.file_encodings() {
+            file_encodings.record(path, encoding);
+        }
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            bytes,
+            content,
+            encoding,
+            _lock: lock,
+        })
+    }
+
+    /// The file's content as it was read
+    pub fn content(&self) -> &str {
+        &self.content
+    }
+
+    /// Replace the file's content with `new_content`
+    ///
+    /// Refused when another process changed the file since it was read, or
+    /// when `new_content` has characters the file's encoding can't hold.
+    /// With `verifyWrites`, a write that doesn't read back is undone by
+    /// restoring the original content, so the edit is all-or-nothing.
+    pub async fn write(&self, new_content: &str, context: &ToolContext) -> Result<(), ToolResult> {
+        let path = self.path.as_path();
+
+        let new_bytes: Cow<'_, [u8]> = if self.encoding.is_plain_utf8() {
+            Cow::Borrowed(new_content.as_bytes())
+        } else {
+            Cow::Owned(self.encoding.encode(new_content).map_err(|e| {
+                ToolResult::error(format!("Cannot write {}: {}", path.display(), e))
+            })?)
+        };
+
+        match retry_transient(|| tokio::fs::read(path)).await {
+            Ok(current) if current == self.bytes => {}
+            Ok(_) => {
+                return Err(ToolResult::error(format!(
+                    "{} was modified while it was being edited. Read it again and retry the edit.",
+                    path.display()
+                )));
+            }
+            Err(e) => return Err(ToolResult::io_error("reading", path, &e, &context.cwd)),
+        }
+
+        retry_transient(|| write_atomic(path, &new_bytes))
+            .await
+            .map_err(|e| ToolResult::io_error("writing", path, &e, &context.cwd))?;
+
+        if context.verify_writes()
+            && let Err(message) = verify_written(path, &new_bytes).await
+        {
+            let rolled_back = retry_transient(|| write_atomic(path, &self.bytes))
+                .await
+                .is_ok();
+            return Err(ToolResult::error(message).with_metadata(json!({
+                "path": path.display().to_string(),
+                "verification_failed": true,
+                "rolled_back": rolled_back
+            })));
+        }
+
+        Ok(())
+    }
+}
+
+/// Write `content` to `path` atomically
+///
+/// The content is streamed in chunks to a temporary file next to the
+/// target, which then replaces it. A failed write leaves the original file
+/// untouched. Symlinks are followed, and an existing file keeps its
+/// permissions.
+pub(crate) async fn write_atomic(path: &Path, content: &[u8]) -> std::io::Result<()> {
+    let target = tokio::fs::canonicalize(path)
+        .await
+        .unwrap_or_else(|_| path.to_path_buf());
+    let dir = target.parent().unwrap_or_else(|| Path::new("."));
+    let file_name = target
+        .file_name()
+        .map(|name| name.to_string_lossy())
+        .unwrap_or_default();
+    let temp_path = dir.join(format!(".{file_name}.{}.tmp", uuid::Uuid::new_v4()));
+
+    let result = async {
+        let mut file = tokio::fs::File::create(&temp_path).await?;
+        if let Ok(metadata) = tokio::fs::metadata(&target).await {
+            file.set_permissions(metadata.permissions()).await?;
+        }
+        for chunk in content.chunks(WRITE_CHUNK_SIZE) {
+            file.write_all(chunk).await?;
+        }
+        file.sync_all().await?;
+        drop(file);
+        tokio::fs::rename(&temp_path, &target).await
+    }
+    .await;
+
+    if result.is_err() {
+        drop(tokio::fs::remove_file(&temp_path).await);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::tools::FileEncodings;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_locked_file_rejects_concurrent_changes() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("file.txt");
+        std::fs::write(&path, "one\n").unwrap();
+        let context = ToolContext::new("test", temp_dir.path());
+
+        let file = LockedFile::open(&path, &context).await.unwrap();
+        assert_eq!(file.content(), "one\n");
+        file.write("two\n", &context).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "two\n");
+        drop(file);
+
+        // Another process writes between the read and the write
+        let file = LockedFile::open(&path, &context).await.unwrap();
+        std::fs::write(&path, "three\n").unwrap();
+        let result = file.write("four\n", &context).await.unwrap_err();
+        assert!(
+            result
+                .content
+                .contains("was modified while it was being edited")
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "three\n");
+
+        let missing = LockedFile::open(&temp_dir.path().join("missing.txt"), &context).await;
+        assert!(missing.unwrap_err().content.starts_with("File not found"));
+    }
+
+    #[tokio::test]
+    async fn test_locked_file_keeps_encoding() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("legacy.txt");
+        std::fs::write(&path, b"caf\xE9\r\n").unwrap();
+        let encodings = Arc::new(FileEncodings::new());
+        let context =
+            ToolContext::new("test", temp_dir.path()).with_file_encodings(encodings.clone());
+
+        let file = LockedFile::open(&path, &context).await.unwrap();
+        assert_eq!(file.content(), "caf\u{e9}\r\n");
+        assert_eq!(encodings.get(&path).unwrap().name(), "windows-1252");
+
+        // Content the encoding can't hold is refused rather than mangled
+        let result = file.write("snowman \u{2603}\r\n", &context).await;
+        assert!(result.unwrap_err().content.contains("can't be written"));
+        assert_eq!(std::fs::read(&path).unwrap(), b"caf\xE9\r\n");
+
+        file.write("caf\u{e9} cr\u{e8}me\r\n", &context)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&path).unwrap(), b"caf\xE9 cr\xE8me\r\n");
+    }
+}
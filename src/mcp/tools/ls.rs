@@ -123,13 +123,7 @@ impl Tool for LsTool {
         // Read directory entries
         let entries = match fs::read_dir(&target_path) {
             Ok(e) => e,
-            Err(e) => {
-                return ToolResult::error(format!(
-                    "Failed to read directory {}: {}",
-                    target_path.display(),
-                    e
-                ));
-            }
+            Err(e) => return ToolResult::io_error("listing", &target_path, &e, &context.cwd),
         };
 
         // Collect and format entries
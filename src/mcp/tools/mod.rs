@@ -4,42 +4,91 @@ mod ask_user_question;
 mod base;
 pub mod bash;
 mod bash_output;
+mod cargo_add;
+mod create_directory;
+mod delete;
+mod diff_files;
+mod domain_filter;
 mod edit;
+mod encoding;
+mod env_info;
 mod exit_plan_mode;
+mod file_lock;
+mod final_newline;
 mod glob;
 mod grep;
+mod grep_builtin;
+mod html_to_markdown;
+mod image;
+mod io_retry;
 mod kill_shell;
+mod list_shells;
+mod locked_file;
 mod ls;
+mod move_file;
+mod multi_edit;
 mod notebook_edit;
 mod notebook_read;
+mod pdf;
+mod preview;
 mod read;
+mod recent_files;
+mod repo_stats;
 mod skill;
 mod slash_command;
+mod special_file;
+mod structured_edit;
+mod structured_json;
+mod structured_yaml;
 mod task;
 mod task_output;
+mod test_results;
 mod todo_write;
+mod touch;
+mod verify;
+mod walk;
 mod web_fetch;
 mod web_search;
+pub(crate) mod workspace;
 mod write;
 
 pub use ask_user_question::AskUserQuestionTool;
 pub use base::Tool;
-pub use bash::{BashTool, contains_shell_operator};
+pub use bash::{
+    BashTool, check_command_env, contains_shell_operator, resolve_command_cwd,
+    unexpected_exit_message,
+};
 pub use bash_output::BashOutputTool;
+pub use cargo_add::CargoAddTool;
+pub use create_directory::CreateDirectoryTool;
+pub use delete::DeleteTool;
+pub use diff_files::DiffFilesTool;
+pub use domain_filter::{DomainBlock, DomainFilter};
 pub use edit::EditTool;
+pub use encoding::{FileEncoding, FileEncodings};
+pub use env_info::{EnvInfoConfig, EnvInfoTool};
 pub use exit_plan_mode::ExitPlanModeTool;
+pub use final_newline::FinalNewlinePolicy;
 pub use glob::GlobTool;
 pub use grep::GrepTool;
 pub use kill_shell::KillShellTool;
+pub use list_shells::ListShellsTool;
 pub use ls::LsTool;
+pub use move_file::MoveTool;
+pub use multi_edit::MultiEditTool;
 pub use notebook_edit::NotebookEditTool;
 pub use notebook_read::NotebookReadTool;
 pub use read::ReadTool;
+pub use recent_files::RecentFilesTool;
+pub use repo_stats::RepoStatsTool;
 pub use skill::SkillTool;
 pub use slash_command::SlashCommandTool;
+pub use structured_edit::{StructuredEditTool, StructuredFormat};
 pub use task::TaskTool;
 pub use task_output::TaskOutputTool;
-pub use todo_write::{TodoItem, TodoList, TodoStatus, TodoWriteTool};
+pub use test_results::{TestFailure, TestSummary, parse_test_output};
+pub use todo_write::{TodoChange, TodoItem, TodoList, TodoPriority, TodoStatus, TodoWriteTool};
+pub use touch::TouchTool;
 pub use web_fetch::WebFetchTool;
 pub use web_search::WebSearchTool;
 pub use write::WriteTool;
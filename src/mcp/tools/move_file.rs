@@ -0,0 +1,609 @@
+//! Move tool implementation
+//!
+//! Moves or renames files and directories inside the workspace. With
+//! `references`, the working directory is then searched for mentions of
+//! the old path so imports and docs can be fixed:
+//!
+//! - `report` lists the referencing lines, leaving files untouched
+//! - `update` also rewrites exact mentions of the old path (relative to the
+//!   working directory) to the new one, and lists what is left to fix.
+//!   Files that Edit rules deny are skipped, and rewritten files keep their
+//!   encoding like Edit does
+//!
+//! Rewriting is deliberately conservative: only whole-path mentions are
+//! replaced, so `src/a.rs` isn't touched inside `src/a.rs.bak` or
+//! `lib/src/a.rs`, and relative imports are only reported.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use std::fmt::Write as _;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+
+use super::base::{Tool, ToolKind};
+use super::delete::move_path;
+use super::grep_builtin::{self, FileMatches, SearchOptions};
+use super::locked_file::LockedFile;
+use super::workspace::{normalize, reject_outside_workspace};
+use crate::mcp::registry::{ToolContext, ToolResult};
+use crate::settings::PermissionDecision;
+use crate::types::ToolCallLocation;
+
+/// Maximum number of referencing lines listed in the result
+const MAX_REFERENCES: usize = 100;
+
+/// Move tool for moving and renaming files and directories
+#[derive(Debug, Default)]
+pub struct MoveTool;
+
+/// Move tool input parameters
+#[derive(Debug, Deserialize)]
+struct MoveInput {
+    /// File or directory to move (`from` is accepted as an alias)
+    #[serde(alias = "from")]
+    source: String,
+    /// Where to move it (`to` is accepted as an alias)
+    #[serde(alias = "to")]
+    destination: String,
+    /// What to do about references to the old path
+    #[serde(default)]
+    references: ReferenceMode,
+}
+
+/// A file whose references were rewritten
+#[derive(Debug)]
+struct UpdatedFile {
+    path: String,
+    replacements: usize,
+    /// Final-newline note for the rewritten content
+    note: Option<&'static str>,
+    ends_with_newline: Option<bool>,
+}
+
+/// How references to the moved path are handled
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ReferenceMode {
+    /// Don't look for references
+    #[default]
+    Ignore,
+    /// List the lines that mention the old path
+    Report,
+    /// Rewrite exact mentions of the old path and list the rest
+    Update,
+}
+
+impl MoveTool {
+    /// Create a new Move tool instance
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Tool for MoveTool {
+    fn name(&self) -> &str {
+        "Move"
+    }
+
+    fn description(&self) -> &str {
+        "Move or rename a file or directory inside the workspace. Missing parent directories \
+         of the destination are created; an existing destination is never overwritten. Set \
+         references to \"report\" to list lines that still mention the old path (e.g. imports \
+         to fix), or \"update\" to also rewrite exact mentions of the old path to the new one."
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "required": ["source", "destination"],
+            "properties": {
+                "source": {
+                    "type": "string",
+                    "description": "The file or directory to move (absolute, or relative to the working directory)"
+                },
+                "destination": {
+                    "type": "string",
+                    "description": "The new path (absolute, or relative to the working directory)"
+                },
+                "references": {
+                    "type": "string",
+                    "enum": ["ignore", "report", "update"],
+                    "description": "Search the working directory for references to the old path after moving: \"report\" lists them, \"update\" rewrites exact path mentions and lists the rest (default: ignore)"
+                }
+            }
+        })
+    }
+
+    fn kind(&self) -> ToolKind {
+        ToolKind::Edit
+    }
+
+    fn requires_permission(&self) -> bool {
+        true // Moving files requires permission
+    }
+
+    async fn execute(&self, input: serde_json::Value, context: &ToolContext) -> ToolResult {
+        // Parse input
+        let params: MoveInput = match serde_json::from_value(input) {
+            Ok(p) => p,
+            Err(e) => return ToolResult::error(format!("Invalid input: {}", e)),
+        };
+
+        // Resolve paths relative to working directory if not absolute
+        let source = normalize(&context.cwd.join(&params.source));
+        let destination = normalize(&context.cwd.join(&params.destination));
+
+        let metadata = match std::fs::symlink_metadata(&source) {
+            Ok(metadata) => metadata,
+            Err(e) => return ToolResult::io_error("moving", &source, &e, &context.cwd),
+        };
+
+        // A symlink is moved itself, so only its location must be inside
+        let checked = match source.parent() {
+            Some(parent) if metadata.is_symlink() => parent,
+            _ => &source,
+        };
+        if let Some(result) = reject_outside_workspace("move", checked, context).await {
+            return result;
+        }
+        if let Some(result) = reject_outside_workspace("move to", &destination, context).await {
+            return result;
+        }
+        if destination.symlink_metadata().is_ok() {
+            return ToolResult::error(format!(
+                "{} already exists. Delete it first or choose another destination.",
+                destination.display()
+            ));
+        }
+        if destination.starts_with(&source) {
+            return ToolResult::error(format!("Cannot move {} into itself", source.display()));
+        }
+
+        let (from, to) = (source.clone(), destination.clone());
+        let moved = tokio::task::spawn_blocking(move || {
+            if let Some(parent) = to.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            move_path(&from, &to)
+        })
+        .await
+        .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+        if let Err(e) = moved {
+            return ToolResult::io_error("moving", &source, &e, &context.cwd);
+        }
+
+        tracing::info!(
+            source = %source.display(),
+            destination = %destination.display(),
+            "Moved"
+        );
+
+        let mut output = format!("Moved {} to {}", source.display(), destination.display());
+        let mut metadata = json!({
+            "source": source.display().to_string(),
+            "destination": destination.display().to_string(),
+        });
+        if params.references == ReferenceMode::Ignore {
+            return ToolResult::success(output).with_metadata(metadata);
+        }
+
+        let (Ok(old_rel), Ok(new_rel)) = (
+            source.strip_prefix(&context.cwd),
+            destination.strip_prefix(&context.cwd),
+        ) else {
+            output.push_str(
+                "\n\nReferences weren't searched: only paths inside the working directory are.",
+            );
+            return ToolResult::success(output).with_metadata(metadata);
+        };
+        let (old_rel, new_rel) = (slash_path(old_rel), slash_path(new_rel));
+
+        let mut updated_files = Vec::new();
+        let mut skipped_files = Vec::new();
+        if params.references == ReferenceMode::Update {
+            let files = match find_references(&context.cwd, std::slice::from_ref(&old_rel)).await {
+                Ok(files) => files,
+                Err(e) => return ToolResult::error(format!("Moved, but {e}")),
+            };
+            let final_newline = context.final_newline();
+            for file in files {
+                let path = PathBuf::from(&file.path);
+                if edit_denied(&path, context).await {
+                    skipped_files.push(file.path);
+                    continue;
+                }
+                match update_references(&path, &old_rel, &new_rel, context).await {
+                    Ok((0, _)) => {}
+                    Ok((replacements, content)) => updated_files.push(UpdatedFile {
+                        path: file.path,
+                        replacements,
+                        note: final_newline.note(&content),
+                        ends_with_newline: final_newline
+                            .is_enabled()
+                            .then(|| content.ends_with('\n')),
+                    }),
+                    Err(result) => {
+                        tracing::warn!(
+                            path = %path.display(),
+                            error = %result.content,
+                            "Failed to update references"
+                        );
+                    }
+                }
+            }
+
+            let total: usize = updated_files.iter().map(|file| file.replacements).sum();
+            let _ = write!(
+                output,
+                "\n\nUpdated {total} reference{} to {old_rel} in {} file{}",
+                if total == 1 { "" } else { "s" },
+                updated_files.len(),
+                if updated_files.len() == 1 { "" } else { "s" }
+            );
+            for file in &updated_files {
+                let _ = write!(output, "\n  {} ({})", file.path, file.replacements);
+                if let Some(note) = file.note {
+                    let _ = write!(output, ": {note}");
+                }
+            }
+            if !skipped_files.is_empty() {
+                let _ = write!(
+                    output,
+                    "\n\nSkipped {} file{} that Edit rules deny:",
+                    skipped_files.len(),
+                    if skipped_files.len() == 1 { "" } else { "s" }
+                );
+                for path in &skipped_files {
+                    let _ = write!(output, "\n  {path}");
+                }
+            }
+        }
+
+        // What's left: mentions the rewrite skipped and relative imports
+        let remaining = match find_references(&context.cwd, &reference_patterns(&old_rel)).await {
+            Ok(files) => files,
+            Err(e) => return ToolResult::error(format!("Moved, but {e}")),
+        };
+        let references: Vec<(&str, u64, &str)> = remaining
+            .iter()
+            .flat_map(|file| {
+                file.lines
+                    .iter()
+                    .filter(|line| line.is_match)
+                    .map(|line| (file.path.as_str(), line.number, line.text.as_str()))
+            })
+            .collect();
+
+        if references.is_empty() {
+            output.push_str("\n\nNo references to the old path found.");
+        } else {
+            let heading = if params.references == ReferenceMode::Update {
+                "Remaining references to review"
+            } else {
+                "References to the old path"
+            };
+            let _ = write!(output, "\n\n{heading} ({}):", references.len());
+            for (path, number, text) in references.iter().take(MAX_REFERENCES) {
+                let _ = write!(output, "\n  {path}:{number}: {}", text.trim());
+            }
+            if references.len() > MAX_REFERENCES {
+                let _ = write!(
+                    output,
+                    "\n  ... and {} more",
+                    references.len() - MAX_REFERENCES
+                );
+            }
+        }
+
+        let locations = references
+            .iter()
+            .take(MAX_REFERENCES)
+            .map(|(path, number, _)| {
+                ToolCallLocation::with_line(*path, u32::try_from(*number).unwrap_or(u32::MAX))
+            })
+            .collect();
+        metadata["references"] = references
+            .iter()
+            .map(|(path, number, text)| json!({"path": path, "line": number, "text": text}))
+            .collect();
+        metadata["updated_files"] = updated_files
+            .iter()
+            .map(|file| {
+                json!({
+                    "path": file.path,
+                    "replacements": file.replacements,
+                    "ends_with_newline": file.ends_with_newline,
+                })
+            })
+            .collect();
+        metadata["skipped_files"] = json!(skipped_files);
+
+        ToolResult::success(output)
+            .with_metadata(metadata)
+            .with_locations(locations)
+    }
+}
+
+/// `path` with `/` separators
+fn slash_path(path: &Path) -> String {
+    path.components()
+        .filter_map(|component| match component {
+            Component::Normal(name) => Some(name.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Strings that mention `old_rel`, a path relative to the working directory
+///
+/// Besides the path itself, this covers extensionless module paths
+/// (`src/utils/foo`) and the last two components, which relative imports
+/// such as `../utils/foo` end with.
+fn reference_patterns(old_rel: &str) -> Vec<String> {
+    let mut patterns = vec![old_rel.to_string()];
+    if let Some((parent, name)) = old_rel.rsplit_once('/') {
+        let stem = name.split_once('.').map_or(name, |(stem, _)| stem);
+        if !stem.is_empty() {
+            patterns.push(format!("{parent}/{stem}"));
+            let dir = parent.rsplit('/').next().unwrap_or(parent);
+            patterns.push(format!("{dir}/{stem}"));
+        }
+    }
+    patterns.dedup();
+    patterns
+}
+
+/// Files under `cwd` with lines containing any of `patterns`
+async fn find_references(cwd: &Path, patterns: &[String]) -> Result<Vec<FileMatches>, String> {
+    let options = SearchOptions {
+        pattern: patterns
+            .iter()
+            .map(|pattern| regex::escape(pattern))
+            .collect::<Vec<_>>()
+            .join("|"),
+        ..SearchOptions::default()
+    };
+    let root = cwd.to_path_buf();
+    tokio::task::spawn_blocking(move || {
+        grep_builtin::search(&root, &options, &AtomicBool::new(false))
+    })
+    .await
+    .map_err(|e| format!("searching for references failed: {e}"))?
+    .map_err(|e| format!("searching for references failed: {e}"))
+}
+
+/// Whether the permission rules deny editing `path`
+async fn edit_denied(path: &Path, context: &ToolContext) -> bool {
+    let Some(checker) = &context.permission_checker else {
+        return false;
+    };
+    let input = json!({"file_path": path.display().to_string()});
+    let decision = checker
+        .read()
+        .await
+        .check_permission("Edit", &input)
+        .decision;
+    decision == PermissionDecision::Deny
+}
+
+/// Rewrite whole-path mentions of `old` in `path` to `new`
+///
+/// The file is written back through [`LockedFile`], so it keeps its
+/// encoding. Returns the number of replacements and the new content.
+async fn update_references(
+    path: &Path,
+    old: &str,
+    new: &str,
+    context: &ToolContext,
+) -> Result<(usize, String), ToolResult> {
+    let file = LockedFile::open(path, context).await?;
+    let (updated, count) = replace_path_mentions(file.content(), old, new);
+    if count > 0 {
+        file.write(&updated, context).await?;
+    }
+    Ok((count, updated))
+}
+
+/// Replace mentions of `old` in `text` that aren't part of a longer path
+///
+/// A `./` prefix is allowed; other path characters right before or after
+/// the mention (other than a trailing `/`) rule it out.
+fn replace_path_mentions(text: &str, old: &str, new: &str) -> (String, usize) {
+    let is_path_char = |c: char| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | '\\');
+
+    let mut result = String::with_capacity(text.len());
+    let mut count = 0;
+    let mut rest = 0;
+    for (start, _) in text.match_indices(old) {
+        let end = start + old.len();
+        let before = &text[..start];
+        let before = before.strip_suffix("./").unwrap_or(before);
+        let starts_path = before.chars().next_back().is_none_or(|c| !is_path_char(c));
+        let ends_path = text[end..]
+            .chars()
+            .next()
+            .is_none_or(|c| c == '/' || !is_path_char(c));
+        if start >= rest && starts_path && ends_path {
+            result.push_str(&text[rest..start]);
+            result.push_str(new);
+            rest = end;
+            count += 1;
+        }
+    }
+    result.push_str(&text[rest..]);
+    (result, count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::tools::FinalNewlinePolicy;
+    use tempfile::TempDir;
+
+    fn project() -> TempDir {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        std::fs::create_dir_all(root.join("src/utils")).unwrap();
+        std::fs::create_dir(root.join("web")).unwrap();
+        std::fs::write(root.join("src/utils/old.rs"), "pub fn helper() {}\n").unwrap();
+        std::fs::write(
+            root.join("README.md"),
+            "See src/utils/old.rs for helpers.\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("web/index.js"),
+            "import { helper } from \"../src/utils/old\";\n",
+        )
+        .unwrap();
+        std::fs::write(root.join("notes.txt"), "backup: src/utils/old.rs.bak\n").unwrap();
+        std::fs::write(root.join("unrelated.txt"), "nothing to see\n").unwrap();
+        temp_dir
+    }
+
+    fn referencing_files(result: &ToolResult) -> Vec<String> {
+        let metadata = result.metadata.as_ref().unwrap();
+        let mut files: Vec<String> = metadata["references"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| {
+                let path = r["path"].as_str().unwrap();
+                path.rsplit('/').next().unwrap().to_string()
+            })
+            .collect();
+        files.sort();
+        files.dedup();
+        files
+    }
+
+    #[tokio::test]
+    async fn test_move_reports_references() {
+        let temp_dir = project();
+        let root = temp_dir.path();
+        let tool = MoveTool::new();
+        let context = ToolContext::new("test", root);
+
+        let result = tool
+            .execute(
+                json!({
+                    "source": "src/utils/old.rs",
+                    "destination": "src/helpers/new.rs",
+                    "references": "report"
+                }),
+                &context,
+            )
+            .await;
+
+        assert!(!result.is_error, "{}", result.content);
+        assert!(!root.join("src/utils/old.rs").exists());
+        assert!(root.join("src/helpers/new.rs").is_file());
+        assert_eq!(
+            referencing_files(&result),
+            vec!["README.md", "index.js", "notes.txt"]
+        );
+        assert_eq!(result.locations.len(), 3);
+        // Reporting leaves files untouched
+        assert_eq!(
+            std::fs::read_to_string(root.join("README.md")).unwrap(),
+            "See src/utils/old.rs for helpers.\n"
+        );
+
+        // An existing destination is never overwritten
+        let result = tool
+            .execute(
+                json!({"source": "README.md", "destination": "notes.txt"}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error);
+        assert!(result.content.contains("already exists"));
+    }
+
+    #[tokio::test]
+    async fn test_move_updates_exact_references() {
+        let temp_dir = project();
+        let root = temp_dir.path();
+        let tool = MoveTool::new();
+        let context = ToolContext::new("test", root);
+
+        let result = tool
+            .execute(
+                json!({
+                    "source": "src/utils/old.rs",
+                    "destination": "src/helpers/new.rs",
+                    "references": "update"
+                }),
+                &context,
+            )
+            .await;
+
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(
+            std::fs::read_to_string(root.join("README.md")).unwrap(),
+            "See src/helpers/new.rs for helpers.\n"
+        );
+        // Longer paths and relative imports are left for review
+        assert_eq!(
+            std::fs::read_to_string(root.join("notes.txt")).unwrap(),
+            "backup: src/utils/old.rs.bak\n"
+        );
+        assert_eq!(referencing_files(&result), vec!["index.js", "notes.txt"]);
+        assert_eq!(
+            result.metadata.unwrap()["updated_files"][0]["replacements"],
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn test_move_updates_references_like_edit() {
+        let temp_dir = project();
+        let root = temp_dir.path();
+        std::fs::write(root.join("legacy.txt"), b"caf\xE9: src/utils/old.rs").unwrap();
+        let context = ToolContext::new("test", root).with_final_newline(FinalNewlinePolicy::Warn);
+
+        let result = MoveTool::new()
+            .execute(
+                json!({
+                    "source": "src/utils/old.rs",
+                    "destination": "src/helpers/new.rs",
+                    "references": "update"
+                }),
+                &context,
+            )
+            .await;
+
+        assert!(!result.is_error, "{}", result.content);
+        // The file keeps its encoding, and its missing newline is mentioned
+        assert_eq!(
+            std::fs::read(root.join("legacy.txt")).unwrap(),
+            b"caf\xE9: src/helpers/new.rs"
+        );
+        assert!(
+            result
+                .content
+                .contains("legacy.txt (1): File does not end with a newline")
+        );
+        assert!(!result.content.contains("README.md (1): "));
+    }
+
+    #[test]
+    fn test_replace_path_mentions() {
+        let text = "src/a.rs ./src/a.rs lib/src/a.rs src/a.rs.bak (src/a.rs)";
+        let (updated, count) = replace_path_mentions(text, "src/a.rs", "src/b.rs");
+        assert_eq!(count, 3);
+        assert_eq!(
+            updated,
+            "src/b.rs ./src/b.rs lib/src/a.rs src/a.rs.bak (src/b.rs)"
+        );
+
+        assert_eq!(
+            reference_patterns("src/utils/old.rs"),
+            vec!["src/utils/old.rs", "src/utils/old", "utils/old"]
+        );
+        assert_eq!(reference_patterns("old.rs"), vec!["old.rs"]);
+    }
+}
@@ -0,0 +1,282 @@
+//! MultiEdit tool implementation
+//!
+//! Applies several string replacements to one file in a single call. The
+//! edits run in order on the file's content in memory, each seeing the
+//! result of the previous one, and the file is only written once all of
+//! them succeeded. If the written content then fails verification, the
+//! original content is restored.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+
+use super::base::{Tool, ToolKind};
+use super::locked_file::LockedFile;
+use crate::mcp::registry::{ToolContext, ToolResult};
+
+/// MultiEdit tool for applying several replacements to a file at once
+#[derive(Debug, Default)]
+pub struct MultiEditTool;
+
+/// MultiEdit tool input parameters
+#[derive(Debug, Deserialize)]
+struct MultiEditInput {
+    /// Path to the file to edit (canonical name; `path` and `filePath` are
+    /// accepted as aliases)
+    #[serde(alias = "path", alias = "filePath")]
+    file_path: String,
+    /// Replacements, applied in order
+    edits: Vec<EditOperation>,
+}
+
+/// A single replacement of a MultiEdit call
+#[derive(Debug, Deserialize)]
+struct EditOperation {
+    /// String to search for
+    old_string: String,
+    /// String to replace with
+    new_string: String,
+    /// Whether to replace all occurrences (default: false)
+    #[serde(default)]
+    replace_all: bool,
+}
+
+impl MultiEditTool {
+    /// Create a new MultiEdit tool instance
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Tool for MultiEditTool {
+    fn name(&self) -> &str {
+        "MultiEdit"
+    }
+
+    fn description(&self) -> &str {
+        "Perform several string replacement edits in one file in a single call. Edits are applied in order, each to the result of the previous one, with the same rules as Edit. Either all edits are applied or, if any fails, none are. Prefer this over several Edit calls to the same file."
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "required": ["file_path", "edits"],
+            "properties": {
+                "file_path": {
+                    "type": "string",
+                    "description": "The absolute path to the file to edit"
+                },
+                "edits": {
+                    "type": "array",
+                    "minItems": 1,
+                    "description": "Edits to apply in order",
+                    "items": {
+                        "type": "object",
+                        "required": ["old_string", "new_string"],
+                        "properties": {
+                            "old_string": {
+                                "type": "string",
+                                "description": "The exact string to find and replace"
+                            },
+                            "new_string": {
+                                "type": "string",
+                                "description": "The string to replace old_string with"
+                            },
+                            "replace_all": {
+                                "type": "boolean",
+                                "description": "Whether to replace all occurrences. Default: false (requires unique match)"
+                            }
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    fn kind(&self) -> ToolKind {
+        ToolKind::Edit
+    }
+
+    fn requires_permission(&self) -> bool {
+        true // Editing requires permission
+    }
+
+    async fn execute(&self, input: serde_json::Value, context: &ToolContext) -> ToolResult {
+        // Parse input
+        let params: MultiEditInput = match serde_json::from_value(input) {
+            Ok(p) => p,
+            Err(e) => return ToolResult::error(format!("Invalid input: {}", e)),
+        };
+        if params.edits.is_empty() {
+            return ToolResult::error("No edits given. Provide at least one edit.");
+        }
+
+        // Resolve path relative to working directory if not absolute
+        let path = if std::path::Path::new(&params.file_path).is_absolute() {
+            std::path::PathBuf::from(&params.file_path)
+        } else {
+            context.cwd.join(&params.file_path)
+        };
+
+        // Lock and read the file
+        let file = match LockedFile::open(&path, context).await {
+            Ok(file) => file,
+            Err(result) => return result,
+        };
+
+        let (new_content, replacements) = match apply_edits(file.content(), &params.edits) {
+            Ok(applied) => applied,
+            Err(message) => {
+                return ToolResult::error(format!("{message} No edits were applied."));
+            }
+        };
+
+        // Write updated content
+        if let Err(result) = file.write(&new_content, context).await {
+            return result;
+        }
+
+        let total: usize = replacements.iter().sum();
+        let mut output = format!(
+            "Edited {} ({} edit{}, {} replacement{})",
+            path.display(),
+            params.edits.len(),
+            if params.edits.len() > 1 { "s" } else { "" },
+            total,
+            if total > 1 { "s" } else { "" }
+        );
+        let final_newline = context.final_newline();
+        if let Some(note) = final_newline.note(&new_content) {
+            output.push('\n');
+            output.push_str(note);
+        }
+
+        ToolResult::success(output).with_metadata(json!({
+            "path": path.display().to_string(),
+            "edits": params.edits.len(),
+            "replacements": replacements,
+            "verified": context.verify_writes(),
+            "ends_with_newline": final_newline
+                .is_enabled()
+                .then(|| new_content.ends_with('\n'))
+        }))
+    }
+}
+
+/// Apply `edits` to `content` in order
+///
+/// Returns the new content and the number of replacements of each edit, or
+/// a message naming the first edit that failed.
+fn apply_edits(content: &str, edits: &[EditOperation]) -> Result<(String, Vec<usize>), String> {
+    let mut content = content.to_string();
+    let mut replacements = Vec::with_capacity(edits.len());
+
+    for (i, edit) in edits.iter().enumerate() {
+        let number = i + 1;
+        if edit.old_string.is_empty() {
+            return Err(format!("Edit {number}: old_string is empty."));
+        }
+        if edit.old_string == edit.new_string {
+            return Err(format!(
+                "Edit {number}: old_string and new_string are identical."
+            ));
+        }
+
+        let match_count = content.matches(&edit.old_string).count();
+        if match_count == 0 {
+            return Err(format!(
+                "Edit {number}: string not found in file (after applying the previous edits). The old_string must match exactly."
+            ));
+        }
+        if match_count > 1 && !edit.replace_all {
+            return Err(format!(
+                "Edit {number}: found {match_count} occurrences of the search string. Use replace_all: true to replace all, or provide a more unique string."
+            ));
+        }
+
+        content = if edit.replace_all {
+            content.replace(&edit.old_string, &edit.new_string)
+        } else {
+            content.replacen(&edit.old_string, &edit.new_string, 1)
+        };
+        replacements.push(if edit.replace_all { match_count } else { 1 });
+    }
+
+    Ok((content, replacements))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_multi_edit_applies_edits_in_order() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("lib.rs");
+        std::fs::write(&file_path, "fn old() {}\nfn caller() { old(); old(); }\n").unwrap();
+
+        let tool = MultiEditTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+
+        let result = tool
+            .execute(
+                json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "edits": [
+                        {"old_string": "old", "new_string": "renamed", "replace_all": true},
+                        {"old_string": "fn caller", "new_string": "pub fn caller"}
+                    ]
+                }),
+                &context,
+            )
+            .await;
+
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "fn renamed() {}\npub fn caller() { renamed(); renamed(); }\n"
+        );
+        assert_eq!(result.metadata.unwrap()["replacements"], json!([3, 1]));
+    }
+
+    #[tokio::test]
+    async fn test_multi_edit_is_all_or_nothing() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("test.txt");
+        std::fs::write(&file_path, "alpha beta beta").unwrap();
+
+        let tool = MultiEditTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+
+        // The second edit is ambiguous, so the first one isn't applied either
+        let result = tool
+            .execute(
+                json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "edits": [
+                        {"old_string": "alpha", "new_string": "gamma"},
+                        {"old_string": "beta", "new_string": "delta"}
+                    ]
+                }),
+                &context,
+            )
+            .await;
+
+        assert!(result.is_error);
+        assert!(result.content.starts_with("Edit 2: found 2 occurrences"));
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "alpha beta beta"
+        );
+
+        let result = tool
+            .execute(
+                json!({"file_path": file_path.to_str().unwrap(), "edits": []}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error);
+    }
+}
@@ -13,7 +13,9 @@ use crate::mcp::registry::{ToolContext, ToolResult};
 /// Input parameters for NotebookEdit
 #[derive(Debug, Deserialize)]
 struct NotebookEditInput {
-    /// The absolute path to the notebook file
+    /// The absolute path to the notebook file (canonical name; `path` and
+    /// `notebookPath` are accepted as aliases)
+    #[serde(alias = "path", alias = "notebookPath")]
     notebook_path: String,
     /// The new source for the cell
     new_source: String,
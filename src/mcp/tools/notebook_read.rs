@@ -13,7 +13,9 @@ use crate::mcp::registry::{ToolContext, ToolResult};
 /// Input parameters for NotebookRead
 #[derive(Debug, Deserialize)]
 struct NotebookReadInput {
-    /// The absolute path to the notebook file
+    /// The absolute path to the notebook file (canonical name; `path` and
+    /// `notebookPath` are accepted as aliases)
+    #[serde(alias = "path", alias = "notebookPath")]
     notebook_path: String,
 }
 
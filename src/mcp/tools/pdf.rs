@@ -0,0 +1,172 @@
+//! PDF text extraction shared by the Read and WebFetch tools
+//!
+//! Text is extracted with a pure-Rust parser, so layout, tables and images
+//! are not preserved. Encrypted documents are rejected.
+
+use std::path::Path;
+
+/// Maximum PDF size accepted for text extraction (20MB)
+pub(crate) const MAX_PDF_SIZE: usize = 20 * 1024 * 1024;
+
+/// Note included with extracted PDF text
+pub(crate) const PDF_FORMATTING_NOTE: &str =
+    "Note: text was extracted from a PDF; formatting, tables and images may be lost.";
+
+/// Check whether a path has a `.pdf` extension
+pub(crate) fn has_pdf_extension(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("pdf"))
+}
+
+/// Check whether content starts with the PDF magic header
+pub(crate) fn is_pdf_bytes(bytes: &[u8]) -> bool {
+    bytes.starts_with(b"%PDF-")
+}
+
+/// Extract plain text from an in-memory PDF
+///
+/// Parsing is CPU-bound and may panic on malformed input, so it runs on the
+/// blocking pool where a panic surfaces as a join error instead of
+/// unwinding through the tool.
+pub(crate) async fn extract_pdf_text(bytes: Vec<u8>) -> Result<String, String> {
+    if bytes.len() > MAX_PDF_SIZE {
+        #[allow(clippy::cast_precision_loss)]
+        let size_mb = bytes.len() as f64 / 1024.0 / 1024.0;
+        return Err(format!(
+            "PDF too large ({:.1}MB). Maximum supported size for text extraction is {}MB.",
+            size_mb,
+            MAX_PDF_SIZE / 1024 / 1024
+        ));
+    }
+
+    tokio::task::spawn_blocking(move || extract_blocking(&bytes))
+        .await
+        .map_err(|e| format!("Failed to extract PDF text: {}", e))?
+}
+
+fn extract_blocking(bytes: &[u8]) -> Result<String, String> {
+    let doc = match pdf_extract::Document::load_mem(bytes) {
+        Ok(doc) => doc,
+        Err(_) if contains(bytes, b"/Encrypt") => return Err(encrypted_error()),
+        Err(e) => return Err(format!("Failed to parse PDF: {}", e)),
+    };
+
+    if doc.is_encrypted() {
+        return Err(encrypted_error());
+    }
+
+    let mut text = String::new();
+    {
+        let mut output = pdf_extract::PlainTextOutput::new(&mut text);
+        pdf_extract::output_doc(&doc, &mut output)
+            .map_err(|e| format!("Failed to extract PDF text: {}", e))?;
+    }
+
+    Ok(text.trim().to_string())
+}
+
+fn encrypted_error() -> String {
+    "PDF is encrypted; text extraction from encrypted PDFs is not supported".to_string()
+}
+
+fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+    haystack
+        .windows(needle.len())
+        .any(|window| window == needle)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use pdf_extract::content::{Content, Operation};
+    use pdf_extract::{Document, Object, Stream, dictionary};
+
+    /// Build a single-page PDF containing `text`
+    pub(crate) fn sample_pdf(text: &str) -> Vec<u8> {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let resources_id = doc.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+        let content = Content {
+            operations: vec![
+                Operation::new("BT", vec![]),
+                Operation::new("Tf", vec!["F1".into(), 24.into()]),
+                Operation::new("Td", vec![100.into(), 600.into()]),
+                Operation::new("Tj", vec![Object::string_literal(text)]),
+                Operation::new("ET", vec![]),
+            ],
+        };
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_id.into()],
+                "Count" => 1,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+        bytes
+    }
+
+    #[tokio::test]
+    async fn test_extract_pdf_text() {
+        let bytes = sample_pdf("Hello PDF world");
+        assert!(is_pdf_bytes(&bytes));
+
+        let text = extract_pdf_text(bytes).await.unwrap();
+        assert!(text.contains("Hello PDF world"), "unexpected text: {text}");
+    }
+
+    #[tokio::test]
+    async fn test_extract_rejects_encrypted_pdf() {
+        let mut doc = Document::load_mem(&sample_pdf("secret")).unwrap();
+        let encrypt_id = doc.add_object(dictionary! {
+            "Filter" => "Standard",
+            "V" => 1,
+            "R" => 2,
+        });
+        doc.trailer.set("Encrypt", encrypt_id);
+        let mut bytes = Vec::new();
+        doc.save_to(&mut bytes).unwrap();
+
+        let err = extract_pdf_text(bytes).await.unwrap_err();
+        assert!(err.contains("encrypted"), "unexpected error: {err}");
+    }
+
+    #[tokio::test]
+    async fn test_extract_rejects_invalid_pdf() {
+        let err = extract_pdf_text(b"%PDF-1.5 garbage".to_vec())
+            .await
+            .unwrap_err();
+        assert!(err.contains("PDF"));
+    }
+
+    #[test]
+    fn test_has_pdf_extension() {
+        assert!(has_pdf_extension(Path::new("docs/manual.pdf")));
+        assert!(has_pdf_extension(Path::new("MANUAL.PDF")));
+        assert!(!has_pdf_extension(Path::new("manual.txt")));
+        assert!(!has_pdf_extension(Path::new("pdf")));
+    }
+}
@@ -0,0 +1,59 @@
+//! Dry-run previews for Edit and Write
+//!
+//! With `dry_run: true` these tools compute the change as usual but return
+//! it as a unified diff instead of writing it. The client is shown that diff
+//! as the tool call's Diff content.
+
+use std::path::Path;
+
+use serde_json::json;
+use similar::{ChangeTag, TextDiff};
+
+use crate::mcp::registry::ToolResult;
+
+/// First line of every preview result
+pub(super) const PREVIEW_NOTICE: &str = "Preview only, no changes written.";
+
+/// Unchanged lines shown around each change of a preview
+const PREVIEW_CONTEXT_LINES: usize = 3;
+
+/// Result of a dry run that would change `path` from `old` to `new`
+pub(super) fn preview_result(path: &Path, old: &str, new: &str, created: bool) -> ToolResult {
+    let diff = TextDiff::from_lines(old, new);
+    let (mut additions, mut removals) = (0, 0);
+    for change in diff.iter_all_changes() {
+        match change.tag() {
+            ChangeTag::Insert => additions += 1,
+            ChangeTag::Delete => removals += 1,
+            ChangeTag::Equal => {}
+        }
+    }
+
+    let mut output = PREVIEW_NOTICE.to_string();
+    if additions == 0 && removals == 0 {
+        let outcome = if created {
+            "would be created empty"
+        } else {
+            "would not change"
+        };
+        output.push_str(&format!(" {} {}.", path.display(), outcome));
+    } else {
+        let name = path.display().to_string();
+        output.push_str("\n\n");
+        output.push_str(
+            &diff
+                .unified_diff()
+                .context_radius(PREVIEW_CONTEXT_LINES)
+                .header(if created { "/dev/null" } else { &name }, &name)
+                .to_string(),
+        );
+    }
+
+    ToolResult::success(output).with_metadata(json!({
+        "path": path.display().to_string(),
+        "dry_run": true,
+        "created": created,
+        "additions": additions,
+        "removals": removals
+    }))
+}
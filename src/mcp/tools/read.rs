@@ -7,12 +7,20 @@ use serde::Deserialize;
 use serde_json::json;
 
 use super::base::{Tool, ToolKind};
+use super::encoding::decode;
+use super::image::{IMAGE_HEADER_SIZE, MAX_IMAGE_SIZE, encode_image, image_media_type};
+use super::io_retry::retry_transient;
+use super::pdf::{PDF_FORMATTING_NOTE, extract_pdf_text, has_pdf_extension};
+use super::special_file::reject_special_file;
 use crate::mcp::registry::{ToolContext, ToolResult};
 
-/// Maximum file size in bytes (100MB)
-const MAX_FILE_SIZE: u64 = 100 * 1024 * 1024;
 /// Maximum output size in characters (for UTF-8 safe truncation)
 const MAX_OUTPUT_SIZE: usize = 50_000;
+/// Default maximum lines returned when no limit is given
+const DEFAULT_MAX_READ_LINES: usize = 2000;
+/// Bytes kept per line when reading a range of a file over the size limit;
+/// a line can't show more than the whole output allows
+const MAX_RANGE_LINE_BYTES: usize = MAX_OUTPUT_SIZE;
 
 /// Read tool for reading file contents
 #[derive(Debug, Default)]
@@ -21,7 +29,9 @@ pub struct ReadTool;
 /// Read tool input parameters
 #[derive(Debug, Deserialize)]
 struct ReadInput {
-    /// Path to the file to read
+    /// Path to the file to read (canonical name; `path` and `filePath` are
+    /// accepted as aliases)
+    #[serde(alias = "path", alias = "filePath")]
     file_path: String,
     /// Optional line offset to start reading from (1-indexed)
     #[serde(default)]
@@ -29,6 +39,9 @@ struct ReadInput {
     /// Optional maximum number of lines to read
     #[serde(default)]
     limit: Option<usize>,
+    /// Optional override of the line cap applied without a limit (0 disables it)
+    #[serde(default)]
+    max_lines: Option<usize>,
 }
 
 impl ReadTool {
@@ -65,7 +78,7 @@ impl Tool for ReadTool {
     }
 
     fn description(&self) -> &str {
-        "Read the contents of a file from the filesystem. Supports reading specific line ranges with offset and limit parameters."
+        "Read the contents of a file from the filesystem. Supports reading specific line ranges with offset and limit parameters. Text in other encodings (UTF-16, Latin-1, files with a BOM) is decoded to UTF-8, and Write and Edit keep the encoding. Files over the size limit (10MB unless configured) can only be read in ranges with offset and limit. Text is extracted from PDF files. Images (PNG, JPEG, GIF, WebP up to 5MB) are returned as images."
     }
 
     fn input_schema(&self) -> serde_json::Value {
@@ -79,11 +92,18 @@ impl Tool for ReadTool {
                 },
                 "offset": {
                     "type": "integer",
+                    "minimum": 1,
                     "description": "Line number to start reading from (1-indexed). Defaults to 1."
                 },
                 "limit": {
                     "type": "integer",
-                    "description": "Maximum number of lines to read. Defaults to reading entire file."
+                    "minimum": 1,
+                    "description": "Maximum number of lines to read. Defaults to the line cap (2000 lines unless configured)."
+                },
+                "max_lines": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Override the line cap for this call when no limit is given. 0 reads the entire file."
                 }
             }
         })
@@ -103,6 +123,11 @@ impl Tool for ReadTool {
             Ok(p) => p,
             Err(e) => return ToolResult::error(format!("Invalid input: {}", e)),
         };
+        if params.limit == Some(0) {
+            return ToolResult::error(
+                "Invalid input: limit must be at least 1. Omit it to read up to the line cap.",
+            );
+        }
 
         // Resolve path relative to working directory if not absolute
         let path = if std::path::Path::new(&params.file_path).is_absolute() {
@@ -116,39 +141,82 @@ impl Tool for ReadTool {
             return ToolResult::error(format!("File not found: {}", path.display()));
         }
 
+        // Reading a FIFO would block until something writes to it
+        if let Some(result) = reject_special_file("read", &path) {
+            return result;
+        }
+
         // Check if it's a file
         if !path.is_file() {
             return ToolResult::error(format!("Not a file: {}", path.display()));
         }
 
         // Check file size before reading
-        let metadata = match tokio::fs::metadata(&path).await {
+        let metadata = match retry_transient(|| tokio::fs::metadata(&path)).await {
             Ok(m) => m,
-            Err(e) => {
-                return ToolResult::error(format!("Failed to get file metadata: {}", e));
-            }
+            Err(e) => return ToolResult::io_error("reading", &path, &e, &context.cwd),
         };
 
         let file_size = metadata.len();
-        if file_size > MAX_FILE_SIZE {
-            #[allow(clippy::cast_precision_loss)]
-            let file_size_mb = file_size as f64 / 1024.0 / 1024.0;
-            #[allow(clippy::cast_precision_loss)]
-            let max_file_size_mb = MAX_FILE_SIZE as f64 / 1024.0 / 1024.0;
-            return ToolResult::error(format!(
-                "File too large ({:.1}MB). Maximum supported size is {:.1}MB. Use offset/limit parameters to read portions of the file.",
-                file_size_mb, max_file_size_mb
-            ));
+
+        // Images go back to the model as images rather than garbled text
+        let is_pdf = has_pdf_extension(&path);
+        if !is_pdf {
+            let header = match retry_transient(|| read_header(&path)).await {
+                Ok(header) => header,
+                Err(e) => return ToolResult::io_error("reading", &path, &e, &context.cwd),
+            };
+            if let Some(media_type) = image_media_type(&path, &header) {
+                return read_image(&path, media_type, file_size, context).await;
+            }
+        }
+
+        // Files over the size limit are only read in explicit line ranges
+        if let Some(max_file_size) = context.max_file_size()
+            && file_size > max_file_size
+        {
+            if let Some(limit) = params.limit
+                && !is_pdf
+            {
+                let offset = params.offset.unwrap_or(1).saturating_sub(1);
+                return read_line_range(&path, offset, limit, file_size, context).await;
+            }
+            return ToolResult::file_too_large(
+                format!(
+                    "File too large ({:.1}MB, limit {:.1}MB). Read it in portions with offset and \
+                     limit, e.g. offset=1, limit={}.",
+                    to_mb(file_size),
+                    to_mb(max_file_size),
+                    DEFAULT_MAX_READ_LINES
+                ),
+                &path,
+                file_size,
+                max_file_size,
+            );
         }
 
-        // Read file content with timing
+        // Read file content with timing (PDFs are converted to plain text,
+        // other encodings are decoded to UTF-8)
         let read_start = std::time::Instant::now();
-        let content = match tokio::fs::read_to_string(&path).await {
-            Ok(c) => c,
+        let bytes = match retry_transient(|| tokio::fs::read(&path)).await {
+            Ok(bytes) => bytes,
+            Err(e) => return ToolResult::io_error("reading", &path, &e, &context.cwd),
+        };
+        let read_result = if is_pdf {
+            extract_pdf_text(bytes).await.map(|text| (text, None))
+        } else {
+            decode(&bytes)
+                .map(|(text, encoding)| (text, Some(encoding)))
+                .ok_or_else(|| {
+                    "Failed to read file: binary content or unsupported text encoding".to_string()
+                })
+        };
+        let (content, encoding) = match read_result {
+            Ok(read) => read,
             Err(e) => {
                 let read_duration = read_start.elapsed();
                 return ToolResult::error(format!(
-                    "Failed to read file: {} (elapsed: {}ms)",
+                    "{} (elapsed: {}ms)",
                     e,
                     read_duration.as_millis()
                 ));
@@ -156,6 +224,13 @@ impl Tool for ReadTool {
         };
         let read_duration = read_start.elapsed();
 
+        // Remember the encoding so writing the file back keeps it
+        if let Some(encoding) = encoding
+            && let Some(file_encodings) = context.file_encodings()
+        {
+            file_encodings.record(&path, encoding);
+        }
+
         tracing::debug!(
             file_path = %path.display(),
             file_size_bytes = content.len(),
@@ -168,12 +243,29 @@ impl Tool for ReadTool {
         let total_lines = lines.len();
 
         let offset = params.offset.unwrap_or(1).saturating_sub(1); // Convert to 0-indexed
-        let limit = params.limit.unwrap_or(lines.len());
+        // Without an explicit limit, cap the lines so huge files don't flood the context
+        let line_cap = params
+            .max_lines
+            .or(context.max_read_lines())
+            .unwrap_or(DEFAULT_MAX_READ_LINES);
+        let limit = match params.limit {
+            Some(limit) => limit,
+            None if line_cap > 0 => line_cap,
+            None => lines.len(),
+        };
 
         if offset >= lines.len() {
-            return ToolResult::success("").with_metadata(json!({
+            return ToolResult::success(format!(
+                "(offset {} is past the end of the file, which has {} line{})",
+                offset + 1,
+                total_lines,
+                if total_lines == 1 { "" } else { "s" }
+            ))
+            .with_metadata(json!({
                 "total_lines": total_lines,
-                "returned_lines": 0
+                "returned_lines": 0,
+                "offset": offset + 1,
+                "past_end": true
             }));
         }
 
@@ -182,43 +274,52 @@ impl Tool for ReadTool {
             .skip(offset)
             .take(limit)
             .enumerate()
-            .map(|(i, line)| format!("{:6}→{}", offset + i + 1, line))
+            .map(|(i, line)| format!("{:6}→{}", offset + i + 1, context.limit_line_length(line)))
             .collect();
 
         let returned_lines = selected_lines.len();
+        let capped = params.limit.is_none() && offset + returned_lines < total_lines;
 
-        // Calculate display path:
-        // - If file is under cwd, show relative path with ./ prefix for cwd files
-        // - If file is outside cwd, show absolute path
-        let display_path = if let Ok(rel) = path.strip_prefix(&context.cwd) {
-            let rel_str = rel.to_string_lossy();
-            if rel_str.is_empty() {
-                // File is the cwd directory itself (unlikely)
-                path.display().to_string()
-            } else if rel_str.contains('/') {
-                // File in subdirectory: crates/rcoder/Cargo.toml
-                rel_str.to_string()
-            } else {
-                // File directly in cwd: add ./ prefix
-                format!("./{}", rel_str)
-            }
-        } else {
-            // File outside cwd: show absolute path
-            path.display().to_string()
-        };
+        let display_path = display_path(&path, &context.cwd);
 
         // Add file header with path and line range information
+        let note = if is_pdf {
+            format!("{}\n", PDF_FORMATTING_NOTE)
+        } else {
+            match encoding {
+                Some(encoding) if !encoding.is_plain_utf8() => format!(
+                    "Encoding: {} (shown as UTF-8; Write keeps the file's encoding)\n",
+                    encoding.describe()
+                ),
+                _ => String::new(),
+            }
+        };
         let header = format!(
-            "File: {} (lines {}-{} of {}, total {} lines)\n{}\n",
+            "File: {} (lines {}-{} of {}, total {} lines)\n{}{}\n",
             display_path,
             offset + 1,
             offset + returned_lines.min(total_lines),
             total_lines,
             total_lines,
+            note,
             "-".repeat(60)
         );
 
         let mut result = format!("{}\n{}", header, selected_lines.join("\n"));
+        if capped {
+            result.push_str(&format!(
+                "\n\n[truncated, {} more lines] (showing lines {}-{} of {}. The file is longer \
+                 than the {}-line read cap; use offset and limit to read the rest, e.g. \
+                 offset={}, limit={})",
+                total_lines - offset - returned_lines,
+                offset + 1,
+                offset + returned_lines,
+                total_lines,
+                line_cap,
+                offset + returned_lines + 1,
+                line_cap
+            ));
+        }
 
         // Apply UTF-8 safe truncation if result is too large
         Self::safe_truncate(&mut result, MAX_OUTPUT_SIZE);
@@ -235,17 +336,235 @@ impl Tool for ReadTool {
             "total_lines": total_lines,
             "returned_lines": returned_lines,
             "offset": offset + 1,
+            "capped": capped,
             "path": path.display().to_string(),
             "read_duration_ms": read_duration.as_millis(),
-            "file_size_bytes": content.len()
+            "file_size_bytes": content.len(),
+            "pdf": is_pdf,
+            "encoding": encoding.map(|encoding| encoding.name()),
+            "bom": encoding.map(|encoding| encoding.has_bom())
         }))
     }
 }
 
+/// Path to show in the Read header
+///
+/// - If file is under cwd, show relative path with ./ prefix for cwd files
+/// - If file is outside cwd, show absolute path
+fn display_path(path: &std::path::Path, cwd: &std::path::Path) -> String {
+    if let Ok(rel) = path.strip_prefix(cwd) {
+        let rel_str = rel.to_string_lossy();
+        if rel_str.is_empty() {
+            // File is the cwd directory itself (unlikely)
+            path.display().to_string()
+        } else if rel_str.contains('/') {
+            // File in subdirectory: crates/rcoder/Cargo.toml
+            rel_str.to_string()
+        } else {
+            // File directly in cwd: add ./ prefix
+            format!("./{}", rel_str)
+        }
+    } else {
+        // File outside cwd: show absolute path
+        path.display().to_string()
+    }
+}
+
+/// Read one line into `buf`, keeping at most `max_len` of its bytes
+///
+/// The rest of a longer line is read and dropped, so the whole line never
+/// has to fit in memory. Returns the full length of the line in bytes,
+/// including its newline; 0 means end of file.
+async fn read_capped_line<R>(
+    reader: &mut R,
+    buf: &mut Vec<u8>,
+    max_len: usize,
+) -> std::io::Result<usize>
+where
+    R: tokio::io::AsyncBufRead + Unpin,
+{
+    use tokio::io::AsyncBufReadExt;
+
+    let mut total = 0;
+    loop {
+        let available = reader.fill_buf().await?;
+        if available.is_empty() {
+            return Ok(total);
+        }
+        let (chunk, done) = match available.iter().position(|&b| b == b'\n') {
+            Some(end) => (&available[..=end], true),
+            None => (available, false),
+        };
+        let keep = chunk.len().min(max_len.saturating_sub(buf.len()));
+        buf.extend_from_slice(&chunk[..keep]);
+        let used = chunk.len();
+        reader.consume(used);
+        total += used;
+        if done {
+            return Ok(total);
+        }
+    }
+}
+
+/// Size in bytes as megabytes, for messages
+#[allow(clippy::cast_precision_loss)]
+fn to_mb(bytes: u64) -> f64 {
+    bytes as f64 / 1024.0 / 1024.0
+}
+
+/// Read lines `offset..offset + limit` (0-indexed) of a file over the size limit
+///
+/// The file is scanned line by line and only the requested lines are kept,
+/// so memory use doesn't grow with the file, even when it is one huge line.
+/// Lines are decoded as UTF-8, replacing invalid bytes.
+async fn read_line_range(
+    path: &std::path::Path,
+    offset: usize,
+    limit: usize,
+    file_size: u64,
+    context: &ToolContext,
+) -> ToolResult {
+    let scan = async {
+        let file = tokio::fs::File::open(path).await?;
+        let mut reader = tokio::io::BufReader::new(file);
+        let mut lines = Vec::new();
+        let mut buf = Vec::new();
+        let mut index = 0;
+        loop {
+            buf.clear();
+            // Lines before the range are skipped without keeping any bytes
+            let keep = if index >= offset {
+                MAX_RANGE_LINE_BYTES
+            } else {
+                0
+            };
+            if read_capped_line(&mut reader, &mut buf, keep).await? == 0 {
+                return Ok::<_, std::io::Error>((lines, false));
+            }
+            if index >= offset.saturating_add(limit) {
+                return Ok((lines, true));
+            }
+            if index >= offset {
+                let line = buf.strip_suffix(b"\n").unwrap_or(&buf);
+                let line = line.strip_suffix(b"\r").unwrap_or(line);
+                lines.push(String::from_utf8_lossy(line).into_owned());
+            }
+            index += 1;
+        }
+    };
+    let (lines, more) = match scan.await {
+        Ok(scanned) => scanned,
+        Err(e) => return ToolResult::io_error("reading", path, &e, &context.cwd),
+    };
+
+    if lines.is_empty() {
+        return ToolResult::success(format!(
+            "(offset {} is past the end of the file)",
+            offset + 1
+        ))
+        .with_metadata(json!({
+            "returned_lines": 0,
+            "offset": offset + 1,
+            "past_end": true,
+            "partial": true
+        }));
+    }
+
+    let returned_lines = lines.len();
+    let numbered: Vec<String> = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| format!("{:6}→{}", offset + i + 1, context.limit_line_length(line)))
+        .collect();
+    let mut result = format!(
+        "File: {} (lines {}-{}; the file is {:.1}MB, over the size limit, so only this range was read)\n{}\n\n{}",
+        display_path(path, &context.cwd),
+        offset + 1,
+        offset + returned_lines,
+        to_mb(file_size),
+        "-".repeat(60),
+        numbered.join("\n")
+    );
+    if more {
+        result.push_str(&format!(
+            "\n\n[more lines follow; continue with offset={}]",
+            offset + returned_lines + 1
+        ));
+    }
+    ReadTool::safe_truncate(&mut result, MAX_OUTPUT_SIZE);
+
+    ToolResult::success(result).with_metadata(json!({
+        "returned_lines": returned_lines,
+        "offset": offset + 1,
+        "more": more,
+        "partial": true,
+        "path": path.display().to_string(),
+        "file_size_bytes": file_size
+    }))
+}
+
+/// Read the first bytes of `path`, enough to recognise image formats
+async fn read_header(path: &std::path::Path) -> std::io::Result<Vec<u8>> {
+    use tokio::io::AsyncReadExt;
+
+    let file = tokio::fs::File::open(path).await?;
+    let mut header = Vec::with_capacity(IMAGE_HEADER_SIZE);
+    file.take(IMAGE_HEADER_SIZE as u64)
+        .read_to_end(&mut header)
+        .await?;
+    Ok(header)
+}
+
+/// Read an image file and return it base64 encoded
+async fn read_image(
+    path: &std::path::Path,
+    media_type: &str,
+    file_size: u64,
+    context: &ToolContext,
+) -> ToolResult {
+    if file_size > MAX_IMAGE_SIZE {
+        #[allow(clippy::cast_precision_loss)]
+        let file_size_mb = file_size as f64 / 1024.0 / 1024.0;
+        return ToolResult::error(format!(
+            "Image too large ({:.1}MB). Maximum supported image size is {}MB.",
+            file_size_mb,
+            MAX_IMAGE_SIZE / 1024 / 1024
+        ));
+    }
+
+    let bytes = match retry_transient(|| tokio::fs::read(path)).await {
+        Ok(bytes) => bytes,
+        Err(e) => return ToolResult::io_error("reading", path, &e, &context.cwd),
+    };
+
+    tracing::info!(
+        file_path = %path.display(),
+        media_type = %media_type,
+        file_size_bytes = bytes.len(),
+        "Image read successfully"
+    );
+
+    ToolResult::success(format!(
+        "Image: {} ({}, {} bytes)",
+        path.display(),
+        media_type,
+        bytes.len()
+    ))
+    .with_image(media_type, encode_image(&bytes))
+    .with_metadata(json!({
+        "path": path.display().to_string(),
+        "file_size_bytes": bytes.len(),
+        "image": true,
+        "media_type": media_type
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mcp::tools::FileEncodings;
     use std::io::Write;
+    use std::sync::Arc;
     use tempfile::TempDir;
 
     #[tokio::test]
@@ -271,6 +590,50 @@ mod tests {
         assert!(result.content.contains("Line 3"));
     }
 
+    #[tokio::test]
+    async fn test_read_accepts_path_aliases() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("alias.txt");
+        std::fs::write(&file_path, "aliased content\n").unwrap();
+
+        let tool = ReadTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+
+        for key in ["file_path", "path", "filePath"] {
+            let result = tool
+                .execute(json!({ key: file_path.to_str().unwrap() }), &context)
+                .await;
+            assert!(!result.is_error, "{key}: {}", result.content);
+            assert!(result.content.contains("aliased content"));
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_read_refuses_fifo() {
+        let temp_dir = TempDir::new().unwrap();
+        let fifo_path = temp_dir.path().join("pipe");
+        let status = std::process::Command::new("mkfifo")
+            .arg(&fifo_path)
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let tool = ReadTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+
+        // Opening the FIFO would block forever, so bound the wait
+        let result = tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            tool.execute(json!({"file_path": fifo_path.to_str().unwrap()}), &context),
+        )
+        .await
+        .expect("Read should refuse a FIFO instead of blocking");
+
+        assert!(result.is_error);
+        assert!(result.content.contains("named pipe"));
+    }
+
     #[tokio::test]
     async fn test_read_with_offset_and_limit() {
         let temp_dir = TempDir::new().unwrap();
@@ -301,6 +664,228 @@ mod tests {
         assert!(!result.content.contains("Line 5"));
     }
 
+    #[tokio::test]
+    async fn test_read_caps_lines_without_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("big.log");
+        let mut file = std::fs::File::create(&file_path).unwrap();
+        for i in 1..=50 {
+            writeln!(file, "Line {}", i).unwrap();
+        }
+
+        let tool = ReadTool::new();
+        let context = ToolContext::new("test", temp_dir.path()).with_max_read_lines(20);
+        let file_path = file_path.to_str().unwrap();
+
+        let result = tool
+            .execute(json!({"file_path": file_path}), &context)
+            .await;
+        assert!(!result.is_error);
+        assert!(result.content.contains("    20→Line 20"));
+        assert!(!result.content.contains("Line 21"));
+        assert!(result.content.contains("[truncated, 30 more lines]"));
+        assert!(result.content.contains("showing lines 1-20 of 50"));
+        assert!(result.content.contains("offset=21, limit=20"));
+        let metadata = result.metadata.unwrap();
+        assert_eq!(metadata["returned_lines"], 20);
+        assert_eq!(metadata["capped"], true);
+
+        // The cap can be overridden per call
+        let result = tool
+            .execute(json!({"file_path": file_path, "max_lines": 0}), &context)
+            .await;
+        assert!(result.content.contains("    50→Line 50"));
+        assert_eq!(result.metadata.unwrap()["capped"], false);
+
+        // An explicit limit is not capped
+        let result = tool
+            .execute(
+                json!({"file_path": file_path, "offset": 11, "limit": 40}),
+                &context,
+            )
+            .await;
+        assert!(result.content.contains("    50→Line 50"));
+        assert!(!result.content.contains("showing lines"));
+    }
+
+    #[tokio::test]
+    async fn test_read_offset_past_end_and_zero_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("short.txt");
+        std::fs::write(&file_path, "one\ntwo\n").unwrap();
+
+        let tool = ReadTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+        let file_path = file_path.to_str().unwrap();
+
+        let result = tool
+            .execute(json!({"file_path": file_path, "offset": 5}), &context)
+            .await;
+        assert!(!result.is_error);
+        assert_eq!(
+            result.content,
+            "(offset 5 is past the end of the file, which has 2 lines)"
+        );
+        let metadata = result.metadata.unwrap();
+        assert_eq!(metadata["returned_lines"], 0);
+        assert_eq!(metadata["past_end"], true);
+
+        let result = tool
+            .execute(json!({"file_path": file_path, "limit": 0}), &context)
+            .await;
+        assert!(result.is_error);
+        assert!(result.content.contains("limit must be at least 1"));
+    }
+
+    #[tokio::test]
+    async fn test_read_truncates_long_lines() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("bundle.min.js");
+        std::fs::write(
+            &file_path,
+            format!("// header\n{}\n// footer\n", "a".repeat(5000)),
+        )
+        .unwrap();
+
+        let tool = ReadTool::new();
+        let context = ToolContext::new("test", temp_dir.path()).with_max_line_length(100);
+
+        let result = tool
+            .execute(json!({"file_path": file_path.to_str().unwrap()}), &context)
+            .await;
+
+        assert!(!result.is_error);
+        assert!(result.content.contains("     1→// header"));
+        assert!(result.content.contains(&format!(
+            "     2→{}…[4900 chars truncated]",
+            "a".repeat(100)
+        )));
+        assert!(result.content.contains("     3→// footer"));
+        assert_eq!(result.metadata.unwrap()["returned_lines"], 3);
+    }
+
+    #[tokio::test]
+    async fn test_read_decodes_other_encodings() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("legacy.ini");
+        // "[Größe]\r\n" in UTF-16LE with a BOM
+        let mut bytes = vec![0xFF, 0xFE];
+        bytes.extend("[Größe]\r\n".encode_utf16().flat_map(u16::to_le_bytes));
+        std::fs::write(&file_path, bytes).unwrap();
+
+        let tool = ReadTool::new();
+        let encodings = Arc::new(FileEncodings::new());
+        let context =
+            ToolContext::new("test", temp_dir.path()).with_file_encodings(encodings.clone());
+
+        let result = tool
+            .execute(json!({"file_path": file_path.to_str().unwrap()}), &context)
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("Encoding: UTF-16LE with BOM"));
+        assert!(result.content.contains("     1→[Größe]"));
+        let metadata = result.metadata.unwrap();
+        assert_eq!(metadata["encoding"], "UTF-16LE");
+        assert_eq!(metadata["bom"], true);
+        assert_eq!(
+            encodings.get(&file_path).unwrap().describe(),
+            "UTF-16LE with BOM"
+        );
+
+        // Latin-1 is read as Windows-1252
+        std::fs::write(&file_path, b"caf\xE9\n").unwrap();
+        let result = tool
+            .execute(json!({"file_path": file_path.to_str().unwrap()}), &context)
+            .await;
+        assert!(result.content.contains("     1→café"));
+        assert_eq!(result.metadata.unwrap()["encoding"], "windows-1252");
+
+        // Binary files are still refused
+        std::fs::write(&file_path, b"\x00\x01\x02\xFF\xFE\x00\x9C").unwrap();
+        let result = tool
+            .execute(json!({"file_path": file_path.to_str().unwrap()}), &context)
+            .await;
+        assert!(result.is_error);
+        assert!(result.content.contains("binary content"));
+    }
+
+    #[tokio::test]
+    async fn test_read_refuses_files_over_size_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("big.log");
+        let lines: Vec<String> = (1..=100).map(|i| format!("entry {i}")).collect();
+        let content = lines.join("\n") + "\n";
+        std::fs::write(&file_path, &content).unwrap();
+        let file_path = file_path.to_str().unwrap();
+
+        let tool = ReadTool::new();
+        let context = ToolContext::new("test", temp_dir.path()).with_max_file_size(100);
+
+        let result = tool
+            .execute(json!({"file_path": file_path}), &context)
+            .await;
+        assert!(result.is_error);
+        assert!(result.content.contains("offset and limit"));
+        let metadata = result.metadata.unwrap();
+        assert_eq!(metadata["error_kind"], "file_too_large");
+        assert_eq!(metadata["size"], content.len());
+        assert_eq!(metadata["max_file_size"], 100);
+
+        // An explicit range is still read
+        let result = tool
+            .execute(
+                json!({"file_path": file_path, "offset": 50, "limit": 2}),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("    50→entry 50\n    51→entry 51"));
+        assert!(result.content.contains("continue with offset=52"));
+        assert!(!result.content.contains("entry 52"));
+        assert_eq!(result.metadata.unwrap()["more"], true);
+
+        // 0 disables the limit
+        let context = ToolContext::new("test", temp_dir.path()).with_max_file_size(0);
+        let result = tool
+            .execute(json!({"file_path": file_path}), &context)
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.metadata.unwrap()["total_lines"], 100);
+    }
+
+    #[tokio::test]
+    async fn test_read_range_with_huge_line() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("minified.js");
+        let huge = "x".repeat(MAX_RANGE_LINE_BYTES * 4);
+        std::fs::write(&file_path, format!("first\n{huge}\n{huge}\nlast\n")).unwrap();
+        let file_path = file_path.to_str().unwrap();
+
+        let tool = ReadTool::new();
+        let context = ToolContext::new("test", temp_dir.path()).with_max_file_size(100);
+
+        // A huge line still counts as one line, before and inside the range
+        let result = tool
+            .execute(
+                json!({"file_path": file_path, "offset": 4, "limit": 1}),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("     4→last"));
+
+        let result = tool
+            .execute(
+                json!({"file_path": file_path, "offset": 2, "limit": 1}),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("     2→xxx"));
+        assert!(result.content.len() < MAX_RANGE_LINE_BYTES * 2);
+        assert_eq!(result.metadata.unwrap()["more"], true);
+    }
+
     #[tokio::test]
     async fn test_read_file_not_found() {
         let temp_dir = TempDir::new().unwrap();
@@ -315,6 +900,60 @@ mod tests {
         assert!(result.content.contains("not found"));
     }
 
+    #[tokio::test]
+    async fn test_read_pdf_extracts_text() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("manual.pdf");
+        std::fs::write(
+            &file_path,
+            crate::mcp::tools::pdf::tests::sample_pdf("Install guide"),
+        )
+        .unwrap();
+
+        let tool = ReadTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+
+        let result = tool
+            .execute(json!({"file_path": file_path.to_str().unwrap()}), &context)
+            .await;
+
+        assert!(!result.is_error, "unexpected error: {}", result.content);
+        assert!(result.content.contains("Install guide"));
+        assert!(result.content.contains(PDF_FORMATTING_NOTE));
+        assert_eq!(result.metadata.unwrap()["pdf"], true);
+    }
+
+    #[tokio::test]
+    async fn test_read_image_returns_base64() {
+        let temp_dir = TempDir::new().unwrap();
+        // Recognised by its magic bytes despite the extension
+        let file_path = temp_dir.path().join("screenshot.bin");
+        std::fs::write(&file_path, b"\x89PNG\r\n\x1a\nrest").unwrap();
+
+        let tool = ReadTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+
+        let result = tool
+            .execute(json!({"file_path": file_path.to_str().unwrap()}), &context)
+            .await;
+
+        assert!(!result.is_error, "unexpected error: {}", result.content);
+        let image = result.image.unwrap();
+        assert_eq!(image.media_type, "image/png");
+        assert_eq!(image.data, "iVBORw0KGgpyZXN0");
+
+        // Oversized images are rejected instead of flooding the context
+        let large_path = temp_dir.path().join("large.png");
+        let file = std::fs::File::create(&large_path).unwrap();
+        file.set_len(MAX_IMAGE_SIZE + 1).unwrap();
+        let result = tool
+            .execute(json!({"file_path": large_path.to_str().unwrap()}), &context)
+            .await;
+        assert!(result.is_error);
+        assert!(result.content.starts_with("Image too large"));
+        assert!(result.image.is_none());
+    }
+
     #[test]
     fn test_read_tool_properties() {
         let tool = ReadTool::new();
@@ -0,0 +1,270 @@
+//! RecentFiles tool for finding recently modified files
+//!
+//! Lists the most recently modified files under a directory (respecting
+//! `.gitignore`), which is much cheaper than a broad Glob followed by a sort.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Local};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use super::base::{Tool, ToolKind};
+use super::walk::project_walker;
+use crate::mcp::registry::{ToolContext, ToolResult};
+
+/// Default number of files to return
+const DEFAULT_LIMIT: usize = 20;
+
+/// Maximum number of files to return
+const MAX_LIMIT: usize = 500;
+
+/// Maximum number of files visited
+const MAX_FILES: usize = 100_000;
+
+/// Maximum time spent walking the tree
+const MAX_WALK_DURATION: Duration = Duration::from_secs(10);
+
+/// RecentFiles tool for recently modified files
+#[derive(Debug, Default)]
+pub struct RecentFilesTool;
+
+/// Input parameters for RecentFiles
+#[derive(Debug, Deserialize)]
+struct RecentFilesInput {
+    /// Directory to search (defaults to cwd)
+    #[serde(default)]
+    path: Option<String>,
+    /// Number of files to return
+    #[serde(default)]
+    limit: Option<usize>,
+    /// Only include files with these extensions
+    #[serde(default)]
+    extensions: Option<Vec<String>>,
+}
+
+impl RecentFilesTool {
+    /// Create a new RecentFiles tool instance
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Check whether a file has one of the given extensions (case-insensitive)
+    fn has_extension(path: &Path, extensions: &[String]) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| extensions.iter().any(|x| x.eq_ignore_ascii_case(e)))
+    }
+
+    /// Walk `root` and return the files sorted by modification time (newest first)
+    ///
+    /// The second value reports whether the walk stopped early.
+    fn collect(root: &Path, extensions: &[String]) -> (Vec<(PathBuf, SystemTime)>, bool) {
+        let start = Instant::now();
+        let mut files = Vec::new();
+        let mut visited = 0;
+        let mut truncated = false;
+
+        for entry in project_walker(root).build().filter_map(Result::ok) {
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+            visited += 1;
+            if visited > MAX_FILES || start.elapsed() >= MAX_WALK_DURATION {
+                truncated = true;
+                break;
+            }
+            if !extensions.is_empty() && !Self::has_extension(entry.path(), extensions) {
+                continue;
+            }
+            let Some(modified) = entry.metadata().ok().and_then(|m| m.modified().ok()) else {
+                continue;
+            };
+            files.push((entry.path().to_path_buf(), modified));
+        }
+
+        files.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        (files, truncated)
+    }
+}
+
+#[async_trait]
+impl Tool for RecentFilesTool {
+    fn name(&self) -> &str {
+        "RecentFiles"
+    }
+
+    fn description(&self) -> &str {
+        "Lists the most recently modified files with their modification times, newest first. \
+         Respects .gitignore. Useful when resuming work to see what changed recently."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "The directory to search. Defaults to the current working directory."
+                },
+                "limit": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "description": "Number of files to return (default: 20, max: 500)"
+                },
+                "extensions": {
+                    "type": "array",
+                    "items": {"type": "string"},
+                    "description": "Only include files with these extensions (e.g., ['rs', 'toml'])"
+                }
+            }
+        })
+    }
+
+    fn kind(&self) -> ToolKind {
+        ToolKind::Search
+    }
+
+    fn requires_permission(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, input: Value, context: &ToolContext) -> ToolResult {
+        let params: RecentFilesInput = match serde_json::from_value(input) {
+            Ok(p) => p,
+            Err(e) => return ToolResult::error(format!("Invalid input: {}", e)),
+        };
+
+        let root = match &params.path {
+            Some(p) => {
+                let path = Path::new(p);
+                if path.is_absolute() {
+                    path.to_path_buf()
+                } else {
+                    context.cwd.join(path)
+                }
+            }
+            None => context.cwd.clone(),
+        };
+
+        if !root.is_dir() {
+            return ToolResult::error(format!("Directory not found: {}", root.display()));
+        }
+
+        let limit = params.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+        let extensions: Vec<String> = params
+            .extensions
+            .unwrap_or_default()
+            .iter()
+            .map(|e| e.trim_start_matches('.').to_string())
+            .collect();
+
+        let (files, truncated) = Self::collect(&root, &extensions);
+        let total_found = files.len();
+
+        let entries: Vec<(String, DateTime<Local>)> = files
+            .into_iter()
+            .take(limit)
+            .map(|(path, modified)| {
+                let relative = path.strip_prefix(&root).unwrap_or(&path);
+                (relative.to_string_lossy().to_string(), modified.into())
+            })
+            .collect();
+
+        let output = if entries.is_empty() {
+            format!("No files found in {}", root.display())
+        } else {
+            let mut output = entries
+                .iter()
+                .map(|(path, modified)| {
+                    format!("{}  {}", modified.format("%Y-%m-%d %H:%M:%S"), path)
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            if total_found > entries.len() {
+                output.push_str(&format!(
+                    "\n\n... (showing {} of {} files, use limit to see more)",
+                    entries.len(),
+                    total_found
+                ));
+            }
+            output
+        };
+
+        ToolResult::success(output).with_metadata(json!({
+            "path": root.display().to_string(),
+            "files": entries
+                .iter()
+                .map(|(path, modified)| json!({"path": path, "modified": modified.to_rfc3339()}))
+                .collect::<Vec<_>>(),
+            "total_found": total_found,
+            "truncated": truncated
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs::{self, File};
+    use tempfile::TempDir;
+
+    fn set_modified(path: &Path, time: SystemTime) {
+        File::options()
+            .write(true)
+            .open(path)
+            .unwrap()
+            .set_modified(time)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_recent_files_tool_properties() {
+        let tool = RecentFilesTool::new();
+        assert_eq!(tool.name(), "RecentFiles");
+        assert!(!tool.requires_permission());
+        assert!(tool.input_schema()["properties"]["extensions"].is_object());
+    }
+
+    #[tokio::test]
+    async fn test_touched_file_comes_first() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        let hour_ago = SystemTime::now() - Duration::from_secs(3600);
+        for name in ["src/main.rs", "src/lib.rs", "notes.txt"] {
+            fs::write(root.join(name), name).unwrap();
+            set_modified(&root.join(name), hour_ago);
+        }
+        set_modified(&root.join("notes.txt"), hour_ago - Duration::from_secs(60));
+
+        let tool = RecentFilesTool::new();
+        let context = ToolContext::new("test", root);
+
+        // Touch lib.rs
+        set_modified(&root.join("src/lib.rs"), SystemTime::now());
+        let result = tool.execute(json!({}), &context).await;
+        assert!(!result.is_error, "{}", result.content);
+        let files = result.metadata.unwrap()["files"].clone();
+        assert_eq!(files.as_array().unwrap().len(), 3);
+        assert_eq!(files[0]["path"], "src/lib.rs");
+        assert_eq!(files[2]["path"], "notes.txt");
+        assert!(
+            result
+                .content
+                .lines()
+                .next()
+                .unwrap()
+                .ends_with("src/lib.rs")
+        );
+
+        // Filter by extension, with or without a leading dot
+        let result = tool
+            .execute(json!({"extensions": [".txt"], "limit": 5}), &context)
+            .await;
+        let files = result.metadata.unwrap()["files"].clone();
+        assert_eq!(files.as_array().unwrap().len(), 1);
+        assert_eq!(files[0]["path"], "notes.txt");
+    }
+}
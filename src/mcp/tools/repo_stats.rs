@@ -0,0 +1,343 @@
+//! RepoStats tool for scoping a repository
+//!
+//! Walks the working tree (respecting `.gitignore`) and summarizes it:
+//! file count, lines of code by language and the largest files.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use super::base::{Tool, ToolKind};
+use super::walk::project_walker;
+use crate::mcp::registry::{ToolContext, ToolResult};
+
+/// Maximum number of files visited
+const MAX_FILES: usize = 50_000;
+
+/// Maximum time spent walking the tree
+const MAX_WALK_DURATION: Duration = Duration::from_secs(10);
+
+/// Files larger than this are counted but not read for line counts
+const MAX_COUNTED_FILE_SIZE: u64 = 1024 * 1024;
+
+/// Default number of largest files to list
+const DEFAULT_TOP_FILES: usize = 10;
+
+/// Languages by file extension
+const LANGUAGES: &[(&str, &[&str])] = &[
+    ("Rust", &["rs"]),
+    ("TypeScript", &["ts", "tsx", "mts", "cts"]),
+    ("JavaScript", &["js", "jsx", "mjs", "cjs"]),
+    ("Python", &["py", "pyi"]),
+    ("Go", &["go"]),
+    ("Java", &["java"]),
+    ("Kotlin", &["kt", "kts"]),
+    ("C", &["c", "h"]),
+    ("C++", &["cc", "cpp", "cxx", "hh", "hpp", "hxx"]),
+    ("C#", &["cs"]),
+    ("Swift", &["swift"]),
+    ("Ruby", &["rb"]),
+    ("PHP", &["php"]),
+    ("Shell", &["sh", "bash", "zsh"]),
+    ("SQL", &["sql"]),
+    ("HTML", &["html", "htm"]),
+    ("CSS", &["css", "scss", "sass", "less"]),
+    ("Markdown", &["md", "markdown"]),
+    ("JSON", &["json"]),
+    ("TOML", &["toml"]),
+    ("YAML", &["yaml", "yml"]),
+];
+
+/// RepoStats tool for repository statistics
+#[derive(Debug, Default)]
+pub struct RepoStatsTool;
+
+/// Input parameters for RepoStats
+#[derive(Debug, Deserialize)]
+struct RepoStatsInput {
+    /// Directory to summarize (defaults to cwd)
+    #[serde(default)]
+    path: Option<String>,
+    /// Number of largest files to list
+    #[serde(default)]
+    top_files: Option<usize>,
+}
+
+/// Per-language totals
+#[derive(Debug, Default, Clone, Copy)]
+struct LanguageStats {
+    files: usize,
+    lines: usize,
+}
+
+/// Aggregate statistics of a directory tree
+#[derive(Debug, Default)]
+struct RepoStats {
+    files: usize,
+    total_bytes: u64,
+    lines: usize,
+    languages: HashMap<&'static str, LanguageStats>,
+    largest: Vec<(u64, String)>,
+    truncated: bool,
+}
+
+impl RepoStatsTool {
+    /// Create a new RepoStats tool instance
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Look up the language of a file from its extension
+    fn language(path: &Path) -> Option<&'static str> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        LANGUAGES
+            .iter()
+            .find(|(_, extensions)| extensions.contains(&extension.as_str()))
+            .map(|&(language, _)| language)
+    }
+
+    /// Count non-blank lines, or `None` for binary or unreadable files
+    fn count_lines(path: &Path) -> Option<usize> {
+        let bytes = std::fs::read(path).ok()?;
+        if bytes.iter().take(8192).any(|&b| b == 0) {
+            return None;
+        }
+        Some(
+            bytes
+                .split(|&b| b == b'\n')
+                .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+                .count(),
+        )
+    }
+
+    /// Walk `root` and collect statistics
+    fn collect(root: &Path, top_files: usize) -> RepoStats {
+        let start = Instant::now();
+        let mut stats = RepoStats::default();
+
+        for entry in project_walker(root).build().filter_map(Result::ok) {
+            if !entry.file_type().is_some_and(|t| t.is_file()) {
+                continue;
+            }
+            if stats.files >= MAX_FILES || start.elapsed() >= MAX_WALK_DURATION {
+                stats.truncated = true;
+                break;
+            }
+
+            let path = entry.path();
+            let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+            stats.files += 1;
+            stats.total_bytes += size;
+
+            let relative = path.strip_prefix(root).unwrap_or(path);
+            stats
+                .largest
+                .push((size, relative.to_string_lossy().to_string()));
+
+            let Some(language) = Self::language(path) else {
+                continue;
+            };
+            let lines = if size <= MAX_COUNTED_FILE_SIZE {
+                Self::count_lines(path).unwrap_or(0)
+            } else {
+                0
+            };
+            let language_stats = stats.languages.entry(language).or_default();
+            language_stats.files += 1;
+            language_stats.lines += lines;
+            stats.lines += lines;
+        }
+
+        stats
+            .largest
+            .sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        stats.largest.truncate(top_files);
+        stats
+    }
+
+    /// Format a byte count for display
+    #[allow(clippy::cast_precision_loss)]
+    fn format_size(bytes: u64) -> String {
+        const KB: f64 = 1024.0;
+        let bytes_f = bytes as f64;
+        if bytes_f >= KB * KB {
+            format!("{:.1} MB", bytes_f / (KB * KB))
+        } else if bytes_f >= KB {
+            format!("{:.1} KB", bytes_f / KB)
+        } else {
+            format!("{} B", bytes)
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for RepoStatsTool {
+    fn name(&self) -> &str {
+        "RepoStats"
+    }
+
+    fn description(&self) -> &str {
+        "Summarizes a repository before starting work: file count, total size, lines of code \
+         by language and the largest files. Respects .gitignore and skips hidden files."
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "path": {
+                    "type": "string",
+                    "description": "The directory to summarize. Defaults to the current working directory."
+                },
+                "top_files": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "description": "Number of largest files to list (default: 10)"
+                }
+            }
+        })
+    }
+
+    fn kind(&self) -> ToolKind {
+        ToolKind::Search
+    }
+
+    fn requires_permission(&self) -> bool {
+        false
+    }
+
+    async fn execute(&self, input: Value, context: &ToolContext) -> ToolResult {
+        let params: RepoStatsInput = match serde_json::from_value(input) {
+            Ok(p) => p,
+            Err(e) => return ToolResult::error(format!("Invalid input: {}", e)),
+        };
+
+        let root = match &params.path {
+            Some(p) => {
+                let path = Path::new(p);
+                if path.is_absolute() {
+                    path.to_path_buf()
+                } else {
+                    context.cwd.join(path)
+                }
+            }
+            None => context.cwd.clone(),
+        };
+
+        if !root.is_dir() {
+            return ToolResult::error(format!("Directory not found: {}", root.display()));
+        }
+
+        let stats = Self::collect(&root, params.top_files.unwrap_or(DEFAULT_TOP_FILES));
+
+        let mut languages: Vec<(&str, LanguageStats)> =
+            stats.languages.iter().map(|(k, v)| (*k, *v)).collect();
+        languages.sort_by(|a, b| b.1.lines.cmp(&a.1.lines).then_with(|| a.0.cmp(b.0)));
+
+        let mut output = format!(
+            "Repository stats for {}\nFiles: {} ({}), lines of code: {}\n",
+            root.display(),
+            stats.files,
+            Self::format_size(stats.total_bytes),
+            stats.lines
+        );
+        if !languages.is_empty() {
+            output.push_str("\nLanguages:\n");
+            for (language, language_stats) in &languages {
+                output.push_str(&format!(
+                    "  {:<12} {:>6} files {:>9} lines\n",
+                    language, language_stats.files, language_stats.lines
+                ));
+            }
+        }
+        if !stats.largest.is_empty() {
+            output.push_str("\nLargest files:\n");
+            for (size, path) in &stats.largest {
+                output.push_str(&format!("  {:>10}  {}\n", Self::format_size(*size), path));
+            }
+        }
+        if stats.truncated {
+            output.push_str(&format!(
+                "\n(stopped after {} files; statistics are partial)\n",
+                stats.files
+            ));
+        }
+
+        ToolResult::success(output).with_metadata(json!({
+            "path": root.display().to_string(),
+            "files": stats.files,
+            "total_bytes": stats.total_bytes,
+            "lines": stats.lines,
+            "languages": languages
+                .iter()
+                .map(|(language, s)| json!({"language": language, "files": s.files, "lines": s.lines}))
+                .collect::<Vec<_>>(),
+            "largest_files": stats
+                .largest
+                .iter()
+                .map(|(size, path)| json!({"path": path, "bytes": size}))
+                .collect::<Vec<_>>(),
+            "truncated": stats.truncated
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_repo_stats_tool_properties() {
+        let tool = RepoStatsTool::new();
+        assert_eq!(tool.name(), "RepoStats");
+        assert!(!tool.requires_permission());
+        assert!(tool.input_schema()["properties"]["top_files"].is_object());
+    }
+
+    #[tokio::test]
+    async fn test_repo_stats_small_repo() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("target/debug")).unwrap();
+        fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {\n\n    run();\n}\n").unwrap();
+        fs::write(root.join("src/lib.rs"), "pub fn run() {}\n").unwrap();
+        fs::write(root.join("app.py"), "print('hi')\n").unwrap();
+        fs::write(root.join("README.md"), "# Demo\n\nA demo repo.\n").unwrap();
+        fs::write(
+            root.join("target/debug/build.rs"),
+            "fn ignored() {}\n".repeat(100),
+        )
+        .unwrap();
+
+        let tool = RepoStatsTool::new();
+        let context = ToolContext::new("test", root);
+        let result = tool.execute(json!({"top_files": 2}), &context).await;
+
+        assert!(!result.is_error, "{}", result.content);
+        let metadata = result.metadata.unwrap();
+        // The ignored build output and the hidden .gitignore are skipped
+        assert_eq!(metadata["files"], 4);
+        assert_eq!(metadata["lines"], 7);
+        assert_eq!(metadata["truncated"], false);
+
+        let languages = metadata["languages"].as_array().unwrap();
+        assert_eq!(
+            languages[0],
+            json!({"language": "Rust", "files": 2, "lines": 4})
+        );
+        assert!(languages.contains(&json!({"language": "Python", "files": 1, "lines": 1})));
+        assert!(languages.contains(&json!({"language": "Markdown", "files": 1, "lines": 2})));
+
+        let largest = metadata["largest_files"].as_array().unwrap();
+        assert_eq!(largest.len(), 2);
+        assert_eq!(largest[0]["path"], "src/main.rs");
+        assert!(result.content.contains("Rust"));
+    }
+}
@@ -0,0 +1,81 @@
+//! Detection of special files
+//!
+//! Opening a named pipe blocks until another process writes to it, and
+//! devices and sockets have no content a file tool could sensibly read or
+//! replace, so Read, Write and Edit refuse them with a clear error.
+
+use std::path::Path;
+
+use serde_json::json;
+
+use crate::mcp::registry::ToolResult;
+
+/// Describe the special file type of `metadata`, if it is one
+#[cfg(unix)]
+fn special_file_type(metadata: &std::fs::Metadata) -> Option<&'static str> {
+    use std::os::unix::fs::FileTypeExt;
+
+    let file_type = metadata.file_type();
+    if file_type.is_fifo() {
+        Some("named pipe (FIFO)")
+    } else if file_type.is_char_device() {
+        Some("character device")
+    } else if file_type.is_block_device() {
+        Some("block device")
+    } else if file_type.is_socket() {
+        Some("socket")
+    } else {
+        None
+    }
+}
+
+#[cfg(not(unix))]
+fn special_file_type(_metadata: &std::fs::Metadata) -> Option<&'static str> {
+    None
+}
+
+/// Refuse to `action` a FIFO, device or socket at `path`
+///
+/// Symlinks are followed. Returns None for regular files, directories and
+/// paths that don't exist, leaving those to the tool's own checks.
+pub fn reject_special_file(action: &str, path: &Path) -> Option<ToolResult> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let file_type = special_file_type(&metadata)?;
+
+    Some(
+        ToolResult::error(format!(
+            "Refusing to {action} {}: it is a {file_type}, not a regular file",
+            path.display()
+        ))
+        .with_metadata(json!({
+            "error_kind": "special_file",
+            "file_type": file_type,
+            "path": path.display().to_string(),
+        })),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_regular_files_are_not_special() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("file.txt");
+        std::fs::write(&path, "content").unwrap();
+
+        assert!(reject_special_file("read", &path).is_none());
+        assert!(reject_special_file("read", temp_dir.path()).is_none());
+        assert!(reject_special_file("read", &temp_dir.path().join("missing")).is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_device_is_special() {
+        let result = reject_special_file("read", Path::new("/dev/null")).unwrap();
+        assert!(result.is_error);
+        assert!(result.content.contains("character device"));
+    }
+}
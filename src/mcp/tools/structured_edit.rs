@@ -0,0 +1,561 @@
+//! JsonEdit, YamlEdit and TomlEdit tool implementation
+//!
+//! Sets a single value in a config file, addressed by a key path like
+//! `compilerOptions.paths` or `jobs.test.steps[0].run`, instead of matching
+//! strings. Only the addressed value is rewritten: the rest of the file,
+//! including its formatting and comments, is left as it is. Missing keys
+//! along the path are created.
+//!
+//! JSON (including JSON with comments) and YAML are edited in place by
+//! [`super::structured_json`] and [`super::structured_yaml`]; TOML goes
+//! through the format-preserving `toml_edit` parser.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{Value, json};
+
+use super::base::{Tool, ToolKind};
+use super::locked_file::LockedFile;
+use super::{structured_json, structured_yaml};
+use crate::mcp::registry::{ToolContext, ToolResult};
+
+/// File format edited by a [`StructuredEditTool`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructuredFormat {
+    /// JSON, optionally with comments
+    Json,
+    /// YAML in block style
+    Yaml,
+    /// TOML
+    Toml,
+}
+
+/// One step of a key path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Segment {
+    /// Object key (or, on an array, an index written as `items.0`)
+    Key(String),
+    /// Array index written as `items[0]`
+    Index(usize),
+}
+
+/// Result of setting a value in a document
+#[derive(Debug)]
+pub(crate) struct Edited {
+    /// The whole updated document
+    pub content: String,
+    /// The replaced value as it was written, or None if it was added
+    pub previous: Option<String>,
+}
+
+/// Tool setting a value at a key path in a JSON, YAML or TOML file
+#[derive(Debug)]
+pub struct StructuredEditTool {
+    format: StructuredFormat,
+}
+
+/// Structured edit tool input parameters
+#[derive(Debug, Deserialize)]
+struct StructuredEditInput {
+    /// Path to the file to edit (`path` is taken by the key path, so only
+    /// `filePath` is accepted as an alias)
+    #[serde(alias = "filePath")]
+    file_path: String,
+    /// Key path of the value to set
+    #[serde(alias = "key")]
+    path: String,
+    /// New value
+    value: Value,
+}
+
+impl StructuredEditTool {
+    /// Create a tool for the given format
+    pub fn new(format: StructuredFormat) -> Self {
+        Self { format }
+    }
+
+    /// Set `value` at `path` in `content`
+    fn set_value(&self, content: &str, path: &[Segment], value: &Value) -> Result<Edited, String> {
+        match self.format {
+            StructuredFormat::Json => structured_json::set_value(content, path, value),
+            StructuredFormat::Yaml => structured_yaml::set_value(content, path, value),
+            StructuredFormat::Toml => set_toml_value(content, path, value),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for StructuredEditTool {
+    fn name(&self) -> &str {
+        match self.format {
+            StructuredFormat::Json => "JsonEdit",
+            StructuredFormat::Yaml => "YamlEdit",
+            StructuredFormat::Toml => "TomlEdit",
+        }
+    }
+
+    fn description(&self) -> &str {
+        match self.format {
+            StructuredFormat::Json => {
+                "Set a value in a JSON file (comments allowed) by key path, e.g. \
+                 compilerOptions.strict or scripts[\"build:prod\"]. Only that value changes; \
+                 formatting and comments elsewhere are preserved and missing keys are created. \
+                 Prefer this over Edit for JSON config files."
+            }
+            StructuredFormat::Yaml => {
+                "Set a value in a YAML file by key path, e.g. jobs.test.steps[0].run. Only \
+                 that value changes; formatting and comments elsewhere are preserved and missing \
+                 keys are created. Prefer this over Edit for YAML config files."
+            }
+            StructuredFormat::Toml => {
+                "Set a value in a TOML file by key path, e.g. dependencies.serde.version. Only \
+                 that value changes; formatting and comments elsewhere are preserved and missing \
+                 keys are created. Prefer this over Edit for TOML config files."
+            }
+        }
+    }
+
+    fn input_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "required": ["file_path", "path", "value"],
+            "properties": {
+                "file_path": {
+                    "type": "string",
+                    "description": "The absolute path to the file to edit"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Dotted key path of the value, with [N] for array elements and [\"key\"] for keys containing dots, e.g. a.b[0].c. A leading $. is allowed."
+                },
+                "value": {
+                    "description": "The new value, as JSON"
+                }
+            }
+        })
+    }
+
+    fn kind(&self) -> ToolKind {
+        ToolKind::Edit
+    }
+
+    fn requires_permission(&self) -> bool {
+        true // Editing requires permission
+    }
+
+    async fn execute(&self, input: Value, context: &ToolContext) -> ToolResult {
+        // Parse input
+        let params: StructuredEditInput = match serde_json::from_value(input) {
+            Ok(p) => p,
+            Err(e) => return ToolResult::error(format!("Invalid input: {}", e)),
+        };
+        let key_path = match parse_key_path(&params.path) {
+            Ok(key_path) => key_path,
+            Err(e) => return ToolResult::error(format!("Invalid path '{}': {}", params.path, e)),
+        };
+
+        // Resolve path relative to working directory if not absolute
+        let path = if std::path::Path::new(&params.file_path).is_absolute() {
+            std::path::PathBuf::from(&params.file_path)
+        } else {
+            context.cwd.join(&params.file_path)
+        };
+
+        // Lock and read the file
+        let file = match LockedFile::open(&path, context).await {
+            Ok(file) => file,
+            Err(result) => return result,
+        };
+        let content = file.content();
+
+        let edited = match self.set_value(content, &key_path, &params.value) {
+            Ok(edited) => edited,
+            Err(message) => {
+                return ToolResult::error(format!(
+                    "Cannot set '{}' in {}: {}",
+                    params.path,
+                    path.display(),
+                    message
+                ));
+            }
+        };
+
+        if edited.content == content {
+            return ToolResult::success(format!(
+                "'{}' in {} already has that value",
+                params.path,
+                path.display()
+            ))
+            .with_metadata(json!({
+                "path": path.display().to_string(),
+                "key_path": params.path,
+                "changed": false
+            }));
+        }
+
+        if let Err(result) = file.write(&edited.content, context).await {
+            return result;
+        }
+
+        tracing::info!(
+            file_path = %path.display(),
+            key_path = %params.path,
+            added = edited.previous.is_none(),
+            "Structured edit applied"
+        );
+
+        let summary = match &edited.previous {
+            Some(previous) => format!("was {}", truncate_previous(previous)),
+            None => "added".to_string(),
+        };
+        ToolResult::success(format!(
+            "Set '{}' in {} ({})",
+            params.path,
+            path.display(),
+            summary
+        ))
+        .with_metadata(json!({
+            "path": path.display().to_string(),
+            "key_path": params.path,
+            "changed": true,
+            "added": edited.previous.is_none(),
+            "previous": edited.previous,
+            "verified": context.verify_writes()
+        }))
+    }
+}
+
+/// Maximum length of the previous value echoed in the result
+const MAX_PREVIOUS_LENGTH: usize = 200;
+
+/// Shorten a previous value for the result message
+fn truncate_previous(previous: &str) -> String {
+    match previous.char_indices().nth(MAX_PREVIOUS_LENGTH) {
+        Some((end, _)) => format!("{}…", &previous[..end]),
+        None => previous.to_string(),
+    }
+}
+
+/// Parse a key path like `a.b[0]["c.d"]`, optionally prefixed by `$.`
+pub(crate) fn parse_key_path(path: &str) -> Result<Vec<Segment>, String> {
+    let path = path.strip_prefix('$').unwrap_or(path);
+    let path = path.strip_prefix('.').unwrap_or(path);
+    let mut segments = Vec::new();
+    let mut chars = path.chars().peekable();
+
+    while let Some(&c) = chars.peek() {
+        match c {
+            '[' => {
+                chars.next();
+                if let Some(&quote @ ('"' | '\'')) = chars.peek() {
+                    chars.next();
+                    let mut key = String::new();
+                    loop {
+                        match chars.next() {
+                            Some(c) if c == quote => break,
+                            Some('\\') => key.extend(chars.next()),
+                            Some(c) => key.push(c),
+                            None => return Err("unterminated quoted key".to_string()),
+                        }
+                    }
+                    if chars.next() != Some(']') {
+                        return Err("expected ] after quoted key".to_string());
+                    }
+                    segments.push(Segment::Key(key));
+                } else {
+                    let mut digits = String::new();
+                    loop {
+                        match chars.next() {
+                            Some(']') => break,
+                            Some(c) => digits.push(c),
+                            None => return Err("expected ] after array index".to_string()),
+                        }
+                    }
+                    let index = digits
+                        .trim()
+                        .parse()
+                        .map_err(|_| format!("invalid array index [{digits}]"))?;
+                    segments.push(Segment::Index(index));
+                }
+            }
+            '.' => {
+                chars.next();
+                if matches!(chars.peek(), None | Some('.')) {
+                    return Err("empty key".to_string());
+                }
+            }
+            _ => {
+                let mut key = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == '.' || c == '[' {
+                        break;
+                    }
+                    key.push(c);
+                    chars.next();
+                }
+                segments.push(Segment::Key(key));
+            }
+        }
+    }
+
+    if segments.is_empty() {
+        return Err("the path is empty".to_string());
+    }
+    Ok(segments)
+}
+
+/// Human-readable form of a key path prefix, for error messages
+pub(crate) fn display_path(path: &[Segment]) -> String {
+    let mut out = String::new();
+    for segment in path {
+        match segment {
+            Segment::Key(key) => {
+                if !out.is_empty() {
+                    out.push('.');
+                }
+                out.push_str(key);
+            }
+            Segment::Index(index) => out.push_str(&format!("[{index}]")),
+        }
+    }
+    if out.is_empty() {
+        "the document".to_string()
+    } else {
+        out
+    }
+}
+
+/// The array index a segment addresses, if it can address one
+pub(crate) fn segment_index(segment: &Segment) -> Option<usize> {
+    match segment {
+        Segment::Index(index) => Some(*index),
+        Segment::Key(key) => key.parse().ok(),
+    }
+}
+
+/// Set a value in a TOML document with `toml_edit`
+fn set_toml_value(content: &str, path: &[Segment], value: &Value) -> Result<Edited, String> {
+    let mut document: toml_edit::DocumentMut = content
+        .parse()
+        .map_err(|e: toml_edit::TomlError| format!("invalid TOML: {}", e.message()))?;
+    let previous = set_toml_item(document.as_item_mut(), path, path, value)?;
+    Ok(Edited {
+        content: document.to_string(),
+        previous,
+    })
+}
+
+/// Set `value` at `rest` below `item`, returning the replaced value
+fn set_toml_item(
+    item: &mut toml_edit::Item,
+    full: &[Segment],
+    rest: &[Segment],
+    value: &Value,
+) -> Result<Option<String>, String> {
+    let Some((segment, rest)) = rest.split_first() else {
+        return Err("the path is empty".to_string());
+    };
+    let parent = &full[..full.len() - rest.len() - 1];
+
+    if let Some(table) = item.as_table_like_mut() {
+        let Segment::Key(key) = segment else {
+            return Err(format!("{} is a table, not an array", display_path(parent)));
+        };
+        if rest.is_empty() {
+            let new_value = toml_value(value)?;
+            if let Some(old) = table.get_mut(key).and_then(|i| i.as_value_mut()) {
+                // Keep the comments and spacing around the old value
+                let previous = toml_value_text(old);
+                let decor = old.decor().clone();
+                *old = new_value;
+                *old.decor_mut() = decor;
+                return Ok(Some(previous));
+            }
+            let previous = table.get(key).map(|i| i.to_string().trim().to_string());
+            table.insert(key, toml_edit::Item::Value(new_value));
+            return Ok(previous);
+        }
+        if table.get(key).is_none() {
+            table.insert(
+                key,
+                toml_edit::Item::Value(toml_edit::InlineTable::new().into()),
+            );
+        }
+        let Some(child) = table.get_mut(key) else {
+            return Err(format!("{key} could not be created"));
+        };
+        return set_toml_item(child, full, rest, value);
+    }
+
+    let Some(index) = segment_index(segment) else {
+        return Err(format!("{} is not a table", display_path(parent)));
+    };
+    if let Some(tables) = item.as_array_of_tables_mut() {
+        let len = tables.len();
+        let Some(table) = tables.get_mut(index) else {
+            return Err(format!(
+                "index {index} is out of range ({} has {len} elements)",
+                display_path(parent)
+            ));
+        };
+        if rest.is_empty() {
+            return Err(format!(
+                "{} is a table; set its keys instead",
+                display_path(&full[..=parent.len()])
+            ));
+        }
+        let mut child = toml_edit::Item::Table(std::mem::take(table));
+        let previous = set_toml_item(&mut child, full, rest, value);
+        if let toml_edit::Item::Table(updated) = child {
+            *table = updated;
+        }
+        return previous;
+    }
+    if let Some(array) = item.as_array_mut() {
+        let len = array.len();
+        if index == len && rest.is_empty() {
+            array.push(toml_value(value)?);
+            return Ok(None);
+        }
+        let Some(element) = array.get_mut(index) else {
+            return Err(format!(
+                "index {index} is out of range ({} has {len} elements)",
+                display_path(parent)
+            ));
+        };
+        if rest.is_empty() {
+            let previous = toml_value_text(element);
+            let decor = element.decor().clone();
+            *element = toml_value(value)?;
+            *element.decor_mut() = decor;
+            return Ok(Some(previous));
+        }
+        let mut child = toml_edit::Item::Value(element.clone());
+        let previous = set_toml_item(&mut child, full, rest, value)?;
+        if let toml_edit::Item::Value(updated) = child {
+            *element = updated;
+        }
+        return Ok(previous);
+    }
+
+    Err(format!("{} is not a table or array", display_path(parent)))
+}
+
+/// A TOML value as written, without surrounding comments and spacing
+fn toml_value_text(value: &toml_edit::Value) -> String {
+    let mut value = value.clone();
+    value.decor_mut().clear();
+    value.to_string()
+}
+
+/// Convert a JSON value to a TOML value
+fn toml_value(value: &Value) -> Result<toml_edit::Value, String> {
+    Ok(match value {
+        Value::Null => return Err("TOML has no null value".to_string()),
+        Value::Bool(b) => (*b).into(),
+        Value::Number(n) => match n.as_i64() {
+            Some(i) => i.into(),
+            None => n.as_f64().unwrap_or_default().into(),
+        },
+        Value::String(s) => s.as_str().into(),
+        Value::Array(items) => {
+            let mut array = toml_edit::Array::new();
+            for item in items {
+                array.push(toml_value(item)?);
+            }
+            array.into()
+        }
+        Value::Object(map) => {
+            let mut table = toml_edit::InlineTable::new();
+            for (key, item) in map {
+                table.insert(key, toml_value(item)?);
+            }
+            table.into()
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_key_path() {
+        assert_eq!(
+            parse_key_path("$.jobs.test.steps[0][\"run.sh\"]").unwrap(),
+            vec![
+                Segment::Key("jobs".to_string()),
+                Segment::Key("test".to_string()),
+                Segment::Key("steps".to_string()),
+                Segment::Index(0),
+                Segment::Key("run.sh".to_string()),
+            ]
+        );
+        assert!(parse_key_path("").is_err());
+        assert!(parse_key_path("a..b").is_err());
+        assert!(parse_key_path("a[x]").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_json_edit_sets_nested_key() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("tsconfig.json");
+        let original = r#"{
+  // Compiler settings
+  "compilerOptions": {
+    "target": "es2020",
+    "strict": false,
+    "paths": {"@/*": ["src/*"]}
+  },
+  "include": ["src"]
+}
+"#;
+        std::fs::write(&file_path, original).unwrap();
+
+        let tool = StructuredEditTool::new(StructuredFormat::Json);
+        let context = ToolContext::new("test", temp_dir.path());
+
+        let result = tool
+            .execute(
+                json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "path": "compilerOptions.strict",
+                    "value": true
+                }),
+                &context,
+            )
+            .await;
+
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            original.replace("\"strict\": false", "\"strict\": true")
+        );
+        assert_eq!(result.metadata.unwrap()["previous"], "false");
+    }
+
+    #[test]
+    fn test_toml_edit_preserves_comments() {
+        let original =
+            "# Manifest\n[package]\nname = \"demo\" # the name\n\n[dependencies]\nserde = \"1\"\n";
+        let tool = StructuredEditTool::new(StructuredFormat::Toml);
+
+        let path = parse_key_path("package.name").unwrap();
+        let edited = tool.set_value(original, &path, &json!("renamed")).unwrap();
+        assert_eq!(edited.content, original.replace("\"demo\"", "\"renamed\""));
+        assert_eq!(edited.previous.as_deref(), Some("\"demo\""));
+
+        let path = parse_key_path("dependencies.tokio.version").unwrap();
+        let edited = tool.set_value(original, &path, &json!("1.48")).unwrap();
+        assert!(
+            edited
+                .content
+                .ends_with("serde = \"1\"\ntokio = { version = \"1.48\" }\n")
+        );
+        assert!(edited.previous.is_none());
+
+        let path = parse_key_path("package.name").unwrap();
+        assert!(tool.set_value(original, &path, &Value::Null).is_err());
+    }
+}
@@ -0,0 +1,515 @@
+//! Format-preserving value replacement in JSON documents
+//!
+//! The document is scanned just far enough to find the byte range of the
+//! addressed value, which is then replaced by the new value; everything
+//! else is copied unchanged. `//` and `/* */` comments and trailing commas
+//! are skipped like whitespace, so JSON-with-comments files such as
+//! `tsconfig.json` or `.vscode/settings.json` can be edited too.
+
+use std::ops::Range;
+
+use serde::Serialize;
+use serde_json::Value;
+
+use super::structured_edit::{Edited, Segment, display_path, segment_index};
+
+/// Indentation assumed when a document doesn't show its own
+const DEFAULT_INDENT: &str = "  ";
+
+/// An object member found while scanning
+struct Member {
+    /// Decoded key
+    key: String,
+    /// Byte offset of the key's opening quote
+    key_start: usize,
+    /// Text between the key and its value, e.g. `": "`
+    separator: Range<usize>,
+    /// Byte range of the value
+    value: Range<usize>,
+}
+
+/// Set `value` at `path` in the JSON document `content`
+pub(crate) fn set_value(content: &str, path: &[Segment], value: &Value) -> Result<Edited, String> {
+    let edited = splice_value(content, path, value)?;
+    check_written(content, &edited.content, path, value)?;
+    Ok(edited)
+}
+
+/// Replace the byte range of the value at `path`, or insert it
+fn splice_value(content: &str, path: &[Segment], value: &Value) -> Result<Edited, String> {
+    let bytes = content.as_bytes();
+    let root = skip_blank(bytes, 0);
+    if root >= bytes.len() {
+        return Err("the file is empty".to_string());
+    }
+    let mut span = root..value_end(bytes, root)?;
+    let indent = detect_indent(content);
+
+    for (depth, segment) in path.iter().enumerate() {
+        let parent = &path[..depth];
+        match bytes[span.start] {
+            b'{' => {
+                let Segment::Key(key) = segment else {
+                    return Err(format!(
+                        "{} is an object, not an array",
+                        display_path(parent)
+                    ));
+                };
+                let (members, close) = object_members(content, span.start)?;
+                let Some(member) = members.iter().rev().find(|m| m.key == *key) else {
+                    let new_member = missing_member(&path[depth..], value)?;
+                    let new_content =
+                        insert_member(content, span.start, close, &members, &new_member, &indent);
+                    return Ok(added(new_content));
+                };
+                span = member.value.clone();
+            }
+            b'[' => {
+                let Some(index) = segment_index(segment) else {
+                    return Err(format!(
+                        "{} is an array, not an object",
+                        display_path(parent)
+                    ));
+                };
+                let (elements, close) = array_elements(bytes, span.start)?;
+                match elements.get(index) {
+                    Some(element) => span = element.clone(),
+                    None if index == elements.len() && depth + 1 == path.len() => {
+                        let new_content =
+                            insert_element(content, span.start, close, &elements, value, &indent);
+                        return Ok(added(new_content));
+                    }
+                    None => {
+                        return Err(format!(
+                            "index {index} is out of range ({} has {} elements)",
+                            display_path(parent),
+                            elements.len()
+                        ));
+                    }
+                }
+            }
+            _ => {
+                return Err(format!(
+                    "{} is not an object or array",
+                    display_path(parent)
+                ));
+            }
+        }
+    }
+
+    let rendered = render(value, &line_indent(content, span.start), &indent);
+    let mut new_content = String::with_capacity(content.len() + rendered.len());
+    new_content.push_str(&content[..span.start]);
+    new_content.push_str(&rendered);
+    new_content.push_str(&content[span.end..]);
+    Ok(Edited {
+        content: new_content,
+        previous: Some(content[span].to_string()),
+    })
+}
+
+/// Result of an edit that added a value
+fn added(new_content: String) -> Edited {
+    Edited {
+        content: new_content,
+        previous: None,
+    }
+}
+
+/// The member to add for the missing `path`, wrapping `value` in objects
+/// for every key after the first
+fn missing_member(path: &[Segment], value: &Value) -> Result<(String, Value), String> {
+    let mut value = value.clone();
+    for segment in path[1..].iter().rev() {
+        let Segment::Key(key) = segment else {
+            return Err(format!(
+                "{} doesn't exist, so there is no array to index",
+                display_path(path)
+            ));
+        };
+        value = Value::Object([(key.clone(), value)].into_iter().collect());
+    }
+    match &path[0] {
+        Segment::Key(key) => Ok((key.clone(), value)),
+        Segment::Index(_) => Err("an object can't be indexed".to_string()),
+    }
+}
+
+/// Add `key: value` to the object opened at `open` and closed at `close`
+fn insert_member(
+    content: &str,
+    open: usize,
+    close: usize,
+    members: &[Member],
+    (key, value): &(String, Value),
+    indent: &str,
+) -> String {
+    let key_json = Value::String(key.clone()).to_string();
+    let (Some(first), Some(last)) = (members.first(), members.last()) else {
+        let text = if indent.is_empty() {
+            format!("{key_json}: {}", render(value, "", indent))
+        } else {
+            let outer = line_indent(content, open);
+            let member_indent = format!("{outer}{indent}");
+            format!(
+                "\n{member_indent}{key_json}: {}\n{outer}",
+                render(value, &member_indent, indent)
+            )
+        };
+        return splice(content, open + 1..close, &text);
+    };
+
+    let member_indent = line_indent(content, first.key_start);
+    let between = members
+        .get(1)
+        .map(|second| first.value.end..second.key_start);
+    let separator = member_separator(content, between, open + 1..first.key_start, &member_indent);
+    let text = format!(
+        ",{separator}{key_json}{}{}",
+        &content[first.separator.clone()],
+        render(value, &member_indent, indent)
+    );
+    splice(content, last.value.end..last.value.end, &text)
+}
+
+/// Append `value` to the array opened at `open` and closed at `close`
+fn insert_element(
+    content: &str,
+    open: usize,
+    close: usize,
+    elements: &[Range<usize>],
+    value: &Value,
+    indent: &str,
+) -> String {
+    let (Some(first), Some(last)) = (elements.first(), elements.last()) else {
+        return splice(content, open + 1..close, &render(value, "", indent));
+    };
+
+    let element_indent = line_indent(content, first.start);
+    let between = elements.get(1).map(|second| first.end..second.start);
+    let separator = member_separator(content, between, open + 1..first.start, &element_indent);
+    let text = format!(",{separator}{}", render(value, &element_indent, indent));
+    splice(content, last.end..last.end, &text)
+}
+
+/// Separator to put after the comma before a new member, copied from
+/// between the first two members or else from before the first one
+fn member_separator(
+    content: &str,
+    between: Option<Range<usize>>,
+    before_first: Range<usize>,
+    member_indent: &str,
+) -> String {
+    if let Some(between) = between {
+        let between = &content[between];
+        if between.contains('\n') {
+            return format!("\n{member_indent}");
+        }
+        if let Some(after_comma) = between.trim_start().strip_prefix(',')
+            && after_comma.trim().is_empty()
+        {
+            return after_comma.to_string();
+        }
+    }
+    let before_first = &content[before_first];
+    if before_first.contains('\n') {
+        format!("\n{member_indent}")
+    } else if before_first.trim().is_empty() {
+        before_first.to_string()
+    } else {
+        " ".to_string()
+    }
+}
+
+/// Replace `range` of `content` with `text`
+fn splice(content: &str, range: Range<usize>, text: &str) -> String {
+    let mut out = String::with_capacity(content.len() + text.len());
+    out.push_str(&content[..range.start]);
+    out.push_str(text);
+    out.push_str(&content[range.end..]);
+    out
+}
+
+/// Serialize `value` in the document's style
+///
+/// Containers are written over several lines, indented from
+/// `line_indent`, when the document itself is; scalars and empty
+/// containers always fit on one line.
+fn render(value: &Value, line_indent: &str, indent: &str) -> String {
+    let is_container = match value {
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(map) => !map.is_empty(),
+        _ => false,
+    };
+    if !is_container || indent.is_empty() {
+        return value.to_string();
+    }
+
+    let mut out = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut serializer = serde_json::Serializer::with_formatter(&mut out, formatter);
+    if value.serialize(&mut serializer).is_err() {
+        return value.to_string();
+    }
+    String::from_utf8_lossy(&out).replace('\n', &format!("\n{line_indent}"))
+}
+
+/// Refuse edits that would turn a valid JSON document invalid, or that
+/// don't read back as `value` at `path`
+///
+/// Documents with comments aren't strict JSON, so they aren't checked.
+fn check_written(
+    original: &str,
+    edited: &str,
+    path: &[Segment],
+    value: &Value,
+) -> Result<(), String> {
+    if serde_json::from_str::<Value>(original).is_err() {
+        return Ok(());
+    }
+    let document: Value = serde_json::from_str(edited)
+        .map_err(|e| format!("the edit would make the file invalid JSON ({e})"))?;
+    let written = path.iter().try_fold(&document, |node, segment| match node {
+        Value::Array(items) => items.get(segment_index(segment)?),
+        Value::Object(map) => match segment {
+            Segment::Key(key) => map.get(key),
+            Segment::Index(_) => None,
+        },
+        _ => None,
+    });
+    if written != Some(value) {
+        return Err(format!(
+            "the edit would not read back as the given value at {}",
+            display_path(path)
+        ));
+    }
+    Ok(())
+}
+
+/// Indentation unit of the document: that of its first indented line, or
+/// empty if the document is on one line
+fn detect_indent(content: &str) -> String {
+    if !content.trim_end().contains('\n') {
+        return String::new();
+    }
+    content
+        .lines()
+        .map(|line| &line[..line.len() - line.trim_start().len()])
+        .find(|indent| !indent.is_empty())
+        .unwrap_or(DEFAULT_INDENT)
+        .to_string()
+}
+
+/// Leading whitespace of the line containing byte `offset`
+fn line_indent(content: &str, offset: usize) -> String {
+    let line_start = content[..offset].rfind('\n').map_or(0, |i| i + 1);
+    content[line_start..]
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect()
+}
+
+/// Skip whitespace and comments from `i`
+fn skip_blank(bytes: &[u8], mut i: usize) -> usize {
+    loop {
+        while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+            i += 1;
+        }
+        if bytes[i..].starts_with(b"//") {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+        } else if bytes[i..].starts_with(b"/*") {
+            i = bytes[i + 2..]
+                .windows(2)
+                .position(|w| w == b"*/")
+                .map_or(bytes.len(), |p| i + 2 + p + 2);
+        } else {
+            return i;
+        }
+    }
+}
+
+/// End of the string starting at `i`
+fn string_end(bytes: &[u8], mut i: usize) -> Result<usize, String> {
+    let start = i;
+    i += 1;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'"' => return Ok(i + 1),
+            _ => i += 1,
+        }
+    }
+    Err(format!("unterminated string at byte {start}"))
+}
+
+/// End of the value starting at `i`
+fn value_end(bytes: &[u8], i: usize) -> Result<usize, String> {
+    match bytes.get(i) {
+        Some(b'"') => string_end(bytes, i),
+        Some(b'{' | b'[') => {
+            let mut depth = 0usize;
+            let mut j = i;
+            while j < bytes.len() {
+                match bytes[j] {
+                    b'"' => {
+                        j = string_end(bytes, j)?;
+                        continue;
+                    }
+                    b'/' if matches!(bytes.get(j + 1), Some(b'/' | b'*')) => {
+                        j = skip_blank(bytes, j);
+                        continue;
+                    }
+                    b'{' | b'[' => depth += 1,
+                    b'}' | b']' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Ok(j + 1);
+                        }
+                    }
+                    _ => {}
+                }
+                j += 1;
+            }
+            Err(format!("unterminated object or array at byte {i}"))
+        }
+        Some(_) => {
+            let end = bytes[i..]
+                .iter()
+                .position(|b| matches!(b, b',' | b'}' | b']' | b'/') || b.is_ascii_whitespace())
+                .map_or(bytes.len(), |p| i + p);
+            if end == i {
+                return Err(format!("expected a value at byte {i}"));
+            }
+            Ok(end)
+        }
+        None => Err("unexpected end of file".to_string()),
+    }
+}
+
+/// Expect `byte` at `i`
+fn expect(bytes: &[u8], i: usize, byte: u8) -> Result<(), String> {
+    if bytes.get(i) == Some(&byte) {
+        Ok(())
+    } else {
+        Err(format!("expected '{}' at byte {i}", char::from(byte)))
+    }
+}
+
+/// Members of the object opened at `open`, and the offset of its `}`
+fn object_members(content: &str, open: usize) -> Result<(Vec<Member>, usize), String> {
+    let bytes = content.as_bytes();
+    let mut members = Vec::new();
+    let mut i = skip_blank(bytes, open + 1);
+    loop {
+        if bytes.get(i) == Some(&b'}') {
+            return Ok((members, i));
+        }
+        expect(bytes, i, b'"')?;
+        let key_end = string_end(bytes, i)?;
+        let key = serde_json::from_str(&content[i..key_end])
+            .map_err(|e| format!("invalid key at byte {i}: {e}"))?;
+        let colon = skip_blank(bytes, key_end);
+        expect(bytes, colon, b':')?;
+        let value_start = skip_blank(bytes, colon + 1);
+        let value = value_start..value_end(bytes, value_start)?;
+        members.push(Member {
+            key,
+            key_start: i,
+            separator: key_end..value_start,
+            value: value.clone(),
+        });
+
+        i = skip_blank(bytes, value.end);
+        match bytes.get(i) {
+            Some(b',') => i = skip_blank(bytes, i + 1),
+            Some(b'}') => return Ok((members, i)),
+            _ => return Err(format!("expected ',' or '}}' at byte {i}")),
+        }
+    }
+}
+
+/// Elements of the array opened at `open`, and the offset of its `]`
+fn array_elements(bytes: &[u8], open: usize) -> Result<(Vec<Range<usize>>, usize), String> {
+    let mut elements = Vec::new();
+    let mut i = skip_blank(bytes, open + 1);
+    loop {
+        if bytes.get(i) == Some(&b']') {
+            return Ok((elements, i));
+        }
+        let end = value_end(bytes, i)?;
+        elements.push(i..end);
+
+        i = skip_blank(bytes, end);
+        match bytes.get(i) {
+            Some(b',') => i = skip_blank(bytes, i + 1),
+            Some(b']') => return Ok((elements, i)),
+            _ => return Err(format!("expected ',' or ']' at byte {i}")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::tools::structured_edit::parse_key_path;
+    use serde_json::json;
+
+    fn set(content: &str, path: &str, value: Value) -> Result<Edited, String> {
+        set_value(content, &parse_key_path(path).unwrap(), &value)
+    }
+
+    #[test]
+    fn test_set_value_preserves_formatting() {
+        let original = "{\n    \"name\": \"demo\", // the name\n    \"deps\": [1, 2],\n    \"nested\": {\"a\": {\"b\": 1}}\n}\n";
+
+        let edited = set(original, "nested.a.b", json!(2)).unwrap();
+        assert_eq!(edited.content, original.replace("\"b\": 1", "\"b\": 2"));
+        assert_eq!(edited.previous.as_deref(), Some("1"));
+
+        let edited = set(original, "deps[1]", json!("two")).unwrap();
+        assert_eq!(edited.content, original.replace("[1, 2]", "[1, \"two\"]"));
+
+        // Appending to an array and adding keys follow the existing layout
+        let edited = set(original, "deps[2]", json!(3)).unwrap();
+        assert_eq!(edited.content, original.replace("[1, 2]", "[1, 2, 3]"));
+        assert!(edited.previous.is_none());
+
+        let edited = set(original, "scripts.build", json!("make")).unwrap();
+        assert_eq!(
+            edited.content,
+            original.replace(
+                "{\"b\": 1}}\n",
+                "{\"b\": 1}},\n    \"scripts\": {\n        \"build\": \"make\"\n    }\n"
+            )
+        );
+    }
+
+    #[test]
+    fn test_set_value_errors() {
+        let original = r#"{"list": [1], "text": "x"}"#;
+        assert!(set(original, "list[5]", json!(1)).is_err());
+        assert!(set(original, "text.inner", json!(1)).is_err());
+        assert!(set(original, "list.key", json!(1)).is_err());
+        assert!(set("", "a", json!(1)).is_err());
+        assert!(set("{\"a\": ", "a", json!(1)).is_err());
+
+        // Adding to an empty compact object
+        let edited = set("{}", "a", json!(true)).unwrap();
+        assert_eq!(edited.content, r#"{"a": true}"#);
+    }
+
+    #[test]
+    fn test_check_written_reads_the_value_back() {
+        let path = parse_key_path("a.b").unwrap();
+        let original = r#"{"a": {"b": 1}}"#;
+        assert!(check_written(original, r#"{"a": {"b": 2}}"#, &path, &json!(2)).is_ok());
+        assert!(check_written(original, r#"{"a": {"b": 2}}"#, &path, &json!("2")).is_err());
+        assert!(check_written(original, r#"{"a": {"b": 2}"#, &path, &json!(2)).is_err());
+
+        // Files with comments aren't strict JSON and are left unchecked
+        let original = "{\n  // note\n  \"a\": {\"b\": 1}\n}";
+        assert!(check_written(original, original, &path, &json!(2)).is_ok());
+    }
+}
@@ -0,0 +1,653 @@
+//! Format-preserving value replacement in YAML documents
+//!
+//! Block-style YAML is navigated line by line using indentation: mapping
+//! keys and `- ` sequence items are located at the column of their parent
+//! node, and only the line holding the addressed value (plus the lines of
+//! a nested value it replaces) is rewritten, so comments and formatting
+//! elsewhere survive. New values are written inline, with objects and
+//! arrays in flow style (`{"a": 1}`), which is valid YAML. Navigating into
+//! flow-style collections, multi-line flow values and documents after the
+//! first are not supported.
+//!
+//! The edited document is parsed back before it is written, so an edit that
+//! would break a valid file or store a different value is refused.
+
+use serde_json::Value;
+use yaml_rust2::{Yaml, YamlLoader};
+
+use super::structured_edit::{Edited, Segment, display_path, segment_index};
+
+/// Indentation used for keys created below an existing one
+const INDENT: usize = 2;
+
+/// A block node: the lines `start..end`, whose entries begin at `col`
+///
+/// The first entry may start mid-line, e.g. after the `- ` of a sequence
+/// item.
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    start: usize,
+    col: usize,
+    end: usize,
+}
+
+/// A mapping entry or sequence item of a node
+#[derive(Debug)]
+struct Entry {
+    /// Line of the key or dash
+    line: usize,
+    /// Key of a mapping entry
+    key: Option<String>,
+    /// Byte offset in the line just after the `:` or `-`
+    value_col: usize,
+    /// End of the lines belonging to the entry (exclusive)
+    end: usize,
+}
+
+/// Set `value` at `path` in the YAML document `content`
+pub(crate) fn set_value(content: &str, path: &[Segment], value: &Value) -> Result<Edited, String> {
+    let edited = splice_value(content, path, value)?;
+    check_written(content, &edited.content, path, value)?;
+    Ok(edited)
+}
+
+/// Rewrite the lines holding the value at `path`
+fn splice_value(content: &str, path: &[Segment], value: &Value) -> Result<Edited, String> {
+    let lines: Vec<&str> = content.split_inclusive('\n').collect();
+    let (doc_start, doc_end) = document_bounds(&lines);
+    let Some(first) = (doc_start..doc_end).find(|&i| !is_blank(lines[i])) else {
+        // Empty document: every key along the path is new
+        let text = new_entries(path, value, 0)?;
+        let insert_at = doc_end;
+        return Ok(splice_lines(&lines, insert_at..insert_at, &text, None));
+    };
+    let mut node = Node {
+        start: first,
+        col: indent_of(lines[first]),
+        end: doc_end,
+    };
+
+    for (depth, segment) in path.iter().enumerate() {
+        let parent = &path[..depth];
+        let is_last = depth + 1 == path.len();
+        let entries = node_entries(&lines, node)?;
+        let is_sequence = entries.iter().any(|entry| entry.key.is_none());
+
+        let entry = if is_sequence {
+            let Some(index) = segment_index(segment) else {
+                return Err(format!(
+                    "{} is a sequence, not a mapping",
+                    display_path(parent)
+                ));
+            };
+            match entries.get(index) {
+                Some(entry) => entry,
+                None if index == entries.len() && is_last => {
+                    let line = format!("{}- {}\n", " ".repeat(node.col), render(value));
+                    let at = last_content_line(&lines, node) + 1;
+                    return Ok(splice_lines(&lines, at..at, &line, None));
+                }
+                None => {
+                    return Err(format!(
+                        "index {index} is out of range ({} has {} items)",
+                        display_path(parent),
+                        entries.len()
+                    ));
+                }
+            }
+        } else {
+            let Segment::Key(key) = segment else {
+                return Err(format!(
+                    "{} is a mapping, not a sequence",
+                    display_path(parent)
+                ));
+            };
+            let Some(entry) = entries.iter().rev().find(|e| e.key.as_deref() == Some(key)) else {
+                let text = new_entries(&path[depth..], value, node.col)?;
+                let at = last_content_line(&lines, node) + 1;
+                return Ok(splice_lines(&lines, at..at, &text, None));
+            };
+            entry
+        };
+
+        let line = lines[entry.line];
+        let rest = line_content(line)[entry.value_col..].trim_start();
+        let has_inline_value = !rest.is_empty() && !rest.starts_with('#');
+
+        if is_last {
+            return replace_value(&lines, entry, value);
+        }
+
+        if has_inline_value {
+            // A sequence item holding a mapping starts on the dash line
+            if entry.key.is_none() && !rest.starts_with(['[', '{', '"', '\'', '|', '>']) {
+                let col = line_content(line).len() - rest.len();
+                node = Node {
+                    start: entry.line,
+                    col,
+                    end: entry.end,
+                };
+                continue;
+            }
+            return Err(format!(
+                "{} is an inline value; set it as a whole instead",
+                display_path(&path[..=depth])
+            ));
+        }
+
+        let Some(start) = (entry.line + 1..entry.end).find(|&i| !is_blank(lines[i])) else {
+            // An empty value (`key:`): create the rest of the path below it
+            let indent = indent_of(line) + INDENT;
+            let text = new_entries(&path[depth + 1..], value, indent)?;
+            let at = entry.line + 1;
+            return Ok(splice_lines(&lines, at..at, &text, None));
+        };
+        node = Node {
+            start,
+            col: indent_of(lines[start]),
+            end: entry.end,
+        };
+    }
+
+    Err("the path is empty".to_string())
+}
+
+/// Replace the value of `entry`, keeping any comment after it
+fn replace_value(lines: &[&str], entry: &Entry, value: &Value) -> Result<Edited, String> {
+    let line = lines[entry.line];
+    let content = line_content(line);
+    let after = &content[entry.value_col..];
+    let rest = after.trim_start();
+    let rest_start = entry.value_col + (after.len() - rest.len());
+
+    let (value_end, replaces_children, previous) = if rest.is_empty() || rest.starts_with('#') {
+        // Block value on the following lines (or none at all)
+        let children = lines[entry.line + 1..entry.end].concat();
+        let previous = (!children.trim().is_empty()).then(|| children.trim_end().to_string());
+        (rest_start, true, previous)
+    } else if rest.starts_with(['|', '>']) {
+        // Block scalar: the indicator plus the following lines
+        let end = scalar_end(content, rest_start)?;
+        let previous = format!(
+            "{}\n{}",
+            &content[rest_start..end],
+            lines[entry.line + 1..entry.end].concat().trim_end()
+        );
+        (end, true, Some(previous))
+    } else {
+        let end = scalar_end(content, rest_start)?;
+        (end, false, Some(content[rest_start..end].to_string()))
+    };
+
+    let mut new_line = String::with_capacity(line.len());
+    new_line.push_str(&content[..entry.value_col]);
+    new_line.push(' ');
+    new_line.push_str(&render(value));
+    let tail = &line[value_end..];
+    if !tail.trim().is_empty() && !tail.starts_with([' ', '\t']) {
+        new_line.push(' ');
+    }
+    new_line.push_str(tail);
+
+    let replaced = if replaces_children {
+        entry.line..entry.end
+    } else {
+        entry.line..entry.line + 1
+    };
+    Ok(splice_lines(lines, replaced, &new_line, previous))
+}
+
+/// Byte offset just past the scalar starting at `start` in a line
+fn scalar_end(content: &str, start: usize) -> Result<usize, String> {
+    let rest = &content[start..];
+    let bytes = rest.as_bytes();
+    let end = match bytes.first() {
+        Some(&quote @ (b'"' | b'\'')) => {
+            let mut i = 1;
+            loop {
+                match bytes.get(i) {
+                    // Backslash escapes in double quotes, '' in single quotes
+                    Some(b'\\') if quote == b'"' => i += 2,
+                    Some(&c) if c == quote && quote == b'\'' && bytes.get(i + 1) == Some(&c) => {
+                        i += 2;
+                    }
+                    Some(&c) if c == quote => break Some(i + 1),
+                    Some(_) => i += 1,
+                    None => break None,
+                }
+            }
+        }
+        Some(b'[' | b'{') => {
+            let mut depth = 0usize;
+            rest.char_indices().find_map(|(i, c)| {
+                match c {
+                    '[' | '{' => depth += 1,
+                    ']' | '}' => {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(i + 1);
+                        }
+                    }
+                    _ => {}
+                }
+                None
+            })
+        }
+        _ => Some(rest.find(" #").unwrap_or(rest.len())),
+    };
+    match end {
+        Some(end) => Ok(start + rest[..end].trim_end().len()),
+        None => Err("multi-line values are not supported; set the value above it".to_string()),
+    }
+}
+
+/// Entries of `node`: its mapping entries or sequence items
+fn node_entries(lines: &[&str], node: Node) -> Result<Vec<Entry>, String> {
+    let mut entries = Vec::new();
+    let mut i = node.start;
+    while i < node.end {
+        let line = lines[i];
+        let col = if i == node.start {
+            node.col
+        } else {
+            indent_of(line)
+        };
+        if is_blank(line) || col > node.col {
+            i += 1;
+            continue;
+        }
+        if col < node.col {
+            break;
+        }
+
+        let content = line_content(line);
+        let text = &content[col..];
+        let (key, value_col) = if text == "-" || text.starts_with("- ") {
+            (None, col + 1)
+        } else {
+            let (key, key_len) =
+                parse_key(text).ok_or_else(|| format!("unsupported YAML at line {}", i + 1))?;
+            (Some(key), col + key_len + 1)
+        };
+
+        // The entry owns the following lines indented past it, and a
+        // sequence at its own column if it's a mapping key
+        let mut last = i;
+        for (j, child) in lines.iter().enumerate().take(node.end).skip(i + 1) {
+            if is_blank(child) {
+                continue;
+            }
+            let child_col = indent_of(child);
+            let child_text = &line_content(child)[child_col..];
+            let is_item = child_text == "-" || child_text.starts_with("- ");
+            if child_col > col || (child_col == col && key.is_some() && is_item) {
+                last = j;
+            } else {
+                break;
+            }
+        }
+        entries.push(Entry {
+            line: i,
+            key,
+            value_col,
+            end: last + 1,
+        });
+        i = last + 1;
+    }
+
+    if entries.iter().any(|e| e.key.is_some()) && entries.iter().any(|e| e.key.is_none()) {
+        return Err(format!(
+            "mixed mapping and sequence at line {}",
+            node.start + 1
+        ));
+    }
+    Ok(entries)
+}
+
+/// Parse the key at the start of a mapping entry, returning it and the
+/// length up to (not including) the `:`
+fn parse_key(text: &str) -> Option<(String, usize)> {
+    let (key, len) = match text.chars().next()? {
+        '"' => {
+            let end = scalar_end(text, 0).ok()?;
+            (serde_json::from_str(&text[..end]).ok()?, end)
+        }
+        '\'' => {
+            let end = scalar_end(text, 0).ok()?;
+            (text[1..end - 1].replace("''", "'"), end)
+        }
+        _ => {
+            let colon = text
+                .match_indices(':')
+                .map(|(i, _)| i)
+                .find(|&i| text[i + 1..].is_empty() || text[i + 1..].starts_with([' ', '\t']))?;
+            (text[..colon].trim_end().to_string(), colon)
+        }
+    };
+    let after = text[len..].trim_start();
+    if !after.starts_with(':') {
+        return None;
+    }
+    Some((key, text.len() - after.len()))
+}
+
+/// Lines for the missing keys of `path`, the first at column `col`
+fn new_entries(path: &[Segment], value: &Value, col: usize) -> Result<String, String> {
+    let mut text = String::new();
+    for (depth, segment) in path.iter().enumerate() {
+        let Segment::Key(key) = segment else {
+            return Err(format!(
+                "{} doesn't exist, so there is no sequence to index",
+                display_path(&path[..depth])
+            ));
+        };
+        let indent = " ".repeat(col + depth * INDENT);
+        if depth + 1 == path.len() {
+            text.push_str(&format!("{indent}{}: {}\n", render_key(key), render(value)));
+        } else {
+            text.push_str(&format!("{indent}{}:\n", render_key(key)));
+        }
+    }
+    Ok(text)
+}
+
+/// Replace `range` of `lines` with `text`
+fn splice_lines(
+    lines: &[&str],
+    range: std::ops::Range<usize>,
+    text: &str,
+    previous: Option<String>,
+) -> Edited {
+    let mut content = lines[..range.start].concat();
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(text);
+    content.push_str(&lines[range.end..].concat());
+    Edited { content, previous }
+}
+
+/// Lines `start..end` of the first document, after any directives and
+/// `---` marker
+fn document_bounds(lines: &[&str]) -> (usize, usize) {
+    let is_marker = |line: &str| {
+        let content = line_content(line).trim_end();
+        content == "---" || content.starts_with("--- ")
+    };
+    let start = match lines.iter().position(|line| is_marker(line)) {
+        Some(marker)
+            if lines[..marker]
+                .iter()
+                .all(|line| is_blank(line) || line.starts_with('%')) =>
+        {
+            marker + 1
+        }
+        _ => 0,
+    };
+    let end = (start..lines.len())
+        .find(|&i| is_marker(lines[i]) || line_content(lines[i]).trim_end() == "...")
+        .unwrap_or(lines.len());
+    (start, end)
+}
+
+/// Last non-blank line of `node`
+fn last_content_line(lines: &[&str], node: Node) -> usize {
+    (node.start..node.end)
+        .rev()
+        .find(|&i| !is_blank(lines[i]))
+        .unwrap_or(node.start)
+}
+
+/// A line without its line ending
+fn line_content(line: &str) -> &str {
+    line.trim_end_matches(['\n', '\r'])
+}
+
+/// Number of leading spaces of a line
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Whether a line is empty or only a comment
+fn is_blank(line: &str) -> bool {
+    let trimmed = line.trim();
+    trimmed.is_empty() || trimmed.starts_with('#')
+}
+
+/// A mapping key, quoted if it couldn't be written plain
+fn render_key(key: &str) -> String {
+    if is_plain_safe(key) {
+        key.to_string()
+    } else {
+        Value::String(key.to_string()).to_string()
+    }
+}
+
+/// A value as inline YAML: scalars as themselves, strings quoted when
+/// they'd otherwise be read differently, containers in flow style
+fn render(value: &Value) -> String {
+    match value {
+        Value::String(s) if is_plain_safe(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+/// Whether a string reads back as the same string when written unquoted
+///
+/// Only word-like strings are left plain. Anything some YAML version could
+/// read as a number, date, boolean or null (`0x1F`, `0o17`, `.inf`,
+/// `2024-01-01`, `yes`) or that holds an indicator character is quoted.
+fn is_plain_safe(s: &str) -> bool {
+    const RESERVED: &[&str] = &["true", "false", "yes", "no", "on", "off", "null", "y", "n"];
+    let mut chars = s.chars();
+    let Some(first) = chars.next() else {
+        return false;
+    };
+    // Numbers start with a digit, `.`, `+` or `-` followed by one of those
+    let plain_start = first.is_alphabetic()
+        || matches!(first, '_' | '/')
+        || (first == '-' && chars.next().is_some_and(char::is_alphabetic));
+    plain_start
+        && s.trim() == s
+        && s.chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/' | ' '))
+        && !RESERVED.iter().any(|word| word.eq_ignore_ascii_case(s))
+        && parse_document(s).is_ok_and(|parsed| parsed.as_str() == Some(s))
+}
+
+/// Parse `content` as a single YAML document; an empty one is null
+fn parse_document(content: &str) -> Result<Yaml, String> {
+    let mut documents = YamlLoader::load_from_str(content).map_err(|e| e.to_string())?;
+    match documents.len() {
+        0 => Ok(Yaml::Null),
+        1 => Ok(documents.remove(0)),
+        _ => Err("more than one document".to_string()),
+    }
+}
+
+/// Whether a parsed YAML node holds the same data as `value`
+fn same_value(node: &Yaml, value: &Value) -> bool {
+    match (node, value) {
+        (Yaml::Null, Value::Null) => true,
+        (Yaml::Boolean(a), Value::Bool(b)) => a == b,
+        (Yaml::Integer(a), Value::Number(b)) => b.as_i64() == Some(*a),
+        (Yaml::Real(_), Value::Number(b)) => node.as_f64() == b.as_f64(),
+        (Yaml::String(a), Value::String(b)) => a == b,
+        (Yaml::Array(a), Value::Array(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| same_value(a, b))
+        }
+        (Yaml::Hash(a), Value::Object(b)) => {
+            a.len() == b.len()
+                && b.iter().all(|(key, b)| {
+                    a.get(&Yaml::String(key.clone()))
+                        .is_some_and(|a| same_value(a, b))
+                })
+        }
+        _ => false,
+    }
+}
+
+/// Refuse edits that would turn a valid YAML document invalid, or that
+/// don't read back as `value` at `path`
+fn check_written(
+    original: &str,
+    edited: &str,
+    path: &[Segment],
+    value: &Value,
+) -> Result<(), String> {
+    if parse_document(original).is_err() {
+        return Ok(());
+    }
+    let document = parse_document(edited)
+        .map_err(|e| format!("the edit would make the file invalid YAML ({e})"))?;
+    let written = path.iter().try_fold(&document, |node, segment| match node {
+        Yaml::Array(items) => items.get(segment_index(segment)?),
+        Yaml::Hash(map) => match segment {
+            Segment::Key(key) => map.get(&Yaml::String(key.clone())),
+            Segment::Index(_) => None,
+        },
+        _ => None,
+    });
+    if !written.is_some_and(|node| same_value(node, value)) {
+        return Err(format!(
+            "the edit would not read back as the given value at {}",
+            display_path(path)
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::tools::structured_edit::parse_key_path;
+    use serde_json::json;
+
+    fn set(content: &str, path: &str, value: Value) -> Result<Edited, String> {
+        set_value(content, &parse_key_path(path).unwrap(), &value)
+    }
+
+    const WORKFLOW: &str = "\
+# CI workflow
+name: CI
+on: [push]
+
+jobs:
+  test:
+    runs-on: ubuntu-latest # pinned below
+    steps:
+      - uses: actions/checkout@v4
+      - name: Test
+        run: cargo test
+  lint:
+    runs-on: ubuntu-latest
+";
+
+    #[test]
+    fn test_set_value_replaces_only_the_value() {
+        let edited = set(WORKFLOW, "jobs.test.runs-on", json!("ubuntu-24.04")).unwrap();
+        assert_eq!(
+            edited.content,
+            WORKFLOW.replace(
+                "runs-on: ubuntu-latest # pinned",
+                "runs-on: ubuntu-24.04 # pinned"
+            )
+        );
+        assert_eq!(edited.previous.as_deref(), Some("ubuntu-latest"));
+
+        let edited = set(
+            WORKFLOW,
+            "jobs.test.steps[1].run",
+            json!("cargo test --all"),
+        )
+        .unwrap();
+        assert_eq!(
+            edited.content,
+            WORKFLOW.replace("run: cargo test", "run: cargo test --all")
+        );
+
+        let edited = set(WORKFLOW, "on", json!(["push", "pull_request"])).unwrap();
+        assert_eq!(
+            edited.content,
+            WORKFLOW.replace("on: [push]", "on: [\"push\",\"pull_request\"]")
+        );
+
+        // Strings that would read as something else are quoted
+        let edited = set(WORKFLOW, "name", json!("true")).unwrap();
+        assert!(edited.content.contains("name: \"true\"\n"));
+    }
+
+    #[test]
+    fn test_set_value_adds_missing_keys() {
+        let edited = set(WORKFLOW, "jobs.lint.env.RUSTFLAGS", json!("-Dwarnings")).unwrap();
+        assert_eq!(
+            edited.content,
+            WORKFLOW.replace(
+                "  lint:\n    runs-on: ubuntu-latest\n",
+                "  lint:\n    runs-on: ubuntu-latest\n    env:\n      RUSTFLAGS: -Dwarnings\n"
+            )
+        );
+        assert!(edited.previous.is_none());
+
+        let edited = set(WORKFLOW, "jobs.test.steps[2]", json!({"run": "cargo fmt"})).unwrap();
+        assert!(
+            edited
+                .content
+                .contains("        run: cargo test\n      - {\"run\":\"cargo fmt\"}\n  lint:")
+        );
+
+        // Replacing a nested block drops its lines
+        let edited = set(WORKFLOW, "jobs.test", json!("skipped")).unwrap();
+        assert!(edited.content.contains("jobs:\n  test: skipped\n  lint:\n"));
+
+        assert!(set(WORKFLOW, "on.branch", json!(1)).is_err());
+        assert!(set(WORKFLOW, "jobs.test.steps[5]", json!(1)).is_err());
+        assert_eq!(set("", "a.b", json!(1)).unwrap().content, "a:\n  b: 1\n");
+    }
+
+    #[test]
+    fn test_set_value_quotes_non_trivial_scalars() {
+        for text in [
+            "0x1F",
+            "0o17",
+            ".inf",
+            "-.inf",
+            ".nan",
+            "2024-01-01",
+            "1.0",
+            "-1",
+            "+1",
+            "~",
+            "Yes",
+            "NULL",
+            "a: b",
+            "#note",
+            "*alias",
+            "key:",
+        ] {
+            let edited = set("name: CI\n", "name", json!(text)).unwrap();
+            assert_eq!(
+                edited.content,
+                format!("name: {}\n", json!(text)),
+                "{text} should be quoted"
+            );
+        }
+
+        // Plain words stay unquoted
+        let edited = set("name: CI\n", "name", json!("ubuntu-24.04 runner")).unwrap();
+        assert_eq!(edited.content, "name: ubuntu-24.04 runner\n");
+
+        // Keys that would read as numbers are quoted too
+        let edited = set("name: CI\n", "2024", json!(1)).unwrap();
+        assert_eq!(edited.content, "name: CI\n\"2024\": 1\n");
+    }
+
+    #[test]
+    fn test_check_written_reads_the_value_back() {
+        let path = parse_key_path("a.b").unwrap();
+        let original = "a:\n  b: 1\n";
+        assert!(check_written(original, "a:\n  b: 2\n", &path, &json!(2)).is_ok());
+        assert!(check_written(original, "a:\n  b: 0x1F\n", &path, &json!("0x1F")).is_err());
+        assert!(check_written(original, "a:\n  b: [1\n", &path, &json!([1])).is_err());
+    }
+}
@@ -0,0 +1,319 @@
+//! Structured results from test runner output
+//!
+//! Bash output of `cargo test`, `pytest` or `jest` is recognized and
+//! summarized as pass/fail counts plus the failing tests, with the location
+//! each one failed at when the output has it. Output from anything else is
+//! left as plain text.
+
+use std::sync::LazyLock;
+
+use regex::Regex;
+use serde::Serialize;
+
+/// `test result: ok. 3 passed; 1 failed; 0 ignored; ...` (libtest)
+static CARGO_RESULT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^test result: \w+\. (\d+) passed; (\d+) failed; (\d+) ignored")
+        .expect("valid cargo result regex")
+});
+
+/// `thread 'name' panicked at src/lib.rs:10:5:` (and the pre-1.73
+/// `panicked at 'message', src/lib.rs:10:5` form)
+static CARGO_PANIC_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^thread '([^']+)' panicked at (?:'.*', )?([^\s:]+:\d+:\d+)")
+        .expect("valid cargo panic regex")
+});
+
+/// `==== 1 failed, 2 passed, 1 skipped in 0.12s ====`
+static PYTEST_RESULT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^=+ (.*\d+ (?:passed|failed|skipped|errors?).*) in [\d.]+s.* =+$")
+        .expect("valid pytest result regex")
+});
+
+/// `Tests:       1 failed, 1 skipped, 5 passed, 7 total`
+static JEST_RESULT_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^Tests:\s+(.*\d+ total)$").expect("valid jest result regex"));
+
+/// `at Object.<anonymous> (src/sum.test.js:5:17)`
+static JEST_LOCATION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^at .*\(([^()\s]+:\d+:\d+)\)$").expect("valid jest location regex")
+});
+
+/// A test that failed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TestFailure {
+    /// Test name as the framework prints it
+    pub name: String,
+    /// Where it failed (`path:line[:column]`), if the output says
+    pub location: Option<String>,
+}
+
+/// Summary of a test run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TestSummary {
+    /// Framework the output came from (`cargo`, `pytest`, `jest`)
+    pub framework: &'static str,
+    /// Tests that passed
+    pub passed: usize,
+    /// Tests that failed
+    pub failed: usize,
+    /// Tests that were ignored or skipped
+    pub skipped: usize,
+    /// The failing tests, in output order
+    pub failures: Vec<TestFailure>,
+}
+
+impl TestSummary {
+    /// One-line summary, e.g. `cargo test: 3 passed, 1 failed (tests::b at src/lib.rs:10:5)`
+    pub fn summary_line(&self) -> String {
+        let mut line = format!(
+            "{} test: {} passed, {} failed",
+            self.framework, self.passed, self.failed
+        );
+        if self.skipped > 0 {
+            line.push_str(&format!(", {} skipped", self.skipped));
+        }
+        if !self.failures.is_empty() {
+            let failures: Vec<String> = self
+                .failures
+                .iter()
+                .map(|failure| match &failure.location {
+                    Some(location) => format!("{} at {}", failure.name, location),
+                    None => failure.name.clone(),
+                })
+                .collect();
+            line.push_str(&format!(" ({})", failures.join(", ")));
+        }
+        line
+    }
+}
+
+/// Recognize test runner output and summarize it
+///
+/// Returns None for output that doesn't look like any supported framework.
+pub fn parse_test_output(output: &str) -> Option<TestSummary> {
+    parse_cargo(output)
+        .or_else(|| parse_pytest(output))
+        .or_else(|| parse_jest(output))
+}
+
+/// libtest output, summed over every test binary of the run
+fn parse_cargo(output: &str) -> Option<TestSummary> {
+    let mut summary = TestSummary {
+        framework: "cargo",
+        ..TestSummary::default()
+    };
+    let mut found = false;
+    for line in output.lines() {
+        let line = line.trim_end();
+        if let Some(captures) = CARGO_RESULT_REGEX.captures(line) {
+            found = true;
+            summary.passed += captures[1].parse::<usize>().unwrap_or(0);
+            summary.failed += captures[2].parse::<usize>().unwrap_or(0);
+            summary.skipped += captures[3].parse::<usize>().unwrap_or(0);
+        } else if let Some(name) = line
+            .strip_prefix("test ")
+            .and_then(|rest| rest.strip_suffix(" ... FAILED"))
+        {
+            summary.failures.push(TestFailure {
+                name: name.to_string(),
+                location: None,
+            });
+        } else if let Some(captures) = CARGO_PANIC_REGEX.captures(line)
+            && let Some(failure) = summary
+                .failures
+                .iter_mut()
+                .find(|failure| failure.name == captures[1] && failure.location.is_none())
+        {
+            failure.location = Some(captures[2].to_string());
+        }
+    }
+    found.then_some(summary)
+}
+
+/// pytest output; failures come from the short test summary
+fn parse_pytest(output: &str) -> Option<TestSummary> {
+    let mut summary = TestSummary {
+        framework: "pytest",
+        ..TestSummary::default()
+    };
+    let mut found = false;
+    for line in output.lines() {
+        let line = line.trim_end();
+        if let Some(captures) = PYTEST_RESULT_REGEX.captures(line) {
+            found = true;
+            for (count, label) in counts(&captures[1]) {
+                match label {
+                    "passed" => summary.passed += count,
+                    "failed" | "error" | "errors" => summary.failed += count,
+                    "skipped" => summary.skipped += count,
+                    _ => {}
+                }
+            }
+        } else if let Some(rest) = line
+            .strip_prefix("FAILED ")
+            .or_else(|| line.strip_prefix("ERROR "))
+        {
+            let name = rest.split(" - ").next().unwrap_or(rest).trim();
+            summary.failures.push(TestFailure {
+                name: name.to_string(),
+                location: name.split("::").next().map(String::from),
+            });
+        }
+    }
+    found.then_some(summary)
+}
+
+/// jest output; a failure's location is the first stack frame after it
+fn parse_jest(output: &str) -> Option<TestSummary> {
+    let mut summary = TestSummary {
+        framework: "jest",
+        ..TestSummary::default()
+    };
+    let mut found = false;
+    for line in output.lines() {
+        let line = line.trim();
+        if let Some(captures) = JEST_RESULT_REGEX.captures(line) {
+            found = true;
+            for (count, label) in counts(&captures[1]) {
+                match label {
+                    "passed" => summary.passed += count,
+                    "failed" => summary.failed += count,
+                    "skipped" | "todo" => summary.skipped += count,
+                    _ => {}
+                }
+            }
+        } else if let Some(name) = line.strip_prefix("● ") {
+            summary.failures.push(TestFailure {
+                name: name.to_string(),
+                location: None,
+            });
+        } else if let Some(captures) = JEST_LOCATION_REGEX.captures(line)
+            && let Some(failure) = summary.failures.last_mut()
+            && failure.location.is_none()
+        {
+            failure.location = Some(captures[1].to_string());
+        }
+    }
+    found.then_some(summary)
+}
+
+/// `(count, label)` pairs of a `1 failed, 2 passed` style list
+fn counts(list: &str) -> impl Iterator<Item = (usize, &str)> {
+    list.split(',').filter_map(|part| {
+        let mut words = part.split_whitespace();
+        let count = words.next()?.parse().ok()?;
+        Some((count, words.next()?))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARGO_OUTPUT: &str = "\
+running 4 tests
+test tests::adds ... ok
+test tests::subtracts ... FAILED
+test tests::divides ... FAILED
+test tests::slow ... ignored
+
+failures:
+
+---- tests::subtracts stdout ----
+
+thread 'tests::subtracts' panicked at src/lib.rs:21:9:
+assertion `left == right` failed
+  left: 1
+ right: 2
+
+---- tests::divides stdout ----
+
+thread 'tests::divides' panicked at 'attempt to divide by zero', src/math.rs:8:5
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+
+failures:
+    tests::divides
+    tests::subtracts
+
+test result: FAILED. 1 passed; 2 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+running 2 tests
+test src/lib.rs - add (line 3) ... ok
+test src/lib.rs - sub (line 9) ... ok
+
+test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.31s
+";
+
+    #[test]
+    fn test_parse_cargo_test_output() {
+        let summary = parse_test_output(CARGO_OUTPUT).unwrap();
+        assert_eq!(
+            summary,
+            TestSummary {
+                framework: "cargo",
+                passed: 3,
+                failed: 2,
+                skipped: 1,
+                failures: vec![
+                    TestFailure {
+                        name: "tests::subtracts".to_string(),
+                        location: Some("src/lib.rs:21:9".to_string()),
+                    },
+                    TestFailure {
+                        name: "tests::divides".to_string(),
+                        location: Some("src/math.rs:8:5".to_string()),
+                    },
+                ],
+            }
+        );
+        assert_eq!(
+            summary.summary_line(),
+            "cargo test: 3 passed, 2 failed, 1 skipped \
+             (tests::subtracts at src/lib.rs:21:9, tests::divides at src/math.rs:8:5)"
+        );
+    }
+
+    #[test]
+    fn test_parse_pytest_and_jest_output() {
+        let pytest = "\
+tests/test_math.py .F.s                                                  [100%]
+=========================== short test summary info ============================
+FAILED tests/test_math.py::test_sub - assert 1 == 2
+=================== 1 failed, 2 passed, 1 skipped in 0.05s ====================
+";
+        let summary = parse_test_output(pytest).unwrap();
+        assert_eq!(summary.framework, "pytest");
+        assert_eq!((summary.passed, summary.failed, summary.skipped), (2, 1, 1));
+        assert_eq!(
+            summary.failures,
+            vec![TestFailure {
+                name: "tests/test_math.py::test_sub".to_string(),
+                location: Some("tests/test_math.py".to_string()),
+            }]
+        );
+
+        let jest = "\
+ FAIL  src/sum.test.js
+  ● sum › adds numbers
+
+    expect(received).toBe(expected)
+
+      at Object.<anonymous> (src/sum.test.js:5:17)
+
+Tests:       1 failed, 4 passed, 5 total
+";
+        let summary = parse_test_output(jest).unwrap();
+        assert_eq!(summary.framework, "jest");
+        assert_eq!((summary.passed, summary.failed, summary.skipped), (4, 1, 0));
+        assert_eq!(
+            summary.failures[0].location.as_deref(),
+            Some("src/sum.test.js:5:17")
+        );
+    }
+
+    #[test]
+    fn test_other_output_is_not_parsed() {
+        assert!(parse_test_output("Compiling foo v0.1.0\nFinished dev").is_none());
+    }
+}
@@ -1,12 +1,14 @@
 //! TodoWrite tool for task list management
 //!
 //! Manages a structured task list for tracking progress during coding sessions.
+//! With `merge: true` the todos sent are merged into the list, so the model
+//! can update statuses without resending everything.
 
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use serde_json::{Value, json};
-use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::fmt;
+use std::sync::{Arc, PoisonError, RwLock};
 
 use super::base::Tool;
 use crate::mcp::registry::{ToolContext, ToolResult};
@@ -47,16 +49,74 @@ impl TodoStatus {
     }
 }
 
+/// Todo item priority
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TodoPriority {
+    High,
+    #[default]
+    Medium,
+    Low,
+}
+
+impl TodoPriority {
+    /// Parse a priority, treating unknown values as medium
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "high" => Self::High,
+            "low" => Self::Low,
+            _ => Self::Medium,
+        }
+    }
+}
+
 /// A single todo item
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct TodoItem {
     /// The task description
     pub content: String,
     /// Current status
     pub status: String,
     /// Active form of the description (shown when in progress)
-    #[serde(rename = "activeForm")]
+    #[serde(rename = "activeForm", default)]
     pub active_form: String,
+    /// Priority: "high", "medium" or "low" (None = medium)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<String>,
+}
+
+impl TodoItem {
+    /// The item's priority (medium when unset)
+    pub fn priority(&self) -> TodoPriority {
+        self.priority
+            .as_deref()
+            .map(TodoPriority::parse)
+            .unwrap_or_default()
+    }
+}
+
+/// A difference between two versions of the todo list
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TodoChange {
+    /// A todo was added
+    Added(String),
+    /// A todo was removed
+    Removed(String),
+    /// A todo's status changed
+    Status {
+        content: String,
+        from: String,
+        to: String,
+    },
+}
+
+impl fmt::Display for TodoChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Added(content) => write!(f, "+ {}", content),
+            Self::Removed(content) => write!(f, "- {}", content),
+            Self::Status { content, from, to } => write!(f, "~ {}: {} -> {}", content, from, to),
+        }
+    }
 }
 
 /// Input parameters for TodoWrite
@@ -64,9 +124,15 @@ pub struct TodoItem {
 struct TodoWriteInput {
     /// The updated todo list
     todos: Vec<TodoItem>,
+    /// Merge the todos into the current list instead of replacing it
+    #[serde(default)]
+    merge: bool,
 }
 
 /// Shared todo list state
+///
+/// Uses a std RwLock so the list can also be updated from the (synchronous)
+/// notification converter; the lock is never held across an await.
 #[derive(Debug, Default)]
 pub struct TodoList {
     items: RwLock<Vec<TodoItem>>,
@@ -79,17 +145,50 @@ impl TodoList {
         }
     }
 
-    pub async fn update(&self, items: Vec<TodoItem>) {
-        let mut guard = self.items.write().await;
-        *guard = items;
+    /// Replace the whole list
+    pub fn update(&self, items: Vec<TodoItem>) {
+        *self.items.write().unwrap_or_else(PoisonError::into_inner) = items;
+    }
+
+    /// Apply a TodoWrite list and return what changed
+    ///
+    /// The list replaces the current one, unless `merge` is set: then todos
+    /// matching an existing one by content update it in place, new ones are
+    /// appended and the others are kept.
+    pub fn apply(&self, items: Vec<TodoItem>, merge: bool) -> Vec<TodoChange> {
+        let mut current = self.items.write().unwrap_or_else(PoisonError::into_inner);
+        let previous = current.clone();
+
+        if merge {
+            for item in items {
+                match current.iter_mut().find(|todo| todo.content == item.content) {
+                    Some(todo) => {
+                        todo.status = item.status;
+                        if !item.active_form.is_empty() {
+                            todo.active_form = item.active_form;
+                        }
+                        if item.priority.is_some() {
+                            todo.priority = item.priority;
+                        }
+                    }
+                    None => current.push(item),
+                }
+            }
+        } else {
+            *current = items;
+        }
+        diff_todos(&previous, &current)
     }
 
-    pub async fn get_all(&self) -> Vec<TodoItem> {
-        self.items.read().await.clone()
+    pub fn get_all(&self) -> Vec<TodoItem> {
+        self.items
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
     }
 
-    pub async fn format(&self) -> String {
-        let items = self.items.read().await;
+    pub fn format(&self) -> String {
+        let items = self.items.read().unwrap_or_else(PoisonError::into_inner);
         if items.is_empty() {
             return "No todos".to_string();
         }
@@ -102,15 +201,64 @@ impl TodoList {
             } else {
                 &item.content
             };
+            let priority = match item.priority() {
+                TodoPriority::High => " (high priority)",
+                TodoPriority::Medium => "",
+                TodoPriority::Low => " (low priority)",
+            };
             output.push_str(&format!(
-                "{}. {} {}\n",
+                "{}. {} {}{}\n",
                 i + 1,
                 status.symbol(),
-                display_text
+                display_text,
+                priority
             ));
         }
         output
     }
+
+    /// Render the list as a markdown checklist under a `title` heading
+    ///
+    /// Completed todos are checked; the one in progress is marked as such.
+    pub fn to_markdown(&self, title: &str) -> String {
+        let items = self.items.read().unwrap_or_else(PoisonError::into_inner);
+        let mut output = format!("# {}\n\n", title);
+        for item in items.iter() {
+            let status = TodoStatus::from_str(&item.status);
+            let checkbox = if status == TodoStatus::Completed {
+                "[x]"
+            } else {
+                "[ ]"
+            };
+            output.push_str(&format!("- {} {}", checkbox, item.content));
+            if status == TodoStatus::InProgress {
+                output.push_str(" *(in progress)*");
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// Changes from `previous` to `current`, matching todos by content
+fn diff_todos(previous: &[TodoItem], current: &[TodoItem]) -> Vec<TodoChange> {
+    let mut changes: Vec<TodoChange> = previous
+        .iter()
+        .filter(|old| !current.iter().any(|todo| todo.content == old.content))
+        .map(|old| TodoChange::Removed(old.content.clone()))
+        .collect();
+    for todo in current {
+        match previous.iter().find(|old| old.content == todo.content) {
+            None => changes.push(TodoChange::Added(todo.content.clone())),
+            Some(old) if old.status != todo.status => changes.push(TodoChange::Status {
+                content: todo.content.clone(),
+                from: old.status.clone(),
+                to: todo.status.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    changes
 }
 
 /// TodoWrite tool for task list management
@@ -154,7 +302,9 @@ impl Tool for TodoWriteTool {
     fn description(&self) -> &str {
         "Manages a structured task list for tracking progress. Use this to plan tasks, \
          track progress, and demonstrate thoroughness. Each todo has content, status \
-         (pending/in_progress/completed), and activeForm (shown when in progress)."
+         (pending/in_progress/completed), and activeForm (shown when in progress). Send \
+         the full list, or only the todos that changed with merge: true to update them \
+         in place."
     }
 
     fn input_schema(&self) -> Value {
@@ -164,7 +314,7 @@ impl Tool for TodoWriteTool {
             "properties": {
                 "todos": {
                     "type": "array",
-                    "description": "The updated todo list",
+                    "description": "The updated todo list, or with merge the todos to update (matched by content) or add",
                     "items": {
                         "type": "object",
                         "required": ["content", "status", "activeForm"],
@@ -183,9 +333,18 @@ impl Tool for TodoWriteTool {
                                 "type": "string",
                                 "minLength": 1,
                                 "description": "Present continuous form shown during execution"
+                            },
+                            "priority": {
+                                "type": "string",
+                                "enum": ["high", "medium", "low"],
+                                "description": "How urgent the task is (default: medium)"
                             }
                         }
                     }
+                },
+                "merge": {
+                    "type": "boolean",
+                    "description": "Merge the todos into the current list instead of replacing it (default: false)"
                 }
             }
         })
@@ -216,41 +375,51 @@ impl Tool for TodoWriteTool {
                     valid_statuses
                 ));
             }
+            let valid_priorities = ["high", "medium", "low"];
+            if let Some(priority) = &todo.priority
+                && !valid_priorities.contains(&priority.as_str())
+            {
+                return ToolResult::error(format!(
+                    "Todo {} has invalid priority '{}'. Must be one of: {:?}",
+                    i + 1,
+                    priority,
+                    valid_priorities
+                ));
+            }
         }
 
-        // Count statuses
-        let pending = params
-            .todos
-            .iter()
-            .filter(|t| t.status == "pending")
-            .count();
-        let in_progress = params
-            .todos
-            .iter()
-            .filter(|t| t.status == "in_progress")
-            .count();
-        let completed = params
-            .todos
-            .iter()
-            .filter(|t| t.status == "completed")
-            .count();
-
         // Update the todo list
-        self.todo_list.update(params.todos.clone()).await;
+        let changes = self.todo_list.apply(params.todos, params.merge);
+        let todos = self.todo_list.get_all();
+
+        // Count statuses
+        let pending = todos.iter().filter(|t| t.status == "pending").count();
+        let in_progress = todos.iter().filter(|t| t.status == "in_progress").count();
+        let completed = todos.iter().filter(|t| t.status == "completed").count();
 
         // Format output
-        let formatted = self.todo_list.format().await;
+        let formatted = self.todo_list.format();
+        let changed = if changes.is_empty() {
+            "No changes".to_string()
+        } else {
+            changes
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
 
         let output = format!(
-            "Todos updated successfully.\n\n{}\n\nSummary: {} pending, {} in progress, {} completed",
-            formatted, pending, in_progress, completed
+            "Todos updated successfully.\n\n{}\n\nChanges:\n{}\n\nSummary: {} pending, {} in progress, {} completed",
+            formatted, changed, pending, in_progress, completed
         );
 
         ToolResult::success(output).with_metadata(json!({
-            "total": params.todos.len(),
+            "total": todos.len(),
             "pending": pending,
             "in_progress": in_progress,
-            "completed": completed
+            "completed": completed,
+            "changes": changes.len()
         }))
     }
 }
@@ -355,6 +524,117 @@ mod tests {
         assert!(result.content.contains("1 completed"));
     }
 
+    #[tokio::test]
+    async fn test_todo_write_merges_partial_list() {
+        let temp_dir = TempDir::new().unwrap();
+        let tool = TodoWriteTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+
+        tool.execute(
+            json!({
+                "todos": [
+                    {"content": "Task 1", "status": "in_progress", "activeForm": "Doing task 1"},
+                    {"content": "Task 2", "status": "pending", "activeForm": "Doing task 2"},
+                    {"content": "Task 3", "status": "pending", "activeForm": "Doing task 3"}
+                ]
+            }),
+            &context,
+        )
+        .await;
+
+        // Only the changed todo is sent; the others are kept
+        let result = tool
+            .execute(
+                json!({
+                    "todos": [
+                        {"content": "Task 1", "status": "completed", "activeForm": "Doing task 1"}
+                    ],
+                    "merge": true
+                }),
+                &context,
+            )
+            .await;
+
+        assert!(!result.is_error, "{}", result.content);
+        assert!(
+            result
+                .content
+                .contains("~ Task 1: in_progress -> completed")
+        );
+        assert!(
+            result
+                .content
+                .contains("2 pending, 0 in progress, 1 completed")
+        );
+        let items = tool.todo_list().get_all();
+        assert_eq!(
+            items
+                .iter()
+                .map(|item| (item.content.as_str(), item.status.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                ("Task 1", "completed"),
+                ("Task 2", "pending"),
+                ("Task 3", "pending")
+            ]
+        );
+
+        // Merging appends new todos
+        let task = |content: &str| TodoItem {
+            content: content.to_string(),
+            status: "pending".to_string(),
+            active_form: format!("Doing {}", content),
+            priority: None,
+        };
+        let changes = tool.todo_list().apply(vec![task("Task 4")], true);
+        assert_eq!(changes, [TodoChange::Added("Task 4".to_string())]);
+        assert_eq!(tool.todo_list().get_all().len(), 4);
+
+        // Without merge, even a list naming only existing todos replaces it
+        let changes = tool.todo_list().apply(vec![task("Task 2")], false);
+        assert_eq!(changes.len(), 3);
+        assert!(changes.contains(&TodoChange::Removed("Task 1".to_string())));
+        assert_eq!(tool.todo_list().get_all(), [task("Task 2")]);
+    }
+
+    #[tokio::test]
+    async fn test_todo_write_priority() {
+        let temp_dir = TempDir::new().unwrap();
+        let tool = TodoWriteTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+
+        let result = tool
+            .execute(
+                json!({
+                    "todos": [
+                        {"content": "Fix crash", "status": "pending", "activeForm": "Fixing crash", "priority": "high"},
+                        {"content": "Tidy docs", "status": "pending", "activeForm": "Tidying docs"}
+                    ]
+                }),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.contains("1. ○ Fix crash (high priority)\n"));
+        assert!(result.content.contains("2. ○ Tidy docs\n"));
+        let items = tool.todo_list().get_all();
+        assert_eq!(items[0].priority(), TodoPriority::High);
+        assert_eq!(items[1].priority(), TodoPriority::Medium);
+
+        let result = tool
+            .execute(
+                json!({
+                    "todos": [
+                        {"content": "Task", "status": "pending", "activeForm": "Doing task", "priority": "urgent"}
+                    ]
+                }),
+                &context,
+            )
+            .await;
+        assert!(result.is_error);
+        assert!(result.content.contains("invalid priority 'urgent'"));
+    }
+
     #[tokio::test]
     async fn test_todo_write_invalid_status() {
         let temp_dir = TempDir::new().unwrap();
@@ -413,9 +693,37 @@ mod tests {
         assert_eq!(TodoStatus::from_str("unknown"), TodoStatus::Pending);
 
         assert_eq!(TodoStatus::Pending.as_str(), "pending");
+
+        assert_eq!(TodoPriority::parse("high"), TodoPriority::High);
+        assert_eq!(TodoPriority::parse("low"), TodoPriority::Low);
+        assert_eq!(TodoPriority::parse("urgent"), TodoPriority::Medium);
         assert_eq!(TodoStatus::InProgress.symbol(), "◐");
     }
 
+    #[test]
+    fn test_todo_list_to_markdown() {
+        let list = TodoList::new();
+        let todo = |content: &str, status: &str| TodoItem {
+            content: content.to_string(),
+            status: status.to_string(),
+            active_form: String::new(),
+            priority: None,
+        };
+        list.update(vec![
+            todo("Read the code", "completed"),
+            todo("Fix the bug", "in_progress"),
+            todo("Add a test", "pending"),
+        ]);
+
+        assert_eq!(
+            list.to_markdown("Login fix"),
+            "# Login fix\n\n\
+             - [x] Read the code\n\
+             - [ ] Fix the bug *(in progress)*\n\
+             - [ ] Add a test\n"
+        );
+    }
+
     #[tokio::test]
     async fn test_shared_todo_list() {
         let shared_list = Arc::new(TodoList::new());
@@ -442,7 +750,7 @@ mod tests {
             .await;
 
         // Verify via tool2's shared list
-        let items = tool2.todo_list().get_all().await;
+        let items = tool2.todo_list().get_all();
         assert_eq!(items.len(), 1);
         assert_eq!(items[0].content, "Shared task");
     }
@@ -0,0 +1,184 @@
+//! Touch tool implementation
+//!
+//! Creates empty files (e.g. `.gitkeep`, `__init__.py`) or updates the
+//! modification time of existing ones, like `touch(1)`.
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::json;
+use std::path::Path;
+use std::time::SystemTime;
+
+use super::base::{Tool, ToolKind};
+use super::special_file::reject_special_file;
+use crate::mcp::registry::{ToolContext, ToolResult};
+
+/// Touch tool for creating empty files
+#[derive(Debug, Default)]
+pub struct TouchTool;
+
+/// Touch tool input parameters
+#[derive(Debug, Deserialize)]
+struct TouchInput {
+    /// Path to the file to touch (canonical name; `path` and `filePath` are
+    /// accepted as aliases)
+    #[serde(alias = "path", alias = "filePath")]
+    file_path: String,
+}
+
+impl TouchTool {
+    /// Create a new Touch tool instance
+    pub fn new() -> Self {
+        Self
+    }
+}
+
+#[async_trait]
+impl Tool for TouchTool {
+    fn name(&self) -> &str {
+        "Touch"
+    }
+
+    fn description(&self) -> &str {
+        "Create an empty file, or update the modification time of an existing file without \
+         changing its content. Creates parent directories as needed. Use for placeholder files \
+         such as .gitkeep or __init__.py."
+    }
+
+    fn input_schema(&self) -> serde_json::Value {
+        json!({
+            "type": "object",
+            "required": ["file_path"],
+            "properties": {
+                "file_path": {
+                    "type": "string",
+                    "description": "The absolute path to the file to create or touch"
+                }
+            }
+        })
+    }
+
+    fn kind(&self) -> ToolKind {
+        ToolKind::Edit
+    }
+
+    fn requires_permission(&self) -> bool {
+        true // Creating files requires permission
+    }
+
+    async fn execute(&self, input: serde_json::Value, context: &ToolContext) -> ToolResult {
+        // Parse input
+        let params: TouchInput = match serde_json::from_value(input) {
+            Ok(p) => p,
+            Err(e) => return ToolResult::error(format!("Invalid input: {}", e)),
+        };
+
+        // Resolve path relative to working directory if not absolute
+        let path = if Path::new(&params.file_path).is_absolute() {
+            std::path::PathBuf::from(&params.file_path)
+        } else {
+            context.cwd.join(&params.file_path)
+        };
+
+        if let Some(result) = reject_special_file("touch", &path) {
+            return result;
+        }
+        if path.is_dir() {
+            return ToolResult::error(format!("{} is a directory", path.display()));
+        }
+
+        // Create parent directories if they don't exist
+        if let Some(parent) = path.parent()
+            && !parent.exists()
+            && let Err(e) = tokio::fs::create_dir_all(parent).await
+        {
+            return ToolResult::io_error("creating directory", parent, &e, &context.cwd);
+        }
+
+        let file_existed = path.exists();
+        let touch_path = path.clone();
+        let touched = tokio::task::spawn_blocking(move || touch(&touch_path))
+            .await
+            .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+        if let Err(e) = touched {
+            return ToolResult::io_error("touching", &path, &e, &context.cwd);
+        }
+
+        tracing::info!(
+            file_path = %path.display(),
+            created = !file_existed,
+            "File touched"
+        );
+
+        let message = if file_existed {
+            format!("Updated modification time of {}", path.display())
+        } else {
+            format!("Created empty file {}", path.display())
+        };
+        ToolResult::success(message).with_metadata(json!({
+            "path": path.display().to_string(),
+            "created": !file_existed
+        }))
+    }
+}
+
+/// Create `path` if missing and set its modification time to now
+///
+/// Existing content is left untouched.
+fn touch(path: &Path) -> std::io::Result<()> {
+    let file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    file.set_modified(SystemTime::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_touch_creates_empty_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let tool = TouchTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+
+        let result = tool
+            .execute(json!({"file_path": "assets/.gitkeep"}), &context)
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.starts_with("Created empty file"));
+
+        let path = temp_dir.path().join("assets/.gitkeep");
+        assert!(path.is_file());
+        assert_eq!(std::fs::metadata(&path).unwrap().len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_touch_keeps_existing_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("notes.txt");
+        std::fs::write(&path, "keep me").unwrap();
+        let old_time = SystemTime::now() - Duration::from_secs(3600);
+        std::fs::File::options()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(old_time)
+            .unwrap();
+
+        let tool = TouchTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+        let result = tool
+            .execute(json!({"file_path": path.to_str().unwrap()}), &context)
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.metadata.unwrap()["created"], false);
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+        let modified = std::fs::metadata(&path).unwrap().modified().unwrap();
+        assert!(modified > old_time + Duration::from_secs(60));
+    }
+}
@@ -0,0 +1,74 @@
+//! Read-back verification of written files
+//!
+//! With `verifyWrites` enabled, Write and Edit re-read a file after writing
+//! it and compare its SHA-256 with that of the intended content, catching
+//! writes a filesystem silently dropped or corrupted.
+
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncReadExt;
+
+/// Size of the chunks the file is read back in
+const VERIFY_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Check that the file at `path` holds exactly `expected`
+///
+/// Returns a message describing the failure when the content differs or
+/// the file can't be read back.
+pub async fn verify_written(path: &Path, expected: &[u8]) -> Result<(), String> {
+    let read_failed = |e: std::io::Error| {
+        format!(
+            "Failed to read {} back for verification: {e}",
+            path.display()
+        )
+    };
+
+    let mut file = tokio::fs::File::open(path).await.map_err(read_failed)?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; VERIFY_CHUNK_SIZE];
+    let mut actual_len = 0usize;
+    loop {
+        let n = file.read(&mut buf).await.map_err(read_failed)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+        actual_len += n;
+    }
+
+    if hasher.finalize() == Sha256::digest(expected) {
+        return Ok(());
+    }
+    Err(format!(
+        "Verification failed: {} holds {} bytes that don't match the {} bytes written. The filesystem may have dropped or corrupted the write; read the file and retry.",
+        path.display(),
+        actual_len,
+        expected.len()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_verify_written() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("file.txt");
+        std::fs::write(&path, "written content").unwrap();
+
+        assert!(verify_written(&path, b"written content").await.is_ok());
+
+        // Simulate a write the filesystem didn't persist correctly
+        let err = verify_written(&path, b"intended content")
+            .await
+            .unwrap_err();
+        assert!(err.contains("Verification failed"));
+
+        let missing = temp_dir.path().join("missing.txt");
+        let err = verify_written(&missing, b"content").await.unwrap_err();
+        assert!(err.contains("read"));
+    }
+}
@@ -0,0 +1,15 @@
+//! Gitignore-aware traversal of the working tree
+
+use std::path::Path;
+
+use ignore::WalkBuilder;
+
+/// Build a walker over `root` that honours `.gitignore`, `.ignore` and git excludes
+///
+/// Hidden files and directories (including `.git`) are skipped, symlinks are
+/// not followed, and ignore files apply even outside a git repository.
+pub(crate) fn project_walker(root: &Path) -> WalkBuilder {
+    let mut builder = WalkBuilder::new(root);
+    builder.hidden(true).follow_links(false).require_git(false);
+    builder
+}
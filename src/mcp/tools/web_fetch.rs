@@ -1,15 +1,52 @@
 //! WebFetch tool for fetching and processing web content
 //!
-//! Fetches content from URLs and processes it using an AI model.
-//! Note: Full implementation requires HTTP client and AI API integration.
+//! Fetches content from URLs and converts HTML pages to markdown (or, on
+//! request, plain text).
+//! The prompt is returned alongside the content so the calling model can
+//! apply it to the fetched page. Converted pages are kept in a small LRU
+//! cache for a while, so asking several questions about the same page
+//! doesn't fetch it again.
+//!
+//! Redirects are followed by hand so that every hop is checked: only http(s)
+//! URLs are fetched, and loopback, private and link-local addresses are
+//! refused unless the `allowLocalhostFetch` setting is on. Hosts are also
+//! checked against the `webFetchAllowedDomains` and `webFetchDeniedDomains`
+//! settings before any request is made.
 
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::{Value, json};
+use std::collections::VecDeque;
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
 
 use super::base::Tool;
+use super::domain_filter::DomainFilter;
+use super::html_to_markdown::{html_to_markdown, html_to_text};
+use super::pdf::{PDF_FORMATTING_NOTE, extract_pdf_text, has_pdf_extension, is_pdf_bytes};
 use crate::mcp::registry::{ToolContext, ToolResult};
 
+/// Maximum size of the returned content in characters
+const MAX_CONTENT_SIZE: usize = 100_000;
+/// Default time limit of a fetch, redirects included
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+/// Maximum accepted `timeout_ms`
+const MAX_TIMEOUT_MS: u64 = 300_000;
+/// Default number of redirects followed
+const DEFAULT_MAX_REDIRECTS: usize = 5;
+/// Maximum accepted `max_redirects`
+const MAX_REDIRECTS: usize = 20;
+/// Maximum size of a response body in bytes
+const MAX_BODY_BYTES: usize = 10 * 1024 * 1024;
+/// User agent sent with fetch requests
+const USER_AGENT: &str = concat!("claude-code-acp-rs/", env!("CARGO_PKG_VERSION"));
+/// Default time a fetched page is served from the cache
+pub const DEFAULT_CACHE_TTL: Duration = Duration::from_secs(15 * 60);
+/// Default maximum number of cached pages
+pub const DEFAULT_CACHE_CAPACITY: usize = 50;
+
 /// Input parameters for WebFetch
 #[derive(Debug, Deserialize)]
 struct WebFetchInput {
@@ -17,16 +54,192 @@ struct WebFetchInput {
     url: String,
     /// The prompt to run on the fetched content
     prompt: String,
+    /// How HTML pages are returned
+    #[serde(default)]
+    format: OutputFormat,
+    /// Time limit of the fetch in milliseconds
+    #[serde(default)]
+    timeout_ms: Option<u64>,
+    /// Number of redirects followed
+    #[serde(default)]
+    max_redirects: Option<usize>,
+}
+
+/// Limits applied to a single fetch
+#[derive(Debug, Clone, Copy)]
+struct FetchLimits<'a> {
+    /// Time limit of the whole fetch, redirects included
+    timeout: Duration,
+    /// Number of redirects followed
+    max_redirects: usize,
+    /// Whether loopback and private addresses may be fetched
+    allow_local: bool,
+    /// Hosts that may be fetched
+    domains: &'a DomainFilter,
+    /// Largest response body read, in bytes
+    max_body_bytes: usize,
+}
+
+/// How a fetched HTML page is returned
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum OutputFormat {
+    /// Main content converted to markdown, links kept as markdown links
+    #[default]
+    Markdown,
+    /// Main content as plain text
+    Text,
+    /// The HTML as served
+    Html,
+}
+
+impl OutputFormat {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Markdown => "markdown",
+            Self::Text => "text",
+            Self::Html => "html",
+        }
+    }
+}
+
+/// Response received from a fetched URL
+#[derive(Debug)]
+struct FetchedPage {
+    /// URL after following redirects
+    final_url: String,
+    /// Number of redirects followed to reach `final_url`
+    redirects: usize,
+    /// HTTP status code
+    status: u16,
+    /// Value of the Content-Type header (may be empty)
+    content_type: String,
+    /// Raw response body
+    body: Vec<u8>,
+}
+
+/// Converted page kept in the fetch cache
+#[derive(Debug, Clone)]
+struct CachedPage {
+    /// URL after following redirects
+    final_url: String,
+    /// Number of redirects followed to reach `final_url`
+    redirects: usize,
+    /// HTTP status code
+    status: u16,
+    /// Value of the Content-Type header (may be empty)
+    content_type: String,
+    /// Text returned to the model, already truncated
+    content: String,
+    /// Whether the content was truncated
+    truncated: bool,
+    /// When the page was fetched
+    fetched_at: Instant,
+}
+
+/// LRU cache of converted pages keyed by normalized URL
+#[derive(Debug)]
+struct FetchCache {
+    /// How long a page is served from the cache
+    ttl: Duration,
+    /// Maximum number of cached pages (0 disables caching)
+    capacity: usize,
+    /// Cached pages, least recently used first
+    entries: Mutex<VecDeque<(String, CachedPage)>>,
+}
+
+impl FetchCache {
+    fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            entries: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    /// Get a cached page that hasn't expired, marking it as recently used
+    fn get(&self, key: &str) -> Option<CachedPage> {
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        let index = entries.iter().position(|(k, _)| k == key)?;
+        let (key, page) = entries.remove(index)?;
+        if page.fetched_at.elapsed() >= self.ttl {
+            return None;
+        }
+        entries.push_back((key, page.clone()));
+        Some(page)
+    }
+
+    /// Store a page, evicting the least recently used one when full
+    fn insert(&self, key: String, page: CachedPage) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut entries = self.entries.lock().unwrap_or_else(PoisonError::into_inner);
+        entries.retain(|(k, _)| *k != key);
+        while entries.len() >= self.capacity {
+            entries.pop_front();
+        }
+        entries.push_back((key, page));
+    }
+
+    fn clear(&self) {
+        self.entries
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
 }
 
 /// WebFetch tool for fetching and analyzing web content
-#[derive(Debug, Default)]
-pub struct WebFetchTool;
+#[derive(Debug)]
+pub struct WebFetchTool {
+    client: reqwest::Client,
+    cache: FetchCache,
+}
 
 impl WebFetchTool {
     /// Create a new WebFetch tool
-    pub fn new() -> Self {
-        Self
+    ///
+    /// Pages are cached for [`DEFAULT_CACHE_TTL`], keeping up to
+    /// [`DEFAULT_CACHE_CAPACITY`] of them. Fails when the HTTP client
+    /// can't be built, e.g. because no TLS backend is available.
+    pub fn new() -> Result<Self, reqwest::Error> {
+        Ok(Self {
+            client: Self::client_builder().build()?,
+            cache: FetchCache::new(DEFAULT_CACHE_TTL, DEFAULT_CACHE_CAPACITY),
+        })
+    }
+
+    /// Client settings shared by every fetch
+    fn client_builder() -> reqwest::ClientBuilder {
+        // Redirects are followed in `fetch` so each hop can be checked
+        reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .user_agent(USER_AGENT)
+    }
+
+    /// Set how long fetched pages are cached and how many are kept
+    ///
+    /// A capacity of 0 disables the cache.
+    #[must_use]
+    pub fn with_cache(mut self, ttl: Duration, capacity: usize) -> Self {
+        self.cache = FetchCache::new(ttl, capacity);
+        self
+    }
+
+    /// Cache key of a URL fetched in the given format
+    ///
+    /// The URL is normalized: scheme and host are lowercased, default ports
+    /// and the fragment are dropped, and an empty path becomes `/`.
+    fn cache_key(url: &str, format: OutputFormat) -> String {
+        let url = match reqwest::Url::parse(url) {
+            Ok(mut parsed) => {
+                parsed.set_fragment(None);
+                parsed.to_string()
+            }
+            Err(_) => url.to_string(),
+        };
+        format!("{} {}", format.as_str(), url)
     }
 
     /// Validate URL format
@@ -40,6 +253,164 @@ impl WebFetchTool {
         }
         Ok(())
     }
+
+    /// Fetch a URL and return its raw body
+    async fn fetch(&self, url: &str, limits: FetchLimits<'_>) -> Result<FetchedPage, String> {
+        tokio::time::timeout(limits.timeout, self.fetch_with_redirects(url, limits))
+            .await
+            .unwrap_or_else(|_| {
+                Err(format!(
+                    "Failed to fetch {}: timed out after {} ms",
+                    url,
+                    limits.timeout.as_millis()
+                ))
+            })
+    }
+
+    /// Fetch a URL, following up to `limits.max_redirects` redirects
+    async fn fetch_with_redirects(
+        &self,
+        url: &str,
+        limits: FetchLimits<'_>,
+    ) -> Result<FetchedPage, String> {
+        let mut current =
+            reqwest::Url::parse(url).map_err(|e| format!("Invalid URL {}: {}", url, e))?;
+        let mut redirects = 0;
+        let mut response = loop {
+            if let Some(host) = current.host_str()
+                && let Some(message) =
+                    domain_blocked_message(current.as_str(), host, limits.domains)
+            {
+                return Err(message);
+            }
+            let pinned = check_destination(&current, limits.allow_local).await?;
+            let client = match (pinned, current.host_str()) {
+                // Connect to the addresses just checked, so the host can't
+                // resolve to a local address for the request itself
+                (Some(addresses), Some(host)) => Self::client_builder()
+                    .resolve_to_addrs(host, &addresses)
+                    .build()
+                    .map_err(|e| format!("Failed to fetch {}: {}", current, e))?,
+                _ => self.client.clone(),
+            };
+            let response = client
+                .get(current.clone())
+                .send()
+                .await
+                .map_err(|e| format!("Failed to fetch {}: {}", current, e))?;
+
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|v| v.to_str().ok());
+            let Some(location) = location.filter(|_| response.status().is_redirection()) else {
+                break response;
+            };
+            if redirects == limits.max_redirects {
+                return Err(format!(
+                    "Failed to fetch {}: stopped after {} redirects",
+                    url, limits.max_redirects
+                ));
+            }
+            current = current
+                .join(location)
+                .map_err(|e| format!("Invalid redirect from {} to {}: {}", current, location, e))?;
+            redirects += 1;
+        };
+
+        let status = response.status();
+        let final_url = current.to_string();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+
+        if !status.is_success() {
+            return Err(format!("Failed to fetch {}: HTTP {}", url, status));
+        }
+
+        // Read the body in chunks, stopping once it exceeds the limit
+        let too_large = || {
+            format!(
+                "Failed to fetch {}: the response is larger than {} bytes",
+                url, limits.max_body_bytes
+            )
+        };
+        if response
+            .content_length()
+            .is_some_and(|len| usize::try_from(len).map_or(true, |len| len > limits.max_body_bytes))
+        {
+            return Err(too_large());
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response
+            .chunk()
+            .await
+            .map_err(|e| format!("Failed to read response body from {}: {}", url, e))?
+        {
+            if body.len() + chunk.len() > limits.max_body_bytes {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+
+        Ok(FetchedPage {
+            final_url,
+            redirects,
+            status: status.as_u16(),
+            content_type,
+            body,
+        })
+    }
+
+    /// Convert a fetched page to the text returned to the model
+    ///
+    /// HTML is converted to `format` and PDFs to plain text; other text
+    /// formats are returned as-is.
+    async fn page_to_text(page: &FetchedPage, format: OutputFormat) -> Result<String, String> {
+        if Self::is_pdf(page) {
+            let text = extract_pdf_text(page.body.clone()).await?;
+            return Ok(format!("{}\n\n{}", PDF_FORMATTING_NOTE, text));
+        }
+
+        let body = String::from_utf8_lossy(&page.body);
+        let is_html = page.content_type.contains("html")
+            || (page.content_type.is_empty() && Self::looks_like_html(&body));
+        Ok(match format {
+            OutputFormat::Markdown if is_html => html_to_markdown(&body, Some(&page.final_url)),
+            OutputFormat::Text if is_html => html_to_text(&body),
+            _ => body.into_owned(),
+        })
+    }
+
+    /// Detect PDFs by content type, URL extension or magic bytes
+    fn is_pdf(page: &FetchedPage) -> bool {
+        let url_path = page.final_url.split(['?', '#']).next().unwrap_or_default();
+        page.content_type.contains("application/pdf")
+            || has_pdf_extension(Path::new(url_path))
+            || is_pdf_bytes(&page.body)
+    }
+
+    /// Sniff HTML content when the server omits the Content-Type header
+    fn looks_like_html(body: &str) -> bool {
+        let head: String = body.trim_start().chars().take(64).collect();
+        let head = head.to_ascii_lowercase();
+        head.starts_with("<!doctype html") || head.starts_with("<html")
+    }
+
+    /// Safely truncate string to maximum size, respecting UTF-8 character boundaries
+    fn safe_truncate(s: &mut String, max_len: usize) {
+        if s.len() > max_len {
+            let mut truncate_at = max_len;
+            while truncate_at > 0 && !s.is_char_boundary(truncate_at) {
+                truncate_at -= 1;
+            }
+            s.truncate(truncate_at);
+            s.push_str("\n... (content truncated)");
+        }
+    }
 }
 
 #[async_trait]
@@ -52,7 +423,8 @@ impl Tool for WebFetchTool {
         "Fetches content from a specified URL and processes it using an AI model. \
          Takes a URL and a prompt as input, fetches the URL content, converts HTML to markdown, \
          and processes the content with the prompt. Use this tool when you need to retrieve \
-         and analyze web content."
+         and analyze web content. Links are kept as absolute markdown links that can be \
+         fetched in turn; set format to text or html to get the page in another form."
     }
 
     fn input_schema(&self) -> Value {
@@ -68,6 +440,23 @@ impl Tool for WebFetchTool {
                 "prompt": {
                     "type": "string",
                     "description": "The prompt to run on the fetched content"
+                },
+                "format": {
+                    "type": "string",
+                    "enum": ["markdown", "text", "html"],
+                    "description": "How HTML pages are returned: the main content as markdown (default) or plain text, or the raw HTML"
+                },
+                "timeout_ms": {
+                    "type": "integer",
+                    "minimum": 1,
+                    "maximum": MAX_TIMEOUT_MS,
+                    "description": "Time limit of the fetch in milliseconds, redirects included (default: 30000)"
+                },
+                "max_redirects": {
+                    "type": "integer",
+                    "minimum": 0,
+                    "maximum": MAX_REDIRECTS,
+                    "description": "Number of redirects followed (default: 5)"
                 }
             }
         })
@@ -90,35 +479,209 @@ impl Tool for WebFetchTool {
             return ToolResult::error("Prompt cannot be empty");
         }
 
+        // Check the domain settings before the cache or the network
+        if let Ok(url) = reqwest::Url::parse(&params.url)
+            && let Some(host) = url.host_str()
+            && let Some(message) =
+                domain_blocked_message(&params.url, host, context.web_fetch_domains())
+        {
+            tracing::warn!(url = %params.url, host = %host, "WebFetch blocked by domain settings");
+            return ToolResult::domain_blocked(message, &params.url, host);
+        }
+
         tracing::info!(
-            "WebFetch request for URL: {} with prompt: {} (session: {})",
-            params.url,
-            params.prompt,
-            context.session_id
+            url = %params.url,
+            session_id = %context.session_id,
+            "WebFetch request"
         );
 
-        // Note: Full implementation would:
-        // 1. Use reqwest to fetch the URL content
-        // 2. Convert HTML to markdown
-        // 3. Use AI API to process content with the prompt
-        // 4. Return the processed result
+        let max_redirects = params
+            .max_redirects
+            .unwrap_or(DEFAULT_MAX_REDIRECTS)
+            .min(MAX_REDIRECTS);
 
-        // For now, return a placeholder indicating the tool is available
-        // but requires external HTTP client integration
-        let output = format!(
-            "WebFetch is available but requires HTTP client integration.\n\n\
-             Requested URL: {}\n\
-             Prompt: {}\n\n\
-             To fully implement this tool, add the 'reqwest' crate and configure \
-             an AI API for content processing.",
-            params.url, params.prompt
+        // A page reached through more redirects than this call allows is
+        // fetched again, so the limit is enforced
+        let cache_key = Self::cache_key(&params.url, params.format);
+        if let Some(page) = self
+            .cache
+            .get(&cache_key)
+            .filter(|page| page.redirects <= max_redirects)
+        {
+            let age = page.fetched_at.elapsed().as_secs();
+            tracing::info!(
+                url = %page.final_url,
+                age_secs = age,
+                "WebFetch served from cache"
+            );
+            let output = format!(
+                "Content fetched from {} (cached, fetched {}s ago)\nPrompt: {}\n\n{}",
+                page.final_url, age, params.prompt, page.content
+            );
+            return ToolResult::success(output).with_metadata(json!({
+                "url": params.url,
+                "final_url": page.final_url,
+                "prompt": params.prompt,
+                "status_code": page.status,
+                "content_type": page.content_type,
+                "format": params.format.as_str(),
+                "truncated": page.truncated,
+                "cached": true
+            }));
+        }
+
+        let limits = FetchLimits {
+            timeout: Duration::from_millis(
+                params
+                    .timeout_ms
+                    .unwrap_or(DEFAULT_TIMEOUT_MS)
+                    .clamp(1, MAX_TIMEOUT_MS),
+            ),
+            max_redirects,
+            allow_local: context.allow_localhost_fetch(),
+            domains: context.web_fetch_domains(),
+            max_body_bytes: MAX_BODY_BYTES,
+        };
+        let start = Instant::now();
+        let page = match self.fetch(&params.url, limits).await {
+            Ok(page) => page,
+            Err(e) => return ToolResult::error(e),
+        };
+
+        let mut content = match Self::page_to_text(&page, params.format).await {
+            Ok(content) => content,
+            Err(e) => return ToolResult::error(format!("{} ({})", e, page.final_url)),
+        };
+        let was_truncated = content.len() > MAX_CONTENT_SIZE;
+        Self::safe_truncate(&mut content, MAX_CONTENT_SIZE);
+
+        tracing::info!(
+            url = %page.final_url,
+            status = page.status,
+            content_type = %page.content_type,
+            content_size = content.len(),
+            was_truncated = was_truncated,
+            elapsed_ms = start.elapsed().as_millis(),
+            "WebFetch completed"
         );
 
-        ToolResult::success(output).with_metadata(json!({
+        let output = format!(
+            "Content fetched from {}\nPrompt: {}\n\n{}",
+            page.final_url, params.prompt, content
+        );
+        let metadata = json!({
             "url": params.url,
+            "final_url": page.final_url,
             "prompt": params.prompt,
-            "status": "stub_implementation"
-        }))
+            "status_code": page.status,
+            "content_type": page.content_type,
+            "format": params.format.as_str(),
+            "truncated": was_truncated,
+            "cached": false
+        });
+
+        self.cache.insert(
+            cache_key,
+            CachedPage {
+                final_url: page.final_url,
+                redirects: page.redirects,
+                status: page.status,
+                content_type: page.content_type,
+                content,
+                truncated: was_truncated,
+                fetched_at: Instant::now(),
+            },
+        );
+
+        ToolResult::success(output).with_metadata(metadata)
+    }
+
+    fn clear_cache(&self) {
+        self.cache.clear();
+    }
+}
+
+/// Message refusing to fetch `url` if the domain settings block `host`
+fn domain_blocked_message(url: &str, host: &str, domains: &DomainFilter) -> Option<String> {
+    let block = domains.check(host).err()?;
+    Some(format!(
+        "Permission denied fetching {}: {}",
+        url,
+        block.describe(host, "webFetch")
+    ))
+}
+
+/// Reject URLs that aren't http(s) or that point at local addresses
+///
+/// Domains are resolved and refused if any of their addresses is local;
+/// the checked addresses are returned so the request can be sent to them.
+/// Returns None if there is nothing to pin (an IP address, or local
+/// addresses are allowed).
+async fn check_destination(
+    url: &reqwest::Url,
+    allow_local: bool,
+) -> Result<Option<Vec<SocketAddr>>, String> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(format!(
+            "Refusing to fetch {}: only http and https URLs are supported",
+            url
+        ));
+    }
+    if allow_local {
+        return Ok(None);
+    }
+
+    let Some(host) = url.host_str() else {
+        return Err(format!("Invalid URL {}: it has no host", url));
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    let port = url.port_or_known_default().unwrap_or(80);
+    let (addresses, pin) = if let Ok(ip) = host.parse() {
+        (vec![SocketAddr::new(ip, port)], false)
+    } else {
+        let addresses = tokio::net::lookup_host((host, port))
+            .await
+            .map_err(|e| format!("Failed to fetch {}: cannot resolve {}: {}", url, host, e))?
+            .collect();
+        (addresses, true)
+    };
+    if let Some(address) = addresses
+        .iter()
+        .find(|address| is_local_address(address.ip()))
+    {
+        return Err(format!(
+            "Refusing to fetch {}: {} is a local address. Set allowLocalhostFetch in settings \
+             to allow fetching local and private addresses.",
+            url,
+            address.ip()
+        ));
+    }
+    Ok(pin.then_some(addresses))
+}
+
+/// Whether an address is loopback, private, link-local or otherwise not
+/// publicly routable
+fn is_local_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            ip.is_loopback()
+                || ip.is_private()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                // Shared address space (100.64.0.0/10)
+                || (a == 100 && (64..128).contains(&b))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_local_address(IpAddr::V4(ip)),
+            None => {
+                ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_unique_local()
+                    || ip.is_unicast_link_local()
+            }
+        },
     }
 }
 
@@ -126,10 +689,71 @@ impl Tool for WebFetchTool {
 mod tests {
     use super::*;
     use tempfile::TempDir;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve a single HTTP response on a local port and return its URL
+    async fn serve_once(content_type: &'static str, body: impl Into<Vec<u8>>) -> String {
+        let body = body.into();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            if let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 4096];
+                if socket.read(&mut request).await.is_ok() {
+                    let header = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        content_type,
+                        body.len()
+                    );
+                    let mut response = header.into_bytes();
+                    response.extend_from_slice(&body);
+                    socket.write_all(&response).await.ok();
+                }
+            }
+        });
+        format!("http://{}/", addr)
+    }
+
+    /// Answer every request with a redirect to `/`, returning the server's URL
+    async fn serve_redirect_loop() -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 4096];
+                if socket.read(&mut request).await.is_ok() {
+                    let response = "HTTP/1.1 302 Found\r\nLocation: /\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+                    socket.write_all(response.as_bytes()).await.ok();
+                }
+            }
+        });
+        format!("http://{}/", addr)
+    }
+
+    /// Answer every request with a redirect to `location`, returning the
+    /// server's URL
+    async fn serve_redirect(location: String) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 4096];
+                if socket.read(&mut request).await.is_ok() {
+                    let response = format!(
+                        "HTTP/1.1 302 Found\r\nLocation: {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        location
+                    );
+                    socket.write_all(response.as_bytes()).await.ok();
+                }
+            }
+        });
+        format!("http://{}/", addr)
+    }
 
     #[test]
     fn test_web_fetch_properties() {
-        let tool = WebFetchTool::new();
+        let tool = WebFetchTool::new().unwrap();
         assert_eq!(tool.name(), "WebFetch");
         assert!(tool.description().contains("URL"));
         assert!(tool.description().contains("content"));
@@ -137,7 +761,7 @@ mod tests {
 
     #[test]
     fn test_web_fetch_input_schema() {
-        let tool = WebFetchTool::new();
+        let tool = WebFetchTool::new().unwrap();
         let schema = tool.input_schema();
 
         assert_eq!(schema["type"], "object");
@@ -173,29 +797,354 @@ mod tests {
     #[tokio::test]
     async fn test_web_fetch_execute() {
         let temp_dir = TempDir::new().unwrap();
-        let tool = WebFetchTool::new();
-        let context = ToolContext::new("test-session", temp_dir.path());
+        let tool = WebFetchTool::new().unwrap();
+        let context =
+            ToolContext::new("test-session", temp_dir.path()).with_allow_localhost_fetch(true);
+        let url = serve_once(
+            "text/html; charset=utf-8",
+            "<html><body><h1>API</h1><table>\
+             <tr><th>Method</th><th>Path</th></tr>\
+             <tr><td>GET</td><td>/users</td></tr>\
+             </table></body></html>",
+        )
+        .await;
 
         let result = tool
             .execute(
                 json!({
-                    "url": "https://example.com",
+                    "url": url,
                     "prompt": "Extract the main content"
                 }),
                 &context,
             )
             .await;
 
-        // Should succeed (stub implementation)
-        assert!(!result.is_error);
-        assert!(result.content.contains("WebFetch"));
-        assert!(result.content.contains("https://example.com"));
+        assert!(!result.is_error, "unexpected error: {}", result.content);
+        assert!(result.content.contains(&url));
+        assert!(result.content.contains("API\n==="));
+        assert!(
+            result
+                .content
+                .contains("|Method| Path |\n|------|------|\n| GET  |/users|")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_web_fetch_formats() {
+        let temp_dir = TempDir::new().unwrap();
+        let tool = WebFetchTool::new().unwrap();
+        let context =
+            ToolContext::new("test-session", temp_dir.path()).with_allow_localhost_fetch(true);
+        let html = "<html><body><nav>Menu</nav><main><p>See <a href=\"/docs/next\">next</a>.</p>\
+                    <script>track()</script></main></body></html>";
+
+        let url = serve_once("text/html", html).await;
+        let result = tool
+            .execute(json!({"url": url, "prompt": "Read"}), &context)
+            .await;
+        assert!(!result.is_error, "unexpected error: {}", result.content);
+        assert!(
+            result
+                .content
+                .ends_with(&format!("\n\nSee [next]({url}docs/next)."))
+        );
+        assert_eq!(result.metadata.unwrap()["format"], "markdown");
+
+        let url = serve_once("text/html", html).await;
+        let result = tool
+            .execute(
+                json!({"url": url, "prompt": "Read", "format": "text"}),
+                &context,
+            )
+            .await;
+        assert!(result.content.ends_with("\n\nSee next."));
+
+        let url = serve_once("text/html", html).await;
+        let result = tool
+            .execute(
+                json!({"url": url, "prompt": "Read", "format": "html"}),
+                &context,
+            )
+            .await;
+        assert!(result.content.ends_with(html));
+
+        let result = tool
+            .execute(
+                json!({"url": url, "prompt": "Read", "format": "pdf"}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error);
+    }
+
+    #[tokio::test]
+    async fn test_web_fetch_refuses_local_addresses() {
+        let temp_dir = TempDir::new().unwrap();
+        let tool = WebFetchTool::new().unwrap();
+        let context = ToolContext::new("test-session", temp_dir.path());
+        let url = serve_once("text/plain", "internal").await;
+
+        let result = tool
+            .execute(json!({"url": url, "prompt": "Read"}), &context)
+            .await;
+        assert!(result.is_error);
+        assert!(result.content.contains("127.0.0.1 is a local address"));
+        assert!(result.content.contains("allowLocalhostFetch"));
+
+        // Hosts resolving to a local address are refused before connecting
+        let localhost = reqwest::Url::parse("http://localhost:1/").unwrap();
+        assert!(check_destination(&localhost, false).await.is_err());
+        // IP addresses need no pinning
+        let public = reqwest::Url::parse("http://93.184.216.34/").unwrap();
+        assert_eq!(check_destination(&public, false).await, Ok(None));
+
+        assert!(is_local_address("10.1.2.3".parse().unwrap()));
+        assert!(is_local_address("169.254.169.254".parse().unwrap()));
+        assert!(is_local_address("::ffff:192.168.0.1".parse().unwrap()));
+        assert!(is_local_address("fd00::1".parse().unwrap()));
+        assert!(!is_local_address("93.184.216.34".parse().unwrap()));
+        assert!(!is_local_address("2606:2800:220:1::".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_web_fetch_domain_settings() {
+        let temp_dir = TempDir::new().unwrap();
+        let tool = WebFetchTool::new().unwrap();
+        let domains = DomainFilter::new(
+            vec!["127.0.0.1".to_string(), "*.example.com".to_string()],
+            vec!["private.example.com".to_string()],
+        );
+        let context = ToolContext::new("test-session", temp_dir.path())
+            .with_allow_localhost_fetch(true)
+            .with_web_fetch_domains(domains);
+
+        let result = tool
+            .execute(
+                json!({"url": "https://docs.rs/serde", "prompt": "Read"}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error);
+        assert_eq!(
+            result.content,
+            "Permission denied fetching https://docs.rs/serde: docs.rs isn't in webFetchAllowedDomains"
+        );
+        let metadata = result.metadata.unwrap();
+        assert_eq!(metadata["error_kind"], "permission_denied");
+        assert_eq!(metadata["host"], "docs.rs");
+
+        // Denied wins over allowed
+        let result = tool
+            .execute(
+                json!({"url": "https://private.example.com/", "prompt": "Read"}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error);
+        assert!(
+            result
+                .content
+                .contains("matches private.example.com in webFetchDeniedDomains")
+        );
+
+        // Redirects to blocked hosts aren't followed
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            if let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = [0u8; 4096];
+                if socket.read(&mut request).await.is_ok() {
+                    let response = "HTTP/1.1 302 Found\r\nLocation: http://localhost/\r\nContent-Length: 0\r\nConnection: close\r\n\r\n";
+                    socket.write_all(response.as_bytes()).await.ok();
+                }
+            }
+        });
+        let result = tool
+            .execute(json!({"url": url, "prompt": "Read"}), &context)
+            .await;
+        assert!(result.is_error);
+        assert_eq!(
+            result.content,
+            "Permission denied fetching http://localhost/: localhost isn't in webFetchAllowedDomains"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_web_fetch_body_size_limit() {
+        let tool = WebFetchTool::new().unwrap();
+        let domains = DomainFilter::default();
+        let limits = FetchLimits {
+            timeout: Duration::from_secs(10),
+            max_redirects: 0,
+            allow_local: true,
+            domains: &domains,
+            max_body_bytes: 16,
+        };
+
+        let url = serve_once("text/plain", "x".repeat(16)).await;
+        let page = tool.fetch(&url, limits).await.unwrap();
+        assert_eq!(page.body.len(), 16);
+
+        let url = serve_once("text/plain", "x".repeat(17)).await;
+        let error = tool.fetch(&url, limits).await.unwrap_err();
+        assert!(
+            error.ends_with("the response is larger than 16 bytes"),
+            "{error}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_web_fetch_redirect_and_time_limits() {
+        let temp_dir = TempDir::new().unwrap();
+        let tool = WebFetchTool::new().unwrap();
+        let context =
+            ToolContext::new("test-session", temp_dir.path()).with_allow_localhost_fetch(true);
+
+        let url = serve_redirect_loop().await;
+        let result = tool
+            .execute(
+                json!({"url": url, "prompt": "Read", "max_redirects": 2}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error);
+        assert!(result.content.contains("stopped after 2 redirects"));
+
+        // A server that accepts the connection but never answers
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let result = tool
+            .execute(
+                json!({"url": url, "prompt": "Read", "timeout_ms": 100}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error);
+        assert!(result.content.contains("timed out after 100 ms"));
+    }
+
+    #[tokio::test]
+    async fn test_web_fetch_cache() {
+        let temp_dir = TempDir::new().unwrap();
+        let tool = WebFetchTool::new().unwrap();
+        let context =
+            ToolContext::new("test-session", temp_dir.path()).with_allow_localhost_fetch(true);
+        // The server answers only once, so later fetches must hit the cache
+        let url = serve_once("text/plain", "cached body").await;
+
+        let result = tool
+            .execute(json!({"url": url, "prompt": "Read"}), &context)
+            .await;
+        assert!(!result.is_error, "unexpected error: {}", result.content);
+        assert_eq!(result.metadata.unwrap()["cached"], false);
+
+        // Same URL up to normalization
+        let variant = format!("{}#section", url.trim_end_matches('/'));
+        let result = tool
+            .execute(json!({"url": variant, "prompt": "Read again"}), &context)
+            .await;
+        assert!(!result.is_error, "unexpected error: {}", result.content);
+        assert!(result.content.contains("(cached, fetched"));
+        assert!(result.content.contains("Prompt: Read again\n\ncached body"));
+        assert_eq!(result.metadata.unwrap()["cached"], true);
+
+        tool.clear_cache();
+        let result = tool
+            .execute(json!({"url": url, "prompt": "Read"}), &context)
+            .await;
+        assert!(result.is_error);
+
+        // A page cached after a redirect isn't served past a lower limit
+        let target = serve_once("text/plain", "moved body").await;
+        let url = serve_redirect(target).await;
+        let result = tool
+            .execute(json!({"url": url, "prompt": "Read"}), &context)
+            .await;
+        assert!(!result.is_error, "unexpected error: {}", result.content);
+        let result = tool
+            .execute(
+                json!({"url": url, "prompt": "Read", "max_redirects": 0}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error);
+        assert!(result.content.contains("stopped after 0 redirects"));
+        let result = tool
+            .execute(
+                json!({"url": url, "prompt": "Read", "max_redirects": 1}),
+                &context,
+            )
+            .await;
+        assert_eq!(result.metadata.unwrap()["cached"], true);
+    }
+
+    #[test]
+    fn test_fetch_cache_evicts_least_recently_used() {
+        let cache = FetchCache::new(DEFAULT_CACHE_TTL, 2);
+        let page = |content: &str| CachedPage {
+            final_url: String::new(),
+            redirects: 0,
+            status: 200,
+            content_type: String::new(),
+            content: content.to_string(),
+            truncated: false,
+            fetched_at: Instant::now(),
+        };
+        cache.insert("a".to_string(), page("a"));
+        cache.insert("b".to_string(), page("b"));
+        assert!(cache.get("a").is_some());
+        cache.insert("c".to_string(), page("c"));
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("c").is_some());
+
+        let expired = FetchCache::new(Duration::ZERO, 2);
+        expired.insert("a".to_string(), page("a"));
+        assert!(expired.get("a").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_page_to_text_keeps_plain_text() {
+        let page = FetchedPage {
+            final_url: "https://example.com/data.txt".to_string(),
+            redirects: 0,
+            status: 200,
+            content_type: "text/plain".to_string(),
+            body: b"<b>not html</b>".to_vec(),
+        };
+        assert_eq!(
+            WebFetchTool::page_to_text(&page, OutputFormat::Markdown)
+                .await
+                .unwrap(),
+            "<b>not html</b>"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_web_fetch_pdf() {
+        let temp_dir = TempDir::new().unwrap();
+        let tool = WebFetchTool::new().unwrap();
+        let context =
+            ToolContext::new("test-session", temp_dir.path()).with_allow_localhost_fetch(true);
+        let url = serve_once(
+            "application/pdf",
+            crate::mcp::tools::pdf::tests::sample_pdf("Quarterly report"),
+        )
+        .await;
+
+        let result = tool
+            .execute(json!({"url": url, "prompt": "Summarize"}), &context)
+            .await;
+
+        assert!(!result.is_error, "unexpected error: {}", result.content);
+        assert!(result.content.contains("Quarterly report"));
+        assert!(result.content.contains(PDF_FORMATTING_NOTE));
     }
 
     #[tokio::test]
     async fn test_web_fetch_invalid_url() {
         let temp_dir = TempDir::new().unwrap();
-        let tool = WebFetchTool::new();
+        let tool = WebFetchTool::new().unwrap();
         let context = ToolContext::new("test-session", temp_dir.path());
 
         let result = tool
@@ -215,7 +1164,7 @@ mod tests {
     #[tokio::test]
     async fn test_web_fetch_empty_prompt() {
         let temp_dir = TempDir::new().unwrap();
-        let tool = WebFetchTool::new();
+        let tool = WebFetchTool::new().unwrap();
         let context = ToolContext::new("test-session", temp_dir.path());
 
         let result = tool
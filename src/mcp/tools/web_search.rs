@@ -2,12 +2,17 @@
 //!
 //! Searches the web and returns results to inform responses.
 //! Note: Full implementation requires external search API integration.
+//!
+//! Domains the query asks for (`allowed_domains` and `site:` operators) are
+//! checked against the `webSearchAllowedDomains` and
+//! `webSearchDeniedDomains` settings before searching.
 
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::{Value, json};
 
 use super::base::Tool;
+use super::domain_filter::DomainFilter;
 use crate::mcp::registry::{ToolContext, ToolResult};
 
 /// Input parameters for WebSearch
@@ -32,6 +37,26 @@ impl WebSearchTool {
     pub fn new() -> Self {
         Self
     }
+
+    /// First domain the query asks for that the domain settings block
+    ///
+    /// Returns the domain and the error message.
+    fn blocked_domain(params: &WebSearchInput, domains: &DomainFilter) -> Option<(String, String)> {
+        let site_operators = params
+            .query
+            .split_whitespace()
+            .filter_map(|term| term.strip_prefix("site:"));
+        let requested = params.allowed_domains.iter().flatten().map(String::as_str);
+        site_operators.chain(requested).find_map(|domain| {
+            let block = domains.check(domain).err()?;
+            let message = format!(
+                "Permission denied searching {}: {}",
+                domain,
+                block.describe(domain, "webSearch")
+            );
+            Some((domain.to_string(), message))
+        })
+    }
 }
 
 #[async_trait]
@@ -83,6 +108,12 @@ impl Tool for WebSearchTool {
             return ToolResult::error("Search query must be at least 2 characters");
         }
 
+        if let Some((domain, message)) = Self::blocked_domain(&params, context.web_search_domains())
+        {
+            tracing::warn!(domain = %domain, "WebSearch blocked by domain settings");
+            return ToolResult::domain_blocked(message, &params.query, &domain);
+        }
+
         tracing::info!(
             "WebSearch request for query: {} (session: {})",
             params.query,
@@ -91,7 +122,7 @@ impl Tool for WebSearchTool {
 
         // Note: Full implementation would:
         // 1. Call an external search API (Google, Bing, etc.)
-        // 2. Filter results by allowed/blocked domains
+        // 2. Filter results by allowed/blocked domains and the domain settings
         // 3. Format results as markdown with hyperlinks
         // 4. Return structured search results
 
@@ -109,6 +140,19 @@ impl Tool for WebSearchTool {
         if let Some(ref blocked) = params.blocked_domains {
             output.push_str(&format!("Blocked domains: {}\n", blocked.join(", ")));
         }
+        let domains = context.web_search_domains();
+        if !domains.allowed().is_empty() {
+            output.push_str(&format!(
+                "Domains allowed by settings: {}\n",
+                domains.allowed().join(", ")
+            ));
+        }
+        if !domains.denied().is_empty() {
+            output.push_str(&format!(
+                "Domains denied by settings: {}\n",
+                domains.denied().join(", ")
+            ));
+        }
 
         output.push_str(
             "\nTo fully implement this tool, integrate with a search API \
@@ -197,6 +241,51 @@ mod tests {
         assert!(result.content.contains("stackoverflow.com"));
     }
 
+    #[tokio::test]
+    async fn test_web_search_domain_settings() {
+        let temp_dir = TempDir::new().unwrap();
+        let tool = WebSearchTool::new();
+        let context = ToolContext::new("test-session", temp_dir.path()).with_web_search_domains(
+            DomainFilter::new(
+                vec!["docs.rs".to_string()],
+                vec!["evil.docs.rs".to_string()],
+            ),
+        );
+
+        let result = tool
+            .execute(json!({"query": "serde site:docs.rs"}), &context)
+            .await;
+        assert!(!result.is_error);
+        assert!(
+            result
+                .content
+                .contains("Domains allowed by settings: docs.rs")
+        );
+
+        let result = tool
+            .execute(json!({"query": "serde site:crates.io"}), &context)
+            .await;
+        assert!(result.is_error);
+        assert_eq!(
+            result.content,
+            "Permission denied searching crates.io: crates.io isn't in webSearchAllowedDomains"
+        );
+        assert_eq!(result.metadata.unwrap()["error_kind"], "permission_denied");
+
+        let result = tool
+            .execute(
+                json!({"query": "serde", "allowed_domains": ["docs.rs", "evil.docs.rs"]}),
+                &context,
+            )
+            .await;
+        assert!(result.is_error);
+        assert!(
+            result
+                .content
+                .contains("evil.docs.rs matches evil.docs.rs in webSearchDeniedDomains")
+        );
+    }
+
     #[tokio::test]
     async fn test_web_search_short_query() {
         let temp_dir = TempDir::new().unwrap();
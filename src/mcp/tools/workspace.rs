@@ -0,0 +1,90 @@
+//! Workspace boundaries for tools that create or remove paths
+//!
+//! The workspace is the session's working directory plus the
+//! `additionalDirectories` permission setting. Paths are compared after
+//! resolving `..` and following symlinks in the part that already exists,
+//! so `../x` or a symlink out of the tree can't escape it.
+
+use serde_json::json;
+use std::path::{Component, Path, PathBuf};
+
+use crate::mcp::registry::{ToolContext, ToolResult};
+
+/// Directories that make up the workspace, resolved
+pub async fn workspace_roots(context: &ToolContext) -> Vec<PathBuf> {
+    let mut roots = vec![context.cwd.clone()];
+    if let Some(checker) = &context.permission_checker
+        && let Some(dirs) = checker.read().await.additional_directories()
+    {
+        roots.extend(dirs.iter().map(|dir| context.cwd.join(dir)));
+    }
+    roots.iter().map(|root| resolve(&normalize(root))).collect()
+}
+
+/// Return an error result if `path` is outside the workspace
+///
+/// `action` describes the operation for the message, e.g. "delete".
+pub async fn reject_outside_workspace(
+    action: &str,
+    path: &Path,
+    context: &ToolContext,
+) -> Option<ToolResult> {
+    let roots = workspace_roots(context).await;
+    if is_within(path, &roots) {
+        return None;
+    }
+    Some(
+        ToolResult::error(format!(
+            "Refusing to {action} {}: it is outside the workspace ({})",
+            path.display(),
+            context.cwd.display()
+        ))
+        .with_metadata(json!({
+            "error_kind": "outside_workspace",
+            "path": path.display().to_string(),
+        })),
+    )
+}
+
+/// Whether `path` lies within one of `roots`, following symlinks in the
+/// part of `path` that already exists
+pub fn is_within(path: &Path, roots: &[PathBuf]) -> bool {
+    let path = resolve(path);
+    roots.iter().any(|root| path.starts_with(root))
+}
+
+/// Remove `.` and `..` components without touching the filesystem
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// Canonicalize the longest existing prefix of `path` and append the rest
+pub fn resolve(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return rest
+                .iter()
+                .rev()
+                .fold(canonical, |acc, name| acc.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
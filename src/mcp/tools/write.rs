@@ -1,15 +1,32 @@
 //! Write tool implementation
 //!
-//! Writes content to files on the filesystem.
+//! Writes content to files on the filesystem. With `dry_run` the write is
+//! only previewed as a diff. A file Read decoded from another encoding is
+//! written back in that encoding.
 
 use async_trait::async_trait;
 use serde::Deserialize;
 use serde_json::json;
+use std::borrow::Cow;
+use std::path::Path;
 use std::time::Instant;
 
 use super::base::{Tool, ToolKind};
+use super::encoding::{FileEncoding, decode};
+use super::file_lock::lock_file;
+use super::io_retry::retry_transient;
+use super::locked_file::write_atomic;
+use super::preview::preview_result;
+use super::special_file::reject_special_file;
+use super::verify::verify_written;
 use crate::mcp::registry::{ToolContext, ToolResult};
 
+/// Content size above which free disk space is checked before writing (10MB)
+const DISK_SPACE_CHECK_THRESHOLD: u64 = 10 * 1024 * 1024;
+
+/// Free space kept in reserve beyond the written content (1MB)
+const DISK_SPACE_MARGIN: u64 = 1024 * 1024;
+
 /// Write tool for creating/overwriting files
 #[derive(Debug, Default)]
 pub struct WriteTool;
@@ -17,10 +34,15 @@ pub struct WriteTool;
 /// Write tool input parameters
 #[derive(Debug, Deserialize)]
 struct WriteInput {
-    /// Path to the file to write
+    /// Path to the file to write (canonical name; `path` and `filePath` are
+    /// accepted as aliases)
+    #[serde(alias = "path", alias = "filePath")]
     file_path: String,
     /// Content to write to the file
     content: String,
+    /// Only preview the write as a diff, without writing it (default: false)
+    #[serde(default)]
+    dry_run: bool,
 }
 
 impl WriteTool {
@@ -66,6 +88,10 @@ impl Tool for WriteTool {
                 "content": {
                     "type": "string",
                     "description": "The content to write to the file"
+                },
+                "dry_run": {
+                    "type": "boolean",
+                    "description": "Return the diff against the current file without writing it, e.g. to show a proposed change before making it. Default: false"
                 }
             }
         })
@@ -98,14 +124,73 @@ impl Tool for WriteTool {
             context.cwd.join(&params.file_path)
         };
 
+        // Replacing a FIFO, device or socket with a regular file is never intended
+        if let Some(result) = reject_special_file("write", &path) {
+            return result;
+        }
+
+        if params.dry_run {
+            return match retry_transient(|| tokio::fs::read(&path)).await {
+                Ok(bytes) => {
+                    let old = decode(&bytes).map_or_else(
+                        || String::from_utf8_lossy(&bytes).into_owned(),
+                        |(old, _)| old,
+                    );
+                    preview_result(&path, &old, &params.content, false)
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    preview_result(&path, "", &params.content, true)
+                }
+                Err(e) => ToolResult::io_error("reading", &path, &e, &context.cwd),
+            };
+        }
+
         let total_start = Instant::now();
 
+        // Write a file Read decoded from another encoding back in that encoding
+        let encoding = context
+            .file_encodings()
+            .and_then(|encodings| encodings.get(&path))
+            .unwrap_or(FileEncoding::UTF8);
+        let content: Cow<'_, [u8]> = if encoding.is_plain_utf8() {
+            Cow::Borrowed(params.content.as_bytes())
+        } else {
+            match encoding.encode(&params.content) {
+                Ok(bytes) => Cow::Owned(bytes),
+                Err(e) => {
+                    return ToolResult::error(format!("Cannot write {}: {}", path.display(), e));
+                }
+            }
+        };
+
+        if let Some(max_file_size) = context.max_file_size()
+            && content.len() as u64 > max_file_size
+        {
+            #[allow(clippy::cast_precision_loss)]
+            let to_mb = |bytes: u64| bytes as f64 / 1024.0 / 1024.0;
+            return ToolResult::file_too_large(
+                format!(
+                    "Content too large to write ({:.1}MB, limit {:.1}MB)",
+                    to_mb(content.len() as u64),
+                    to_mb(max_file_size)
+                ),
+                &path,
+                content.len() as u64,
+                max_file_size,
+            );
+        }
+
+        // Fail early rather than leave a half-written file on a full disk
+        if let Err(e) = check_disk_space(&path, content.len() as u64) {
+            return ToolResult::error(e);
+        }
+
         // Create parent directories if they don't exist
         if let Some(parent) = path.parent() {
             if !parent.exists() {
                 let dir_start = Instant::now();
-                if let Err(e) = tokio::fs::create_dir_all(parent).await {
-                    return ToolResult::error(format!("Failed to create directory: {}", e));
+                if let Err(e) = retry_transient(|| tokio::fs::create_dir_all(parent)).await {
+                    return ToolResult::io_error("creating directory", parent, &e, &context.cwd);
                 }
                 tracing::debug!(
                     parent_dir = %parent.display(),
@@ -118,16 +203,31 @@ impl Tool for WriteTool {
         // Check if file exists (for reporting)
         let file_existed = path.exists();
 
-        // Write content to file
+        // Write content to file, waiting for edits in progress on it
+        let _lock = lock_file(&path).await;
         let write_start = Instant::now();
-        match tokio::fs::write(&path, &params.content).await {
+        match retry_transient(|| write_atomic(&path, &content)).await {
             Ok(()) => {
+                if context.verify_writes()
+                    && let Err(message) = verify_written(&path, &content).await
+                {
+                    tracing::error!(
+                        file_path = %path.display(),
+                        error = %message,
+                        "File write verification failed"
+                    );
+                    return ToolResult::error(message).with_metadata(json!({
+                        "path": path.display().to_string(),
+                        "verification_failed": true
+                    }));
+                }
+
                 let write_duration = write_start.elapsed();
                 let total_elapsed = total_start.elapsed();
 
                 let action = if file_existed { "Updated" } else { "Created" };
                 let lines = params.content.lines().count();
-                let bytes = params.content.len();
+                let bytes = content.len();
 
                 tracing::info!(
                     file_path = %path.display(),
@@ -139,18 +239,36 @@ impl Tool for WriteTool {
                     "File write successful"
                 );
 
-                ToolResult::success(format!(
+                let mut output = format!(
                     "{} {} ({} lines, {} bytes)",
                     action,
                     path.display(),
                     lines,
                     bytes
-                ))
-                .with_metadata(json!({
+                );
+                if !encoding.is_plain_utf8() {
+                    output.push_str(&format!(
+                        "\nKept the file's encoding ({}).",
+                        encoding.describe()
+                    ));
+                }
+                let final_newline = context.final_newline();
+                if let Some(note) = final_newline.note(&params.content) {
+                    output.push('\n');
+                    output.push_str(note);
+                }
+
+                ToolResult::success(output).with_metadata(json!({
                     "path": path.display().to_string(),
                     "created": !file_existed,
                     "lines": lines,
                     "bytes": bytes,
+                    "encoding": encoding.name(),
+                    "bom": encoding.has_bom(),
+                    "verified": context.verify_writes(),
+                    "ends_with_newline": final_newline
+                        .is_enabled()
+                        .then(|| params.content.ends_with('\n')),
                     "write_duration_ms": write_duration.as_millis(),
                     "total_elapsed_ms": total_elapsed.as_millis()
                 }))
@@ -163,15 +281,64 @@ impl Tool for WriteTool {
                     elapsed_ms = elapsed.as_millis(),
                     "File write failed"
                 );
-                ToolResult::error(format!("Failed to write file: {}", e))
+                ToolResult::io_error("writing", &path, &e, &context.cwd)
             }
         }
     }
 }
 
+/// Free space a write of `content_len` bytes needs, including the margin
+///
+/// The old file is only released once the temporary file replaces it, so
+/// the whole content counts even when overwriting.
+fn required_space(content_len: u64) -> u64 {
+    content_len + DISK_SPACE_MARGIN
+}
+
+/// Check that the filesystem holding `path` has room for a large write
+///
+/// Small writes and filesystems whose free space can't be determined are
+/// not checked.
+fn check_disk_space(path: &Path, content_len: u64) -> Result<(), String> {
+    if content_len <= DISK_SPACE_CHECK_THRESHOLD {
+        return Ok(());
+    }
+    // The file and its parent directories may not exist yet
+    let Some(existing_dir) = path.ancestors().skip(1).find(|dir| dir.is_dir()) else {
+        return Ok(());
+    };
+    let available = match fs4::available_space(existing_dir) {
+        Ok(available) => available,
+        Err(e) => {
+            tracing::debug!(
+                dir = %existing_dir.display(),
+                error = %e,
+                "Could not determine free disk space, skipping check"
+            );
+            return Ok(());
+        }
+    };
+
+    let required = required_space(content_len);
+    if available < required {
+        #[allow(clippy::cast_precision_loss)]
+        let to_mb = |bytes: u64| bytes as f64 / 1024.0 / 1024.0;
+        return Err(format!(
+            "Not enough disk space to write {}: {:.1}MB needed, {:.1}MB available",
+            path.display(),
+            to_mb(required),
+            to_mb(available)
+        ));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mcp::tools::locked_file::WRITE_CHUNK_SIZE;
+    use crate::mcp::tools::{FileEncodings, FinalNewlinePolicy, ReadTool};
+    use std::sync::Arc;
     use tempfile::TempDir;
 
     #[tokio::test]
@@ -200,6 +367,163 @@ mod tests {
         assert_eq!(content, "Hello, World!");
     }
 
+    #[tokio::test]
+    async fn test_write_keeps_encoding_of_read_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("legacy.txt");
+        std::fs::write(&file_path, b"caf\xE9\r\n").unwrap();
+
+        let encodings = Arc::new(FileEncodings::new());
+        let context = ToolContext::new("test", temp_dir.path())
+            .with_file_encodings(encodings)
+            .with_verify_writes(true);
+        let result = ReadTool::new()
+            .execute(json!({"file_path": file_path.to_str().unwrap()}), &context)
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+
+        let tool = WriteTool::new();
+        let result = tool
+            .execute(
+                json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "content": "crème brûlée\r\n"
+                }),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(
+            result
+                .content
+                .contains("Kept the file's encoding (windows-1252)")
+        );
+        assert_eq!(result.metadata.unwrap()["encoding"], "windows-1252");
+        assert_eq!(
+            std::fs::read(&file_path).unwrap(),
+            b"cr\xE8me br\xFBl\xE9e\r\n"
+        );
+
+        // Content the encoding can't hold is refused rather than mangled
+        let result = tool
+            .execute(
+                json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "content": "日本語\n"
+                }),
+                &context,
+            )
+            .await;
+        assert!(result.is_error);
+        assert!(
+            result
+                .content
+                .contains("can't be written in the file's encoding")
+        );
+        assert_eq!(
+            std::fs::read(&file_path).unwrap(),
+            b"cr\xE8me br\xFBl\xE9e\r\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_write_refuses_content_over_size_limit() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("blob.bin");
+
+        let tool = WriteTool::new();
+        let context = ToolContext::new("test", temp_dir.path()).with_max_file_size(16);
+        let result = tool
+            .execute(
+                json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "content": "x".repeat(17)
+                }),
+                &context,
+            )
+            .await;
+
+        assert!(result.is_error);
+        assert!(result.content.contains("Content too large to write"));
+        let metadata = result.metadata.unwrap();
+        assert_eq!(metadata["error_kind"], "file_too_large");
+        assert_eq!(metadata["size"], 17);
+        assert_eq!(metadata["max_file_size"], 16);
+        assert!(!file_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_write_verifies_content() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("verified.txt");
+
+        let tool = WriteTool::new();
+        let context = ToolContext::new("test", temp_dir.path()).with_verify_writes(true);
+
+        let result = tool
+            .execute(
+                json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "content": "Checked content"
+                }),
+                &context,
+            )
+            .await;
+
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.metadata.unwrap()["verified"], true);
+    }
+
+    #[tokio::test]
+    async fn test_write_reports_final_newline() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("lint.rs");
+
+        let tool = WriteTool::new();
+        let context = ToolContext::new("test", temp_dir.path())
+            .with_final_newline(FinalNewlinePolicy::Report);
+
+        let result = tool
+            .execute(
+                json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "content": "fn main() {}"
+                }),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(result.content.ends_with(
+            "File does not end with a newline; add one if the project's linters expect it."
+        ));
+        assert_eq!(result.metadata.unwrap()["ends_with_newline"], false);
+
+        let result = tool
+            .execute(
+                json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "content": "fn main() {}\n"
+                }),
+                &context,
+            )
+            .await;
+        assert!(result.content.ends_with("File ends with a newline."));
+        assert_eq!(result.metadata.unwrap()["ends_with_newline"], true);
+
+        // Nothing is reported when the check is off
+        let result = tool
+            .execute(
+                json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "content": "fn main() {}"
+                }),
+                &ToolContext::new("test", temp_dir.path()),
+            )
+            .await;
+        assert!(!result.content.contains("newline"));
+        assert!(result.metadata.unwrap()["ends_with_newline"].is_null());
+    }
+
     #[tokio::test]
     async fn test_write_overwrite_file() {
         let temp_dir = TempDir::new().unwrap();
@@ -229,6 +553,50 @@ mod tests {
         assert_eq!(content, "New content");
     }
 
+    #[tokio::test]
+    async fn test_write_dry_run() {
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("existing.txt");
+        std::fs::write(&file_path, "one\ntwo\n").unwrap();
+
+        let tool = WriteTool::new();
+        let context = ToolContext::new("test", temp_dir.path());
+        let result = tool
+            .execute(
+                json!({
+                    "file_path": file_path.to_str().unwrap(),
+                    "content": "one\nthree\n",
+                    "dry_run": true
+                }),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert!(
+            result
+                .content
+                .starts_with("Preview only, no changes written.")
+        );
+        assert!(result.content.contains("-two\n+three"));
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "one\ntwo\n");
+
+        // A new file is previewed, not created
+        let new_path = temp_dir.path().join("nested/new.txt");
+        let result = tool
+            .execute(
+                json!({
+                    "file_path": new_path.to_str().unwrap(),
+                    "content": "hello\n",
+                    "dry_run": true
+                }),
+                &context,
+            )
+            .await;
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(result.metadata.unwrap()["created"], true);
+        assert!(!new_path.parent().unwrap().exists());
+    }
+
     #[tokio::test]
     async fn test_write_creates_directories() {
         let temp_dir = TempDir::new().unwrap();
@@ -251,6 +619,92 @@ mod tests {
         assert!(file_path.exists());
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_write_permission_denied() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let locked = temp_dir.path().join("locked");
+        std::fs::create_dir(&locked).unwrap();
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o555)).unwrap();
+
+        // Directory permissions don't apply to root
+        if std::fs::write(locked.join("probe"), "").is_ok() {
+            return;
+        }
+
+        let file_path = locked.join("out.txt");
+        let context = ToolContext::new("test", temp_dir.path());
+        let result = WriteTool::new()
+            .execute(
+                json!({"file_path": file_path.to_str().unwrap(), "content": "data"}),
+                &context,
+            )
+            .await;
+        std::fs::set_permissions(&locked, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        assert!(result.is_error);
+        assert_eq!(
+            result.content,
+            format!("Permission denied writing {}", file_path.display())
+        );
+        assert_eq!(result.metadata.unwrap()["error_kind"], "permission_denied");
+    }
+
+    #[test]
+    fn test_required_space() {
+        assert_eq!(required_space(50), 50 + DISK_SPACE_MARGIN);
+        assert_eq!(required_space(0), DISK_SPACE_MARGIN);
+    }
+
+    #[tokio::test]
+    async fn test_write_large_file_atomically() {
+        use std::fmt::Write as _;
+
+        let temp_dir = TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("large.txt");
+        std::fs::write(&file_path, "old content").unwrap();
+
+        // ~6MB spanning many write chunks
+        let mut content = String::new();
+        for i in 0..200_000 {
+            writeln!(content, "line {i:>8} of the large file").unwrap();
+        }
+        assert!(content.len() > 50 * WRITE_CHUNK_SIZE);
+
+        let context = ToolContext::new("test", temp_dir.path());
+        let result = WriteTool::new()
+            .execute(
+                json!({"file_path": file_path.to_str().unwrap(), "content": content}),
+                &context,
+            )
+            .await;
+
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), content);
+
+        // The temporary file was renamed into place
+        let entries: Vec<_> = std::fs::read_dir(temp_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(entries, vec!["large.txt"]);
+    }
+
+    #[test]
+    fn test_check_disk_space() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("a/b/out.txt");
+        // Small writes are not checked
+        assert_eq!(check_disk_space(&path, 1024), Ok(()));
+        // The temp filesystem has room for a write just over the threshold
+        assert_eq!(
+            check_disk_space(&path, DISK_SPACE_CHECK_THRESHOLD + 1),
+            Ok(())
+        );
+    }
+
     #[test]
     fn test_write_tool_properties() {
         let tool = WriteTool::new();
@@ -0,0 +1,259 @@
+//! Tool argument validation
+//!
+//! Arguments are checked against the tool's declared input schema before the
+//! tool runs, so the model gets an error naming the offending field and the
+//! expected type instead of a generic deserialization failure.
+//!
+//! Only the subset of JSON Schema used by the built-in tools is supported:
+//! `type`, `required`, `properties`, `items`, `enum`, `minLength`,
+//! `minimum` and `maximum`.
+
+use serde_json::Value;
+
+/// Validate tool arguments against the tool's JSON schema
+///
+/// Returns every problem found, each naming the offending field.
+pub fn validate_arguments(schema: &Value, input: &Value) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+    validate_value(schema, input, "", &mut errors);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn validate_value(schema: &Value, value: &Value, path: &str, errors: &mut Vec<String>) {
+    if let Some(expected) = schema.get("type")
+        && !matches_type(expected, value)
+    {
+        errors.push(format!(
+            "{} must be {} (got {})",
+            field_name(path),
+            describe_type(expected),
+            describe_value(value)
+        ));
+        return;
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+        && !allowed.contains(value)
+    {
+        let allowed = allowed
+            .iter()
+            .map(Value::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        errors.push(format!(
+            "{} must be one of {} (got {})",
+            field_name(path),
+            allowed,
+            describe_value(value)
+        ));
+        return;
+    }
+
+    match value {
+        Value::Object(map) => {
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !map.contains_key(name) {
+                        let expected = schema
+                            .pointer(&format!("/properties/{}/type", name))
+                            .map(|t| format!(" ({})", describe_type(t)))
+                            .unwrap_or_default();
+                        errors.push(format!(
+                            "missing required field `{}`{}",
+                            join_path(path, name),
+                            expected
+                        ));
+                    }
+                }
+            }
+            if let Some(properties) = schema.get("properties").and_then(Value::as_object) {
+                for (name, value) in map {
+                    if let Some(property) = properties.get(name) {
+                        validate_value(property, value, &join_path(path, name), errors);
+                    }
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (index, item) in items.iter().enumerate() {
+                    validate_value(item_schema, item, &format!("{}[{}]", path, index), errors);
+                }
+            }
+        }
+        Value::String(s) => {
+            if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+                && (s.chars().count() as u64) < min
+            {
+                errors.push(format!(
+                    "{} must be at least {} characters long",
+                    field_name(path),
+                    min
+                ));
+            }
+        }
+        Value::Number(n) => {
+            let n = n.as_f64().unwrap_or_default();
+            if let Some(min) = schema.get("minimum").and_then(Value::as_f64)
+                && n < min
+            {
+                errors.push(format!("{} must be at least {}", field_name(path), min));
+            }
+            if let Some(max) = schema.get("maximum").and_then(Value::as_f64)
+                && n > max
+            {
+                errors.push(format!("{} must be at most {}", field_name(path), max));
+            }
+        }
+        Value::Null | Value::Bool(_) => {}
+    }
+}
+
+/// Check a value against a `type` keyword (a single type or a list of types)
+fn matches_type(expected: &Value, value: &Value) -> bool {
+    match expected {
+        Value::String(t) => matches_single_type(t, value),
+        Value::Array(types) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|t| matches_single_type(t, value)),
+        _ => true,
+    }
+}
+
+fn matches_single_type(expected: &str, value: &Value) -> bool {
+    match expected {
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn describe_type(expected: &Value) -> String {
+    let article = |t: &str| match t {
+        "integer" | "array" | "object" => format!("an {}", t),
+        "null" => t.to_string(),
+        _ => format!("a {}", t),
+    };
+    match expected {
+        Value::String(t) => article(t),
+        Value::Array(types) => types
+            .iter()
+            .filter_map(Value::as_str)
+            .map(article)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        other => other.to_string(),
+    }
+}
+
+fn describe_value(value: &Value) -> String {
+    match value {
+        Value::Null => "null".to_string(),
+        Value::Bool(b) => format!("boolean {}", b),
+        Value::Number(n) => format!("number {}", n),
+        Value::String(s) if s.chars().count() > 40 => "a long string".to_string(),
+        Value::String(s) => format!("string {:?}", s),
+        Value::Array(_) => "an array".to_string(),
+        Value::Object(_) => "an object".to_string(),
+    }
+}
+
+fn join_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn field_name(path: &str) -> String {
+    if path.is_empty() {
+        "input".to_string()
+    } else {
+        format!("`{}`", path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schema() -> Value {
+        json!({
+            "type": "object",
+            "required": ["file_path", "todos"],
+            "properties": {
+                "file_path": {"type": "string"},
+                "limit": {"type": "integer", "minimum": 1},
+                "todos": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["content", "status"],
+                        "properties": {
+                            "content": {"type": "string", "minLength": 1},
+                            "status": {"type": "string", "enum": ["pending", "completed"]}
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn test_valid_arguments() {
+        let input = json!({
+            "file_path": "/tmp/a.txt",
+            "limit": 10,
+            "todos": [{"content": "Write tests", "status": "pending"}],
+            "unknown": true
+        });
+        assert!(validate_arguments(&schema(), &input).is_ok());
+    }
+
+    #[test]
+    fn test_missing_required_field() {
+        let errors = validate_arguments(&schema(), &json!({"todos": []})).unwrap_err();
+        assert_eq!(
+            errors,
+            vec!["missing required field `file_path` (a string)"]
+        );
+    }
+
+    #[test]
+    fn test_invalid_fields_are_named() {
+        let input = json!({
+            "file_path": 42,
+            "limit": 0,
+            "todos": [{"content": "", "status": "done"}, {"status": "pending"}]
+        });
+        let errors = validate_arguments(&schema(), &input).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                "`file_path` must be a string (got number 42)",
+                "`limit` must be at least 1",
+                "`todos[0].content` must be at least 1 characters long",
+                "`todos[0].status` must be one of \"pending\", \"completed\" (got string \"done\")",
+                "missing required field `todos[1].content` (a string)",
+            ]
+        );
+
+        let errors = validate_arguments(&schema(), &json!("not an object")).unwrap_err();
+        assert_eq!(
+            errors,
+            vec!["input must be an object (got string \"not an object\")"]
+        );
+    }
+}
@@ -11,16 +11,18 @@ use claude_code_agent_sdk::types::permissions::{
 use sacp::{JrConnectionCx, link::AgentToClient};
 use sacp::schema::{
     Content, ContentBlock, PermissionOption, PermissionOptionId, PermissionOptionKind,
-    RequestPermissionOutcome, RequestPermissionRequest, SessionId, TextContent, ToolCallContent,
-    ToolCallUpdate, ToolCallUpdateFields,
+    RequestPermissionOutcome, RequestPermissionRequest, SessionId, SessionNotification,
+    SessionUpdate, TextContent, ToolCallContent, ToolCallStatus, ToolCallUpdate,
+    ToolCallUpdateFields,
 };
 use std::sync::{Arc, OnceLock};
 use tracing::{debug, info, warn};
 
-use crate::session::{PermissionMode, PermissionOutcome, PermissionRequestBuilder, Session, ToolPermissionResult};
+use crate::session::{
+    AuditEntry, AuditSource, DiffSuggestion, PermissionMode, PermissionOutcome,
+    PermissionRequestBuilder, Session, ToolPermissionResult,
+};
 use crate::types::AgentError;
-use std::fs;
-use std::path::PathBuf;
 
 /// ExitPlanMode specific permission outcome
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -31,90 +33,14 @@ enum ExitPlanModeOutcome {
     KeepPlanning,
 }
 
-/// Read the most recent plan file from ~/.claude/plans/
+/// Options of the ExitPlanMode permission request
 ///
-/// Returns Ok(Some(content)) if plan file is found and readable,
-/// Ok(None) if no plan file exists or file is too large (>20MB),
-/// or Err if there's an error reading.
-fn read_plan_file() -> Result<Option<String>, std::io::Error> {
-    // Maximum plan file size: 20MB
-    // Plan files are typically small (a few KB), but we add a safety limit
-    const MAX_PLAN_FILE_SIZE: u64 = 20 * 1024 * 1024; // 20MB
-
-    // Get the home directory
-    let Some(home) = dirs::home_dir() else {
-        return Ok(None);
-    };
-
-    let plans_dir = home.join(".claude").join("plans");
-
-    // Check if plans directory exists
-    if !plans_dir.exists() {
-        return Ok(None);
-    }
-
-    // Read the directory and find the most recently modified .md file
-    let entries = fs::read_dir(&plans_dir)?;
-    let mut most_recent_file: Option<PathBuf> = None;
-    let mut most_recent_mtime: std::time::SystemTime = std::time::SystemTime::UNIX_EPOCH;
-
-    for entry in entries.flatten() {
-        let path = entry.path();
-        if path.extension().and_then(|s| s.to_str()) == Some("md") {
-            if let Ok(metadata) = entry.metadata() {
-                if let Ok(mtime) = metadata.modified() {
-                    if mtime > most_recent_mtime {
-                        most_recent_mtime = mtime;
-                        most_recent_file = Some(path);
-                    }
-                }
-            }
-        }
-    }
-
-    // Read the most recent plan file
-    if let Some(file_path) = most_recent_file {
-        // Check file size before reading
-        let metadata = fs::metadata(&file_path)?;
-        let file_size = metadata.len();
-
-        if file_size > MAX_PLAN_FILE_SIZE {
-            warn!(
-                "Plan file too large ({} bytes > {} limit), skipping: {:?}",
-                file_size,
-                MAX_PLAN_FILE_SIZE,
-                file_path
-            );
-            return Ok(None);
-        }
-
-        match fs::read_to_string(&file_path) {
-            Ok(content) => {
-                info!(
-                    "Read plan file: {:?} (size: {} bytes)",
-                    file_path, file_size
-                );
-                Ok(Some(content))
-            }
-            Err(e) => {
-                warn!("Failed to read plan file {:?}: {}", file_path, e);
-                Err(e)
-            }
-        }
-    } else {
-        Ok(None)
-    }
-}
-
-/// Send ExitPlanMode permission request with custom options
-async fn send_exit_plan_mode_request(
-    session_id: &str,
-    tool_use_id: &str,
-    tool_input: &serde_json::Value,
-    connection_cx: &JrConnectionCx<AgentToClient>,
-) -> Result<ExitPlanModeOutcome, AgentError> {
+/// Clients preselect the first option, so "auto-accept edits" comes first
+/// with the `acceptEditsAfterPlan` setting and "manually approve edits"
+/// otherwise.
+fn exit_plan_mode_options(accept_edits_after_plan: bool) -> Vec<PermissionOption> {
     // ExitPlanMode specific options matching TypeScript implementation
-    let options = vec![
+    let mut options = vec![
         PermissionOption::new(
             PermissionOptionId::new("acceptEdits"),
             "Yes, and auto-accept edits",
@@ -131,10 +57,26 @@ async fn send_exit_plan_mode_request(
             PermissionOptionKind::RejectOnce,
         ),
     ];
+    if !accept_edits_after_plan {
+        options.swap(0, 1);
+    }
+    options
+}
+
+/// Send ExitPlanMode permission request with custom options
+async fn send_exit_plan_mode_request(
+    session: &Session,
+    tool_use_id: &str,
+    tool_input: &serde_json::Value,
+    connection_cx: &JrConnectionCx<AgentToClient>,
+) -> Result<ExitPlanModeOutcome, AgentError> {
+    let session_id = session.session_id.as_str();
+    let current_plan = session.current_plan();
+    let options = exit_plan_mode_options(session.accept_edits_after_plan());
 
     // Determine the raw input to display
     // Priority: 1. Use 'plan' field from tool_input if provided
-    //           2. Try to read from plan file
+    //           2. Try to read the session's current plan file
     //           3. Fall back to original tool_input
     let (raw_input, plan_content_for_display) = if let Some(plan_content) =
         tool_input.get("plan").and_then(|v| v.as_str())
@@ -145,8 +87,8 @@ async fn send_exit_plan_mode_request(
             Some(plan_content.to_string()),
         )
     } else {
-        // Try to read the most recent plan file
-        match read_plan_file() {
+        // Try to read the current plan file
+        match current_plan.read() {
             Ok(Some(plan_content)) => {
                 info!("Read plan file content for ExitPlanMode display");
                 (
@@ -246,6 +188,27 @@ async fn send_exit_plan_mode_request(
     }
 }
 
+/// Record a decision in the session's audit log, if one is configured
+fn audit(
+    session: &Session,
+    tool_name: &str,
+    tool_input: &serde_json::Value,
+    decision: &str,
+    rule: Option<&str>,
+    source: AuditSource,
+) {
+    if let Some(audit_log) = session.audit_log() {
+        audit_log.record(&AuditEntry::new(
+            &session.session_id,
+            tool_name,
+            decision,
+            rule,
+            source,
+            tool_input,
+        ));
+    }
+}
+
 /// Handle ExitPlanMode tool with special permission dialog
 async fn handle_exit_plan_mode(
     session: &Session,
@@ -262,6 +225,14 @@ async fn handle_exit_plan_mode(
             session_id = %session.session_id,
             "Connection not ready for ExitPlanMode"
         );
+        audit(
+            session,
+            "ExitPlanMode",
+            &tool_input,
+            "deny",
+            None,
+            AuditSource::Agent,
+        );
         return PermissionResult::Deny(PermissionResultDeny {
             message: "Connection not ready for ExitPlanMode".to_string(),
             interrupt: false,
@@ -269,19 +240,24 @@ async fn handle_exit_plan_mode(
     };
 
     // Send ExitPlanMode permission request
-    match send_exit_plan_mode_request(&session.session_id, tool_use_id, &tool_input, connection_cx).await {
-        Ok(ExitPlanModeOutcome::Approve(mode)) => {
+    match send_exit_plan_mode_request(session, tool_use_id, &tool_input, connection_cx).await {
+        Ok(ExitPlanModeOutcome::Approve(selected)) => {
+            // Update session permission mode and send session/update notification
+            let mode = session.approve_plan(selected).await;
+
             info!(
                 session_id = %session.session_id,
                 mode = ?mode,
-                "ExitPlanMode approved, switching to new mode"
+                "ExitPlanMode approved, switched to new mode"
+            );
+            audit(
+                session,
+                "ExitPlanMode",
+                &tool_input,
+                "allow",
+                None,
+                AuditSource::User,
             );
-
-            // Update session permission mode
-            session.set_permission_mode(mode).await;
-
-            // Send session/update notification
-            session.send_mode_update(mode.as_str());
 
             // Return Allow with updated_permissions (matching TypeScript implementation)
             // This tells the SDK:
@@ -305,6 +281,14 @@ async fn handle_exit_plan_mode(
                 session_id = %session.session_id,
                 "ExitPlanMode rejected, staying in Plan mode"
             );
+            audit(
+                session,
+                "ExitPlanMode",
+                &tool_input,
+                "deny",
+                None,
+                AuditSource::User,
+            );
             PermissionResult::Deny(PermissionResultDeny {
                 message: "Plan mode continued. You can keep working on your plan.".to_string(),
                 interrupt: false,
@@ -316,6 +300,14 @@ async fn handle_exit_plan_mode(
                 error = %e,
                 "ExitPlanMode request failed"
             );
+            audit(
+                session,
+                "ExitPlanMode",
+                &tool_input,
+                "deny",
+                None,
+                AuditSource::Agent,
+            );
             PermissionResult::Deny(PermissionResultDeny {
                 message: format!("ExitPlanMode failed: {}", e),
                 interrupt: false,
@@ -373,6 +365,14 @@ pub fn create_can_use_tool_callback(
                                 cached_id
                             } else {
                                 warn!("No tool_use_id available for ExitPlanMode");
+                                audit(
+                                    session,
+                                    &tool_name,
+                                    &tool_input,
+                                    "deny",
+                                    None,
+                                    AuditSource::Agent,
+                                );
                                 return PermissionResult::Deny(PermissionResultDeny {
                                     message: "No tool_use_id available for ExitPlanMode".to_string(),
                                     interrupt: false,
@@ -389,6 +389,7 @@ pub fn create_can_use_tool_callback(
                 let result = handler_guard
                     .check_permission(&tool_name, &tool_input)
                     .await;
+                let mode = handler_guard.mode();
                 drop(handler_guard); // Release the lock before async operations
 
                 match result {
@@ -397,6 +398,14 @@ pub fn create_can_use_tool_callback(
                             tool_name = %tool_name,
                             "Permission allowed by handler"
                         );
+                        audit(
+                            session,
+                            &tool_name,
+                            &tool_input,
+                            "allow",
+                            Some(mode.as_str()),
+                            AuditSource::Mode,
+                        );
                         PermissionResult::Allow(PermissionResultAllow::default())
                     }
                     ToolPermissionResult::Blocked { reason } => {
@@ -405,12 +414,51 @@ pub fn create_can_use_tool_callback(
                             reason = %reason,
                             "Permission blocked by handler"
                         );
+                        audit(
+                            session,
+                            &tool_name,
+                            &tool_input,
+                            "deny",
+                            Some(mode.as_str()),
+                            AuditSource::Mode,
+                        );
                         PermissionResult::Deny(PermissionResultDeny {
                             message: reason,
                             interrupt: false,
                         })
                     }
                     ToolPermissionResult::NeedsPermission => {
+                        // A client that opted out of permission requests would never
+                        // answer, so the configured fallback decides instead
+                        if !session.permission_requests_supported() {
+                            let fallback = session.permission_fallback();
+                            info!(
+                                tool_name = %tool_name,
+                                fallback = ?fallback,
+                                "Client doesn't take permission requests - using fallback"
+                            );
+                            let settings_check = session
+                                .permission_checker()
+                                .read()
+                                .await
+                                .check_permission(&tool_name, &tool_input);
+                            let result = fallback.decide(&settings_check);
+                            let decision = if matches!(result, PermissionResult::Allow(_)) {
+                                "allow"
+                            } else {
+                                "deny"
+                            };
+                            audit(
+                                session,
+                                &tool_name,
+                                &tool_input,
+                                decision,
+                                Some("permissionFallback"),
+                                AuditSource::Rules,
+                            );
+                            return result;
+                        }
+
                         // This is the "ask" case - send permission request to client
                         // Following TypeScript version's design
                         info!(
@@ -436,6 +484,14 @@ pub fn create_can_use_tool_callback(
                                         tool_name = %tool_name,
                                         "No tool_use_id in context or cache - denying for security"
                                     );
+                                    audit(
+                                        session,
+                                        &tool_name,
+                                        &tool_input,
+                                        "deny",
+                                        None,
+                                        AuditSource::Agent,
+                                    );
                                     return PermissionResult::Deny(PermissionResultDeny {
                                         message:
                                             "No tool_use_id available for permission request"
@@ -452,37 +508,130 @@ pub fn create_can_use_tool_callback(
                                 tool_name = %tool_name,
                                 "Connection not ready - denying for security"
                             );
+                            audit(
+                                session,
+                                &tool_name,
+                                &tool_input,
+                                "deny",
+                                None,
+                                AuditSource::Agent,
+                            );
                             return PermissionResult::Deny(PermissionResultDeny {
                                 message: "Connection not ready for permission request".to_string(),
                                 interrupt: false,
                             });
                         };
 
-                        // Send permission request and wait for response
-                        let outcome = PermissionRequestBuilder::new(
-                            &session.session_id,
-                            &tool_use_id,
-                            &tool_name,
-                            tool_input.clone(),
-                        )
-                        .request(connection_cx)
-                        .await;
+                        // Let clients that opted in review (and edit) the proposed diff
+                        let suggestion = if session.diff_suggestions() {
+                            DiffSuggestion::from_tool_input(&tool_name, &tool_input)
+                        } else {
+                            None
+                        };
+
+                        // Send permission request and wait for response; clients
+                        // handling grouped requests get nearby calls in one prompt
+                        // (a diff to review needs a prompt of its own)
+                        let outcome = match (&suggestion, session.permission_manager()) {
+                            (None, Some(manager)) => Ok((
+                                manager
+                                    .decide(
+                                        tool_name.clone(),
+                                        tool_input.clone(),
+                                        tool_use_id.clone(),
+                                        session.session_id.clone(),
+                                        session.permission_request_timeout(),
+                                    )
+                                    .await,
+                                None,
+                            )),
+                            _ => {
+                                let mut builder = PermissionRequestBuilder::new(
+                                    &session.session_id,
+                                    &tool_use_id,
+                                    &tool_name,
+                                    tool_input.clone(),
+                                );
+                                if let Some(suggestion) = &suggestion {
+                                    builder = builder.suggestion(suggestion.clone());
+                                }
+                                let reject_always_rule = session
+                                    .permission_checker()
+                                    .read()
+                                    .await
+                                    .deny_rule_for_tool_call(&tool_name, &tool_input);
+                                builder
+                                    .reject_always_rule(reject_always_rule)
+                                    .timeout(session.permission_request_timeout())
+                                    .request_with_edits(connection_cx)
+                                    .await
+                            }
+                        };
+
+                        // Write the client's edited text instead of the model's proposal
+                        let allow = |edited: Option<String>| {
+                            let updated_input = suggestion
+                                .as_ref()
+                                .zip(edited)
+                                .and_then(|(s, text)| s.apply_edit(&tool_input, &text));
+                            PermissionResult::Allow(PermissionResultAllow {
+                                updated_input,
+                                ..PermissionResultAllow::default()
+                            })
+                        };
+
+                        // Record the user's answer in the audit log
+                        let audit_answer = |decision: &str, source: AuditSource| {
+                            audit(session, &tool_name, &tool_input, decision, None, source);
+                        };
 
                         match outcome {
-                            Ok(PermissionOutcome::AllowOnce) => {
+                            Ok((PermissionOutcome::AllowOnce, edited)) => {
                                 info!(tool_name = %tool_name, "Permission allowed once by user");
-                                PermissionResult::Allow(PermissionResultAllow::default())
+                                audit_answer("allow", AuditSource::User);
+                                allow(edited)
                             }
-                            Ok(PermissionOutcome::AllowAlways) => {
+                            Ok((PermissionOutcome::AllowAlways, edited)) => {
                                 info!(tool_name = %tool_name, "Permission allowed always by user");
+                                audit_answer("allow", AuditSource::User);
                                 // Add rule to permission checker for future invocations
                                 let handler_guard = session.permission().await;
                                 handler_guard.add_allow_rule_for_tool_call(&tool_name, &tool_input);
                                 drop(handler_guard);
-                                PermissionResult::Allow(PermissionResultAllow::default())
+                                allow(edited)
                             }
-                            Ok(PermissionOutcome::Rejected | PermissionOutcome::Cancelled) => {
+                            Ok((PermissionOutcome::RejectAlways, _)) => {
+                                info!(tool_name = %tool_name, "Permission rejected always by user");
+                                audit_answer("deny", AuditSource::User);
+                                // Deny future invocations, in this session and the next ones
+                                session
+                                    .permission()
+                                    .await
+                                    .add_deny_rule(&tool_name, &tool_input)
+                                    .await;
+                                PermissionResult::Deny(PermissionResultDeny {
+                                    message: "User denied permission".to_string(),
+                                    interrupt: false,
+                                })
+                            }
+                            Ok((PermissionOutcome::TimedOut, _)) => {
+                                warn!(tool_name = %tool_name, "Permission request timed out");
+                                audit_answer("deny", AuditSource::Agent);
+                                let message = "Permission request timed out".to_string();
+                                send_failed_tool_call(
+                                    connection_cx,
+                                    &session.session_id,
+                                    &tool_use_id,
+                                    &message,
+                                );
+                                PermissionResult::Deny(PermissionResultDeny {
+                                    message,
+                                    interrupt: false,
+                                })
+                            }
+                            Ok((PermissionOutcome::Rejected | PermissionOutcome::Cancelled, _)) => {
                                 info!(tool_name = %tool_name, "Permission rejected/cancelled by user");
+                                audit_answer("deny", AuditSource::User);
                                 PermissionResult::Deny(PermissionResultDeny {
                                     message: "User denied permission".to_string(),
                                     interrupt: false,
@@ -494,6 +643,7 @@ pub fn create_can_use_tool_callback(
                                     error = %e,
                                     "Permission request failed"
                                 );
+                                audit_answer("deny", AuditSource::Agent);
                                 PermissionResult::Deny(PermissionResultDeny {
                                     message: format!("Permission request failed: {}", e),
                                     interrupt: false,
@@ -507,6 +657,29 @@ pub fn create_can_use_tool_callback(
     )
 }
 
+/// Mark a tool call as failed, e.g. when its permission request went unanswered
+fn send_failed_tool_call(
+    connection_cx: &JrConnectionCx<AgentToClient>,
+    session_id: &str,
+    tool_use_id: &str,
+    message: &str,
+) {
+    let fields = ToolCallUpdateFields::new()
+        .status(ToolCallStatus::Failed)
+        .content(vec![ToolCallContent::from(message.to_string())]);
+    let notification = SessionNotification::new(
+        SessionId::new(session_id.to_string()),
+        SessionUpdate::ToolCallUpdate(ToolCallUpdate::new(tool_use_id.to_string(), fields)),
+    );
+    if let Err(e) = connection_cx.send_notification(notification) {
+        warn!(
+            tool_use_id = %tool_use_id,
+            error = %e,
+            "Failed to mark tool call as failed"
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -523,4 +696,76 @@ mod tests {
         let _callback = create_can_use_tool_callback(session_lock);
         // If this compiles, the signature is correct
     }
+
+    #[test]
+    fn test_exit_plan_mode_options_preselect_setting() {
+        let ids = |accept_edits_after_plan| {
+            exit_plan_mode_options(accept_edits_after_plan)
+                .into_iter()
+                .map(|option| option.option_id.0.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(false), ["default", "acceptEdits", "plan"]);
+        assert_eq!(ids(true), ["acceptEdits", "default", "plan"]);
+    }
+
+    #[tokio::test]
+    async fn test_fallback_decides_without_a_permission_request() {
+        use crate::permissions::PermissionFallback;
+        use crate::session::SessionManager;
+        use crate::types::AgentConfig;
+
+        let config = AgentConfig {
+            permission_fallback: PermissionFallback::Rules,
+            ..Default::default()
+        };
+        let sessions = SessionManager::new();
+        let session = sessions
+            .create_session(
+                "fallback-session".to_string(),
+                std::path::PathBuf::from("/tmp"),
+                &config,
+                None,
+            )
+            .unwrap();
+        session
+            .permission_checker()
+            .write()
+            .await
+            .add_allow_rule("Delete");
+        let session_lock = Arc::new(OnceLock::new());
+        session_lock.set(session.clone()).unwrap();
+        let callback = create_can_use_tool_callback(session_lock);
+        let call = |tool_name: &str, tool_input: serde_json::Value| {
+            callback(
+                tool_name.to_string(),
+                tool_input,
+                ToolPermissionContext {
+                    tool_use_id: Some("toolu_fallback".to_string()),
+                    ..Default::default()
+                },
+            )
+        };
+        let edit =
+            serde_json::json!({"file_path": "/tmp/a.txt", "old_string": "a", "new_string": "b"});
+        let permanent_delete = serde_json::json!({"path": "/tmp/a.txt", "permanent": true});
+
+        // The session has no client connection, so a call that went on to
+        // request permission would be denied with "Connection not ready"
+        let PermissionResult::Deny(deny) = call("Edit", edit.clone()).await else {
+            panic!("Edit should need permission");
+        };
+        assert!(deny.message.contains("Connection not ready"));
+
+        // A client that opted out is never asked: the fallback decides
+        session.set_permission_requests_supported(false);
+        let PermissionResult::Deny(deny) = call("Edit", edit).await else {
+            panic!("the fallback should deny calls no allow rule matches");
+        };
+        assert!(deny.message.contains("doesn't support permission requests"));
+        assert!(matches!(
+            call("Delete", permanent_delete).await,
+            PermissionResult::Allow(_)
+        ));
+    }
 }
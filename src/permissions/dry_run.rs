@@ -0,0 +1,36 @@
+//! Tool calls that only preview a change
+//!
+//! Edit and Write with `dry_run: true` return the diff of the change without
+//! writing it, so they are approved like read-only tools: automatically in
+//! Default and Plan mode.
+
+use serde_json::Value;
+
+/// Whether a tool call is a dry run that writes nothing
+pub fn is_dry_run(tool_name: &str, tool_input: &Value) -> bool {
+    let tool_name = tool_name.strip_prefix("mcp__acp__").unwrap_or(tool_name);
+
+    matches!(tool_name, "Edit" | "Write")
+        && tool_input
+            .get("dry_run")
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_is_dry_run() {
+        let preview = json!({"file_path": "a.txt", "content": "a", "dry_run": true});
+        assert!(is_dry_run("Write", &preview));
+        assert!(is_dry_run("mcp__acp__Edit", &preview));
+        assert!(!is_dry_run(
+            "Write",
+            &json!({"file_path": "a.txt", "content": "a"})
+        ));
+        assert!(!is_dry_run("MultiEdit", &preview));
+    }
+}
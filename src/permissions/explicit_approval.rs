@@ -0,0 +1,36 @@
+//! Tool calls that always need the user's approval
+//!
+//! Some calls are destructive enough that neither the permission mode
+//! (acceptEdits, bypassPermissions) nor allow rules may approve them: the
+//! user is asked every time. Deny rules and Plan mode still block them.
+
+use serde_json::Value;
+
+/// Whether a tool call must be approved by the user, whatever the mode
+/// and allow rules say
+pub fn requires_explicit_approval(tool_name: &str, tool_input: &Value) -> bool {
+    let tool_name = tool_name.strip_prefix("mcp__acp__").unwrap_or(tool_name);
+
+    // Permanent deletes bypass the session trash and can't be undone
+    tool_name == "Delete"
+        && tool_input
+            .get("permanent")
+            .and_then(Value::as_bool)
+            .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_permanent_delete_requires_explicit_approval() {
+        let permanent = json!({"path": "a.txt", "permanent": true});
+        assert!(requires_explicit_approval("Delete", &permanent));
+        assert!(requires_explicit_approval("mcp__acp__Delete", &permanent));
+        let trash = json!({"path": "a.txt"});
+        assert!(!requires_explicit_approval("Delete", &trash));
+        assert!(!requires_explicit_approval("Write", &permanent));
+    }
+}
@@ -0,0 +1,66 @@
+//! Permission decisions for clients that can't be asked
+//!
+//! A client can opt out of `session/request_permission` by setting
+//! `requestPermission: false` in the `_meta` of its initialize capabilities.
+//! Calls that would ask the user are then decided by the `permissionFallback`
+//! setting instead of waiting for an answer that never comes.
+
+use claude_code_agent_sdk::types::permissions::{
+    PermissionResult, PermissionResultAllow, PermissionResultDeny,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::settings::{PermissionCheckResult, PermissionDecision};
+
+/// How calls needing permission are decided when the client can't be asked
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PermissionFallback {
+    /// Deny every such call
+    #[default]
+    Deny,
+    /// Allow the calls an allow rule in settings matches, deny the rest
+    Rules,
+}
+
+impl PermissionFallback {
+    /// Decide a call given the settings rules' result for it
+    pub fn decide(self, settings_check: &PermissionCheckResult) -> PermissionResult {
+        if self == Self::Rules && settings_check.decision == PermissionDecision::Allow {
+            return PermissionResult::Allow(PermissionResultAllow::default());
+        }
+        PermissionResult::Deny(PermissionResultDeny {
+            message: "Permission required, but the client doesn't support permission requests"
+                .to_string(),
+            interrupt: false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_decisions() {
+        let allowed = PermissionCheckResult::allow("Edit");
+        let asked = PermissionCheckResult::ask();
+
+        assert!(matches!(
+            PermissionFallback::Deny.decide(&allowed),
+            PermissionResult::Deny(_)
+        ));
+        assert!(matches!(
+            PermissionFallback::Rules.decide(&allowed),
+            PermissionResult::Allow(_)
+        ));
+        assert!(matches!(
+            PermissionFallback::Rules.decide(&asked),
+            PermissionResult::Deny(_)
+        ));
+        assert_eq!(
+            serde_json::from_str::<PermissionFallback>("\"rules\"").unwrap(),
+            PermissionFallback::Rules
+        );
+    }
+}
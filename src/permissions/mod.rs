@@ -4,6 +4,14 @@
 //! tool permissions before execution.
 
 pub mod can_use_tool;
+mod dry_run;
+mod explicit_approval;
+mod fallback;
 pub mod strategies;
+mod within_cwd;
 
 pub use can_use_tool::create_can_use_tool_callback;
+pub use dry_run::is_dry_run;
+pub use explicit_approval::requires_explicit_approval;
+pub use fallback::PermissionFallback;
+pub use within_cwd::is_within_cwd;
@@ -1,11 +1,12 @@
 //! Default mode strategy
 //!
 //! This strategy provides standard permission checking:
-//! - Auto-approves read operations
+//! - Auto-approves read operations and dry-run previews
 //! - Auto-approves known safe Bash commands
 //! - Requires user permission for other operations
 
 use crate::command_safety::is_known_safe_command;
+use crate::permissions::is_dry_run;
 use crate::session::{PermissionMode, ToolPermissionResult};
 use crate::permissions::strategies::PermissionModeStrategy;
 use serde_json::Value;
@@ -21,7 +22,20 @@ impl PermissionModeStrategy for DefaultModeStrategy {
 
     fn should_auto_approve(&self, tool_name: &str, tool_input: &Value) -> bool {
         // Auto-approve read operations
-        if matches!(tool_name, "Read" | "Glob" | "Grep" | "LS" | "NotebookRead") {
+        if matches!(
+            tool_name,
+            "Read"
+                | "Glob"
+                | "Grep"
+                | "LS"
+                | "NotebookRead"
+                | "RepoStats"
+                | "RecentFiles"
+                | "DiffFiles"
+                | "EnvInfo"
+                | "ListShells"
+        ) || is_dry_run(tool_name, tool_input)
+        {
             return true;
         }
 
@@ -71,6 +85,7 @@ mod tests {
         assert!(strategy.should_auto_approve("Grep", &json!({})));
         assert!(strategy.should_auto_approve("LS", &json!({})));
         assert!(strategy.should_auto_approve("NotebookRead", &json!({})));
+        assert!(strategy.should_auto_approve("ListShells", &json!({})));
     }
 
     #[test]
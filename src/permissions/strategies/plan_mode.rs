@@ -1,44 +1,80 @@
 //! Plan mode strategy
 //!
 //! This strategy provides read-only access with an exception for writing to
-//! the ~/.claude/plans/ directory. This is used during planning phases where
-//! the user should be able to explore and write plans, but not make changes
-//! to the codebase.
+//! the plans directory (~/.claude/plans/ unless `plansDir` is set). This is
+//! used during planning phases where the user should be able to explore and
+//! write plans, but not make changes to the codebase.
 
 use crate::session::{PermissionMode, ToolPermissionResult};
+use crate::permissions::is_dry_run;
 use crate::permissions::strategies::PermissionModeStrategy;
-use crate::utils::is_plans_directory_path;
+use crate::utils::is_session_plan_path;
 use serde_json::Value;
+use std::path::PathBuf;
 
 /// Strategy for Plan mode - read-only with exceptions for plan files
-#[derive(Debug)]
-pub struct PlanModeStrategy;
+#[derive(Debug, Default)]
+pub struct PlanModeStrategy {
+    /// Plans directory from the `plansDir` setting (None = the default one)
+    plans_dir: Option<PathBuf>,
+}
+
+impl PlanModeStrategy {
+    /// Create a strategy allowing writes to plan files in `plans_dir`
+    pub fn with_plans_dir(plans_dir: Option<PathBuf>) -> Self {
+        Self { plans_dir }
+    }
+}
 
 impl PermissionModeStrategy for PlanModeStrategy {
     fn mode(&self) -> PermissionMode {
         PermissionMode::Plan
     }
 
-    fn should_auto_approve(&self, tool_name: &str, _tool_input: &Value) -> bool {
-        // Auto-approve read operations
+    fn should_auto_approve(&self, tool_name: &str, tool_input: &Value) -> bool {
+        // Auto-approve read operations and previews
         matches!(
             tool_name,
-            "Read" | "Glob" | "Grep" | "LS" | "NotebookRead"
-        )
+            "Read"
+                | "Glob"
+                | "Grep"
+                | "LS"
+                | "NotebookRead"
+                | "RepoStats"
+                | "RecentFiles"
+                | "DiffFiles"
+                | "EnvInfo"
+                | "ListShells"
+        ) || is_dry_run(tool_name, tool_input)
     }
 
     fn is_tool_blocked(&self, tool_name: &str, tool_input: &Value) -> Option<String> {
         let is_write_operation = matches!(
             tool_name,
-            "Edit" | "Write" | "Bash" | "NotebookEdit"
+            "Edit"
+                | "MultiEdit"
+                | "JsonEdit"
+                | "YamlEdit"
+                | "TomlEdit"
+                | "CargoAdd"
+                | "Write"
+                | "Touch"
+                | "CreateDirectory"
+                | "Delete"
+                | "Move"
+                | "Bash"
+                | "NotebookEdit"
         );
 
-        if !is_write_operation {
-            return None; // Read operations are allowed
+        if !is_write_operation || is_dry_run(tool_name, tool_input) {
+            return None; // Read operations and previews are allowed
         }
 
         // Check if this is a write to the plans directory (exception)
-        if matches!(tool_name, "Edit" | "Write" | "NotebookEdit") {
+        if matches!(
+            tool_name,
+            "Edit" | "MultiEdit" | "Write" | "Touch" | "NotebookEdit"
+        ) {
             let file_path = tool_input
                 .get("file_path")
                 .or_else(|| tool_input.get("path"))
@@ -46,7 +82,7 @@ impl PermissionModeStrategy for PlanModeStrategy {
                 .and_then(|v| v.as_str());
 
             if let Some(path) = file_path {
-                if is_plans_directory_path(path) {
+                if is_session_plan_path(path, self.plans_dir.as_deref()) {
                     return None; // Allow plan file writes
                 }
             }
@@ -54,7 +90,7 @@ impl PermissionModeStrategy for PlanModeStrategy {
 
         // Block all other write operations
         Some(format!(
-            "Tool {} is not allowed in Plan mode (only read operations and writing to ~/.claude/plans/ are allowed)",
+            "Tool {} is not allowed in Plan mode (only read operations and writing to the plans directory are allowed)",
             tool_name
         ))
     }
@@ -88,23 +124,24 @@ mod tests {
 
     #[test]
     fn test_mode() {
-        let strategy = PlanModeStrategy;
+        let strategy = PlanModeStrategy::default();
         assert_eq!(strategy.mode(), PermissionMode::Plan);
     }
 
     #[test]
     fn test_auto_approves_reads() {
-        let strategy = PlanModeStrategy;
+        let strategy = PlanModeStrategy::default();
         assert!(strategy.should_auto_approve("Read", &json!({})));
         assert!(strategy.should_auto_approve("Glob", &json!({})));
         assert!(strategy.should_auto_approve("Grep", &json!({})));
         assert!(strategy.should_auto_approve("LS", &json!({})));
         assert!(strategy.should_auto_approve("NotebookRead", &json!({})));
+        assert!(strategy.should_auto_approve("ListShells", &json!({})));
     }
 
     #[test]
     fn test_does_not_auto_approve_writes() {
-        let strategy = PlanModeStrategy;
+        let strategy = PlanModeStrategy::default();
         assert!(!strategy.should_auto_approve("Write", &json!({})));
         assert!(!strategy.should_auto_approve("Edit", &json!({})));
         assert!(!strategy.should_auto_approve("Bash", &json!({})));
@@ -112,18 +149,37 @@ mod tests {
 
     #[test]
     fn test_blocks_non_plan_writes() {
-        let strategy = PlanModeStrategy;
+        let strategy = PlanModeStrategy::default();
         let result = strategy.is_tool_blocked(
             "Write",
             &json!({"file_path": "/tmp/test.txt", "content": "test"}),
         );
         assert!(result.is_some());
         assert!(result.unwrap().contains("not allowed in Plan mode"));
+
+        let result = strategy.is_tool_blocked("Touch", &json!({"file_path": "/tmp/.gitkeep"}));
+        assert!(result.is_some());
+    }
+
+    #[test]
+    fn test_allows_dry_runs() {
+        let strategy = PlanModeStrategy::default();
+        let preview = json!({
+            "file_path": "/tmp/test.txt",
+            "old_string": "a",
+            "new_string": "b",
+            "dry_run": true
+        });
+        assert!(strategy.is_tool_blocked("Edit", &preview).is_none());
+        assert_eq!(
+            strategy.check_permission("Edit", &preview),
+            ToolPermissionResult::Allowed
+        );
     }
 
     #[test]
     fn test_blocks_bash() {
-        let strategy = PlanModeStrategy;
+        let strategy = PlanModeStrategy::default();
         let result = strategy.is_tool_blocked(
             "Bash",
             &json!({"command": "echo test"}),
@@ -134,7 +190,7 @@ mod tests {
 
     #[test]
     fn test_allows_plan_file_writes() {
-        let strategy = PlanModeStrategy;
+        let strategy = PlanModeStrategy::default();
         let plan_path = home_plans_path();
         let result = strategy.is_tool_blocked(
             "Write",
@@ -143,9 +199,29 @@ mod tests {
         assert!(result.is_none(), "Plan file writes should be allowed");
     }
 
+    #[test]
+    fn test_allows_writes_to_custom_plans_dir() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let plans_dir = temp_dir.path().join("plans");
+        let strategy = PlanModeStrategy::with_plans_dir(Some(plans_dir.clone()));
+
+        let plan_path = plans_dir.join("plan.md");
+        assert!(
+            strategy
+                .is_tool_blocked("Write", &json!({"file_path": plan_path}))
+                .is_none()
+        );
+        // The default plans directory is not the session's
+        assert!(
+            strategy
+                .is_tool_blocked("Write", &json!({"file_path": home_plans_path()}))
+                .is_some()
+        );
+    }
+
     #[test]
     fn test_check_permission_allows_reads() {
-        let strategy = PlanModeStrategy;
+        let strategy = PlanModeStrategy::default();
         match strategy.check_permission("Read", &json!({})) {
             ToolPermissionResult::Allowed => {}
             _ => panic!("Expected Allowed for Read"),
@@ -154,7 +230,7 @@ mod tests {
 
     #[test]
     fn test_check_permission_blocks_non_plan_writes() {
-        let strategy = PlanModeStrategy;
+        let strategy = PlanModeStrategy::default();
         match strategy.check_permission("Write", &json!({"file_path": "/tmp/test.txt"})) {
             ToolPermissionResult::Blocked { .. } => {}
             _ => panic!("Expected Blocked for non-plan file writes"),
@@ -163,7 +239,7 @@ mod tests {
 
     #[test]
     fn test_check_permission_allows_plan_writes() {
-        let strategy = PlanModeStrategy;
+        let strategy = PlanModeStrategy::default();
         let plan_path = home_plans_path();
         match strategy.check_permission("Write", &json!({"file_path": plan_path})) {
             ToolPermissionResult::Allowed => {}
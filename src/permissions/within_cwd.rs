@@ -0,0 +1,83 @@
+//! File calls inside the session's working directory
+//!
+//! With `autoApproveWithinCwd`, Read, Edit, Write and MultiEdit calls on
+//! files inside the working directory are approved in Default mode. Paths
+//! are resolved with symlinks and `..` before comparing, so a link or a
+//! relative path that leads out of the directory still asks.
+
+use std::path::{Component, Path};
+
+use serde_json::Value;
+
+use crate::mcp::tools::workspace::{normalize, resolve};
+
+/// Whether a Read/Edit/Write/MultiEdit call works on a file inside `cwd`
+pub fn is_within_cwd(tool_name: &str, tool_input: &Value, cwd: &Path) -> bool {
+    let tool_name = tool_name.strip_prefix("mcp__acp__").unwrap_or(tool_name);
+    if !matches!(tool_name, "Read" | "Edit" | "Write" | "MultiEdit") {
+        return false;
+    }
+    let Some(file_path) = tool_input.get("file_path").and_then(Value::as_str) else {
+        return false;
+    };
+    // Resolve `..` after symlinks, like the OS does when the file is opened
+    let path = resolve(&cwd.join(file_path));
+    // A `..` left over follows a missing directory, which a Write creates
+    if path.components().any(|c| c == Component::ParentDir) {
+        return false;
+    }
+    path.starts_with(resolve(&normalize(cwd)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_is_within_cwd() {
+        let dir = TempDir::new().unwrap();
+        let cwd = dir.path().join("project");
+        std::fs::create_dir_all(cwd.join("src")).unwrap();
+        std::fs::write(cwd.join("src/lib.rs"), "").unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "").unwrap();
+
+        let call = |path: &str| json!({"file_path": path});
+        assert!(is_within_cwd("Read", &call("src/lib.rs"), &cwd));
+        assert!(is_within_cwd("Edit", &call("src/lib.rs"), &cwd));
+        assert!(is_within_cwd(
+            "mcp__acp__Write",
+            &call("src/new/mod.rs"),
+            &cwd
+        ));
+        let absolute = cwd.join("src/lib.rs");
+        assert!(is_within_cwd(
+            "MultiEdit",
+            &call(absolute.to_str().unwrap()),
+            &cwd
+        ));
+
+        // `..` escapes and other tools
+        assert!(!is_within_cwd("Edit", &call("../secret.txt"), &cwd));
+        assert!(!is_within_cwd("Write", &call("src/../../new.txt"), &cwd));
+        assert!(!is_within_cwd(
+            "Write",
+            &call("missing/../../new.txt"),
+            &cwd
+        ));
+        assert!(!is_within_cwd("Delete", &call("src/lib.rs"), &cwd));
+        assert!(!is_within_cwd("Edit", &json!({}), &cwd));
+
+        // Symlinks are followed before comparing
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(dir.path(), cwd.join("outside")).unwrap();
+            assert!(!is_within_cwd("Edit", &call("outside/secret.txt"), &cwd));
+            assert!(!is_within_cwd("Write", &call("outside/new.txt"), &cwd));
+            // `..` applies to the symlink's target, not to `outside` itself
+            assert!(!is_within_cwd("Edit", &call("outside/../secret.txt"), &cwd));
+            assert!(!is_within_cwd("Write", &call("outside/../new.txt"), &cwd));
+        }
+    }
+}
@@ -0,0 +1,186 @@
+//! Permission audit log
+//!
+//! With the `permissionAuditLog` setting, every permission decision is
+//! appended to a JSONL file: calls the permission mode approves or blocks,
+//! the decision of the settings rules, the user's answer when they were
+//! asked, and denials when they couldn't be. Tool inputs are stored as a
+//! SHA-256 digest, so secrets in arguments don't end up in the log.
+
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use super::session::stable_cache_key;
+
+/// Who made a logged decision
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditSource {
+    /// The permission rules from settings (or a custom mode)
+    Rules,
+    /// The permission mode, e.g. read-only calls in default mode
+    Mode,
+    /// The user, answering a permission request
+    User,
+    /// The agent, denying a call it couldn't ask the user about
+    Agent,
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    /// RFC 3339 time of the decision
+    pub timestamp: String,
+    pub session_id: String,
+    pub tool: String,
+    /// `allow`, `deny` or `ask`
+    pub decision: String,
+    /// The rule (or mode behavior) that decided, if any
+    pub rule: Option<String>,
+    pub source: AuditSource,
+    /// SHA-256 of the tool input with sorted keys
+    pub input_digest: String,
+}
+
+impl AuditEntry {
+    /// Entry for a decision made now on `tool` called with `tool_input`
+    pub fn new(
+        session_id: &str,
+        tool: &str,
+        decision: &str,
+        rule: Option<&str>,
+        source: AuditSource,
+        tool_input: &serde_json::Value,
+    ) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            session_id: session_id.to_string(),
+            tool: tool.to_string(),
+            decision: decision.to_string(),
+            rule: rule.map(str::to_string),
+            source,
+            input_digest: input_digest(tool_input),
+        }
+    }
+}
+
+/// SHA-256 of `tool_input`, independent of the order of its keys
+pub fn input_digest(tool_input: &serde_json::Value) -> String {
+    format!(
+        "sha256:{:x}",
+        Sha256::digest(stable_cache_key(tool_input).as_bytes())
+    )
+}
+
+/// Append-only JSONL log of permission decisions
+#[derive(Debug)]
+pub struct PermissionAuditLog {
+    /// Path of the log file
+    path: PathBuf,
+    /// Keeps lines from one process from interleaving
+    lock: Mutex<()>,
+}
+
+impl PermissionAuditLog {
+    /// Create a log appending to `path`
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// Path of the log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append `entry`, logging failures instead of failing the tool call
+    pub fn record(&self, entry: &AuditEntry) {
+        if let Err(e) = self.append(entry) {
+            tracing::warn!(
+                path = %self.path.display(),
+                error = %e,
+                "Failed to write permission audit log"
+            );
+        }
+    }
+
+    fn append(&self, entry: &AuditEntry) -> io::Result<()> {
+        let mut line = serde_json::to_string(entry).map_err(io::Error::other)?;
+        line.push('\n');
+
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_audit_log_appends_entries() {
+        let dir = TempDir::new().unwrap();
+        let log = PermissionAuditLog::new(dir.path().join("logs").join("audit.jsonl"));
+        let input = json!({"command": "curl -H 'Authorization: secret' example.com"});
+
+        log.record(&AuditEntry::new(
+            "s1",
+            "Bash",
+            "ask",
+            None,
+            AuditSource::Rules,
+            &input,
+        ));
+        log.record(&AuditEntry::new(
+            "s1",
+            "Bash",
+            "deny",
+            None,
+            AuditSource::User,
+            &input,
+        ));
+
+        let content = std::fs::read_to_string(log.path()).unwrap();
+        assert!(!content.contains("secret"));
+        let entries: Vec<serde_json::Value> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0]["session_id"], "s1");
+        assert_eq!(entries[0]["decision"], "ask");
+        assert_eq!(entries[0]["source"], "rules");
+        assert_eq!(entries[1]["decision"], "deny");
+        assert_eq!(entries[1]["source"], "user");
+        assert!(entries[0]["rule"].is_null());
+        assert!(entries[0]["timestamp"].as_str().is_some());
+
+        // The digest doesn't depend on key order
+        assert_eq!(entries[0]["input_digest"], input_digest(&input));
+        assert_eq!(
+            input_digest(&json!({"a": 1, "b": 2})),
+            input_digest(&json!({"b": 2, "a": 1}))
+        );
+        assert_ne!(
+            input_digest(&json!({"a": 1})),
+            input_digest(&json!({"a": 2}))
+        );
+    }
+}
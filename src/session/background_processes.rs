@@ -2,17 +2,34 @@
 //!
 //! Manages background terminal processes that are started with `run_in_background=true`.
 //! Supports retrieving incremental output and killing running processes.
+//!
+//! A process still running after the manager's maximum lifetime is killed, and
+//! processes left running when the manager is dropped are killed with it, so a
+//! forgotten background command can't outlive its session. The output kept for
+//! each process is capped; a chatty process loses its oldest output first.
+//! Subscribers are told when a process finishes, so the client can show it
+//! without polling BashOutput.
 
 use std::io;
-use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::{Duration, SystemTime};
 
 use dashmap::DashMap;
+#[cfg(unix)]
+use process_wrap::tokio::ChildWrapper;
 use tokio::process::Child;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, broadcast};
 
 use crate::session::wrapped_child::WrappedChild;
 
+/// How often [`ChildHandle::wait`] checks whether the process exited
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// SIGTERM, the same number on every Unix
+#[cfg(unix)]
+const SIGTERM: i32 = 15;
+
 /// Child process handle that can be either wrapped or unwrapped
 ///
 /// This enum allows us to support both:
@@ -76,17 +93,60 @@ impl ChildHandle {
     }
 
     /// Wait for the process to exit
+    ///
+    /// Polls with the lock released in between, so the process can still be
+    /// signalled or killed while a task waits for it.
     pub async fn wait(&mut self) -> io::Result<std::process::ExitStatus> {
-        match self {
-            Self::Unwrapped { child } => {
-                let mut guard = child.lock().await;
-                guard.wait().await
+        loop {
+            let status = match self {
+                Self::Unwrapped { child } => child.lock().await.try_wait()?,
+                Self::Wrapped { child } => child.lock().await.try_wait()?,
+            };
+            if let Some(status) = status {
+                return Ok(status);
             }
-            Self::Wrapped { child } => {
-                let mut guard = child.lock().await;
-                guard.wait().await
+            tokio::time::sleep(WAIT_POLL_INTERVAL).await;
+        }
+    }
+
+    /// Stop the process, giving it `grace` to exit after SIGTERM
+    ///
+    /// A process still running after the grace period is killed. Returns
+    /// whether it had to be killed. Outside Unix the process is killed
+    /// right away.
+    pub async fn terminate(&mut self, grace: Duration) -> io::Result<bool> {
+        #[cfg(unix)]
+        {
+            let signalled = match self {
+                Self::Unwrapped { child } => ChildWrapper::signal(&*child.lock().await, SIGTERM),
+                Self::Wrapped { child } => child.lock().await.signal(SIGTERM),
+            };
+            if signalled.is_ok() && tokio::time::timeout(grace, self.wait()).await.is_ok() {
+                return Ok(false);
             }
         }
+        #[cfg(not(unix))]
+        let _ = grace;
+
+        self.kill().await?;
+        Ok(true)
+    }
+
+    /// Start killing the process without waiting for it to exit
+    ///
+    /// Does nothing if the process is locked by another task (which is then
+    /// waiting for or killing it).
+    pub fn start_kill(&self) -> io::Result<()> {
+        match self {
+            Self::Unwrapped { child } => match child.try_lock() {
+                Ok(mut guard) => guard.start_kill(),
+                Err(_) => Ok(()),
+            },
+            Self::Wrapped { child } => match child.try_lock() {
+                Ok(mut guard) => guard.start_kill(),
+                Err(_) => Ok(()),
+            },
+        }
     }
 
     /// Try to wait without blocking
@@ -134,6 +194,97 @@ impl TerminalExitStatus {
             Self::Aborted => "aborted",
         }
     }
+
+    /// Exit code, if the process exited on its own
+    pub fn exit_code(&self) -> Option<i32> {
+        match self {
+            Self::Exited(code) => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+/// A background process that finished, as sent to exit subscribers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackgroundExit {
+    /// Shell ID of the process
+    pub shell_id: String,
+    /// How it finished
+    pub status: TerminalExitStatus,
+}
+
+/// Output of a background process, keeping at most a fixed number of bytes
+///
+/// Once the limit is exceeded the oldest output is dropped, bringing the
+/// buffer back to three quarters of the limit so that trimming doesn't happen
+/// on every appended line. Offsets count all output ever appended, so readers
+/// can tell how much of what they haven't seen yet was dropped.
+#[derive(Debug, Clone, Default)]
+pub struct OutputBuffer {
+    /// Retained output
+    text: String,
+    /// Bytes dropped from the front so far
+    dropped: usize,
+    /// Maximum bytes retained (unlimited if None)
+    max_bytes: Option<usize>,
+}
+
+impl OutputBuffer {
+    /// Create a buffer retaining at most `max_bytes` bytes
+    pub fn new(max_bytes: Option<usize>) -> Self {
+        Self {
+            text: String::new(),
+            dropped: 0,
+            max_bytes,
+        }
+    }
+
+    /// Append output, dropping the oldest output beyond the limit
+    pub fn push_str(&mut self, output: &str) {
+        self.text.push_str(output);
+        let Some(max_bytes) = self.max_bytes else {
+            return;
+        };
+        if self.text.len() <= max_bytes {
+            return;
+        }
+
+        let mut cut = self.text.len() - max_bytes * 3 / 4;
+        while !self.text.is_char_boundary(cut) {
+            cut += 1;
+        }
+        self.text.drain(..cut);
+        self.dropped += cut;
+    }
+
+    /// Append a single character
+    pub fn push(&mut self, c: char) {
+        self.push_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Total bytes appended, including dropped ones
+    pub fn total_len(&self) -> usize {
+        self.dropped + self.text.len()
+    }
+
+    /// Output from `offset` (in total bytes appended) on
+    ///
+    /// Starts with a marker when part of that output was dropped.
+    pub fn since(&self, offset: usize) -> String {
+        if offset >= self.dropped {
+            return self.text[(offset - self.dropped).min(self.text.len())..].to_string();
+        }
+        format!(
+            "[... {} bytes of earlier output dropped ...]\n{}",
+            self.dropped - offset,
+            self.text
+        )
+    }
+
+    /// All retained output, with a marker if older output was dropped
+    pub fn contents(&self) -> String {
+        self.since(0)
+    }
 }
 
 /// Background terminal state
@@ -144,8 +295,8 @@ pub enum BackgroundTerminal {
         /// The child process handle (wrapped or unwrapped)
         child: ChildHandle,
         /// Accumulated output buffer
-        output_buffer: Arc<Mutex<String>>,
-        /// Last read offset for incremental output
+        output_buffer: Arc<Mutex<OutputBuffer>>,
+        /// Last read offset for incremental output, in total bytes appended
         /// Using AtomicUsize for lock-free atomic operations
         last_read_offset: Arc<AtomicUsize>,
     },
@@ -155,15 +306,24 @@ pub enum BackgroundTerminal {
         status: TerminalExitStatus,
         /// Final output
         final_output: String,
+        /// Bytes of `final_output` already returned by incremental reads
+        read_offset: Arc<AtomicUsize>,
     },
 }
 
 impl BackgroundTerminal {
     /// Create a new running terminal with a child handle
+    ///
+    /// Output is capped at [`DEFAULT_BACKGROUND_OUTPUT_LIMIT`] bytes.
     pub fn new_running(child: ChildHandle) -> Self {
+        Self::new_running_with_limit(child, Some(DEFAULT_BACKGROUND_OUTPUT_LIMIT))
+    }
+
+    /// Create a new running terminal keeping at most `max_output_bytes` of output
+    pub fn new_running_with_limit(child: ChildHandle, max_output_bytes: Option<usize>) -> Self {
         Self::Running {
             child,
-            output_buffer: Arc::new(Mutex::new(String::new())),
+            output_buffer: Arc::new(Mutex::new(OutputBuffer::new(max_output_bytes))),
             last_read_offset: Arc::new(AtomicUsize::new(0)),
         }
     }
@@ -171,13 +331,9 @@ impl BackgroundTerminal {
     /// Create a new running terminal from a legacy Child (unwrapped)
     /// Note: stdout/stderr should be taken before creating the handle
     pub fn new_running_unwrapped(child: Child) -> Self {
-        Self::Running {
-            child: ChildHandle::Unwrapped {
-                child: Arc::new(Mutex::new(child)),
-            },
-            output_buffer: Arc::new(Mutex::new(String::new())),
-            last_read_offset: Arc::new(AtomicUsize::new(0)),
-        }
+        Self::new_running(ChildHandle::Unwrapped {
+            child: Arc::new(Mutex::new(child)),
+        })
     }
 
     /// Check if terminal is still running
@@ -205,8 +361,8 @@ impl BackgroundTerminal {
                 let current_offset = last_read_offset.load(Ordering::Acquire);
 
                 let buffer = output_buffer.lock().await;
-                let new_output = buffer[current_offset..].to_string();
-                let new_len = buffer.len();
+                let new_output = buffer.since(current_offset);
+                let new_len = buffer.total_len();
                 drop(buffer);
 
                 // Update offset using atomic store (lock-free)
@@ -214,7 +370,14 @@ impl BackgroundTerminal {
 
                 new_output
             }
-            Self::Finished { final_output, .. } => final_output.clone(),
+            Self::Finished {
+                final_output,
+                read_offset,
+                ..
+            } => {
+                let offset = read_offset.swap(final_output.len(), Ordering::AcqRel);
+                final_output[offset.min(final_output.len())..].to_string()
+            }
         }
     }
 
@@ -231,7 +394,7 @@ impl BackgroundTerminal {
         match self {
             Self::Running { output_buffer, .. } => {
                 let buffer = output_buffer.lock().await;
-                buffer.clone()
+                buffer.contents()
             }
             Self::Finished { final_output, .. } => final_output.clone(),
         }
@@ -240,11 +403,26 @@ impl BackgroundTerminal {
     /// Transition to finished state
     pub async fn finish(self, status: TerminalExitStatus) -> Self {
         match self {
-            Self::Running { output_buffer, .. } => {
-                let final_output = output_buffer.lock().await.clone();
+            Self::Running {
+                output_buffer,
+                last_read_offset,
+                ..
+            } => {
+                let buffer = output_buffer.lock().await;
+                let final_output = buffer.contents();
+                // Output already read stays read; the retained text ends `final_output`
+                let offset = last_read_offset.load(Ordering::Acquire);
+                let read = if offset < buffer.dropped {
+                    0
+                } else {
+                    let read_text = (offset - buffer.dropped).min(buffer.text.len());
+                    final_output.len() - buffer.text.len() + read_text
+                };
+                drop(buffer);
                 Self::Finished {
                     status,
                     final_output,
+                    read_offset: Arc::new(AtomicUsize::new(read)),
                 }
             }
             finished @ Self::Finished { .. } => finished,
@@ -252,26 +430,188 @@ impl BackgroundTerminal {
     }
 }
 
+/// Default maximum lifetime of a background process (4 hours)
+pub const DEFAULT_BACKGROUND_MAX_LIFETIME: Duration = Duration::from_secs(4 * 60 * 60);
+
+/// Default maximum output kept per background process (1MB)
+pub const DEFAULT_BACKGROUND_OUTPUT_LIMIT: usize = 1024 * 1024;
+
+/// Exit events buffered per subscriber before the oldest are dropped
+const EXIT_CHANNEL_CAPACITY: usize = 64;
+
+/// What a background shell was started with
+#[derive(Debug, Clone)]
+struct ShellInfo {
+    /// The command it runs
+    command: String,
+    /// When it was registered
+    started_at: SystemTime,
+}
+
+/// Overview of a background shell, as listed by the ListShells tool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShellSummary {
+    /// Shell ID used by BashOutput and KillShell
+    pub shell_id: String,
+    /// The command it runs
+    pub command: String,
+    /// Status string (`running`, `exited`, `killed`, ...)
+    pub status: &'static str,
+    /// When it was started
+    pub started_at: SystemTime,
+    /// Bytes of output produced so far, including any dropped ones
+    pub output_bytes: usize,
+}
+
 /// Manager for background terminal processes
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct BackgroundProcessManager {
     /// Map of shell ID to background terminal
     terminals: DashMap<String, BackgroundTerminal>,
+    /// Command and start time of each terminal, by shell ID
+    shells: DashMap<String, ShellInfo>,
+    /// Status of terminals being stopped, recorded once their process exits
+    stopping: DashMap<String, TerminalExitStatus>,
+    /// Sender of exit events, one per finished terminal
+    exits: broadcast::Sender<BackgroundExit>,
+    /// Time after which a still running process is killed (unlimited if None)
+    max_lifetime: Option<Duration>,
+    /// Maximum output kept per process (unlimited if None)
+    max_output_bytes: Option<usize>,
+}
+
+impl Default for BackgroundProcessManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl BackgroundProcessManager {
     /// Create a new background process manager
+    ///
+    /// Processes run until they exit or are killed; use
+    /// [`with_max_lifetime`](Self::with_max_lifetime) to limit them. Output
+    /// is capped at [`DEFAULT_BACKGROUND_OUTPUT_LIMIT`] bytes per process.
     pub fn new() -> Self {
         Self {
             terminals: DashMap::new(),
+            shells: DashMap::new(),
+            stopping: DashMap::new(),
+            exits: broadcast::channel(EXIT_CHANNEL_CAPACITY).0,
+            max_lifetime: None,
+            max_output_bytes: Some(DEFAULT_BACKGROUND_OUTPUT_LIMIT),
         }
     }
 
-    /// Register a new background terminal
-    pub fn register(&self, shell_id: String, terminal: BackgroundTerminal) {
+    /// Create a manager that kills processes still running after `max_lifetime`
+    pub fn with_max_lifetime(max_lifetime: Option<Duration>) -> Self {
+        let mut manager = Self::new();
+        manager.max_lifetime = max_lifetime;
+        manager
+    }
+
+    /// Set the maximum output kept per process (None keeps all of it)
+    #[must_use]
+    pub fn with_max_output_bytes(mut self, max_output_bytes: Option<usize>) -> Self {
+        self.max_output_bytes = max_output_bytes;
+        self
+    }
+
+    /// Time after which a still running process is killed
+    pub fn max_lifetime(&self) -> Option<Duration> {
+        self.max_lifetime
+    }
+
+    /// Maximum output kept per process
+    pub fn max_output_bytes(&self) -> Option<usize> {
+        self.max_output_bytes
+    }
+
+    /// Register a new background terminal running `command`
+    ///
+    /// With a maximum lifetime configured, a running terminal is killed once
+    /// it has run that long.
+    pub fn register(
+        self: &Arc<Self>,
+        shell_id: String,
+        command: impl Into<String>,
+        terminal: BackgroundTerminal,
+    ) {
+        if let Some(max_lifetime) = self.max_lifetime
+            && terminal.is_running()
+        {
+            let manager = Arc::downgrade(self);
+            let shell_id = shell_id.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(max_lifetime).await;
+                Self::expire(&manager, &shell_id, max_lifetime).await;
+            });
+        }
+        self.shells.insert(
+            shell_id.clone(),
+            ShellInfo {
+                command: command.into(),
+                started_at: SystemTime::now(),
+            },
+        );
         self.terminals.insert(shell_id, terminal);
     }
 
+    /// Kill a terminal that outlived the maximum lifetime
+    async fn expire(manager: &Weak<Self>, shell_id: &str, max_lifetime: Duration) {
+        // The session (and with it the manager) may be gone already
+        let Some(manager) = manager.upgrade() else {
+            return;
+        };
+        let Some(terminal) = manager.get(shell_id) else {
+            return;
+        };
+        let BackgroundTerminal::Running { child, .. } = &*terminal else {
+            return;
+        };
+        let mut child = child.clone();
+        drop(terminal); // Release DashMap read lock before await
+
+        tracing::warn!(
+            shell_id = %shell_id,
+            max_lifetime_secs = max_lifetime.as_secs(),
+            "Background process exceeded its maximum lifetime, killing it"
+        );
+        // The output reader records the exit as a timeout
+        manager.stop_with(shell_id, TerminalExitStatus::TimedOut);
+        match child.kill().await {
+            // Wait for the process to exit (prevents zombie)
+            Ok(()) => {
+                drop(child.wait().await);
+                manager
+                    .finish_terminal(shell_id, TerminalExitStatus::TimedOut)
+                    .await;
+            }
+            Err(e) => {
+                manager.cancel_stop(shell_id);
+                tracing::warn!(
+                    shell_id = %shell_id,
+                    error = %e,
+                    "Failed to kill background process that exceeded its maximum lifetime"
+                );
+            }
+        }
+    }
+
+    /// Record that the terminal `shell_id` is being stopped with `status`
+    ///
+    /// Whoever reports the process's exit, the terminal finishes with
+    /// `status` rather than as having exited on its own. Undo it with
+    /// [`cancel_stop`](Self::cancel_stop) if stopping fails.
+    pub fn stop_with(&self, shell_id: &str, status: TerminalExitStatus) {
+        self.stopping.insert(shell_id.to_string(), status);
+    }
+
+    /// Forget a [`stop_with`](Self::stop_with) whose process kept running
+    pub fn cancel_stop(&self, shell_id: &str) {
+        self.stopping.remove(shell_id);
+    }
+
     /// Check if a terminal exists
     pub fn has_terminal(&self, shell_id: &str) -> bool {
         self.terminals.contains_key(shell_id)
@@ -287,17 +627,41 @@ impl BackgroundProcessManager {
 
     /// Remove terminal by ID
     pub fn remove(&self, shell_id: &str) -> Option<(String, BackgroundTerminal)> {
+        self.shells.remove(shell_id);
         self.terminals.remove(shell_id)
     }
 
     /// Update a terminal to finished state
+    ///
+    /// Exit subscribers are told the first time a terminal finishes; later
+    /// calls keep the first status. A terminal being stopped (see
+    /// [`stop_with`](Self::stop_with)) gets the status it is stopped with.
     pub async fn finish_terminal(&self, shell_id: &str, status: TerminalExitStatus) {
+        let status = self
+            .stopping
+            .remove(shell_id)
+            .map_or(status, |(_, stopped)| stopped);
         if let Some((id, terminal)) = self.terminals.remove(shell_id) {
+            let was_running = terminal.is_running();
             let finished = terminal.finish(status).await;
-            self.terminals.insert(id, finished);
+            self.terminals.insert(id.clone(), finished);
+            if was_running {
+                // Nobody listening is fine
+                drop(self.exits.send(BackgroundExit {
+                    shell_id: id,
+                    status,
+                }));
+            }
         }
     }
 
+    /// Subscribe to terminals finishing
+    ///
+    /// Only terminals finishing after the call are reported.
+    pub fn subscribe_exits(&self) -> broadcast::Receiver<BackgroundExit> {
+        self.exits.subscribe()
+    }
+
     /// Get number of terminals
     pub fn count(&self) -> usize {
         self.terminals.len()
@@ -307,6 +671,71 @@ impl BackgroundProcessManager {
     pub fn shell_ids(&self) -> Vec<String> {
         self.terminals.iter().map(|r| r.key().clone()).collect()
     }
+
+    /// Summarize all terminals, oldest first
+    pub async fn list(&self) -> Vec<ShellSummary> {
+        // Collect what's needed before awaiting: DashMap refs can't be held across awaits
+        let entries: Vec<_> = self
+            .terminals
+            .iter()
+            .map(|terminal| {
+                let output = match terminal.value() {
+                    BackgroundTerminal::Running { output_buffer, .. } => Err(output_buffer.clone()),
+                    BackgroundTerminal::Finished { final_output, .. } => Ok(final_output.len()),
+                };
+                (terminal.key().clone(), terminal.status_str(), output)
+            })
+            .collect();
+
+        let mut summaries = Vec::with_capacity(entries.len());
+        for (shell_id, status, output) in entries {
+            let output_bytes = match output {
+                Ok(len) => len,
+                Err(buffer) => buffer.lock().await.total_len(),
+            };
+            let info = self.shells.get(&shell_id).map(|info| info.clone());
+            summaries.push(ShellSummary {
+                command: info
+                    .as_ref()
+                    .map(|info| info.command.clone())
+                    .unwrap_or_default(),
+                started_at: info.map_or(SystemTime::UNIX_EPOCH, |info| info.started_at),
+                shell_id,
+                status,
+                output_bytes,
+            });
+        }
+        summaries.sort_by(|a, b| {
+            a.started_at
+                .cmp(&b.started_at)
+                .then_with(|| a.shell_id.cmp(&b.shell_id))
+        });
+        summaries
+    }
+}
+
+impl Drop for BackgroundProcessManager {
+    /// Kill processes still running when the session goes away
+    ///
+    /// Session cleanup normally kills them first; this catches sessions that
+    /// are dropped without it.
+    fn drop(&mut self) {
+        for terminal in &self.terminals {
+            if let BackgroundTerminal::Running { child, .. } = terminal.value() {
+                tracing::info!(
+                    shell_id = %terminal.key(),
+                    "Killing background process left running at session end"
+                );
+                if let Err(e) = child.start_kill() {
+                    tracing::warn!(
+                        shell_id = %terminal.key(),
+                        error = %e,
+                        "Failed to kill background process at session end"
+                    );
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -327,6 +756,142 @@ mod tests {
         assert_eq!(manager.count(), 0);
     }
 
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_background_process_killed_after_max_lifetime() {
+        let manager = Arc::new(BackgroundProcessManager::with_max_lifetime(Some(
+            Duration::from_millis(100),
+        )));
+        let child = tokio::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let handle = ChildHandle::Unwrapped {
+            child: Arc::new(Mutex::new(child)),
+        };
+        manager.register(
+            "shell-1".to_string(),
+            "sleep 30",
+            BackgroundTerminal::new_running(handle.clone()),
+        );
+        assert!(manager.get("shell-1").unwrap().is_running());
+
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+        while manager.get("shell-1").unwrap().is_running() {
+            assert!(
+                tokio::time::Instant::now() < deadline,
+                "process should be killed after its maximum lifetime"
+            );
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(manager.get("shell-1").unwrap().status_str(), "timedOut");
+
+        // The process itself is gone, not just marked as finished
+        let status = handle.clone().wait().await.unwrap();
+        assert!(!status.success());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_terminate_kills_after_grace_period() {
+        let mut child = tokio::process::Command::new("sh")
+            .args([
+                "-c",
+                "trap '' TERM; echo ready; while :; do sleep 0.1; done",
+            ])
+            .stdout(std::process::Stdio::piped())
+            .spawn()
+            .unwrap();
+        // Wait until the trap is installed
+        let mut stdout = child.stdout.take().unwrap();
+        let mut handle = ChildHandle::Unwrapped {
+            child: Arc::new(Mutex::new(child)),
+        };
+        let mut ready = [0u8; 6];
+        tokio::io::AsyncReadExt::read_exact(&mut stdout, &mut ready)
+            .await
+            .unwrap();
+
+        let forced = handle.terminate(Duration::from_millis(200)).await.unwrap();
+        assert!(forced, "a process ignoring SIGTERM should be killed");
+        assert!(!handle.wait().await.unwrap().success());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_background_output_keeps_recent_output() {
+        let mut buffer = OutputBuffer::new(Some(100));
+        for i in 0..50 {
+            buffer.push_str(&format!("line {i:02}\n"));
+        }
+
+        // 50 lines of 8 bytes: only the most recent ones fit in 100 bytes
+        let contents = buffer.contents();
+        assert!(contents.starts_with("[... "));
+        assert!(contents.contains("bytes of earlier output dropped ...]"));
+        assert!(contents.ends_with("line 49\n"));
+        assert!(!contents.contains("line 00"));
+        assert_eq!(buffer.total_len(), 400);
+
+        // Incremental reads report output dropped before it was read
+        let child = tokio::process::Command::new("true").spawn().unwrap();
+        let terminal = BackgroundTerminal::new_running_with_limit(
+            ChildHandle::Unwrapped {
+                child: Arc::new(Mutex::new(child)),
+            },
+            Some(16),
+        );
+        terminal.append_output("first\n").await;
+        assert_eq!(terminal.get_incremental_output().await, "first\n");
+        terminal.append_output("second\nthird\nfourth\n").await;
+        let output = terminal.get_incremental_output().await;
+        assert!(output.starts_with("[... "), "{output}");
+        assert!(output.ends_with("fourth\n"), "{output}");
+        assert!(terminal.get_incremental_output().await.is_empty());
+        assert!(terminal.get_all_output().await.len() < 64);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_background_exit_is_reported() {
+        let manager = Arc::new(BackgroundProcessManager::new());
+        let mut exits = manager.subscribe_exits();
+        let child = tokio::process::Command::new("sh")
+            .args(["-c", "exit 3"])
+            .spawn()
+            .unwrap();
+        let mut handle = ChildHandle::Unwrapped {
+            child: Arc::new(Mutex::new(child)),
+        };
+        manager.register(
+            "shell-1".to_string(),
+            "exit 3",
+            BackgroundTerminal::new_running(handle.clone()),
+        );
+
+        let code = handle.wait().await.unwrap().code().unwrap();
+        manager
+            .finish_terminal("shell-1", TerminalExitStatus::Exited(code))
+            .await;
+
+        let exit = exits.recv().await.unwrap();
+        assert_eq!(
+            exit,
+            BackgroundExit {
+                shell_id: "shell-1".to_string(),
+                status: TerminalExitStatus::Exited(3),
+            }
+        );
+        assert_eq!(exit.status.exit_code(), Some(3));
+
+        // Finishing again doesn't report a second exit
+        manager
+            .finish_terminal("shell-1", TerminalExitStatus::Killed)
+            .await;
+        assert!(exits.try_recv().is_err());
+        assert_eq!(manager.get("shell-1").unwrap().status_str(), "exited");
+    }
+
     #[test]
     fn test_background_process_manager_has_terminal() {
         let manager = BackgroundProcessManager::new();
@@ -338,10 +903,27 @@ mod tests {
         let terminal = BackgroundTerminal::Finished {
             status: TerminalExitStatus::Exited(0),
             final_output: "test output".to_string(),
+            read_offset: Arc::new(AtomicUsize::new(0)),
         };
 
         assert!(!terminal.is_running());
         assert_eq!(terminal.status_str(), "exited");
         assert_eq!(terminal.get_all_output().await, "test output");
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_incremental_output_after_finish() {
+        let child = tokio::process::Command::new("true").spawn().unwrap();
+        let terminal = BackgroundTerminal::new_running_unwrapped(child);
+        terminal.append_output("first\n").await;
+        assert_eq!(terminal.get_incremental_output().await, "first\n");
+        terminal.append_output("second\n").await;
+
+        // Only output not read while running is returned, once
+        let terminal = terminal.finish(TerminalExitStatus::Exited(0)).await;
+        assert_eq!(terminal.get_incremental_output().await, "second\n");
+        assert!(terminal.get_incremental_output().await.is_empty());
+        assert_eq!(terminal.get_all_output().await, "first\nsecond\n");
+    }
 }
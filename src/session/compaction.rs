@@ -0,0 +1,178 @@
+//! Automatic conversation compaction
+//!
+//! When the estimated context of a session nears the model's context window,
+//! earlier turns are summarized by the Claude CLI's `/compact` command and the
+//! conversation continues from that summary.
+
+use async_trait::async_trait;
+
+use crate::types::{DEFAULT_CONTEXT_WINDOW, Result};
+
+use super::Session;
+
+/// Fraction of the context window at which compaction is triggered
+pub const DEFAULT_COMPACT_THRESHOLD: f64 = 0.8;
+
+/// Something that can compact a conversation
+#[async_trait]
+pub trait Compactor: Send + Sync {
+    /// Summarize earlier turns so the conversation continues from the summary
+    async fn compact(&self) -> Result<()>;
+}
+
+#[async_trait]
+impl Compactor for Session {
+    async fn compact(&self) -> Result<()> {
+        self.compact_conversation().await
+    }
+}
+
+/// When to compact a conversation
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompactionPolicy {
+    /// Context window of the model in tokens
+    pub context_window: u64,
+    /// Fraction of the context window at which compaction is triggered
+    pub threshold: f64,
+}
+
+impl Default for CompactionPolicy {
+    fn default() -> Self {
+        Self {
+            context_window: DEFAULT_CONTEXT_WINDOW,
+            threshold: DEFAULT_COMPACT_THRESHOLD,
+        }
+    }
+}
+
+impl CompactionPolicy {
+    /// Policy for a model with the given context window and the default threshold
+    pub fn new(context_window: u64) -> Self {
+        Self {
+            context_window,
+            ..Self::default()
+        }
+    }
+
+    /// Number of context tokens at which compaction is triggered
+    #[allow(
+        clippy::cast_possible_truncation,
+        clippy::cast_precision_loss,
+        clippy::cast_sign_loss
+    )]
+    pub fn trigger_tokens(&self) -> u64 {
+        (self.context_window as f64 * self.threshold.clamp(0.0, 1.0)) as u64
+    }
+
+    /// Check whether a context of `context_tokens` should be compacted
+    pub fn should_compact(&self, context_tokens: u64) -> bool {
+        context_tokens > 0 && context_tokens >= self.trigger_tokens()
+    }
+}
+
+/// Compact the session's conversation if its estimated context crossed the
+/// policy threshold
+///
+/// Returns the estimated context size before compaction when compaction ran.
+pub async fn compact_if_needed(
+    session: &Session,
+    policy: &CompactionPolicy,
+    compactor: &dyn Compactor,
+) -> Result<Option<u64>> {
+    let context_tokens = session.usage_tracker().context_tokens();
+    if !policy.should_compact(context_tokens) {
+        return Ok(None);
+    }
+
+    tracing::info!(
+        session_id = %session.session_id,
+        context_tokens,
+        trigger_tokens = policy.trigger_tokens(),
+        "Context nearing limit, compacting conversation"
+    );
+    compactor.compact().await?;
+    session.usage_tracker().reset_context();
+
+    Ok(Some(context_tokens))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AgentConfig, TokenUsage};
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct CountingCompactor {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl Compactor for CountingCompactor {
+        async fn compact(&self) -> Result<()> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    fn usage(input_tokens: u64) -> TokenUsage {
+        TokenUsage {
+            input_tokens,
+            output_tokens: 0,
+            cache_read_input_tokens: None,
+            cache_creation_input_tokens: None,
+        }
+    }
+
+    #[test]
+    fn test_policy_threshold() {
+        let policy = CompactionPolicy {
+            context_window: 1000,
+            threshold: 0.8,
+        };
+        assert_eq!(policy.trigger_tokens(), 800);
+        assert!(!policy.should_compact(0));
+        assert!(!policy.should_compact(799));
+        assert!(policy.should_compact(800));
+    }
+
+    #[tokio::test]
+    async fn test_compact_when_crossing_threshold() {
+        let session = Session::new(
+            "test-compaction".to_string(),
+            PathBuf::from("/tmp"),
+            &AgentConfig::default(),
+            None,
+        )
+        .unwrap();
+        let policy = CompactionPolicy {
+            context_window: 1000,
+            threshold: 0.8,
+        };
+        let compactor = CountingCompactor::default();
+
+        // Below the threshold nothing happens
+        session.usage_tracker().record_context(&usage(500));
+        let compacted = compact_if_needed(&session, &policy, &compactor)
+            .await
+            .unwrap();
+        assert_eq!(compacted, None);
+        assert_eq!(compactor.calls.load(Ordering::SeqCst), 0);
+
+        // Crossing it triggers exactly one compaction and resets the estimate
+        session.usage_tracker().record_context(&usage(850));
+        let compacted = compact_if_needed(&session, &policy, &compactor)
+            .await
+            .unwrap();
+        assert_eq!(compacted, Some(850));
+        assert_eq!(compactor.calls.load(Ordering::SeqCst), 1);
+        assert_eq!(session.usage_tracker().context_tokens(), 0);
+
+        let compacted = compact_if_needed(&session, &policy, &compactor)
+            .await
+            .unwrap();
+        assert_eq!(compacted, None);
+        assert_eq!(compactor.calls.load(Ordering::SeqCst), 1);
+    }
+}
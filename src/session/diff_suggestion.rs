@@ -0,0 +1,117 @@
+//! Client-reviewed diffs for Edit and Write
+//!
+//! Clients that opt in with `_meta.diffSuggestions` on `session/new` receive
+//! the proposed diff of an Edit or Write in its permission request. When the
+//! user tweaks the suggestion before accepting it, the client returns the
+//! edited new text in the response's `_meta.editedText`, and the tool writes
+//! that text instead of the model's proposal.
+//!
+//! Clients that don't opt in keep the regular permission flow, where the
+//! model's content is written as soon as the call is allowed.
+
+use sacp::schema::Diff;
+
+/// Response `_meta` key carrying the client's edited new text
+pub const EDITED_TEXT_META_KEY: &str = "editedText";
+
+/// Proposed change of an Edit or Write tool call
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffSuggestion {
+    /// File being changed
+    pub path: String,
+    /// Text being replaced (None for Write)
+    pub old_text: Option<String>,
+    /// Proposed new text
+    pub new_text: String,
+}
+
+impl DiffSuggestion {
+    /// Build the suggestion for an Edit or Write tool call
+    ///
+    /// Returns None for other tools or incomplete input.
+    pub fn from_tool_input(tool_name: &str, input: &serde_json::Value) -> Option<Self> {
+        let str_field = |key: &str| input.get(key).and_then(|v| v.as_str()).map(String::from);
+        let path = str_field("file_path")?;
+
+        match tool_name.strip_prefix("mcp__acp__").unwrap_or(tool_name) {
+            "Edit" => Some(Self {
+                path,
+                old_text: Some(str_field("old_string")?),
+                new_text: str_field("new_string")?,
+            }),
+            "Write" => Some(Self {
+                path,
+                old_text: None,
+                new_text: str_field("content")?,
+            }),
+            _ => None,
+        }
+    }
+
+    /// The suggestion as ACP diff content
+    pub fn to_diff(&self) -> Diff {
+        Diff::new(self.path.clone(), self.new_text.clone()).old_text(self.old_text.clone())
+    }
+
+    /// Tool input that writes `edited_text` in place of the proposed new text
+    ///
+    /// Returns None when the client left the suggestion unchanged.
+    pub fn apply_edit(
+        &self,
+        input: &serde_json::Value,
+        edited_text: &str,
+    ) -> Option<serde_json::Value> {
+        if edited_text == self.new_text {
+            return None;
+        }
+        let key = if self.old_text.is_some() {
+            "new_string"
+        } else {
+            "content"
+        };
+        let mut updated = input.clone();
+        updated.as_object_mut()?.insert(
+            key.to_string(),
+            serde_json::Value::String(edited_text.to_string()),
+        );
+        Some(updated)
+    }
+}
+
+/// Extract the client's edited text from a permission response `_meta`
+pub fn edited_text(meta: Option<&serde_json::Map<String, serde_json::Value>>) -> Option<String> {
+    meta?
+        .get(EDITED_TEXT_META_KEY)
+        .and_then(|v| v.as_str())
+        .map(String::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_suggestion_from_tool_input() {
+        let edit = json!({"file_path": "/p/a.rs", "old_string": "a", "new_string": "b"});
+        assert_eq!(
+            DiffSuggestion::from_tool_input("mcp__acp__Edit", &edit),
+            Some(DiffSuggestion {
+                path: "/p/a.rs".to_string(),
+                old_text: Some("a".to_string()),
+                new_text: "b".to_string(),
+            })
+        );
+
+        let write = json!({"file_path": "/p/b.rs", "content": "fn main() {}"});
+        let suggestion = DiffSuggestion::from_tool_input("Write", &write).unwrap();
+        assert_eq!(suggestion.old_text, None);
+        assert_eq!(
+            suggestion.apply_edit(&write, "fn main() { run() }"),
+            Some(json!({"file_path": "/p/b.rs", "content": "fn main() { run() }"}))
+        );
+        assert_eq!(suggestion.apply_edit(&write, "fn main() {}"), None);
+
+        assert!(DiffSuggestion::from_tool_input("Bash", &json!({"command": "ls"})).is_none());
+    }
+}
@@ -0,0 +1,217 @@
+//! One-shot completions on the small/fast model
+//!
+//! Internal helper tasks (such as naming a session) don't need the primary
+//! model. They run as a single, tool-less turn on `ANTHROPIC_SMALL_FAST_MODEL`,
+//! falling back to the primary model when no fast model is configured.
+
+use async_trait::async_trait;
+use claude_code_agent_sdk::{ClaudeAgentOptions, Message};
+
+use crate::types::{AgentConfig, AgentError, Result};
+
+use super::Session;
+
+/// Maximum characters of each side of the exchange sent for title generation
+const TITLE_EXCERPT_LENGTH: usize = 2000;
+
+/// A model that answers a single prompt with plain text
+#[async_trait]
+pub trait FastModel: Send + Sync {
+    /// Send `prompt` and return the model's text response
+    async fn complete(&self, prompt: &str) -> Result<String>;
+}
+
+/// [`FastModel`] backed by a one-shot Claude SDK query
+#[derive(Debug, Clone)]
+pub struct SdkFastModel {
+    config: AgentConfig,
+}
+
+impl SdkFastModel {
+    /// Create a fast model client from the agent configuration
+    pub fn new(config: AgentConfig) -> Self {
+        Self { config }
+    }
+
+    /// The model used for completions, if one is configured
+    pub fn model(&self) -> Option<&str> {
+        self.config.internal_model()
+    }
+
+    /// Build the options for a single, tool-less turn on the internal model
+    fn options(&self) -> ClaudeAgentOptions {
+        let mut options = ClaudeAgentOptions::builder().max_turns(1).build();
+        self.config.apply_to_options(&mut options);
+        options.model = self.model().map(str::to_string);
+        options.fallback_model = None;
+        options.max_thinking_tokens = None;
+        options
+    }
+}
+
+#[async_trait]
+impl FastModel for SdkFastModel {
+    async fn complete(&self, prompt: &str) -> Result<String> {
+        let messages = claude_code_agent_sdk::query(prompt, Some(self.options())).await?;
+        messages
+            .into_iter()
+            .find_map(|message| match message {
+                Message::Result(result) if !result.is_error => result.result,
+                _ => None,
+            })
+            .ok_or_else(|| AgentError::Internal("Fast model returned no result".to_string()))
+    }
+}
+
+/// Ask the fast model for a short title summarizing the first exchange
+/// and store it on the session
+///
+/// Titles set explicitly by the client are never replaced. Returns the
+/// stored title, or `None` if nothing was stored.
+pub async fn generate_session_title(
+    session: &Session,
+    model: &dyn FastModel,
+    user_prompt: &str,
+    assistant_response: &str,
+) -> Result<Option<String>> {
+    if session.has_explicit_title() {
+        return Ok(None);
+    }
+
+    let prompt = format!(
+        "Write a short title (at most 6 words) that summarizes this conversation. \
+         Reply with the title only, without quotes or punctuation at the end.\n\n\
+         User:\n{}\n\nAssistant:\n{}",
+        excerpt(user_prompt),
+        excerpt(assistant_response)
+    );
+    let response = model.complete(&prompt).await?;
+
+    Ok(session.set_generated_title(clean_title(&response)))
+}
+
+fn excerpt(text: &str) -> String {
+    text.chars().take(TITLE_EXCERPT_LENGTH).collect()
+}
+
+/// Strip the decoration models tend to add around a bare title
+fn clean_title(response: &str) -> &str {
+    let line = response
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    let line = line.strip_prefix("Title:").unwrap_or(line);
+    line.trim_start_matches(|c: char| {
+        c.is_whitespace() || matches!(c, '"' | '\'' | '`' | '*' | '#')
+    })
+    .trim_end_matches(|c: char| c.is_whitespace() || matches!(c, '"' | '\'' | '`' | '*' | '.'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    /// Fast model that returns a canned response and records prompts
+    struct MockFastModel {
+        response: String,
+        prompts: Mutex<Vec<String>>,
+    }
+
+    impl MockFastModel {
+        fn new(response: &str) -> Self {
+            Self {
+                response: response.to_string(),
+                prompts: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl FastModel for MockFastModel {
+        async fn complete(&self, prompt: &str) -> Result<String> {
+            self.prompts.lock().unwrap().push(prompt.to_string());
+            Ok(self.response.clone())
+        }
+    }
+
+    fn test_session(id: &str) -> std::sync::Arc<Session> {
+        Session::new(
+            id.to_string(),
+            PathBuf::from("/tmp"),
+            &AgentConfig::default(),
+            None,
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_generate_session_title() {
+        let session = test_session("test-generate-title");
+        session.set_title_from_prompt("the login page throws a 500 when the password is empty");
+
+        let model = MockFastModel::new("\"Fix empty password login error.\"\n");
+        let title = generate_session_title(
+            &session,
+            &model,
+            "the login page throws a 500 when the password is empty",
+            "Fixed the missing check in auth.rs",
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(title.as_deref(), Some("Fix empty password login error"));
+        assert_eq!(
+            session.title().as_deref(),
+            Some("Fix empty password login error")
+        );
+
+        let prompts = model.prompts.lock().unwrap();
+        assert_eq!(prompts.len(), 1);
+        assert!(prompts[0].contains("Fixed the missing check in auth.rs"));
+    }
+
+    #[tokio::test]
+    async fn test_generate_session_title_keeps_explicit_title() {
+        let session = test_session("test-generate-title-explicit");
+        session.set_title("Auth refactor");
+
+        let model = MockFastModel::new("Something else");
+        let title = generate_session_title(&session, &model, "hi", "hello")
+            .await
+            .unwrap();
+
+        assert_eq!(title, None);
+        assert_eq!(session.title().as_deref(), Some("Auth refactor"));
+        assert!(model.prompts.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_sdk_fast_model_targets_small_fast_model() {
+        let config = AgentConfig {
+            model: Some("claude-opus-4-1".to_string()),
+            small_fast_model: Some("claude-haiku-4-5".to_string()),
+            ..Default::default()
+        };
+        let options = SdkFastModel::new(config.clone()).options();
+        assert_eq!(options.model.as_deref(), Some("claude-haiku-4-5"));
+        assert_eq!(options.fallback_model, None);
+
+        // Without a fast model, internal calls use the primary model
+        let config = AgentConfig {
+            small_fast_model: None,
+            ..config
+        };
+        let options = SdkFastModel::new(config).options();
+        assert_eq!(options.model.as_deref(), Some("claude-opus-4-1"));
+    }
+
+    #[test]
+    fn test_clean_title() {
+        assert_eq!(clean_title("Title: **Add PDF support**"), "Add PDF support");
+        assert_eq!(clean_title("\n`Parser cleanup`.\nextra"), "Parser cleanup");
+        assert_eq!(clean_title("   "), "");
+    }
+}
@@ -4,13 +4,16 @@
 
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 
 use dashmap::DashMap;
 use tracing::instrument;
 
-use crate::types::{AgentConfig, AgentError, NewSessionMeta, Result};
+use crate::types::{AgentConfig, AgentError, NewSessionMeta, Result, SessionInfo};
 
+use super::permission::PermissionMode;
 use super::session::Session;
+use super::session_index::SessionIndex;
 
 /// Manager for active sessions
 ///
@@ -20,6 +23,10 @@ use super::session::Session;
 pub struct SessionManager {
     /// Active sessions keyed by session_id
     sessions: DashMap<String, Arc<Session>>,
+    /// Where session permission modes are recorded for resuming (None = not recorded)
+    session_index: Option<Arc<SessionIndex>>,
+    /// Whether the client opted out of permission requests at initialize
+    permission_requests_declined: AtomicBool,
 }
 
 impl SessionManager {
@@ -27,9 +34,57 @@ impl SessionManager {
     pub fn new() -> Self {
         Self {
             sessions: DashMap::new(),
+            session_index: None,
+            permission_requests_declined: AtomicBool::new(false),
         }
     }
 
+    /// Record session permission modes in `index` and restore them on resume
+    #[must_use]
+    pub fn with_session_index(mut self, index: SessionIndex) -> Self {
+        self.session_index = Some(Arc::new(index));
+        self
+    }
+
+    /// Permission mode recorded for `session_id` in the session index, if any
+    pub fn recorded_permission_mode(&self, session_id: &str) -> Option<PermissionMode> {
+        self.session_index.as_ref()?.permission_mode(session_id)
+    }
+
+    /// Resume in the recorded permission mode of the session `meta` resumes
+    ///
+    /// Does nothing if `meta` doesn't resume a session, already sets a
+    /// permission mode, or no mode was recorded for the session. Modes that
+    /// approve edits without asking (acceptEdits, bypassPermissions) need
+    /// the user to opt in again, so they aren't restored.
+    pub fn restore_permission_mode(&self, meta: &mut NewSessionMeta) {
+        if meta.get_permission_mode().is_some() {
+            return;
+        }
+        let Some(mode) = meta
+            .get_resume_session_id()
+            .and_then(|session_id| self.recorded_permission_mode(session_id))
+        else {
+            return;
+        };
+        if mode.auto_approve_edits() {
+            tracing::info!(
+                mode = mode.as_str(),
+                "Not restoring recorded permission mode: it needs the user's opt-in"
+            );
+            return;
+        }
+        *meta = std::mem::take(meta).with_permission_mode(mode.as_str());
+    }
+
+    /// Record whether the client answers `session/request_permission`
+    ///
+    /// Applies to the sessions created afterwards; called at initialize.
+    pub fn set_permission_requests_supported(&self, supported: bool) {
+        self.permission_requests_declined
+            .store(!supported, Ordering::SeqCst);
+    }
+
     /// Create a new session and store it
     ///
     /// # Arguments
@@ -60,6 +115,12 @@ impl SessionManager {
             dashmap::Entry::Vacant(vacant) => {
                 // Session::new() now directly returns Arc<Session>
                 let arc_session = Session::new(session_id, cwd, config, meta)?;
+                if let Some(index) = &self.session_index {
+                    arc_session.set_session_index(Arc::clone(index));
+                }
+                arc_session.set_permission_requests_supported(
+                    !self.permission_requests_declined.load(Ordering::SeqCst),
+                );
                 vacant.insert(Arc::clone(&arc_session));
                 Ok(arc_session)
             }
@@ -113,6 +174,14 @@ impl SessionManager {
         self.sessions.iter().map(|r| r.key().clone()).collect()
     }
 
+    /// List all sessions with their titles, sorted by session ID
+    pub fn list_sessions(&self) -> Vec<SessionInfo> {
+        let mut sessions: Vec<SessionInfo> =
+            self.sessions.iter().map(|r| r.value().info()).collect();
+        sessions.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+        sessions
+    }
+
     /// Clear all sessions with cleanup
     ///
     /// This properly cleans up all child processes for all sessions
@@ -142,6 +211,10 @@ impl SessionManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mcp::{
+        DomainFilter, EnvInfoConfig, FinalNewlinePolicy, InteractiveInputPolicy, ToolTimeouts,
+    };
+    use crate::types::PricingTable;
 
     fn test_config() -> AgentConfig {
         AgentConfig {
@@ -150,6 +223,40 @@ mod tests {
             model: None,
             small_fast_model: None,
             max_thinking_tokens: None,
+            auto_generate_title: false,
+            auto_compact: false,
+            context_window: None,
+            synthesize_bash_description: false,
+            interactive_input: InteractiveInputPolicy::default(),
+            max_line_length: None,
+            read_max_lines: None,
+            max_file_size: None,
+            tool_timeouts: ToolTimeouts::default(),
+            max_tool_argument_bytes: None,
+            bash_output_limit_bytes: None,
+            bash_timeout_warning_percent: None,
+            combine_streams: true,
+            plans_dir: None,
+            accept_edits_after_plan: false,
+            auto_approve_within_cwd: false,
+            permission_audit_log: None,
+            permission_request_timeout: None,
+            permission_fallback: crate::permissions::PermissionFallback::Deny,
+            verify_writes: false,
+            prefer_ripgrep: true,
+            diff_context_lines: None,
+            env_info: EnvInfoConfig::default(),
+            allow_localhost_fetch: false,
+            web_fetch_domains: DomainFilter::default(),
+            web_search_domains: DomainFilter::default(),
+            background_max_lifetime: None,
+            background_output_limit: None,
+            model_pricing: PricingTable::default(),
+            final_newline: FinalNewlinePolicy::Off,
+            agent_name: None,
+            agent_version: None,
+            thinking_triggers: None,
+            custom_modes: std::collections::HashMap::new(),
         }
     }
 
@@ -298,6 +405,30 @@ mod tests {
         assert!(ids.contains(&"session-2".to_string()));
     }
 
+    #[test]
+    fn test_manager_list_sessions() {
+        let manager = SessionManager::new();
+        let config = test_config();
+
+        for id in ["session-2", "session-1"] {
+            manager
+                .create_session(id.to_string(), PathBuf::from("/tmp"), &config, None)
+                .unwrap();
+        }
+        manager
+            .get_session("session-2")
+            .unwrap()
+            .set_title("Release notes");
+
+        let sessions = manager.list_sessions();
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].session_id, "session-1");
+        assert_eq!(sessions[0].title, None);
+        assert_eq!(sessions[1].session_id, "session-2");
+        assert_eq!(sessions[1].title.as_deref(), Some("Release notes"));
+        assert_eq!(sessions[1].cwd, "/tmp");
+    }
+
     #[tokio::test]
     async fn test_manager_clear() {
         let manager = SessionManager::new();
@@ -6,29 +6,53 @@
 //! - Permission handling
 //! - Session state management
 //! - Interactive permission requests
+//! - Client-reviewed diffs for Edit and Write
 //! - Background process management
+//! - One-shot completions on the small/fast model
+//! - Automatic conversation compaction
+//! - The current plan file of Plan mode
+//! - The session index used to resume sessions in their permission mode
+//! - The permission audit log
 
+mod audit_log;
 mod background_processes;
+mod compaction;
+mod diff_suggestion;
+mod fast_model;
 mod manager;
 mod permission;
 mod permission_manager;
 mod permission_request;
+mod plan_file;
 mod prompt_manager;
 #[allow(clippy::module_inception)]
 mod session;
+mod session_index;
 mod usage;
 mod wrapped_child;
 
+pub use audit_log::{AuditEntry, AuditSource, PermissionAuditLog, input_digest};
 pub use background_processes::{
-    BackgroundProcessManager, BackgroundTerminal, ChildHandle, TerminalExitStatus,
+    BackgroundExit, BackgroundProcessManager, BackgroundTerminal, ChildHandle,
+    DEFAULT_BACKGROUND_MAX_LIFETIME, DEFAULT_BACKGROUND_OUTPUT_LIMIT, OutputBuffer, ShellSummary,
+    TerminalExitStatus,
 };
+pub use compaction::{CompactionPolicy, Compactor, DEFAULT_COMPACT_THRESHOLD, compact_if_needed};
+pub use diff_suggestion::{DiffSuggestion, EDITED_TEXT_META_KEY};
+pub use fast_model::{FastModel, SdkFastModel, generate_session_title};
 pub use manager::SessionManager;
-pub use permission::{PermissionHandler, PermissionMode, ToolPermissionResult};
+pub use permission::{CustomMode, PermissionHandler, PermissionMode, ToolPermissionResult};
 pub use permission_manager::{
-    PendingPermissionRequest, PermissionManager, PermissionManagerDecision,
+    BATCH_META_KEY, DEFAULT_BATCH_WINDOW, PendingPermissionRequest, PermissionManager,
+    PermissionManagerDecision,
 };
-pub use permission_request::{PermissionOutcome, PermissionRequestBuilder};
+pub use permission_request::{
+    DEFAULT_PERMISSION_REQUEST_TIMEOUT, PermissionClient, PermissionOutcome,
+    PermissionRequestBuilder,
+};
+pub use plan_file::CurrentPlan;
 pub use prompt_manager::{PromptManager, PromptId, PromptTask};
 pub use session::{Session, stable_cache_key};
+pub use session_index::{SessionIndex, SessionIndexEntry};
 pub use usage::UsageTracker;
 pub use wrapped_child::WrappedChild;
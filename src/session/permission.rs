@@ -4,15 +4,22 @@
 //! where each permission mode has its own strategy implementation.
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
+use crate::permissions::requires_explicit_approval;
 use crate::permissions::strategies::{
     AcceptEditsModeStrategy, BypassPermissionsModeStrategy, DefaultModeStrategy,
     DontAskModeStrategy, PermissionModeStrategy, PlanModeStrategy,
 };
-use crate::settings::{PermissionChecker, PermissionDecision};
+use crate::settings::{
+    CustomModeSettings, PermissionCheckResult, PermissionChecker, PermissionDecision,
+    PermissionSettings, Settings, SettingsManager,
+};
+use crate::utils::is_session_plan_path;
 use claude_code_agent_sdk::PermissionMode as SdkPermissionMode;
 
 /// Permission mode for tool execution
@@ -88,6 +95,110 @@ impl PermissionMode {
     }
 }
 
+/// Custom permission mode defined in settings (`customModes`)
+///
+/// A named set of allow/ask/deny rules on top of a built-in base mode.
+/// Tool calls its rules match are decided by them; the rest are handled by
+/// the base mode.
+#[derive(Debug)]
+pub struct CustomMode {
+    id: String,
+    name: String,
+    description: Option<String>,
+    base: PermissionMode,
+    /// The mode's rules, matched like settings rules
+    rules: PermissionChecker,
+}
+
+impl CustomMode {
+    /// Build a custom mode from its settings
+    ///
+    /// Returns None if `id` is a built-in mode or the base mode is unknown.
+    pub fn from_settings(id: &str, settings: &CustomModeSettings, cwd: &Path) -> Option<Self> {
+        if PermissionMode::parse(id).is_some() {
+            tracing::warn!(mode = %id, "Custom mode can't replace a built-in mode");
+            return None;
+        }
+        let base = match settings.base_mode.as_deref() {
+            None => PermissionMode::Default,
+            Some(base) => {
+                let Some(base) = PermissionMode::parse(base) else {
+                    tracing::warn!(mode = %id, base_mode = %base, "Unknown base mode for custom mode");
+                    return None;
+                };
+                base
+            }
+        };
+        let rules = Settings {
+            permissions: Some(PermissionSettings {
+                allow: settings.allow.clone(),
+                deny: settings.deny.clone(),
+                ask: settings.ask.clone(),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        Some(Self {
+            id: id.to_string(),
+            name: settings.name.clone().unwrap_or_else(|| id.to_string()),
+            description: settings.description.clone(),
+            base,
+            rules: PermissionChecker::new(rules, cwd),
+        })
+    }
+
+    /// All valid custom modes in `settings`, sorted by ID
+    pub fn all_from_settings(settings: &Settings, cwd: &Path) -> Vec<Arc<Self>> {
+        settings
+            .custom_modes
+            .as_ref()
+            .map(|modes| Self::all(modes, cwd))
+            .unwrap_or_default()
+    }
+
+    /// All valid custom modes in a `customModes` setting, sorted by ID
+    pub fn all(modes: &HashMap<String, CustomModeSettings>, cwd: &Path) -> Vec<Arc<Self>> {
+        let mut modes: Vec<_> = modes
+            .iter()
+            .filter_map(|(id, mode)| Self::from_settings(id, mode, cwd))
+            .map(Arc::new)
+            .collect();
+        modes.sort_by(|a, b| a.id.cmp(&b.id));
+        modes
+    }
+
+    /// Mode ID used in ACP setMode requests
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Display name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Description for the mode list
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Built-in mode handling calls no rule matches
+    pub fn base(&self) -> PermissionMode {
+        self.base
+    }
+
+    /// Decision of the mode's rules for a tool call, if one matches
+    pub fn decide(
+        &self,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+    ) -> Option<PermissionCheckResult> {
+        let mut result = self.rules.check_permission(tool_name, tool_input);
+        result.origin = Some(format!("custom mode {}", self.id));
+        result.rule.is_some().then_some(result)
+    }
+}
+
 /// Permission check result from the handler
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ToolPermissionResult {
@@ -108,6 +219,10 @@ pub struct PermissionHandler {
     strategy: Arc<dyn PermissionModeStrategy>,
     /// Shared permission checker from settings (shared with hook)
     checker: Option<Arc<RwLock<PermissionChecker>>>,
+    /// Custom mode in effect, on top of `mode` (its base mode)
+    custom_mode: Option<Arc<CustomMode>>,
+    /// Plans directory from the `plansDir` setting (None = the default one)
+    plans_dir: Option<PathBuf>,
 }
 
 impl fmt::Debug for PermissionHandler {
@@ -116,6 +231,8 @@ impl fmt::Debug for PermissionHandler {
             .field("mode", &self.mode)
             .field("strategy", &"<strategy>")
             .field("checker", &self.checker)
+            .field("custom_mode", &self.custom_mode.as_ref().map(|m| m.id()))
+            .field("plans_dir", &self.plans_dir)
             .finish()
     }
 }
@@ -126,6 +243,8 @@ impl Default for PermissionHandler {
             mode: PermissionMode::Default,
             strategy: Arc::new(DefaultModeStrategy),
             checker: None,
+            custom_mode: None,
+            plans_dir: None,
         }
     }
 }
@@ -142,8 +261,10 @@ impl PermissionHandler {
     pub fn with_mode(mode: PermissionMode) -> Self {
         Self {
             mode,
-            strategy: Self::create_strategy(mode),
+            strategy: Self::create_strategy(mode, None),
             checker: None,
+            custom_mode: None,
+            plans_dir: None,
         }
     }
 
@@ -155,6 +276,8 @@ impl PermissionHandler {
             mode: PermissionMode::Default,
             strategy: Arc::new(DefaultModeStrategy),
             checker: Some(checker),
+            custom_mode: None,
+            plans_dir: None,
         }
     }
 
@@ -166,15 +289,20 @@ impl PermissionHandler {
             mode: PermissionMode::Default,
             strategy: Arc::new(DefaultModeStrategy),
             checker: Some(Arc::new(RwLock::new(checker))),
+            custom_mode: None,
+            plans_dir: None,
         }
     }
 
     /// Create strategy for a given mode
-    fn create_strategy(mode: PermissionMode) -> Arc<dyn PermissionModeStrategy> {
+    fn create_strategy(
+        mode: PermissionMode,
+        plans_dir: Option<&PathBuf>,
+    ) -> Arc<dyn PermissionModeStrategy> {
         match mode {
             PermissionMode::Default => Arc::new(DefaultModeStrategy),
             PermissionMode::AcceptEdits => Arc::new(AcceptEditsModeStrategy),
-            PermissionMode::Plan => Arc::new(PlanModeStrategy),
+            PermissionMode::Plan => Arc::new(PlanModeStrategy::with_plans_dir(plans_dir.cloned())),
             PermissionMode::DontAsk => Arc::new(DontAskModeStrategy),
             PermissionMode::BypassPermissions => Arc::new(BypassPermissionsModeStrategy),
         }
@@ -186,9 +314,51 @@ impl PermissionHandler {
     }
 
     /// Set permission mode
+    ///
+    /// Leaves any custom mode.
     pub fn set_mode(&mut self, mode: PermissionMode) {
         self.mode = mode;
-        self.strategy = Self::create_strategy(mode);
+        self.strategy = Self::create_strategy(mode, self.plans_dir.as_ref());
+        self.custom_mode = None;
+    }
+
+    /// Use `plans_dir` (the `plansDir` setting) as the session's plans
+    /// directory instead of the default one
+    pub fn set_plans_dir(&mut self, plans_dir: Option<PathBuf>) {
+        self.plans_dir = plans_dir;
+        self.strategy = Self::create_strategy(self.mode, self.plans_dir.as_ref());
+    }
+
+    /// Whether `path` is in the session's plans directory
+    pub fn is_plan_file(&self, path: &str) -> bool {
+        is_session_plan_path(path, self.plans_dir.as_deref())
+    }
+
+    /// Switch to a custom mode, on top of its base mode
+    pub fn set_custom_mode(&mut self, custom_mode: Arc<CustomMode>) {
+        self.set_mode(custom_mode.base());
+        self.custom_mode = Some(custom_mode);
+    }
+
+    /// Custom mode in effect, if any
+    pub fn custom_mode(&self) -> Option<&Arc<CustomMode>> {
+        self.custom_mode.as_ref()
+    }
+
+    /// ID of the current mode (the custom mode's, if one is in effect)
+    pub fn mode_id(&self) -> &str {
+        self.custom_mode
+            .as_ref()
+            .map_or(self.mode.as_str(), |custom_mode| custom_mode.id())
+    }
+
+    /// Decision of the custom mode's rules for a tool call, if one matches
+    pub fn custom_decision(
+        &self,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+    ) -> Option<PermissionCheckResult> {
+        self.custom_mode.as_ref()?.decide(tool_name, tool_input)
     }
 
     /// Set the permission checker
@@ -231,13 +401,34 @@ impl PermissionHandler {
 
     /// Check permission for a tool with full context
     ///
-    /// Combines strategy-based checking with settings rules.
+    /// Combines strategy-based checking with settings rules. Calls that
+    /// require explicit approval (e.g. permanent deletes) always ask the
+    /// user unless a deny rule or the mode blocks them. In a custom mode,
+    /// its deny rules come first and its allow and ask rules come before
+    /// the base mode.
     /// Returns the permission result.
     pub async fn check_permission(
         &self,
         tool_name: &str,
         tool_input: &serde_json::Value,
     ) -> ToolPermissionResult {
+        let explicit_approval = requires_explicit_approval(tool_name, tool_input);
+
+        // Custom mode deny rules come first; its allow and ask rules come
+        // after settings deny rules but before settings allow rules
+        let custom_decision = self.custom_decision(tool_name, tool_input);
+        if let Some(result) = &custom_decision
+            && result.decision == PermissionDecision::Deny
+        {
+            return ToolPermissionResult::Blocked {
+                reason: format!(
+                    "Denied by {} mode rule: {}",
+                    self.mode_id(),
+                    result.rule.as_deref().unwrap_or_default()
+                ),
+            };
+        }
+
         // Check settings rules first (if available)
         if let Some(ref checker) = self.checker {
             let checker_read = checker.read().await;
@@ -251,15 +442,45 @@ impl PermissionHandler {
                             .unwrap_or_else(|| "Denied by settings".to_string()),
                     };
                 }
-                PermissionDecision::Allow => {
+                PermissionDecision::Allow if !explicit_approval && custom_decision.is_none() => {
                     return ToolPermissionResult::Allowed;
                 }
-                PermissionDecision::Ask => {
+                PermissionDecision::Allow | PermissionDecision::Ask => {
                     // Fall through to strategy-based check
                 }
             }
         }
 
+        match custom_decision.map(|result| result.decision) {
+            Some(PermissionDecision::Allow) if !explicit_approval => {
+                return ToolPermissionResult::Allowed;
+            }
+            Some(PermissionDecision::Ask) if self.mode == PermissionMode::DontAsk => {
+                return ToolPermissionResult::Blocked {
+                    reason: format!(
+                        "{tool_name} needs the user's approval in {} mode, which never asks",
+                        self.mode_id()
+                    ),
+                };
+            }
+            Some(PermissionDecision::Ask) => return ToolPermissionResult::NeedsPermission,
+            _ => {}
+        }
+
+        if explicit_approval {
+            if let Some(reason) = self.strategy.is_tool_blocked(tool_name, tool_input) {
+                return ToolPermissionResult::Blocked { reason };
+            }
+            if self.mode == PermissionMode::DontAsk {
+                return ToolPermissionResult::Blocked {
+                    reason: format!(
+                        "{tool_name} needs the user's approval, which DontAsk mode never asks for"
+                    ),
+                };
+            }
+            return ToolPermissionResult::NeedsPermission;
+        }
+
         // Use strategy for mode-specific logic
         let strategy_result = self.strategy.check_permission(tool_name, tool_input);
 
@@ -301,6 +522,37 @@ impl PermissionHandler {
             }
         }
     }
+
+    /// Add a deny rule based on tool call details and save it to the local settings
+    ///
+    /// This is used for "Always Reject": the rule applies for the rest of the
+    /// session, and future sessions load it from `.claude/settings.local.json`.
+    pub async fn add_deny_rule(&self, tool_name: &str, tool_input: &serde_json::Value) {
+        let Some(ref checker) = self.checker else {
+            return;
+        };
+        let (rule, project_dir) = {
+            let mut checker_write = checker.write().await;
+            let rule = checker_write.add_deny_rule_for_tool_call(tool_name, tool_input);
+            (rule, checker_write.cwd().to_path_buf())
+        };
+        match SettingsManager::add_local_permission_rule(
+            &project_dir,
+            PermissionDecision::Deny,
+            &rule,
+        ) {
+            Ok(path) => tracing::info!(
+                rule = %rule,
+                path = %path.display(),
+                "Saved deny rule to local settings"
+            ),
+            Err(e) => tracing::warn!(
+                rule = %rule,
+                error = %e,
+                "Failed to save deny rule to local settings"
+            ),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -459,6 +711,159 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_permanent_delete_needs_permission_in_every_mode() {
+        let permanent = json!({"path": "/tmp/test.txt", "permanent": true});
+        for mode in [
+            PermissionMode::Default,
+            PermissionMode::AcceptEdits,
+            PermissionMode::BypassPermissions,
+        ] {
+            let handler = PermissionHandler::with_mode(mode);
+            assert_eq!(
+                handler.check_permission("Delete", &permanent).await,
+                ToolPermissionResult::NeedsPermission,
+                "{mode:?}"
+            );
+        }
+
+        // Moving to the trash follows the mode
+        let handler = PermissionHandler::with_mode(PermissionMode::BypassPermissions);
+        assert_eq!(
+            handler
+                .check_permission("Delete", &json!({"path": "/tmp/test.txt"}))
+                .await,
+            ToolPermissionResult::Allowed
+        );
+
+        // Allow rules don't cover permanent deletes
+        let settings = crate::settings::Settings {
+            permissions: Some(crate::settings::PermissionSettings {
+                allow: Some(vec!["Delete".into()]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let handler = PermissionHandler::with_checker_owned(PermissionChecker::new(
+            settings,
+            std::path::PathBuf::from("/tmp"),
+        ));
+        assert_eq!(
+            handler.check_permission("Delete", &permanent).await,
+            ToolPermissionResult::NeedsPermission
+        );
+
+        let handler = PermissionHandler::with_mode(PermissionMode::Plan);
+        assert!(matches!(
+            handler.check_permission("Delete", &permanent).await,
+            ToolPermissionResult::Blocked { .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_custom_mode_rules_are_applied() {
+        let settings: Settings = serde_json::from_value(json!({
+            "customModes": {
+                "review": {
+                    "name": "Review",
+                    "allow": ["Read", "Grep"],
+                    "ask": ["Edit", "Write"],
+                    "deny": ["Bash"]
+                },
+                "plan": {"deny": ["Read"]},
+                "broken": {"baseMode": "yolo"}
+            }
+        }))
+        .unwrap();
+        let cwd = std::path::PathBuf::from("/tmp");
+        let modes = CustomMode::all_from_settings(&settings, &cwd);
+        // Built-in mode IDs and unknown base modes are rejected
+        assert_eq!(modes.len(), 1);
+        let review = modes[0].clone();
+        assert_eq!(review.name(), "Review");
+        assert_eq!(review.base(), PermissionMode::Default);
+
+        // Settings allow everything, but the mode's rules come first
+        let allow_all = Settings {
+            permissions: Some(PermissionSettings {
+                allow: Some(vec!["Edit".into(), "Bash".into()]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut handler =
+            PermissionHandler::with_checker_owned(PermissionChecker::new(allow_all, &cwd));
+        handler.set_mode(PermissionMode::BypassPermissions);
+        handler.set_custom_mode(review);
+        assert_eq!(handler.mode_id(), "review");
+        assert_eq!(handler.mode(), PermissionMode::Default);
+
+        assert!(matches!(
+            handler.check_permission("Bash", &json!({"command": "ls"})).await,
+            ToolPermissionResult::Blocked { reason } if reason.contains("review")
+        ));
+        assert_eq!(
+            handler
+                .check_permission("Read", &json!({"file_path": "/tmp/a.txt"}))
+                .await,
+            ToolPermissionResult::Allowed
+        );
+        assert_eq!(
+            handler
+                .check_permission("Edit", &json!({"file_path": "/tmp/a.txt"}))
+                .await,
+            ToolPermissionResult::NeedsPermission
+        );
+        // Calls no rule matches follow the base mode
+        assert_eq!(
+            handler.check_permission("Glob", &json!({})).await,
+            ToolPermissionResult::Allowed
+        );
+
+        // Switching to a built-in mode leaves the custom mode
+        handler.set_mode(PermissionMode::AcceptEdits);
+        assert_eq!(handler.mode_id(), "acceptEdits");
+        assert!(handler.custom_decision("Bash", &json!({})).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_add_deny_rule_is_saved() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let checker = Arc::new(RwLock::new(PermissionChecker::new(
+            Settings::default(),
+            dir.path(),
+        )));
+        let handler = PermissionHandler::with_checker(checker.clone());
+
+        handler
+            .add_deny_rule("mcp__acp__Bash", &json!({"command": "rm -rf build"}))
+            .await;
+
+        // Denied for the rest of the session, only this exact command
+        let result = checker
+            .read()
+            .await
+            .check_permission("Bash", &json!({"command": "rm -rf build"}));
+        assert_eq!(result.decision, PermissionDecision::Deny);
+        assert_eq!(result.rule.as_deref(), Some("Bash(rm -rf build)"));
+        let result = checker
+            .read()
+            .await
+            .check_permission("Bash", &json!({"command": "rm -r target"}));
+        assert_eq!(result.decision, PermissionDecision::Ask);
+
+        // And in future sessions
+        let manager = SettingsManager::new(dir.path()).unwrap();
+        let result = PermissionChecker::new(manager.settings().clone(), dir.path())
+            .check_permission("Bash", &json!({"command": "rm -rf build"}));
+        assert_eq!(result.decision, PermissionDecision::Deny);
+        assert!(
+            result
+                .origin
+                .is_some_and(|origin| origin.ends_with("settings.local.json"))
+        );
+    }
+
     #[tokio::test]
     async fn test_accept_edits_strategy() {
         let handler = PermissionHandler::with_mode(PermissionMode::AcceptEdits);
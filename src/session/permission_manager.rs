@@ -5,8 +5,15 @@
 //! - Background task handles the request
 //! - Uses unbounded channels (never block)
 //! - Uses one-shot channels for request/response
+//!
+//! Optionally, requests arriving close together for the same session are
+//! batched into one grouped request (allow all / reject all / decide
+//! individually), so a burst of related edits doesn't become a burst of
+//! prompts. Sessions use it for clients that opt in with
+//! `_meta.groupedPermissions` on `session/new`.
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use sacp::JrConnectionCx;
 use sacp::link::AgentToClient;
@@ -15,8 +22,17 @@ use sacp::schema::{
     RequestPermissionRequest, SessionId, ToolCallUpdate, ToolCallUpdateFields,
 };
 
+use super::permission_request::{PermissionClient, PermissionOutcome};
 use crate::types::AgentError;
 
+/// Key in the `_meta` of a grouped permission request listing the IDs of
+/// the tool calls it covers
+pub const BATCH_META_KEY: &str = "batchedToolCalls";
+
+/// Time after a permission request during which further requests of the
+/// same session join its grouped prompt
+pub const DEFAULT_BATCH_WINDOW: Duration = Duration::from_millis(100);
+
 /// Permission decision result
 #[derive(Debug, Clone, PartialEq)]
 pub enum PermissionManagerDecision {
@@ -26,10 +42,24 @@ pub enum PermissionManagerDecision {
     AllowAlways,
     /// User rejected this tool call
     Rejected,
+    /// User rejected this tool call and wants to always reject this pattern
+    RejectAlways,
     /// Permission request was cancelled
     Cancelled,
 }
 
+impl From<PermissionManagerDecision> for PermissionOutcome {
+    fn from(decision: PermissionManagerDecision) -> Self {
+        match decision {
+            PermissionManagerDecision::AllowOnce => Self::AllowOnce,
+            PermissionManagerDecision::AllowAlways => Self::AllowAlways,
+            PermissionManagerDecision::Rejected => Self::Rejected,
+            PermissionManagerDecision::RejectAlways => Self::RejectAlways,
+            PermissionManagerDecision::Cancelled => Self::Cancelled,
+        }
+    }
+}
+
 /// Pending permission request from hook
 pub struct PendingPermissionRequest {
     pub tool_name: String,
@@ -60,6 +90,9 @@ impl std::fmt::Debug for PendingPermissionRequest {
 /// 2. Background task processes request
 /// 3. One-shot channel returns result to caller
 ///
+/// With a batch window, requests of a session that arrive within the
+/// window after the first one are asked about together.
+///
 /// # Example
 ///
 /// ```rust,ignore
@@ -70,36 +103,38 @@ impl std::fmt::Debug for PendingPermissionRequest {
 pub struct PermissionManager {
     /// Pending permission requests (unbounded, never blocks on send)
     pending_requests: tokio::sync::mpsc::UnboundedSender<PendingPermissionRequest>,
-
-    /// Connection to client for sending permission requests
-    /// Note: Currently used by `send_permission_request_to_client` which is prepared
-    /// for future interactive permission dialog implementation.
-    #[allow(dead_code)]
-    connection_cx: Arc<JrConnectionCx<AgentToClient>>,
 }
 
 impl std::fmt::Debug for PermissionManager {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("PermissionManager")
             .field("pending_requests", &"<mpsc::UnboundedSender>")
-            .field("connection_cx", &"<JrConnectionCx>")
             .finish()
     }
 }
 
 impl PermissionManager {
-    /// Create a new PermissionManager
+    /// Create a new PermissionManager asking the client one call at a time
     pub fn new(connection_cx: Arc<JrConnectionCx<AgentToClient>>) -> Self {
+        Self::with_client(connection_cx, None)
+    }
+
+    /// Create a PermissionManager sending its requests to `client`
+    ///
+    /// With a `batch_window`, requests of a session arriving within it after
+    /// the first one are presented as a single grouped request. Only use it
+    /// with clients that handle grouped requests; without one every call
+    /// gets its own prompt.
+    pub fn with_client(client: Arc<dyn PermissionClient>, batch_window: Option<Duration>) -> Self {
         let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
 
         // Spawn background task to handle permission requests
         tokio::spawn(async move {
-            Self::handle_permission_requests(rx).await;
+            Self::handle_permission_requests(client, rx, batch_window).await;
         });
 
         Self {
             pending_requests: tx,
-            connection_cx,
         }
     }
 
@@ -132,9 +167,36 @@ impl PermissionManager {
         rx
     }
 
+    /// Request permission and wait for the decision
+    ///
+    /// Resolves as [`PermissionOutcome::TimedOut`] when no decision arrives
+    /// within `timeout` (None waits indefinitely); the request is then
+    /// cancelled.
+    pub async fn decide(
+        &self,
+        tool_name: String,
+        tool_input: serde_json::Value,
+        tool_call_id: String,
+        session_id: String,
+        timeout: Option<Duration>,
+    ) -> PermissionOutcome {
+        let rx = self.request_permission(tool_name, tool_input, tool_call_id, session_id);
+        let decision = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, rx).await {
+                Ok(decision) => decision,
+                Err(_) => return PermissionOutcome::TimedOut,
+            },
+            None => rx.await,
+        };
+        // A dropped sender means the background task went away
+        decision.map_or(PermissionOutcome::Cancelled, PermissionOutcome::from)
+    }
+
     /// Background task: handle permission requests
     async fn handle_permission_requests(
+        client: Arc<dyn PermissionClient>,
         mut receiver: tokio::sync::mpsc::UnboundedReceiver<PendingPermissionRequest>,
+        batch_window: Option<Duration>,
     ) {
         while let Some(request) = receiver.recv().await {
             tracing::info!(
@@ -143,34 +205,170 @@ impl PermissionManager {
                 "Processing permission request in background task"
             );
 
-            // TODO: Send permission request to client via SACP
-            // For now, we'll simulate the request and response
+            // Collect the requests arriving within the window
+            let mut batch = vec![request];
+            if let Some(window) = batch_window {
+                let deadline = tokio::time::Instant::now() + window;
+                while let Ok(Some(request)) =
+                    tokio::time::timeout_at(deadline, receiver.recv()).await
+                {
+                    batch.push(request);
+                }
+            }
 
-            // This is where we would:
-            // 1. Build the RequestPermissionRequest
-            // 2. Send it via SACP to the client
-            // 3. Wait for the client's response
-            // 4. Send the result to response_tx
+            for group in group_by_session(batch) {
+                if group.len() > 1 {
+                    Self::ask_batch(client.as_ref(), group).await;
+                } else {
+                    Self::ask_individually(client.as_ref(), group).await;
+                }
+            }
+        }
+    }
 
-            // For now, deny with a message explaining the limitation
-            let _ = request
-                .response_tx
-                .send(PermissionManagerDecision::Cancelled);
+    /// Ask about each request with its own prompt
+    ///
+    /// A request whose caller stopped waiting (its timeout passed) is
+    /// dropped, so an unanswered prompt doesn't hold up the ones queued
+    /// behind it.
+    async fn ask_individually(
+        client: &dyn PermissionClient,
+        requests: Vec<PendingPermissionRequest>,
+    ) {
+        for mut request in requests {
+            let decision = tokio::select! {
+                decision = Self::send_permission_request_to_client(
+                    client,
+                    &request.tool_name,
+                    &request.tool_input,
+                    &request.tool_call_id,
+                    &request.session_id,
+                ) => decision,
+                () = request.response_tx.closed() => {
+                    tracing::info!(
+                        tool_call_id = %request.tool_call_id,
+                        "Permission request abandoned by its caller - cancelling it"
+                    );
+                    continue;
+                }
+            };
+            let decision = decision.unwrap_or_else(|e| {
+                tracing::warn!(
+                    tool_call_id = %request.tool_call_id,
+                    error = %e,
+                    "Permission request failed"
+                );
+                PermissionManagerDecision::Cancelled
+            });
+            let _ = request.response_tx.send(decision);
+        }
+    }
 
-            tracing::warn!(
-                tool_name = %request.tool_name,
-                "Permission request sent but interactive dialog not yet implemented"
-            );
+    /// Ask about several requests of one session with a single grouped prompt
+    ///
+    /// The prompt is attached to the first tool call and lists all of them
+    /// in its title and [`BATCH_META_KEY`]; each call's input stays on its
+    /// own tool call. "Decide individually" (or an unknown option) falls
+    /// back to one prompt per call.
+    ///
+    /// ACP has no neutral option kind, so "Decide Individually" uses
+    /// `RejectOnce`: a client going by the kind alone never reads it as
+    /// approval. The prompt is dropped once none of its callers is waiting.
+    async fn ask_batch(client: &dyn PermissionClient, mut requests: Vec<PendingPermissionRequest>) {
+        let titles: Vec<String> = requests
+            .iter()
+            .map(|request| format_tool_title(&request.tool_name, &request.tool_input))
+            .collect();
+        let tool_call_ids: Vec<String> = requests
+            .iter()
+            .map(|request| request.tool_call_id.clone())
+            .collect();
+        tracing::info!(
+            count = requests.len(),
+            tool_call_ids = ?tool_call_ids,
+            "Sending grouped permission request"
+        );
+
+        let options = vec![
+            PermissionOption::new(
+                PermissionOptionId::new("allow_all"),
+                "Allow All",
+                PermissionOptionKind::AllowOnce,
+            ),
+            PermissionOption::new(
+                PermissionOptionId::new("reject_all"),
+                "Reject All",
+                PermissionOptionKind::RejectOnce,
+            ),
+            PermissionOption::new(
+                PermissionOptionId::new("decide_individually"),
+                "Decide Individually",
+                PermissionOptionKind::RejectOnce,
+            ),
+        ];
+        let tool_call_update = ToolCallUpdate::new(
+            requests[0].tool_call_id.clone(),
+            ToolCallUpdateFields::new().title(format!(
+                "{} tool calls: {}",
+                requests.len(),
+                titles.join(", ")
+            )),
+        );
+        let mut meta = serde_json::Map::new();
+        meta.insert(BATCH_META_KEY.to_string(), serde_json::json!(tool_call_ids));
+        let request = RequestPermissionRequest::new(
+            SessionId::new(requests[0].session_id.clone()),
+            tool_call_update,
+            options,
+        )
+        .meta(meta);
+
+        let all_abandoned = futures::future::join_all(
+            requests
+                .iter_mut()
+                .map(|request| request.response_tx.closed()),
+        );
+        let response = tokio::select! {
+            response = client.request_permission(request) => response,
+            _ = all_abandoned => {
+                tracing::info!(
+                    tool_call_ids = ?tool_call_ids,
+                    "Grouped permission request abandoned by its callers - cancelling it"
+                );
+                return;
+            }
+        };
+        let decision = match response {
+            Ok(response) => match response.outcome {
+                RequestPermissionOutcome::Selected(selected) => {
+                    match selected.option_id.0.as_ref() {
+                        "allow_all" => Some(PermissionManagerDecision::AllowOnce),
+                        "reject_all" => Some(PermissionManagerDecision::Rejected),
+                        _ => None,
+                    }
+                }
+                // Cancelled, or any future variant (non_exhaustive enum)
+                _ => Some(PermissionManagerDecision::Cancelled),
+            },
+            Err(e) => {
+                tracing::warn!(error = %e, "Grouped permission request failed");
+                Some(PermissionManagerDecision::Cancelled)
+            }
+        };
+
+        match decision {
+            Some(decision) => {
+                for request in requests {
+                    let _ = request.response_tx.send(decision.clone());
+                }
+            }
+            None => Self::ask_individually(client, requests).await,
         }
     }
 
     /// Send permission request to client via SACP
-    ///
-    /// Note: This method is prepared for future interactive permission dialog implementation.
-    /// Currently, permission requests are handled by the SDK's `can_use_tool` callback.
-    #[allow(dead_code)]
     async fn send_permission_request_to_client(
-        &self,
+        client: &dyn PermissionClient,
         tool_name: &str,
         tool_input: &serde_json::Value,
         tool_call_id: &str,
@@ -208,22 +406,29 @@ impl PermissionManager {
             RequestPermissionRequest::new(SessionId::new(session_id), tool_call_update, options);
 
         // Send request and wait for response
-        let response = self
-            .connection_cx
-            .send_request(request)
-            .block_task()
-            .await
-            .map_err(|e| AgentError::Internal(format!("Permission request failed: {}", e)))?;
+        let response = client.request_permission(request).await?;
 
         // Parse the response
         Ok(parse_permission_response(response.outcome))
     }
 }
 
+/// Split a batch into the requests of each session, in order of arrival
+fn group_by_session(batch: Vec<PendingPermissionRequest>) -> Vec<Vec<PendingPermissionRequest>> {
+    let mut groups: Vec<Vec<PendingPermissionRequest>> = Vec::new();
+    for request in batch {
+        match groups
+            .iter_mut()
+            .find(|group| group[0].session_id == request.session_id)
+        {
+            Some(group) => group.push(request),
+            None => groups.push(vec![request]),
+        }
+    }
+    groups
+}
+
 /// Parse a permission response outcome into our decision type
-///
-/// Note: Prepared for future interactive permission dialog implementation.
-#[allow(dead_code)]
 fn parse_permission_response(outcome: RequestPermissionOutcome) -> PermissionManagerDecision {
     match outcome {
         RequestPermissionOutcome::Selected(selected) => {
@@ -231,6 +436,7 @@ fn parse_permission_response(outcome: RequestPermissionOutcome) -> PermissionMan
                 "allow_always" => PermissionManagerDecision::AllowAlways,
                 "allow_once" => PermissionManagerDecision::AllowOnce,
                 "reject_once" => PermissionManagerDecision::Rejected,
+                "reject_always" => PermissionManagerDecision::RejectAlways,
                 _ => PermissionManagerDecision::Rejected, // Unknown option, treat as reject
             }
         }
@@ -241,9 +447,6 @@ fn parse_permission_response(outcome: RequestPermissionOutcome) -> PermissionMan
 }
 
 /// Format a title for the permission dialog based on tool name and input
-///
-/// Note: Prepared for future interactive permission dialog implementation.
-#[allow(dead_code)]
 fn format_tool_title(tool_name: &str, input: &serde_json::Value) -> String {
     // Strip mcp__acp__ prefix for display
     let display_name = tool_name.strip_prefix("mcp__acp__").unwrap_or(tool_name);
@@ -263,7 +466,7 @@ fn format_tool_title(tool_name: &str, input: &serde_json::Value) -> String {
                 .unwrap_or("file");
             format!("Write to {}", path)
         }
-        "Edit" => {
+        "Edit" | "MultiEdit" => {
             let path = input
                 .get("file_path")
                 .and_then(|v| v.as_str())
@@ -281,9 +484,6 @@ fn format_tool_title(tool_name: &str, input: &serde_json::Value) -> String {
 }
 
 /// Truncate a string to max length, adding "..." if truncated
-///
-/// Note: Prepared for future interactive permission dialog implementation.
-#[allow(dead_code)]
 fn truncate_string(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
         s.to_string()
@@ -295,6 +495,10 @@ fn truncate_string(s: &str, max_len: usize) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use async_trait::async_trait;
+    use sacp::schema::{RequestPermissionResponse, SelectedPermissionOutcome};
+    use serde_json::json;
+    use std::sync::Mutex;
 
     #[test]
     fn test_format_tool_title_read() {
@@ -326,8 +530,172 @@ mod tests {
 
     #[test]
     fn test_parse_permission_response_selected() {
-        // This would require constructing RequestPermissionOutcome
-        // For now, just verify the function compiles
-        let _ = parse_permission_response;
+        let selected = |option: &'static str| {
+            parse_permission_response(RequestPermissionOutcome::Selected(
+                SelectedPermissionOutcome::new(PermissionOptionId::new(option)),
+            ))
+        };
+        assert_eq!(
+            selected("allow_always"),
+            PermissionManagerDecision::AllowAlways
+        );
+        assert_eq!(selected("reject_once"), PermissionManagerDecision::Rejected);
+        assert_eq!(
+            selected("reject_always"),
+            PermissionManagerDecision::RejectAlways
+        );
+        assert_eq!(selected("unknown"), PermissionManagerDecision::Rejected);
+    }
+
+    /// Client that records the requests it gets and always picks `answer`
+    struct RecordingClient {
+        requests: Arc<Mutex<Vec<RequestPermissionRequest>>>,
+        answer: &'static str,
+    }
+
+    #[async_trait]
+    impl PermissionClient for RecordingClient {
+        async fn request_permission(
+            &self,
+            request: RequestPermissionRequest,
+        ) -> Result<RequestPermissionResponse, AgentError> {
+            self.requests.lock().unwrap().push(request);
+            Ok(RequestPermissionResponse::new(
+                RequestPermissionOutcome::Selected(SelectedPermissionOutcome::new(
+                    PermissionOptionId::new(self.answer),
+                )),
+            ))
+        }
+    }
+
+    /// Send two edits at once and wait for both decisions
+    async fn request_two_edits(
+        batch_window: Option<Duration>,
+        answer: &'static str,
+    ) -> (
+        Vec<PermissionManagerDecision>,
+        Vec<RequestPermissionRequest>,
+    ) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let client = RecordingClient {
+            requests: requests.clone(),
+            answer,
+        };
+        let manager = PermissionManager::with_client(Arc::new(client), batch_window);
+
+        let receivers: Vec<_> = ["a", "b"]
+            .into_iter()
+            .map(|name| {
+                manager.request_permission(
+                    "Edit".to_string(),
+                    json!({"file_path": format!("/tmp/{name}.rs")}),
+                    format!("call-{name}"),
+                    "session-1".to_string(),
+                )
+            })
+            .collect();
+        let mut decisions = Vec::new();
+        for receiver in receivers {
+            decisions.push(receiver.await.unwrap());
+        }
+        let requests = std::mem::take(&mut *requests.lock().unwrap());
+        (decisions, requests)
+    }
+
+    #[tokio::test]
+    async fn test_near_simultaneous_requests_are_grouped() {
+        let (decisions, requests) =
+            request_two_edits(Some(Duration::from_millis(50)), "allow_all").await;
+        assert_eq!(
+            decisions,
+            [
+                PermissionManagerDecision::AllowOnce,
+                PermissionManagerDecision::AllowOnce
+            ]
+        );
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].tool_call.fields.title.as_deref(),
+            Some("2 tool calls: Edit /tmp/a.rs, Edit /tmp/b.rs")
+        );
+        assert_eq!(
+            requests[0].meta.as_ref().unwrap()[BATCH_META_KEY],
+            json!(["call-a", "call-b"])
+        );
+        // The first call keeps its own input, and only "Allow All" reads as approval
+        assert!(requests[0].tool_call.fields.raw_input.is_none());
+        let allowing: Vec<&str> = requests[0]
+            .options
+            .iter()
+            .filter(|option| {
+                matches!(
+                    option.kind,
+                    PermissionOptionKind::AllowOnce | PermissionOptionKind::AllowAlways
+                )
+            })
+            .map(|option| option.option_id.0.as_ref())
+            .collect();
+        assert_eq!(allowing, ["allow_all"]);
+
+        // Deciding individually asks about each call again
+        let (decisions, requests) =
+            request_two_edits(Some(Duration::from_millis(50)), "decide_individually").await;
+        assert_eq!(requests.len(), 3);
+        assert_eq!(decisions.len(), 2);
+    }
+
+    /// Client that never answers about `call-stuck` and allows everything else
+    struct StuckClient;
+
+    #[async_trait]
+    impl PermissionClient for StuckClient {
+        async fn request_permission(
+            &self,
+            request: RequestPermissionRequest,
+        ) -> Result<RequestPermissionResponse, AgentError> {
+            if request.tool_call.tool_call_id.0.as_ref() == "call-stuck" {
+                std::future::pending::<()>().await;
+            }
+            Ok(RequestPermissionResponse::new(
+                RequestPermissionOutcome::Selected(SelectedPermissionOutcome::new(
+                    PermissionOptionId::new("allow_once"),
+                )),
+            ))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_timed_out_request_is_cancelled() {
+        let manager = PermissionManager::with_client(Arc::new(StuckClient), None);
+        let decide = |tool_call_id: &str, timeout: Duration| {
+            manager.decide(
+                "Edit".to_string(),
+                json!({"file_path": "/tmp/a.rs"}),
+                tool_call_id.to_string(),
+                "session-1".to_string(),
+                Some(timeout),
+            )
+        };
+
+        let outcome = decide("call-stuck", Duration::from_millis(20)).await;
+        assert_eq!(outcome, PermissionOutcome::TimedOut);
+
+        // The unanswered request no longer holds up the next one
+        let outcome = decide("call-next", Duration::from_secs(5)).await;
+        assert_eq!(outcome, PermissionOutcome::AllowOnce);
+    }
+
+    #[tokio::test]
+    async fn test_requests_are_asked_individually_without_batching() {
+        let (decisions, requests) = request_two_edits(None, "allow_once").await;
+        assert_eq!(
+            decisions,
+            [
+                PermissionManagerDecision::AllowOnce,
+                PermissionManagerDecision::AllowOnce
+            ]
+        );
+        assert_eq!(requests.len(), 2);
+        assert!(requests.iter().all(|request| request.meta.is_none()));
     }
 }
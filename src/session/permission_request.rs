@@ -3,15 +3,46 @@
 //! Implements the ACP permission request/response protocol for asking users
 //! whether to allow tool execution.
 
+use std::time::Duration;
+
+use async_trait::async_trait;
 use sacp::JrConnectionCx;
 use sacp::link::AgentToClient;
 use sacp::schema::{
     PermissionOption, PermissionOptionId, PermissionOptionKind, RequestPermissionOutcome,
-    RequestPermissionRequest, SessionId, ToolCallUpdate, ToolCallUpdateFields,
+    RequestPermissionRequest, RequestPermissionResponse, SessionId, ToolCallContent,
+    ToolCallUpdate, ToolCallUpdateFields,
 };
 
+use super::diff_suggestion::{DiffSuggestion, edited_text};
 use crate::types::AgentError;
 
+/// Time a client has to answer a permission request by default
+pub const DEFAULT_PERMISSION_REQUEST_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// A client that answers `session/request_permission` requests
+#[async_trait]
+pub trait PermissionClient: Send + Sync {
+    /// Send the request and wait for the client's response
+    async fn request_permission(
+        &self,
+        request: RequestPermissionRequest,
+    ) -> Result<RequestPermissionResponse, AgentError>;
+}
+
+#[async_trait]
+impl PermissionClient for JrConnectionCx<AgentToClient> {
+    async fn request_permission(
+        &self,
+        request: RequestPermissionRequest,
+    ) -> Result<RequestPermissionResponse, AgentError> {
+        self.send_request(request)
+            .block_task()
+            .await
+            .map_err(|e| AgentError::Internal(format!("Permission request failed: {}", e)))
+    }
+}
+
 /// Permission request outcome after user interaction
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PermissionOutcome {
@@ -21,8 +52,12 @@ pub enum PermissionOutcome {
     AllowAlways,
     /// User rejected this tool call
     Rejected,
+    /// User rejected this tool call and wants to always reject this pattern
+    RejectAlways,
     /// Permission request was cancelled
     Cancelled,
+    /// The client didn't answer before the request timed out
+    TimedOut,
 }
 
 /// Builder for creating permission requests
@@ -33,6 +68,9 @@ pub struct PermissionRequestBuilder {
     title: String,
     tool_name: String,
     tool_input: serde_json::Value,
+    suggestion: Option<DiffSuggestion>,
+    reject_always_rule: Option<String>,
+    timeout: Option<Duration>,
 }
 
 impl PermissionRequestBuilder {
@@ -51,6 +89,9 @@ impl PermissionRequestBuilder {
             title,
             tool_name: tool_name_str,
             tool_input,
+            suggestion: None,
+            reject_always_rule: None,
+            timeout: Some(DEFAULT_PERMISSION_REQUEST_TIMEOUT),
         }
     }
 
@@ -60,13 +101,48 @@ impl PermissionRequestBuilder {
         self
     }
 
+    /// Attach the proposed diff so the client can review and edit it
+    pub fn suggestion(mut self, suggestion: DiffSuggestion) -> Self {
+        self.suggestion = Some(suggestion);
+        self
+    }
+
+    /// Name the rule "Always Reject" saves in its option
+    pub fn reject_always_rule(mut self, rule: impl Into<String>) -> Self {
+        self.reject_always_rule = Some(rule.into());
+        self
+    }
+
+    /// Time the client has to answer before the request resolves as
+    /// [`PermissionOutcome::TimedOut`] (None waits indefinitely)
+    ///
+    /// Defaults to [`DEFAULT_PERMISSION_REQUEST_TIMEOUT`], so a client that
+    /// went away doesn't stall the prompt forever.
+    pub fn timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
     /// Build the request and send it to the client
     ///
     /// Returns the user's decision as a `PermissionOutcome`.
     pub async fn request(
         self,
-        connection_cx: &JrConnectionCx<AgentToClient>,
+        client: &impl PermissionClient,
     ) -> Result<PermissionOutcome, AgentError> {
+        self.request_with_edits(client)
+            .await
+            .map(|(outcome, _)| outcome)
+    }
+
+    /// Build the request and send it to the client, accepting edits to the suggestion
+    ///
+    /// Returns the user's decision together with the new text the client
+    /// returned for the attached [`DiffSuggestion`], if any.
+    pub async fn request_with_edits(
+        self,
+        client: &impl PermissionClient,
+    ) -> Result<(PermissionOutcome, Option<String>), AgentError> {
         // Build the options
         let options = vec![
             PermissionOption::new(
@@ -84,15 +160,24 @@ impl PermissionRequestBuilder {
                 "Reject",
                 PermissionOptionKind::RejectOnce,
             ),
+            PermissionOption::new(
+                PermissionOptionId::new("reject_always"),
+                self.reject_always_rule.as_ref().map_or_else(
+                    || "Always Reject".to_string(),
+                    |rule| format!("Always Reject {}", rule),
+                ),
+                PermissionOptionKind::RejectAlways,
+            ),
         ];
 
-        // Build the tool call update with title
-        let tool_call_update = ToolCallUpdate::new(
-            self.tool_call_id.clone(),
-            ToolCallUpdateFields::new()
-                .title(&self.title)
-                .raw_input(self.tool_input.clone()),
-        );
+        // Build the tool call update with title (and the proposed diff, if any)
+        let mut fields = ToolCallUpdateFields::new()
+            .title(&self.title)
+            .raw_input(self.tool_input.clone());
+        if let Some(suggestion) = &self.suggestion {
+            fields = fields.content(vec![ToolCallContent::Diff(suggestion.to_diff())]);
+        }
+        let tool_call_update = ToolCallUpdate::new(self.tool_call_id.clone(), fields);
 
         // Debug: Log the tool call update being sent
         tracing::debug!(
@@ -125,26 +210,50 @@ impl PermissionRequestBuilder {
             "Sending permission request, waiting for user response..."
         );
 
-        let response = connection_cx
-            .send_request(request)
-            .block_task()
-            .await
-            .map_err(|e| {
-                tracing::error!(
-                    tool_call_id = %self.tool_call_id,
-                    error = %e,
-                    "Permission request failed"
-                );
-                AgentError::Internal(format!("Permission request failed: {}", e))
-            })?;
+        let response = match self.timeout {
+            Some(timeout) => {
+                let Ok(response) =
+                    tokio::time::timeout(timeout, client.request_permission(request)).await
+                else {
+                    tracing::warn!(
+                        tool_call_id = %self.tool_call_id,
+                        timeout_secs = timeout.as_secs(),
+                        "Permission request timed out"
+                    );
+                    return Ok((PermissionOutcome::TimedOut, None));
+                };
+                response
+            }
+            None => client.request_permission(request).await,
+        };
+        let response = response.map_err(|e| {
+            tracing::error!(
+                tool_call_id = %self.tool_call_id,
+                error = %e,
+                "Permission request failed"
+            );
+            e
+        })?;
 
         tracing::info!(
             tool_call_id = %self.tool_call_id,
             "Received permission response"
         );
 
+        // Edited text only applies to a suggestion the client was shown
+        let edited = self
+            .suggestion
+            .as_ref()
+            .and_then(|_| edited_text(response.meta.as_ref()));
+        if edited.is_some() {
+            tracing::info!(
+                tool_call_id = %self.tool_call_id,
+                "Client edited the suggested diff"
+            );
+        }
+
         // Parse the response
-        Ok(parse_permission_response(response.outcome))
+        Ok((parse_permission_response(response.outcome), edited))
     }
 
     /// Get the tool name
@@ -161,6 +270,7 @@ fn parse_permission_response(outcome: RequestPermissionOutcome) -> PermissionOut
                 "allow_always" => PermissionOutcome::AllowAlways,
                 "allow_once" => PermissionOutcome::AllowOnce,
                 "reject_once" => PermissionOutcome::Rejected,
+                "reject_always" => PermissionOutcome::RejectAlways,
                 _ => PermissionOutcome::Rejected, // Unknown option, treat as reject
             }
         }
@@ -190,7 +300,7 @@ fn format_tool_title(tool_name: &str, input: &serde_json::Value) -> String {
                 .unwrap_or("file");
             format!("Write to {}", path)
         }
-        "Edit" => {
+        "Edit" | "MultiEdit" => {
             let path = input
                 .get("file_path")
                 .and_then(|v| v.as_str())
@@ -289,6 +399,14 @@ mod tests {
             parse_permission_response(selected_reject),
             PermissionOutcome::Rejected
         );
+
+        let selected_reject_always = RequestPermissionOutcome::Selected(
+            SelectedPermissionOutcome::new(PermissionOptionId::new("reject_always")),
+        );
+        assert_eq!(
+            parse_permission_response(selected_reject_always),
+            PermissionOutcome::RejectAlways
+        );
     }
 
     #[test]
@@ -311,4 +429,125 @@ mod tests {
             PermissionOutcome::Rejected
         );
     }
+
+    /// Client that accepts the request after tweaking the suggested diff
+    struct EditingClient;
+
+    #[async_trait]
+    impl PermissionClient for EditingClient {
+        async fn request_permission(
+            &self,
+            request: RequestPermissionRequest,
+        ) -> Result<RequestPermissionResponse, AgentError> {
+            let Some([ToolCallContent::Diff(diff)]) = request.tool_call.fields.content.as_deref()
+            else {
+                panic!("expected a single diff in the permission request");
+            };
+            let mut meta = serde_json::Map::new();
+            meta.insert(
+                "editedText".to_string(),
+                json!(diff.new_text.replace("Hi", "Hello there")),
+            );
+            let outcome = RequestPermissionOutcome::Selected(SelectedPermissionOutcome::new(
+                PermissionOptionId::new("allow_once"),
+            ));
+            Ok(RequestPermissionResponse::new(outcome).meta(meta))
+        }
+    }
+
+    /// Client that never answers, like an editor that was closed
+    struct SilentClient;
+
+    #[async_trait]
+    impl PermissionClient for SilentClient {
+        async fn request_permission(
+            &self,
+            _request: RequestPermissionRequest,
+        ) -> Result<RequestPermissionResponse, AgentError> {
+            std::future::pending().await
+        }
+    }
+
+    /// Client that picks "Always Reject", checking the option names the rule
+    struct RejectingClient;
+
+    #[async_trait]
+    impl PermissionClient for RejectingClient {
+        async fn request_permission(
+            &self,
+            request: RequestPermissionRequest,
+        ) -> Result<RequestPermissionResponse, AgentError> {
+            let option = request
+                .options
+                .iter()
+                .find(|option| option.option_id.0.as_ref() == "reject_always")
+                .unwrap();
+            assert_eq!(option.name, "Always Reject Bash(rm -rf build)");
+            let outcome = RequestPermissionOutcome::Selected(SelectedPermissionOutcome::new(
+                option.option_id.clone(),
+            ));
+            Ok(RequestPermissionResponse::new(outcome))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reject_always_option_names_the_rule() {
+        let outcome = PermissionRequestBuilder::new(
+            "session-1",
+            "call-1",
+            "Bash",
+            json!({"command": "rm -rf build"}),
+        )
+        .reject_always_rule("Bash(rm -rf build)")
+        .request(&RejectingClient)
+        .await
+        .unwrap();
+        assert_eq!(outcome, PermissionOutcome::RejectAlways);
+    }
+
+    #[tokio::test]
+    async fn test_unanswered_request_times_out() {
+        let outcome =
+            PermissionRequestBuilder::new("session-1", "call-1", "Bash", json!({"command": "ls"}))
+                .timeout(Some(Duration::from_millis(20)))
+                .request(&SilentClient)
+                .await
+                .unwrap();
+        assert_eq!(outcome, PermissionOutcome::TimedOut);
+    }
+
+    #[tokio::test]
+    async fn test_client_edits_suggestion_before_accepting() {
+        use crate::mcp::ToolContext;
+        use crate::mcp::tools::{EditTool, Tool};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let file_path = temp_dir.path().join("greeting.txt");
+        std::fs::write(&file_path, "Hey, World!\n").unwrap();
+        let input = json!({
+            "file_path": file_path.to_str().unwrap(),
+            "old_string": "Hey",
+            "new_string": "Hi"
+        });
+
+        let suggestion = DiffSuggestion::from_tool_input("Edit", &input).unwrap();
+        let (outcome, edited) =
+            PermissionRequestBuilder::new("session-1", "call-1", "Edit", input.clone())
+                .suggestion(suggestion.clone())
+                .request_with_edits(&EditingClient)
+                .await
+                .unwrap();
+        assert_eq!(outcome, PermissionOutcome::AllowOnce);
+        assert_eq!(edited.as_deref(), Some("Hello there"));
+
+        // The tool writes the client's edited text instead of the proposal
+        let updated = suggestion.apply_edit(&input, &edited.unwrap()).unwrap();
+        let context = ToolContext::new("session-1", temp_dir.path());
+        let result = EditTool::new().execute(updated, &context).await;
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(
+            std::fs::read_to_string(&file_path).unwrap(),
+            "Hello there, World!\n"
+        );
+    }
 }
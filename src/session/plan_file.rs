@@ -0,0 +1,153 @@
+//! The session's current plan file
+//!
+//! In Plan mode the model writes its plan to a file in the plans directory.
+//! The plan file the session wrote most recently is its current plan:
+//! ExitPlanMode shows it for approval, and edits to it are reflected to the
+//! client as Plan notifications.
+
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::utils::{is_session_plan_path, plans_dir};
+
+/// Plan files larger than this are not read
+const MAX_PLAN_FILE_SIZE: u64 = 20 * 1024 * 1024;
+
+/// Tracks the plan file a session is working on
+#[derive(Debug, Default)]
+pub struct CurrentPlan {
+    /// Plans directory to check writes against (None = the configured one)
+    plans_dir: RwLock<Option<PathBuf>>,
+    /// Path of the current plan file, once one has been written
+    path: RwLock<Option<PathBuf>>,
+}
+
+impl CurrentPlan {
+    /// Create a tracker for plan files in the configured plans directory
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a tracker for plan files in `plans_dir` (the `plansDir` setting)
+    pub fn with_plans_dir(plans_dir: PathBuf) -> Self {
+        Self {
+            plans_dir: RwLock::new(Some(plans_dir)),
+            path: RwLock::default(),
+        }
+    }
+
+    /// The session's plans directory
+    pub fn plans_dir(&self) -> Option<PathBuf> {
+        self.plans_dir_setting().or_else(plans_dir)
+    }
+
+    /// Use `plans_dir` (the `plansDir` setting) instead of the configured
+    /// plans directory, or go back to it with None
+    pub fn set_plans_dir(&self, plans_dir: Option<PathBuf>) {
+        *self
+            .plans_dir
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = plans_dir;
+    }
+
+    /// The `plansDir` setting the tracker uses, if any
+    fn plans_dir_setting(&self) -> Option<PathBuf> {
+        self.plans_dir
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Path of the current plan file, if the session has written one
+    pub fn path(&self) -> Option<PathBuf> {
+        self.path
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Record a write to the absolute path `path`
+    ///
+    /// A file in the plans directory becomes the current plan. Returns
+    /// whether `path` is the current plan.
+    pub fn record_write(&self, path: &Path) -> bool {
+        let Some(path_str) = path.to_str() else {
+            return false;
+        };
+        let is_plan_file = is_session_plan_path(path_str, self.plans_dir_setting().as_deref());
+        if is_plan_file {
+            *self
+                .path
+                .write()
+                .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(path.to_path_buf());
+        }
+        is_plan_file
+    }
+
+    /// Read the current plan file
+    ///
+    /// Falls back to the most recently modified `.md` file in the plans
+    /// directory when the session hasn't written a plan file (e.g. one
+    /// left over from an earlier session). Returns Ok(None) when there is no
+    /// plan file or it is larger than 20MB.
+    pub fn read(&self) -> std::io::Result<Option<String>> {
+        let Some(path) = self.path().or_else(|| self.most_recent_plan_file()) else {
+            return Ok(None);
+        };
+
+        let size = std::fs::metadata(&path)?.len();
+        if size > MAX_PLAN_FILE_SIZE {
+            tracing::warn!(
+                "Plan file too large ({} bytes > {} limit), skipping: {:?}",
+                size,
+                MAX_PLAN_FILE_SIZE,
+                path
+            );
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(&path)?;
+        tracing::info!("Read plan file: {:?} (size: {} bytes)", path, size);
+        Ok(Some(content))
+    }
+
+    /// The most recently modified `.md` file in the plans directory
+    fn most_recent_plan_file(&self) -> Option<PathBuf> {
+        std::fs::read_dir(self.plans_dir()?)
+            .ok()?
+            .flatten()
+            .filter(|entry| entry.path().extension().and_then(|s| s.to_str()) == Some("md"))
+            .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+            .max_by_key(|(mtime, _)| *mtime)
+            .map(|(_, path)| path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_current_plan_follows_plan_file_writes() {
+        let temp_dir = TempDir::new().unwrap();
+        let plans_dir = temp_dir.path().join("plans");
+        std::fs::create_dir(&plans_dir).unwrap();
+        let current_plan = CurrentPlan::with_plans_dir(plans_dir.clone());
+        assert_eq!(current_plan.path(), None);
+        assert_eq!(current_plan.read().unwrap(), None);
+
+        // Without a write, the most recent plan file is read
+        std::fs::write(plans_dir.join("old.md"), "# Old plan\n").unwrap();
+        assert_eq!(
+            current_plan.read().unwrap().as_deref(),
+            Some("# Old plan\n")
+        );
+
+        let plan_file = plans_dir.join("plan.md");
+        std::fs::write(&plan_file, "# Plan\n").unwrap();
+        assert!(current_plan.record_write(&plan_file));
+        assert!(!current_plan.record_write(&temp_dir.path().join("main.rs")));
+        assert_eq!(current_plan.path(), Some(plan_file));
+        assert_eq!(current_plan.read().unwrap().as_deref(), Some("# Plan\n"));
+    }
+}
@@ -4,19 +4,21 @@
 //! ClaudeClient instance, usage tracking, and permission state.
 
 use dashmap::DashMap;
+use futures::StreamExt;
 use std::collections::{BTreeMap, HashMap};
 use std::error::Error;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::sync::OnceLock;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::time::Instant;
+use std::sync::RwLock as StdRwLock;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 use tokio::sync::broadcast;
 
 use claude_code_agent_sdk::types::config::PermissionMode as SdkPermissionMode;
 use claude_code_agent_sdk::types::mcp::McpSdkServerConfig;
 use claude_code_agent_sdk::{
-    ClaudeAgentOptions, ClaudeClient, HookEvent, HookMatcher, McpServerConfig, McpServers,
+    ClaudeAgentOptions, ClaudeClient, HookEvent, HookMatcher, McpServerConfig, McpServers, Message,
     SystemPrompt, SystemPromptPreset,
 };
 use sacp::JrConnectionCx;
@@ -30,21 +32,30 @@ use tracing::instrument;
 use crate::converter::NotificationConverter;
 use crate::hooks::{HookCallbackRegistry, create_post_tool_use_hook, create_pre_tool_use_hook};
 use crate::mcp::AcpMcpServer;
-use crate::permissions::create_can_use_tool_callback;
-use crate::settings::{PermissionChecker, SettingsManager};
+use crate::mcp::tools::{TodoItem, TodoList};
+use crate::permissions::{PermissionFallback, create_can_use_tool_callback};
+use crate::settings::{
+    CONFIG_DIR_ENV, PermissionChecker, SettingsManager, configured_user_config_dir, user_config_dir,
+};
 use crate::terminal::TerminalClient;
-use crate::types::{AgentConfig, AgentError, NewSessionMeta, Result};
+use crate::types::{
+    AgentConfig, AgentError, ContextBudget, NewSessionMeta, Result, SessionInfo, SessionStats,
+};
 
-use super::background_processes::BackgroundTerminal;
 use super::BackgroundProcessManager;
-use super::permission::{PermissionHandler, PermissionMode};
+use super::audit_log::PermissionAuditLog;
+use super::background_processes::BackgroundTerminal;
+use super::permission::{CustomMode, PermissionHandler, PermissionMode};
+use super::permission_manager::{DEFAULT_BATCH_WINDOW, PermissionManager};
+use super::plan_file::CurrentPlan;
+use super::session_index::SessionIndex;
 use super::usage::UsageTracker;
 
 /// Get the list of tools that should be replaced by ACP MCP server tools.
 ///
 /// Only tools that interact with the terminal or filesystem should be replaced:
 /// - Terminal tools: Bash, BashOutput, KillShell
-/// - File tools: Read, Write, Edit
+/// - File tools: Read, Write, Edit, MultiEdit
 ///
 /// Other tools like Glob, Grep, Task, etc. should remain as CLI built-in tools.
 fn get_acp_replacement_tools() -> Vec<&'static str> {
@@ -57,9 +68,22 @@ fn get_acp_replacement_tools() -> Vec<&'static str> {
         "Read",
         "Write",
         "Edit",
+        "MultiEdit",
     ]
 }
 
+/// Maximum length of a session title in characters
+const MAX_TITLE_LENGTH: usize = 100;
+
+/// Maximum length of a title derived from the first prompt
+const PROMPT_TITLE_LENGTH: usize = 60;
+
+/// Project memory files picked up when the working directory changes
+const MEMORY_FILES: &[&str] = &["CLAUDE.md", ".claude/CLAUDE.md", "CLAUDE.local.md"];
+
+/// Maximum characters of a memory file included after a cwd change
+const MAX_MEMORY_FILE_CHARS: usize = 40_000;
+
 /// An active Claude session
 ///
 /// Each session holds its own ClaudeClient instance and maintains
@@ -67,24 +91,52 @@ fn get_acp_replacement_tools() -> Vec<&'static str> {
 pub struct Session {
     /// Unique session identifier
     pub session_id: String,
-    /// Working directory for this session
-    pub cwd: PathBuf,
+    /// Working directory for this session (changed by `session/setCwd`)
+    cwd: StdRwLock<PathBuf>,
+    /// Note on a changed working directory, sent with the next prompt
+    cwd_notice: StdRwLock<Option<String>>,
+    /// Human-readable title (set by the client or derived from the first prompt)
+    title: StdRwLock<Option<String>>,
+    /// Whether the title was set explicitly by the client
+    title_explicit: AtomicBool,
+    /// Whether a title has already been requested from the fast model
+    title_generation_claimed: AtomicBool,
     /// The Claude client for this session
     client: RwLock<ClaudeClient>,
+    /// Options the Claude client was built with, to restart the CLI elsewhere
+    cli_options: ClaudeAgentOptions,
+    /// The CLI's ID for the conversation, once it has reported one
+    cli_session_id: StdRwLock<Option<String>>,
     /// Permission handler for tool execution (wrapped in Arc for can_use_tool callback)
     permission: Arc<RwLock<PermissionHandler>>,
-    /// Token usage tracker
-    usage_tracker: UsageTracker,
+    /// Index the permission mode is recorded in, so a resumed session keeps it
+    session_index: OnceLock<Arc<SessionIndex>>,
+    /// Token usage and cost tracker (shared with the converter)
+    usage_tracker: Arc<UsageTracker>,
+    /// Context window of the session's model in tokens
+    context_window: AtomicU64,
+    /// Primary model of the session (None = CLI default)
+    primary_model: StdRwLock<Option<String>>,
+    /// Whether the primary model changed and still has to be set on the CLI
+    model_change_pending: AtomicBool,
+    /// Default thinking budget of the session (None = CLI default)
+    max_thinking_tokens: Option<u32>,
+    /// Thinking budget currently set on the CLI
+    turn_thinking_tokens: StdRwLock<Option<u32>>,
+    /// Model for internal operations, when it differs from the primary model
+    internal_model: Option<String>,
     /// Notification converter with tool use cache (wrapped for interior mutability)
     converter: RwLock<NotificationConverter>,
+    /// Todo list kept from the model's TodoWrite calls (shared with the converter)
+    todos: Arc<TodoList>,
+    /// Plan file the session is working on in Plan mode (shared with the converter)
+    current_plan: Arc<CurrentPlan>,
     /// Whether the client is connected
     connected: AtomicBool,
     /// Hook callback registry for PostToolUse callbacks
     hook_callback_registry: Arc<HookCallbackRegistry>,
     /// Permission checker for hooks
     permission_checker: Arc<RwLock<PermissionChecker>>,
-    /// Current model ID for this session (set once during initialization)
-    current_model: OnceLock<String>,
     /// ACP MCP server for tool execution with notifications
     acp_mcp_server: Arc<AcpMcpServer>,
     /// Background process manager
@@ -113,6 +165,29 @@ pub struct Session {
     /// Set to true when cancel() is called, reset to false at start of new prompt
     /// Used to distinguish user cancellation from execution errors
     cancelled: AtomicBool,
+    /// Whether the client reviews Edit/Write diffs in permission requests
+    diff_suggestions: bool,
+    /// Whether the client handles grouped permission requests
+    grouped_permissions: bool,
+    /// Batches the permission requests of clients handling grouped ones
+    /// Created on first use, once the connection is ready
+    permission_manager: OnceLock<PermissionManager>,
+    /// Whether ExitPlanMode preselects "auto-accept edits"
+    accept_edits_after_plan: bool,
+    /// Custom permission modes from settings, selectable besides the built-in ones
+    custom_modes: StdRwLock<Vec<Arc<CustomMode>>>,
+    /// The `plansDir` setting, checked against each new working directory
+    plans_dir_setting: Option<PathBuf>,
+    /// Log of permission decisions, from the `permissionAuditLog` setting
+    audit_log: Option<Arc<PermissionAuditLog>>,
+    /// Time the client has to answer a permission request (None = no limit)
+    permission_request_timeout: Option<Duration>,
+    /// Whether the client opted out of permission requests at initialize
+    permission_requests_declined: AtomicBool,
+    /// Decides calls needing permission when the client can't be asked
+    permission_fallback: PermissionFallback,
+    /// Project environment from the client, applied to Bash commands
+    client_env: HashMap<String, String>,
 }
 
 /// Generate a stable cache key from JSON value
@@ -179,36 +254,55 @@ impl Session {
         let hook_callback_registry = Arc::new(HookCallbackRegistry::new());
 
         // Create permission checker for hooks
-        // Load settings from ~/.claude/settings.json, .claude/settings.json, etc.
-        let settings_manager = SettingsManager::new(&cwd)
-            .unwrap_or_else(|e| {
-                tracing::warn!("Failed to load settings manager from cwd: {}. Using default settings.", e);
-                // Fallback: try to load settings from home directory
-                if let Some(home) = dirs::home_dir() {
-                    tracing::info!("Attempting to load settings from home directory");
-                    SettingsManager::new(&home).unwrap_or_else(|e2| {
-                        tracing::error!("Failed to load settings from home directory: {}. Using minimal default settings.", e2);
-                        // Last resort: create a manager with minimal settings
-                        SettingsManager::new_with_settings(crate::settings::Settings::default(), "/")
-                    })
-                } else {
-                    tracing::error!("No home directory found. Using minimal default settings.");
-                    SettingsManager::new_with_settings(crate::settings::Settings::default(), "/")
-                }
-            });
+        let settings_manager = load_settings_manager(&cwd);
         // Create shared permission checker that will be used by both hook and permission handler
         // This ensures that runtime rule changes (e.g., "Always Allow") are reflected in both places
-        let permission_checker = Arc::new(RwLock::new(PermissionChecker::new(
-            settings_manager.settings().clone(),
-            &cwd,
-        )));
+        let mut checker = PermissionChecker::new(settings_manager.settings().clone(), &cwd);
+        let custom_modes = CustomMode::all_from_settings(settings_manager.settings(), &cwd);
+
+        // Seed session-scoped rules from the client (not persisted to settings)
+        if let Some(meta) = meta {
+            for rule in &meta.allowed_tools {
+                checker.add_allow_rule(rule);
+            }
+            for rule in &meta.denied_tools {
+                checker.add_deny_rule(rule);
+            }
+            if !meta.allowed_tools.is_empty() || !meta.denied_tools.is_empty() {
+                tracing::info!(
+                    session_id = %session_id,
+                    allowed_tools = ?meta.allowed_tools,
+                    denied_tools = ?meta.denied_tools,
+                    "Applied session permission rules from meta"
+                );
+            }
+        }
+        let permission_checker = Arc::new(RwLock::new(checker));
 
         // Create PermissionHandler with shared PermissionChecker
         // This ensures both pre_tool_use_hook and can_use_tool callback use the same rules
         // PermissionHandler uses AcceptEdits mode (compatible with root, allows all tools)
-        let permission_handler = Arc::new(RwLock::new(PermissionHandler::with_checker(
-            permission_checker.clone(),
-        )));
+        let mut handler = PermissionHandler::with_checker(permission_checker.clone());
+        let plans_dir = session_plans_dir(config.plans_dir.as_deref(), &cwd, &session_id);
+        handler.set_plans_dir(plans_dir.clone());
+        if let Some(mode) = meta.and_then(NewSessionMeta::get_permission_mode) {
+            match PermissionMode::parse(mode) {
+                Some(mode) => {
+                    handler.set_mode(mode);
+                    tracing::info!(
+                        session_id = %session_id,
+                        mode = mode.as_str(),
+                        "Starting session in permission mode from meta"
+                    );
+                }
+                None => tracing::warn!(
+                    session_id = %session_id,
+                    mode = %mode,
+                    "Ignoring unknown permission mode from meta"
+                ),
+            }
+        }
+        let permission_handler = Arc::new(RwLock::new(handler));
 
         // Create shared connection_cx_lock for hook permission requests
         let connection_cx_lock: Arc<OnceLock<JrConnectionCx<AgentToClient>>> =
@@ -223,6 +317,11 @@ impl Session {
         // can_use_tool callback uses this when CLI doesn't provide tool_use_id
         let tool_use_id_cache: Arc<DashMap<String, String>> = Arc::new(DashMap::new());
 
+        let audit_log = config
+            .permission_audit_log
+            .clone()
+            .map(|path| Arc::new(PermissionAuditLog::new(path)));
+
         // Create hooks with shared permission checker and handler
         let pre_tool_use_hook = create_pre_tool_use_hook(
             connection_cx_lock.clone(),
@@ -231,6 +330,8 @@ impl Session {
             permission_handler.clone(),
             permission_cache.clone(),
             tool_use_id_cache.clone(),
+            config.auto_approve_within_cwd,
+            audit_log.clone(),
         );
         let post_tool_use_hook = create_post_tool_use_hook(hook_callback_registry.clone());
 
@@ -263,10 +364,48 @@ impl Session {
         let session_lock: Arc<OnceLock<Arc<Session>>> = Arc::new(OnceLock::new());
 
         // Create ACP MCP server
-        let acp_mcp_server = Arc::new(AcpMcpServer::new("acp", env!("CARGO_PKG_VERSION")));
+        let acp_mcp_server = Arc::new(AcpMcpServer::new(
+            config.agent_name(),
+            config.agent_version(),
+        ));
+        acp_mcp_server.set_interactive_input(config.interactive_input);
+        acp_mcp_server.set_tool_timeouts(config.tool_timeouts.clone());
+        if let Some(max_argument_bytes) = config.max_tool_argument_bytes {
+            acp_mcp_server.set_max_argument_bytes(max_argument_bytes);
+        }
+        if let Some(bash_output_limit) = config.bash_output_limit_bytes {
+            acp_mcp_server.set_bash_output_limit(bash_output_limit);
+        }
+        if let Some(percent) = config.bash_timeout_warning_percent {
+            acp_mcp_server.set_bash_timeout_warning_percent(percent);
+        }
+        acp_mcp_server.set_combine_streams(config.combine_streams);
+        acp_mcp_server.set_verify_writes(config.verify_writes);
+        acp_mcp_server.set_prefer_ripgrep(config.prefer_ripgrep);
+        acp_mcp_server.set_env_info(config.env_info.clone());
+        acp_mcp_server.set_allow_localhost_fetch(config.allow_localhost_fetch);
+        acp_mcp_server.set_web_fetch_domains(config.web_fetch_domains.clone());
+        acp_mcp_server.set_web_search_domains(config.web_search_domains.clone());
+        acp_mcp_server.set_final_newline(config.final_newline);
+        if let Some(max_line_length) = config.max_line_length {
+            acp_mcp_server.set_max_line_length(max_line_length);
+        }
+        if let Some(max_read_lines) = config.read_max_lines {
+            acp_mcp_server.set_max_read_lines(max_read_lines);
+        }
+        if let Some(max_file_size) = config.max_file_size {
+            acp_mcp_server.set_max_file_size(max_file_size);
+        }
+        let client_env = meta.map(|m| m.env.clone()).unwrap_or_default();
+        if !client_env.is_empty() {
+            acp_mcp_server.set_env(client_env.clone());
+        }
 
         // Create background process manager
-        let background_processes = Arc::new(BackgroundProcessManager::new());
+        let background_processes = Arc::new(
+            BackgroundProcessManager::with_max_lifetime(config.background_max_lifetime)
+                .with_max_output_bytes(config.background_output_limit),
+        );
 
         // Build MCP servers with our ACP server
         let mut mcp_servers_dict = HashMap::new();
@@ -356,6 +495,14 @@ impl Session {
         // Apply config from environment
         config.apply_to_options(&mut options);
 
+        // Point the Claude CLI at the same user configuration directory
+        if let Some(config_dir) = configured_user_config_dir() {
+            options.env.insert(
+                CONFIG_DIR_ENV.to_string(),
+                config_dir.to_string_lossy().into_owned(),
+            );
+        }
+
         tracing::debug!(
             session_id = %session_id,
             model = ?options.model,
@@ -399,6 +546,16 @@ impl Session {
                 );
             }
 
+            // Pass the client's project environment to the Claude CLI
+            if !meta.env.is_empty() {
+                options.env.extend(meta.env.clone());
+                tracing::info!(
+                    session_id = %session_id,
+                    env_vars = ?meta.env.keys().collect::<Vec<_>>(),
+                    "Applied client environment from meta"
+                );
+            }
+
             // Set max thinking tokens if provided (enables extended thinking mode)
             if let Some(tokens) = meta.get_max_thinking_tokens() {
                 options.max_thinking_tokens = Some(tokens);
@@ -411,6 +568,8 @@ impl Session {
         }
 
         // Create the client
+        let max_thinking_tokens = options.max_thinking_tokens;
+        let cli_options = options.clone();
         let client = ClaudeClient::new(options);
 
         let elapsed = start_time.elapsed();
@@ -422,19 +581,55 @@ impl Session {
 
         // Clone cwd for converter before moving cwd into the struct
         let cwd_for_converter = cwd.clone();
+        let todos = Arc::new(TodoList::new());
+        let current_plan = Arc::new(match plans_dir {
+            Some(plans_dir) => CurrentPlan::with_plans_dir(plans_dir),
+            None => CurrentPlan::new(),
+        });
+        // Priced at the configured model until the CLI reports the one it uses
+        let usage_tracker = Arc::new(UsageTracker::with_pricing_table(
+            config.model_pricing.clone(),
+        ));
+        if let Some(model) = &config.model {
+            usage_tracker.set_model(model);
+        }
 
         // Build the Session struct
         let session = Self {
             session_id,
-            cwd,
+            cwd: StdRwLock::new(cwd),
+            cwd_notice: StdRwLock::new(None),
+            title: StdRwLock::new(None),
+            title_explicit: AtomicBool::new(false),
+            title_generation_claimed: AtomicBool::new(false),
             client: RwLock::new(client),
+            cli_options,
+            cli_session_id: StdRwLock::new(None),
             permission: permission_handler,
-            usage_tracker: UsageTracker::new(),
-            converter: RwLock::new(NotificationConverter::with_cwd(cwd_for_converter)),
+            session_index: OnceLock::new(),
+            usage_tracker: usage_tracker.clone(),
+            context_window: AtomicU64::new(config.resolved_context_window()),
+            primary_model: StdRwLock::new(config.model.clone()),
+            model_change_pending: AtomicBool::new(false),
+            max_thinking_tokens,
+            turn_thinking_tokens: StdRwLock::new(max_thinking_tokens),
+            internal_model: config
+                .internal_model()
+                .filter(|model| config.model.as_deref() != Some(*model))
+                .map(str::to_string),
+            converter: RwLock::new(
+                NotificationConverter::with_cwd(cwd_for_converter)
+                    .with_bash_description_synthesis(config.synthesize_bash_description)
+                    .with_diff_context_lines(config.diff_context_lines)
+                    .with_todo_list(todos.clone())
+                    .with_current_plan(current_plan.clone())
+                    .with_usage_tracker(usage_tracker),
+            ),
+            todos,
+            current_plan,
             connected: AtomicBool::new(false),
             hook_callback_registry,
             permission_checker,
-            current_model: OnceLock::new(),
             acp_mcp_server,
             background_processes,
             external_mcp_servers: OnceLock::new(),
@@ -444,6 +639,17 @@ impl Session {
             permission_cache,
             tool_use_id_cache,
             cancelled: AtomicBool::new(false),
+            diff_suggestions: meta.is_some_and(|m| m.diff_suggestions),
+            grouped_permissions: meta.is_some_and(|m| m.grouped_permissions),
+            permission_manager: OnceLock::new(),
+            accept_edits_after_plan: config.accept_edits_after_plan,
+            custom_modes: StdRwLock::new(custom_modes),
+            plans_dir_setting: config.plans_dir.clone(),
+            audit_log,
+            permission_request_timeout: config.permission_request_timeout,
+            permission_requests_declined: AtomicBool::new(false),
+            permission_fallback: config.permission_fallback,
+            client_env,
         };
 
         // Wrap in Arc
@@ -663,7 +869,7 @@ impl Session {
                             s.command.to_string_lossy().as_ref(),
                             &s.args,
                             env.as_ref(),
-                            Some(self.cwd.as_path()),
+                            Some(self.cwd().as_path()),
                         )
                         .await
                     {
@@ -699,12 +905,47 @@ impl Session {
                     );
                 }
                 McpServer::Sse(s) => {
-                    tracing::warn!(
+                    let server_start = Instant::now();
+
+                    tracing::info!(
                         session_id = %self.session_id,
                         server_name = %s.name,
                         url = %s.url,
-                        "SSE MCP servers not yet supported"
+                        "Connecting to external MCP server (sse)"
                     );
+
+                    let headers: HashMap<String, String> = s
+                        .headers
+                        .iter()
+                        .map(|h| (h.name.clone(), h.value.clone()))
+                        .collect();
+
+                    match external_manager
+                        .connect_sse(s.name.clone(), &s.url, &headers)
+                        .await
+                    {
+                        Ok(()) => {
+                            success_count += 1;
+                            let elapsed = server_start.elapsed();
+                            tracing::info!(
+                                session_id = %self.session_id,
+                                server_name = %s.name,
+                                elapsed_ms = elapsed.as_millis(),
+                                "Successfully connected to external MCP server"
+                            );
+                        }
+                        Err(e) => {
+                            error_count += 1;
+                            let elapsed = server_start.elapsed();
+                            tracing::error!(
+                                session_id = %self.session_id,
+                                server_name = %s.name,
+                                error = %e,
+                                elapsed_ms = elapsed.as_millis(),
+                                "Failed to connect to external MCP server"
+                            );
+                        }
+                    }
                 }
                 _ => {
                     tracing::warn!(
@@ -749,7 +990,7 @@ impl Session {
         let start_time = Instant::now();
         tracing::info!(
             session_id = %self.session_id,
-            cwd = ?self.cwd,
+            cwd = ?self.cwd(),
             "Connecting to Claude CLI..."
         );
 
@@ -841,6 +1082,94 @@ impl Session {
         self.client.write().await
     }
 
+    /// Get the model used for internal operations, if it differs from the
+    /// primary model
+    pub fn internal_model(&self) -> Option<&str> {
+        self.internal_model.as_deref()
+    }
+
+    /// Compact the conversation with the Claude CLI's `/compact` command
+    ///
+    /// The CLI summarizes earlier turns and continues from the summary. The
+    /// summary is written by the internal (small/fast) model, and the primary
+    /// model is restored afterwards. The CLI's response is consumed here and
+    /// not forwarded to the client.
+    pub async fn compact_conversation(&self) -> Result<()> {
+        let mut client = self.client_mut().await;
+        if let Some(model) = self.internal_model() {
+            client.set_model(Some(model)).await?;
+        }
+
+        let result = async {
+            client.query("/compact").await?;
+            let mut stream = client.receive_response();
+            while let Some(message) = stream.next().await {
+                if let Message::Result(result) = message? {
+                    if result.is_error {
+                        return Err(AgentError::Internal(format!(
+                            "Compaction failed: {}",
+                            result.result.unwrap_or(result.subtype)
+                        )));
+                    }
+                    break;
+                }
+            }
+            Ok::<(), AgentError>(())
+        }
+        .await;
+
+        if self.internal_model.is_some()
+            && let Err(e) = client.set_model(self.current_model().as_deref()).await
+        {
+            tracing::warn!(
+                session_id = %self.session_id,
+                error = %e,
+                "Failed to restore the primary model after compaction"
+            );
+        }
+        result?;
+
+        tracing::info!(
+            session_id = %self.session_id,
+            model = ?self.internal_model,
+            "Conversation compacted"
+        );
+        Ok(())
+    }
+
+    /// Set the thinking budget for the next turn
+    ///
+    /// `budget` comes from thinking trigger phrases in the prompt and
+    /// overrides the session default for this turn; None restores the
+    /// default. The CLI is only updated when the budget changes.
+    pub async fn set_turn_thinking_budget(&self, budget: Option<u32>) -> Result<()> {
+        let tokens = budget.or(self.max_thinking_tokens);
+        let current = *self
+            .turn_thinking_tokens
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if current == tokens {
+            return Ok(());
+        }
+
+        self.client_mut()
+            .await
+            .set_max_thinking_tokens(tokens)
+            .await?;
+        *self
+            .turn_thinking_tokens
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = tokens;
+
+        tracing::info!(
+            session_id = %self.session_id,
+            max_thinking_tokens = ?tokens,
+            from_trigger = budget.is_some(),
+            "Thinking budget set for turn"
+        );
+        Ok(())
+    }
+
     /// Get a receiver for cancel signals
     ///
     /// This can be used to listen for MCP cancellation notifications.
@@ -918,10 +1247,48 @@ impl Session {
         self.permission.read().await.mode()
     }
 
+    /// ID of the current permission mode, which may be a custom mode
+    pub async fn permission_mode_id(&self) -> String {
+        self.permission.read().await.mode_id().to_string()
+    }
+
+    /// Custom permission modes defined in settings
+    pub fn custom_modes(&self) -> Vec<Arc<CustomMode>> {
+        self.custom_modes
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Custom permission mode with the given ID, if settings define one
+    pub fn custom_mode(&self, mode_id: &str) -> Option<Arc<CustomMode>> {
+        self.custom_modes
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .iter()
+            .find(|mode| mode.id() == mode_id)
+            .cloned()
+    }
+
+    /// Switch to a custom permission mode
+    ///
+    /// The SDK only knows built-in modes; it is told the custom mode's base
+    /// mode by the caller. Custom modes aren't recorded in the session index.
+    pub async fn set_custom_mode(&self, custom_mode: Arc<CustomMode>) {
+        tracing::info!(
+            session_id = %self.session_id,
+            mode = custom_mode.id(),
+            base_mode = custom_mode.base().as_str(),
+            "Custom permission mode set"
+        );
+        self.permission.write().await.set_custom_mode(custom_mode);
+    }
+
     /// Set the permission mode
     ///
     /// Updates the PermissionHandler. The hook will read the mode
     /// from the same PermissionHandler, ensuring consistency.
+    /// The mode is also recorded in the session index, if set.
     pub async fn set_permission_mode(&self, mode: PermissionMode) {
         // Update the permission handler (single source of truth)
         self.permission.write().await.set_mode(mode);
@@ -931,6 +1298,65 @@ impl Session {
             mode = mode.as_str(),
             "Permission mode updated"
         );
+
+        if let Some(index) = self.session_index.get()
+            && let Err(e) = index.record_permission_mode(&self.session_id, mode)
+        {
+            tracing::warn!(
+                session_id = %self.session_id,
+                path = %index.path().display(),
+                error = %e,
+                "Failed to record permission mode in session index"
+            );
+        }
+    }
+
+    /// Record this session's permission mode changes in `index`
+    ///
+    /// Set once by the SessionManager; later calls are ignored.
+    pub fn set_session_index(&self, index: Arc<SessionIndex>) {
+        drop(self.session_index.set(index));
+    }
+
+    /// Record whether the client answers `session/request_permission`
+    ///
+    /// Set by the SessionManager from the client's initialize capabilities.
+    pub fn set_permission_requests_supported(&self, supported: bool) {
+        self.permission_requests_declined
+            .store(!supported, Ordering::SeqCst);
+    }
+
+    /// Whether permission requests can be sent to the client
+    pub fn permission_requests_supported(&self) -> bool {
+        !self.permission_requests_declined.load(Ordering::SeqCst)
+    }
+
+    /// How calls needing permission are decided when the client can't be asked
+    pub fn permission_fallback(&self) -> PermissionFallback {
+        self.permission_fallback
+    }
+
+    /// Whether the ExitPlanMode dialog preselects "auto-accept edits"
+    /// (the `acceptEditsAfterPlan` setting)
+    pub fn accept_edits_after_plan(&self) -> bool {
+        self.accept_edits_after_plan
+    }
+
+    /// Leave Plan mode for the mode the user picked when approving the plan
+    ///
+    /// Sets the mode, notifies the client and returns the mode switched to.
+    pub async fn approve_plan(&self, selected: PermissionMode) -> PermissionMode {
+        self.set_permission_mode(selected).await;
+        self.send_mode_update(selected.as_str());
+        selected
+    }
+
+    /// Build the session/update notification for a permission mode change
+    pub fn mode_update_notification(&self, mode: &str) -> SessionNotification {
+        SessionNotification::new(
+            SessionId::new(self.session_id.clone()),
+            SessionUpdate::CurrentModeUpdate(CurrentModeUpdate::new(SessionModeId::new(mode))),
+        )
     }
 
     /// Send session/update notification for permission mode change
@@ -948,11 +1374,7 @@ impl Session {
             return;
         };
 
-        let mode_update = CurrentModeUpdate::new(SessionModeId::new(mode));
-        let notification = SessionNotification::new(
-            SessionId::new(self.session_id.clone()),
-            SessionUpdate::CurrentModeUpdate(mode_update),
-        );
+        let notification = self.mode_update_notification(mode);
 
         if let Err(e) = connection_cx.send_notification(notification) {
             tracing::warn!(
@@ -977,23 +1399,346 @@ impl Session {
         self.permission.read().await.add_allow_rule(tool_name).await;
     }
 
-    /// Get the current model ID
-    ///
-    /// Note: Not yet used because sacp SDK does not support SetSessionModel.
-    #[allow(dead_code)]
+    /// Get the current model ID (None = CLI default)
     pub fn current_model(&self) -> Option<String> {
-        self.current_model.get().cloned()
+        self.primary_model
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Switch the session to another model
+    ///
+    /// The model is set on the CLI at the start of the next prompt, so a
+    /// turn in progress finishes with the model it started with.
+    pub fn set_model(&self, model_id: &str, context_window: u64) {
+        let previous = self
+            .primary_model
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .replace(model_id.to_string());
+        self.context_window.store(context_window, Ordering::SeqCst);
+        self.usage_tracker.set_model(model_id);
+        self.model_change_pending.store(true, Ordering::SeqCst);
+        tracing::info!(
+            session_id = %self.session_id,
+            previous = ?previous,
+            model = %model_id,
+            context_window = context_window,
+            "Session model changed"
+        );
+    }
+
+    /// Set a model changed with [`Self::set_model`] on the CLI
+    ///
+    /// Does nothing unless the model changed since the last call.
+    pub async fn apply_model_change(&self) -> Result<()> {
+        if !self.model_change_pending.swap(false, Ordering::SeqCst) {
+            return Ok(());
+        }
+        let model = self.current_model();
+        if let Err(e) = self.client_mut().await.set_model(model.as_deref()).await {
+            // Try again with the next prompt
+            self.model_change_pending.store(true, Ordering::SeqCst);
+            return Err(e.into());
+        }
+        tracing::debug!(
+            session_id = %self.session_id,
+            model = ?model,
+            "Model set on Claude CLI"
+        );
+        Ok(())
+    }
+
+    /// Get the session title, if one has been set
+    pub fn title(&self) -> Option<String> {
+        self.title
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Get the working directory
+    pub fn cwd(&self) -> PathBuf {
+        self.cwd
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Change the working directory
+    ///
+    /// A relative path is resolved against the current directory. The
+    /// session's settings are discovered again from the new directory:
+    /// permission rules (rules added during the session are kept), custom
+    /// modes and whether the `plansDir` setting applies. The ACP tools
+    /// operate in the new directory from their next call on, and the Claude
+    /// CLI is restarted there, resuming the conversation. The next prompt
+    /// tells Claude about the change along with the project memory
+    /// (CLAUDE.md) found there.
+    ///
+    /// Returns the canonical new directory.
+    pub async fn set_cwd(&self, path: &Path) -> Result<PathBuf> {
+        let requested = self.cwd().join(path);
+        let cwd = requested.canonicalize().map_err(|e| {
+            AgentError::invalid_params(format!(
+                "Cannot change directory to {}: {}",
+                requested.display(),
+                e
+            ))
+        })?;
+        if !cwd.is_dir() {
+            return Err(AgentError::invalid_params(format!(
+                "Cannot change directory to {}: not a directory",
+                cwd.display()
+            )));
+        }
+
+        let previous = self.cwd();
+        if cwd != previous {
+            self.restart_cli_in(&previous, &cwd).await?;
+        }
+
+        let settings_manager = load_settings_manager(&cwd);
+        let settings = settings_manager.settings();
+        *self
+            .custom_modes
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) =
+            CustomMode::all_from_settings(settings, &cwd);
+        self.permission_checker
+            .write()
+            .await
+            .relocate(settings.clone(), &cwd);
+        let plans_dir =
+            session_plans_dir(self.plans_dir_setting.as_deref(), &cwd, &self.session_id);
+        self.permission
+            .write()
+            .await
+            .set_plans_dir(plans_dir.clone());
+        self.current_plan.set_plans_dir(plans_dir);
+        self.converter.write().await.set_cwd(cwd.clone());
+        self.acp_mcp_server.set_cwd(cwd.clone());
+
+        let notice = cwd_change_notice(&cwd);
+        *self
+            .cwd_notice
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(notice);
+        *self
+            .cwd
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = cwd.clone();
+
+        tracing::info!(
+            session_id = %self.session_id,
+            previous = %previous.display(),
+            cwd = %cwd.display(),
+            "Session working directory changed"
+        );
+        Ok(cwd)
+    }
+
+    /// Restart the Claude CLI in `cwd`, resuming the conversation
+    ///
+    /// A running CLI is stopped and the next prompt starts the new one, with
+    /// the session's current model and default thinking budget.
+    async fn restart_cli_in(&self, previous: &Path, cwd: &Path) -> Result<()> {
+        let mut options = self.cli_options.clone();
+        options.cwd = Some(cwd.to_path_buf());
+        options.model = self.current_model();
+        if let Some(cli_session_id) = self.cli_session_id().or(options.resume.take()) {
+            copy_cli_transcript(&cli_session_id, previous, cwd).await;
+            options.resume = Some(cli_session_id);
+        }
+
+        let resume = options.resume.clone();
+        let mut client = self.client.write().await;
+        if self.connected.load(Ordering::SeqCst) {
+            client.disconnect().await.map_err(AgentError::from)?;
+            self.connected.store(false, Ordering::SeqCst);
+        }
+        *client = ClaudeClient::new(options);
+        self.model_change_pending.store(false, Ordering::SeqCst);
+        *self
+            .turn_thinking_tokens
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = self.max_thinking_tokens;
+
+        tracing::info!(
+            session_id = %self.session_id,
+            cwd = %cwd.display(),
+            resume = ?resume,
+            "Claude CLI will restart in the new working directory"
+        );
+        Ok(())
+    }
+
+    /// The CLI's ID for the conversation, once it has reported one
+    pub fn cli_session_id(&self) -> Option<String> {
+        self.cli_session_id
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Record the conversation ID the CLI reported
+    pub fn set_cli_session_id(&self, cli_session_id: &str) {
+        let mut guard = self
+            .cli_session_id
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if guard.as_deref() != Some(cli_session_id) {
+            *guard = Some(cli_session_id.to_string());
+        }
+    }
+
+    /// Take the note on a changed working directory, if one is pending
+    pub fn take_cwd_notice(&self) -> Option<String> {
+        self.cwd_notice
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .take()
+    }
+
+    /// Set the session title
+    ///
+    /// Whitespace is collapsed and the title is capped at 100 characters.
+    /// An empty title clears it. Returns the stored title.
+    pub fn set_title(&self, title: &str) -> Option<String> {
+        let normalized = normalize_title(title, MAX_TITLE_LENGTH);
+        tracing::info!(
+            session_id = %self.session_id,
+            title = ?normalized,
+            "Session title updated"
+        );
+        let mut guard = self
+            .title
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        self.title_explicit
+            .store(normalized.is_some(), Ordering::SeqCst);
+        *guard = normalized.clone();
+        normalized
+    }
+
+    /// Check whether the client has set the title explicitly
+    pub fn has_explicit_title(&self) -> bool {
+        self.title_explicit.load(Ordering::SeqCst)
+    }
+
+    /// Store a generated title unless the client has set one explicitly
+    ///
+    /// Replaces a title derived from the first prompt. Returns the stored title.
+    pub fn set_generated_title(&self, title: &str) -> Option<String> {
+        let normalized = normalize_title(title, PROMPT_TITLE_LENGTH)?;
+        let mut guard = self
+            .title
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if self.has_explicit_title() {
+            return None;
+        }
+        tracing::info!(
+            session_id = %self.session_id,
+            title = %normalized,
+            "Session title generated"
+        );
+        *guard = Some(normalized.clone());
+        Some(normalized)
+    }
+
+    /// Claim the one-time title generation for this session
+    ///
+    /// Returns true only for the first caller.
+    pub fn claim_title_generation(&self) -> bool {
+        !self.title_generation_claimed.swap(true, Ordering::SeqCst)
+    }
+
+    /// Derive a title from the first prompt if no title has been set yet
+    pub fn set_title_from_prompt(&self, prompt: &str) {
+        let first_line = prompt
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or_default();
+        let Some(title) = normalize_title(first_line, PROMPT_TITLE_LENGTH) else {
+            return;
+        };
+
+        let mut guard = self
+            .title
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        if guard.is_none() {
+            tracing::debug!(
+                session_id = %self.session_id,
+                title = %title,
+                "Session title derived from first prompt"
+            );
+            *guard = Some(title);
+        }
+    }
+
+    /// Get a summary of this session for the session list API
+    pub fn info(&self) -> SessionInfo {
+        SessionInfo {
+            session_id: self.session_id.clone(),
+            cwd: self.cwd().display().to_string(),
+            title: self.title(),
+        }
+    }
+
+    /// Get the current todo list
+    ///
+    /// Reflects every TodoWrite call of the session, with partial lists
+    /// merged into the previous one.
+    pub fn current_todos(&self) -> Vec<TodoItem> {
+        self.todos.get_all()
     }
 
-    /// Set the model for this session
+    /// Write the current todo list to a markdown file in `dir`
     ///
-    /// Note: Not yet used because sacp SDK does not support SetSessionModel.
-    #[allow(dead_code)]
-    pub fn set_model(&self, model_id: String) {
-        // Only set if not already set (may be called multiple times)
-        if self.current_model.get().is_none() {
-            drop(self.current_model.set(model_id));
+    /// Todos become checkboxes, checked once completed. The file is named
+    /// `file_name` (with `.md` added if missing), or after the session by
+    /// default; an existing file of that name is replaced.
+    pub fn export_plan(&self, dir: &Path, file_name: Option<&str>) -> Result<PathBuf> {
+        if self.todos.get_all().is_empty() {
+            return Err(AgentError::invalid_params(format!(
+                "Session {} has no plan to export",
+                self.session_id
+            )));
+        }
+
+        let mut file_name = file_name.map_or_else(
+            || format!("plan-{}", self.session_id),
+            |name| name.trim().to_string(),
+        );
+        if file_name.is_empty() || file_name.starts_with('.') || file_name.contains(['/', '\\']) {
+            return Err(AgentError::invalid_params(format!(
+                "Invalid plan file name: {:?}",
+                file_name
+            )));
+        }
+        if !file_name.ends_with(".md") {
+            file_name.push_str(".md");
         }
+
+        let path = dir.join(file_name);
+        let title = self.title().unwrap_or_else(|| "Plan".to_string());
+        std::fs::create_dir_all(dir)?;
+        std::fs::write(&path, self.todos.to_markdown(&title))?;
+        tracing::info!(
+            session_id = %self.session_id,
+            path = %path.display(),
+            "Plan exported"
+        );
+        Ok(path)
+    }
+
+    /// Get the plan file the session is working on in Plan mode
+    pub fn current_plan(&self) -> &CurrentPlan {
+        &self.current_plan
     }
 
     /// Get the usage tracker
@@ -1001,6 +1746,64 @@ impl Session {
         &self.usage_tracker
     }
 
+    /// Get the context window of the session's model in tokens
+    pub fn context_window(&self) -> u64 {
+        self.context_window.load(Ordering::SeqCst)
+    }
+
+    /// Whether the client opted in to reviewing Edit/Write diffs before they are written
+    pub fn diff_suggestions(&self) -> bool {
+        self.diff_suggestions
+    }
+
+    /// Permission manager grouping requests that arrive close together
+    ///
+    /// None unless the client opted in with `_meta.groupedPermissions` on
+    /// `session/new`, or before the connection is ready; permission
+    /// requests are then sent one at a time.
+    pub fn permission_manager(&self) -> Option<&PermissionManager> {
+        if !self.grouped_permissions {
+            return None;
+        }
+        let connection_cx = self.get_connection_cx()?;
+        Some(self.permission_manager.get_or_init(|| {
+            PermissionManager::with_client(
+                Arc::new(connection_cx.clone()),
+                Some(DEFAULT_BATCH_WINDOW),
+            )
+        }))
+    }
+
+    /// Log of permission decisions, if `permissionAuditLog` is set
+    pub fn audit_log(&self) -> Option<&PermissionAuditLog> {
+        self.audit_log.as_deref()
+    }
+
+    /// Time the client has to answer a permission request (None = no limit)
+    pub fn permission_request_timeout(&self) -> Option<Duration> {
+        self.permission_request_timeout
+    }
+
+    /// Get the project environment the client provided for this session
+    pub fn client_env(&self) -> &HashMap<String, String> {
+        &self.client_env
+    }
+
+    /// Get how much of the context window is left after the latest turn
+    pub fn context_budget(&self) -> ContextBudget {
+        ContextBudget::new(self.context_window(), self.usage_tracker.context_tokens())
+    }
+
+    /// Get usage statistics for this session
+    pub fn stats(&self) -> SessionStats {
+        SessionStats {
+            active_sessions: 1,
+            total_usage: self.usage_tracker.get(),
+            total_cost_usd: self.usage_tracker.cost_usd(),
+            context: Some(self.context_budget()),
+        }
+    }
+
     /// Get the notification converter (read-only access)
     pub async fn converter(&self) -> tokio::sync::RwLockReadGuard<'_, NotificationConverter> {
         self.converter.read().await
@@ -1141,6 +1944,9 @@ impl Session {
             "Background processes cleanup completed"
         );
 
+        // 3. Drop tool caches (e.g. fetched web pages)
+        self.acp_mcp_server.mcp_server().registry().clear_caches();
+
         let elapsed = start_time.elapsed();
         tracing::info!(
             session_id = %self.session_id,
@@ -1162,7 +1968,7 @@ impl Session {
     ) {
         self.acp_mcp_server.set_session_id(&self.session_id);
         self.acp_mcp_server.set_connection(connection_cx);
-        self.acp_mcp_server.set_cwd(self.cwd.clone());
+        self.acp_mcp_server.set_cwd(self.cwd());
         self.acp_mcp_server
             .set_background_processes(self.background_processes.clone());
         self.acp_mcp_server
@@ -1190,12 +1996,135 @@ impl Session {
     }
 }
 
+/// Collapse whitespace and cap a title at `max_chars` characters
+///
+/// Returns `None` for titles that are empty after normalization.
+fn normalize_title(title: &str, max_chars: usize) -> Option<String> {
+    let collapsed = title.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.is_empty() {
+        return None;
+    }
+    if collapsed.chars().count() <= max_chars {
+        return Some(collapsed);
+    }
+    let truncated: String = collapsed.chars().take(max_chars - 1).collect();
+    Some(format!("{}…", truncated.trim_end()))
+}
+
+/// Load the settings that apply in `cwd`
+///
+/// Reads ~/.claude/settings.json, .claude/settings.json, etc. Falls back to
+/// the home directory's settings, then to defaults, when they can't be loaded.
+fn load_settings_manager(cwd: &Path) -> SettingsManager {
+    SettingsManager::new(cwd).unwrap_or_else(|e| {
+        tracing::warn!("Failed to load settings manager from cwd: {}. Using default settings.", e);
+        // Fallback: try to load settings from home directory
+        if let Some(home) = dirs::home_dir() {
+            tracing::info!("Attempting to load settings from home directory");
+            SettingsManager::new(&home).unwrap_or_else(|e2| {
+                tracing::error!("Failed to load settings from home directory: {}. Using minimal default settings.", e2);
+                // Last resort: create a manager with minimal settings
+                SettingsManager::new_with_settings(crate::settings::Settings::default(), "/")
+            })
+        } else {
+            tracing::error!("No home directory found. Using minimal default settings.");
+            SettingsManager::new_with_settings(crate::settings::Settings::default(), "/")
+        }
+    })
+}
+
+/// Describe a working directory change to the model
+///
+/// Includes the project memory files of the new directory, since the CLI
+/// only loaded those of the directory the session started in.
+/// The `plansDir` setting, unless the session's directory `cwd` is inside it
+fn session_plans_dir(plans_dir: Option<&Path>, cwd: &Path, session_id: &str) -> Option<PathBuf> {
+    let plans_dir = plans_dir?;
+    if crate::utils::plans_dir_contains(plans_dir, cwd) {
+        tracing::warn!(
+            session_id = %session_id,
+            plans_dir = %plans_dir.display(),
+            "Ignoring plansDir setting: it contains the session's working directory"
+        );
+        return None;
+    }
+    Some(plans_dir.to_path_buf())
+}
+
+/// Copy the CLI's transcript of conversation `cli_session_id` from the
+/// project of directory `from` to the project of `to`
+///
+/// The CLI only resumes conversations of its own working directory. It
+/// keeps them in `projects/<directory>/<id>.jsonl` in the user
+/// configuration directory, with every character of the directory other
+/// than a letter or digit replaced by `-`.
+async fn copy_cli_transcript(cli_session_id: &str, from: &Path, to: &Path) {
+    let Some(projects_dir) = user_config_dir().map(|dir| dir.join("projects")) else {
+        return;
+    };
+    let project_dir = |cwd: &Path| {
+        let name: String = cwd
+            .to_string_lossy()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        projects_dir.join(name)
+    };
+    let file_name = format!("{cli_session_id}.jsonl");
+    let source = project_dir(from).join(&file_name);
+    let target_dir = project_dir(to);
+    if source.parent() == Some(target_dir.as_path()) {
+        return;
+    }
+
+    let result = async {
+        tokio::fs::create_dir_all(&target_dir).await?;
+        tokio::fs::copy(&source, target_dir.join(&file_name)).await
+    }
+    .await;
+    if let Err(e) = result {
+        tracing::warn!(
+            source = %source.display(),
+            target = %target_dir.display(),
+            error = %e,
+            "Failed to copy the CLI transcript, the conversation may not resume"
+        );
+    }
+}
+
+fn cwd_change_notice(cwd: &Path) -> String {
+    let mut notice = format!(
+        "The working directory of this session changed to {}. Resolve relative paths \
+         against it and run commands there.",
+        cwd.display()
+    );
+    for name in MEMORY_FILES {
+        let Ok(content) = std::fs::read_to_string(cwd.join(name)) else {
+            continue;
+        };
+        let content = content.trim();
+        if content.is_empty() {
+            continue;
+        }
+        let mut memory: String = content.chars().take(MAX_MEMORY_FILE_CHARS).collect();
+        if memory.len() < content.len() {
+            memory.push_str("\n[truncated]");
+        }
+        notice.push_str(&format!(
+            "\n\nContents of {} in the new directory:\n\n{}",
+            name, memory
+        ));
+    }
+    notice
+}
+
 #[allow(clippy::missing_fields_in_debug)]
 impl std::fmt::Debug for Session {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Session")
             .field("session_id", &self.session_id)
-            .field("cwd", &self.cwd)
+            .field("cwd", &self.cwd())
+            .field("title", &self.title())
             .field("connected", &self.connected.load(Ordering::Relaxed))
             .finish()
     }
@@ -1204,6 +2133,11 @@ impl std::fmt::Debug for Session {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mcp::{
+        DomainFilter, EnvInfoConfig, FinalNewlinePolicy, InteractiveInputPolicy, ToolTimeouts,
+    };
+    use crate::session::ToolPermissionResult;
+    use crate::types::PricingTable;
 
     fn test_config() -> AgentConfig {
         AgentConfig {
@@ -1212,6 +2146,40 @@ mod tests {
             model: None,
             small_fast_model: None,
             max_thinking_tokens: None,
+            auto_generate_title: false,
+            auto_compact: false,
+            context_window: None,
+            synthesize_bash_description: false,
+            interactive_input: InteractiveInputPolicy::default(),
+            max_line_length: None,
+            read_max_lines: None,
+            max_file_size: None,
+            tool_timeouts: ToolTimeouts::default(),
+            max_tool_argument_bytes: None,
+            bash_output_limit_bytes: None,
+            bash_timeout_warning_percent: None,
+            combine_streams: true,
+            plans_dir: None,
+            accept_edits_after_plan: false,
+            auto_approve_within_cwd: false,
+            permission_audit_log: None,
+            permission_request_timeout: None,
+            permission_fallback: crate::permissions::PermissionFallback::Deny,
+            verify_writes: false,
+            prefer_ripgrep: true,
+            diff_context_lines: None,
+            env_info: EnvInfoConfig::default(),
+            allow_localhost_fetch: false,
+            web_fetch_domains: DomainFilter::default(),
+            web_search_domains: DomainFilter::default(),
+            background_max_lifetime: None,
+            background_output_limit: None,
+            model_pricing: PricingTable::default(),
+            final_newline: FinalNewlinePolicy::Off,
+            agent_name: None,
+            agent_version: None,
+            thinking_triggers: None,
+            custom_modes: HashMap::new(),
         }
     }
 
@@ -1226,12 +2194,228 @@ mod tests {
         .unwrap();
 
         assert_eq!(session.session_id, "test-session-1");
-        assert_eq!(session.cwd, PathBuf::from("/tmp"));
+        assert_eq!(session.cwd(), PathBuf::from("/tmp"));
         assert!(!session.is_connected());
         // Cancelled flag should be false initially
         assert!(!session.is_user_cancelled());
     }
 
+    #[tokio::test]
+    async fn test_session_permission_rules_from_meta() {
+        let meta = NewSessionMeta::from_request_meta(Some(&serde_json::json!({
+            "allowedTools": ["Bash(cargo test:*)"],
+            "deniedTools": ["Bash(rm:*)"]
+        })));
+        let session = Session::new(
+            "test-meta-rules".to_string(),
+            PathBuf::from("/tmp"),
+            &test_config(),
+            Some(&meta),
+        )
+        .unwrap();
+
+        let permission = session.permission().await;
+        assert_eq!(
+            permission
+                .check_permission("Bash", &serde_json::json!({"command": "cargo test --all"}))
+                .await,
+            ToolPermissionResult::Allowed
+        );
+        assert!(matches!(
+            permission
+                .check_permission("Bash", &serde_json::json!({"command": "rm -rf build"}))
+                .await,
+            ToolPermissionResult::Blocked { .. }
+        ));
+        assert_eq!(
+            permission
+                .check_permission("Bash", &serde_json::json!({"command": "cargo build"}))
+                .await,
+            ToolPermissionResult::NeedsPermission
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_set_cwd() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let sub = temp_dir.path().join("sub");
+        std::fs::create_dir(&sub).unwrap();
+        std::fs::write(sub.join("CLAUDE.md"), "Run tests with cargo nextest").unwrap();
+        std::fs::create_dir(sub.join(".claude")).unwrap();
+        std::fs::write(
+            sub.join(".claude").join("settings.json"),
+            r#"{"customModes": {"review": {"name": "Review", "baseMode": "plan"}}}"#,
+        )
+        .unwrap();
+        let meta = NewSessionMeta::from_request_meta(Some(&serde_json::json!({
+            "allowedTools": ["Bash(cargo test:*)"]
+        })));
+        let session = Session::new(
+            "test-set-cwd".to_string(),
+            temp_dir.path().to_path_buf(),
+            &test_config(),
+            Some(&meta),
+        )
+        .unwrap();
+        session.acp_mcp_server.set_session_id("test-set-cwd");
+        session.acp_mcp_server.set_cwd(session.cwd());
+
+        // Missing directories and files are rejected
+        assert!(session.set_cwd(Path::new("missing")).await.is_err());
+        std::fs::write(temp_dir.path().join("file.txt"), "").unwrap();
+        assert!(session.set_cwd(Path::new("file.txt")).await.is_err());
+        assert_eq!(session.cwd(), temp_dir.path());
+        assert!(session.custom_mode("review").is_none());
+
+        let cwd = session.set_cwd(Path::new("sub")).await.unwrap();
+        assert_eq!(cwd, sub.canonicalize().unwrap());
+        assert_eq!(session.cwd(), cwd);
+        assert_eq!(session.info().cwd, cwd.display().to_string());
+
+        // Settings are discovered again from the new directory
+        assert!(session.custom_mode("review").is_some());
+
+        // Relative paths of later tool calls resolve against the new directory
+        let result = session
+            .acp_mcp_server
+            .execute_tool(
+                "Write",
+                serde_json::json!({"file_path": "notes.txt", "content": "moved"}),
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(!result.is_error, "{}", result.content);
+        assert_eq!(
+            std::fs::read_to_string(sub.join("notes.txt")).unwrap(),
+            "moved"
+        );
+        assert!(!temp_dir.path().join("notes.txt").exists());
+
+        // Session rules survive the settings reload
+        assert_eq!(
+            session
+                .permission()
+                .await
+                .check_permission("Bash", &serde_json::json!({"command": "cargo test"}))
+                .await,
+            ToolPermissionResult::Allowed
+        );
+
+        // The next prompt learns about the change and the new project memory
+        let notice = session.take_cwd_notice().unwrap();
+        assert!(notice.contains(&cwd.display().to_string()));
+        assert!(notice.contains("Run tests with cargo nextest"));
+        assert_eq!(session.take_cwd_notice(), None);
+    }
+
+    #[test]
+    fn test_session_title() {
+        let session = Session::new(
+            "test-title-session".to_string(),
+            PathBuf::from("/tmp"),
+            &test_config(),
+            None,
+        )
+        .unwrap();
+
+        assert_eq!(session.title(), None);
+
+        // The first prompt provides a title when none is set
+        session.set_title_from_prompt("\n  Fix the   login bug\nin auth.rs");
+        assert_eq!(session.title().as_deref(), Some("Fix the login bug"));
+
+        // An explicit title replaces it and is not overwritten by later prompts
+        session.set_title("Auth refactor");
+        session.set_title_from_prompt("Something else");
+        assert_eq!(session.title().as_deref(), Some("Auth refactor"));
+        assert_eq!(session.info().title.as_deref(), Some("Auth refactor"));
+
+        // Long titles are capped
+        let long = "x".repeat(200);
+        let stored = session.set_title(&long).unwrap();
+        assert_eq!(stored.chars().count(), MAX_TITLE_LENGTH);
+        assert!(stored.ends_with('…'));
+
+        // Empty titles clear it
+        assert_eq!(session.set_title("   "), None);
+        assert_eq!(session.title(), None);
+    }
+
+    #[test]
+    fn test_session_export_plan() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let plans_dir = temp_dir.path().join("plans");
+        let session = Session::new(
+            "test-export-session".to_string(),
+            PathBuf::from("/tmp"),
+            &test_config(),
+            None,
+        )
+        .unwrap();
+
+        // Nothing to export yet
+        assert!(session.export_plan(&plans_dir, None).is_err());
+
+        let todo = |content: &str, status: &str| TodoItem {
+            content: content.to_string(),
+            status: status.to_string(),
+            active_form: String::new(),
+            priority: None,
+        };
+        session.todos.update(vec![
+            todo("Add the setting", "completed"),
+            todo("Wire it up", "in_progress"),
+            todo("Write tests", "pending"),
+        ]);
+        session.set_title("Config work");
+
+        let path = session.export_plan(&plans_dir, None).unwrap();
+        assert_eq!(path, plans_dir.join("plan-test-export-session.md"));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "# Config work\n\n\
+             - [x] Add the setting\n\
+             - [ ] Wire it up *(in progress)*\n\
+             - [ ] Write tests\n"
+        );
+
+        let path = session.export_plan(&plans_dir, Some("config")).unwrap();
+        assert_eq!(path, plans_dir.join("config.md"));
+        assert!(session.export_plan(&plans_dir, Some("../config")).is_err());
+    }
+
+    #[test]
+    fn test_session_internal_model() {
+        let config = AgentConfig {
+            model: Some("claude-opus-4-1".to_string()),
+            small_fast_model: Some("claude-haiku-4-5".to_string()),
+            ..Default::default()
+        };
+        let session = Session::new(
+            "test-internal-model".to_string(),
+            PathBuf::from("/tmp"),
+            &config,
+            None,
+        )
+        .unwrap();
+        assert_eq!(session.internal_model(), Some("claude-haiku-4-5"));
+
+        // Without a separate fast model there is nothing to switch to
+        let config = AgentConfig {
+            small_fast_model: None,
+            ..config
+        };
+        let session = Session::new(
+            "test-internal-model-fallback".to_string(),
+            PathBuf::from("/tmp"),
+            &config,
+            None,
+        )
+        .unwrap();
+        assert_eq!(session.internal_model(), None);
+    }
+
     #[test]
     fn test_cancelled_flag_lifecycle() {
         let session = Session::new(
@@ -1301,6 +2485,46 @@ mod tests {
         assert_eq!(session.permission_mode().await, PermissionMode::DontAsk);
     }
 
+    #[tokio::test]
+    async fn test_approve_plan_keeps_the_selected_mode() {
+        let config = AgentConfig {
+            accept_edits_after_plan: true,
+            ..test_config()
+        };
+        let session = Session::new(
+            "plan-approval".to_string(),
+            PathBuf::from("/tmp"),
+            &config,
+            None,
+        )
+        .unwrap();
+        assert!(session.accept_edits_after_plan());
+        session.set_permission_mode(PermissionMode::Plan).await;
+
+        // The setting only preselects an option; the user's choice is kept
+        let mode = session.approve_plan(PermissionMode::Default).await;
+        assert_eq!(mode, PermissionMode::Default);
+        assert_eq!(session.permission_mode().await, PermissionMode::Default);
+
+        session.set_permission_mode(PermissionMode::Plan).await;
+        let mode = session.approve_plan(PermissionMode::AcceptEdits).await;
+        assert_eq!(session.permission_mode().await, PermissionMode::AcceptEdits);
+        let notification = session.mode_update_notification(mode.as_str());
+        let SessionUpdate::CurrentModeUpdate(update) = &notification.update else {
+            panic!("expected a mode update, got {:?}", notification.update);
+        };
+        assert_eq!(&*update.current_mode_id.0, "acceptEdits");
+
+        // An explicit choice other than Default is kept
+        session.set_permission_mode(PermissionMode::Plan).await;
+        assert_eq!(
+            session
+                .approve_plan(PermissionMode::BypassPermissions)
+                .await,
+            PermissionMode::BypassPermissions
+        );
+    }
+
     #[test]
     fn test_stable_cache_key_ordering() {
         use serde_json::json;
@@ -0,0 +1,227 @@
+//! Persisted per-session state for resuming
+//!
+//! The SDK restores a resumed session's conversation, but not the agent's
+//! own state. The session index keeps what the agent needs to pick up where
+//! a session left off (currently its permission mode) in a JSON file in the
+//! user configuration directory. Sessions not updated for 90 days are
+//! dropped, and at most 1000 are kept.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::settings::user_config_dir;
+
+use super::permission::PermissionMode;
+
+/// File name of the session index in the user configuration directory
+const SESSION_INDEX_FILE: &str = "acp-sessions.json";
+
+/// Sessions not updated for longer than this are dropped from the index
+const MAX_SESSION_AGE: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+
+/// Most sessions the index keeps; the least recently updated go first
+const MAX_SESSIONS: usize = 1000;
+
+/// What the index remembers about one session
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionIndexEntry {
+    /// Permission mode the session was last in
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permission_mode: Option<PermissionMode>,
+    /// When the entry was last updated, in seconds since the Unix epoch
+    #[serde(default)]
+    pub updated_at: u64,
+}
+
+/// On-disk layout of the index
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct SessionIndexFile {
+    #[serde(default)]
+    sessions: BTreeMap<String, SessionIndexEntry>,
+}
+
+/// Index of sessions that can be resumed, keyed by session ID
+#[derive(Debug)]
+pub struct SessionIndex {
+    /// Path of the index file
+    path: PathBuf,
+    /// Serializes read-modify-write updates within this process
+    lock: Mutex<()>,
+}
+
+impl SessionIndex {
+    /// Create an index stored at `path`
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            lock: Mutex::new(()),
+        }
+    }
+
+    /// The index in the user configuration directory (`~/.claude/acp-sessions.json`)
+    pub fn user() -> Option<Self> {
+        user_config_dir().map(|dir| Self::new(dir.join(SESSION_INDEX_FILE)))
+    }
+
+    /// Path of the index file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Entry recorded for `session_id`, if any
+    pub fn get(&self, session_id: &str) -> Option<SessionIndexEntry> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        self.load().sessions.remove(session_id)
+    }
+
+    /// Permission mode recorded for `session_id`, if any
+    pub fn permission_mode(&self, session_id: &str) -> Option<PermissionMode> {
+        self.get(session_id)?.permission_mode
+    }
+
+    /// Record the permission mode of `session_id`
+    pub fn record_permission_mode(&self, session_id: &str, mode: PermissionMode) -> io::Result<()> {
+        let _guard = self
+            .lock
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let mut index = self.load();
+        let entry = index.sessions.entry(session_id.to_string()).or_default();
+        if entry.permission_mode == Some(mode) {
+            return Ok(());
+        }
+        entry.permission_mode = Some(mode);
+        entry.updated_at = unix_time(SystemTime::now());
+        index.prune(SystemTime::now());
+        self.store(&index)
+    }
+
+    /// Read the index file; a missing or unreadable file is an empty index
+    fn load(&self) -> SessionIndexFile {
+        let Ok(content) = std::fs::read_to_string(&self.path) else {
+            return SessionIndexFile::default();
+        };
+        serde_json::from_str(&content).unwrap_or_else(|e| {
+            tracing::warn!(
+                path = %self.path.display(),
+                error = %e,
+                "Ignoring malformed session index"
+            );
+            SessionIndexFile::default()
+        })
+    }
+
+    /// Write the index file, replacing it atomically
+    ///
+    /// The temporary file has a unique name, so agents in other processes
+    /// writing the index at the same time don't write into each other's.
+    fn store(&self, index: &SessionIndexFile) -> io::Result<()> {
+        let dir = self.path.parent().unwrap_or_else(|| Path::new("."));
+        std::fs::create_dir_all(dir)?;
+        let content = serde_json::to_string_pretty(index).map_err(io::Error::other)?;
+        let tmp_path = dir.join(format!(
+            ".{SESSION_INDEX_FILE}.{}.tmp",
+            uuid::Uuid::new_v4()
+        ));
+        let result = std::fs::write(&tmp_path, content)
+            .and_then(|()| std::fs::rename(&tmp_path, &self.path));
+        if result.is_err() {
+            drop(std::fs::remove_file(&tmp_path));
+        }
+        result
+    }
+}
+
+impl SessionIndexFile {
+    /// Drop the sessions older than [`MAX_SESSION_AGE`] at `now`, and the
+    /// least recently updated ones beyond [`MAX_SESSIONS`]
+    fn prune(&mut self, now: SystemTime) {
+        let oldest = unix_time(now).saturating_sub(MAX_SESSION_AGE.as_secs());
+        self.sessions.retain(|_, entry| entry.updated_at >= oldest);
+        if self.sessions.len() > MAX_SESSIONS {
+            let mut updated: Vec<u64> = self.sessions.values().map(|e| e.updated_at).collect();
+            updated.sort_unstable_by(|a, b| b.cmp(a));
+            let cutoff = updated[MAX_SESSIONS - 1];
+            self.sessions.retain(|_, entry| entry.updated_at >= cutoff);
+        }
+    }
+}
+
+/// Seconds since the Unix epoch
+fn unix_time(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_session_index_records_permission_mode() {
+        let dir = TempDir::new().unwrap();
+        let index = SessionIndex::new(dir.path().join("nested").join(SESSION_INDEX_FILE));
+        assert_eq!(index.permission_mode("s1"), None);
+
+        index
+            .record_permission_mode("s1", PermissionMode::Plan)
+            .unwrap();
+        index
+            .record_permission_mode("s2", PermissionMode::AcceptEdits)
+            .unwrap();
+        assert_eq!(index.permission_mode("s1"), Some(PermissionMode::Plan));
+
+        // A fresh index over the same file sees the recorded modes
+        let reopened = SessionIndex::new(index.path().to_path_buf());
+        assert_eq!(reopened.permission_mode("s1"), Some(PermissionMode::Plan));
+        assert_eq!(
+            reopened.permission_mode("s2"),
+            Some(PermissionMode::AcceptEdits)
+        );
+
+        std::fs::write(index.path(), "not json").unwrap();
+        assert_eq!(index.permission_mode("s1"), None);
+
+        // No temporary file is left behind
+        let files: Vec<_> = std::fs::read_dir(index.path().parent().unwrap())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(files, [SESSION_INDEX_FILE]);
+    }
+
+    #[test]
+    fn test_session_index_prunes_old_sessions() {
+        let now = SystemTime::now();
+        let entry = |age: Duration| SessionIndexEntry {
+            permission_mode: Some(PermissionMode::Plan),
+            updated_at: unix_time(now - age),
+        };
+        let mut index = SessionIndexFile::default();
+        index
+            .sessions
+            .insert("stale".to_string(), entry(MAX_SESSION_AGE * 2));
+        for i in 0..MAX_SESSIONS + 5 {
+            index
+                .sessions
+                .insert(format!("s{i}"), entry(Duration::from_secs(i as u64 * 60)));
+        }
+
+        index.prune(now);
+        assert_eq!(index.sessions.len(), MAX_SESSIONS);
+        assert!(!index.sessions.contains_key("stale"));
+        // The least recently updated sessions are dropped first
+        assert!(index.sessions.contains_key("s0"));
+        assert!(!index.sessions.contains_key(&format!("s{}", MAX_SESSIONS)));
+    }
+}
@@ -1,10 +1,15 @@
 //! Token usage tracking for sessions
 //!
-//! Tracks cumulative token usage across a session's lifetime.
+//! Tracks cumulative token usage across a session's lifetime, and its
+//! estimated cost from the per-token prices of the model the CLI reports.
 
+use std::sync::RwLock;
 use std::sync::atomic::{AtomicU64, Ordering};
 
-use crate::types::TokenUsage;
+use crate::types::{ModelPricing, PricingTable, TokenUsage};
+
+/// Cost is accumulated in billionths of a dollar to stay exact in an integer
+const NANO_USD_PER_USD: f64 = 1e9;
 
 /// Tracks token usage across a session
 ///
@@ -19,6 +24,16 @@ pub struct UsageTracker {
     cache_read_input_tokens: AtomicU64,
     /// Total cache creation tokens
     cache_creation_input_tokens: AtomicU64,
+    /// Estimated size of the conversation context after the latest turn
+    context_tokens: AtomicU64,
+    /// Estimated cost so far, in billionths of a dollar
+    cost_nano_usd: AtomicU64,
+    /// Latest session cost the CLI reported, in billionths of a dollar
+    reported_nano_usd: AtomicU64,
+    /// Prices of models, to look up the one the CLI reports
+    pricing_table: PricingTable,
+    /// Prices of the session's current model (None if unknown)
+    pricing: RwLock<Option<ModelPricing>>,
 }
 
 impl UsageTracker {
@@ -27,6 +42,14 @@ impl UsageTracker {
         Self::default()
     }
 
+    /// Create a usage tracker looking up model prices in `pricing_table`
+    pub fn with_pricing_table(pricing_table: PricingTable) -> Self {
+        Self {
+            pricing_table,
+            ..Self::default()
+        }
+    }
+
     /// Add usage from a completed request
     pub fn add(&self, usage: &TokenUsage) {
         self.input_tokens
@@ -54,12 +77,98 @@ impl UsageTracker {
         }
     }
 
+    /// Price usage at the prices of `model` (e.g. the model the CLI reports)
+    pub fn set_model(&self, model: &str) {
+        self.set_pricing(self.pricing_table.for_model(model));
+    }
+
+    /// Set the prices applied to usage added with [`Self::add_cost`]
+    pub fn set_pricing(&self, pricing: Option<ModelPricing>) {
+        *self
+            .pricing
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = pricing;
+    }
+
+    /// Add the cost of `usage` at the current model's prices
+    ///
+    /// Does nothing when the model's prices are unknown. Returns whether
+    /// the cost was added.
+    pub fn add_cost(&self, usage: &TokenUsage) -> bool {
+        let pricing = *self
+            .pricing
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        let Some(pricing) = pricing else {
+            return false;
+        };
+        self.cost_nano_usd
+            .fetch_add(to_nano_usd(pricing.cost(usage)), Ordering::Relaxed);
+        true
+    }
+
+    /// Add the cost of a turn from the CLI's result
+    ///
+    /// `usage` is priced at the current model's prices. When those are
+    /// unknown, the increase of `reported_total_usd` (the CLI's running
+    /// total for the session) is added instead; a total lower than the
+    /// previous one means the CLI restarted and counts in full.
+    pub fn add_turn_cost(&self, usage: Option<&TokenUsage>, reported_total_usd: Option<f64>) {
+        let reported = reported_total_usd.map(|total| {
+            let total = to_nano_usd(total);
+            let previous = self.reported_nano_usd.swap(total, Ordering::Relaxed);
+            if total >= previous {
+                total - previous
+            } else {
+                total
+            }
+        });
+        let priced = usage.is_some_and(|usage| self.add_cost(usage));
+        if !priced && let Some(reported) = reported {
+            self.cost_nano_usd.fetch_add(reported, Ordering::Relaxed);
+        }
+    }
+
+    /// Get the estimated cost so far in USD
+    pub fn cost_usd(&self) -> f64 {
+        #[allow(clippy::cast_precision_loss)]
+        let nano_usd = self.cost_nano_usd.load(Ordering::Relaxed) as f64;
+        nano_usd / NANO_USD_PER_USD
+    }
+
     /// Reset usage counters
     pub fn reset(&self) {
         self.input_tokens.store(0, Ordering::Relaxed);
         self.output_tokens.store(0, Ordering::Relaxed);
         self.cache_read_input_tokens.store(0, Ordering::Relaxed);
         self.cache_creation_input_tokens.store(0, Ordering::Relaxed);
+        self.context_tokens.store(0, Ordering::Relaxed);
+        self.cost_nano_usd.store(0, Ordering::Relaxed);
+        self.reported_nano_usd.store(0, Ordering::Relaxed);
+    }
+
+    /// Record the usage of the latest model call as the current context size
+    ///
+    /// Everything the model read (including cached prompt tokens) plus what it
+    /// wrote is carried into the next call, so their sum estimates the context.
+    /// A turn's total usage counts the prompt once per call, so it must not be
+    /// recorded here.
+    pub fn record_context(&self, usage: &TokenUsage) {
+        let tokens = usage.input_tokens
+            + usage.output_tokens
+            + usage.cache_read_input_tokens.unwrap_or(0)
+            + usage.cache_creation_input_tokens.unwrap_or(0);
+        self.context_tokens.store(tokens, Ordering::Relaxed);
+    }
+
+    /// Get the estimated context size in tokens
+    pub fn context_tokens(&self) -> u64 {
+        self.context_tokens.load(Ordering::Relaxed)
+    }
+
+    /// Forget the context estimate (e.g. after the conversation was compacted)
+    pub fn reset_context(&self) {
+        self.context_tokens.store(0, Ordering::Relaxed);
     }
 
     /// Get input tokens
@@ -78,6 +187,13 @@ impl UsageTracker {
     }
 }
 
+/// `usd` in billionths of a dollar
+fn to_nano_usd(usd: f64) -> u64 {
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    let nano_usd = (usd * NANO_USD_PER_USD).round() as u64;
+    nano_usd
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -145,4 +261,79 @@ mod tests {
         assert_eq!(total.input_tokens, 0);
         assert_eq!(total.output_tokens, 0);
     }
+
+    #[test]
+    fn test_usage_tracker_cost() {
+        let tracker = UsageTracker::new();
+        let usage = TokenUsage {
+            input_tokens: 10_000,
+            output_tokens: 2_000,
+            cache_read_input_tokens: Some(100_000),
+            cache_creation_input_tokens: Some(4_000),
+        };
+
+        // Unknown prices don't add to the cost
+        assert!(!tracker.add_cost(&usage));
+        assert!(tracker.cost_usd().abs() < f64::EPSILON);
+
+        // Sonnet: $3/$15 per million, cache reads $0.30, cache writes $3.75
+        tracker.set_model("claude-sonnet-4-5");
+        assert!(tracker.add_cost(&usage));
+        tracker.add_cost(&usage);
+        assert!((tracker.cost_usd() - 2.0 * 0.105).abs() < 1e-9);
+
+        tracker.reset();
+        assert!(tracker.cost_usd().abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_usage_tracker_turn_cost() {
+        let tracker = UsageTracker::with_pricing_table(PricingTable::default());
+        let usage = TokenUsage {
+            input_tokens: 10_000,
+            output_tokens: 2_000,
+            cache_read_input_tokens: Some(100_000),
+            cache_creation_input_tokens: Some(4_000),
+        };
+
+        // A model without a price falls back to the CLI's reported total
+        tracker.set_model("some-local-model");
+        tracker.add_turn_cost(Some(&usage), Some(0.5));
+        tracker.add_turn_cost(Some(&usage), Some(0.75));
+        assert!((tracker.cost_usd() - 0.75).abs() < 1e-9);
+
+        // The model the CLI reports is priced, its total is only tracked
+        tracker.set_model("claude-sonnet-4-5-20250929");
+        tracker.add_turn_cost(Some(&usage), Some(2.0));
+        assert!((tracker.cost_usd() - 0.855).abs() < 1e-9);
+
+        // A restarted CLI counts from zero again
+        tracker.set_model("some-local-model");
+        tracker.add_turn_cost(None, Some(0.1));
+        assert!((tracker.cost_usd() - 0.955).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_usage_tracker_context() {
+        let tracker = UsageTracker::new();
+        tracker.record_context(&TokenUsage {
+            input_tokens: 100,
+            output_tokens: 50,
+            cache_read_input_tokens: Some(1000),
+            cache_creation_input_tokens: None,
+        });
+        assert_eq!(tracker.context_tokens(), 1150);
+
+        // The latest turn replaces the estimate rather than adding to it
+        tracker.record_context(&TokenUsage {
+            input_tokens: 10,
+            output_tokens: 5,
+            cache_read_input_tokens: Some(1200),
+            cache_creation_input_tokens: Some(20),
+        });
+        assert_eq!(tracker.context_tokens(), 1235);
+
+        tracker.reset_context();
+        assert_eq!(tracker.context_tokens(), 0);
+    }
 }
@@ -3,12 +3,17 @@
 //! Handles loading, merging, and accessing settings from multiple sources.
 
 use std::collections::HashMap;
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 
 use serde::{Deserialize, Serialize};
 
-use super::rule::PermissionSettings;
-use crate::types::Result;
+use super::rule::{PermissionDecision, PermissionRule, PermissionSettings};
+use crate::converter::ThinkingTriggers;
+use crate::mcp::{FinalNewlinePolicy, InteractiveInputMode};
+use crate::permissions::PermissionFallback;
+use crate::types::{ModelPricing, Result};
 
 /// Settings file names
 const USER_SETTINGS_DIR: &str = ".claude";
@@ -16,6 +21,54 @@ const PROJECT_SETTINGS_DIR: &str = ".claude";
 const SETTINGS_FILE: &str = "settings.json";
 const LOCAL_SETTINGS_FILE: &str = "settings.local.json";
 
+/// Environment variable that moves the user configuration directory
+pub const CONFIG_DIR_ENV: &str = "CLAUDE_CONFIG_DIR";
+
+/// User configuration directory given with `--config-dir`
+static CONFIG_DIR_OVERRIDE: OnceLock<PathBuf> = OnceLock::new();
+
+/// Use `dir` as the user configuration directory instead of `~/.claude`
+///
+/// Set once at startup from `--config-dir`; later calls are ignored.
+pub fn set_user_config_dir(dir: PathBuf) {
+    if CONFIG_DIR_OVERRIDE.set(dir).is_err() {
+        tracing::warn!("User configuration directory already set, ignoring");
+    }
+}
+
+/// User configuration directory, when it was moved from `~/.claude`
+///
+/// `--config-dir` takes priority over `CLAUDE_CONFIG_DIR`.
+pub fn configured_user_config_dir() -> Option<PathBuf> {
+    resolve_user_config_dir(
+        CONFIG_DIR_OVERRIDE.get().map(PathBuf::as_path),
+        std::env::var_os(CONFIG_DIR_ENV),
+        None,
+    )
+}
+
+/// User configuration directory: settings, plans and memory live here
+///
+/// `--config-dir`, then `CLAUDE_CONFIG_DIR`, then `~/.claude`.
+pub fn user_config_dir() -> Option<PathBuf> {
+    resolve_user_config_dir(
+        CONFIG_DIR_OVERRIDE.get().map(PathBuf::as_path),
+        std::env::var_os(CONFIG_DIR_ENV),
+        dirs::home_dir(),
+    )
+}
+
+/// First of the `--config-dir` flag, the environment variable and `~/.claude`
+fn resolve_user_config_dir(
+    flag: Option<&Path>,
+    env: Option<OsString>,
+    home: Option<PathBuf>,
+) -> Option<PathBuf> {
+    flag.map(Path::to_path_buf)
+        .or_else(|| env.filter(|dir| !dir.is_empty()).map(PathBuf::from))
+        .or_else(|| home.map(|home| home.join(USER_SETTINGS_DIR)))
+}
+
 /// Claude Code settings structure
 ///
 /// This mirrors the settings structure used by Claude Code.
@@ -47,6 +100,156 @@ pub struct Settings {
     #[serde(default)]
     pub always_thinking_enabled: Option<bool>,
 
+    /// Generate a session title with the small/fast model after the first turn
+    #[serde(default)]
+    pub auto_generate_title: Option<bool>,
+
+    /// Compact the conversation automatically when the context nears its limit
+    #[serde(default)]
+    pub auto_compact_enabled: Option<bool>,
+
+    /// Context window override in tokens (defaults to the model's known window)
+    #[serde(default)]
+    pub context_window: Option<u64>,
+
+    /// Title Bash tool calls without a description with one synthesized from the command
+    #[serde(default)]
+    pub synthesize_bash_description: Option<bool>,
+
+    /// What to do when a Bash command appears to wait for input
+    /// (`eof`, `decline`, `report` or `off`)
+    #[serde(default)]
+    pub bash_interactive_input: Option<InteractiveInputMode>,
+
+    /// Milliseconds without output before a Bash command is considered to wait for input
+    #[serde(default)]
+    pub bash_input_grace_ms: Option<u64>,
+
+    /// Maximum characters per line of Read, Grep and Bash output
+    #[serde(default)]
+    pub max_line_length: Option<usize>,
+
+    /// Maximum lines returned by Read when no offset/limit is given
+    #[serde(default)]
+    pub read_max_lines: Option<usize>,
+
+    /// Largest file in bytes Read reads and Write writes (0 = unlimited)
+    #[serde(default)]
+    pub max_file_size: Option<u64>,
+
+    /// Milliseconds a built-in tool may run before it is stopped (0 = unlimited)
+    #[serde(default)]
+    pub tool_timeout_ms: Option<u64>,
+
+    /// Per-tool timeouts in milliseconds by tool name (e.g. `Bash`,
+    /// `mcp__server__tool`), overriding `toolTimeoutMs` and the defaults
+    #[serde(default)]
+    pub tool_timeouts: Option<HashMap<String, u64>>,
+
+    /// Maximum serialized size of a tool call's arguments in bytes (0 = unlimited)
+    #[serde(default)]
+    pub max_tool_argument_bytes: Option<usize>,
+
+    /// Maximum bytes of Bash output returned to the model (0 = unlimited)
+    #[serde(default)]
+    pub bash_output_limit_bytes: Option<usize>,
+
+    /// Percentage of a Bash timeout after which the client is warned (0 = never)
+    #[serde(default)]
+    pub bash_timeout_warning_percent: Option<u8>,
+
+    /// Merge Bash stdout and stderr in the order they're written (default true)
+    #[serde(default)]
+    pub combine_streams: Option<bool>,
+
+    /// Directory Plan mode may write plans to (default `~/.claude/plans`)
+    ///
+    /// Only honored in user settings.
+    #[serde(default)]
+    pub plans_dir: Option<String>,
+
+    /// Preselect "auto-accept edits" when a plan is approved
+    ///
+    /// Only honored in user settings.
+    #[serde(default)]
+    pub accept_edits_after_plan: Option<bool>,
+
+    /// Approve Read/Edit/Write/MultiEdit of files inside the working
+    /// directory in Default mode
+    #[serde(default)]
+    pub auto_approve_within_cwd: Option<bool>,
+
+    /// JSONL file every permission decision is appended to
+    #[serde(default)]
+    pub permission_audit_log: Option<String>,
+
+    /// Seconds the client has to answer a permission request (0 = no limit)
+    #[serde(default)]
+    pub permission_request_timeout_secs: Option<u64>,
+
+    /// How calls needing permission are decided when the client can't be
+    /// asked (`deny` or `rules`)
+    #[serde(default)]
+    pub permission_fallback: Option<PermissionFallback>,
+
+    /// Re-read files after Write and Edit to verify their content
+    #[serde(default)]
+    pub verify_writes: Option<bool>,
+
+    /// Use ripgrep for Grep when available (false forces the built-in search)
+    #[serde(default)]
+    pub prefer_ripgrep: Option<bool>,
+
+    /// Unchanged lines shown around each change in Edit diffs (unset shows all)
+    #[serde(default)]
+    pub diff_context_lines: Option<usize>,
+
+    /// Thinking budgets for "think"/"think harder"/"ultrathink" prompts
+    #[serde(default)]
+    pub thinking_triggers: Option<ThinkingTriggers>,
+
+    /// Version commands and environment variables reported by EnvInfo
+    #[serde(default)]
+    pub env_info: Option<EnvInfoSettings>,
+
+    /// Let WebFetch fetch loopback and private network addresses
+    #[serde(default)]
+    pub allow_localhost_fetch: Option<bool>,
+
+    /// Hosts WebFetch may fetch (`*.example.com` for subdomains)
+    #[serde(default)]
+    pub web_fetch_allowed_domains: Option<Vec<String>>,
+
+    /// Hosts WebFetch may never fetch, even if allowed
+    #[serde(default)]
+    pub web_fetch_denied_domains: Option<Vec<String>>,
+
+    /// Domains WebSearch may return results from (`*.example.com` for subdomains)
+    #[serde(default)]
+    pub web_search_allowed_domains: Option<Vec<String>>,
+
+    /// Domains WebSearch may never return results from, even if allowed
+    #[serde(default)]
+    pub web_search_denied_domains: Option<Vec<String>>,
+
+    /// Seconds a background Bash command may run before it is killed (0 = unlimited)
+    #[serde(default)]
+    pub background_max_lifetime_secs: Option<u64>,
+
+    /// Bytes of output kept per background Bash command (0 = unlimited)
+    #[serde(default)]
+    pub background_output_limit_bytes: Option<usize>,
+
+    /// Prices in USD per million tokens by model ID (or part of it), for
+    /// session cost estimates
+    #[serde(default)]
+    pub model_pricing: Option<HashMap<String, ModelPricing>>,
+
+    /// Report whether files end with a newline after Write and Edit
+    /// (`off`, `report` or `warn`)
+    #[serde(default)]
+    pub final_newline: Option<FinalNewlinePolicy>,
+
     /// Allowed tools list (legacy, use permissions instead)
     #[serde(default)]
     pub allowed_tools: Option<Vec<String>>,
@@ -59,6 +262,10 @@ pub struct Settings {
     #[serde(default)]
     pub permissions: Option<PermissionSettings>,
 
+    /// Custom permission modes by mode ID, selectable like the built-in modes
+    #[serde(default)]
+    pub custom_modes: Option<HashMap<String, CustomModeSettings>>,
+
     /// MCP servers configuration
     #[serde(default)]
     pub mcp_servers: Option<HashMap<String, McpServerConfig>>,
@@ -92,6 +299,56 @@ pub struct McpServerConfig {
     pub disabled: bool,
 }
 
+/// Custom permission mode configuration
+///
+/// Rules use the same syntax as `permissions`. Tool calls no rule matches
+/// are handled by the base mode.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CustomModeSettings {
+    /// Display name (default: the mode ID)
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// Description shown in the mode list
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// Built-in mode the custom mode builds on (default: `default`)
+    #[serde(default)]
+    pub base_mode: Option<String>,
+
+    /// Rules that allow tool execution
+    #[serde(default)]
+    pub allow: Option<Vec<PermissionRule>>,
+
+    /// Rules that deny tool execution
+    #[serde(default)]
+    pub deny: Option<Vec<PermissionRule>>,
+
+    /// Rules that require asking the user
+    #[serde(default)]
+    pub ask: Option<Vec<PermissionRule>>,
+}
+
+/// EnvInfo configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvInfoSettings {
+    /// Version commands by tool name, added to the defaults (an empty
+    /// command removes a default one)
+    ///
+    /// Only honored in user settings, as EnvInfo runs them without asking.
+    #[serde(default)]
+    pub commands: Option<HashMap<String, String>>,
+
+    /// Environment variables reported in addition to the defaults
+    ///
+    /// Only honored in user settings, as their values reach the model.
+    #[serde(default)]
+    pub env_vars: Option<Vec<String>>,
+}
+
 impl Settings {
     /// Create empty settings
     pub fn new() -> Self {
@@ -120,6 +377,117 @@ impl Settings {
         if other.always_thinking_enabled.is_some() {
             self.always_thinking_enabled = other.always_thinking_enabled;
         }
+        if other.auto_generate_title.is_some() {
+            self.auto_generate_title = other.auto_generate_title;
+        }
+        if other.auto_compact_enabled.is_some() {
+            self.auto_compact_enabled = other.auto_compact_enabled;
+        }
+        if other.context_window.is_some() {
+            self.context_window = other.context_window;
+        }
+        if other.synthesize_bash_description.is_some() {
+            self.synthesize_bash_description = other.synthesize_bash_description;
+        }
+        if other.bash_interactive_input.is_some() {
+            self.bash_interactive_input = other.bash_interactive_input;
+        }
+        if other.bash_input_grace_ms.is_some() {
+            self.bash_input_grace_ms = other.bash_input_grace_ms;
+        }
+        if other.max_line_length.is_some() {
+            self.max_line_length = other.max_line_length;
+        }
+        if other.read_max_lines.is_some() {
+            self.read_max_lines = other.read_max_lines;
+        }
+        if other.max_file_size.is_some() {
+            self.max_file_size = other.max_file_size;
+        }
+        if other.tool_timeout_ms.is_some() {
+            self.tool_timeout_ms = other.tool_timeout_ms;
+        }
+        if other.tool_timeouts.is_some() {
+            self.tool_timeouts = other.tool_timeouts;
+        }
+        if other.max_tool_argument_bytes.is_some() {
+            self.max_tool_argument_bytes = other.max_tool_argument_bytes;
+        }
+        if other.bash_output_limit_bytes.is_some() {
+            self.bash_output_limit_bytes = other.bash_output_limit_bytes;
+        }
+        if other.bash_timeout_warning_percent.is_some() {
+            self.bash_timeout_warning_percent = other.bash_timeout_warning_percent;
+        }
+        if other.combine_streams.is_some() {
+            self.combine_streams = other.combine_streams;
+        }
+        if other.plans_dir.is_some() {
+            self.plans_dir = other.plans_dir;
+        }
+        if other.accept_edits_after_plan.is_some() {
+            self.accept_edits_after_plan = other.accept_edits_after_plan;
+        }
+        if other.auto_approve_within_cwd.is_some() {
+            self.auto_approve_within_cwd = other.auto_approve_within_cwd;
+        }
+        if other.permission_audit_log.is_some() {
+            self.permission_audit_log = other.permission_audit_log;
+        }
+        if other.permission_request_timeout_secs.is_some() {
+            self.permission_request_timeout_secs = other.permission_request_timeout_secs;
+        }
+        if other.permission_fallback.is_some() {
+            self.permission_fallback = other.permission_fallback;
+        }
+        if other.verify_writes.is_some() {
+            self.verify_writes = other.verify_writes;
+        }
+        if other.prefer_ripgrep.is_some() {
+            self.prefer_ripgrep = other.prefer_ripgrep;
+        }
+        if other.diff_context_lines.is_some() {
+            self.diff_context_lines = other.diff_context_lines;
+        }
+        if other.thinking_triggers.is_some() {
+            self.thinking_triggers = other.thinking_triggers;
+        }
+        if let Some(other_env_info) = other.env_info {
+            let env_info = self.env_info.get_or_insert_with(EnvInfoSettings::default);
+            if other_env_info.commands.is_some() {
+                env_info.commands = other_env_info.commands;
+            }
+            if other_env_info.env_vars.is_some() {
+                env_info.env_vars = other_env_info.env_vars;
+            }
+        }
+        if other.allow_localhost_fetch.is_some() {
+            self.allow_localhost_fetch = other.allow_localhost_fetch;
+        }
+        if other.web_fetch_allowed_domains.is_some() {
+            self.web_fetch_allowed_domains = other.web_fetch_allowed_domains;
+        }
+        if other.web_fetch_denied_domains.is_some() {
+            self.web_fetch_denied_domains = other.web_fetch_denied_domains;
+        }
+        if other.web_search_allowed_domains.is_some() {
+            self.web_search_allowed_domains = other.web_search_allowed_domains;
+        }
+        if other.web_search_denied_domains.is_some() {
+            self.web_search_denied_domains = other.web_search_denied_domains;
+        }
+        if other.background_max_lifetime_secs.is_some() {
+            self.background_max_lifetime_secs = other.background_max_lifetime_secs;
+        }
+        if other.background_output_limit_bytes.is_some() {
+            self.background_output_limit_bytes = other.background_output_limit_bytes;
+        }
+        if other.model_pricing.is_some() {
+            self.model_pricing = other.model_pricing;
+        }
+        if other.final_newline.is_some() {
+            self.final_newline = other.final_newline;
+        }
         if other.allowed_tools.is_some() {
             self.allowed_tools = other.allowed_tools;
         }
@@ -153,6 +521,16 @@ impl Settings {
             if other_perms.default_mode.is_some() {
                 perms.default_mode = other_perms.default_mode;
             }
+            // A rule in several files matches at its first position, so
+            // it keeps the file it was first loaded from
+            for (key, path) in other_perms.rule_files {
+                perms.rule_files.entry(key).or_insert(path);
+            }
+        }
+        if let Some(other_modes) = other.custom_modes {
+            // Merge custom modes (a later definition replaces the whole mode)
+            let modes = self.custom_modes.get_or_insert_with(HashMap::new);
+            modes.extend(other_modes);
         }
         if other.mcp_servers.is_some() {
             // Merge MCP servers
@@ -198,7 +576,7 @@ impl SettingsManager {
     /// * `project_dir` - The project working directory
     pub fn new(project_dir: impl AsRef<Path>) -> Result<Self> {
         let project_dir = project_dir.as_ref().to_path_buf();
-        let settings = Self::load_all_settings(&project_dir);
+        let settings = Self::load_all_settings(user_config_dir().as_deref(), &project_dir);
 
         Ok(Self {
             settings,
@@ -224,11 +602,11 @@ impl SettingsManager {
     /// Load and merge all settings sources
     ///
     /// Priority: Local > Project > User
-    fn load_all_settings(project_dir: &Path) -> Settings {
+    fn load_all_settings(user_dir: Option<&Path>, project_dir: &Path) -> Settings {
         let mut settings = Settings::new();
 
         // 1. Load user settings (~/.claude/settings.json)
-        if let Some(user_settings) = Self::load_user_settings() {
+        if let Some(user_settings) = user_dir.and_then(Self::load_user_settings) {
             tracing::debug!("Loaded user settings");
             settings.merge(user_settings);
         }
@@ -248,17 +626,15 @@ impl SettingsManager {
         settings
     }
 
-    /// Load user settings from ~/.claude/settings.json
-    fn load_user_settings() -> Option<Settings> {
-        let home = dirs::home_dir()?;
-        let path = home.join(USER_SETTINGS_DIR).join(SETTINGS_FILE);
-        Self::load_settings_file(&path)
+    /// Load user settings from ~/.claude/settings.json (or the configured directory)
+    fn load_user_settings(user_dir: &Path) -> Option<Settings> {
+        Self::load_settings_file(&user_dir.join(SETTINGS_FILE))
     }
 
     /// Load project settings from .claude/settings.json
     fn load_project_settings(project_dir: &Path) -> Option<Settings> {
         let path = project_dir.join(PROJECT_SETTINGS_DIR).join(SETTINGS_FILE);
-        Self::load_settings_file(&path)
+        Self::load_settings_file(&path).map(|settings| Self::without_user_only(settings, &path))
     }
 
     /// Load local settings from .claude/settings.local.json
@@ -266,7 +642,44 @@ impl SettingsManager {
         let path = project_dir
             .join(PROJECT_SETTINGS_DIR)
             .join(LOCAL_SETTINGS_FILE);
-        Self::load_settings_file(&path)
+        Self::load_settings_file(&path).map(|settings| Self::without_user_only(settings, &path))
+    }
+
+    /// Drop the settings only honored in user settings
+    ///
+    /// Project settings come with the repository, so they mustn't widen
+    /// what the agent may do without asking.
+    fn without_user_only(mut settings: Settings, path: &Path) -> Settings {
+        if settings.plans_dir.take().is_some() {
+            tracing::warn!(file = %path.display(), "Ignoring plansDir: only honored in user settings");
+        }
+        if settings.accept_edits_after_plan.take().is_some() {
+            tracing::warn!(
+                file = %path.display(),
+                "Ignoring acceptEditsAfterPlan: only honored in user settings"
+            );
+        }
+        if settings.permission_audit_log.take().is_some() {
+            tracing::warn!(
+                file = %path.display(),
+                "Ignoring permissionAuditLog: only honored in user settings"
+            );
+        }
+        if let Some(env_info) = &mut settings.env_info {
+            if env_info.commands.take().is_some() {
+                tracing::warn!(
+                    file = %path.display(),
+                    "Ignoring envInfo.commands: only honored in user settings"
+                );
+            }
+            if env_info.env_vars.take().is_some() {
+                tracing::warn!(
+                    file = %path.display(),
+                    "Ignoring envInfo.envVars: only honored in user settings"
+                );
+            }
+        }
+        settings
     }
 
     /// Load settings from a file
@@ -276,8 +689,13 @@ impl SettingsManager {
         }
 
         match std::fs::read_to_string(path) {
-            Ok(content) => match serde_json::from_str(&content) {
-                Ok(settings) => Some(settings),
+            Ok(content) => match serde_json::from_str::<Settings>(&content) {
+                Ok(mut settings) => {
+                    if let Some(permissions) = &mut settings.permissions {
+                        permissions.set_rule_file(path);
+                    }
+                    Some(settings)
+                }
                 Err(e) => {
                     tracing::warn!("Failed to parse settings file {:?}: {}", path, e);
                     None
@@ -290,6 +708,50 @@ impl SettingsManager {
         }
     }
 
+    /// Add a permission rule to the local settings (`.claude/settings.local.json`)
+    ///
+    /// The rest of the file is kept as it is, and a rule already in the
+    /// list isn't added again. Returns the path of the file.
+    pub fn add_local_permission_rule(
+        project_dir: &Path,
+        decision: PermissionDecision,
+        rule: &str,
+    ) -> std::io::Result<PathBuf> {
+        let path = project_dir
+            .join(PROJECT_SETTINGS_DIR)
+            .join(LOCAL_SETTINGS_FILE);
+        let mut settings = match std::fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).map_err(std::io::Error::other)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => serde_json::json!({}),
+            Err(e) => return Err(e),
+        };
+
+        let invalid =
+            || std::io::Error::other(format!("Unexpected settings in {}", path.display()));
+        let rules = settings
+            .as_object_mut()
+            .ok_or_else(invalid)?
+            .entry("permissions")
+            .or_insert_with(|| serde_json::json!({}))
+            .as_object_mut()
+            .ok_or_else(invalid)?
+            .entry(decision.as_str())
+            .or_insert_with(|| serde_json::json!([]))
+            .as_array_mut()
+            .ok_or_else(invalid)?;
+        if rules.iter().any(|existing| existing.as_str() == Some(rule)) {
+            return Ok(path);
+        }
+        rules.push(rule.into());
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let content = serde_json::to_string_pretty(&settings).map_err(std::io::Error::other)?;
+        std::fs::write(&path, content + "\n")?;
+        Ok(path)
+    }
+
     /// Get the merged settings
     pub fn settings(&self) -> &Settings {
         &self.settings
@@ -302,7 +764,7 @@ impl SettingsManager {
 
     /// Reload settings from all sources
     pub fn reload(&mut self) {
-        self.settings = Self::load_all_settings(&self.project_dir);
+        self.settings = Self::load_all_settings(user_config_dir().as_deref(), &self.project_dir);
     }
 
     /// Get the system prompt if configured
@@ -335,6 +797,21 @@ impl SettingsManager {
         self.settings.always_thinking_enabled.unwrap_or(false)
     }
 
+    /// Get whether session titles are generated with the small/fast model
+    pub fn auto_generate_title(&self) -> bool {
+        self.settings.auto_generate_title.unwrap_or(false)
+    }
+
+    /// Get whether conversations are compacted automatically
+    pub fn auto_compact_enabled(&self) -> bool {
+        self.settings.auto_compact_enabled.unwrap_or(false)
+    }
+
+    /// Get whether Bash descriptions are synthesized from the command
+    pub fn synthesize_bash_description(&self) -> bool {
+        self.settings.synthesize_bash_description.unwrap_or(false)
+    }
+
     /// Get MCP servers configuration
     pub fn mcp_servers(&self) -> Option<&HashMap<String, McpServerConfig>> {
         self.settings.mcp_servers.as_ref()
@@ -450,6 +927,110 @@ mod tests {
         assert_eq!(manager.project_dir(), temp_dir.path());
     }
 
+    #[test]
+    fn test_user_config_dir_override() {
+        let home = PathBuf::from("/home/user");
+        assert_eq!(
+            resolve_user_config_dir(None, None, Some(home.clone())),
+            Some(home.join(".claude"))
+        );
+        assert_eq!(
+            resolve_user_config_dir(None, Some("/tmp/env".into()), Some(home.clone())),
+            Some(PathBuf::from("/tmp/env"))
+        );
+        assert_eq!(
+            resolve_user_config_dir(
+                Some(Path::new("/tmp/flag")),
+                Some("/tmp/env".into()),
+                Some(home)
+            ),
+            Some(PathBuf::from("/tmp/flag"))
+        );
+
+        // The override directory's settings are loaded instead of the home one
+        let temp_dir = TempDir::new().unwrap();
+        let home_dir = temp_dir.path().join("home").join(".claude");
+        let config_dir = temp_dir.path().join("config");
+        std::fs::create_dir_all(&home_dir).unwrap();
+        std::fs::create_dir_all(&config_dir).unwrap();
+        std::fs::write(home_dir.join("settings.json"), r#"{"model": "home-model"}"#).unwrap();
+        std::fs::write(
+            config_dir.join("settings.json"),
+            r#"{"model": "override-model"}"#,
+        )
+        .unwrap();
+        let project_dir = temp_dir.path().join("project");
+
+        let settings = SettingsManager::load_all_settings(Some(&config_dir), &project_dir);
+        assert_eq!(settings.model.as_deref(), Some("override-model"));
+    }
+
+    #[test]
+    fn test_user_only_settings() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_dir = temp_dir.path().join("user");
+        let project_dir = temp_dir.path().join("project");
+        let settings_dir = project_dir.join(".claude");
+        std::fs::create_dir_all(&user_dir).unwrap();
+        std::fs::create_dir_all(&settings_dir).unwrap();
+        std::fs::write(
+            settings_dir.join("settings.json"),
+            r#"{
+                "model": "project-model",
+                "plansDir": "/",
+                "envInfo": {"commands": {"cargo": "sh -c evil"}, "envVars": ["CI"]}
+            }"#,
+        )
+        .unwrap();
+        std::fs::write(
+            settings_dir.join("settings.local.json"),
+            r#"{
+                "plansDir": ".",
+                "acceptEditsAfterPlan": true,
+                "permissionAuditLog": "../../.bashrc"
+            }"#,
+        )
+        .unwrap();
+
+        // Project and local settings can't set them...
+        let settings = SettingsManager::load_all_settings(Some(&user_dir), &project_dir);
+        assert_eq!(settings.model.as_deref(), Some("project-model"));
+        assert_eq!(settings.plans_dir, None);
+        assert_eq!(settings.accept_edits_after_plan, None);
+        assert_eq!(settings.permission_audit_log, None);
+        let env_info = settings.env_info.unwrap();
+        assert!(env_info.commands.is_none());
+        assert!(env_info.env_vars.is_none());
+
+        // ...user settings can
+        std::fs::write(
+            user_dir.join("settings.json"),
+            r#"{
+                "plansDir": "~/plans",
+                "acceptEditsAfterPlan": true,
+                "permissionAuditLog": "~/logs/permissions.jsonl",
+                "envInfo": {
+                    "commands": {"cargo": "cargo +nightly --version"},
+                    "envVars": ["CI"]
+                }
+            }"#,
+        )
+        .unwrap();
+        let settings = SettingsManager::load_all_settings(Some(&user_dir), &project_dir);
+        assert_eq!(settings.plans_dir.as_deref(), Some("~/plans"));
+        assert_eq!(settings.accept_edits_after_plan, Some(true));
+        assert_eq!(
+            settings.permission_audit_log.as_deref(),
+            Some("~/logs/permissions.jsonl")
+        );
+        let env_info = settings.env_info.unwrap();
+        assert_eq!(
+            env_info.commands.unwrap()["cargo"],
+            "cargo +nightly --version"
+        );
+        assert_eq!(env_info.env_vars, Some(vec!["CI".to_string()]));
+    }
+
     #[test]
     fn test_settings_manager_load_project_settings() {
         let temp_dir = TempDir::new().unwrap();
@@ -486,7 +1067,8 @@ mod tests {
             file,
             r#"{{
             "model": "claude-opus",
-            "systemPrompt": "Project prompt"
+            "systemPrompt": "Project prompt",
+            "permissions": {{ "deny": ["Bash(rm:*)"] }}
         }}"#
         )
         .unwrap();
@@ -497,7 +1079,8 @@ mod tests {
         writeln!(
             file,
             r#"{{
-            "model": "claude-sonnet"
+            "model": "claude-sonnet",
+            "permissions": {{ "deny": ["Bash(rm:*)", "Write"] }}
         }}"#
         )
         .unwrap();
@@ -508,6 +1091,21 @@ mod tests {
         assert_eq!(manager.model(), Some("claude-sonnet"));
         // System prompt from project should remain
         assert_eq!(manager.system_prompt(), Some("Project prompt"));
+
+        // Rules remember the file they were first loaded from
+        let permissions = manager.settings().permissions.as_ref().unwrap();
+        assert_eq!(
+            permissions.rule_file(PermissionDecision::Deny, "Bash(rm:*)"),
+            Some(project_settings.as_path())
+        );
+        assert_eq!(
+            permissions.rule_file(PermissionDecision::Deny, "Write"),
+            Some(local_settings.as_path())
+        );
+        assert_eq!(
+            permissions.rule_file(PermissionDecision::Allow, "Write"),
+            None
+        );
     }
 
     #[test]
@@ -542,6 +1140,62 @@ mod tests {
         assert!(!manager.is_tool_allowed("Bash"));
     }
 
+    #[test]
+    fn test_add_local_permission_rule() {
+        let temp_dir = TempDir::new().unwrap();
+
+        // Creates the file when there is none
+        let path = SettingsManager::add_local_permission_rule(
+            temp_dir.path(),
+            PermissionDecision::Deny,
+            "Bash(rm:*)",
+        )
+        .unwrap();
+        assert_eq!(
+            path,
+            temp_dir.path().join(".claude").join("settings.local.json")
+        );
+
+        // Keeps the rest of the file and doesn't add a rule twice
+        std::fs::write(
+            &path,
+            r#"{"model": "claude-sonnet", "permissions": {"deny": ["Bash(rm:*)"]}}"#,
+        )
+        .unwrap();
+        for rule in ["Bash(rm:*)", "Bash(dd:*)"] {
+            SettingsManager::add_local_permission_rule(
+                temp_dir.path(),
+                PermissionDecision::Deny,
+                rule,
+            )
+            .unwrap();
+        }
+
+        let manager = SettingsManager::new(temp_dir.path()).unwrap();
+        assert_eq!(manager.model(), Some("claude-sonnet"));
+        let permissions = manager.settings().permissions.as_ref().unwrap();
+        let deny: Vec<_> = permissions
+            .deny
+            .iter()
+            .flatten()
+            .map(PermissionRule::pattern)
+            .filter(|rule| rule.starts_with("Bash("))
+            .collect();
+        assert_eq!(deny, ["Bash(rm:*)", "Bash(dd:*)"]);
+
+        // A malformed file is left alone
+        std::fs::write(&path, "not json").unwrap();
+        assert!(
+            SettingsManager::add_local_permission_rule(
+                temp_dir.path(),
+                PermissionDecision::Deny,
+                "Write"
+            )
+            .is_err()
+        );
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "not json");
+    }
+
     #[test]
     fn test_settings_manager_reload() {
         let temp_dir = TempDir::new().unwrap();
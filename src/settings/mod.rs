@@ -6,13 +6,20 @@
 //! - Local settings: `.claude/settings.local.json`
 //!
 //! Priority: Local > Project > User
+//!
+//! The user directory moves with `--config-dir` or `CLAUDE_CONFIG_DIR`.
 
 mod manager;
 mod permission_checker;
 mod rule;
 mod watcher;
 
-pub use manager::{McpServerConfig, Settings, SettingsManager};
+pub use manager::{
+    CONFIG_DIR_ENV, CustomModeSettings, EnvInfoSettings, McpServerConfig, Settings,
+    SettingsManager, configured_user_config_dir, set_user_config_dir, user_config_dir,
+};
 pub use permission_checker::PermissionChecker;
-pub use rule::{ParsedRule, PermissionCheckResult, PermissionDecision, PermissionSettings};
+pub use rule::{
+    ParsedRule, PermissionCheckResult, PermissionDecision, PermissionRule, PermissionSettings,
+};
 pub use watcher::{SettingsChangeEvent, SettingsWatcher, WatcherError, WatcherHandle};
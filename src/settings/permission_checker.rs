@@ -5,8 +5,18 @@
 use std::path::{Path, PathBuf};
 
 use super::manager::Settings;
-use super::rule::{ParsedRule, PermissionCheckResult};
-use crate::command_safety::extract_command_basename;
+use super::rule::{ParsedRule, PermissionCheckResult, PermissionDecision, PermissionRule};
+use crate::command_safety::{extract_command_basename, split_simple_commands};
+
+/// Where a rule is defined; rules of different layers are ranked by
+/// decision alone, see [`PermissionChecker::check_permission`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RuleLayer<'a> {
+    /// A settings file, None if the settings weren't loaded from files
+    Settings(Option<&'a Path>),
+    /// Added at runtime ("Always Allow", session rules)
+    Session,
+}
 
 /// Permission checker that evaluates tool permissions against settings rules
 #[derive(Debug)]
@@ -51,13 +61,40 @@ impl PermissionChecker {
         }
     }
 
-    /// Parse a list of rule strings into ParsedRule objects
-    fn parse_rules(rules: Option<&Vec<String>>, cwd: &Path) -> Vec<(String, ParsedRule)> {
+    /// Switch to the settings of another working directory
+    ///
+    /// Rules from the old settings are replaced by those of `settings`;
+    /// rules added at runtime (session rules, "Always Allow") are kept as
+    /// they were parsed.
+    pub fn relocate(&mut self, settings: Settings, cwd: impl AsRef<Path>) {
+        // Configured rules come first, runtime rules are appended after them
+        let permissions = self.settings.permissions.clone().unwrap_or_default();
+        let count = |rules: Option<Vec<PermissionRule>>| rules.map_or(0, |rules| rules.len());
+        let allow = Self::split_runtime_rules(&mut self.allow_rules, count(permissions.allow));
+        let deny = Self::split_runtime_rules(&mut self.deny_rules, count(permissions.deny));
+        let ask = Self::split_runtime_rules(&mut self.ask_rules, count(permissions.ask));
+
+        *self = Self::new(settings, cwd);
+        self.allow_rules.extend(allow);
+        self.deny_rules.extend(deny);
+        self.ask_rules.extend(ask);
+    }
+
+    /// Remove and return the rules following the `configured` settings rules
+    fn split_runtime_rules(
+        rules: &mut Vec<(String, ParsedRule)>,
+        configured: usize,
+    ) -> Vec<(String, ParsedRule)> {
+        rules.split_off(configured.min(rules.len()))
+    }
+
+    /// Parse a list of settings rules into ParsedRule objects
+    fn parse_rules(rules: Option<&Vec<PermissionRule>>, cwd: &Path) -> Vec<(String, ParsedRule)> {
         rules
             .map(|rules| {
                 rules
                     .iter()
-                    .map(|rule| (rule.clone(), ParsedRule::parse_with_glob(rule, cwd)))
+                    .map(|rule| (rule.to_string(), ParsedRule::parse_rule(rule, cwd)))
                     .collect()
             })
             .unwrap_or_default()
@@ -65,45 +102,237 @@ impl PermissionChecker {
 
     /// Check permission for a tool invocation
     ///
-    /// Priority: deny > allow > ask
+    /// Within one layer (a settings file, or the rules added at runtime) the
+    /// most specific matching rule decides (see [`ParsedRule::specificity`]):
+    /// rules naming a tool outright beat glob patterns such as `mcp__*__Read`,
+    /// and globs with more literal characters beat those with fewer. On equal
+    /// specificity the priority is deny > allow > ask. Between layers the
+    /// priority is deny > allow > ask whatever the specificity, so a project's
+    /// allow `mcp__evil__run` can't override a user's deny `mcp__*`. Bash commands are
+    /// checked one simple command at a time (see [`Self::check_bash_command`]),
+    /// tools working on two paths one path at a time (see [`Self::check_paths`]).
     ///
-    /// Returns the permission decision and matching rule (if any).
+    /// Returns the permission decision and matching rule (if any), with
+    /// where the rule is defined.
     pub fn check_permission(
         &self,
         tool_name: &str,
         tool_input: &serde_json::Value,
     ) -> PermissionCheckResult {
-        // Check deny rules first (highest priority)
-        for (rule_str, parsed) in &self.deny_rules {
-            if parsed.matches(tool_name, tool_input, &self.cwd) {
-                tracing::debug!("Tool {} denied by rule: {}", tool_name, rule_str);
-                return PermissionCheckResult::deny(rule_str);
+        let stripped = tool_name.strip_prefix("mcp__acp__").unwrap_or(tool_name);
+        let command = tool_input.get("command").and_then(|v| v.as_str());
+        let mut result = match command {
+            Some(command) if stripped == "Bash" => {
+                self.check_bash_command(tool_name, tool_input, command)
             }
+            _ => match path_fields(stripped) {
+                Some(fields) => self.check_paths(tool_name, tool_input, fields),
+                None => self.check_rules(tool_name, tool_input),
+            },
+        };
+        result.origin = self.rule_origin(&result);
+        result
+    }
+
+    /// Check a Bash call one simple command at a time
+    ///
+    /// A command line like `git status; git push` runs several programs, and
+    /// a rule written for one of them says nothing about the others. A deny
+    /// rule matching any of the commands denies the call; the call is only
+    /// allowed if every command is allowed, and allow rules with an argument
+    /// only allow plain commands (no redirections, expansions, ...; see
+    /// [`split_simple_commands`]). A command line that can't be split is
+    /// matched as a whole and only allowed by a rule allowing every command.
+    /// Deny rules are also matched against the whole command line, so an
+    /// "Always Reject" rule for `cd build && make` denies it again.
+    fn check_bash_command(
+        &self,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+        command_line: &str,
+    ) -> PermissionCheckResult {
+        let whole = self.matching_rule(tool_name, tool_input);
+        if let Some((PermissionDecision::Deny, _, _)) = whole {
+            return Self::result_for(tool_name, whole);
         }
 
-        // Check allow rules
-        for (rule_str, parsed) in &self.allow_rules {
-            if parsed.matches(tool_name, tool_input, &self.cwd) {
-                tracing::debug!("Tool {} allowed by rule: {}", tool_name, rule_str);
-                return PermissionCheckResult::allow(rule_str);
+        let Some(commands) = split_simple_commands(command_line) else {
+            return match whole {
+                Some((PermissionDecision::Allow, _, parsed)) if parsed.argument.is_some() => {
+                    PermissionCheckResult::ask()
+                }
+                best => Self::result_for(tool_name, best),
+            };
+        };
+
+        let mut results = Vec::with_capacity(commands.len());
+        for command in &commands {
+            let mut input = tool_input.clone();
+            input["command"] = serde_json::Value::from(command.command.as_str());
+            let best = self.matching_rule(tool_name, &input);
+            if let Some((PermissionDecision::Deny, _, _)) = best {
+                return Self::result_for(tool_name, best);
+            }
+            let allowed = matches!(
+                best,
+                Some((PermissionDecision::Allow, _, parsed))
+                    if command.plain || parsed.argument.is_none()
+            );
+            results.push((allowed, Self::result_for(tool_name, best)));
+        }
+
+        if !results.is_empty() && results.iter().all(|(allowed, _)| *allowed) {
+            return results.swap_remove(0).1;
+        }
+        results
+            .into_iter()
+            .map(|(_, result)| result)
+            .find(|result| result.decision == PermissionDecision::Ask && result.rule.is_some())
+            .unwrap_or_else(PermissionCheckResult::ask)
+    }
+
+    /// Check a call on two paths one path at a time
+    ///
+    /// Rules match a tool's first path field (see [`path_fields`]); the call
+    /// is checked once per path, as if that path were in the first field. A
+    /// deny rule matching either path denies the call, and the call is only
+    /// allowed if both paths are.
+    fn check_paths(
+        &self,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+        fields: [&str; 2],
+    ) -> PermissionCheckResult {
+        let mut results = Vec::with_capacity(fields.len());
+        for field in fields {
+            let Some(path) = tool_input.get(field).and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let mut input = tool_input.clone();
+            input[fields[0]] = serde_json::Value::from(path);
+            let best = self.matching_rule(tool_name, &input);
+            if let Some((PermissionDecision::Deny, _, _)) = best {
+                return Self::result_for(tool_name, best);
+            }
+            results.push(Self::result_for(tool_name, best));
+        }
+
+        if results.is_empty() {
+            return self.check_rules(tool_name, tool_input);
+        }
+        if results
+            .iter()
+            .all(|result| result.decision == PermissionDecision::Allow)
+        {
+            return results.swap_remove(0);
+        }
+        results
+            .into_iter()
+            .find(|result| result.decision == PermissionDecision::Ask && result.rule.is_some())
+            .unwrap_or_else(PermissionCheckResult::ask)
+    }
+
+    /// Check a call against the rules as a whole
+    fn check_rules(
+        &self,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+    ) -> PermissionCheckResult {
+        Self::result_for(tool_name, self.matching_rule(tool_name, tool_input))
+    }
+
+    /// The rule deciding a call: decision, rule and parsed rule
+    fn matching_rule(
+        &self,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+    ) -> Option<(PermissionDecision, &str, &ParsedRule)> {
+        let lists = [
+            (PermissionDecision::Deny, &self.deny_rules),
+            (PermissionDecision::Allow, &self.allow_rules),
+            (PermissionDecision::Ask, &self.ask_rules),
+        ];
+        // The most specific matching rule of each layer
+        let mut by_layer: Vec<(RuleLayer<'_>, usize, PermissionDecision, &str, &ParsedRule)> =
+            Vec::new();
+        for (decision, rules) in lists {
+            for (index, (rule_str, parsed)) in rules.iter().enumerate() {
+                if !parsed.matches(tool_name, tool_input, &self.cwd) {
+                    continue;
+                }
+                let layer = self.layer_of(decision, index);
+                let specificity = parsed.specificity();
+                let candidate = (layer, specificity, decision, rule_str.as_str(), parsed);
+                // Lists are visited in priority order, so only a more
+                // specific rule replaces the one found first
+                match by_layer.iter_mut().find(|best| best.0 == layer) {
+                    Some(best) if specificity > best.1 => *best = candidate,
+                    Some(_) => {}
+                    None => by_layer.push(candidate),
+                }
             }
         }
 
-        // Check ask rules
-        for (rule_str, parsed) in &self.ask_rules {
-            if parsed.matches(tool_name, tool_input, &self.cwd) {
+        // Across layers deny > allow > ask, however specific the rules are
+        [
+            PermissionDecision::Deny,
+            PermissionDecision::Allow,
+            PermissionDecision::Ask,
+        ]
+        .into_iter()
+        .find_map(|decision| {
+            by_layer
+                .iter()
+                .find(|best| best.2 == decision)
+                .map(|&(_, _, decision, rule_str, parsed)| (decision, rule_str, parsed))
+        })
+    }
+
+    /// Layer of the `index`th rule of the `decision` list
+    fn layer_of(&self, decision: PermissionDecision, index: usize) -> RuleLayer<'_> {
+        let permissions = self.settings.permissions.as_ref();
+        let configured = permissions.and_then(|p| match decision {
+            PermissionDecision::Allow => p.allow.as_ref(),
+            PermissionDecision::Deny => p.deny.as_ref(),
+            PermissionDecision::Ask => p.ask.as_ref(),
+        });
+        // Configured rules come first, runtime rules are appended after them
+        match configured.and_then(|rules| rules.get(index)) {
+            Some(rule) => {
+                RuleLayer::Settings(permissions.and_then(|p| p.rule_file(decision, rule.pattern())))
+            }
+            None => RuleLayer::Session,
+        }
+    }
+
+    /// Result for the rule deciding a call, if any
+    fn result_for(
+        tool_name: &str,
+        best: Option<(PermissionDecision, &str, &ParsedRule)>,
+    ) -> PermissionCheckResult {
+        match best {
+            Some((PermissionDecision::Deny, rule_str, _)) => {
+                tracing::debug!("Tool {} denied by rule: {}", tool_name, rule_str);
+                PermissionCheckResult::deny(rule_str)
+            }
+            Some((PermissionDecision::Allow, rule_str, _)) => {
+                tracing::debug!("Tool {} allowed by rule: {}", tool_name, rule_str);
+                PermissionCheckResult::allow(rule_str)
+            }
+            Some((PermissionDecision::Ask, rule_str, _)) => {
                 tracing::debug!(
                     "Tool {} requires permission (ask rule): {}",
                     tool_name,
                     rule_str
                 );
-                return PermissionCheckResult::ask_with_rule(rule_str);
+                PermissionCheckResult::ask_with_rule(rule_str)
+            }
+            None => {
+                // Default: ask
+                tracing::debug!("Tool {} has no matching rule, defaulting to ask", tool_name);
+                PermissionCheckResult::ask()
             }
         }
-
-        // Default: ask
-        tracing::debug!("Tool {} has no matching rule, defaulting to ask", tool_name);
-        PermissionCheckResult::ask()
     }
 
     /// Get the settings
@@ -121,6 +350,41 @@ impl PermissionChecker {
         !self.allow_rules.is_empty() || !self.deny_rules.is_empty() || !self.ask_rules.is_empty()
     }
 
+    /// Where the rule `result` matched comes from
+    ///
+    /// `settings` for rules from the settings files, `session` for rules
+    /// added at runtime ("Always Allow"); None if no rule matched.
+    pub fn rule_layer(&self, result: &PermissionCheckResult) -> Option<&'static str> {
+        match self.result_layer(result)? {
+            RuleLayer::Settings(_) => Some("settings"),
+            RuleLayer::Session => Some("session"),
+        }
+    }
+
+    /// Where the rule `result` matched is defined
+    ///
+    /// The settings file for rules from settings (`settings` if it isn't
+    /// known), `session` for rules added at runtime.
+    fn rule_origin(&self, result: &PermissionCheckResult) -> Option<String> {
+        match self.result_layer(result)? {
+            RuleLayer::Settings(Some(file)) => Some(file.display().to_string()),
+            RuleLayer::Settings(None) => Some("settings".to_string()),
+            RuleLayer::Session => Some("session".to_string()),
+        }
+    }
+
+    /// Layer of the rule `result` matched, None if no rule matched
+    fn result_layer(&self, result: &PermissionCheckResult) -> Option<RuleLayer<'_>> {
+        let rule = result.rule.as_deref()?;
+        let rules = match result.decision {
+            PermissionDecision::Allow => &self.allow_rules,
+            PermissionDecision::Deny => &self.deny_rules,
+            PermissionDecision::Ask => &self.ask_rules,
+        };
+        let index = rules.iter().position(|(rule_str, _)| rule_str == rule)?;
+        Some(self.layer_of(result.decision, index))
+    }
+
     /// Add a runtime allow rule (e.g., from user's "Always Allow" choice)
     pub fn add_allow_rule(&mut self, rule: &str) {
         let parsed = ParsedRule::parse_with_glob(rule, &self.cwd);
@@ -144,10 +408,92 @@ impl PermissionChecker {
         tool_name: &str,
         tool_input: &serde_json::Value,
     ) {
+        let rule = self.rule_for_tool_call(tool_name, tool_input);
+        tracing::info!(
+            tool_name = %tool_name,
+            generated_rule = %rule,
+            "Adding allow rule for Always Allow"
+        );
+
+        let parsed = ParsedRule::parse_with_glob(&rule, &self.cwd);
+        self.allow_rules.push((rule, parsed));
+    }
+
+    /// Add a runtime deny rule for "Always Reject" permission decision
+    ///
+    /// The rule is [`Self::deny_rule_for_tool_call`]. Returns the rule.
+    pub fn add_deny_rule_for_tool_call(
+        &mut self,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+    ) -> String {
+        let rule = self.deny_rule_for_tool_call(tool_name, tool_input);
+        tracing::info!(
+            tool_name = %tool_name,
+            generated_rule = %rule,
+            "Adding deny rule for Always Reject"
+        );
+
+        self.add_deny_rule(&rule);
+        rule
+    }
+
+    /// Rule "Always Reject" saves for a tool call
+    ///
+    /// Unlike the "Always Allow" rule it only covers this exact call:
+    /// rejecting `rm -rf build` denies `rm -rf build`, not every `rm`
+    /// command, and rejecting an edit denies edits of that file only.
+    /// Commands a plain rule can't spell out (multi-line, or looking like
+    /// a `:*` prefix or `/regex/` rule) get an escaped regex rule.
+    pub fn deny_rule_for_tool_call(
+        &self,
+        tool_name: &str,
+        tool_input: &serde_json::Value,
+    ) -> String {
+        let stripped = tool_name.strip_prefix("mcp__acp__").unwrap_or(tool_name);
+
+        match stripped {
+            "Bash" => match tool_input.get("command").and_then(|v| v.as_str()) {
+                Some(cmd) if !cmd.is_empty() => {
+                    let needs_regex = cmd.contains('\n')
+                        || cmd.ends_with(":*")
+                        || (cmd.starts_with('/') && cmd.ends_with('/'));
+                    if needs_regex {
+                        format!("Bash(/{}/)", regex::escape(cmd).replace('\n', "\\n"))
+                    } else {
+                        format!("Bash({})", cmd)
+                    }
+                }
+                _ => stripped.to_string(),
+            },
+            "Read" | "Grep" | "Glob" | "LS" | "Edit" | "MultiEdit" | "Write" | "Touch" => {
+                let tool = if matches!(stripped, "Grep" | "Glob" | "LS") {
+                    "Read"
+                } else {
+                    stripped
+                };
+                match tool_input.get("file_path").and_then(|v| v.as_str()) {
+                    Some(path) => {
+                        let path = Path::new(path);
+                        let path = path.strip_prefix(&self.cwd).map_or_else(
+                            |_| path.to_string_lossy().to_string(),
+                            |relative| format!("./{}", relative.display()),
+                        );
+                        format!("{}({})", tool, globset::escape(&path))
+                    }
+                    None => tool.to_string(),
+                }
+            }
+            _ => stripped.to_string(),
+        }
+    }
+
+    /// Rule covering calls like this one, for "Always Allow"
+    fn rule_for_tool_call(&self, tool_name: &str, tool_input: &serde_json::Value) -> String {
         // Strip mcp__acp__ prefix for consistent rule matching
         let stripped = tool_name.strip_prefix("mcp__acp__").unwrap_or(tool_name);
 
-        let rule = match stripped {
+        match stripped {
             "Bash" => {
                 // Extract command name (first word only) for Bash
                 if let Some(cmd) = tool_input.get("command").and_then(|v| v.as_str()) {
@@ -165,21 +511,12 @@ impl PermissionChecker {
                 // For read operations, generate directory-based rule
                 Self::generate_file_rule("Read", tool_input, &self.cwd)
             }
-            "Edit" | "Write" => {
+            "Edit" | "MultiEdit" | "Write" | "Touch" => {
                 // For write operations, generate directory-based rule
                 Self::generate_file_rule(stripped, tool_input, &self.cwd)
             }
             _ => stripped.to_string(),
-        };
-
-        tracing::info!(
-            tool_name = %tool_name,
-            generated_rule = %rule,
-            "Adding allow rule for Always Allow"
-        );
-
-        let parsed = ParsedRule::parse_with_glob(&rule, &self.cwd);
-        self.allow_rules.push((rule, parsed));
+        }
     }
 
     /// Extract command name (basename only) from a shell command
@@ -249,6 +586,18 @@ impl Default for PermissionChecker {
     }
 }
 
+/// The path fields of tools that work on two paths
+///
+/// Rules match the first field (see `extract_tool_argument` in the rule
+/// module); [`PermissionChecker::check_paths`] checks both.
+fn path_fields(tool_name: &str) -> Option<[&'static str; 2]> {
+    match tool_name {
+        "DiffFiles" => Some(["new_path", "old_path"]),
+        "Move" => Some(["source", "destination"]),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -274,7 +623,7 @@ mod tests {
     #[test]
     fn test_allow_rule() {
         let permissions = PermissionSettings {
-            allow: Some(vec!["Read".to_string()]),
+            allow: Some(vec!["Read".into()]),
             ..Default::default()
         };
         let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
@@ -287,7 +636,7 @@ mod tests {
     #[test]
     fn test_deny_rule() {
         let permissions = PermissionSettings {
-            deny: Some(vec!["Bash".to_string()]),
+            deny: Some(vec!["Bash".into()]),
             ..Default::default()
         };
         let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
@@ -299,8 +648,8 @@ mod tests {
     #[test]
     fn test_deny_takes_priority_over_allow() {
         let permissions = PermissionSettings {
-            allow: Some(vec!["Bash".to_string()]),
-            deny: Some(vec!["Bash".to_string()]),
+            allow: Some(vec!["Bash".into()]),
+            deny: Some(vec!["Bash".into()]),
             ..Default::default()
         };
         let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
@@ -312,8 +661,8 @@ mod tests {
     #[test]
     fn test_allow_takes_priority_over_ask() {
         let permissions = PermissionSettings {
-            allow: Some(vec!["Read".to_string()]),
-            ask: Some(vec!["Read".to_string()]),
+            allow: Some(vec!["Read".into()]),
+            ask: Some(vec!["Read".into()]),
             ..Default::default()
         };
         let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
@@ -325,7 +674,7 @@ mod tests {
     #[test]
     fn test_bash_wildcard_rule() {
         let permissions = PermissionSettings {
-            allow: Some(vec!["Bash(npm run:*)".to_string()]),
+            allow: Some(vec!["Bash(npm run:*)".into()]),
             ..Default::default()
         };
         let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
@@ -347,18 +696,276 @@ mod tests {
         );
 
         // Should block command chaining
+        for command in [
+            "npm run build && rm -rf /",
+            "npm run build | cat",
+            "npm run build; malicious",
+            "npm run build & malicious",
+        ] {
+            assert_eq!(
+                checker
+                    .check_permission("Bash", &json!({"command": command}))
+                    .decision,
+                PermissionDecision::Ask,
+                "{command}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_glob_rules_most_specific_wins() {
+        let permissions = PermissionSettings {
+            allow: Some(vec![
+                "mcp__*".into(),
+                "mcp__github__get_*".into(),
+                "mcp__linear__*".into(),
+            ]),
+            deny: Some(vec![
+                "mcp__github__*".into(),
+                "mcp__linear__*".into(),
+                "mcp__github__get_secret".into(),
+            ]),
+            ..Default::default()
+        };
+        let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
+        let check = |tool_name: &str| checker.check_permission(tool_name, &json!({}));
+
+        // The broadest glob allows other servers
+        let result = check("mcp__slack__post_message");
+        assert_eq!(result.decision, PermissionDecision::Allow);
+        assert_eq!(result.rule.as_deref(), Some("mcp__*"));
+
+        // The server glob is more specific than `mcp__*`...
+        let result = check("mcp__github__create_issue");
+        assert_eq!(result.decision, PermissionDecision::Deny);
+        assert_eq!(result.rule.as_deref(), Some("mcp__github__*"));
+
+        // ...and less specific than the getter glob
+        let result = check("mcp__github__get_issue");
+        assert_eq!(result.decision, PermissionDecision::Allow);
+        assert_eq!(result.rule.as_deref(), Some("mcp__github__get_*"));
+
+        // An exact name beats every glob
+        let result = check("mcp__github__get_secret");
+        assert_eq!(result.decision, PermissionDecision::Deny);
+        assert_eq!(result.rule.as_deref(), Some("mcp__github__get_secret"));
+
+        // Deny wins on equal specificity
+        let result = check("mcp__linear__list_issues");
+        assert_eq!(result.decision, PermissionDecision::Deny);
+        assert_eq!(result.rule.as_deref(), Some("mcp__linear__*"));
+
+        // Globs match ACP tools with or without the prefix
+        let checker = PermissionChecker::new(
+            settings_with_permissions(PermissionSettings {
+                deny: Some(vec!["*Edit".into()]),
+                ..Default::default()
+            }),
+            "/tmp",
+        );
+        for tool_name in ["mcp__acp__MultiEdit", "JsonEdit"] {
+            assert_eq!(
+                checker.check_permission(tool_name, &json!({})).decision,
+                PermissionDecision::Deny
+            );
+        }
+        assert_eq!(
+            checker.check_permission("Write", &json!({})).decision,
+            PermissionDecision::Ask
+        );
+    }
+
+    #[test]
+    fn test_deny_wins_across_settings_files() {
+        let settings_from = |file: &str, permissions: PermissionSettings| {
+            let mut permissions = permissions;
+            permissions.set_rule_file(Path::new(file));
+            settings_with_permissions(permissions)
+        };
+        let mut settings = settings_from(
+            "/home/me/.claude/settings.json",
+            PermissionSettings {
+                allow: Some(vec!["mcp__github__get_*".into()]),
+                deny: Some(vec!["mcp__*".into(), "mcp__github__*".into()]),
+                ..Default::default()
+            },
+        );
+        settings.merge(settings_from(
+            "/repo/.claude/settings.json",
+            PermissionSettings {
+                allow: Some(vec![
+                    "mcp__evil__run".into(),
+                    PermissionRule::Scoped {
+                        rule: "Write".to_string(),
+                        paths: vec!["src/**".to_string()],
+                    },
+                ]),
+                ..Default::default()
+            },
+        ));
+        let checker = PermissionChecker::new(settings, "/repo");
+        let check = |tool_name: &str| checker.check_permission(tool_name, &json!({}));
+
+        // A project allow can't override a broader user deny
+        let result = check("mcp__evil__run");
+        assert_eq!(result.decision, PermissionDecision::Deny);
+        assert_eq!(result.rule.as_deref(), Some("mcp__*"));
+        assert_eq!(
+            result.origin.as_deref(),
+            Some("/home/me/.claude/settings.json")
+        );
+
+        // Within one file the most specific rule still decides
+        let result = check("mcp__github__get_issue");
+        assert_eq!(result.decision, PermissionDecision::Allow);
+        assert_eq!(
+            result.origin.as_deref(),
+            Some("/home/me/.claude/settings.json")
+        );
+
+        // Scoped rules report the file they come from
+        let result = checker.check_permission("Write", &json!({"file_path": "/repo/src/main.rs"}));
+        assert_eq!(result.decision, PermissionDecision::Allow);
+        assert_eq!(
+            result.origin.as_deref(),
+            Some("/repo/.claude/settings.json")
+        );
+    }
+
+    #[test]
+    fn test_bash_regex_rule() {
+        let permissions = PermissionSettings {
+            allow: Some(vec!["Bash(/git .*/)".into()]),
+            deny: Some(vec!["Bash(/git push( .*)?/)".into()]),
+            ..Default::default()
+        };
+        let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
+        let decision = |command: &str| {
+            checker
+                .check_permission("Bash", &json!({"command": command}))
+                .decision
+        };
+
+        // Any git subcommand except push
+        assert_eq!(decision("git status"), PermissionDecision::Allow);
+        assert_eq!(decision("git commit -m wip"), PermissionDecision::Allow);
+        assert_eq!(decision("git push origin main"), PermissionDecision::Deny);
+        assert_eq!(decision("git pushx"), PermissionDecision::Allow);
+        assert_eq!(decision("cargo build"), PermissionDecision::Ask);
+        assert_eq!(decision("git status; rm -rf /"), PermissionDecision::Ask);
+
+        // Every command of a line has to be allowed...
+        assert_eq!(decision("git status & rm -rf ~"), PermissionDecision::Ask);
+        assert_eq!(
+            decision("git add . && git commit -m 'a; b'"),
+            PermissionDecision::Allow
+        );
+        // ...and plain, without redirections or expansions
+        assert_eq!(decision("git log > ~/.bashrc"), PermissionDecision::Ask);
+        assert_eq!(decision("git log $(rm -rf ~)"), PermissionDecision::Ask);
+        assert_eq!(decision("GIT_DIR=/tmp git status"), PermissionDecision::Ask);
+        assert_eq!(decision("git log 2>&1"), PermissionDecision::Allow);
+
+        // A deny rule matching any of the commands denies the line
+        assert_eq!(decision("git status; git push"), PermissionDecision::Deny);
+        assert_eq!(
+            decision("cargo test && git push --force"),
+            PermissionDecision::Deny
+        );
+        assert_eq!(
+            decision("FOO=1 git push > /dev/null"),
+            PermissionDecision::Deny
+        );
+        assert_eq!(decision("git 'status"), PermissionDecision::Ask);
+
+        // A rule without an argument allows any command line
+        let checker = PermissionChecker::new(
+            settings_with_permissions(PermissionSettings {
+                allow: Some(vec!["Bash".into()]),
+                ..Default::default()
+            }),
+            "/tmp",
+        );
+        assert_eq!(
+            checker
+                .check_permission("Bash", &json!({"command": "cargo test > log; cat log"}))
+                .decision,
+            PermissionDecision::Allow
+        );
+    }
+
+    #[test]
+    fn test_rules_limited_to_paths() {
+        let permissions: PermissionSettings = serde_json::from_value(json!({
+            "allow": ["Read", {"rule": "Edit", "paths": ["src/**"]}],
+            "ask": [{"rule": "Edit", "paths": [".github/**", "/etc/**"]}],
+            "deny": [{"rule": "Bash", "paths": ["src/**"]}]
+        }))
+        .unwrap();
+        let checker =
+            PermissionChecker::new(settings_with_permissions(permissions), "/tmp/project");
+
+        let result = checker.check_permission("Write", &json!({"file_path": "src/lib/mod.rs"}));
+        assert_eq!(result.decision, PermissionDecision::Allow);
+        assert_eq!(result.rule.as_deref(), Some("Edit in src/**"));
+        assert_eq!(
+            checker
+                .check_permission(
+                    "mcp__acp__Edit",
+                    &json!({"file_path": "/tmp/project/src/main.rs"})
+                )
+                .decision,
+            PermissionDecision::Allow
+        );
+
+        let result = checker.check_permission(
+            "Edit",
+            &json!({"file_path": "/tmp/project/.github/workflows/ci.yml"}),
+        );
+        assert_eq!(result.decision, PermissionDecision::Ask);
+        assert_eq!(result.rule.as_deref(), Some("Edit in .github/**, /etc/**"));
+
+        // `..` is resolved before matching, even for paths that don't exist
+        for file_path in ["src/../../evil/new.rs", "/tmp/project/src/../../../etc/new"] {
+            let result = checker.check_permission("Write", &json!({"file_path": file_path}));
+            assert_ne!(
+                result.rule.as_deref(),
+                Some("Edit in src/**"),
+                "{file_path}"
+            );
+        }
+        assert_eq!(
+            checker
+                .check_permission("Write", &json!({"file_path": "src/../../../etc/new"}))
+                .rule
+                .as_deref(),
+            Some("Edit in .github/**, /etc/**")
+        );
+
+        // Outside the listed paths, or without a path, the rules don't apply
+        let result = checker.check_permission("Write", &json!({"file_path": "README.md"}));
+        assert_eq!(result.decision, PermissionDecision::Ask);
+        assert!(result.rule.is_none());
+        assert!(checker.check_permission("Write", &json!({})).rule.is_none());
+        // Bash has no path argument, so a path-limited Bash rule never matches
         assert_eq!(
             checker
-                .check_permission("Bash", &json!({"command": "npm run build && rm -rf /"}))
+                .check_permission("Bash", &json!({"command": "ls src"}))
                 .decision,
             PermissionDecision::Ask
         );
+        assert_eq!(
+            checker
+                .check_permission("Read", &json!({"file_path": "src/main.rs"}))
+                .decision,
+            PermissionDecision::Allow
+        );
     }
 
     #[test]
     fn test_read_group_matching() {
         let permissions = PermissionSettings {
-            allow: Some(vec!["Read".to_string()]),
+            allow: Some(vec!["Read".into()]),
             ..Default::default()
         };
         let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
@@ -388,6 +995,49 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_two_path_tools_check_both_paths() {
+        let permissions = PermissionSettings {
+            allow: Some(vec!["Read(./src/**)".into()]),
+            deny: Some(vec!["Read(/secret/**)".into()]),
+            ..Default::default()
+        };
+        let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
+        let diff = |old: &str, new: &str| {
+            checker
+                .check_permission("DiffFiles", &json!({"old_path": old, "new_path": new}))
+                .decision
+        };
+
+        // A denied path on either side denies the call
+        assert_eq!(diff("/secret/id_rsa", "missing"), PermissionDecision::Deny);
+        assert_eq!(diff("src/a.rs", "/secret/id_rsa"), PermissionDecision::Deny);
+        // Both paths have to be allowed
+        assert_eq!(diff("src/a.rs", "src/b.rs"), PermissionDecision::Allow);
+        assert_eq!(diff("src/a.rs", "notes.txt"), PermissionDecision::Ask);
+
+        // Moving a file into a protected directory is an edit of it
+        let permissions = PermissionSettings {
+            allow: Some(vec!["Edit(./src/**)".into()]),
+            deny: Some(vec!["Edit(./protected/**)".into()]),
+            ..Default::default()
+        };
+        let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
+        let move_file = |source: &str, destination: &str| {
+            checker
+                .check_permission(
+                    "Move",
+                    &json!({"source": source, "destination": destination}),
+                )
+                .decision
+        };
+        assert_eq!(
+            move_file("src/a.rs", "protected/a.rs"),
+            PermissionDecision::Deny
+        );
+        assert_eq!(move_file("src/a.rs", "src/b.rs"), PermissionDecision::Allow);
+    }
+
     #[test]
     fn test_add_runtime_rule() {
         let mut checker = PermissionChecker::default();
@@ -408,10 +1058,63 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_rule_layer() {
+        let permissions = PermissionSettings {
+            deny: Some(vec!["Bash(rm:*)".into()]),
+            ..Default::default()
+        };
+        let mut checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
+        checker.add_deny_rule("Bash(curl:*)");
+
+        let result = checker.check_permission("Bash", &json!({"command": "rm -rf target"}));
+        assert_eq!(result.decision, PermissionDecision::Deny);
+        assert_eq!(checker.rule_layer(&result), Some("settings"));
+        assert_eq!(result.origin.as_deref(), Some("settings"));
+        let result = checker.check_permission("Bash", &json!({"command": "curl example.com"}));
+        assert_eq!(checker.rule_layer(&result), Some("session"));
+        assert_eq!(result.origin.as_deref(), Some("session"));
+        let result = checker.check_permission("Bash", &json!({"command": "ls"}));
+        assert_eq!(checker.rule_layer(&result), None);
+        assert_eq!(result.origin, None);
+    }
+
+    #[test]
+    fn test_relocate_keeps_runtime_rules() {
+        let permissions = PermissionSettings {
+            allow: Some(vec!["Read".into()]),
+            ..Default::default()
+        };
+        let mut checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
+        checker.add_allow_rule("Bash(cargo:*)");
+
+        let permissions = PermissionSettings {
+            deny: Some(vec!["Write".into()]),
+            ..Default::default()
+        };
+        checker.relocate(settings_with_permissions(permissions), "/tmp/sub");
+
+        assert_eq!(checker.cwd(), Path::new("/tmp/sub"));
+        assert_eq!(
+            checker.check_permission("Read", &json!({})).decision,
+            PermissionDecision::Ask
+        );
+        assert_eq!(
+            checker.check_permission("Write", &json!({})).decision,
+            PermissionDecision::Deny
+        );
+        assert_eq!(
+            checker
+                .check_permission("Bash", &json!({"command": "cargo test"}))
+                .decision,
+            PermissionDecision::Allow
+        );
+    }
+
     #[test]
     fn test_acp_prefix_stripped() {
         let permissions = PermissionSettings {
-            allow: Some(vec!["Read".to_string()]),
+            allow: Some(vec!["Read".into()]),
             ..Default::default()
         };
         let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
@@ -435,7 +1138,7 @@ mod tests {
         assert!(!checker.has_rules());
 
         let permissions = PermissionSettings {
-            allow: Some(vec!["Read".to_string()]),
+            allow: Some(vec!["Read".into()]),
             ..Default::default()
         };
         let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
@@ -447,7 +1150,8 @@ mod tests {
         let mut checker = PermissionChecker::new(Settings::default(), "/tmp");
 
         // Add rule for specific bash command (find)
-        checker.add_allow_rule_for_tool_call("Bash", &json!({"command": "find /path1 -name '*.rs'"}));
+        checker
+            .add_allow_rule_for_tool_call("Bash", &json!({"command": "find /path1 -name '*.rs'"}));
 
         // Should allow ANY find command (same command name)
         assert_eq!(
@@ -519,6 +1223,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_deny_rule_for_tool_call_is_exact() {
+        let mut checker = PermissionChecker::new(Settings::default(), "/tmp/project");
+        let rule = |checker: &PermissionChecker, tool: &str, input: serde_json::Value| {
+            checker.deny_rule_for_tool_call(tool, &input)
+        };
+
+        assert_eq!(
+            rule(
+                &checker,
+                "mcp__acp__Bash",
+                json!({"command": "rm -rf build"})
+            ),
+            "Bash(rm -rf build)"
+        );
+        assert_eq!(
+            rule(
+                &checker,
+                "Edit",
+                json!({"file_path": "/tmp/project/src/[id].rs"})
+            ),
+            "Edit(./src/[[]id[]].rs)"
+        );
+        assert_eq!(
+            rule(&checker, "Write", json!({"file_path": "/etc/hosts"})),
+            "Write(/etc/hosts)"
+        );
+        assert_eq!(
+            rule(&checker, "Bash", json!({"command": "echo a:*"})),
+            "Bash(/echo a:\\*/)"
+        );
+
+        // Compound and multi-line commands are denied again, nothing else is
+        for command in ["cd build && make", "cat <<EOF\nhi\nEOF"] {
+            checker.add_deny_rule_for_tool_call("Bash", &json!({"command": command}));
+            assert_eq!(
+                checker
+                    .check_permission("Bash", &json!({"command": command}))
+                    .decision,
+                PermissionDecision::Deny,
+                "{command}"
+            );
+        }
+        assert_eq!(
+            checker
+                .check_permission("Bash", &json!({"command": "make"}))
+                .decision,
+            PermissionDecision::Ask
+        );
+        checker
+            .add_deny_rule_for_tool_call("Edit", &json!({"file_path": "/tmp/project/src/[id].rs"}));
+        assert_eq!(
+            checker
+                .check_permission("Edit", &json!({"file_path": "/tmp/project/src/[id].rs"}))
+                .decision,
+            PermissionDecision::Deny
+        );
+        assert_eq!(
+            checker
+                .check_permission("Edit", &json!({"file_path": "/tmp/project/src/i.rs"}))
+                .decision,
+            PermissionDecision::Ask
+        );
+    }
+
     #[test]
     fn test_add_allow_rule_for_mcp_prefixed_tool() {
         let mut checker = PermissionChecker::new(Settings::default(), "/tmp");
@@ -553,10 +1322,7 @@ mod tests {
             PermissionChecker::extract_command_name("find /path -name '*.rs'"),
             "find"
         );
-        assert_eq!(
-            PermissionChecker::extract_command_name("ls -la /tmp"),
-            "ls"
-        );
+        assert_eq!(PermissionChecker::extract_command_name("ls -la /tmp"), "ls");
         assert_eq!(PermissionChecker::extract_command_name("npm"), "npm");
         assert_eq!(PermissionChecker::extract_command_name(""), "");
         // Full path commands should return just the basename
@@ -1,29 +1,41 @@
 //! Permission rule parsing and matching
 //!
 //! Implements rule parsing for allow/deny/ask permission rules with glob pattern support.
+//!
+//! Tool names in rules may be glob patterns (`mcp__*__Read`, `mcp__github__get_*`).
+//! When several rules of one settings file match a call, the most specific
+//! one decides:
+//! 1. Rules naming the tool (or its tool group) outright
+//! 2. Glob patterns, those with more literal characters first
+//!
+//! On equal specificity deny wins over allow, and allow over ask. Between
+//! rules of different files (or rules added at runtime) deny always wins
+//! over allow, and allow over ask.
 
-use std::path::Path;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
 
 use globset::{Glob, GlobMatcher};
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use crate::mcp::ExternalMcpManager;
-use crate::mcp::tools::bash::contains_shell_operator;
+use crate::mcp::tools::workspace::normalize;
 
 /// Cached regex for parsing permission rules
 /// Pattern: ToolName or ToolName(argument)
 /// Compiled once and reused for better performance
 static RULE_REGEX: std::sync::LazyLock<Regex> = std::sync::LazyLock::new(|| {
     // This regex is statically known and will always compile correctly
-    Regex::new(r"^(\w+)(?:\((.+)\))?$").expect("Invalid hardcoded regex pattern")
+    Regex::new(r"^([\w*?-]+)(?:\((.+)\))?$").expect("Invalid hardcoded regex pattern")
 });
 
 /// ACP tool name prefix
 const ACP_TOOL_PREFIX: &str = "mcp__acp__";
 
 /// Permission decision result
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum PermissionDecision {
     /// Tool execution is allowed
     Allow,
@@ -33,6 +45,17 @@ pub enum PermissionDecision {
     Ask,
 }
 
+impl PermissionDecision {
+    /// Name of the decision, as used in settings (`allow`, `deny`, `ask`)
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Deny => "deny",
+            Self::Ask => "ask",
+        }
+    }
+}
+
 /// Result of a permission check
 #[derive(Debug, Clone)]
 pub struct PermissionCheckResult {
@@ -42,6 +65,10 @@ pub struct PermissionCheckResult {
     pub rule: Option<String>,
     /// The source of the rule (allow, deny, ask)
     pub source: Option<String>,
+    /// Where the rule is defined: the settings file, `settings` if the
+    /// file isn't known, `session` for rules added at runtime, or the
+    /// custom mode
+    pub origin: Option<String>,
 }
 
 impl PermissionCheckResult {
@@ -51,6 +78,7 @@ impl PermissionCheckResult {
             decision: PermissionDecision::Allow,
             rule: Some(rule.into()),
             source: Some("allow".to_string()),
+            origin: None,
         }
     }
 
@@ -60,6 +88,7 @@ impl PermissionCheckResult {
             decision: PermissionDecision::Deny,
             rule: Some(rule.into()),
             source: Some("deny".to_string()),
+            origin: None,
         }
     }
 
@@ -69,6 +98,7 @@ impl PermissionCheckResult {
             decision: PermissionDecision::Ask,
             rule: Some(rule.into()),
             source: Some("ask".to_string()),
+            origin: None,
         }
     }
 
@@ -78,8 +108,22 @@ impl PermissionCheckResult {
             decision: PermissionDecision::Ask,
             rule: None,
             source: None,
+            origin: None,
         }
     }
+
+    /// Which rule decided and where it is defined, for messages
+    ///
+    /// E.g. ``deny rule `Bash(rm:*)` in /repo/.claude/settings.json``;
+    /// None if no rule matched.
+    pub fn explain(&self) -> Option<String> {
+        let rule = self.rule.as_deref()?;
+        let kind = self.source.as_deref().unwrap_or(self.decision.as_str());
+        Some(match &self.origin {
+            Some(origin) => format!("{kind} rule `{rule}` in {origin}"),
+            None => format!("{kind} rule `{rule}`"),
+        })
+    }
 }
 
 /// Permission settings from settings.json
@@ -88,15 +132,15 @@ impl PermissionCheckResult {
 pub struct PermissionSettings {
     /// Rules that allow tool execution
     #[serde(default)]
-    pub allow: Option<Vec<String>>,
+    pub allow: Option<Vec<PermissionRule>>,
 
     /// Rules that deny tool execution
     #[serde(default)]
-    pub deny: Option<Vec<String>>,
+    pub deny: Option<Vec<PermissionRule>>,
 
     /// Rules that require asking the user
     #[serde(default)]
-    pub ask: Option<Vec<String>>,
+    pub ask: Option<Vec<PermissionRule>>,
 
     /// Additional directories that can be accessed
     #[serde(default)]
@@ -105,6 +149,91 @@ pub struct PermissionSettings {
     /// Default permission mode
     #[serde(default)]
     pub default_mode: Option<String>,
+
+    /// Settings file each rule was loaded from, by list and rule string
+    #[serde(skip)]
+    pub rule_files: HashMap<(PermissionDecision, String), PathBuf>,
+}
+
+impl PermissionSettings {
+    /// Record `path` as the file the rules were loaded from
+    pub fn set_rule_file(&mut self, path: &Path) {
+        let lists = [
+            (PermissionDecision::Allow, &self.allow),
+            (PermissionDecision::Deny, &self.deny),
+            (PermissionDecision::Ask, &self.ask),
+        ];
+        for (decision, rules) in lists {
+            for rule in rules.iter().flatten() {
+                self.rule_files
+                    .insert((decision, rule.pattern().to_string()), path.to_path_buf());
+            }
+        }
+    }
+
+    /// Settings file the `decision` rule `rule` was loaded from, if known
+    pub fn rule_file(&self, decision: PermissionDecision, rule: &str) -> Option<&Path> {
+        self.rule_files
+            .get(&(decision, rule.to_string()))
+            .map(PathBuf::as_path)
+    }
+}
+
+/// A permission rule as written in settings
+///
+/// Either a rule string like `Edit` or `Bash(npm run:*)`, or an object
+/// limiting the rule to tool calls on some paths:
+/// `{"rule": "Write", "paths": ["src/**"]}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum PermissionRule {
+    /// Rule applying wherever the tool is used
+    Pattern(String),
+    /// Rule applying only to calls on paths matching one of the globs
+    Scoped {
+        /// The rule string
+        rule: String,
+        /// Glob patterns, relative to the working directory unless absolute
+        paths: Vec<String>,
+    },
+}
+
+impl PermissionRule {
+    /// The rule string (e.g. `Write`, `Bash(npm run:*)`)
+    pub fn pattern(&self) -> &str {
+        match self {
+            Self::Pattern(rule) | Self::Scoped { rule, .. } => rule,
+        }
+    }
+
+    /// Path globs the rule is limited to (empty if it isn't)
+    pub fn paths(&self) -> &[String] {
+        match self {
+            Self::Pattern(_) => &[],
+            Self::Scoped { paths, .. } => paths,
+        }
+    }
+}
+
+impl From<&str> for PermissionRule {
+    fn from(rule: &str) -> Self {
+        Self::Pattern(rule.to_string())
+    }
+}
+
+impl From<String> for PermissionRule {
+    fn from(rule: String) -> Self {
+        Self::Pattern(rule)
+    }
+}
+
+impl fmt::Display for PermissionRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Pattern(rule) => f.write_str(rule),
+            Self::Scoped { rule, paths } => write!(f, "{} in {}", rule, paths.join(", ")),
+        }
+    }
 }
 
 /// A parsed permission rule
@@ -118,10 +247,19 @@ pub struct ParsedRule {
     pub is_wildcard: bool,
     /// Compiled glob matcher for file paths
     glob_matcher: Option<GlobMatcher>,
+    /// Compiled globs of the paths the rule is limited to, if it is
+    path_matchers: Option<Vec<GlobMatcher>>,
+    /// Compiled regex of a `Bash(/regex/)` rule, None if it didn't compile
+    command_regex: Option<Regex>,
+    /// Compiled matcher of a glob tool name such as `mcp__*__Read`
+    tool_glob: Option<GlobMatcher>,
 }
 
 impl ParsedRule {
     /// Parse a rule string like "Read", "Read(./.env)", "Bash(npm run:*)"
+    ///
+    /// `Bash(/regex/)` rules match commands the regex matches in full. A
+    /// regex that doesn't compile is logged and the rule matches nothing.
     pub fn parse(rule: &str) -> Self {
         // Use cached regex (compiled once at first use)
         // The regex is statically known and guaranteed to compile correctly
@@ -141,11 +279,22 @@ impl ParsedRule {
                 argument
             };
 
+            let command_regex = argument
+                .as_deref()
+                .filter(|arg| is_bash_tool(&tool_name) && is_regex_pattern(arg))
+                .and_then(|arg| compile_command_regex(rule, arg));
+            let tool_glob = is_glob_pattern(&tool_name)
+                .then(|| compile_tool_glob(rule, &tool_name))
+                .flatten();
+
             Self {
                 tool_name,
                 argument,
                 is_wildcard,
                 glob_matcher: None,
+                path_matchers: None,
+                command_regex,
+                tool_glob,
             }
         } else {
             // Fallback: treat entire string as tool name
@@ -154,6 +303,9 @@ impl ParsedRule {
                 argument: None,
                 is_wildcard: false,
                 glob_matcher: None,
+                path_matchers: None,
+                command_regex: None,
+                tool_glob: None,
             }
         }
     }
@@ -175,16 +327,75 @@ impl ParsedRule {
         parsed
     }
 
+    /// Parse a settings rule, compiling the globs of the paths it is limited to
+    ///
+    /// Invalid globs are skipped; a rule limited only to invalid globs
+    /// matches nothing.
+    pub fn parse_rule(rule: &PermissionRule, cwd: &Path) -> Self {
+        let mut parsed = Self::parse_with_glob(rule.pattern(), cwd);
+        if rule.paths().is_empty() {
+            return parsed;
+        }
+
+        let matchers = rule
+            .paths()
+            .iter()
+            .filter_map(|path| match Glob::new(&normalize_path(path, cwd)) {
+                Ok(glob) => Some(glob.compile_matcher()),
+                Err(e) => {
+                    tracing::warn!(rule = %rule, path = %path, error = %e, "Invalid path glob in permission rule");
+                    None
+                }
+            })
+            .collect();
+        parsed.path_matchers = Some(matchers);
+        parsed
+    }
+
+    /// How specific the rule's tool name is; of the rules matching a call,
+    /// the most specific one decides
+    ///
+    /// Rules naming a tool or tool group rank highest, glob patterns by
+    /// their number of literal characters.
+    pub fn specificity(&self) -> usize {
+        if self.tool_glob.is_none() {
+            return usize::MAX;
+        }
+        self.tool_name
+            .chars()
+            .filter(|c| !matches!(c, '*' | '?'))
+            .count()
+    }
+
     /// Check if this rule matches a tool invocation
     pub fn matches(&self, tool_name: &str, tool_input: &serde_json::Value, cwd: &Path) -> bool {
         // Strip ACP prefix if present
         let stripped_name = tool_name.strip_prefix(ACP_TOOL_PREFIX).unwrap_or(tool_name);
 
-        // Check if tool name matches (considering tool groups and MCP tools)
-        if !self.matches_tool_name(stripped_name) {
+        // Check if tool name matches (considering tool groups and MCP tools);
+        // globs may match the name with or without the ACP prefix
+        let name_matches = match &self.tool_glob {
+            Some(glob) => glob.is_match(tool_name) || glob.is_match(stripped_name),
+            None => self.matches_tool_name(stripped_name),
+        };
+        if !name_matches {
             return false;
         }
 
+        // A rule limited to paths only matches calls on one of them
+        if let Some(ref matchers) = self.path_matchers {
+            let Some(path) = extract_tool_path(stripped_name, tool_input) else {
+                return false;
+            };
+            let normalized_path = normalize_path(&path, cwd);
+            if !matchers
+                .iter()
+                .any(|matcher| matcher.is_match(&normalized_path))
+            {
+                return false;
+            }
+        }
+
         // If no argument specified, match all invocations of this tool
         let Some(ref pattern) = self.argument else {
             return true;
@@ -224,10 +435,26 @@ impl ParsedRule {
 
         // Tool group matching
         match self.tool_name.as_str() {
-            // Read rule matches Read, Grep, Glob, LS
-            "Read" => matches!(tool_name, "Read" | "Grep" | "Glob" | "LS"),
-            // Edit rule matches Edit, Write
-            "Edit" => matches!(tool_name, "Edit" | "Write"),
+            // Read rule matches Read, Grep, Glob, LS, RepoStats, RecentFiles, DiffFiles
+            "Read" => matches!(
+                tool_name,
+                "Read" | "Grep" | "Glob" | "LS" | "RepoStats" | "RecentFiles" | "DiffFiles"
+            ),
+            // Edit rule matches the tools that change files
+            "Edit" => matches!(
+                tool_name,
+                "Edit"
+                    | "MultiEdit"
+                    | "JsonEdit"
+                    | "YamlEdit"
+                    | "TomlEdit"
+                    | "CargoAdd"
+                    | "Write"
+                    | "Touch"
+                    | "CreateDirectory"
+                    | "Delete"
+                    | "Move"
+            ),
             // Task rule matches Task, TaskOutput
             "Task" => matches!(tool_name, "Task" | "TaskOutput"),
             // Web rule matches WebSearch, WebFetch
@@ -236,18 +463,22 @@ impl ParsedRule {
         }
     }
 
-    /// Match bash command with prefix/exact matching
+    /// Match bash command with regex, prefix or exact matching
+    ///
+    /// `command` is one simple command: [`PermissionChecker`] splits command
+    /// lines at `;`, `&&`, `|` and the like before matching them.
+    ///
+    /// [`PermissionChecker`]: super::PermissionChecker
     fn matches_bash_command(&self, pattern: &str, command: &str) -> bool {
+        if is_regex_pattern(pattern) {
+            return self
+                .command_regex
+                .as_ref()
+                .is_some_and(|regex| regex.is_match(command));
+        }
         if self.is_wildcard {
             // Prefix match with wildcard
-            if let Some(remainder) = command.strip_prefix(pattern) {
-                // Check remainder for shell operators (security)
-                if contains_shell_operator(remainder) {
-                    return false;
-                }
-                return true;
-            }
-            false
+            command.starts_with(pattern)
         } else {
             // Exact match
             pattern == command
@@ -292,11 +523,14 @@ fn normalize_path(path: &str, cwd: &Path) -> String {
         path.to_string()
     };
 
-    // Normalize path separators and resolve ..
-    Path::new(&path)
+    // Resolve `.` and `..` first so a path that doesn't exist yet can't
+    // climb out of a directory with `..`
+    let normalized = normalize(Path::new(&path));
+    normalized
         .canonicalize()
-        .map(|p| p.to_string_lossy().to_string())
-        .unwrap_or(path)
+        .unwrap_or(normalized)
+        .to_string_lossy()
+        .to_string()
 }
 
 /// Check if tool is bash-like (command execution)
@@ -304,11 +538,54 @@ fn is_bash_tool(tool_name: &str) -> bool {
     matches!(tool_name, "Bash" | "BashOutput" | "KillShell")
 }
 
+/// Whether a rule's tool name is a glob pattern
+fn is_glob_pattern(tool_name: &str) -> bool {
+    tool_name.contains(['*', '?'])
+}
+
+/// Compile the glob of a rule's tool name
+fn compile_tool_glob(rule: &str, tool_name: &str) -> Option<GlobMatcher> {
+    match Glob::new(tool_name) {
+        Ok(glob) => Some(glob.compile_matcher()),
+        Err(e) => {
+            tracing::warn!(rule = %rule, error = %e, "Invalid tool name glob in permission rule");
+            None
+        }
+    }
+}
+
+/// Whether a Bash rule argument is a regex (`/regex/`)
+fn is_regex_pattern(pattern: &str) -> bool {
+    pattern.len() > 2 && pattern.starts_with('/') && pattern.ends_with('/')
+}
+
+/// Compile the regex of a `Bash(/regex/)` rule, anchored to the whole command
+fn compile_command_regex(rule: &str, pattern: &str) -> Option<Regex> {
+    let inner = &pattern[1..pattern.len() - 1];
+    match Regex::new(&format!("^(?:{inner})$")) {
+        Ok(regex) => Some(regex),
+        Err(e) => {
+            tracing::warn!(rule = %rule, error = %e, "Invalid regex in permission rule");
+            None
+        }
+    }
+}
+
 /// Check if tool operates on files
 fn is_file_tool(tool_name: &str) -> bool {
     matches!(
         tool_name,
-        "Read" | "Write" | "Edit" | "Grep" | "Glob" | "LS" | "NotebookRead" | "NotebookEdit"
+        "Read"
+            | "Write"
+            | "Edit"
+            | "MultiEdit"
+            | "Grep"
+            | "Glob"
+            | "LS"
+            | "NotebookRead"
+            | "NotebookEdit"
+            | "DiffFiles"
+            | "Move"
     )
 }
 
@@ -321,13 +598,39 @@ fn extract_tool_argument(tool_name: &str, input: &serde_json::Value) -> Option<S
             .and_then(|v| v.as_str())
             .map(String::from),
         // File tools use "file_path" or "path"
-        "Read" | "Write" | "Edit" | "NotebookRead" | "NotebookEdit" => input
+        "Read" | "Write" | "Edit" | "MultiEdit" | "Touch" | "CreateDirectory" | "Delete"
+        | "NotebookRead" | "NotebookEdit" => input
             .get("file_path")
             .or_else(|| input.get("path"))
             .and_then(|v| v.as_str())
             .map(String::from),
+        // DiffFiles is matched against the file compared against the
+        // original; the permission checker checks `old_path` the same way
+        "DiffFiles" => input
+            .get("new_path")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        // Structured edits carry the key path in "path", so only "file_path" applies
+        "JsonEdit" | "YamlEdit" | "TomlEdit" => input
+            .get("file_path")
+            .and_then(|v| v.as_str())
+            .map(String::from),
+        // CargoAdd is matched against the manifest, Cargo.toml by default
+        "CargoAdd" => Some(
+            input
+                .get("manifest_path")
+                .and_then(|v| v.as_str())
+                .unwrap_or("Cargo.toml")
+                .to_string(),
+        ),
+        // Move is matched against the path being moved; the permission
+        // checker checks `destination` the same way
+        "Move" => input
+            .get("source")
+            .and_then(|v| v.as_str())
+            .map(String::from),
         // Search tools use "path" or "pattern"
-        "Grep" | "Glob" | "LS" => input
+        "Grep" | "Glob" | "LS" | "RepoStats" | "RecentFiles" => input
             .get("path")
             .or_else(|| input.get("pattern"))
             .and_then(|v| v.as_str())
@@ -363,6 +666,25 @@ fn extract_tool_argument(tool_name: &str, input: &serde_json::Value) -> Option<S
     }
 }
 
+/// Extract the path a tool call operates on, for rules limited to paths
+///
+/// Search tools without a `path` search the working directory. Returns
+/// None for tools that don't operate on paths.
+fn extract_tool_path(tool_name: &str, input: &serde_json::Value) -> Option<String> {
+    match tool_name {
+        "Grep" | "Glob" | "LS" | "RepoStats" | "RecentFiles" => Some(
+            input
+                .get("path")
+                .and_then(|v| v.as_str())
+                .unwrap_or(".")
+                .to_string(),
+        ),
+        "Bash" | "BashOutput" | "KillShell" | "Task" | "TaskOutput" | "TodoWrite"
+        | "SlashCommand" | "Skill" => None,
+        _ => extract_tool_argument(tool_name, input),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -437,9 +759,14 @@ mod tests {
         let rule = ParsedRule::parse("Edit");
         let cwd = PathBuf::from("/tmp");
 
-        // Edit rule should match Edit, Write
+        // Edit rule should match Edit, Write, Touch
         assert!(rule.matches("Edit", &json!({}), &cwd));
         assert!(rule.matches("Write", &json!({}), &cwd));
+        assert!(rule.matches("Touch", &json!({}), &cwd));
+        assert!(rule.matches("CreateDirectory", &json!({}), &cwd));
+        assert!(rule.matches("Delete", &json!({}), &cwd));
+        assert!(rule.matches("MultiEdit", &json!({}), &cwd));
+        assert!(rule.matches("Move", &json!({}), &cwd));
         assert!(!rule.matches("Read", &json!({}), &cwd));
     }
 
@@ -465,22 +792,19 @@ mod tests {
     }
 
     #[test]
-    fn test_matches_bash_wildcard_blocks_shell_operators() {
-        let rule = ParsedRule::parse("Bash(npm run:*)");
+    fn test_matches_bash_regex() {
+        let rule = ParsedRule::parse("Bash(/git (status|log)( .*)?/)");
         let cwd = PathBuf::from("/tmp");
 
-        // Should block commands with shell operators after prefix
-        assert!(!rule.matches(
-            "Bash",
-            &json!({"command": "npm run build && rm -rf /"}),
-            &cwd
-        ));
-        assert!(!rule.matches("Bash", &json!({"command": "npm run build | cat"}), &cwd));
-        assert!(!rule.matches(
-            "Bash",
-            &json!({"command": "npm run build; malicious"}),
-            &cwd
-        ));
+        assert!(rule.matches("Bash", &json!({"command": "git status"}), &cwd));
+        assert!(rule.matches("Bash", &json!({"command": "git log --oneline"}), &cwd));
+        // The regex has to match the whole command
+        assert!(!rule.matches("Bash", &json!({"command": "git push"}), &cwd));
+        assert!(!rule.matches("Bash", &json!({"command": "echo git status"}), &cwd));
+
+        // An invalid regex matches nothing instead of panicking
+        let rule = ParsedRule::parse("Bash(/git (/)");
+        assert!(!rule.matches("Bash", &json!({"command": "git ("}), &cwd));
     }
 
     #[test]
@@ -532,7 +856,7 @@ mod tests {
     fn test_deny_web_fetch_blocks_mcp_tool() {
         // Test that deny: ["WebFetch"] blocks the MCP tool
         let permissions = PermissionSettings {
-            deny: Some(vec!["WebFetch".to_string()]),
+            deny: Some(vec!["WebFetch".into()]),
             ..Default::default()
         };
         let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
@@ -546,7 +870,7 @@ mod tests {
     fn test_deny_web_search_blocks_mcp_tool() {
         // Test that deny: ["WebSearch"] blocks the MCP tool
         let permissions = PermissionSettings {
-            deny: Some(vec!["WebSearch".to_string()]),
+            deny: Some(vec!["WebSearch".into()]),
             ..Default::default()
         };
         let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
@@ -560,7 +884,7 @@ mod tests {
     fn test_allow_web_fetch_allows_mcp_tool() {
         // Test that allow: ["WebFetch"] allows the MCP tool
         let permissions = PermissionSettings {
-            allow: Some(vec!["WebFetch".to_string()]),
+            allow: Some(vec!["WebFetch".into()]),
             ..Default::default()
         };
         let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
@@ -574,7 +898,7 @@ mod tests {
     fn test_deny_web_fetch_blocks_builtin_tool() {
         // Test that deny: ["WebFetch"] also blocks the built-in WebFetch tool
         let permissions = PermissionSettings {
-            deny: Some(vec!["WebFetch".to_string()]),
+            deny: Some(vec!["WebFetch".into()]),
             ..Default::default()
         };
         let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
@@ -593,7 +917,7 @@ mod tests {
     fn test_deny_web_search_blocks_builtin_tool() {
         // Test that deny: ["WebSearch"] also blocks the built-in WebSearch tool
         let permissions = PermissionSettings {
-            deny: Some(vec!["WebSearch".to_string()]),
+            deny: Some(vec!["WebSearch".into()]),
             ..Default::default()
         };
         let checker = PermissionChecker::new(settings_with_permissions(permissions), "/tmp");
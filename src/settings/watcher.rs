@@ -10,6 +10,8 @@ use notify::{RecommendedWatcher, RecursiveMode};
 use notify_debouncer_mini::{DebounceEventResult, DebouncedEventKind, Debouncer, new_debouncer};
 use tokio::sync::mpsc;
 
+use super::user_config_dir;
+
 /// Settings file watcher
 ///
 /// Watches settings files for changes and sends notifications via a channel.
@@ -51,11 +53,10 @@ impl SettingsWatcher {
         let mut watched_paths = Vec::new();
 
         // User settings directory
-        if let Some(home) = dirs::home_dir() {
-            let user_settings_dir = home.join(".claude");
-            if user_settings_dir.exists() {
-                watched_paths.push(user_settings_dir);
-            }
+        if let Some(user_settings_dir) = user_config_dir()
+            && user_settings_dir.exists()
+        {
+            watched_paths.push(user_settings_dir);
         }
 
         // Project settings directory
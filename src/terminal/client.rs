@@ -2,6 +2,7 @@
 //!
 //! Provides a client interface for sending Terminal API requests to the ACP Client.
 
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Instant;
@@ -29,6 +30,8 @@ pub struct TerminalClient {
     connection_cx: JrConnectionCx<AgentToClient>,
     /// Session ID for this client
     session_id: SessionId,
+    /// Extra environment for created terminals (from the client's session meta)
+    env: HashMap<String, String>,
 }
 
 impl TerminalClient {
@@ -40,9 +43,17 @@ impl TerminalClient {
         Self {
             connection_cx,
             session_id: session_id.into(),
+            env: HashMap::new(),
         }
     }
 
+    /// Set extra environment variables for created terminals
+    #[must_use]
+    pub fn with_env(mut self, env: HashMap<String, String>) -> Self {
+        self.env = env;
+        self
+    }
+
     /// Create a new terminal and execute a command
     ///
     /// Returns a `TerminalId` that can be used with other terminal methods.
@@ -85,7 +96,13 @@ impl TerminalClient {
         request = request.args(args.clone());
 
         // Set CLAUDECODE environment variable (required by some clients like Zed)
-        request = request.env(vec![EnvVariable::new("CLAUDECODE", "1")]);
+        let mut env: Vec<EnvVariable> = self
+            .env
+            .iter()
+            .map(|(name, value)| EnvVariable::new(name, value))
+            .collect();
+        env.push(EnvVariable::new("CLAUDECODE", "1"));
+        request = request.env(env);
 
         if let Some(cwd_path) = cwd.clone() {
             request = request.cwd(cwd_path);
@@ -1,6 +1,37 @@
 //! Agent configuration from environment variables
 
 use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::context::{DEFAULT_CONTEXT_WINDOW, context_window_for_model};
+use super::pricing::PricingTable;
+use crate::converter::ThinkingTriggers;
+use crate::mcp::{
+    DEFAULT_INPUT_GRACE_MS, DomainFilter, EnvInfoConfig, FinalNewlinePolicy,
+    InteractiveInputPolicy, ToolTimeouts,
+};
+use crate::permissions::PermissionFallback;
+use crate::session::{
+    DEFAULT_BACKGROUND_MAX_LIFETIME, DEFAULT_BACKGROUND_OUTPUT_LIMIT,
+    DEFAULT_PERMISSION_REQUEST_TIMEOUT,
+};
+use crate::settings::CustomModeSettings;
+
+/// Agent name reported to clients when no custom identity is configured
+pub const DEFAULT_AGENT_NAME: &str = "claude-code-acp-rs";
+
+/// Model aliases the Claude CLI resolves to its current models, with display names
+pub const MODEL_ALIASES: &[(&str, &str)] = &[
+    ("default", "Default (recommended)"),
+    ("opus", "Opus"),
+    ("sonnet", "Sonnet"),
+    ("haiku", "Haiku"),
+    ("opusplan", "Opus Plan Mode"),
+];
+
+/// Prefixes of Claude model IDs (e.g. `claude-sonnet-4-5`)
+const CLAUDE_MODEL_FAMILIES: &[&str] = &["claude-opus-", "claude-sonnet-", "claude-haiku-"];
 
 /// Agent configuration loaded from environment variables and settings files
 ///
@@ -34,7 +65,10 @@ pub struct AgentConfig {
     /// Settings field: `model`
     pub model: Option<String>,
 
-    /// Small/fast model name (fallback)
+    /// Small/fast model name
+    ///
+    /// Used as the fallback model and for internal operations
+    /// (see [`AgentConfig::internal_model`]).
     /// Environment variable: `ANTHROPIC_SMALL_FAST_MODEL`
     /// Settings field: `smallFastModel`
     pub small_fast_model: Option<String>,
@@ -49,6 +83,252 @@ pub struct AgentConfig {
     /// When `alwaysThinkingEnabled` is true in settings, this defaults to 20000.
     /// Typical values: 4096, 8000, 16000, 20000
     pub max_thinking_tokens: Option<u32>,
+
+    /// Generate a session title with the small/fast model after the first turn
+    ///
+    /// Off by default because it spends extra tokens.
+    /// Settings field: `autoGenerateTitle`
+    pub auto_generate_title: bool,
+
+    /// Compact the conversation automatically when the context nears its limit
+    ///
+    /// Off by default because summarizing spends extra tokens.
+    /// Settings field: `autoCompactEnabled`
+    pub auto_compact: bool,
+
+    /// Context window override in tokens
+    ///
+    /// When unset, the window is looked up from the model name.
+    /// Settings field: `contextWindow`
+    pub context_window: Option<u64>,
+
+    /// Title Bash tool calls that lack a `description` with a short one
+    /// synthesized from the command
+    ///
+    /// Off by default, in which case the command itself is used as the title.
+    /// Settings field: `synthesizeBashDescription`
+    pub synthesize_bash_description: bool,
+
+    /// Handling of Bash commands that appear to wait for input
+    ///
+    /// Defaults to closing stdin after 10 seconds without output.
+    /// Settings fields: `bashInteractiveInput`, `bashInputGraceMs`
+    pub interactive_input: InteractiveInputPolicy,
+
+    /// Maximum characters per line of Read, Grep and Bash output
+    ///
+    /// Longer lines (minified code, base64 blobs) are cut with a
+    /// `…[N chars truncated]` marker. Unlimited when unset.
+    /// Settings field: `maxLineLength`
+    pub max_line_length: Option<usize>,
+
+    /// Maximum lines returned by Read when no offset/limit is given
+    ///
+    /// Larger files return their head plus a note on how to page through
+    /// the rest. Defaults to 2000 lines when unset; 0 disables the cap.
+    /// Settings field: `readMaxLines`
+    pub read_max_lines: Option<usize>,
+
+    /// Largest file in bytes Read reads whole and Write writes
+    ///
+    /// Keeps the model from loading a multi-gigabyte log into memory or
+    /// filling the disk with one write; a larger file can still be read
+    /// in ranges with offset/limit. Defaults to 10MB when unset; 0 disables
+    /// the limit.
+    /// Settings field: `maxFileSize`
+    pub max_file_size: Option<u64>,
+
+    /// Execution time limits of tools
+    ///
+    /// Built-in tools other than Bash are stopped after 5 minutes, Bash
+    /// commands after 2 minutes and external MCP tools after 3 minutes
+    /// unless configured otherwise; a zero timeout disables the limit.
+    /// Settings fields: `toolTimeoutMs`, `toolTimeouts` (all in milliseconds)
+    /// Environment variables: `BASH_DEFAULT_TIMEOUT_MS`, `MCP_TOOL_TIMEOUT`
+    pub tool_timeouts: ToolTimeouts,
+
+    /// Maximum serialized size of a tool call's arguments in bytes
+    ///
+    /// Oversized calls (e.g. a huge Write `content`) are rejected before
+    /// execution. Defaults to 8MB when unset; 0 disables the limit.
+    /// Settings field: `maxToolArgumentBytes`
+    pub max_tool_argument_bytes: Option<usize>,
+
+    /// Maximum bytes of Bash output returned to the model
+    ///
+    /// Longer output keeps its beginning and end with a marker in between;
+    /// the client's live terminal view still shows everything. Defaults to
+    /// 100KB when unset; 0 disables the limit.
+    /// Settings field: `bashOutputLimitBytes`
+    pub bash_output_limit_bytes: Option<usize>,
+
+    /// Percentage of a foreground Bash command's timeout after which the
+    /// client is warned that it will be killed
+    ///
+    /// Defaults to 80 when unset; 0 disables the warning.
+    /// Settings field: `bashTimeoutWarningPercent`
+    pub bash_timeout_warning_percent: Option<u8>,
+
+    /// Merge a Bash command's stdout and stderr in the order they're written
+    ///
+    /// On by default, matching what a terminal shows. When off, stderr is
+    /// collected separately and appended after a `--- stderr ---` separator.
+    /// Settings field: `combineStreams`
+    pub combine_streams: bool,
+
+    /// Directory Plan mode may write plans to, instead of `~/.claude/plans`
+    ///
+    /// A relative path is taken from the project directory. Only honored in
+    /// user settings, and ignored if it contains the project or home
+    /// directory (or a session's working directory).
+    /// Settings field: `plansDir`
+    pub plans_dir: Option<PathBuf>,
+
+    /// Preselect "auto-accept edits" rather than "manually approve edits"
+    /// when a plan is approved
+    ///
+    /// For users who don't want to approve each edit of a plan they already
+    /// signed off on; the mode the user picks in the ExitPlanMode dialog is
+    /// always kept. Only honored in user settings.
+    /// Settings field: `acceptEditsAfterPlan`
+    pub accept_edits_after_plan: bool,
+
+    /// Approve Read, Edit, Write and MultiEdit of files inside the session's
+    /// working directory in Default mode
+    ///
+    /// Paths are resolved with symlinks and `..` first; files outside the
+    /// directory and calls a settings rule matches go through the usual
+    /// permission flow.
+    /// Settings field: `autoApproveWithinCwd`
+    pub auto_approve_within_cwd: bool,
+
+    /// JSONL file every permission decision is appended to
+    ///
+    /// For compliance records: each line has the time, session, tool,
+    /// decision, matching rule and a SHA-256 digest of the tool input (not
+    /// the input itself). A relative path is taken from the project directory.
+    /// Only honored in user settings, so a repository can't pick the file
+    /// the agent appends to.
+    /// Settings field: `permissionAuditLog`
+    pub permission_audit_log: Option<PathBuf>,
+
+    /// Time the client has to answer a permission request
+    ///
+    /// An unanswered request (editor closed or crashed) is denied and the
+    /// tool call marked failed once it runs out. Defaults to 2 minutes; None
+    /// (a setting of 0) waits indefinitely.
+    /// Settings field: `permissionRequestTimeoutSecs`
+    pub permission_request_timeout: Option<Duration>,
+
+    /// How calls needing permission are decided for clients that opted out
+    /// of permission requests (`requestPermission: false` in the `_meta` of
+    /// their capabilities). Defaults to denying them.
+    /// Settings field: `permissionFallback`
+    pub permission_fallback: PermissionFallback,
+
+    /// Re-read files after Write and Edit and check the content's hash
+    ///
+    /// Catches writes a filesystem silently dropped or corrupted. Off by
+    /// default because it reads every written file back.
+    /// Settings field: `verifyWrites`
+    pub verify_writes: bool,
+
+    /// Search with ripgrep when `rg` is on PATH (Grep falls back to its
+    /// built-in search otherwise)
+    ///
+    /// Turn off to always use the built-in search, e.g. for reproducible
+    /// results across machines.
+    /// Settings field: `preferRipgrep`
+    pub prefer_ripgrep: bool,
+
+    /// Unchanged lines kept around each change in Edit and MultiEdit diffs
+    ///
+    /// Keeps the diffs of large edits readable in the editor and transcript;
+    /// the lines left out are shown as `...`. Full diffs when unset.
+    /// Settings field: `diffContextLines`
+    pub diff_context_lines: Option<usize>,
+
+    /// Version commands and environment variables reported by EnvInfo
+    ///
+    /// Configured commands and variables are added to the defaults
+    /// (cargo, node, python3, ...; PATH, SHELL, LANG, ...). Commands are
+    /// only honored in user settings, as EnvInfo runs them without asking.
+    /// Settings field: `envInfo` (`commands`, `envVars`)
+    pub env_info: EnvInfoConfig,
+
+    /// Let WebFetch fetch loopback, private and link-local addresses
+    ///
+    /// Off by default so a fetched page (or a redirect) can't reach services
+    /// on the machine or its network when the agent runs in a shared
+    /// environment.
+    /// Settings field: `allowLocalhostFetch`
+    pub allow_localhost_fetch: bool,
+
+    /// Hosts WebFetch may fetch
+    ///
+    /// For locked-down environments. Entries match hosts exactly, or their
+    /// subdomains when written as `*.example.com`; denied hosts win over
+    /// allowed ones. Every host is allowed when both lists are empty.
+    /// Settings fields: `webFetchAllowedDomains`, `webFetchDeniedDomains`
+    pub web_fetch_domains: DomainFilter,
+
+    /// Domains WebSearch may return results from
+    ///
+    /// Matched like `web_fetch_domains`.
+    /// Settings fields: `webSearchAllowedDomains`, `webSearchDeniedDomains`
+    pub web_search_domains: DomainFilter,
+
+    /// Time a background Bash command may run before it is killed
+    ///
+    /// Keeps a forgotten server or watcher from running for the rest of the
+    /// session. Defaults to 4 hours; None (a setting of 0) is unlimited.
+    /// Settings field: `backgroundMaxLifetimeSecs`
+    pub background_max_lifetime: Option<Duration>,
+
+    /// Bytes of output kept per background Bash command
+    ///
+    /// Beyond it the oldest output is dropped and BashOutput shows a marker
+    /// in its place. Defaults to 1MB; None (a setting of 0) keeps everything.
+    /// Settings field: `backgroundOutputLimitBytes`
+    pub background_output_limit: Option<usize>,
+
+    /// Model prices used to estimate a session's cost
+    ///
+    /// Claude models are priced by default; configured prices (per model ID
+    /// or part of it) take precedence and cover other providers' models.
+    /// Settings field: `modelPricing` (`input`, `output`, `cacheRead`,
+    /// `cacheWrite` in USD per million tokens)
+    pub model_pricing: PricingTable,
+
+    /// Whether Write, Edit and MultiEdit say if the file ends with a newline
+    ///
+    /// `report` always states it, `warn` only mentions a missing newline,
+    /// sparing a re-read when linters require one. Off by default.
+    /// Settings field: `finalNewline`
+    pub final_newline: FinalNewlinePolicy,
+
+    /// Agent name reported in the `initialize` response and MCP `serverInfo`
+    ///
+    /// Lets embedders brand the agent. Defaults to [`DEFAULT_AGENT_NAME`].
+    pub agent_name: Option<String>,
+
+    /// Agent version reported alongside `agent_name`
+    ///
+    /// Defaults to the version of this crate.
+    pub agent_version: Option<String>,
+
+    /// Thinking budgets for trigger phrases in prompts
+    ///
+    /// When set, "think", "think hard", "think harder" and "ultrathink" in a
+    /// prompt raise `max_thinking_tokens` for that turn. Off when unset.
+    /// Settings field: `thinkingTriggers`
+    pub thinking_triggers: Option<ThinkingTriggers>,
+
+    /// Custom permission modes advertised in the initialize response, by ID
+    ///
+    /// Sessions load their own from the settings of their working directory.
+    /// Settings field: `customModes`
+    pub custom_modes: HashMap<String, CustomModeSettings>,
 }
 
 impl AgentConfig {
@@ -83,6 +363,42 @@ impl AgentConfig {
             model: std::env::var("ANTHROPIC_MODEL").ok(),
             small_fast_model: std::env::var("ANTHROPIC_SMALL_FAST_MODEL").ok(),
             max_thinking_tokens,
+            auto_generate_title: false,
+            auto_compact: false,
+            context_window: None,
+            synthesize_bash_description: false,
+            interactive_input: InteractiveInputPolicy::default(),
+            max_line_length: None,
+            read_max_lines: None,
+            max_file_size: None,
+            tool_timeouts: resolve_tool_timeouts(None, HashMap::new(), |key| {
+                std::env::var(key).ok()
+            }),
+            max_tool_argument_bytes: None,
+            bash_output_limit_bytes: None,
+            bash_timeout_warning_percent: None,
+            combine_streams: true,
+            plans_dir: None,
+            accept_edits_after_plan: false,
+            auto_approve_within_cwd: false,
+            permission_audit_log: None,
+            permission_request_timeout: Some(DEFAULT_PERMISSION_REQUEST_TIMEOUT),
+            permission_fallback: PermissionFallback::Deny,
+            verify_writes: false,
+            prefer_ripgrep: true,
+            diff_context_lines: None,
+            env_info: EnvInfoConfig::default(),
+            allow_localhost_fetch: false,
+            web_fetch_domains: DomainFilter::default(),
+            web_search_domains: DomainFilter::default(),
+            background_max_lifetime: Some(DEFAULT_BACKGROUND_MAX_LIFETIME),
+            background_output_limit: Some(DEFAULT_BACKGROUND_OUTPUT_LIMIT),
+            model_pricing: PricingTable::default(),
+            final_newline: FinalNewlinePolicy::Off,
+            agent_name: None,
+            agent_version: None,
+            thinking_triggers: None,
+            custom_modes: HashMap::new(),
         }
     }
 
@@ -244,12 +560,95 @@ impl AgentConfig {
                 }
             });
 
+        let custom_modes = settings.custom_modes.clone().unwrap_or_default();
+
         let config = Self {
             base_url,
             api_key,
             model,
             small_fast_model,
             max_thinking_tokens,
+            auto_generate_title: settings.auto_generate_title.unwrap_or(false),
+            auto_compact: settings.auto_compact_enabled.unwrap_or(false),
+            context_window: settings.context_window,
+            synthesize_bash_description: settings.synthesize_bash_description.unwrap_or(false),
+            interactive_input: InteractiveInputPolicy {
+                mode: settings.bash_interactive_input.unwrap_or_default(),
+                grace: Duration::from_millis(
+                    settings
+                        .bash_input_grace_ms
+                        .unwrap_or(DEFAULT_INPUT_GRACE_MS),
+                ),
+            },
+            max_line_length: settings.max_line_length,
+            read_max_lines: settings.read_max_lines,
+            max_file_size: settings.max_file_size,
+            tool_timeouts: resolve_tool_timeouts(
+                settings.tool_timeout_ms.map(Duration::from_millis),
+                settings.tool_timeouts.unwrap_or_default(),
+                |key| {
+                    std::env::var(key)
+                        .ok()
+                        .or_else(|| settings.env.as_ref()?.get(key).cloned())
+                },
+            ),
+            max_tool_argument_bytes: settings.max_tool_argument_bytes,
+            bash_output_limit_bytes: settings.bash_output_limit_bytes,
+            bash_timeout_warning_percent: settings.bash_timeout_warning_percent,
+            combine_streams: settings.combine_streams.unwrap_or(true),
+            plans_dir: settings
+                .plans_dir
+                .as_deref()
+                .and_then(|dir| crate::utils::normalize_plans_dir(dir, project_dir)),
+            accept_edits_after_plan: settings.accept_edits_after_plan.unwrap_or(false),
+            auto_approve_within_cwd: settings.auto_approve_within_cwd.unwrap_or(false),
+            permission_audit_log: settings
+                .permission_audit_log
+                .as_deref()
+                .and_then(|path| crate::utils::resolve_setting_path(path, project_dir)),
+            permission_request_timeout: settings
+                .permission_request_timeout_secs
+                .map_or(Some(DEFAULT_PERMISSION_REQUEST_TIMEOUT), |secs| {
+                    (secs > 0).then(|| Duration::from_secs(secs))
+                }),
+            permission_fallback: settings.permission_fallback.unwrap_or_default(),
+            verify_writes: settings.verify_writes.unwrap_or(false),
+            prefer_ripgrep: settings.prefer_ripgrep.unwrap_or(true),
+            diff_context_lines: settings.diff_context_lines,
+            env_info: settings
+                .env_info
+                .map(|env_info| {
+                    EnvInfoConfig::with_overrides(
+                        env_info.commands.unwrap_or_default(),
+                        env_info.env_vars.unwrap_or_default(),
+                    )
+                })
+                .unwrap_or_default(),
+            allow_localhost_fetch: settings.allow_localhost_fetch.unwrap_or(false),
+            web_fetch_domains: DomainFilter::new(
+                settings.web_fetch_allowed_domains.unwrap_or_default(),
+                settings.web_fetch_denied_domains.unwrap_or_default(),
+            ),
+            web_search_domains: DomainFilter::new(
+                settings.web_search_allowed_domains.unwrap_or_default(),
+                settings.web_search_denied_domains.unwrap_or_default(),
+            ),
+            background_max_lifetime: settings
+                .background_max_lifetime_secs
+                .map_or(Some(DEFAULT_BACKGROUND_MAX_LIFETIME), |secs| {
+                    (secs > 0).then(|| Duration::from_secs(secs))
+                }),
+            background_output_limit: settings
+                .background_output_limit_bytes
+                .map_or(Some(DEFAULT_BACKGROUND_OUTPUT_LIMIT), |bytes| {
+                    (bytes > 0).then_some(bytes)
+                }),
+            model_pricing: PricingTable::with_overrides(settings.model_pricing.unwrap_or_default()),
+            final_newline: settings.final_newline.unwrap_or_default(),
+            agent_name: None,
+            agent_version: None,
+            thinking_triggers: settings.thinking_triggers,
+            custom_modes,
         };
 
         // Log configuration sources
@@ -263,6 +662,38 @@ impl AgentConfig {
             max_thinking_tokens = ?config.max_thinking_tokens,
             max_thinking_tokens_source = if has_max_thinking_tokens_env { "env" } else if has_max_thinking_tokens_env_settings { "settings.env" } else if always_thinking_enabled { "alwaysThinkingEnabled" } else { "default" },
             always_thinking_enabled = always_thinking_enabled,
+            auto_generate_title = config.auto_generate_title,
+            auto_compact = config.auto_compact,
+            context_window = config.resolved_context_window(),
+            synthesize_bash_description = config.synthesize_bash_description,
+            interactive_input = ?config.interactive_input,
+            max_line_length = ?config.max_line_length,
+            read_max_lines = ?config.read_max_lines,
+            max_file_size = ?config.max_file_size,
+            tool_timeouts = ?config.tool_timeouts,
+            max_tool_argument_bytes = ?config.max_tool_argument_bytes,
+            bash_output_limit_bytes = ?config.bash_output_limit_bytes,
+            bash_timeout_warning_percent = ?config.bash_timeout_warning_percent,
+            combine_streams = config.combine_streams,
+            plans_dir = ?config.plans_dir,
+            accept_edits_after_plan = config.accept_edits_after_plan,
+            auto_approve_within_cwd = config.auto_approve_within_cwd,
+            permission_audit_log = ?config.permission_audit_log,
+            permission_request_timeout = ?config.permission_request_timeout,
+            permission_fallback = ?config.permission_fallback,
+            verify_writes = config.verify_writes,
+            prefer_ripgrep = config.prefer_ripgrep,
+            diff_context_lines = ?config.diff_context_lines,
+            env_info = ?config.env_info,
+            allow_localhost_fetch = config.allow_localhost_fetch,
+            web_fetch_domains = ?config.web_fetch_domains,
+            web_search_domains = ?config.web_search_domains,
+            background_max_lifetime = ?config.background_max_lifetime,
+            background_output_limit = ?config.background_output_limit,
+            model_pricing = ?config.model_pricing,
+            final_newline = ?config.final_newline,
+            thinking_triggers = ?config.thinking_triggers,
+            custom_modes = ?config.custom_modes.keys().collect::<Vec<_>>(),
             api_key = ?config.masked_api_key(),
             "Configuration loaded (priority: env > settings.{{top-level, env}} > default)"
         );
@@ -270,6 +701,77 @@ impl AgentConfig {
         config
     }
 
+    /// Get the model used for internal operations
+    ///
+    /// Session titles, compaction summaries and tool-argument repair don't
+    /// need the primary model, so they use `small_fast_model` and fall back
+    /// to the primary model when it isn't configured.
+    pub fn internal_model(&self) -> Option<&str> {
+        self.small_fast_model.as_deref().or(self.model.as_deref())
+    }
+
+    /// Get the context window of the configured model in tokens
+    ///
+    /// Uses the `contextWindow` override if set, otherwise looks the model up
+    /// in the known context windows table.
+    pub fn resolved_context_window(&self) -> u64 {
+        self.context_window.unwrap_or_else(|| {
+            self.model
+                .as_deref()
+                .map_or(DEFAULT_CONTEXT_WINDOW, context_window_for_model)
+        })
+    }
+
+    /// Get the context window of `model` in tokens, honouring the
+    /// `contextWindow` override
+    pub fn context_window_of(&self, model: &str) -> u64 {
+        self.context_window
+            .unwrap_or_else(|| context_window_for_model(model))
+    }
+
+    /// Check whether a session can switch to `model`
+    ///
+    /// Accepts the CLI's aliases (`opus`, `sonnet`, ...), Claude model IDs
+    /// and the configured primary and small/fast models, each optionally
+    /// with the `[1m]` suffix. Other providers' models have to be configured
+    /// to be selectable.
+    pub fn is_known_model(&self, model: &str) -> bool {
+        let model = model.trim();
+        let base = model.strip_suffix("[1m]").unwrap_or(model);
+        MODEL_ALIASES.iter().any(|(alias, _)| *alias == base)
+            || CLAUDE_MODEL_FAMILIES
+                .iter()
+                .any(|family| base.starts_with(family))
+            || [self.model.as_deref(), self.small_fast_model.as_deref()]
+                .into_iter()
+                .flatten()
+                .any(|configured| configured == model || configured == base)
+    }
+
+    /// Set the agent name and version reported to clients
+    #[must_use]
+    pub fn with_agent_identity(
+        mut self,
+        name: impl Into<String>,
+        version: impl Into<String>,
+    ) -> Self {
+        self.agent_name = Some(name.into());
+        self.agent_version = Some(version.into());
+        self
+    }
+
+    /// Get the agent name reported to clients
+    pub fn agent_name(&self) -> &str {
+        self.agent_name.as_deref().unwrap_or(DEFAULT_AGENT_NAME)
+    }
+
+    /// Get the agent version reported to clients
+    pub fn agent_version(&self) -> &str {
+        self.agent_version
+            .as_deref()
+            .unwrap_or(env!("CARGO_PKG_VERSION"))
+    }
+
     /// Check if any configuration is set
     pub fn is_configured(&self) -> bool {
         self.base_url.is_some()
@@ -365,9 +867,42 @@ impl AgentConfig {
     }
 }
 
+/// Resolve tool timeouts from settings and the CLI's environment variables
+///
+/// `per_tool_ms` (the `toolTimeouts` setting) takes priority. Tools it
+/// doesn't mention fall back to `BASH_DEFAULT_TIMEOUT_MS` (Bash) and
+/// `MCP_TOOL_TIMEOUT` (external MCP tools), looked up with `env`, and then
+/// to the built-in defaults.
+fn resolve_tool_timeouts(
+    default: Option<Duration>,
+    per_tool_ms: HashMap<String, u64>,
+    env: impl Fn(&str) -> Option<String>,
+) -> ToolTimeouts {
+    let env_timeout = |key: &str| {
+        env(key)
+            .and_then(|ms| ms.trim().parse::<u64>().ok())
+            .map(Duration::from_millis)
+    };
+
+    let mut per_tool: HashMap<String, Duration> = per_tool_ms
+        .into_iter()
+        .map(|(tool, ms)| (tool, Duration::from_millis(ms)))
+        .collect();
+    if let Some(bash) = env_timeout("BASH_DEFAULT_TIMEOUT_MS") {
+        per_tool.entry("Bash".to_string()).or_insert(bash);
+    }
+
+    ToolTimeouts {
+        default,
+        per_tool,
+        external: env_timeout("MCP_TOOL_TIMEOUT"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mcp::InteractiveInputMode;
 
     /// Guard that saves and restores environment variables
     /// Automatically restores on drop, ensuring cleanup even on test failure
@@ -416,6 +951,9 @@ mod tests {
         assert!(config.model.is_none());
         assert!(config.small_fast_model.is_none());
         assert!(config.max_thinking_tokens.is_none());
+        assert!(!config.auto_generate_title);
+        assert!(!config.auto_compact);
+        assert!(!config.synthesize_bash_description);
         assert!(!config.is_configured());
     }
 
@@ -427,6 +965,40 @@ mod tests {
             model: Some("claude-3".to_string()),
             small_fast_model: None,
             max_thinking_tokens: None,
+            auto_generate_title: false,
+            auto_compact: false,
+            context_window: None,
+            synthesize_bash_description: false,
+            interactive_input: InteractiveInputPolicy::default(),
+            max_line_length: None,
+            read_max_lines: None,
+            max_file_size: None,
+            tool_timeouts: ToolTimeouts::default(),
+            max_tool_argument_bytes: None,
+            bash_output_limit_bytes: None,
+            bash_timeout_warning_percent: None,
+            combine_streams: true,
+            plans_dir: None,
+            accept_edits_after_plan: false,
+            auto_approve_within_cwd: false,
+            permission_audit_log: None,
+            permission_request_timeout: None,
+            permission_fallback: PermissionFallback::Deny,
+            verify_writes: false,
+            prefer_ripgrep: true,
+            diff_context_lines: None,
+            env_info: EnvInfoConfig::default(),
+            allow_localhost_fetch: false,
+            web_fetch_domains: DomainFilter::default(),
+            web_search_domains: DomainFilter::default(),
+            background_max_lifetime: None,
+            background_output_limit: None,
+            model_pricing: PricingTable::default(),
+            final_newline: FinalNewlinePolicy::Off,
+            agent_name: None,
+            agent_version: None,
+            thinking_triggers: None,
+            custom_modes: HashMap::new(),
         };
 
         let env = config.to_env_vars();
@@ -500,6 +1072,50 @@ mod tests {
         assert!(config.is_configured());
     }
 
+    #[test]
+    fn test_internal_model() {
+        let mut config = AgentConfig::default();
+        assert_eq!(config.internal_model(), None);
+
+        config.model = Some("claude-opus-4-1".to_string());
+        assert_eq!(config.internal_model(), Some("claude-opus-4-1"));
+
+        config.small_fast_model = Some("claude-haiku-4-5".to_string());
+        assert_eq!(config.internal_model(), Some("claude-haiku-4-5"));
+    }
+
+    #[test]
+    fn test_resolved_context_window() {
+        let mut config = AgentConfig::default();
+        assert_eq!(config.resolved_context_window(), DEFAULT_CONTEXT_WINDOW);
+
+        config.model = Some("glm-4.5".to_string());
+        assert_eq!(config.resolved_context_window(), 128_000);
+
+        config.context_window = Some(64_000);
+        assert_eq!(config.resolved_context_window(), 64_000);
+    }
+
+    #[test]
+    fn test_is_known_model() {
+        let mut config = AgentConfig::default();
+        assert!(config.is_known_model("opus"));
+        assert!(config.is_known_model("sonnet[1m]"));
+        assert!(config.is_known_model("claude-haiku-4-5"));
+        assert!(!config.is_known_model("glm-4.7"));
+        assert!(!config.is_known_model("claude-unknown"));
+
+        config.model = Some("glm-4.7".to_string());
+        config.small_fast_model = Some("glm-4.5-air".to_string());
+        assert!(config.is_known_model("glm-4.7"));
+        assert!(config.is_known_model("glm-4.5-air"));
+        assert!(!config.is_known_model("gpt-5"));
+
+        assert_eq!(config.context_window_of("claude-sonnet-4-5[1m]"), 1_000_000);
+        config.context_window = Some(64_000);
+        assert_eq!(config.context_window_of("claude-sonnet-4-5[1m]"), 64_000);
+    }
+
     #[test]
     fn test_max_thinking_tokens_config() {
         let config = AgentConfig {
@@ -508,6 +1124,40 @@ mod tests {
             model: None,
             small_fast_model: None,
             max_thinking_tokens: Some(4096),
+            auto_generate_title: false,
+            auto_compact: false,
+            context_window: None,
+            synthesize_bash_description: false,
+            interactive_input: InteractiveInputPolicy::default(),
+            max_line_length: None,
+            read_max_lines: None,
+            max_file_size: None,
+            tool_timeouts: ToolTimeouts::default(),
+            max_tool_argument_bytes: None,
+            bash_output_limit_bytes: None,
+            bash_timeout_warning_percent: None,
+            combine_streams: true,
+            plans_dir: None,
+            accept_edits_after_plan: false,
+            auto_approve_within_cwd: false,
+            permission_audit_log: None,
+            permission_request_timeout: None,
+            permission_fallback: PermissionFallback::Deny,
+            verify_writes: false,
+            prefer_ripgrep: true,
+            diff_context_lines: None,
+            env_info: EnvInfoConfig::default(),
+            allow_localhost_fetch: false,
+            web_fetch_domains: DomainFilter::default(),
+            web_search_domains: DomainFilter::default(),
+            background_max_lifetime: None,
+            background_output_limit: None,
+            model_pricing: PricingTable::default(),
+            final_newline: FinalNewlinePolicy::Off,
+            agent_name: None,
+            agent_version: None,
+            thinking_triggers: None,
+            custom_modes: HashMap::new(),
         };
 
         assert!(config.is_configured());
@@ -612,6 +1262,8 @@ mod tests {
             "ANTHROPIC_MODEL",
             "ANTHROPIC_SMALL_FAST_MODEL",
             "ANTHROPIC_BASE_URL",
+            "BASH_DEFAULT_TIMEOUT_MS",
+            "MCP_TOOL_TIMEOUT",
         ]);
 
         let temp_base = std::env::temp_dir();
@@ -634,7 +1286,38 @@ mod tests {
             r#"{
             "model": "local-model",
             "smallFastModel": "local-small-model",
-            "apiBaseUrl": "https://local.api.com"
+            "apiBaseUrl": "https://local.api.com",
+            "autoGenerateTitle": true,
+            "autoCompactEnabled": true,
+            "synthesizeBashDescription": true,
+            "bashInteractiveInput": "decline",
+            "bashInputGraceMs": 3000,
+            "maxLineLength": 500,
+            "readMaxLines": 800,
+            "maxFileSize": 1048576,
+            "toolTimeoutMs": 120000,
+            "toolTimeouts": {"WebFetch": 30000},
+            "env": {"MCP_TOOL_TIMEOUT": "60000"},
+            "maxToolArgumentBytes": 4096,
+            "bashOutputLimitBytes": 65536,
+            "bashTimeoutWarningPercent": 90,
+            "combineStreams": false,
+            "plansDir": "docs/../plans",
+            "acceptEditsAfterPlan": true,
+            "autoApproveWithinCwd": true,
+            "permissionAuditLog": "logs/permissions.jsonl",
+            "permissionRequestTimeoutSecs": 30,
+            "permissionFallback": "rules",
+            "verifyWrites": true,
+            "preferRipgrep": false,
+            "webFetchAllowedDomains": ["docs.rs", "*.rust-lang.org"],
+            "webSearchDeniedDomains": ["example.com"],
+            "customModes": {"review": {"name": "Review", "baseMode": "plan"}},
+            "diffContextLines": 3,
+            "finalNewline": "warn",
+            "backgroundMaxLifetimeSecs": 600,
+            "backgroundOutputLimitBytes": 0,
+            "thinkingTriggers": {"thinkHarder": 24000}
         }"#,
         )
         .ok();
@@ -647,11 +1330,95 @@ mod tests {
             Some("local-small-model".to_string())
         );
         assert_eq!(config.base_url, Some("https://local.api.com".to_string()));
+        assert!(config.auto_generate_title);
+        assert!(config.auto_compact);
+        assert!(config.synthesize_bash_description);
+        assert_eq!(config.interactive_input.mode, InteractiveInputMode::Decline);
+        assert_eq!(config.interactive_input.grace, Duration::from_secs(3));
+        assert_eq!(config.max_line_length, Some(500));
+        assert_eq!(config.read_max_lines, Some(800));
+        assert_eq!(config.max_file_size, Some(1_048_576));
+        assert_eq!(config.tool_timeouts.default, Some(Duration::from_secs(120)));
+        assert_eq!(config.max_tool_argument_bytes, Some(4096));
+        assert_eq!(config.bash_output_limit_bytes, Some(65536));
+        assert_eq!(config.bash_timeout_warning_percent, Some(90));
+        assert!(!config.combine_streams);
+        // Only honored in user settings
+        assert_eq!(config.plans_dir, None);
+        assert!(!config.accept_edits_after_plan);
+        assert_eq!(config.permission_audit_log, None);
+        assert!(config.auto_approve_within_cwd);
+        assert_eq!(
+            config.permission_request_timeout,
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(config.permission_fallback, PermissionFallback::Rules);
+        assert!(config.verify_writes);
+        assert!(!config.prefer_ripgrep);
+        assert_eq!(config.diff_context_lines, Some(3));
+        assert!(config.web_fetch_domains.allows("doc.rust-lang.org"));
+        assert!(!config.web_fetch_domains.allows("example.com"));
+        assert!(!config.web_search_domains.allows("example.com"));
+        let custom_mode_ids: Vec<_> = config.custom_modes.keys().collect();
+        assert_eq!(custom_mode_ids, ["review"]);
+        assert_eq!(config.final_newline, FinalNewlinePolicy::Warn);
+        assert_eq!(
+            config.background_max_lifetime,
+            Some(Duration::from_secs(600))
+        );
+        assert_eq!(config.background_output_limit, None);
+        assert_eq!(
+            config.tool_timeouts.for_tool("WebFetch", false),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            config.tool_timeouts.for_external_tool("mcp__docs__search"),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(
+            config.thinking_triggers,
+            Some(ThinkingTriggers {
+                think_harder: 24_000,
+                ..ThinkingTriggers::default()
+            })
+        );
 
         // Cleanup
         drop(std::fs::remove_dir_all(&temp_dir));
     }
 
+    #[test]
+    fn test_resolve_tool_timeouts() {
+        let env = |key: &str| match key {
+            "BASH_DEFAULT_TIMEOUT_MS" => Some("90000".to_string()),
+            "MCP_TOOL_TIMEOUT" => Some(" 60000 ".to_string()),
+            _ => None,
+        };
+
+        let timeouts = resolve_tool_timeouts(
+            Some(Duration::from_secs(120)),
+            HashMap::from([("WebFetch".to_string(), 30_000)]),
+            env,
+        );
+        assert_eq!(timeouts.default, Some(Duration::from_secs(120)));
+        assert_eq!(
+            timeouts.for_tool("WebFetch", false),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            timeouts.for_tool("Bash", true),
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(timeouts.external, Some(Duration::from_secs(60)));
+
+        // The toolTimeouts setting beats the environment
+        let timeouts = resolve_tool_timeouts(None, HashMap::from([("Bash".to_string(), 0)]), env);
+        assert_eq!(timeouts.for_tool("Bash", true), None);
+
+        let timeouts = resolve_tool_timeouts(None, HashMap::new(), |_| None);
+        assert_eq!(timeouts, ToolTimeouts::default());
+    }
+
     #[test]
     #[serial_test::serial]
     fn test_from_settings_priority_order() {
@@ -0,0 +1,125 @@
+//! Model context windows and the remaining context budget
+
+use serde::{Deserialize, Serialize};
+
+/// Context window assumed for models that aren't in the table
+pub const DEFAULT_CONTEXT_WINDOW: u64 = 200_000;
+
+/// Fraction of the context window below which remaining context is flagged as low
+pub const CONTEXT_WARNING_THRESHOLD: f64 = 0.2;
+
+/// Context window of the 1M-token variants (model IDs ending in `[1m]`)
+const EXTENDED_CONTEXT_WINDOW: u64 = 1_000_000;
+
+/// Known context windows, matched by model ID prefix (first match wins)
+const CONTEXT_WINDOWS: &[(&str, u64)] = &[
+    ("claude-", 200_000),
+    ("glm-4.5", 128_000),
+    ("glm-4", 200_000),
+    ("kimi-k2", 256_000),
+    ("qwen3-coder", 256_000),
+    ("deepseek-", 128_000),
+    ("gpt-4.1", 1_047_576),
+    ("gpt-4o", 128_000),
+    ("gpt-5", 400_000),
+];
+
+/// Look up the context window of a model in tokens
+///
+/// Provider prefixes (`anthropic/claude-...`) are ignored and unknown models
+/// fall back to [`DEFAULT_CONTEXT_WINDOW`].
+pub fn context_window_for_model(model: &str) -> u64 {
+    let model = model.trim().to_ascii_lowercase();
+    if model.ends_with("[1m]") {
+        return EXTENDED_CONTEXT_WINDOW;
+    }
+    let name = model.rsplit('/').next().unwrap_or_default();
+
+    CONTEXT_WINDOWS
+        .iter()
+        .find(|(prefix, _)| name.starts_with(prefix))
+        .map_or(DEFAULT_CONTEXT_WINDOW, |&(_, window)| window)
+}
+
+/// How much of the model's context window a session has left
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextBudget {
+    /// Context window of the model in tokens
+    pub context_window: u64,
+
+    /// Estimated tokens currently in the context
+    pub used_tokens: u64,
+
+    /// Tokens left before the context window is full
+    pub remaining_tokens: u64,
+
+    /// Whether the remaining context is below [`CONTEXT_WARNING_THRESHOLD`]
+    pub low: bool,
+}
+
+impl ContextBudget {
+    /// Compute the budget for `used_tokens` out of `context_window`
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(context_window: u64, used_tokens: u64) -> Self {
+        let remaining_tokens = context_window.saturating_sub(used_tokens);
+        Self {
+            context_window,
+            used_tokens,
+            remaining_tokens,
+            low: (remaining_tokens as f64) < context_window as f64 * CONTEXT_WARNING_THRESHOLD,
+        }
+    }
+
+    /// Remaining context as a percentage of the window (0-100)
+    #[allow(clippy::cast_precision_loss)]
+    pub fn remaining_percent(&self) -> f64 {
+        if self.context_window == 0 {
+            return 0.0;
+        }
+        self.remaining_tokens as f64 * 100.0 / self.context_window as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_window_for_model() {
+        assert_eq!(
+            context_window_for_model("claude-sonnet-4-5-20250929"),
+            200_000
+        );
+        assert_eq!(context_window_for_model("claude-sonnet-4-5[1m]"), 1_000_000);
+        assert_eq!(
+            context_window_for_model("anthropic/claude-opus-4-1"),
+            200_000
+        );
+        assert_eq!(context_window_for_model("glm-4.5-air"), 128_000);
+        assert_eq!(context_window_for_model("GLM-4.7"), 200_000);
+        assert_eq!(
+            context_window_for_model("some-local-model"),
+            DEFAULT_CONTEXT_WINDOW
+        );
+    }
+
+    #[test]
+    fn test_context_budget() {
+        let model = "claude-sonnet-4-5-20250929";
+        let budget = ContextBudget::new(context_window_for_model(model), 150_000);
+        assert_eq!(budget.context_window, 200_000);
+        assert_eq!(budget.remaining_tokens, 50_000);
+        assert!((budget.remaining_percent() - 25.0).abs() < f64::EPSILON);
+        assert!(!budget.low);
+
+        let budget = ContextBudget::new(200_000, 170_000);
+        assert_eq!(budget.remaining_tokens, 30_000);
+        assert!(budget.low);
+
+        // Usage beyond the window saturates instead of underflowing
+        let budget = ContextBudget::new(200_000, 250_000);
+        assert_eq!(budget.remaining_tokens, 0);
+        assert!(budget.low);
+    }
+}
@@ -96,6 +96,11 @@ pub enum AgentError {
     #[error("Invalid mode: {0}")]
     InvalidMode(String),
 
+    // === Request errors ===
+    /// Invalid request parameters
+    #[error("Invalid params: {0}")]
+    InvalidParams(String),
+
     // === Prompt errors ===
     /// Empty prompt
     #[error("Prompt cannot be empty")]
@@ -176,6 +181,7 @@ impl AgentError {
             AgentError::AuthRequired => ErrorCode::AuthRequired,
             AgentError::InvalidApiKey => ErrorCode::AuthRequired,
             AgentError::InvalidMode(_) => ErrorCode::InvalidMode,
+            AgentError::InvalidParams(_) => ErrorCode::InvalidParams,
             AgentError::EmptyPrompt => ErrorCode::InvalidParams,
             AgentError::PromptTooLong { .. } => ErrorCode::InvalidParams,
             AgentError::StreamingError(_) => ErrorCode::StreamingError,
@@ -210,6 +216,7 @@ impl AgentError {
             self,
             AgentError::SessionNotFound(_)
                 | AgentError::InvalidMode(_)
+                | AgentError::InvalidParams(_)
                 | AgentError::EmptyPrompt
                 | AgentError::PromptTooLong { .. }
                 | AgentError::ToolNotFound(_)
@@ -239,6 +246,11 @@ impl AgentError {
         AgentError::InvalidMode(mode.into())
     }
 
+    /// Create an invalid params error
+    pub fn invalid_params(msg: impl Into<String>) -> Self {
+        AgentError::InvalidParams(msg.into())
+    }
+
     /// Create a tool execution failed error
     pub fn tool_failed(msg: impl Into<String>) -> Self {
         AgentError::ToolExecutionFailed(msg.into())
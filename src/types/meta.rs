@@ -3,8 +3,58 @@
 //! ACP protocol's `new_session` and `load_session` requests support a `_meta` field
 //! for passing additional configuration.
 
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
 
+/// Environment variables a client (or a Bash call's `env`) may set
+///
+/// An allowlist rather than a denylist: many variables make tools load code
+/// (`PYTHONPATH`, `PERL5OPT`, `RUSTC_WRAPPER`, `GIT_SSH_COMMAND`, ...), and a
+/// denylist misses some of them. These only select modes, locales, log
+/// levels and output formatting; none names a program, library or config
+/// file to load.
+pub const ALLOWED_CLIENT_ENV_VARS: &[&str] = &[
+    // Build and run modes
+    "CI",
+    "NODE_ENV",
+    "RAILS_ENV",
+    "RACK_ENV",
+    "APP_ENV",
+    "FLASK_ENV",
+    "DEBUG",
+    "LOG_LEVEL",
+    "RUST_LOG",
+    "RUST_BACKTRACE",
+    "RUST_TEST_THREADS",
+    "PYTHONUNBUFFERED",
+    "PYTHONDONTWRITEBYTECODE",
+    "DATABASE_URL",
+    // Locale and time zone
+    "LANG",
+    "LANGUAGE",
+    "LC_ALL",
+    "LC_CTYPE",
+    "LC_MESSAGES",
+    "TZ",
+    // Output formatting
+    "TERM",
+    "COLUMNS",
+    "LINES",
+    "NO_COLOR",
+    "FORCE_COLOR",
+    "CLICOLOR",
+    "CLICOLOR_FORCE",
+    "CARGO_TERM_COLOR",
+];
+
+/// Check whether a client-provided environment variable may be applied
+///
+/// Only the names in [`ALLOWED_CLIENT_ENV_VARS`] are allowed.
+pub fn is_allowed_client_env(name: &str) -> bool {
+    ALLOWED_CLIENT_ENV_VARS.contains(&name)
+}
+
 /// System prompt configuration from meta field
 ///
 /// Allows clients to customize the system prompt via the `_meta.systemPrompt` field.
@@ -70,6 +120,10 @@ pub struct ClaudeCodeOptions {
     /// Typical values: 4096, 8000, 16000
     #[serde(skip_serializing_if = "Option::is_none", rename = "maxThinkingTokens")]
     pub max_thinking_tokens: Option<u32>,
+
+    /// Permission mode to start the session in (e.g. `plan`)
+    #[serde(skip_serializing_if = "Option::is_none", rename = "permissionMode")]
+    pub permission_mode: Option<String>,
 }
 
 /// Claude Code meta configuration
@@ -96,6 +150,11 @@ impl ClaudeCodeMeta {
     pub fn get_max_thinking_tokens(&self) -> Option<u32> {
         self.options.as_ref()?.max_thinking_tokens
     }
+
+    /// Get the permission mode to start in, if any
+    pub fn get_permission_mode(&self) -> Option<&str> {
+        self.options.as_ref()?.permission_mode.as_deref()
+    }
 }
 
 /// Combined meta configuration for new session requests
@@ -111,6 +170,27 @@ pub struct NewSessionMeta {
 
     /// Whether to disable built-in tools
     pub disable_built_in_tools: bool,
+
+    /// Whether the client reviews (and may edit) Edit/Write diffs before they are written
+    pub diff_suggestions: bool,
+
+    /// Whether the client handles grouped permission requests
+    ///
+    /// Requests arriving close together are then asked about with one
+    /// prompt (allow all / reject all / decide individually).
+    pub grouped_permissions: bool,
+
+    /// Project environment from the client (e.g. a `.env` the editor loaded)
+    ///
+    /// Applied to the Claude CLI and the Bash tool. Only variables in
+    /// [`ALLOWED_CLIENT_ENV_VARS`] are kept.
+    pub env: HashMap<String, String>,
+
+    /// Session-scoped allow rules (e.g. `Bash(npm run:*)`), not persisted
+    pub allowed_tools: Vec<String>,
+
+    /// Session-scoped deny rules, not persisted
+    pub denied_tools: Vec<String>,
 }
 
 impl NewSessionMeta {
@@ -125,12 +205,28 @@ impl NewSessionMeta {
                 options: Some(ClaudeCodeOptions {
                     resume: Some(session_id.to_string()),
                     max_thinking_tokens: None,
+                    permission_mode: None,
                 }),
             }),
             disable_built_in_tools: false,
+            diff_suggestions: false,
+            grouped_permissions: false,
+            env: HashMap::new(),
+            allowed_tools: Vec::new(),
+            denied_tools: Vec::new(),
         }
     }
 
+    /// Start the session in permission mode `mode` (e.g. `plan`)
+    pub fn with_permission_mode(mut self, mode: &str) -> Self {
+        self.claude_code
+            .get_or_insert_with(ClaudeCodeMeta::default)
+            .options
+            .get_or_insert_with(ClaudeCodeOptions::default)
+            .permission_mode = Some(mode.to_string());
+        self
+    }
+
     /// Parse from ACP request's `_meta` field
     ///
     /// # Arguments
@@ -152,9 +248,55 @@ impl NewSessionMeta {
                 .get("disableBuiltInTools")
                 .and_then(|v| v.as_bool())
                 .unwrap_or(false),
+            diff_suggestions: meta
+                .get("diffSuggestions")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            grouped_permissions: meta
+                .get("groupedPermissions")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            env: Self::parse_env(meta),
+            allowed_tools: Self::parse_rules(meta, "allowedTools"),
+            denied_tools: Self::parse_rules(meta, "deniedTools"),
         }
     }
 
+    /// Parse an array of permission rule strings, skipping anything else
+    fn parse_rules(meta: &serde_json::Value, key: &str) -> Vec<String> {
+        meta.get(key)
+            .and_then(|v| v.as_array())
+            .map(|rules| {
+                rules
+                    .iter()
+                    .filter_map(|rule| rule.as_str())
+                    .map(str::trim)
+                    .filter(|rule| !rule.is_empty())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Parse `_meta.env`, dropping non-string values and variables that aren't allowed
+    fn parse_env(meta: &serde_json::Value) -> HashMap<String, String> {
+        let Some(env) = meta.get("env").and_then(|v| v.as_object()) else {
+            return HashMap::new();
+        };
+
+        env.iter()
+            .filter_map(|(name, value)| {
+                let value = value.as_str()?;
+                if is_allowed_client_env(name) {
+                    Some((name.clone(), value.to_string()))
+                } else {
+                    tracing::warn!(name = %name, "Ignoring environment variable from client that isn't allowed");
+                    None
+                }
+            })
+            .collect()
+    }
+
     /// Get the text to append to the system prompt, if any
     pub fn get_system_prompt_append(&self) -> Option<&str> {
         self.system_prompt.as_ref()?.append.as_deref()
@@ -175,6 +317,11 @@ impl NewSessionMeta {
         self.claude_code.as_ref()?.get_max_thinking_tokens()
     }
 
+    /// Get the permission mode to start in, if any
+    pub fn get_permission_mode(&self) -> Option<&str> {
+        self.claude_code.as_ref()?.get_permission_mode()
+    }
+
     /// Check if this session should resume from a previous session
     pub fn should_resume(&self) -> bool {
         self.get_resume_session_id().is_some()
@@ -230,6 +377,19 @@ mod tests {
         assert_eq!(parsed.get_max_thinking_tokens(), Some(4096));
     }
 
+    #[test]
+    fn test_new_session_meta_with_permission_mode() {
+        let meta = NewSessionMeta::with_resume("session-uuid-12345").with_permission_mode("plan");
+        assert_eq!(meta.get_resume_session_id(), Some("session-uuid-12345"));
+        assert_eq!(meta.get_permission_mode(), Some("plan"));
+
+        let parsed = NewSessionMeta::from_request_meta(Some(&json!({
+            "claudeCode": {"options": {"permissionMode": "acceptEdits"}}
+        })));
+        assert_eq!(parsed.get_permission_mode(), Some("acceptEdits"));
+        assert!(!parsed.should_resume());
+    }
+
     #[test]
     fn test_new_session_meta_full() {
         let meta = json!({
@@ -242,7 +402,9 @@ mod tests {
                     "maxThinkingTokens": 8000
                 }
             },
-            "disableBuiltInTools": true
+            "disableBuiltInTools": true,
+            "diffSuggestions": true,
+            "groupedPermissions": true
         });
 
         let parsed = NewSessionMeta::from_request_meta(Some(&meta));
@@ -250,6 +412,8 @@ mod tests {
         assert_eq!(parsed.get_resume_session_id(), Some("abc-123"));
         assert_eq!(parsed.get_max_thinking_tokens(), Some(8000));
         assert!(parsed.disable_built_in_tools);
+        assert!(parsed.diff_suggestions);
+        assert!(parsed.grouped_permissions);
         assert!(parsed.should_resume());
     }
 
@@ -260,6 +424,45 @@ mod tests {
         assert!(parsed.claude_code.is_none());
         assert!(!parsed.disable_built_in_tools);
         assert!(!parsed.should_resume());
+        assert!(parsed.env.is_empty());
+        assert!(parsed.allowed_tools.is_empty());
+        assert!(parsed.denied_tools.is_empty());
+    }
+
+    #[test]
+    fn test_new_session_meta_permission_rules() {
+        let meta = json!({
+            "allowedTools": ["Bash(npm run:*)", "Read", "", 42],
+            "deniedTools": ["Bash(rm:*)"]
+        });
+
+        let parsed = NewSessionMeta::from_request_meta(Some(&meta));
+        assert_eq!(parsed.allowed_tools, vec!["Bash(npm run:*)", "Read"]);
+        assert_eq!(parsed.denied_tools, vec!["Bash(rm:*)"]);
+    }
+
+    #[test]
+    fn test_new_session_meta_env_allowlist() {
+        let meta = json!({
+            "env": {
+                "DATABASE_URL": "postgres://localhost/app",
+                "NODE_ENV": "test",
+                "PATH": "/tmp/evil",
+                "LD_PRELOAD": "/tmp/evil.so",
+                "ANTHROPIC_API_KEY": "sk-other",
+                "PYTHONPATH": "/tmp/evil",
+                "RUSTC_WRAPPER": "/tmp/evil",
+                "GIT_SSH_COMMAND": "/tmp/evil",
+                "_PRIVATE": "1",
+                "BAD-NAME": "x",
+                "CI": true
+            }
+        });
+
+        let parsed = NewSessionMeta::from_request_meta(Some(&meta));
+        assert_eq!(parsed.env.len(), 2);
+        assert_eq!(parsed.env["DATABASE_URL"], "postgres://localhost/app");
+        assert_eq!(parsed.env["NODE_ENV"], "test");
     }
 
     #[test]
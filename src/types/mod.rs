@@ -3,13 +3,22 @@
 //! This module contains all the shared types used across the crate.
 
 mod config;
+mod context;
 mod error;
 mod meta;
+mod pricing;
 mod session;
 mod tool;
 
-pub use config::AgentConfig;
+pub use config::{AgentConfig, DEFAULT_AGENT_NAME, MODEL_ALIASES};
+pub use context::{
+    CONTEXT_WARNING_THRESHOLD, ContextBudget, DEFAULT_CONTEXT_WINDOW, context_window_for_model,
+};
 pub use error::{AgentError, ErrorCode, Result};
-pub use meta::{ClaudeCodeMeta, ClaudeCodeOptions, NewSessionMeta, SystemPromptMeta};
-pub use session::{SessionStats, TokenUsage};
+pub use meta::{
+    ALLOWED_CLIENT_ENV_VARS, ClaudeCodeMeta, ClaudeCodeOptions, NewSessionMeta, SystemPromptMeta,
+    is_allowed_client_env,
+};
+pub use pricing::{ModelPricing, PricingTable};
+pub use session::{SessionInfo, SessionStats, TokenUsage};
 pub use tool::{ToolCallLocation, ToolInfo, ToolInfoContent, ToolKind, ToolUseEntry, ToolUseType};
@@ -0,0 +1,144 @@
+//! Model prices for cost estimates
+//!
+//! Per-token prices of models, built in for Claude models and configurable
+//! with the `modelPricing` setting for others.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::session::TokenUsage;
+
+/// Built-in prices of Claude models in USD per million tokens, matched
+/// against model IDs and aliases by the longest contained key
+const DEFAULT_PRICES: &[(&str, f64, f64)] = &[
+    ("opus-4-5", 5.0, 25.0),
+    ("opus", 15.0, 75.0),
+    ("sonnet", 3.0, 15.0),
+    ("haiku-4-5", 1.0, 5.0),
+    ("haiku", 0.8, 4.0),
+];
+
+/// Per-token prices of a model, in USD per million tokens
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPricing {
+    /// Price of input tokens
+    pub input: f64,
+    /// Price of output tokens
+    pub output: f64,
+    /// Price of tokens read from the prompt cache (default: 10% of `input`)
+    #[serde(default)]
+    pub cache_read: Option<f64>,
+    /// Price of tokens written to the prompt cache (default: 125% of `input`)
+    #[serde(default)]
+    pub cache_write: Option<f64>,
+}
+
+impl ModelPricing {
+    /// Prices with the usual cache discount and surcharge
+    pub fn new(input: f64, output: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_read: None,
+            cache_write: None,
+        }
+    }
+
+    /// Cost of `usage` in USD
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        let cache_read = self.cache_read.unwrap_or(self.input * 0.1);
+        let cache_write = self.cache_write.unwrap_or(self.input * 1.25);
+        #[allow(clippy::cast_precision_loss)]
+        let tokens = |n: u64| n as f64 / 1_000_000.0;
+        tokens(usage.input_tokens) * self.input
+            + tokens(usage.output_tokens) * self.output
+            + tokens(usage.cache_read_input_tokens.unwrap_or(0)) * cache_read
+            + tokens(usage.cache_creation_input_tokens.unwrap_or(0)) * cache_write
+    }
+}
+
+/// Prices of models by (part of) their ID
+///
+/// Configured prices take precedence over the built-in prices of Claude
+/// models. A model matching no entry has no known price and adds nothing to
+/// the cost.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PricingTable {
+    /// Configured prices by model ID or part of it
+    configured: HashMap<String, ModelPricing>,
+}
+
+impl PricingTable {
+    /// Built-in prices extended by configured ones
+    pub fn with_overrides(configured: HashMap<String, ModelPricing>) -> Self {
+        Self {
+            configured: configured
+                .into_iter()
+                .map(|(model, pricing)| (model.to_lowercase(), pricing))
+                .collect(),
+        }
+    }
+
+    /// Prices of `model`, from the longest matching configured entry or,
+    /// failing that, the longest matching built-in one
+    pub fn for_model(&self, model: &str) -> Option<ModelPricing> {
+        let model = model.to_lowercase();
+        let configured = self
+            .configured
+            .iter()
+            .filter(|(key, _)| model.contains(key.as_str()))
+            .max_by_key(|(key, _)| key.len())
+            .map(|(_, pricing)| *pricing);
+        configured.or_else(|| {
+            DEFAULT_PRICES
+                .iter()
+                .filter(|(key, _, _)| model.contains(key))
+                .max_by_key(|(key, _, _)| key.len())
+                .map(|&(_, input, output)| ModelPricing::new(input, output))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pricing_table() {
+        let table = PricingTable::with_overrides(HashMap::from([
+            ("glm-4".to_string(), ModelPricing::new(0.6, 2.2)),
+            (
+                "claude-opus-4-1".to_string(),
+                ModelPricing {
+                    cache_read: Some(1.0),
+                    ..ModelPricing::new(10.0, 50.0)
+                },
+            ),
+        ]));
+
+        assert_eq!(
+            table.for_model("glm-4.7"),
+            Some(ModelPricing::new(0.6, 2.2))
+        );
+        assert_eq!(
+            table.for_model("claude-opus-4-1"),
+            Some(ModelPricing {
+                cache_read: Some(1.0),
+                ..ModelPricing::new(10.0, 50.0)
+            })
+        );
+        // The most specific built-in entry wins
+        assert_eq!(
+            table.for_model("claude-opus-4-5"),
+            Some(ModelPricing::new(5.0, 25.0))
+        );
+        assert_eq!(table.for_model("opus"), Some(ModelPricing::new(15.0, 75.0)));
+        assert_eq!(
+            table.for_model("claude-haiku-4-5-20251001"),
+            Some(ModelPricing::new(1.0, 5.0))
+        );
+        assert_eq!(table.for_model("gpt-4"), None);
+    }
+}
@@ -2,6 +2,8 @@
 
 use serde::{Deserialize, Serialize};
 
+use super::context::ContextBudget;
+
 /// Token usage statistics
 ///
 /// Tracks the number of tokens used in a session or query.
@@ -68,7 +70,8 @@ impl TokenUsage {
 }
 
 /// Session statistics
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct SessionStats {
     /// Number of active sessions
     pub active_sessions: usize,
@@ -78,6 +81,10 @@ pub struct SessionStats {
 
     /// Total cost in USD
     pub total_cost_usd: f64,
+
+    /// Remaining context of a single session (not set for aggregated stats)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<ContextBudget>,
 }
 
 impl SessionStats {
@@ -87,6 +94,21 @@ impl SessionStats {
     }
 }
 
+/// Summary of an active session, as returned by the session list API
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionInfo {
+    /// Session identifier
+    pub session_id: String,
+
+    /// Working directory of the session
+    pub cwd: String,
+
+    /// Human-readable session title, if one has been set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
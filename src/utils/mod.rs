@@ -1,5 +1,11 @@
 //! Shared utility functions
 
 mod paths;
+mod text;
 
-pub use paths::is_plans_directory_path;
+pub(crate) use paths::is_session_plan_path;
+pub use paths::{
+    is_plans_directory_path, normalize_plans_dir, plans_dir, plans_dir_contains,
+    resolve_setting_path,
+};
+pub use text::{truncate_long_lines, truncate_middle};
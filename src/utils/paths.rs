@@ -1,9 +1,74 @@
 //! Path utility functions
 
-use std::path::{Component, Path};
+use std::path::{Component, Path, PathBuf};
+
+use crate::mcp::tools::workspace::{normalize, resolve};
+use crate::settings::user_config_dir;
+
+/// The default directory plan files are written to in Plan mode
+///
+/// `plans` in the user configuration directory (`~/.claude/plans`, moved by
+/// `--config-dir` / `CLAUDE_CONFIG_DIR`). A session may use the `plansDir`
+/// setting instead, see [`normalize_plans_dir`].
+pub fn plans_dir() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join("plans"))
+}
+
+/// Validate and normalize a `plansDir` setting
+///
+/// `~/` is expanded and relative paths are taken from `project_dir`; `.` and
+/// `..` are resolved. Returns None for an empty value, one naming an
+/// existing file, or one containing the project or home directory.
+pub fn normalize_plans_dir(dir: &str, project_dir: &Path) -> Option<PathBuf> {
+    let dir = dir.trim();
+    if dir.is_empty() {
+        tracing::warn!("Ignoring empty plansDir setting");
+        return None;
+    }
+
+    let normalized = resolve_setting_path(dir, project_dir)?;
+    if normalized.exists() && !normalized.is_dir() {
+        tracing::warn!(plans_dir = %normalized.display(), "Ignoring plansDir setting: not a directory");
+        return None;
+    }
+    let home = dirs::home_dir();
+    if [Some(project_dir), home.as_deref()]
+        .into_iter()
+        .flatten()
+        .any(|dir| plans_dir_contains(&normalized, dir))
+    {
+        tracing::warn!(
+            plans_dir = %normalized.display(),
+            "Ignoring plansDir setting: it contains the project or home directory"
+        );
+        return None;
+    }
+    Some(normalized)
+}
+
+/// Whether `dir` is inside the plans directory `plans_dir`
+///
+/// Plan mode may write anywhere in the plans directory, so it mustn't
+/// contain the project (or home) directory. Symlinks are followed.
+pub fn plans_dir_contains(plans_dir: &Path, dir: &Path) -> bool {
+    resolve(&normalize(dir)).starts_with(resolve(plans_dir))
+}
+
+/// Resolve a path from settings
+///
+/// `~/` is expanded and relative paths are taken from `project_dir`; `.` and
+/// `..` are resolved. Returns None if the home directory is unknown.
+pub fn resolve_setting_path(path: &str, project_dir: &Path) -> Option<PathBuf> {
+    Some(normalize(&match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir()?.join(rest),
+        None => project_dir.join(path),
+    }))
+}
 
 /// Check if a file path is within the Claude plans directory (~/.claude/plans/)
 ///
+/// The plans directory is the one [`plans_dir`] returns.
+///
 /// This function handles:
 /// - Absolute paths: /Users/soddy/.claude/plans/plan.md
 /// - Home-relative paths: ~/.claude/plans/plan.md
@@ -18,17 +83,34 @@ use std::path::{Component, Path};
 /// * `true` if the path is within ~/.claude/plans/
 /// * `false` otherwise
 pub fn is_plans_directory_path(path_str: &str) -> bool {
-    let Some(home) = dirs::home_dir() else {
-        tracing::warn!("Could not determine home directory for plans path check");
+    let Some(plans_dir) = plans_dir() else {
+        tracing::warn!("Could not determine the plans directory for plans path check");
         return false;
     };
+    is_in_plans_directory(path_str, plans_dir)
+}
 
-    let plans_dir = home.join(".claude").join("plans");
+/// Check if a file path is within the session's plans directory
+///
+/// `plans_dir` is the one from the `plansDir` setting, None for the default.
+pub(crate) fn is_session_plan_path(path_str: &str, plans_dir: Option<&Path>) -> bool {
+    match plans_dir {
+        Some(plans_dir) => is_in_plans_directory(path_str, plans_dir.to_path_buf()),
+        None => is_plans_directory_path(path_str),
+    }
+}
 
+/// Check if a file path (absolute or `~/`-relative) is within `plans_dir`
+pub(crate) fn is_in_plans_directory(path_str: &str, plans_dir: PathBuf) -> bool {
     let normalized_input = if let Some(rest) = path_str.strip_prefix("~/") {
-        home.join(rest)
+        let Some(home) = dirs::home_dir() else {
+            tracing::warn!("Could not determine home directory for plans path check");
+            return false;
+        };
+        normalize(&home.join(rest))
     } else if Path::new(path_str).is_absolute() {
-        Path::new(path_str).to_path_buf()
+        // `plans/../elsewhere` must not count as inside the plans directory
+        normalize(Path::new(path_str))
     } else {
         return false;
     };
@@ -126,6 +208,72 @@ mod tests {
         assert!(!is_plans_directory_path("~/../.claude/plans/plan.md"));
     }
 
+    #[test]
+    fn test_custom_plans_directory() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let root = temp_dir.path().canonicalize().unwrap();
+        let plans = root.join("my-plans");
+        std::fs::create_dir(&plans).unwrap();
+        std::fs::write(plans.join("plan.md"), "# Plan").unwrap();
+        let path = |p: PathBuf| p.to_str().unwrap().to_string();
+
+        assert!(is_in_plans_directory(
+            &path(plans.join("plan.md")),
+            plans.clone()
+        ));
+        assert!(is_in_plans_directory(
+            &path(plans.join("new").join("plan.md")),
+            plans.clone()
+        ));
+        assert!(!is_in_plans_directory(
+            &path(root.join("plan.md")),
+            plans.clone()
+        ));
+        assert!(!is_in_plans_directory(
+            &path(plans.join("..").join("plan.md")),
+            plans.clone()
+        ));
+
+        // The default plans directory is not the custom one
+        assert!(!is_in_plans_directory("~/.claude/plans/plan.md", plans));
+    }
+
+    #[test]
+    fn test_normalize_plans_dir() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path();
+        let home = dirs::home_dir().unwrap();
+
+        assert_eq!(
+            normalize_plans_dir("docs/plans", project),
+            Some(project.join("docs/plans"))
+        );
+        assert_eq!(
+            normalize_plans_dir("./a/../plans/", project),
+            Some(project.join("plans"))
+        );
+        assert_eq!(
+            normalize_plans_dir("~/plans", project),
+            Some(home.join("plans"))
+        );
+        assert_eq!(
+            normalize_plans_dir("/srv/plans", project),
+            Some(PathBuf::from("/srv/plans"))
+        );
+        assert_eq!(normalize_plans_dir("  ", project), None);
+
+        std::fs::write(project.join("file"), "").unwrap();
+        assert_eq!(normalize_plans_dir("file", project), None);
+
+        // Plan mode may write anywhere in it, so it can't hold the project
+        // or home directory
+        for dir in [".", "..", "/", "~/", "./a/.."] {
+            assert_eq!(normalize_plans_dir(dir, project), None, "{dir}");
+        }
+        assert!(plans_dir_contains(&home, &home.join("project")));
+        assert!(!plans_dir_contains(&home.join("plans"), &home));
+    }
+
     #[test]
     fn test_normalize_path_components() {
         use std::path::Path;
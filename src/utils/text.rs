@@ -0,0 +1,113 @@
+//! Text helpers for tool output
+
+use std::borrow::Cow;
+
+/// Truncate lines longer than `max_line_length` characters
+///
+/// A long line keeps its first `max_line_length` characters followed by a
+/// `…[N chars truncated]` marker, so the number of lines is unchanged.
+/// Text without long lines is returned as is.
+pub fn truncate_long_lines(text: &str, max_line_length: usize) -> Cow<'_, str> {
+    let is_long = |line: &str| line.chars().nth(max_line_length).is_some();
+    if !text.split('\n').any(is_long) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut truncated = String::with_capacity(text.len());
+    for (index, line) in text.split('\n').enumerate() {
+        if index > 0 {
+            truncated.push('\n');
+        }
+        match line.char_indices().nth(max_line_length) {
+            Some((cut, _)) => {
+                let removed = line[cut..].chars().count();
+                truncated.push_str(&line[..cut]);
+                truncated.push_str(&format!("…[{} chars truncated]", removed));
+            }
+            None => truncated.push_str(line),
+        }
+    }
+    Cow::Owned(truncated)
+}
+
+/// Truncate text longer than `max_bytes` bytes from the middle
+///
+/// Keeps the first and last half of `max_bytes` (at character boundaries)
+/// with a `...[N bytes truncated]...` marker in between, since both the
+/// start of a command's output and its final errors or summary matter.
+/// Text within the limit is returned as is.
+pub fn truncate_middle(text: &str, max_bytes: usize) -> Cow<'_, str> {
+    if text.len() <= max_bytes {
+        return Cow::Borrowed(text);
+    }
+
+    let mut head_end = max_bytes / 2;
+    while !text.is_char_boundary(head_end) {
+        head_end -= 1;
+    }
+    let mut tail_start = text.len() - (max_bytes - max_bytes / 2);
+    while !text.is_char_boundary(tail_start) {
+        tail_start += 1;
+    }
+    let removed = tail_start - head_end;
+    Cow::Owned(format!(
+        "{}\n...[{} bytes truncated]...\n{}",
+        &text[..head_end],
+        removed,
+        &text[tail_start..]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_long_lines() {
+        let long_line = "x".repeat(250);
+        let text = format!("short\n{}\nalso short\n", long_line);
+
+        let truncated = truncate_long_lines(&text, 100);
+        let lines: Vec<&str> = truncated.split('\n').collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "short");
+        assert_eq!(
+            lines[1],
+            format!("{}…[150 chars truncated]", "x".repeat(100))
+        );
+        assert_eq!(lines[2], "also short");
+        assert_eq!(lines[3], "");
+    }
+
+    #[test]
+    fn test_short_lines_pass_through() {
+        let text = "fn main() {\n    println!(\"héllo\");\n}";
+        assert!(matches!(truncate_long_lines(text, 40), Cow::Borrowed(_)));
+
+        // Limits count characters, not bytes
+        assert_eq!(truncate_long_lines("héllo", 5), "héllo");
+        assert_eq!(truncate_long_lines("héllo", 2), "hé…[3 chars truncated]");
+    }
+
+    #[test]
+    fn test_truncate_middle() {
+        let text = format!("{}{}{}", "a".repeat(100), "b".repeat(1000), "c".repeat(100));
+        let truncated = truncate_middle(&text, 200);
+        assert_eq!(
+            truncated,
+            format!(
+                "{}\n...[1000 bytes truncated]...\n{}",
+                "a".repeat(100),
+                "c".repeat(100)
+            )
+        );
+
+        assert!(matches!(truncate_middle("short", 200), Cow::Borrowed(_)));
+
+        // Cuts stay on character boundaries
+        assert_eq!(
+            truncate_middle("ééééé", 5),
+            "é\n...[6 bytes truncated]...\né"
+        );
+    }
+}